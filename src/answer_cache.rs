@@ -0,0 +1,106 @@
+// src/answer_cache.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::chatbot::Message;
+use crate::shared_summaries::content_hash;
+
+pub const ANSWER_CACHE_FILE: &str = "answer_cache.json";
+// Oldest answers are dropped past this many
+const MAX_CACHED_ANSWERS: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedAnswer {
+    pub answer: String,
+    pub cached_at: DateTime<Utc>,
+}
+
+/// Full answers keyed by question, context and model, so an exact repeat costs nothing
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AnswerCache {
+    answers: HashMap<String, CachedAnswer>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+// Case, spacing and trailing punctuation don't make a different question
+fn normalize_question(question: &str) -> String {
+    question
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .trim_end_matches(['?', '.', '!'])
+        .to_string()
+}
+
+/// The cache key of a question asked with this context of this model, after this
+/// conversation and under this system prompt, so a follow-up never gets another
+/// conversation's answer
+pub fn answer_key(
+    question: &str,
+    context: &str,
+    model: &str,
+    history: &[Message],
+    system: &str,
+) -> String {
+    let conversation: String = history
+        .iter()
+        .map(|message| format!("{}\0{}\0", message.role, message.content))
+        .collect();
+    format!(
+        "{}:{}:{}:{}:{}",
+        model,
+        content_hash("", &normalize_question(question)),
+        content_hash("", context),
+        content_hash("", &conversation),
+        content_hash("", system)
+    )
+}
+
+impl AnswerCache {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(ANSWER_CACHE_FILE);
+        let mut cache: AnswerCache = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        cache.path = Some(path);
+        cache
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string(self)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&CachedAnswer> {
+        self.answers.get(key)
+    }
+
+    pub fn insert(&mut self, key: String, answer: String) {
+        self.answers.insert(
+            key,
+            CachedAnswer {
+                answer,
+                cached_at: Utc::now(),
+            },
+        );
+        while self.answers.len() > MAX_CACHED_ANSWERS {
+            let Some(oldest) = self
+                .answers
+                .iter()
+                .min_by_key(|(_, cached)| cached.cached_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            self.answers.remove(&oldest);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// src/answer_error.rs
+//! Failed turns kept as errors in the transcript rather than as answer text: what kind of
+//! failure it was, a one-line message, the full error, and the question to send again.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::chatbot::NO_RELEVANT_FILES;
+
+// Characters of the message shown before the details are expanded
+const SUMMARY_CHARS: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    RateLimited,
+    Overloaded,
+    Auth,
+    PromptTooLong,
+    NoContext,
+    Timeout,
+    Network,
+    Api,
+    Other,
+}
+
+impl ErrorClass {
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorClass::RateLimited => "Rate limited",
+            ErrorClass::Overloaded => "API overloaded",
+            ErrorClass::Auth => "Authentication failed",
+            ErrorClass::PromptTooLong => "Prompt too long",
+            ErrorClass::NoContext => "No relevant files",
+            ErrorClass::Timeout => "Timed out",
+            ErrorClass::Network => "Network error",
+            ErrorClass::Api => "API error",
+            ErrorClass::Other => "Error",
+        }
+    }
+
+    fn of(error: &str) -> ErrorClass {
+        // Status codes are matched where request errors name them, not in any number
+        let lower = error.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|needle| lower.contains(needle));
+        if error == NO_RELEVANT_FILES {
+            ErrorClass::NoContext
+        } else if has(&["prompt too long", "prompt is too long"]) {
+            ErrorClass::PromptTooLong
+        } else if has(&["failed: 429", "rate_limit"]) {
+            ErrorClass::RateLimited
+        } else if has(&["failed: 529", "overloaded"]) {
+            ErrorClass::Overloaded
+        } else if has(&[
+            "failed: 401",
+            "failed: 403",
+            "authentication_error",
+            "permission_error",
+        ]) {
+            ErrorClass::Auth
+        } else if has(&["time budget", "timed out", "timeout"]) {
+            ErrorClass::Timeout
+        } else if has(&["error sending request", "connection", "dns"]) {
+            ErrorClass::Network
+        } else if has(&["claude api", "api response"]) {
+            ErrorClass::Api
+        } else {
+            ErrorClass::Other
+        }
+    }
+}
+
+/// A turn that failed, with what's needed to ask it again as it was first asked
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AnswerError {
+    pub class: ErrorClass,
+    pub summary: String,
+    // The error as reported, response body included
+    pub detail: String,
+    pub question: String,
+    // Scratchpad notes sent with the question
+    pub scratchpad: Option<String>,
+}
+
+// The API's own message from an error body after " - ", else the error's first line
+fn summarize(error: &str) -> String {
+    let from_body = error
+        .split_once(" - ")
+        .and_then(|(_, body)| serde_json::from_str::<Value>(body).ok())
+        .and_then(|body| body["error"]["message"].as_str().map(str::to_string));
+    let summary = from_body.unwrap_or_else(|| error.lines().next().unwrap_or("").to_string());
+    if summary.chars().count() > SUMMARY_CHARS {
+        let cut: String = summary.chars().take(SUMMARY_CHARS - 1).collect();
+        format!("{}…", cut)
+    } else {
+        summary
+    }
+}
+
+impl AnswerError {
+    pub fn new(error: &str, question: &str, scratchpad: Option<String>) -> AnswerError {
+        AnswerError {
+            class: ErrorClass::of(error),
+            summary: summarize(error),
+            detail: error.to_string(),
+            question: question.to_string(),
+            scratchpad,
+        }
+    }
+
+    /// "Rate limited: Number of requests has exceeded your rate limit"
+    pub fn headline(&self) -> String {
+        format!("{}: {}", self.class.label(), self.summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_errors_are_classified_and_shortened_to_their_message() {
+        let error = AnswerError::new(
+            "Claude API request failed: 429 Too Many Requests - {\"type\":\"error\",\"error\":{\"type\":\"rate_limit_error\",\"message\":\"Number of requests has exceeded your rate limit\"}}",
+            "Where are tokens refreshed?",
+            None,
+        );
+        assert_eq!(error.class, ErrorClass::RateLimited);
+        assert_eq!(
+            error.headline(),
+            "Rate limited: Number of requests has exceeded your rate limit"
+        );
+        assert!(error.detail.contains("rate_limit_error"));
+
+        let error = AnswerError::new(NO_RELEVANT_FILES, "Why?", None);
+        assert_eq!(error.class, ErrorClass::NoContext);
+        assert_eq!(error.summary, NO_RELEVANT_FILES);
+    }
+
+    #[test]
+    fn classes_come_from_where_errors_name_them_and_long_messages_are_cut() {
+        let class = |error: &str| ErrorClass::of(error);
+        // A number elsewhere in the body isn't a status code
+        assert_eq!(
+            class("Claude API request failed: 500 - only 429 tokens left"),
+            ErrorClass::Api
+        );
+        assert_eq!(
+            class(
+                "Claude API request failed: 400 - {\"error\":{\"type\":\"authentication_error\"}}"
+            ),
+            ErrorClass::Auth
+        );
+        assert_eq!(
+            class("prompt is too long: 210000 tokens > 200000 maximum"),
+            ErrorClass::PromptTooLong
+        );
+        assert_eq!(
+            class("Question exceeded its time budget"),
+            ErrorClass::Timeout
+        );
+        assert_eq!(class("error sending request for url"), ErrorClass::Network);
+        assert_eq!(class("Failed to parse config"), ErrorClass::Other);
+
+        // A body that isn't JSON leaves the first line as the summary
+        let error = AnswerError::new(
+            "Claude API request failed: 502 - <html>\nBad gateway",
+            "q",
+            None,
+        );
+        assert_eq!(error.summary, "Claude API request failed: 502 - <html>");
+
+        let long = "é".repeat(SUMMARY_CHARS + 20);
+        let summary = summarize(&long);
+        assert_eq!(summary.chars().count(), SUMMARY_CHARS);
+        assert!(summary.ends_with("é…"));
+        assert_eq!(
+            summarize(&"é".repeat(SUMMARY_CHARS)),
+            "é".repeat(SUMMARY_CHARS)
+        );
+    }
+}
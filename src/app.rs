@@ -1,12 +1,104 @@
-use colored::Colorize;
+use clipboard::{ClipboardContext, ClipboardProvider};
 use home::home_dir;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::ui::chat::Message;
+use crate::answer_cache::AnswerCache;
+use crate::answer_error::{AnswerError, ErrorClass};
+use crate::ask_about::{self, AskAbout};
+use crate::audit::{ApiCallLog, AuditLog};
+use crate::branches::{
+    current_branch, ensure_worktree, head_commit, list_branches, load_snapshot, repo_root,
+    save_snapshot, BranchEntry, BranchSnapshot,
+};
+use crate::briefing::{changes_since, ChangesSince};
+use crate::carry_over::CarryOver;
+use crate::change_ledger::{ChangeLedger, FileChange};
+use crate::chaos::{ChaosClient, ChaosConfig};
+use crate::chatbot::{detect_language, index_codebase, load_index_cache, Chatbot};
+use crate::churn::stale_summaries;
+use crate::claude_client::{ClaudeClient, HttpClient};
+use crate::clipboard_ring::ClipboardRing;
+use crate::config::{save_config, Config};
+use crate::constants::{CLAUDE_API_URL, DEFAULT_MODEL};
+use crate::conversation_audit::{AuditedClient, ConversationAudit};
+use crate::dataset::{export_dataset, session_record};
+use crate::dialog::{Dialog, DialogAction};
+use crate::digest::{undigested_day, write_digest_in_background};
+use crate::draft::{clear_draft, load_draft, save_draft, Draft, DRAFT_SAVE_INTERVAL};
+use crate::embeddings::EmbeddingStore;
+use crate::events::{AppEvent, EventBus};
+use crate::failed_summaries::{retry_failed_summaries, FailedSummaries};
+use crate::file_viewer::{cited_lines, first_cited_file, FileViewer};
+use crate::gen_tests::{spawn_shell, test_command, PendingTests};
+use crate::gist::{create_gist, snippet_for, GistSnippet};
+use crate::git_hooks::load_reindex_queue;
+use crate::glossary::Glossary;
+use crate::handoff::{read_handoff, write_handoff, Handoff};
+use crate::heatmap::{build_heatmap, HeatmapCell};
+use crate::history_compaction::HistoryCompaction;
+use crate::i18n::Catalog;
+use crate::identifier_completion::IdentifierCompletion;
+use crate::index_progress::{load_index_progress, IndexProgress};
+use crate::index_snapshot::diff_against_index;
+use crate::input_history::InputHistory;
+use crate::instance_lock::{self, LockInfo, LockOutcome};
+use crate::model_routing::LatencyTracker;
+use crate::models::estimate_tokens;
+use crate::outline::outline;
+use crate::privacy::{PrivacyLevel, PrivacyPolicy};
+use crate::profiler;
+use crate::profiles::{default_profile, find_profile};
+use crate::project_status::{project_status, ProjectStatus, STATUS_REFRESH_INTERVAL};
+use crate::projects::{ProjectIndex, ProjectRegistry, RegisteredProject};
+use crate::prompts::PromptTemplates;
+use crate::query_suggestions::{self, QuerySuggestion, LOW_CONFIDENCE};
+use crate::question_lint::{missing_names, symbol_search};
+use crate::question_templates::{completions, variable_kind, TemplateFill, VariableKind};
+use crate::refactor_plan::{PendingEdit, RefactorPlan};
+use crate::refine::{materially_differs, refine};
+use crate::remote::{clone_remote, index_remote, RemoteProject};
+use crate::repo_map::repo_map;
+use crate::retrieval_memory::{cited_files, RetrievalMemory};
+use crate::rust_check::{check_rust_blocks, rust_code_blocks, RustCheck};
+use crate::saved_responses::code_blocks;
+use crate::schema_drift::SchemaDriftLog;
+use crate::scrubber::{export_transcript, scrubbed_transcript, ScrubReport, Scrubber};
+use crate::session::{
+    delete_session, list_sessions, load_most_recent_session, load_session, merge_sessions,
+    model_memory, save_session, title_from_messages, Bookmark, Session,
+};
+use crate::shared_summaries::SharedSummaries;
+use crate::startup::{reset_model, run_startup_checks, Remediation, StartupError};
+use crate::structured::StructuredAnswer;
+use crate::style::AnswerStyle;
+use crate::sweep::{apply_selected, Sweep};
+use crate::todos::{TodoItem, TodoTable};
+use crate::token_counter::load_plugin;
+use crate::transcript_log::{append_turn, load_turns, TranscriptTurn};
+use crate::transcript_print::{print_range, transcript_lines};
+use crate::truncation::{
+    head_tail, input_budget, split_turns, summarize_first, TruncationStrategy, MIN_INPUT_BUDGET,
+    STRATEGIES,
+};
+use crate::turn_latency::TurnLatency;
+use crate::ui::chat::{blockquote, Message, Sender, MAX_QUOTE_LINES};
 use crate::ui::directory_tree::DirectoryTree;
+use crate::ui::highlight::code_block_count;
+use crate::usage::{export_usage_csv, UsageMetrics};
+use crate::whats_new::{
+    last_seen_version, mark_seen, releases, unseen_releases, Release, CHANGELOG,
+};
+use crate::workspace::Workspace;
 
 // src/app.rs or within your main App module
 
+// Sent by /continue; the cut-off answer is already in the conversation
+const CONTINUE_PROMPT: &str = "Your previous answer was cut off. Continue it exactly where it stopped, without repeating anything.";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppState {
     MainMenu,
@@ -17,7 +109,36 @@ pub enum AppState {
     Settings,
     QuitConfirm,
     Quit,
-    SelectCodebase, // New state for codebase selection
+    SelectCodebase,  // New state for codebase selection
+    Bookmarks,       // Jump list of bookmarked messages
+    SessionSwitcher, // Most-recently-used session overlay
+    StartupError,    // Startup failure with remediation actions
+    RemoteRepo,      // Git URL entry for indexing a remote repository
+    Audit,           // Persistent API call audit log
+    BranchSwitcher,  // Git branches and worktrees with per-branch indexes
+    Usage,           // Local-only usage metrics and trends
+    RestoreDraft,    // Offer to restore an unsent draft after a crash
+    RefactorPlan,    // /plan-refactor checklist of ordered steps
+    Heatmap,         // Treemap of file token counts colored by retrieval frequency
+    LockConflict,    // Another live instance holds this project's lock
+    Truncation,      // Strategies for a message too long for the context window
+    Sweep,           // /sweep per-file patches, reviewed as a batch
+    Dialog,          // Modal confirmation in front of a destructive action
+    ClipboardRing,   // Ctrl+Y picker over recently copied snippets
+    WhatsNew,        // Changelog entries since the last version used
+    Projects,        // Multi-select of registered projects questions span
+    Todos,           // /todos list of TODO, FIXME and HACK comments by file
+}
+
+/// Which part of the chat screen receives key presses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatFocus {
+    Input,
+    Transcript,
+    Annotation,
+    Viewer,
+    Scratchpad,
+    Outline,
 }
 
 pub struct App {
@@ -28,11 +149,227 @@ pub struct App {
     pub input: String,
     // Add fields for directory tree navigation
     pub dir_tree: DirectoryTree,
+    // Transcript navigation and bookmarks
+    pub chat_focus: ChatFocus,
+    pub selected_message: Option<usize>,
+    // Blockquote of a previous message, prefixed to the next question
+    pub reply_quote: Option<String>,
+    // Rewrites offered under the input after a weak answer, picked with Left/Right
+    pub query_suggestions: Vec<QuerySuggestion>,
+    pub selected_suggestion: usize,
+    // Code block focused with [ and ], as (message, block within the message)
+    pub selected_code_block: Option<(usize, usize)>,
+    // Message whose Rust blocks show their `cargo check` errors inline
+    pub expanded_check: Option<usize>,
+    pub bookmarks: Vec<Bookmark>,
+    pub selected_bookmark: usize,
+    // /template being filled in
+    pub pending_template: Option<TemplateFill>,
+    // Sent once the dialog in front of it is accepted: a filled-in template, or a
+    // question sent despite naming code the index doesn't know
+    pub queued_question: Option<String>,
+    // A snippet the user agreed to upload, sent once the dialog closes
+    pub pending_gist: Option<GistSnippet>,
+    // Recent copies for the Ctrl+Y picker
+    pub clipboard_ring: ClipboardRing,
+    pub selected_clip: usize,
+    pub annotation_input: String,
+    pub status_message: Option<String>,
+    pub terminal_size: (u16, u16),
+    // Lines /print wrote, printed to the normal screen's scrollback by the UI loop
+    pub pending_print: Option<Vec<String>>,
+    pub chatbot: Chatbot,
+    // Session persistence and the Ctrl+O switcher
+    pub session_id: String,
+    pub session_cost: f64,
+    pub session_list: Vec<Session>,
+    pub selected_session: usize,
+    // Sessions marked in the switcher for a dataset export
+    pub export_selection: Vec<String>,
+    pub state_before_switcher: AppState,
+    // Configuration and startup error recovery
+    pub config: Config,
+    pub startup_error: Option<StartupError>,
+    pub selected_remediation: usize,
+    pub data_dir_input: Option<String>,
+    // Remote repositories cloned by URL are tagged read-only
+    pub url_input: String,
+    pub remote_project: Option<RemoteProject>,
+    // Tests generated by /gen-tests awaiting /accept or /reject
+    pub pending_tests: Option<PendingTests>,
+    // A message over the input budget, held back until a truncation strategy is chosen
+    pub pending_long_input: Option<String>,
+    pub selected_truncation: usize,
+    // The /plan-refactor checklist and the edits of the step awaiting /accept
+    pub refactor_plan: Option<RefactorPlan>,
+    pub selected_plan_step: usize,
+    pub pending_edits: Vec<PendingEdit>,
+    pub pending_step: Option<usize>,
+    // The /sweep under review and the file whose diff is shown
+    pub sweep: Option<Sweep>,
+    pub selected_sweep_patch: usize,
+    pub sweep_scroll: usize,
+    // /heatmap cells, largest file first
+    pub heatmap: Vec<HeatmapCell>,
+    pub selected_heatmap_cell: usize,
+    // Audit log viewer
+    pub audit_entries: Vec<ApiCallLog>,
+    pub audit_filter: String,
+    pub audit_filter_editing: bool,
+    pub audit_scroll: usize,
+    // Set while a request is in flight so the footer can say so
+    pub busy: bool,
+    // When Enter sent the question being asked, for its latency breakdown
+    pub input_received: Option<std::time::Instant>,
+    // Root of the indexed code and the git branch it belongs to
+    pub project_root: PathBuf,
+    pub active_branch: Option<String>,
+    pub branch_list: Vec<BranchEntry>,
+    pub selected_branch: usize,
+    // Registered projects on the /projects screen and the names ticked there
+    pub project_list: Vec<RegisteredProject>,
+    pub selected_project: usize,
+    pub chosen_projects: Vec<String>,
+    // Comments listed by /todos, grouped by file
+    pub todo_list: Vec<(String, TodoItem)>,
+    pub selected_todo: usize,
+    // Split-screen mode: a viewer beside the chat following the latest cited file
+    pub split_view: bool,
+    // Outline sidebar of the transcript's turns, and the turn selected in it
+    pub outline_open: bool,
+    pub selected_outline: usize,
+    pub file_viewer: Option<FileViewer>,
+    // Focus mode: only the transcript and input, full screen, without the header, key
+    // hints, footer or per-answer context details
+    pub focus_mode: bool,
+    // Crash recovery of the unsent input
+    pub recovered_draft: Option<Draft>,
+    pub last_saved_draft: Option<Draft>,
+    pub last_draft_save: Instant,
+    // Sent inputs, recalled with Ctrl+Up/Down and kept across runs
+    pub input_history: InputHistory,
+    // Ctrl+N completion of the identifier being typed; cleared by any other key
+    pub identifier_completion: Option<IdentifierCompletion>,
+    // Notes sent with the next question only, unless pinned to every question
+    pub scratchpad: String,
+    pub scratchpad_pinned: bool,
+    // Open modal; the screen it was opened over is drawn beneath it
+    pub dialog: Option<Dialog>,
+    // Reports from background tasks, applied once per tick by the render loop
+    pub events: EventBus,
+    // Shell command running in the background, if any
+    pub running_task: Option<String>,
+    // Full-content answers still being refined in the background
+    pub refinements_in_flight: usize,
+    // Answers whose Rust blocks `cargo check` is still compiling
+    pub rust_checks_in_flight: usize,
+    // A past day's digest is being written in the background
+    pub digest_in_flight: bool,
+    // Files added, changed or removed since the session loaded at startup was saved
+    pub briefing: Option<ChangesSince>,
+    // Timing overlay toggled with /profile, in builds with the profiling feature
+    pub show_profile: bool,
+    // NO_COLOR was set when the app started; nothing is drawn in color
+    pub no_color: bool,
+    // Translations for the configured locale
+    pub catalog: Catalog,
+    // Recent sessions, index freshness and spend shown beside the main menu
+    pub project_status: Option<ProjectStatus>,
+    // Attached without the project lock: reads work, indexing is refused
+    pub read_only: bool,
+    pub lock_holder: Option<LockInfo>,
+    // Started with --safe-mode: persisted state only, nothing indexed or scanned
+    pub safe_mode: bool,
+    // Changelog sections on the what's-new screen
+    pub whats_new: Vec<Release>,
+    pub whats_new_scroll: u16,
 }
 
 impl App {
-    pub fn new() -> App {
-        App {
+    pub fn new(config: Config) -> App {
+        let client: Arc<dyn ClaudeClient> = Arc::new(HttpClient::new(CLAUDE_API_URL));
+        // SAGACITY_CHAOS injects API faults so error handling can be exercised by hand
+        let client: Arc<dyn ClaudeClient> = match ChaosConfig::from_env() {
+            Some(chaos) => Arc::new(ChaosClient::new(client, chaos)),
+            None => client,
+        };
+        let client: Arc<dyn ClaudeClient> = if config.conversation_audit {
+            let audit = ConversationAudit::open(&config.data_path());
+            Arc::new(AuditedClient::new(client, audit))
+        } else {
+            client
+        };
+        App::with_client(config, client, PathBuf::from("."))
+    }
+
+    /// Builds the app around the given Messages API client, such as a test mock, on the
+    /// project at `project_root`
+    pub fn with_client(
+        config: Config,
+        client: Arc<dyn ClaudeClient>,
+        project_root: PathBuf,
+    ) -> App {
+        let root_dir = project_root.display().to_string();
+        let session = load_most_recent_session(&config.data_path()).unwrap_or_default();
+        // Counts go to the built-in tokenizer when the plugin can't be loaded
+        let plugin_error = config.tokenizer_plugin.as_ref().and_then(|path| {
+            load_plugin(Path::new(&shellexpand::tilde(path).into_owned()))
+                .err()
+                .map(|e| format!("Tokenizer plugin: {}; using the built-in tokenizer", e))
+        });
+        let mut chatbot = Chatbot::new(
+            HashMap::new(),
+            HashMap::new(),
+            std::env::var("ANTHROPIC_API_KEY").unwrap_or_default(),
+            client,
+        );
+        chatbot.index_root = root_dir.clone();
+        chatbot.failed_summaries = FailedSummaries::load(&root_dir);
+        if let Ok(Some(mut cache)) = load_index_cache(&root_dir) {
+            chatbot.failed_summaries.take_placeholders(&mut cache.index);
+            chatbot.index = Arc::new(cache.index);
+            chatbot.file_mod_times = cache.file_mod_times;
+            chatbot.keywords = cache.keywords;
+            chatbot.churn = cache.churn;
+            chatbot.aliases = cache.aliases;
+        }
+        chatbot.retrieval_memory = RetrievalMemory::load(&config.data_path());
+        chatbot.audit_log = AuditLog::open(&config.data_path());
+        chatbot.schema_drift = SchemaDriftLog::open(&config.data_path());
+        chatbot.schema_drift.enabled = config.schema_drift_checks;
+        chatbot.usage = UsageMetrics::load(&config.data_path());
+        chatbot.todos = TodoTable::load(&config.data_path());
+        chatbot.privacy = PrivacyPolicy::new(&config.privacy_rules());
+        chatbot.index_exclude = config.index_exclude.clone();
+        chatbot.relevance_candidates = config.relevance_candidates;
+        chatbot.rerank_profiles = config.rerank_profiles.clone();
+        chatbot.org_policy = config.org_policy.clone();
+        chatbot.embedding_config = config.embeddings.clone();
+        chatbot.profile = find_profile(&config.task_profile).unwrap_or_else(default_profile);
+        chatbot.embeddings = EmbeddingStore::load(&config.data_path());
+        chatbot.shared_summaries = SharedSummaries::load(&config.data_path());
+        chatbot.answer_cache = AnswerCache::load(&config.data_path());
+        chatbot.prompts = PromptTemplates::load(&config.data_path());
+        chatbot.auto_include_imports = config.auto_include_imports;
+        chatbot.postprocess_config = config.summary_postprocessing.clone();
+        chatbot.sampling = config.sampling.clone();
+        chatbot.time_budget = config.turn_time_budget_secs.map(Duration::from_secs);
+        chatbot.structured_answers = config.structured_answers;
+        chatbot.summary_refresh_limit = config.summary_refresh_limit;
+        chatbot.carry_over_turns = config.context_carry_turns;
+        chatbot.two_phase_answers = config.two_phase_answers;
+        chatbot.compact_history = config.compact_history;
+        chatbot.fast_mode = config.fast_mode;
+        chatbot.latency = LatencyTracker::load(&config.data_path());
+        // Offered from the main menu when the project moved on since the last session
+        let briefing = if session.messages.is_empty() {
+            None
+        } else {
+            changes_since(&session, &chatbot.file_mod_times)
+        };
+        let catalog = Catalog::load(&config.locale);
+        let input_history = InputHistory::load(&config.data_path());
+        let mut app = App {
             state: AppState::MainMenu,
             menu_items: vec![
                 "💬 Chat with any codebase in ~/",
@@ -40,14 +377,2595 @@ impl App {
                 "💬 Chat with GitHub Repo",
                 "📂 Browse Index",
                 "🔍 Browse GitHub Recommendations",
+                "🧾 API Audit Log",
+                "📈 Usage",
+                "🧪 Run Tests",
+                "📰 What's New",
                 "❓ Help",
                 "⚙️ Settings",
                 "🚪 Quit",
             ],
             selected_menu_item: 0,
-            messages: Vec::new(),
+            messages: session.messages,
             input: String::new(),
             dir_tree: DirectoryTree::new(home_dir().unwrap_or(PathBuf::from("/"))),
+            chat_focus: ChatFocus::Input,
+            selected_message: None,
+            reply_quote: None,
+            query_suggestions: Vec::new(),
+            selected_suggestion: 0,
+            selected_code_block: None,
+            expanded_check: None,
+            bookmarks: session.bookmarks,
+            selected_bookmark: 0,
+            pending_template: None,
+            queued_question: None,
+            pending_gist: None,
+            clipboard_ring: ClipboardRing::default(),
+            selected_clip: 0,
+            annotation_input: String::new(),
+            // An interrupted first indexing run is reported so the user knows what resuming costs
+            status_message: load_index_progress(&root_dir).map(|progress| {
+                format!(
+                    "Indexing of {} was interrupted; resuming: {}",
+                    progress.root,
+                    progress.summary()
+                )
+            }),
+            terminal_size: (0, 0),
+            pending_print: None,
+            chatbot,
+            session_id: session.id,
+            session_cost: session.cost,
+            session_list: Vec::new(),
+            selected_session: 0,
+            export_selection: Vec::new(),
+            state_before_switcher: AppState::MainMenu,
+            config,
+            startup_error: None,
+            selected_remediation: 0,
+            data_dir_input: None,
+            url_input: String::new(),
+            remote_project: None,
+            pending_tests: None,
+            pending_long_input: None,
+            selected_truncation: 0,
+            refactor_plan: session.refactor_plan,
+            selected_plan_step: 0,
+            pending_edits: Vec::new(),
+            pending_step: None,
+            sweep: None,
+            selected_sweep_patch: 0,
+            sweep_scroll: 0,
+            heatmap: Vec::new(),
+            selected_heatmap_cell: 0,
+            audit_entries: Vec::new(),
+            audit_filter: String::new(),
+            audit_filter_editing: false,
+            audit_scroll: 0,
+            busy: false,
+            input_received: None,
+            project_root,
+            active_branch: None,
+            branch_list: Vec::new(),
+            selected_branch: 0,
+            project_list: Vec::new(),
+            selected_project: 0,
+            chosen_projects: Vec::new(),
+            todo_list: Vec::new(),
+            selected_todo: 0,
+            split_view: false,
+            outline_open: false,
+            selected_outline: 0,
+            focus_mode: false,
+            file_viewer: None,
+            recovered_draft: None,
+            last_saved_draft: None,
+            last_draft_save: Instant::now(),
+            input_history,
+            identifier_completion: None,
+            scratchpad: String::new(),
+            scratchpad_pinned: false,
+            dialog: None,
+            events: EventBus::default(),
+            running_task: None,
+            refinements_in_flight: 0,
+            rust_checks_in_flight: 0,
+            digest_in_flight: false,
+            briefing,
+            show_profile: false,
+            no_color: std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+            catalog,
+            project_status: None,
+            read_only: false,
+            safe_mode: false,
+            lock_holder: None,
+            whats_new: Vec::new(),
+            whats_new_scroll: 0,
+        };
+        app.chatbot.repo_map = app
+            .messages
+            .iter()
+            .find(|message| message.repo_map)
+            .map(|message| message.content.clone());
+        app.reload_glossary();
+        if plugin_error.is_some() {
+            app.status_message = plugin_error;
+        }
+        if let (Some(changes), None) = (&app.briefing, &app.status_message) {
+            app.status_message = Some(format!(
+                "{} files changed since your last session; press 'b' for a briefing",
+                changes.count()
+            ));
+        }
+        app
+    }
+
+    /// Loads the glossary, answer style and workspace layout of the project being chatted
+    /// about, after it changes
+    pub fn reload_glossary(&mut self) {
+        let (data_path, project) = (self.config.data_path(), self.project_name());
+        self.chatbot.glossary = Glossary::load(&data_path, &project);
+        self.chatbot.style = AnswerStyle::load(&data_path, &project, &self.config.answer_style);
+        self.chatbot.workspace = Workspace::detect(&self.project_dir());
+        self.chatbot.crate_filter = None;
+    }
+
+    /// Builds an app that opens on the startup error screen instead of the main menu
+    pub fn with_startup_error(error: StartupError) -> App {
+        let mut app = App::new(Config::default());
+        app.startup_error = Some(error);
+        app.state = AppState::StartupError;
+        app
+    }
+
+    pub fn remediations(&self) -> Vec<Remediation> {
+        self.startup_error
+            .as_ref()
+            .map(|e| e.remediations())
+            .unwrap_or_default()
+    }
+
+    /// Runs the highlighted remediation action from the startup error screen
+    pub fn apply_selected_remediation(&mut self) {
+        let Some(action) = self.remediations().get(self.selected_remediation).copied() else {
+            return;
+        };
+        match action {
+            Remediation::CreateConfig => match save_config(&Config::default()) {
+                Ok(()) => self.retry_startup(),
+                Err(e) => self.status_message = Some(format!("Failed to write config: {}", e)),
+            },
+            Remediation::ChangeDataDir => {
+                self.data_dir_input = Some(self.config.data_dir.clone());
+            }
+            Remediation::UseDefaultModel => match reset_model() {
+                Ok(()) => self.retry_startup(),
+                Err(e) => self.status_message = Some(format!("Failed to write config: {}", e)),
+            },
+            Remediation::Retry => self.retry_startup(),
+            Remediation::Quit => self.state = AppState::Quit,
+        }
+    }
+
+    /// Saves the data directory typed on the error screen and checks again
+    pub fn commit_data_dir(&mut self) {
+        let Some(data_dir) = self.data_dir_input.take() else {
+            return;
+        };
+        self.config.data_dir = data_dir.trim().to_string();
+        match save_config(&self.config) {
+            Ok(()) => self.retry_startup(),
+            Err(e) => self.status_message = Some(format!("Failed to write config: {}", e)),
+        }
+    }
+
+    /// Sends a question to the chatbot and appends the answer to the transcript
+    pub async fn ask(&mut self, question: &str, on_delta: &mut dyn FnMut(&str)) {
+        let spent_before = self.chatbot.audit_log.spent;
+        let tokens_before = (
+            self.chatbot.audit_log.input_tokens,
+            self.chatbot.audit_log.output_tokens,
+        );
+        let started = std::time::Instant::now();
+        let input_ms = self
+            .input_received
+            .take()
+            .map_or(0, |received| received.elapsed().as_millis() as u64);
+        self.messages.push(Message::user(question));
+        self.chatbot.scratchpad =
+            (!self.scratchpad.trim().is_empty()).then(|| self.scratchpad.trim_end().to_string());
+        let (answer, answered) = match self.chatbot.chat(question, on_delta).await {
+            Ok(response) => {
+                let mut answer = match StructuredAnswer::parse(&response) {
+                    Some(structured) if self.chatbot.structured_answers => {
+                        let mut answer = Message::ai(structured.to_markdown());
+                        answer.structured = Some(structured);
+                        answer
+                    }
+                    _ => Message::ai(response),
+                };
+                answer.context_files = self.chatbot.last_context_files.clone();
+                answer.timed_out = self.chatbot.timed_out;
+                answer.carried_over = self.chatbot.carried_over;
+                answer.cached = self.chatbot.served_from_cache;
+                answer.routed_model = self.chatbot.routed_model.clone();
+                answer.latency = Some(TurnLatency {
+                    input_ms,
+                    ..self.chatbot.turn_latency.clone()
+                });
+                // The full-content answer arrives later on the event bus
+                if let Some(refinement) = self.chatbot.pending_refinement.take() {
+                    answer.from_summaries = true;
+                    self.refinements_in_flight += 1;
+                    tokio::spawn(refine(refinement, self.events.sender()));
+                }
+                answer.scratchpad = self.chatbot.scratchpad.clone();
+                if !self.scratchpad_pinned {
+                    self.scratchpad.clear();
+                }
+                answer.stale_summaries =
+                    stale_summaries(&answer.context_files, &self.chatbot.file_mod_times);
+                // Checked in the background; a first check may build dependencies for minutes
+                if self.config.check_generated_rust && !rust_code_blocks(&answer.content).is_empty()
+                {
+                    self.rust_checks_in_flight += 1;
+                    let (content, root) = (answer.content.clone(), self.project_root.clone());
+                    let (data_dir, sender) = (self.config.data_path(), self.events.sender());
+                    tokio::spawn(async move {
+                        let checks = check_rust_blocks(&content, &root, &data_dir).await;
+                        let _ = sender.send(AppEvent::RustChecked {
+                            answer: content,
+                            checks,
+                        });
+                    });
+                }
+                (answer, true)
+            }
+            Err(e) => (
+                Message::error(AnswerError::new(
+                    &e.to_string(),
+                    question,
+                    self.chatbot.scratchpad.clone(),
+                )),
+                false,
+            ),
+        };
+        self.chatbot.scratchpad = None;
+        // A question that found nothing, or got a doubtful answer, is offered rewrites
+        let retrieval_empty = answer
+            .error
+            .as_ref()
+            .is_some_and(|error| error.class == ErrorClass::NoContext);
+        let low_confidence = answer
+            .structured
+            .as_ref()
+            .and_then(|structured| structured.confidence)
+            .is_some_and(|confidence| confidence < LOW_CONFIDENCE);
+        self.query_suggestions = if retrieval_empty || low_confidence {
+            query_suggestions::suggest(
+                question,
+                &self.chatbot.retrieval_index(),
+                |file| self.chatbot.keywords_for(file),
+                &answer.context_files,
+            )
+        } else {
+            Vec::new()
+        };
+        self.selected_suggestion = 0;
+        self.follow_citation(&answer.content, &answer.context_files);
+        let cost = self.chatbot.audit_log.spent - spent_before;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        self.session_cost += cost;
+        self.chatbot
+            .usage
+            .record_question(latency_ms, answered, cost);
+        // How many of the files retrieval chose the answer cited, against all it cited
+        if answered {
+            let cited = cited_files(&answer.content, &answer.context_files);
+            let retrieved_cited = cited
+                .iter()
+                .filter(|file| self.chatbot.last_retrieved.contains(file))
+                .count();
+            self.chatbot.usage.record_retrieval(
+                self.chatbot.last_retrieved.len(),
+                retrieved_cited,
+                cited.len(),
+            );
+        }
+        // What the answer's input cost is split across its context files by their size
+        if let Some((input_tokens, input_cost)) = self.chatbot.last_answer_input.take() {
+            let weights: Vec<(String, u64)> = answer
+                .context_files
+                .iter()
+                .map(|file| {
+                    let path = std::fs::canonicalize(file)
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_else(|_| file.clone());
+                    let size = std::fs::metadata(file).map_or(1, |metadata| metadata.len());
+                    (path, size.max(1))
+                })
+                .collect();
+            self.chatbot
+                .usage
+                .record_context_cost(&weights, input_tokens, input_cost);
+        }
+
+        let turn = TranscriptTurn {
+            timestamp: chrono::Utc::now(),
+            session_id: self.session_id.clone(),
+            project: self.project_name(),
+            question: question.to_string(),
+            answer: answer.content.clone(),
+            context_files: answer.context_files.clone(),
+            input_tokens: self.chatbot.audit_log.input_tokens - tokens_before.0,
+            output_tokens: self.chatbot.audit_log.output_tokens - tokens_before.1,
+            cost,
+            latency_ms,
+            error: !answered,
+            structured: answer.structured.clone(),
+        };
+        if let Err(e) = append_turn(&self.config.data_path(), &turn) {
+            self.status_message = Some(format!("Failed to append transcript: {}", e));
+        }
+        self.messages.push(answer);
+        if let Err(e) = self.chatbot.usage.save() {
+            self.status_message = Some(format!("Failed to save usage metrics: {}", e));
+        }
+        if !self.chatbot.suggested_imports.is_empty() {
+            self.status_message = Some(format!(
+                "Imported modules not in context: {} — /include to add them",
+                self.chatbot.suggested_imports.join(", ")
+            ));
+        }
+        if let Some(notice) = self.chatbot.schema_drift.take_notice() {
+            self.status_message = Some(notice);
+        }
+        if let Some(warning) = self.chatbot.context_warning.take() {
+            self.status_message = Some(warning);
+        }
+        if answered && self.chatbot.timed_out {
+            self.status_message =
+                Some("Answer cut off by the time budget; /continue picks it up".to_string());
+        }
+        self.persist_session();
+    }
+
+    /// Holds back a question naming code found nowhere in the index and offers to search
+    /// for it first; returns the question when nothing it names is missing
+    pub fn lint_question(&mut self, question: String) -> Option<String> {
+        if !self.config.question_lint {
+            return Some(question);
+        }
+        let missing = missing_names(
+            &question,
+            &self.chatbot.retrieval_index(),
+            |file| self.chatbot.keywords_for(file),
+            |file| self.chatbot.privacy.allows_contents(file),
+        );
+        if missing.is_empty() {
+            return Some(question);
+        }
+        let names = missing
+            .iter()
+            .map(|name| format!("`{}`", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        // The question stays in the input to be edited if the dialog is dismissed
+        self.input = question.clone();
+        self.open_dialog(Dialog::choose(
+            "Not in the index",
+            format!(
+                "{} appears nowhere in the indexed files, so the answer may be a guess. Search for it first?",
+                names
+            ),
+            &["Search first", "Send anyway"],
+            DialogAction::SendQuestion { question, missing },
+        ));
+        None
+    }
+
+    /// Posts the lines of indexed files that mention each name
+    pub fn search_symbols(&mut self, names: &[String]) {
+        let mut report = String::new();
+        for name in names {
+            let hits = symbol_search(self.chatbot.index.keys(), name);
+            report.push_str(&format!("**{}**\n", name));
+            if hits.is_empty() {
+                report.push_str("No indexed file mentions it.\n\n");
+            } else {
+                report.push_str(&format!("```\n{}\n```\n\n", hits.join("\n")));
+            }
+        }
+        self.messages
+            .push(Message::user(format!("/search {}", names.join(" "))));
+        self.messages
+            .push(Message::ai(report.trim_end().to_string()));
+        self.persist_session();
+    }
+
+    /// Holds back a message too long for the context window and offers truncation
+    /// strategies; returns the message when it fits
+    pub fn check_input_budget(&mut self, message: String) -> Option<String> {
+        let Some(budget) = input_budget(&self.chatbot) else {
+            return Some(message);
+        };
+        let tokens = estimate_tokens(&message);
+        if tokens <= budget {
+            return Some(message);
+        }
+        if budget < MIN_INPUT_BUDGET {
+            self.input = message;
+            self.status_message = Some(
+                "The conversation already fills the context window; start a new session to send this"
+                    .to_string(),
+            );
+            return None;
+        }
+        self.status_message = Some(format!(
+            "Message is ~{} tokens but only ~{} fit the context window",
+            tokens, budget
+        ));
+        self.pending_long_input = Some(message);
+        self.selected_truncation = 0;
+        self.state = AppState::Truncation;
+        None
+    }
+
+    pub fn cancel_truncation(&mut self) {
+        if let Some(message) = self.pending_long_input.take() {
+            self.input = message;
+        }
+        self.state = AppState::Chat;
+    }
+
+    /// The held-back message cut down with the highlighted strategy: one message to
+    /// send, or several for a split
+    pub async fn truncate_pending_input(&mut self) -> Vec<String> {
+        self.state = AppState::Chat;
+        let Some(message) = self.pending_long_input.take() else {
+            return Vec::new();
+        };
+        let budget = input_budget(&self.chatbot).unwrap_or(MIN_INPUT_BUDGET);
+        match STRATEGIES[self.selected_truncation.min(STRATEGIES.len() - 1)] {
+            TruncationStrategy::HeadTail => vec![head_tail(&message, budget)],
+            TruncationStrategy::SummarizeFirst => {
+                match summarize_first(&message, budget, &mut self.chatbot).await {
+                    Ok(summary) => vec![summary],
+                    Err(e) => {
+                        self.status_message = Some(format!("Summarizing failed: {}", e));
+                        self.input = message;
+                        Vec::new()
+                    }
+                }
+            }
+            TruncationStrategy::Split => split_turns(&message, budget),
+        }
+    }
+
+    /// The question that resumes an answer cut off by the time budget, if the last one was
+    pub fn continuation_prompt(&mut self) -> Option<&'static str> {
+        if self.messages.last().is_some_and(|m| m.timed_out) {
+            Some(CONTINUE_PROMPT)
+        } else {
+            self.status_message = Some("The last answer wasn't cut off".to_string());
+            None
+        }
+    }
+
+    /// The last question, to ask the model again instead of answering from the cache
+    pub fn regeneration_prompt(&mut self) -> Option<String> {
+        let question = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.sender == Sender::User)
+            .map(|m| m.content.clone());
+        match question {
+            Some(question) => {
+                self.chatbot.skip_answer_cache = true;
+                Some(question)
+            }
+            None => {
+                self.status_message = Some("No question to ask again".to_string());
+                None
+            }
+        }
+    }
+
+    /// Directory the project's files were indexed from
+    pub fn project_dir(&self) -> PathBuf {
+        match &self.remote_project {
+            Some(project) => project.local_path.clone(),
+            None => self.project_root.clone(),
+        }
+    }
+
+    /// Identifies the project being chatted about: the remote URL or the local root
+    pub fn project_name(&self) -> String {
+        match &self.remote_project {
+            Some(project) => project.url.clone(),
+            None => std::fs::canonicalize(&self.project_root)
+                .unwrap_or_else(|_| self.project_root.clone())
+                .display()
+                .to_string(),
+        }
+    }
+
+    /// Exports every audited API call as per-day, per-model CSV for expense reports
+    pub fn export_usage(&mut self) {
+        let entries = self.chatbot.audit_log.entries();
+        self.status_message = Some(match export_usage_csv(&self.config.data_path(), &entries) {
+            Ok(path) => format!("Exported usage to {}", path.display()),
+            Err(e) => format!("Usage export failed: {}", e),
+        });
+    }
+
+    /// Points the file viewer at the file and lines an answer cites, if any
+    fn follow_citation(&mut self, answer: &str, context_files: &[String]) {
+        let Some(path) = first_cited_file(answer, context_files) else {
+            return;
+        };
+        let viewer = match self.file_viewer.take() {
+            Some(viewer) if viewer.path == path => Ok(viewer),
+            _ => FileViewer::open(&path),
+        };
+        match viewer {
+            Ok(mut viewer) => {
+                let range = cited_lines(answer, &path, &viewer.lines);
+                viewer.focus(range);
+                self.file_viewer = Some(viewer);
+            }
+            Err(e) => self.status_message = Some(e.to_string()),
+        }
+    }
+
+    /// Lists the TODO, FIXME and HACK comments of files whose path contains `filter`
+    pub fn open_todos(&mut self, filter: &str) {
+        let todos = self.chatbot.todos.list(filter);
+        if todos.is_empty() {
+            self.status_message = Some(if filter.is_empty() {
+                "No TODO, FIXME or HACK comments in the index".to_string()
+            } else {
+                format!(
+                    "No TODO, FIXME or HACK comments in files matching {}",
+                    filter
+                )
+            });
+            return;
+        }
+        self.todo_list = todos;
+        self.selected_todo = 0;
+        self.state = AppState::Todos;
+    }
+
+    /// Opens the highlighted comment's file beside the chat at its line
+    pub fn jump_to_selected_todo(&mut self) {
+        let Some((file, item)) = self.todo_list.get(self.selected_todo).cloned() else {
+            return;
+        };
+        match FileViewer::open(&file) {
+            Ok(mut viewer) => {
+                let line = item.line.saturating_sub(1);
+                viewer.focus(Some((line, line)));
+                self.file_viewer = Some(viewer);
+                self.split_view = true;
+                self.chat_focus = ChatFocus::Viewer;
+                self.state = AppState::Chat;
+            }
+            Err(e) => self.status_message = Some(e.to_string()),
+        }
+    }
+
+    /// Opens the outline sidebar on the turn being read, focused, or closes it
+    pub fn toggle_outline(&mut self) {
+        self.outline_open = !self.outline_open;
+        if !self.outline_open {
+            if self.chat_focus == ChatFocus::Outline {
+                self.chat_focus = ChatFocus::Input;
+            }
+            return;
+        }
+        let entries = outline(&self.messages);
+        let reading = self.selected_message.unwrap_or(usize::MAX);
+        self.selected_outline = entries
+            .iter()
+            .rposition(|entry| entry.message_index <= reading)
+            .unwrap_or(0);
+        self.chat_focus = ChatFocus::Outline;
+    }
+
+    pub fn move_outline_selection(&mut self, delta: isize) {
+        let last = outline(&self.messages).len().saturating_sub(1);
+        self.selected_outline = self.selected_outline.saturating_add_signed(delta).min(last);
+    }
+
+    /// Scrolls the transcript to the selected turn's question and focuses it there
+    pub fn jump_to_outline_turn(&mut self) {
+        if let Some(entry) = outline(&self.messages).get(self.selected_outline) {
+            self.selected_message = Some(entry.message_index);
+            self.selected_code_block = None;
+            self.chat_focus = ChatFocus::Transcript;
+        }
+    }
+
+    /// Turns split-screen mode on or off, starting from the latest cited answer
+    pub fn toggle_split_view(&mut self) {
+        self.split_view = !self.split_view;
+        if self.split_view && self.file_viewer.is_none() {
+            if let Some(answer) = self
+                .messages
+                .iter()
+                .rev()
+                .find(|m| m.sender == Sender::AI && !m.context_files.is_empty())
+                .cloned()
+            {
+                self.follow_citation(&answer.content, &answer.context_files);
+            }
+        }
+    }
+
+    pub fn scroll_file_viewer(&mut self, delta: isize) {
+        if let Some(viewer) = self.file_viewer.as_mut() {
+            viewer.scroll_by(delta);
+        }
+    }
+
+    /// Lines visible in the file viewer pane at the current terminal size
+    pub fn viewer_height(&self) -> usize {
+        let chrome = if self.config.accessibility_mode {
+            5
+        } else {
+            11
+        };
+        (self.terminal_size.1 as usize).saturating_sub(chrome + 2)
+    }
+
+    pub fn move_viewer_cursor(&mut self, delta: isize) {
+        let height = self.viewer_height();
+        if let Some(viewer) = self.file_viewer.as_mut() {
+            // The diff since indexing just scrolls
+            match &viewer.index_diff {
+                Some(index_diff) => {
+                    let max = index_diff.diff.lines().count().saturating_sub(1);
+                    viewer.scroll = viewer.scroll.saturating_add_signed(delta).min(max);
+                }
+                None => viewer.move_cursor(delta, height),
+            }
+        }
+    }
+
+    /// Shows how the viewed file changed since it was summarized, or the file again
+    pub fn toggle_index_diff(&mut self) {
+        let Some(viewer) = self.file_viewer.as_mut() else {
+            return;
+        };
+        if viewer.index_diff.take().is_some() {
+            return;
+        }
+        let current = match fs::read_to_string(&viewer.path) {
+            Ok(current) => current,
+            Err(e) => {
+                self.status_message = Some(format!("Failed to read {}: {}", viewer.path, e));
+                return;
+            }
+        };
+        self.status_message = Some(match diff_against_index(&viewer.path, &current) {
+            None => format!(
+                "No indexed version of {} was kept; reindex to record one",
+                viewer.path
+            ),
+            Some(diff) if diff.is_empty() => {
+                format!("{} is unchanged since it was indexed", viewer.path)
+            }
+            Some(diff) => {
+                let status = format!(
+                    "{} changed since it was indexed: +{} -{} lines. 'i' to go back",
+                    viewer.path, diff.added, diff.removed
+                );
+                viewer.scroll = 0;
+                viewer.index_diff = Some(diff);
+                status
+            }
+        });
+    }
+
+    /// Replaces the input with an older (-1) or newer (1) sent input that starts with
+    /// what was typed before recall began
+    pub fn recall_history(&mut self, direction: isize) {
+        let recalled = if direction < 0 {
+            self.input_history.older(&self.input)
+        } else {
+            self.input_history.newer()
+        };
+        match recalled {
+            Some(entry) => self.input = entry,
+            None if direction < 0 && !self.input_history.prefix().is_empty() => {
+                self.status_message = Some(format!(
+                    "No earlier input starts with \"{}\"",
+                    self.input_history.prefix()
+                ))
+            }
+            None => {}
+        }
+    }
+
+    /// Completes the identifier at the end of the input from the symbols the index knows,
+    /// or moves on to the next match when repeated
+    pub fn complete_identifier(&mut self) {
+        match self.identifier_completion.as_mut() {
+            Some(completion) => completion.advance(),
+            None => {
+                let symbols = completions(
+                    VariableKind::Symbol,
+                    &self.chatbot.index,
+                    &self.chatbot.keywords,
+                );
+                self.identifier_completion = IdentifierCompletion::begin(&self.input, &symbols);
+            }
+        }
+        match &self.identifier_completion {
+            Some(completion) => {
+                completion.apply(&mut self.input);
+                self.status_message = Some(format!("Completion {}", completion.position()));
+            }
+            None => self.status_message = Some("No known identifier matches".to_string()),
+        }
+    }
+
+    pub fn remember_input(&mut self, input: &str) {
+        self.input_history.push(input);
+        if let Err(e) = self.input_history.save() {
+            self.status_message = Some(format!("Failed to save input history: {}", e));
+        }
+    }
+
+    /// Opens the scratchpad editor in the input box, or returns to the question
+    pub fn toggle_scratchpad(&mut self) {
+        self.chat_focus = if self.chat_focus == ChatFocus::Scratchpad {
+            ChatFocus::Input
+        } else {
+            ChatFocus::Scratchpad
+        };
+    }
+
+    pub fn toggle_scratchpad_pin(&mut self) {
+        self.scratchpad_pinned = !self.scratchpad_pinned;
+        self.status_message = Some(if self.scratchpad_pinned {
+            "Scratchpad pinned: sent with every question until unpinned".to_string()
+        } else {
+            "Scratchpad unpinned: cleared after the next question".to_string()
+        });
+    }
+
+    /// Tab order of the chat screen; the viewer only takes focus in split-screen mode
+    pub fn next_chat_focus(&mut self) {
+        self.chat_focus = match self.chat_focus {
+            ChatFocus::Input => {
+                self.move_message_selection(0);
+                ChatFocus::Transcript
+            }
+            ChatFocus::Transcript if self.split_view && self.file_viewer.is_some() => {
+                ChatFocus::Viewer
+            }
+            ChatFocus::Transcript | ChatFocus::Viewer if self.outline_open => ChatFocus::Outline,
+            _ => ChatFocus::Input,
+        };
+    }
+
+    /// Loads the audit log from disk, newest call first
+    pub fn open_audit(&mut self) {
+        self.audit_entries = self.chatbot.audit_log.entries();
+        self.audit_entries.reverse();
+        self.audit_scroll = 0;
+        self.state = AppState::Audit;
+    }
+
+    pub fn filtered_audit_entries(&self) -> Vec<&ApiCallLog> {
+        let filter = self.audit_filter.to_lowercase();
+        self.audit_entries
+            .iter()
+            .filter(|entry| {
+                filter.is_empty()
+                    || entry.request_summary.to_lowercase().contains(&filter)
+                    || entry.model.to_lowercase().contains(&filter)
+                    || entry.response_status.to_string().contains(&filter)
+                    || (entry.truncated && "truncated".contains(&filter))
+            })
+            .collect()
+    }
+
+    /// Feeds the selected answer back into retrieval memory as a strong signal
+    pub fn mark_answer_helpful(&mut self) {
+        let Some(index) = self.selected_message else {
+            return;
+        };
+        let answer = &self.messages[index];
+        if answer.context_files.is_empty() {
+            self.status_message = Some("Only answers with context files can be rated".to_string());
+            return;
+        }
+        let question = self.messages[..index]
+            .iter()
+            .rev()
+            .find(|m| m.sender == Sender::User)
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        let files = answer.context_files.clone();
+        self.chatbot.retrieval_memory.record(&question, &files, 2.0);
+        self.status_message = Some(match self.chatbot.retrieval_memory.save() {
+            Ok(()) => {
+                "Marked as helpful; these files will rank higher for similar questions".to_string()
+            }
+            Err(e) => format!("Failed to save retrieval memory: {}", e),
+        });
+    }
+
+    /// Indexes the working directory so "Chat with CWD" has something to retrieve from
+    pub async fn index_current_dir(&mut self, on_progress: &mut dyn FnMut(&IndexProgress)) {
+        if !self.ensure_writable() {
+            return;
+        }
+        // Coming back from a remote or another worktree, start from this project's cache
+        if self.chatbot.index_root != "." {
+            self.chatbot.use_index_cache(".");
+        }
+        let api_key = self.chatbot.api_key.clone();
+        let pb = indicatif::ProgressBar::hidden();
+        match index_codebase(".", &api_key, &pb, &mut self.chatbot, on_progress).await {
+            Ok((index, _, file_mod_times)) => {
+                self.status_message = Some(format!("Indexed {} files", index.len()));
+                self.chatbot.index = Arc::new(index);
+                self.chatbot.file_mod_times = file_mod_times;
+                self.remote_project = None;
+                self.project_root = PathBuf::from(".");
+                self.active_branch = None;
+                self.reload_glossary();
+                self.apply_context_eviction();
+            }
+            Err(e) => self.status_message = Some(format!("Indexing failed: {}", e)),
+        }
+    }
+
+    /// Summarizes the files whose summary failed once more, backing off between attempts
+    pub async fn retry_failed_summaries(&mut self) {
+        if self.chatbot.failed_summaries.files.is_empty() || !self.ensure_writable() {
+            return;
+        }
+        let api_key = self.chatbot.api_key.clone();
+        self.status_message = Some(
+            match retry_failed_summaries(&mut self.chatbot, &api_key).await {
+                Ok((fixed, 0)) => format!("Summarized all {} failed files", fixed),
+                Ok((fixed, failing)) => {
+                    format!("Summarized {} failed files; {} still fail", fixed, failing)
+                }
+                Err(e) => format!("Retrying failed summaries failed: {}", e),
+            },
+        );
+        // Shown on the menu, which would otherwise wait for its next refresh
+        self.project_status = None;
+    }
+
+    /// Carries the reindex's deleted/renamed files over to the transcript and reports them
+    pub fn apply_context_eviction(&mut self) {
+        let Some(eviction) = self.chatbot.last_eviction.take() else {
+            return;
+        };
+        for message in self.messages.iter_mut() {
+            eviction.remap_all(&mut message.context_files);
+        }
+        self.persist_session();
+        self.status_message = Some(match self.status_message.take() {
+            Some(status) => format!("{} · {}", status, eviction.notice()),
+            None => eviction.notice(),
+        });
+    }
+
+    /// Measures the indexed files and opens the context size heatmap on the largest one
+    pub fn open_heatmap(&mut self) {
+        let turns = load_turns(&self.config.data_path(), &self.project_name());
+        self.heatmap = build_heatmap(self.chatbot.index.keys(), &turns);
+        self.selected_heatmap_cell = 0;
+        self.state = AppState::Heatmap;
+    }
+
+    /// Shows the refactor checklist, starting at the first unfinished step
+    pub fn open_refactor_plan(&mut self) {
+        let Some(plan) = &self.refactor_plan else {
+            self.status_message = Some("Usage: /plan-refactor <goal>".to_string());
+            return;
+        };
+        self.selected_plan_step = plan.steps.iter().position(|step| !step.done).unwrap_or(0);
+        self.state = AppState::RefactorPlan;
+    }
+
+    /// Ticks or unticks the highlighted step by hand, e.g. for steps done outside the app
+    pub fn toggle_plan_step(&mut self) {
+        let Some(step) = self
+            .refactor_plan
+            .as_mut()
+            .and_then(|plan| plan.steps.get_mut(self.selected_plan_step))
+        else {
+            return;
+        };
+        step.done = !step.done;
+        self.persist_session();
+    }
+
+    pub fn open_sweep(&mut self) {
+        if self.sweep.is_none() {
+            self.status_message =
+                Some("Usage: /sweep <change to make across the repo>".to_string());
+            return;
+        }
+        self.selected_sweep_patch = 0;
+        self.sweep_scroll = 0;
+        self.state = AppState::Sweep;
+    }
+
+    pub fn move_sweep_selection(&mut self, delta: isize) {
+        let count = self.sweep.as_ref().map_or(0, |sweep| sweep.patches.len());
+        if count == 0 {
+            return;
+        }
+        self.selected_sweep_patch =
+            (self.selected_sweep_patch as isize + delta).clamp(0, count as isize - 1) as usize;
+        self.sweep_scroll = 0;
+    }
+
+    /// Includes or leaves out the highlighted file, or every file with `all`
+    pub fn toggle_sweep_patch(&mut self, all: bool) {
+        let Some(sweep) = self.sweep.as_mut() else {
+            return;
+        };
+        if all {
+            let select = sweep.selected_count() < sweep.patches.len();
+            for patch in &mut sweep.patches {
+                patch.selected = select;
+            }
+        } else if let Some(patch) = sweep.patches.get_mut(self.selected_sweep_patch) {
+            patch.selected = !patch.selected;
+        }
+    }
+
+    /// Adds files just written to the session's ledger of applied changes, for /changes
+    pub fn record_change(&mut self, source: &str, description: &str, files: Vec<FileChange>) {
+        let mut ledger = ChangeLedger::load(&self.config.data_path(), &self.session_id);
+        if let Err(e) = ledger.record(source, description, files) {
+            let status = self.status_message.take().unwrap_or_default();
+            self.status_message = Some(format!("{}; not recorded for /changes: {}", status, e));
+        }
+    }
+
+    /// Writes the selected patches together, or none of them if any write fails
+    pub fn apply_sweep(&mut self) {
+        let Some(sweep) = &self.sweep else {
+            return;
+        };
+        if sweep.selected_count() == 0 {
+            self.status_message = Some("No files selected; Space selects a file".to_string());
+            return;
+        }
+        let files: Vec<FileChange> = sweep
+            .patches
+            .iter()
+            .filter(|patch| patch.selected)
+            .map(|patch| FileChange {
+                path: patch.edit.path.clone(),
+                before: Some(patch.original.clone()),
+                after: patch.edit.contents.clone(),
+            })
+            .collect();
+        let description = sweep.description.clone();
+        match apply_selected(sweep) {
+            Ok(count) => {
+                self.status_message = Some(format!("Sweep applied to {} files", count));
+                self.record_change("sweep", &description, files);
+                self.sweep = None;
+                self.state = AppState::Chat;
+            }
+            Err(e) => self.status_message = Some(format!("Sweep not applied: {}", e)),
+        }
+    }
+
+    /// Lists the branches of the current repository, preselecting the checked-out one
+    pub fn open_branch_switcher(&mut self) {
+        let Some(repo) = repo_root(&self.project_root) else {
+            self.status_message = Some("Branch switching failed: not a git repository".to_string());
+            return;
+        };
+        match list_branches(&repo) {
+            Ok(branches) => {
+                let current = self
+                    .active_branch
+                    .clone()
+                    .or_else(|| current_branch(&self.project_root));
+                self.selected_branch = branches
+                    .iter()
+                    .position(|b| Some(&b.name) == current.as_ref())
+                    .unwrap_or(0);
+                self.branch_list = branches;
+                self.state = AppState::BranchSwitcher;
+            }
+            Err(e) => self.status_message = Some(format!("Listing branches failed: {}", e)),
+        }
+    }
+
+    /// Registers the current index under a name so other projects' questions can span it
+    pub fn register_project(&mut self, name: &str) {
+        if self.chatbot.index.is_empty() {
+            self.status_message = Some("Index the project before registering it".to_string());
+            return;
+        }
+        let root = self.project_dir();
+        let name = match name {
+            "" => root
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.project_name()),
+            name => name.to_string(),
+        };
+        let project = ProjectIndex {
+            name: name.clone(),
+            root: root.display().to_string(),
+            index: (*self.chatbot.index).clone(),
+            keywords: self.chatbot.keywords.clone(),
+        };
+        let mut registry = ProjectRegistry::load(&self.config.data_path());
+        self.status_message = Some(match registry.register(&project) {
+            Ok(()) => format!(
+                "Registered {} ({} files); /projects to ask across it",
+                name,
+                project.index.len()
+            ),
+            Err(e) => format!("Registering {} failed: {}", name, e),
+        });
+    }
+
+    pub fn unregister_project(&mut self, name: &str) {
+        let mut registry = ProjectRegistry::load(&self.config.data_path());
+        self.status_message = Some(match registry.remove(name) {
+            Ok(true) => {
+                self.chatbot.projects.retain(|project| project.name != name);
+                format!("Unregistered {}", name)
+            }
+            Ok(false) => format!("No registered project named \"{}\"", name),
+            Err(e) => format!("Unregistering {} failed: {}", name, e),
+        });
+    }
+
+    /// Lists the registered projects, ticking those questions already span
+    pub fn open_projects(&mut self) {
+        let registry = ProjectRegistry::load(&self.config.data_path());
+        if registry.projects.is_empty() {
+            self.status_message =
+                Some("No registered projects; /project add registers this one".to_string());
+            return;
+        }
+        self.project_list = registry.projects;
+        self.selected_project = 0;
+        self.chosen_projects = self
+            .chatbot
+            .projects
+            .iter()
+            .map(|project| project.name.clone())
+            .collect();
+        self.state = AppState::Projects;
+    }
+
+    pub fn toggle_project(&mut self) {
+        let Some(project) = self.project_list.get(self.selected_project) else {
+            return;
+        };
+        match self
+            .chosen_projects
+            .iter()
+            .position(|name| name == &project.name)
+        {
+            Some(i) => {
+                self.chosen_projects.remove(i);
+            }
+            None => self.chosen_projects.push(project.name.clone()),
+        }
+    }
+
+    /// Loads the ticked projects' indexes so retrieval spans them; the current project is
+    /// always searched, so its own registration is skipped
+    pub fn apply_projects(&mut self) {
+        let registry = ProjectRegistry::load(&self.config.data_path());
+        let current = self.project_dir().display().to_string();
+        self.chatbot.projects = self
+            .chosen_projects
+            .iter()
+            .filter_map(|name| registry.load_index(name))
+            .filter(|project| project.root != current)
+            .collect();
+        self.chatbot.carry_over = CarryOver::default();
+        let names: Vec<&str> = self
+            .chatbot
+            .projects
+            .iter()
+            .map(|project| project.name.as_str())
+            .collect();
+        self.status_message = Some(if names.is_empty() {
+            "Questions search this project only".to_string()
+        } else {
+            format!("Questions span this project and {}", names.join(", "))
+        });
+        self.state = AppState::Chat;
+    }
+
+    fn snapshot_current_branch(&mut self, repo: &Path) {
+        let (Some(branch), Some(commit)) = (
+            current_branch(&self.project_root),
+            head_commit(&self.project_root),
+        ) else {
+            return;
+        };
+        let snapshot = BranchSnapshot {
+            branch,
+            commit,
+            root: self.project_root.display().to_string(),
+            index: (*self.chatbot.index).clone(),
+            file_mod_times: self.chatbot.file_mod_times.clone(),
+            keywords: self.chatbot.keywords.clone(),
+        };
+        if let Err(e) = save_snapshot(&self.config.data_path(), repo, &snapshot) {
+            self.status_message = Some(format!("Failed to save branch index: {}", e));
+        }
+    }
+
+    /// Switches to the highlighted branch, reusing its index when the commit hasn't moved
+    pub async fn switch_to_selected_branch(&mut self, on_progress: &mut dyn FnMut(&IndexProgress)) {
+        let Some(entry) = self.branch_list.get(self.selected_branch).cloned() else {
+            return;
+        };
+        let Some(repo) = repo_root(&self.project_root) else {
+            return;
+        };
+        let data_dir = self.config.data_path();
+
+        self.snapshot_current_branch(&repo);
+
+        let root = match ensure_worktree(&repo, &entry, &data_dir) {
+            Ok(root) => root,
+            Err(e) => {
+                self.status_message = Some(format!("Preparing worktree failed: {}", e));
+                return;
+            }
+        };
+
+        let snapshot = load_snapshot(&data_dir, &repo, &entry.name);
+        let up_to_date = snapshot.as_ref().is_some_and(|s| s.commit == entry.commit);
+        match snapshot {
+            Some(snapshot) => {
+                self.chatbot.index = Arc::new(snapshot.index);
+                self.chatbot.file_mod_times = snapshot.file_mod_times;
+                self.chatbot.keywords = snapshot.keywords;
+            }
+            None => {
+                self.chatbot.index = Arc::default();
+                self.chatbot.file_mod_times.clear();
+                self.chatbot.keywords.clear();
+            }
+        }
+        self.chatbot.content_cache.clear();
+
+        if up_to_date {
+            self.status_message = Some(format!(
+                "Switched to {} using its index at {}",
+                entry.name,
+                &entry.commit[..entry.commit.len().min(8)]
+            ));
+        } else if !self.ensure_writable() {
+            return;
+        } else {
+            // The snapshot is stale or missing, so bring it up to the branch's commit
+            let root_str = root.display().to_string();
+            let api_key = self.chatbot.api_key.clone();
+            let pb = indicatif::ProgressBar::hidden();
+            match index_codebase(&root_str, &api_key, &pb, &mut self.chatbot, on_progress).await {
+                Ok((index, _, file_mod_times)) => {
+                    self.chatbot.index = Arc::new(index);
+                    self.chatbot.file_mod_times = file_mod_times;
+                    let snapshot = BranchSnapshot {
+                        branch: entry.name.clone(),
+                        commit: entry.commit.clone(),
+                        root: root_str,
+                        index: (*self.chatbot.index).clone(),
+                        file_mod_times: self.chatbot.file_mod_times.clone(),
+                        keywords: self.chatbot.keywords.clone(),
+                    };
+                    if let Err(e) = save_snapshot(&data_dir, &repo, &snapshot) {
+                        self.status_message = Some(format!("Failed to save branch index: {}", e));
+                    } else {
+                        self.status_message =
+                            Some(format!("Switched to {} and reindexed", entry.name));
+                    }
+                    self.apply_context_eviction();
+                }
+                Err(e) => {
+                    self.status_message = Some(format!("Indexing {} failed: {}", entry.name, e));
+                    return;
+                }
+            }
+        }
+
+        self.chatbot.workspace = Workspace::detect(&root);
+        self.project_root = root;
+        self.active_branch = Some(entry.name);
+        self.state = AppState::Chat;
+    }
+
+    /// Shallow-clones the URL typed on the remote repository screen and indexes it
+    pub async fn open_remote_repo(&mut self, on_progress: &mut dyn FnMut(&IndexProgress)) {
+        let url = self.url_input.trim().to_string();
+        if !crate::remote::is_git_url(&url) {
+            self.status_message = Some(format!("Not a git URL: {}", url));
+            return;
+        }
+        if !self.ensure_writable() {
+            return;
+        }
+        let project = match clone_remote(&url, &self.config.data_path()).await {
+            Ok(project) => project,
+            Err(e) => {
+                self.status_message = Some(format!("Clone failed: {}", e));
+                return;
+            }
+        };
+        match index_remote(&project, &mut self.chatbot, on_progress).await {
+            Ok(count) => {
+                self.status_message = Some(format!("Indexed {} files from {}", count, url));
+                self.remote_project = Some(project);
+                self.reload_glossary();
+                self.url_input.clear();
+                self.state = AppState::Chat;
+                self.apply_context_eviction();
+                self.post_repo_map();
+            }
+            Err(e) => self.status_message = Some(format!("Indexing failed: {}", e)),
+        }
+    }
+
+    pub fn retry_startup(&mut self) {
+        match run_startup_checks() {
+            Ok(config) => {
+                let terminal_size = self.terminal_size;
+                *self = App::new(config);
+                self.on_resize(terminal_size.0, terminal_size.1);
+            }
+            Err(error) => {
+                self.status_message = Some(format!("Still failing: {}", error.detail));
+                self.startup_error = Some(error);
+                self.selected_remediation = 0;
+            }
+        }
+    }
+
+    /// Records the new terminal dimensions and keeps every cursor in range
+    pub fn on_resize(&mut self, width: u16, height: u16) {
+        self.terminal_size = (width, height);
+        self.clamp_selections();
+    }
+
+    pub fn clamp_selections(&mut self) {
+        self.selected_menu_item = self
+            .selected_menu_item
+            .min(self.menu_items.len().saturating_sub(1));
+        self.selected_bookmark = self
+            .selected_bookmark
+            .min(self.bookmarks.len().saturating_sub(1));
+        self.selected_message = match self.selected_message {
+            Some(_) if self.messages.is_empty() => None,
+            Some(i) => Some(i.min(self.messages.len() - 1)),
+            None => None,
+        };
+    }
+
+    /// Moves the transcript selection up (negative) or down (positive)
+    pub fn move_message_selection(&mut self, delta: isize) {
+        if self.messages.is_empty() {
+            self.selected_message = None;
+            return;
+        }
+        let last = self.messages.len() - 1;
+        let current = self.selected_message.unwrap_or(last) as isize;
+        let next = (current + delta).clamp(0, last as isize) as usize;
+        self.selected_message = Some(next);
+        self.selected_code_block = None;
+    }
+
+    /// Moves to the previous or next code block anywhere in the transcript, selecting
+    /// its message so the transcript scrolls to it
+    pub fn move_code_block_selection(&mut self, delta: isize) {
+        let blocks: Vec<(usize, usize)> = self
+            .messages
+            .iter()
+            .enumerate()
+            .flat_map(|(i, msg)| (0..code_block_count(&msg.content)).map(move |block| (i, block)))
+            .collect();
+        if blocks.is_empty() {
+            self.status_message = Some("No code blocks in this conversation".to_string());
+            return;
+        }
+        let position = match self.selected_code_block {
+            Some(current) => blocks
+                .iter()
+                .position(|&block| block == current)
+                .map(|p| (p as isize + delta).clamp(0, blocks.len() as isize - 1) as usize),
+            None => None,
+        }
+        .unwrap_or_else(|| {
+            // Starting out, take the nearest block in the direction of travel from the
+            // selected message, or from the end of the transcript
+            let from = self.selected_message.unwrap_or(self.messages.len());
+            if delta > 0 {
+                blocks
+                    .iter()
+                    .position(|&(message, _)| message >= from)
+                    .unwrap_or(blocks.len() - 1)
+            } else {
+                blocks
+                    .iter()
+                    .rposition(|&(message, _)| message <= from)
+                    .unwrap_or(0)
+            }
+        });
+        let (message, block) = blocks[position];
+        self.selected_code_block = Some((message, block));
+        self.selected_message = Some(message);
+        self.chat_focus = ChatFocus::Transcript;
+        self.status_message = Some(format!(
+            "Code block {} of {} (message {}, block {})",
+            position + 1,
+            blocks.len(),
+            message + 1,
+            block + 1
+        ));
+    }
+
+    pub fn bookmark_for(&self, message_index: usize) -> Option<&Bookmark> {
+        self.bookmarks
+            .iter()
+            .find(|b| b.message_index == message_index)
+    }
+
+    /// Quotes the selected message in the next question, like replying in a chat app
+    pub fn quote_selected_message(&mut self) {
+        let Some(message) = self.selected_message.and_then(|i| self.messages.get(i)) else {
+            return;
+        };
+        self.reply_quote = Some(blockquote(&message.content, MAX_QUOTE_LINES));
+        self.chat_focus = ChatFocus::Input;
+        self.status_message =
+            Some("Replying to the quoted message; Backspace on empty input drops it".to_string());
+    }
+
+    /// Puts text at the front of the clipboard ring and on the system clipboard; the ring
+    /// keeps it even where there is no system clipboard
+    pub fn copy_to_clipboard(&mut self, text: String) -> Result<(), Box<dyn std::error::Error>> {
+        self.clipboard_ring.push(text.clone());
+        ClipboardContext::new().and_then(|mut ctx: ClipboardContext| ctx.set_contents(text))?;
+        Ok(())
+    }
+
+    /// Opens the chat with the picked content quoted and a question to start from,
+    /// unless something is already typed
+    pub fn ask_about(&mut self, ask: AskAbout) {
+        self.reply_quote = Some(ask.quote);
+        if self.input.is_empty() {
+            self.input = ask.question;
+        }
+        self.chat_focus = ChatFocus::Input;
+        self.state = AppState::Chat;
+        self.status_message = Some(
+            "Edit the question and press Enter; Backspace on empty input drops the quote"
+                .to_string(),
+        );
+    }
+
+    /// Asks about the audit entry under the cursor
+    pub fn ask_about_audit_entry(&mut self) {
+        let Some(entry) = self
+            .filtered_audit_entries()
+            .get(self.audit_scroll)
+            .copied()
+        else {
+            return;
+        };
+        let ask = ask_about::audit_entry(entry);
+        self.ask_about(ask);
+    }
+
+    /// Asks about the failing part of the selected command output, such as a test run
+    pub fn ask_about_selected_failure(&mut self) {
+        let Some(message) = self.selected_message.and_then(|i| self.messages.get(i)) else {
+            return;
+        };
+        match ask_about::command_failure(&message.content) {
+            Some(ask) => self.ask_about(ask),
+            None => {
+                self.status_message =
+                    Some("Not the output of a failed command; 'r' quotes any message".to_string())
+            }
+        }
+    }
+
+    /// Takes the selected failed turn out of the transcript, with its question, and returns
+    /// the question to send again with the notes it was first sent with
+    pub fn take_retry(&mut self) -> Option<String> {
+        let index = self.selected_message?;
+        let error = self.messages.get(index)?.error.clone()?;
+        self.messages.remove(index);
+        let asked = index
+            .checked_sub(1)
+            .filter(|&i| self.messages[i].sender == Sender::User);
+        if let Some(asked) = asked {
+            self.messages.remove(asked);
+        }
+        self.selected_message = None;
+        if let Some(notes) = error.scratchpad {
+            self.scratchpad = notes;
+        }
+        Some(error.question)
+    }
+
+    /// Copies the selected message's focused code block, or else the whole message, to
+    /// the system clipboard
+    pub fn copy_selected_message(&mut self) {
+        let Some(index) = self.selected_message.filter(|&i| i < self.messages.len()) else {
+            return;
+        };
+        let block = self
+            .selected_code_block
+            .filter(|(message, _)| *message == index)
+            .and_then(|(_, block)| {
+                code_blocks(&self.messages[index].content)
+                    .into_iter()
+                    .nth(block)
+            });
+        let (what, text) = match block {
+            Some((_, contents)) => ("Code block", contents),
+            None => ("Message", self.messages[index].content.clone()),
+        };
+        self.status_message = Some(match self.copy_to_clipboard(text) {
+            Ok(()) => format!("{} copied to clipboard", what),
+            Err(e) => format!("Failed to copy to clipboard: {}", e),
+        });
+    }
+
+    /// Asks before uploading the selected message or its focused code block, else the
+    /// latest answer, as a secret gist; masked like a shared transcript
+    pub fn confirm_gist_upload(&mut self) {
+        let Some(index) = self
+            .selected_message
+            .filter(|&i| i < self.messages.len())
+            .or_else(|| self.messages.iter().rposition(|m| m.sender == Sender::AI))
+        else {
+            self.status_message = Some("Nothing to upload yet".to_string());
+            return;
+        };
+        if self.config.github_token.is_none() {
+            self.status_message =
+                Some("Set github_token in the config to upload gists".to_string());
+            return;
+        }
+        let block = self
+            .selected_code_block
+            .filter(|(message, _)| *message == index)
+            .map(|(_, block)| block);
+        let (scrubber, _) = Scrubber::new(&self.config.scrub_patterns());
+        let mut report = ScrubReport::default();
+        let Some(snippet) =
+            snippet_for(&self.messages[index].content, block, &scrubber, &mut report)
+        else {
+            return;
+        };
+        self.open_dialog(Dialog::confirm(
+            "Upload Gist",
+            format!(
+                "Upload {} ({} lines) to GitHub as a secret gist? Anyone with the link can read it; {}.",
+                snippet.file_name,
+                snippet.content.lines().count(),
+                report.summary()
+            ),
+            "Upload",
+            DialogAction::UploadGist(snippet),
+        ));
+    }
+
+    /// Uploads the snippet and copies the gist's URL
+    pub async fn upload_gist(&mut self, snippet: GistSnippet) {
+        let Some(token) = self.config.github_token.clone() else {
+            return;
+        };
+        self.status_message = Some(match create_gist(&token, &snippet).await {
+            Ok(url) => match self.copy_to_clipboard(url.clone()) {
+                Ok(()) => format!("Uploaded the gist; copied {}", url),
+                Err(_) => format!("Uploaded the gist to {}", url),
+            },
+            Err(e) => format!("Gist upload failed: {}", e),
+        });
+    }
+
+    /// Exports the conversation as Markdown, or copies it to the clipboard with `share`,
+    /// after masking emails, tokens, internal hostnames and the configured patterns
+    pub fn export_transcript(&mut self, share: bool) -> bool {
+        if self.messages.is_empty() {
+            self.status_message = Some("Nothing to export yet".to_string());
+            return false;
+        }
+        let (scrubber, invalid) = Scrubber::new(&self.config.scrub_patterns());
+        let (markdown, report) = scrubbed_transcript(
+            &title_from_messages(&self.messages),
+            &self.messages,
+            &scrubber,
+        );
+        let outcome = if share {
+            self.copy_to_clipboard(markdown)
+                .map(|()| "Transcript copied to clipboard".to_string())
+        } else {
+            export_transcript(&self.config.data_path(), &markdown)
+                .map(|path| format!("Exported transcript to {}", path.display()))
+        };
+        let exported = outcome.is_ok();
+        let mut status = match outcome {
+            Ok(done) => format!("{}; {}", done, report.summary()),
+            Err(e) => format!("Transcript export failed: {}", e),
+        };
+        if !invalid.is_empty() {
+            status.push_str(&format!(
+                " (invalid scrub patterns skipped: {})",
+                invalid.join(", ")
+            ));
+        }
+        self.status_message = Some(status);
+        exported
+    }
+
+    // The repository's folder name, the same on every machine that has it checked out
+    fn repo_name(&self) -> String {
+        let dir = self.project_dir();
+        let root = repo_root(&dir).unwrap_or(dir);
+        std::fs::canonicalize(&root)
+            .unwrap_or(root)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Writes a handoff bundle of this session for another engineer to /import
+    pub fn write_handoff(&mut self) {
+        if self.messages.is_empty() {
+            self.status_message = Some("Nothing to hand off yet".to_string());
+            return;
+        }
+        let (scrubber, _) = Scrubber::new(&self.config.scrub_patterns());
+        let mut report = ScrubReport::default();
+        let handoff = Handoff::build(
+            &self.repo_name(),
+            &title_from_messages(&self.messages),
+            &self.messages,
+            &self.bookmarks,
+            &self.chatbot.pinned_files,
+            &scrubber,
+            &mut report,
+        );
+        self.status_message = Some(match write_handoff(&self.config.data_path(), &handoff) {
+            Ok(path) => format!(
+                "Wrote handoff to {}; /import it on the same repo; {}",
+                path.display(),
+                report.summary()
+            ),
+            Err(e) => format!("Handoff failed: {}", e),
+        });
+    }
+
+    /// Opens a handoff bundle as a new session: its messages, pinned files and context,
+    /// with the findings and open questions in the scratchpad for the next question
+    pub fn import_handoff(&mut self, path: &str) {
+        if path.is_empty() {
+            self.status_message = Some("Usage: /import <handoff file>".to_string());
+            return;
+        }
+        let handoff = match read_handoff(Path::new(path)) {
+            Ok(handoff) => handoff,
+            Err(e) => {
+                self.status_message = Some(e.to_string());
+                return;
+            }
+        };
+        // Files this checkout doesn't have indexed are left out, and pins of files the
+        // privacy policy keeps from the model
+        let indexed = |file: &&String| self.chatbot.indexed(file).is_some();
+        let pinned_files: Vec<String> = handoff
+            .pinned_files
+            .iter()
+            .filter(indexed)
+            .filter(|file| self.chatbot.privacy.level_for(file) != PrivacyLevel::Never)
+            .cloned()
+            .collect();
+        let unpinned = handoff.pinned_files.len() - pinned_files.len();
+        let context: Vec<(String, String)> = handoff
+            .context_files
+            .iter()
+            .filter(indexed)
+            .map(|file| (file.clone(), detect_language(file)))
+            .collect();
+        let missing = handoff.context_files.len() - context.len();
+
+        // The handoff's transcript is the whole session, without a repo map ahead of it
+        if !self.messages.is_empty() {
+            self.persist_session();
+        }
+        let origin = format!("handoff: {}", handoff.title);
+        let mut session = Session::new();
+        session.messages = handoff
+            .messages
+            .iter()
+            .cloned()
+            .map(|mut message| {
+                message.origin.get_or_insert_with(|| origin.clone());
+                message
+            })
+            .collect();
+        self.load_into_view(session);
+        self.chatbot.pinned_files = pinned_files;
+        self.chatbot.carry_over = CarryOver {
+            files: context,
+            turns: 0,
+        };
+        self.scratchpad = handoff.notes();
+        self.persist_session();
+        let mut status = format!(
+            "Imported handoff \"{}\" with {} open questions; its notes are in the scratchpad",
+            handoff.title,
+            handoff.open_questions.len()
+        );
+        if missing > 0 {
+            status.push_str(&format!("; {} context files aren't indexed here", missing));
+        }
+        if unpinned > 0 {
+            status.push_str(&format!(
+                "; dropped {} pinned files that aren't indexed here or are private",
+                unpinned
+            ));
+        }
+        if handoff.project != self.repo_name() {
+            status.push_str(&format!(
+                "; it was made on {}, not this repo",
+                handoff.project
+            ));
+        }
+        self.status_message = Some(status);
+    }
+
+    /// Formats the conversation, or the messages in `argument`, for printing to the
+    /// terminal's scrollback, scrubbed like a shared transcript
+    pub fn print_transcript(&mut self, argument: &str) {
+        if self.messages.is_empty() {
+            self.status_message = Some("Nothing to print yet".to_string());
+            return;
+        }
+        let range = match print_range(argument, self.messages.len()) {
+            Ok(range) => range,
+            Err(e) => {
+                self.status_message = Some(format!("{}; usage: /print [N|N-M]", e));
+                return;
+            }
+        };
+        let (scrubber, _) = Scrubber::new(&self.config.scrub_patterns());
+        let mut report = ScrubReport::default();
+        let count = range.len();
+        self.pending_print = Some(transcript_lines(
+            &self.messages,
+            range,
+            self.terminal_size.0 as usize,
+            &scrubber,
+            &mut report,
+        ));
+        self.status_message = Some(format!(
+            "Printed {} messages to the terminal; {}",
+            count,
+            report.summary()
+        ));
+    }
+
+    /// Writes sessions as Messages API records for eval datasets or reproductions,
+    /// scrubbed like a transcript export
+    pub fn export_dataset(&mut self, sessions: &[Session]) {
+        let (scrubber, invalid) = Scrubber::new(&self.config.scrub_patterns());
+        let system = self
+            .chatbot
+            .style
+            .system_prompt(self.chatbot.profile.system_prompt.to_string());
+        let mut report = ScrubReport::default();
+        let records: Vec<_> = sessions
+            .iter()
+            .filter(|session| !session.messages.is_empty())
+            .map(|session| {
+                session_record(
+                    session,
+                    self.chatbot.profile.model,
+                    &system,
+                    &scrubber,
+                    &mut report,
+                )
+            })
+            .collect();
+        if records.is_empty() {
+            self.status_message = Some("Nothing to export yet".to_string());
+            return;
+        }
+        let mut status = match export_dataset(&self.config.data_path(), &records) {
+            Ok(path) => format!(
+                "Exported {} sessions to {}; {}",
+                records.len(),
+                path.display(),
+                report.summary()
+            ),
+            Err(e) => format!("Dataset export failed: {}", e),
+        };
+        if !invalid.is_empty() {
+            status.push_str(&format!(
+                " (invalid scrub patterns skipped: {})",
+                invalid.join(", ")
+            ));
+        }
+        self.status_message = Some(status);
+    }
+
+    /// Marks or unmarks the highlighted session for a dataset export
+    pub fn toggle_export_selection(&mut self) {
+        let Some(session) = self.session_list.get(self.selected_session) else {
+            return;
+        };
+        match self
+            .export_selection
+            .iter()
+            .position(|id| id == &session.id)
+        {
+            Some(i) => {
+                self.export_selection.remove(i);
+            }
+            None => self.export_selection.push(session.id.clone()),
+        }
+    }
+
+    /// Exports the marked sessions, or the highlighted one when none are marked
+    pub fn export_selected_sessions(&mut self) {
+        let sessions: Vec<Session> = self
+            .session_list
+            .iter()
+            .enumerate()
+            .filter(|(i, session)| {
+                if self.export_selection.is_empty() {
+                    *i == self.selected_session
+                } else {
+                    self.export_selection.contains(&session.id)
+                }
+            })
+            .map(|(_, session)| session.clone())
+            .collect();
+        self.export_dataset(&sessions);
+        self.export_selection.clear();
+    }
+
+    pub fn move_suggestion(&mut self, delta: isize) {
+        let last = self.query_suggestions.len().saturating_sub(1);
+        self.selected_suggestion = self
+            .selected_suggestion
+            .saturating_add_signed(delta)
+            .min(last);
+    }
+
+    /// Takes the selected suggestion, pinning its file, and returns the question to resend
+    pub fn take_suggestion(&mut self) -> Option<String> {
+        let suggestion = self
+            .query_suggestions
+            .drain(..)
+            .nth(self.selected_suggestion)?;
+        if let Some(file) = suggestion.pin {
+            self.chatbot.pin_file(file);
+        }
+        Some(suggestion.query)
+    }
+
+    /// Prefixes a question with the pending quote, consuming it
+    pub fn with_reply_quote(&mut self, question: &str) -> String {
+        match self.reply_quote.take() {
+            Some(quote) => format!("{}\n\n{}", quote, question),
+            None => question.to_string(),
+        }
+    }
+
+    /// Switches the selected message between rendered markdown and its raw source
+    pub fn toggle_raw_markdown(&mut self) {
+        let Some(message) = self.selected_message.and_then(|i| self.messages.get_mut(i)) else {
+            return;
+        };
+        message.raw = !message.raw;
+        self.status_message = Some(if message.raw {
+            "Showing raw markdown".to_string()
+        } else {
+            "Showing rendered markdown".to_string()
+        });
+        self.persist_session();
+    }
+
+    /// Shows or hides the `cargo check` errors under the selected answer's Rust blocks
+    pub fn toggle_check_errors(&mut self) {
+        let Some(index) = self.selected_message else {
+            return;
+        };
+        let has_errors = self.messages[index]
+            .rust_checks
+            .iter()
+            .any(|check| matches!(check, RustCheck::Errors(_)));
+        if !has_errors {
+            self.status_message = Some("No compile errors in this message".to_string());
+            return;
+        }
+        self.expanded_check = match self.expanded_check {
+            Some(expanded) if expanded == index => None,
+            _ => Some(index),
+        };
+    }
+
+    /// Adds or removes a bookmark on the selected message
+    pub fn toggle_bookmark(&mut self) {
+        let Some(index) = self.selected_message else {
+            return;
+        };
+        if let Some(pos) = self.bookmarks.iter().position(|b| b.message_index == index) {
+            self.bookmarks.remove(pos);
+            self.status_message = Some("Bookmark removed".to_string());
+        } else {
+            self.bookmarks.push(Bookmark {
+                message_index: index,
+                annotation: String::new(),
+            });
+            self.bookmarks.sort_by_key(|b| b.message_index);
+            self.status_message = Some("Message bookmarked".to_string());
+        }
+        self.persist_session();
+    }
+
+    /// Starts editing the annotation of the selected message, bookmarking it if needed
+    pub fn begin_annotation(&mut self) {
+        let Some(index) = self.selected_message else {
+            return;
+        };
+        if self.bookmark_for(index).is_none() {
+            self.toggle_bookmark();
+        }
+        self.annotation_input = self
+            .bookmark_for(index)
+            .map(|b| b.annotation.clone())
+            .unwrap_or_default();
+        self.chat_focus = ChatFocus::Annotation;
+    }
+
+    pub fn commit_annotation(&mut self) {
+        let annotation = self.annotation_input.drain(..).collect::<String>();
+        if let Some(index) = self.selected_message {
+            if let Some(bookmark) = self.bookmarks.iter_mut().find(|b| b.message_index == index) {
+                bookmark.annotation = annotation.trim().to_string();
+            }
+        }
+        self.chat_focus = ChatFocus::Transcript;
+        self.persist_session();
+    }
+
+    pub fn open_clipboard_ring(&mut self) {
+        self.selected_clip = 0;
+        self.state = AppState::ClipboardRing;
+    }
+
+    /// Copies the highlighted snippet again, making it the newest
+    pub fn recopy_selected_clip(&mut self) {
+        let Some(text) = self.clipboard_ring.get(self.selected_clip).cloned() else {
+            return;
+        };
+        self.status_message = Some(match self.copy_to_clipboard(text) {
+            Ok(()) => "Copied to clipboard".to_string(),
+            Err(e) => format!("Failed to copy to clipboard: {}", e),
+        });
+        self.state = AppState::Chat;
+    }
+
+    /// Appends the highlighted snippet to the input box
+    pub fn insert_selected_clip(&mut self) {
+        if let Some(text) = self.clipboard_ring.get(self.selected_clip) {
+            self.input.push_str(text);
+            self.chat_focus = ChatFocus::Input;
+        }
+        self.state = AppState::Chat;
+    }
+
+    /// Jumps to the message of the bookmark highlighted in the jump list
+    pub fn jump_to_selected_bookmark(&mut self) {
+        if let Some(bookmark) = self.bookmarks.get(self.selected_bookmark) {
+            self.selected_message = Some(bookmark.message_index);
+            self.chat_focus = ChatFocus::Transcript;
+        }
+        self.state = AppState::Chat;
+    }
+
+    pub fn current_session(&self) -> Session {
+        Session {
+            id: self.session_id.clone(),
+            title: title_from_messages(&self.messages),
+            last_activity: chrono::Utc::now(),
+            cost: self.session_cost,
+            messages: self.messages.clone(),
+            bookmarks: self.bookmarks.clone(),
+            refactor_plan: self.refactor_plan.clone(),
+            index_snapshot: self.chatbot.file_mod_times.clone(),
+        }
+    }
+
+    pub fn persist_session(&mut self) {
+        if let Err(e) = save_session(&self.config.data_path(), &self.current_session()) {
+            self.status_message = Some(format!("Failed to save session: {}", e));
+        }
+    }
+
+    /// Opens the switcher with the previously active session preselected
+    pub fn open_session_switcher(&mut self) {
+        self.session_list = list_sessions(&self.config.data_path());
+        self.selected_session = match self.session_list.first() {
+            Some(first) if first.id == self.session_id && self.session_list.len() > 1 => 1,
+            _ => 0,
+        };
+        if self.state != AppState::SessionSwitcher {
+            self.state_before_switcher = self.state;
+        }
+        self.state = AppState::SessionSwitcher;
+    }
+
+    pub fn load_into_view(&mut self, session: Session) {
+        self.session_id = session.id;
+        self.session_cost = session.cost;
+        self.messages = session.messages;
+        // The model's side of the conversation follows the transcript, so nothing of the
+        // previous session reaches the next question
+        self.chatbot.memory = model_memory(&self.messages);
+        self.chatbot.compaction = HistoryCompaction::default();
+        self.chatbot.carry_over = CarryOver::default();
+        self.chatbot.pinned_files.clear();
+        self.chatbot.repo_map = self
+            .messages
+            .iter()
+            .find(|message| message.repo_map)
+            .map(|message| message.content.clone());
+        self.bookmarks = session.bookmarks;
+        self.refactor_plan = session.refactor_plan;
+        self.selected_plan_step = 0;
+        self.pending_edits.clear();
+        self.pending_step = None;
+        self.selected_message = None;
+        self.reply_quote = None;
+        self.selected_code_block = None;
+        self.expanded_check = None;
+        self.selected_bookmark = 0;
+        self.chat_focus = ChatFocus::Input;
+        self.state = AppState::Chat;
+    }
+
+    pub fn switch_to_selected_session(&mut self) {
+        let Some(session) = self.session_list.get(self.selected_session).cloned() else {
+            self.state = self.state_before_switcher;
+            return;
+        };
+        if session.id != self.session_id && !self.messages.is_empty() {
+            self.persist_session();
+        }
+        self.status_message = Some(format!("Switched to \"{}\"", session.title));
+        self.load_into_view(session);
+    }
+
+    /// Takes the project lock, or asks whether to attach read-only or take over when
+    /// another instance is running on this directory
+    pub fn check_instance_lock(&mut self) {
+        if self.state != AppState::MainMenu {
+            return;
+        }
+        match instance_lock::try_acquire(Path::new(".")) {
+            Ok(LockOutcome::Acquired) => {}
+            Ok(LockOutcome::HeldBy(holder)) => {
+                self.lock_holder = Some(holder);
+                self.state = AppState::LockConflict;
+            }
+            Err(e) => self.status_message = Some(format!("Failed to lock the project: {}", e)),
+        }
+    }
+
+    pub fn attach_read_only(&mut self) {
+        self.read_only = true;
+        self.state = AppState::MainMenu;
+        self.status_message = Some(
+            "Attached read-only: chat works, indexing is left to the other instance".to_string(),
+        );
+        self.check_for_draft();
+    }
+
+    pub fn take_over_lock(&mut self) {
+        match instance_lock::take_over(Path::new(".")) {
+            Ok(()) => {
+                self.read_only = false;
+                self.lock_holder = None;
+                self.status_message = Some("Took over the project lock".to_string());
+            }
+            Err(e) => {
+                self.read_only = true;
+                self.status_message = Some(format!("Takeover failed, attached read-only: {}", e));
+            }
+        }
+        self.state = AppState::MainMenu;
+        self.check_for_draft();
+    }
+
+    /// Keeps to persisted state for debugging startup: no indexing, project scans,
+    /// briefing or background refinement
+    pub fn enter_safe_mode(&mut self) {
+        self.safe_mode = true;
+        self.briefing = None;
+        self.chatbot.two_phase_answers = false;
+        self.status_message = Some(
+            "Safe mode: indexing and background tasks are off; restart without --safe-mode to resume"
+                .to_string(),
+        );
+    }
+
+    /// Refuses to write the shared index in safe mode, while read-only or after another
+    /// instance took the lock over
+    pub fn ensure_writable(&mut self) -> bool {
+        if self.safe_mode {
+            self.status_message =
+                Some("Safe mode: indexing is disabled in this instance".to_string());
+            return false;
+        }
+        if !self.read_only && !instance_lock::holds_lock(Path::new(".")) {
+            self.read_only = true;
+            self.lock_holder = instance_lock::lock_holder(Path::new("."));
+        }
+        if self.read_only {
+            self.status_message = Some(match &self.lock_holder {
+                Some(holder) => format!(
+                    "Read-only: instance {} owns this project's index, so indexing is disabled",
+                    holder.pid
+                ),
+                None => "Read-only: indexing is disabled in this instance".to_string(),
+            });
+        }
+        !self.read_only
+    }
+
+    /// Recomputes the main menu's project status once it is older than the refresh interval
+    /// Returns whether the status was refreshed and needs drawing
+    pub fn refresh_project_status(&mut self) -> bool {
+        let _span = profiler::span("project status");
+        if self.safe_mode {
+            return false;
+        }
+        if self
+            .project_status
+            .as_ref()
+            .is_some_and(|status| status.refreshed.elapsed() < STATUS_REFRESH_INTERVAL)
+        {
+            return false;
+        }
+        self.project_status = Some(project_status(&self.chatbot, &self.config.data_path()));
+        true
+    }
+
+    pub fn open_dialog(&mut self, mut dialog: Dialog) {
+        dialog.return_state = self.state;
+        self.dialog = Some(dialog);
+        self.state = AppState::Dialog;
+    }
+
+    /// Closes the open dialog, returning to the screen beneath it
+    pub fn close_dialog(&mut self) -> Option<Dialog> {
+        let dialog = self.dialog.take()?;
+        self.state = dialog.return_state;
+        Some(dialog)
+    }
+
+    /// Starts asking a question template, prompting for its first variable; a template
+    /// without variables is queued to send at once
+    pub fn start_template(&mut self, name: &str) {
+        let Some(template) = self.config.question_templates.get(name) else {
+            self.status_message = Some(format!(
+                "No question template named \"{}\"; /template lists them",
+                name
+            ));
+            return;
+        };
+        self.pending_template = Some(TemplateFill::new(name, template));
+        self.prompt_template_variable();
+    }
+
+    /// Records the value typed for the current template variable and moves to the next
+    pub fn fill_template_variable(&mut self, value: &str) {
+        if let Some(fill) = self.pending_template.as_mut() {
+            fill.fill(value);
+        }
+        self.prompt_template_variable();
+    }
+
+    fn prompt_template_variable(&mut self) {
+        let Some(fill) = &self.pending_template else {
+            return;
+        };
+        let Some(variable) = fill.next_variable() else {
+            self.queued_question = self.pending_template.take().map(|fill| fill.question());
+            return;
+        };
+        let completions = completions(
+            variable_kind(variable),
+            &self.chatbot.index,
+            &self.chatbot.keywords,
+        );
+        let dialog = Dialog::input(
+            &format!("Template: {}", fill.name),
+            format!("{}\n\n{}:", fill.question(), variable),
+            "",
+            DialogAction::FillTemplate,
+        )
+        .with_completions(completions);
+        self.open_dialog(dialog);
+    }
+
+    /// Offers the project's test command for review before it runs
+    pub fn confirm_run_tests(&mut self) {
+        let (message, command) = match test_command(&self.project_root) {
+            Some(command) => (
+                "This command will run in the project directory. Edit it if needed.",
+                command,
+            ),
+            None => ("No test runner was detected. Enter the command to run.", ""),
+        };
+        self.open_dialog(Dialog::input(
+            "Run Tests",
+            message.to_string(),
+            command,
+            DialogAction::RunShell,
+        ));
+    }
+
+    /// Starts a shell command in the project root; its output arrives in chat once it
+    /// finishes, with the latest line shown in the status bar meanwhile
+    pub fn run_shell_command(&mut self, command: &str) {
+        if let Some(task) = &self.running_task {
+            self.status_message = Some(format!("`{}` is still running", task));
+            return;
+        }
+        spawn_shell(&self.project_root, command, self.events.sender());
+        self.running_task = Some(command.to_string());
+        self.messages.push(Message::user(format!("$ {}", command)));
+        self.persist_session();
+        self.status_message = Some(format!("Running `{}`…", command));
+        self.chat_focus = ChatFocus::Input;
+        self.state = AppState::Chat;
+    }
+
+    /// Applies what background tasks reported since the last tick; returns whether
+    /// there was anything
+    pub fn drain_events(&mut self) -> bool {
+        let events = self.events.drain();
+        let changed = !events.is_empty();
+        for event in events {
+            match event {
+                AppEvent::LogLine(line) => {
+                    if let Some(task) = &self.running_task {
+                        self.status_message = Some(format!("{}: {}", task, line.trim()));
+                    }
+                }
+                AppEvent::TaskDone { task, result } => {
+                    self.running_task = None;
+                    self.messages
+                        .push(Message::ai(result.unwrap_or_else(|error| error)));
+                    self.persist_session();
+                    self.status_message = Some(format!("Finished `{}`", task));
+                }
+                AppEvent::Refined {
+                    question,
+                    quick_answer,
+                    answer,
+                    call,
+                } => {
+                    self.refinements_in_flight = self.refinements_in_flight.saturating_sub(1);
+                    self.post_refinement(&question, &quick_answer, answer, call)
+                }
+                AppEvent::RustChecked { answer, checks } => {
+                    self.rust_checks_in_flight = self.rust_checks_in_flight.saturating_sub(1);
+                    if let Some(message) = self
+                        .messages
+                        .iter_mut()
+                        .rev()
+                        .find(|m| m.content == answer && m.rust_checks.is_empty())
+                    {
+                        message.rust_checks = checks;
+                        self.persist_session();
+                    }
+                }
+                AppEvent::DigestWritten {
+                    date,
+                    result,
+                    spent,
+                } => {
+                    self.digest_in_flight = false;
+                    self.session_cost += spent;
+                    self.chatbot.audit_log.spent += spent;
+                    self.status_message = Some(match result {
+                        Ok(questions) => format!(
+                            "Wrote the digest of {} ({} questions); /digest {} shows it",
+                            date, questions, date
+                        ),
+                        Err(e) => format!("Failed to write the digest of {}: {}", date, e),
+                    });
+                }
+            }
+        }
+        changed
+    }
+
+    /// Whether something on screen is still moving: a request, a background task, or
+    /// a refinement, Rust check or digest that will post when it arrives
+    pub fn is_active(&self) -> bool {
+        self.busy
+            || self.running_task.is_some()
+            || self.refinements_in_flight > 0
+            || self.rust_checks_in_flight > 0
+            || self.digest_in_flight
+    }
+
+    // Posts the full-content answer under the quick one when it says something different,
+    // and lets the model's memory keep it instead
+    fn post_refinement(
+        &mut self,
+        question: &str,
+        quick_answer: &str,
+        answer: Result<String, String>,
+        call: Option<ApiCallLog>,
+    ) {
+        if let Some(call) = call {
+            self.session_cost += call.cost;
+            if let Err(e) = self.chatbot.audit_log.record(call) {
+                self.status_message = Some(format!("Failed to write audit log: {}", e));
+            }
+        }
+        let refined = match answer {
+            Ok(refined) => refined,
+            Err(e) => {
+                self.status_message = Some(format!("Couldn't refine the answer: {}", e));
+                return;
+            }
+        };
+        if !materially_differs(quick_answer, &refined) {
+            self.status_message = Some("The full files bear out the quick answer".to_string());
+            return;
+        }
+        if let Some(remembered) = self
+            .chatbot
+            .memory
+            .iter_mut()
+            .rev()
+            .find(|m| m.role == "assistant" && m.content == quick_answer)
+        {
+            remembered.content = refined.clone();
+        }
+        let quick = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.from_summaries && m.content == quick_answer);
+        let mut message = Message::ai(refined);
+        message.context_files = quick.map(|m| m.context_files.clone()).unwrap_or_default();
+        message.refined = true;
+        self.messages.push(message);
+        self.persist_session();
+        self.status_message = Some(format!("Posted a refined answer to \"{}\"", question));
+    }
+
+    pub fn confirm_clear_history(&mut self) {
+        if self.messages.is_empty() {
+            self.status_message = Some("The conversation is already empty".to_string());
+            return;
+        }
+        self.open_dialog(Dialog::choose(
+            "Clear History",
+            format!(
+                "Remove all {} messages of this conversation? This can't be undone.",
+                self.messages.len()
+            ),
+            &["Clear", "Export, then clear"],
+            DialogAction::ClearHistory,
+        ));
+    }
+
+    /// Empties the conversation and the model's memory of it, keeping the session
+    pub fn clear_history(&mut self) {
+        self.messages.clear();
+        self.bookmarks.clear();
+        self.chatbot.memory.clear();
+        self.chatbot.compaction = HistoryCompaction::default();
+        self.selected_message = None;
+        self.selected_code_block = None;
+        self.reply_quote = None;
+        self.persist_session();
+        self.status_message = Some("Cleared the conversation".to_string());
+    }
+
+    pub fn confirm_delete_session(&mut self) {
+        let Some(session) = self.session_list.get(self.selected_session) else {
+            return;
+        };
+        self.open_dialog(Dialog::confirm(
+            "Delete Session",
+            format!(
+                "Delete \"{}\" and its {} messages? This can't be undone.",
+                session.title,
+                session.messages.len()
+            ),
+            "Delete",
+            DialogAction::DeleteSession(session.id.clone()),
+        ));
+    }
+
+    pub fn delete_session(&mut self, id: &str) {
+        if let Err(e) = delete_session(&self.config.data_path(), id) {
+            self.status_message = Some(format!("Failed to delete session: {}", e));
+            return;
+        }
+        if id == self.session_id {
+            self.load_into_view(Session::new());
+            self.state = AppState::SessionSwitcher;
+        }
+        self.status_message = Some("Deleted the session".to_string());
+        self.open_session_switcher();
+    }
+
+    pub fn confirm_sweep(&mut self) {
+        let Some(sweep) = &self.sweep else {
+            return;
+        };
+        let count = sweep.selected_count();
+        if count == 0 {
+            self.status_message = Some("No files selected; Space selects a file".to_string());
+            return;
+        }
+        self.open_dialog(Dialog::confirm(
+            "Apply Sweep",
+            format!(
+                "Write the patches to {} files? Files are replaced all at once.",
+                count
+            ),
+            "Apply",
+            DialogAction::ApplySweep,
+        ));
+    }
+
+    pub fn confirm_take_over_lock(&mut self) {
+        let pid = self.lock_holder.as_ref().map_or(0, |holder| holder.pid);
+        self.open_dialog(Dialog::confirm(
+            "Take Over",
+            format!(
+                "Take the project lock from the instance with pid {}? It will be switched to read-only.",
+                pid
+            ),
+            "Take over",
+            DialogAction::TakeOverLock,
+        ));
+    }
+
+    /// Offers to restore an unsent draft left behind by a crash or an accidental quit
+    pub fn check_for_draft(&mut self) {
+        if self.state != AppState::MainMenu {
+            return;
+        }
+        if let Some(draft) = load_draft(&self.config.data_path()) {
+            if !draft.input.trim().is_empty() {
+                self.last_saved_draft = Some(draft.clone());
+                self.recovered_draft = Some(draft);
+                self.state = AppState::RestoreDraft;
+            }
+        }
+    }
+
+    /// Shows what changed since the last version used, once after each upgrade
+    pub fn check_whats_new(&mut self) {
+        if self.state != AppState::MainMenu {
+            return;
+        }
+        let last_seen = last_seen_version(&self.config.data_path());
+        if last_seen.as_deref() == Some(env!("CARGO_PKG_VERSION")) {
+            return;
+        }
+        let unseen = unseen_releases(CHANGELOG, last_seen.as_deref());
+        if !unseen.is_empty() {
+            self.open_whats_new(unseen);
+        }
+    }
+
+    /// Mentions files a git hook queued while this project was open elsewhere, which the
+    /// next index of the working directory picks up
+    pub fn check_reindex_queue(&mut self) {
+        let queued = load_reindex_queue(".").len();
+        if queued > 0 && self.status_message.is_none() {
+            self.status_message = Some(format!(
+                "{} files changed by git are queued for reindexing; Chat with CWD refreshes them",
+                queued
+            ));
+        }
+    }
+
+    /// With `daily_digest` on, writes the digest of the last day with questions when it
+    /// has none yet, in the background; `drain_events` reports how it went
+    pub fn write_missing_digest(&mut self) {
+        if !self.config.daily_digest || self.safe_mode {
+            return;
+        }
+        let data_dir = self.config.data_path();
+        let project = self.project_name();
+        let turns = load_turns(&data_dir, &project);
+        let today = chrono::Local::now().date_naive();
+        let Some(day) = undigested_day(&data_dir, &project, &turns, today) else {
+            return;
+        };
+        if let Some(policy) = &self.chatbot.org_policy {
+            if let Err(e) = policy.check_request(&self.chatbot, DEFAULT_MODEL) {
+                self.status_message = Some(format!("Failed to write the digest of {}: {}", day, e));
+                return;
+            }
+        }
+        // The audit file is append-only, so the task's own log can share it
+        let mut chatbot = Chatbot::new(
+            HashMap::new(),
+            HashMap::new(),
+            self.chatbot.api_key.clone(),
+            self.chatbot.client.clone(),
+        );
+        chatbot.audit_log = AuditLog::open(&data_dir);
+        chatbot.sampling = self.chatbot.sampling.clone();
+        self.digest_in_flight = true;
+        tokio::spawn(write_digest_in_background(
+            chatbot,
+            data_dir,
+            project,
+            day,
+            turns,
+            self.events.sender(),
+        ));
+    }
+
+    /// Opens the what's-new screen; from the main menu it lists every release
+    pub fn open_whats_new(&mut self, releases: Vec<Release>) {
+        self.whats_new = releases;
+        self.whats_new_scroll = 0;
+        self.state = AppState::WhatsNew;
+    }
+
+    pub fn open_full_changelog(&mut self) {
+        self.open_whats_new(releases(CHANGELOG));
+    }
+
+    /// Closes the what's-new screen so it isn't shown again for this version
+    pub fn dismiss_whats_new(&mut self) {
+        if let Err(e) = mark_seen(&self.config.data_path()) {
+            self.status_message = Some(format!("Could not record the version seen: {}", e));
+        }
+        self.state = AppState::MainMenu;
+    }
+
+    /// Reopens the draft's session with its input, selection and viewer position
+    pub fn restore_draft(&mut self) {
+        let Some(draft) = self.recovered_draft.take() else {
+            self.state = AppState::MainMenu;
+            return;
+        };
+        if draft.session_id != self.session_id {
+            match load_session(&self.config.data_path(), &draft.session_id) {
+                Some(session) => self.load_into_view(session),
+                None => {
+                    self.status_message = Some("The draft's session no longer exists".to_string())
+                }
+            }
+        }
+        self.state = AppState::Chat;
+        self.input = draft.input;
+        self.selected_message = draft.selected_message.filter(|&i| i < self.messages.len());
+        if let Some(path) = draft.viewer_path {
+            if let Ok(mut viewer) = FileViewer::open(&path) {
+                viewer.scroll_by(draft.viewer_scroll as isize);
+                self.file_viewer = Some(viewer);
+                self.split_view = true;
+            }
+        }
+        self.status_message = Some("Restored your unsent draft".to_string());
+    }
+
+    pub fn discard_draft(&mut self) {
+        self.recovered_draft = None;
+        self.last_saved_draft = None;
+        clear_draft(&self.config.data_path());
+        self.state = AppState::MainMenu;
+    }
+
+    /// Saves the draft every few seconds while it changes; `force` skips the interval
+    pub fn autosave_draft(&mut self, force: bool) {
+        let _span = profiler::span("draft autosave");
+        // Don't overwrite a draft that is still waiting to be restored
+        if self.state == AppState::RestoreDraft || self.state == AppState::StartupError {
+            return;
+        }
+        if !force && self.last_draft_save.elapsed() < DRAFT_SAVE_INTERVAL {
+            return;
+        }
+        self.last_draft_save = Instant::now();
+
+        let draft = Draft {
+            session_id: self.session_id.clone(),
+            input: self.input.clone(),
+            selected_message: self.selected_message,
+            viewer_path: self.file_viewer.as_ref().map(|v| v.path.clone()),
+            viewer_scroll: self.file_viewer.as_ref().map_or(0, |v| v.scroll),
+        };
+        if self.last_saved_draft.as_ref() == Some(&draft) {
+            return;
+        }
+        let data_dir = self.config.data_path();
+        if draft.input.trim().is_empty() {
+            clear_draft(&data_dir);
+        } else if let Err(e) = save_draft(&data_dir, &draft) {
+            self.status_message = Some(format!("Failed to save draft: {}", e));
+        }
+        self.last_saved_draft = Some(draft);
+    }
+
+    /// Merges the highlighted session with the current one into a new session
+    pub fn merge_selected_session(&mut self) {
+        let Some(other) = self.session_list.get(self.selected_session).cloned() else {
+            return;
+        };
+        if other.id == self.session_id {
+            self.status_message = Some("Pick a different session to merge with".to_string());
+            return;
+        }
+        self.persist_session();
+        let merged = merge_sessions(&self.current_session(), &other);
+        self.status_message = Some(format!(
+            "Merged \"{}\" into a new session of {} messages",
+            other.title,
+            merged.messages.len()
+        ));
+        self.load_into_view(merged);
+        self.persist_session();
+    }
+
+    pub fn start_new_session(&mut self) {
+        if !self.messages.is_empty() {
+            self.persist_session();
+        }
+        self.status_message = Some("Started a new session".to_string());
+        self.load_into_view(Session::new());
+        self.post_repo_map();
+    }
+
+    /// Opens an empty session with a map of the indexed repository, when `repo_map` is on
+    pub fn post_repo_map(&mut self) {
+        if !self.config.repo_map || !self.messages.is_empty() {
+            return;
         }
+        let Some(map) = repo_map(&self.chatbot.index, &self.project_dir()) else {
+            return;
+        };
+        let mut message = Message::ai(map.clone());
+        message.repo_map = true;
+        self.messages.push(message);
+        self.chatbot.repo_map = Some(map);
     }
 }
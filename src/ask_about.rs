@@ -0,0 +1,155 @@
+// src/ask_about.rs
+use chrono::Local;
+
+use crate::audit::ApiCallLog;
+use crate::ui::chat::blockquote;
+
+// Lines of a log entry or failure quoted into the question
+pub const MAX_ASK_ABOUT_LINES: usize = 40;
+// Lines that mark where a test run's failures begin
+const FAILURE_MARKERS: [&str; 6] = [
+    "failures:",
+    "panicked at",
+    "FAILED",
+    "FAIL ",
+    "error[",
+    "AssertionError",
+];
+
+/// Something picked on a screen, packaged as a quote and a question to start from
+pub struct AskAbout {
+    pub quote: String,
+    pub question: String,
+}
+
+/// An audit log entry with the fields that explain a slow, costly or failed call
+pub fn audit_entry(entry: &ApiCallLog) -> AskAbout {
+    let mut details = format!(
+        "{} {} ({})\nmodel: {}\nstatus: {}\ntime: {} ms\ntokens: {} in, {} out\ncost: ${:.4}",
+        entry
+            .timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S"),
+        entry.endpoint,
+        entry.request_summary,
+        entry.model,
+        entry.response_status,
+        entry.response_time_ms,
+        entry.input_tokens,
+        entry.output_tokens,
+        entry.cost
+    );
+    if entry.cache_read_tokens > 0 || entry.cache_write_tokens > 0 {
+        details.push_str(&format!(
+            "\ncache: {} read, {} written",
+            entry.cache_read_tokens, entry.cache_write_tokens
+        ));
+    }
+    if entry.reasoning_tokens > 0 {
+        details.push_str(&format!(
+            "\nreasoning: {} of the output",
+            entry.reasoning_tokens
+        ));
+    }
+    if entry.truncated {
+        details.push_str("\ntruncated: yes");
+    }
+    let question = if entry.response_status >= 400 {
+        "Why did this API call fail, and what should I change?"
+    } else {
+        "What does this API call log entry tell me about its cost and latency?"
+    };
+    AskAbout {
+        quote: blockquote(&details, MAX_ASK_ABOUT_LINES),
+        question: question.to_string(),
+    }
+}
+
+/// The failing part of a command's output as posted in chat ("`cmd` failed: ..."),
+/// or None when the command succeeded
+pub fn command_failure(output: &str) -> Option<AskAbout> {
+    let (heading, report) = output.split_once('\n')?;
+    if !heading.ends_with(" failed:") {
+        return None;
+    }
+    let lines: Vec<&str> = report.trim().lines().collect();
+    // From the first failure on, or the tail when no runner marker is found
+    let start = lines
+        .iter()
+        .position(|line| FAILURE_MARKERS.iter().any(|marker| line.contains(marker)))
+        .unwrap_or_else(|| lines.len().saturating_sub(MAX_ASK_ABOUT_LINES));
+    let excerpt = format!("{}\n{}", heading, lines[start..].join("\n"));
+    Some(AskAbout {
+        quote: blockquote(&excerpt, MAX_ASK_ABOUT_LINES),
+        question: "Why did this fail, and how do I fix it?".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_quoted_from_the_first_marker() {
+        let output = "`cargo test` failed:\n\n   Compiling app\nrunning 2 tests\ntest a ... ok\n\
+                      thread 'b' panicked at src/lib.rs:3:5\nassertion failed\n\nfailures:\n    b";
+        let ask = command_failure(output).unwrap();
+        assert!(ask
+            .quote
+            .starts_with("> `cargo test` failed:\n> thread 'b' panicked"));
+        assert!(!ask.quote.contains("Compiling"));
+        assert!(command_failure("`cargo test` succeeded:\n\nok").is_none());
+    }
+
+    #[test]
+    fn output_without_a_marker_is_quoted_from_its_tail() {
+        let lines: Vec<String> = (1..=100).map(|i| format!("line {}", i)).collect();
+        let output = format!("`make check` failed:\n{}", lines.join("\n"));
+        let ask = command_failure(&output).unwrap();
+        let quoted: Vec<&str> = ask.quote.lines().collect();
+        // The heading and the last lines, capped with a marker for the rest
+        assert_eq!(quoted[0], "> `make check` failed:");
+        assert_eq!(quoted[1], "> line 61");
+        assert_eq!(quoted.len(), MAX_ASK_ABOUT_LINES + 1);
+        assert_eq!(quoted.last(), Some(&"> …"));
+        assert!(command_failure("no heading at all").is_none());
+    }
+
+    #[test]
+    fn failed_calls_ask_why_and_list_only_the_usage_they_had() {
+        let mut entry = ApiCallLog {
+            timestamp: chrono::Utc::now(),
+            endpoint: "https://api.anthropic.com/v1/messages".to_string(),
+            request_summary: "Chat".to_string(),
+            model: "claude-3-5-haiku-20241022".to_string(),
+            response_status: 529,
+            response_time_ms: 1_200,
+            input_tokens: 1_000,
+            output_tokens: 0,
+            cost: 0.0008,
+            truncated: false,
+            cache_write_tokens: 0,
+            cache_read_tokens: 0,
+            reasoning_tokens: 0,
+        };
+        let ask = audit_entry(&entry);
+        assert_eq!(
+            ask.question,
+            "Why did this API call fail, and what should I change?"
+        );
+        assert!(ask
+            .quote
+            .contains("> status: 529\n> time: 1200 ms\n> tokens: 1000 in, 0 out\n> cost: $0.0008"));
+        assert!(!ask.quote.contains("cache:"));
+
+        entry.response_status = 200;
+        entry.cache_read_tokens = 800;
+        entry.reasoning_tokens = 50;
+        entry.truncated = true;
+        let ask = audit_entry(&entry);
+        assert!(ask.question.starts_with("What does this API call"));
+        assert!(ask.quote.ends_with(
+            "> cache: 800 read, 0 written\n> reasoning: 50 of the output\n> truncated: yes"
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+// src/audit.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::models::find_model;
+use crate::token_usage::{truncated, TokenUsage};
+
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+// Struct to log API calls
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiCallLog {
+    pub timestamp: DateTime<Utc>,
+    pub endpoint: String,
+    pub request_summary: String,
+    #[serde(default)]
+    pub model: String,
+    pub response_status: u16,
+    pub response_time_ms: u128,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cost: f64,
+    #[serde(default)]
+    pub truncated: bool,
+    // Prompt caching, billed separately from regular input tokens
+    #[serde(default)]
+    pub cache_write_tokens: u64,
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    // Thinking tokens, already counted in output_tokens
+    #[serde(default)]
+    pub reasoning_tokens: u64,
+}
+
+// Rates for models the catalogue doesn't list, such as other providers' models
+const FALLBACK_RATES: (f64, f64) = (3.0, 15.0);
+
+/// Price per million input and output tokens in USD, from the model's catalogue entry
+fn model_rates(model: &str) -> (f64, f64) {
+    find_model(model).map_or(FALLBACK_RATES, |info| (info.input_price, info.output_price))
+}
+
+// Cache writes cost a premium over regular input tokens, cache reads a fraction
+const CACHE_WRITE_MULTIPLIER: f64 = 1.25;
+const CACHE_READ_MULTIPLIER: f64 = 0.1;
+
+pub fn estimate_cost(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    let (input_rate, output_rate) = model_rates(model);
+    (input_tokens as f64 * input_rate + output_tokens as f64 * output_rate) / 1_000_000.0
+}
+
+pub fn estimate_cache_cost(model: &str, cache_write_tokens: u64, cache_read_tokens: u64) -> f64 {
+    let (input_rate, _) = model_rates(model);
+    (cache_write_tokens as f64 * input_rate * CACHE_WRITE_MULTIPLIER
+        + cache_read_tokens as f64 * input_rate * CACHE_READ_MULTIPLIER)
+        / 1_000_000.0
+}
+
+/// Cost of a call's normalized usage, cached input included
+pub fn estimate_usage_cost(model: &str, usage: &TokenUsage) -> f64 {
+    estimate_cost(model, usage.prompt_tokens, usage.completion_tokens)
+        + estimate_cache_cost(model, usage.cache_write_tokens, usage.cache_read_tokens)
+}
+
+/// Normalized token usage and truncation flag from any provider's response body
+pub fn usage_from_body(body: &Value) -> (TokenUsage, bool) {
+    (TokenUsage::from_body(body), truncated(body))
+}
+
+/// Append-only record of every outgoing API call, kept across runs
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    // Spend and tokens recorded since this log was opened
+    pub spent: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl AuditLog {
+    pub fn open(data_dir: &Path) -> Self {
+        AuditLog {
+            path: Some(data_dir.join(AUDIT_FILE)),
+            spent: 0.0,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    pub fn record(&mut self, entry: ApiCallLog) -> Result<(), Box<dyn std::error::Error>> {
+        self.spent += entry.cost;
+        self.input_tokens += entry.input_tokens;
+        self.output_tokens += entry.output_tokens;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+
+    /// Reads every recorded call, oldest first, skipping unreadable lines
+    pub fn entries(&self) -> Vec<ApiCallLog> {
+        let Some(path) = &self.path else {
+            return Vec::new();
+        };
+        fs::read_to_string(path)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn models_are_priced_by_exact_id() {
+        let million = 1_000_000;
+        assert_eq!(
+            estimate_cost("claude-3-haiku-20240307", million, million),
+            1.5
+        );
+        assert_eq!(
+            estimate_cost("claude-3-5-haiku-20241022", million, million),
+            4.8
+        );
+        assert_eq!(estimate_cost("claude-3-opus-20240229", million, 0), 15.0);
+        assert_eq!(estimate_cost("some-local-model", million, million), 18.0);
+    }
+}
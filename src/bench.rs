@@ -0,0 +1,279 @@
+// src/bench.rs
+//! `sagacity bench`: timings of the paths that decide how responsive the app feels, in
+//! the same shape on every machine, so builds can be compared for regressions. Nothing
+//! is sent to the API unless `--full` asks for retrieval as questions run it.
+use ratatui::{backend::TestBackend, Frame, Terminal};
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::app::{App, AppState};
+use crate::chatbot::{load_index_cache, prepare_context};
+use crate::content_cache::ContentCache;
+use crate::profiles::builtin_profiles;
+use crate::search::lexical_candidates;
+
+// Questions retrieval is timed on, general enough to match in any codebase
+pub const SAMPLE_QUERIES: [&str; 5] = [
+    "Where is the program's entry point?",
+    "How are errors handled and reported?",
+    "How is configuration loaded?",
+    "What does the main loop do?",
+    "How are the tests organized?",
+];
+// Terminal sizes frames are drawn at: a small split, a laptop, a wide monitor
+pub const FRAME_SIZES: [(u16, u16); 3] = [(80, 24), (120, 40), (200, 60)];
+const FRAME_RUNS: usize = 20;
+const ASSEMBLY_RUNS: usize = 5;
+// Local retrieval takes microseconds, so each query is repeated for a stable median
+const LEXICAL_RUNS: usize = 10;
+
+/// One benchmarked operation and the time each of its runs took
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: String,
+    pub runs: Vec<Duration>,
+}
+
+impl Measurement {
+    fn median(&self) -> Duration {
+        let mut runs = self.runs.clone();
+        runs.sort();
+        runs.get(runs.len() / 2).copied().unwrap_or_default()
+    }
+
+    fn max(&self) -> Duration {
+        self.runs.iter().max().copied().unwrap_or_default()
+    }
+}
+
+fn timed<T>(run: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let output = run();
+    (output, start.elapsed())
+}
+
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.2}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+/// The measurements as a table: runs, median and slowest run of each
+pub fn format_report(measurements: &[Measurement]) -> String {
+    let width = measurements
+        .iter()
+        .map(|m| m.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Benchmark".len());
+    let mut report = format!(
+        "{:<width$}  {:>5}  {:>10}  {:>10}\n",
+        "Benchmark",
+        "Runs",
+        "Median",
+        "Max",
+        width = width
+    );
+    for measurement in measurements {
+        report.push_str(&format!(
+            "{:<width$}  {:>5}  {:>10}  {:>10}\n",
+            measurement.name,
+            measurement.runs.len(),
+            format_duration(measurement.median()),
+            format_duration(measurement.max()),
+            width = width
+        ));
+    }
+    report
+}
+
+/// Times reading the index cache from disk; run before anything else in the process reads it
+pub fn cold_index_load() -> Result<Measurement, Box<dyn std::error::Error>> {
+    let (cache, elapsed) = timed(|| load_index_cache("."));
+    let files = cache?.map_or(0, |cache| cache.index.len());
+    Ok(Measurement {
+        name: format!("Cold index load ({} files)", files),
+        runs: vec![elapsed],
+    })
+}
+
+/// Times retrieval over the sample questions, prompt assembly for the largest context
+/// any profile builds, and frames drawn by `draw` at each of the sizes
+pub async fn run_bench(
+    app: &mut App,
+    full: bool,
+    draw: fn(&mut Frame<'_>, &App),
+) -> Result<Vec<Measurement>, Box<dyn std::error::Error>> {
+    let mut measurements = Vec::new();
+    let index = app.chatbot.retrieval_index();
+
+    // Retrieval, model-scored with --full, else the local candidate pass alone
+    let mut runs = Vec::new();
+    for query in SAMPLE_QUERIES {
+        if full {
+            let start = Instant::now();
+            app.chatbot.retrieve(query).await?;
+            runs.push(start.elapsed());
+        } else {
+            for _ in 0..LEXICAL_RUNS {
+                let chatbot = &app.chatbot;
+                let (_, elapsed) = timed(|| {
+                    lexical_candidates(&index, query, chatbot.relevance_candidates, |file| {
+                        chatbot.keywords_for(file)
+                    })
+                });
+                runs.push(elapsed);
+            }
+        }
+    }
+    measurements.push(Measurement {
+        name: format!(
+            "Retrieval, {} ({} questions)",
+            if full { "full" } else { "local candidates" },
+            SAMPLE_QUERIES.len()
+        ),
+        runs,
+    });
+
+    // The biggest indexed files, as many as the profile retrieving the most puts in context
+    let k = builtin_profiles()
+        .iter()
+        .map(|profile| profile.retrieval_k)
+        .max()
+        .unwrap_or(0);
+    let mut files: Vec<(String, String, u64)> = index
+        .iter()
+        .map(|(file, (_, language))| {
+            let size = fs::metadata(file).map_or(0, |metadata| metadata.len());
+            (file.clone(), language.clone(), size)
+        })
+        .collect();
+    files.sort_by_key(|(_, _, size)| std::cmp::Reverse(*size));
+    let largest: Vec<(String, String)> = files
+        .into_iter()
+        .take(k)
+        .map(|(file, language, _)| (file, language))
+        .collect();
+    let mut runs = Vec::new();
+    let mut context_chars = 0;
+    for _ in 0..ASSEMBLY_RUNS {
+        // A fresh cache each run, as for files read for the first time
+        let mut cache = ContentCache::default();
+        let (context, elapsed) = timed(|| {
+            prepare_context(
+                &largest,
+                SAMPLE_QUERIES[0],
+                &app.chatbot.deep_dives,
+                &index,
+                &app.chatbot.privacy,
+                &mut cache,
+            )
+        });
+        context_chars = context?.len();
+        runs.push(elapsed);
+    }
+    measurements.push(Measurement {
+        name: format!(
+            "Prompt assembly ({} largest files, {} chars)",
+            largest.len(),
+            context_chars
+        ),
+        runs,
+    });
+
+    // Frames of the chat screen with the last session's transcript
+    app.state = AppState::Chat;
+    for (width, height) in FRAME_SIZES {
+        let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+        app.on_resize(width, height);
+        let mut runs = Vec::new();
+        for _ in 0..FRAME_RUNS {
+            let start = Instant::now();
+            terminal.draw(|f| draw(f, app))?;
+            runs.push(start.elapsed());
+        }
+        measurements.push(Measurement {
+            name: format!(
+                "Frame render {}x{} ({} messages)",
+                width,
+                height,
+                app.messages.len()
+            ),
+            runs,
+        });
+    }
+    Ok(measurements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_report_lists_runs_median_and_max() {
+        let measurement = Measurement {
+            name: "Frame render 80x24 (0 messages)".to_string(),
+            runs: vec![
+                Duration::from_micros(900),
+                Duration::from_millis(3),
+                Duration::from_micros(1_500),
+            ],
+        };
+        let report = format_report(&[measurement]);
+        let row = report.lines().nth(1).unwrap();
+        assert!(row.starts_with("Frame render 80x24 (0 messages)"));
+        assert!(row.ends_with("    3      1.50ms      3.00ms"));
+    }
+
+    #[test]
+    fn medians_take_the_upper_middle_and_units_scale() {
+        let measurement = |micros: &[u64]| Measurement {
+            name: "x".to_string(),
+            runs: micros.iter().map(|&m| Duration::from_micros(m)).collect(),
+        };
+        assert_eq!(
+            measurement(&[4, 1, 3, 2]).median(),
+            Duration::from_micros(3)
+        );
+        assert_eq!(measurement(&[]).median(), Duration::ZERO);
+        assert_eq!(measurement(&[]).max(), Duration::ZERO);
+        assert_eq!(format_duration(Duration::from_micros(999)), "999µs");
+        assert_eq!(format_duration(Duration::from_micros(1_000)), "1.00ms");
+        assert_eq!(format_duration(Duration::from_millis(1_500)), "1.50s");
+        // Short names are padded to the header
+        let report = format_report(&[measurement(&[])]);
+        assert_eq!(
+            report.lines().nth(1).unwrap(),
+            format!("x{}", " ".repeat(8)) + "      0         0µs         0µs"
+        );
+    }
+
+    #[tokio::test]
+    async fn a_local_bench_times_every_stage_without_calling_the_api() {
+        let mut harness = crate::test_harness::Harness::new().await;
+        let measurements = run_bench(&mut harness.app, false, crate::ui).await.unwrap();
+        let names: Vec<&str> = measurements.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names[0], "Retrieval, local candidates (5 questions)");
+        assert!(names[1].starts_with("Prompt assembly (1 largest files, "));
+        assert_eq!(
+            &names[2..],
+            [
+                "Frame render 80x24 (0 messages)",
+                "Frame render 120x40 (0 messages)",
+                "Frame render 200x60 (0 messages)"
+            ]
+        );
+        assert_eq!(
+            measurements[0].runs.len(),
+            SAMPLE_QUERIES.len() * LEXICAL_RUNS
+        );
+        assert_eq!(measurements[1].runs.len(), ASSEMBLY_RUNS);
+        assert!(measurements[2..].iter().all(|m| m.runs.len() == FRAME_RUNS));
+        assert!(harness.server.received_requests().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+// src/branches.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const BRANCH_INDEXES_DIR: &str = "branch_indexes";
+pub const BRANCH_WORKTREES_DIR: &str = "worktrees";
+
+/// A local branch and, if it is checked out somewhere, its worktree
+#[derive(Debug, Clone)]
+pub struct BranchEntry {
+    pub name: String,
+    pub commit: String,
+    pub worktree: Option<PathBuf>,
+}
+
+/// The index of one (repository, branch) pair at a given commit
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BranchSnapshot {
+    pub branch: String,
+    pub commit: String,
+    pub root: String,
+    pub index: HashMap<String, (String, String)>,
+    pub file_mod_times: HashMap<String, u64>,
+    #[serde(default)]
+    pub keywords: HashMap<String, Vec<String>>,
+}
+
+pub fn git_output(dir: &Path, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn repo_root(dir: &Path) -> Option<PathBuf> {
+    git_output(dir, &["rev-parse", "--show-toplevel"])
+        .ok()
+        .map(PathBuf::from)
+}
+
+pub fn current_branch(dir: &Path) -> Option<String> {
+    git_output(dir, &["rev-parse", "--abbrev-ref", "HEAD"]).ok()
+}
+
+pub fn head_commit(dir: &Path) -> Option<String> {
+    git_output(dir, &["rev-parse", "HEAD"]).ok()
+}
+
+// Function to list local branches together with the worktrees they are checked out in
+pub fn list_branches(repo: &Path) -> Result<Vec<BranchEntry>, Box<dyn std::error::Error>> {
+    let mut worktrees: HashMap<String, PathBuf> = HashMap::new();
+    let porcelain = git_output(repo, &["worktree", "list", "--porcelain"])?;
+    let mut worktree_path: Option<PathBuf> = None;
+    for line in porcelain.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktree_path = Some(PathBuf::from(path));
+        } else if let Some(branch) = line.strip_prefix("branch refs/heads/") {
+            if let Some(path) = worktree_path.take() {
+                worktrees.insert(branch.to_string(), path);
+            }
+        }
+    }
+
+    let listing = git_output(repo, &["branch", "--format=%(refname:short) %(objectname)"])?;
+    Ok(listing
+        .lines()
+        .filter_map(|line| {
+            let (name, commit) = line.split_once(' ')?;
+            Some(BranchEntry {
+                name: name.to_string(),
+                commit: commit.to_string(),
+                worktree: worktrees.get(name).cloned(),
+            })
+        })
+        .collect())
+}
+
+/// Turns a path or branch name into a single safe file name component
+fn safe_name(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// Function to get a directory with the branch's code, adding a detached worktree if needed
+pub fn ensure_worktree(
+    repo: &Path,
+    entry: &BranchEntry,
+    data_dir: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(path) = &entry.worktree {
+        return Ok(path.clone());
+    }
+    let path = data_dir
+        .join(BRANCH_WORKTREES_DIR)
+        .join(safe_name(&repo.display().to_string()))
+        .join(safe_name(&entry.name));
+    let path_str = path.to_string_lossy().to_string();
+    if path.exists() {
+        // Move the cached worktree to the branch's current commit
+        git_output(&path, &["checkout", "--detach", &entry.commit])?;
+    } else {
+        fs::create_dir_all(path.parent().unwrap_or(data_dir))?;
+        git_output(
+            repo,
+            &["worktree", "add", "--detach", &path_str, &entry.commit],
+        )?;
+    }
+    Ok(path)
+}
+
+fn snapshot_path(data_dir: &Path, repo: &Path, branch: &str) -> PathBuf {
+    data_dir
+        .join(BRANCH_INDEXES_DIR)
+        .join(safe_name(&repo.display().to_string()))
+        .join(format!("{}.json", safe_name(branch)))
+}
+
+pub fn load_snapshot(data_dir: &Path, repo: &Path, branch: &str) -> Option<BranchSnapshot> {
+    let contents = fs::read_to_string(snapshot_path(data_dir, repo, branch)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn save_snapshot(
+    data_dir: &Path,
+    repo: &Path,
+    snapshot: &BranchSnapshot,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = snapshot_path(data_dir, repo, &snapshot.branch);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(snapshot)?)?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+// src/briefing.rs
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::project_status::modified_secs;
+use crate::session::Session;
+
+// Added and changed files described to the model; the rest are only counted
+const MAX_BRIEFED_FILES: usize = 25;
+
+/// How the project's files moved on since a session was last saved
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangesSince {
+    pub since: DateTime<Utc>,
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ChangesSince {
+    pub fn count(&self) -> usize {
+        self.added.len() + self.changed.len() + self.removed.len()
+    }
+}
+
+/// Compares the index and the files on disk with the session's snapshot; None when
+/// nothing changed, or the snapshot is missing or belongs to another project
+pub fn changes_since(
+    session: &Session,
+    file_mod_times: &HashMap<String, u64>,
+) -> Option<ChangesSince> {
+    let snapshot = &session.index_snapshot;
+    if !snapshot
+        .keys()
+        .any(|path| file_mod_times.contains_key(path))
+    {
+        return None;
+    }
+    let mut added: Vec<String> = file_mod_times
+        .keys()
+        .filter(|path| !snapshot.contains_key(*path))
+        .cloned()
+        .collect();
+    let mut changed = Vec::new();
+    let mut removed = Vec::new();
+    for (path, &then) in snapshot {
+        // The file on disk counts even when it hasn't been reindexed yet
+        match modified_secs(path) {
+            Some(now) if now.max(file_mod_times.get(path).copied().unwrap_or(0)) > then => {
+                changed.push(path.clone())
+            }
+            Some(_) => {}
+            None => removed.push(path.clone()),
+        }
+    }
+    added.sort();
+    changed.sort();
+    removed.sort();
+    let changes = ChangesSince {
+        since: session.last_activity,
+        added,
+        changed,
+        removed,
+    };
+    (changes.count() > 0).then_some(changes)
+}
+
+/// Prompt asking for a short re-onboarding briefing from the summaries of the files
+/// that moved on
+pub fn briefing_prompt(
+    changes: &ChangesSince,
+    index: &HashMap<String, (String, String)>,
+) -> String {
+    let describe = |path: &String| match index.get(path) {
+        Some((summary, _)) => format!("- {}: {}", path, summary),
+        None => format!("- {} (not indexed yet)", path),
+    };
+    let mut prompt = format!(
+        "The user is coming back to this codebase; their last session was on {}. Write a \
+         short briefing of what changed since then: group related changes, say what each \
+         group is likely about and what the user should look at first. Summaries of files \
+         that changed after the last index may be out of date.\n",
+        changes.since.format("%Y-%m-%d")
+    );
+    let briefed: Vec<&String> = changes
+        .added
+        .iter()
+        .chain(&changes.changed)
+        .take(MAX_BRIEFED_FILES)
+        .collect();
+    if !changes.added.is_empty() {
+        prompt.push_str("\nAdded files:\n");
+        for path in changes.added.iter().filter(|p| briefed.contains(p)) {
+            prompt.push_str(&describe(path));
+            prompt.push('\n');
+        }
+    }
+    if !changes.changed.is_empty() {
+        prompt.push_str("\nChanged files:\n");
+        for path in changes.changed.iter().filter(|p| briefed.contains(p)) {
+            prompt.push_str(&describe(path));
+            prompt.push('\n');
+        }
+    }
+    let left_out = changes.added.len() + changes.changed.len() - briefed.len();
+    if left_out > 0 {
+        prompt.push_str(&format!(
+            "\n({} more added or changed files not listed)\n",
+            left_out
+        ));
+    }
+    if !changes.removed.is_empty() {
+        prompt.push_str(&format!(
+            "\nRemoved files: {}\n",
+            changes.removed.join(", ")
+        ));
+    }
+    prompt
+}
@@ -0,0 +1,52 @@
+// src/carry_over.rs
+
+// Questions longer than this usually bring their own subject
+const FOLLOW_UP_MAX_WORDS: usize = 12;
+const FOLLOW_UP_OPENERS: &[&str] = &[
+    "what about",
+    "how about",
+    "and ",
+    "also",
+    "but ",
+    "so ",
+    "then",
+    "why",
+    "what else",
+];
+// Words that point back at whatever the previous answer was about
+const BACK_REFERENCES: &[&str] = &[
+    "there", "that", "this", "it", "its", "those", "these", "them", "they", "here", "same",
+];
+
+/// Context files of the last retrieval, reused by follow-up questions until they decay
+#[derive(Debug, Clone, Default)]
+pub struct CarryOver {
+    // Files with their languages, as retrieval returned them
+    pub files: Vec<(String, String)>,
+    // Follow-ups answered from these files so far
+    pub turns: usize,
+}
+
+/// Whether a question reads as a follow-up to the previous answer rather than a new
+/// topic: short, and opening like a follow-up or referring back without naming a file
+pub fn is_follow_up(query: &str) -> bool {
+    let lower = query.trim().to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() || words.len() > FOLLOW_UP_MAX_WORDS {
+        return false;
+    }
+    let names_a_file = lower.split_whitespace().any(|word| {
+        word.trim_end_matches(['?', '.', ',', '!'])
+            .contains(['.', '/'])
+    });
+    if names_a_file {
+        return false;
+    }
+    FOLLOW_UP_OPENERS
+        .iter()
+        .any(|opener| lower.starts_with(opener))
+        || words.iter().any(|word| BACK_REFERENCES.contains(word))
+}
@@ -0,0 +1,325 @@
+// src/change_ledger.rs
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::branches::git_output;
+use crate::usage::EXPORTS_DIR;
+
+pub const CHANGE_LEDGERS_DIR: &str = "change_ledgers";
+
+/// One file as it was before a change and as the change left it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    // None when the change created the file
+    pub before: Option<String>,
+    pub after: String,
+}
+
+impl FileChange {
+    /// Takes the file's current contents as `before`; call it before writing `after`
+    pub fn capture(path: &str, after: &str) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            before: fs::read_to_string(path).ok(),
+            after: after.to_string(),
+        }
+    }
+}
+
+/// Files written together by one accepted action
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AppliedChange {
+    pub applied_at: DateTime<Utc>,
+    // "edits", "tests" or "sweep"
+    pub source: String,
+    pub description: String,
+    pub files: Vec<FileChange>,
+}
+
+/// Every change a session wrote to the project, in the order they were applied
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ChangeLedger {
+    pub changes: Vec<AppliedChange>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+// Paths are kept absolute so they can be made relative to the repository on export
+fn absolute(path: &str) -> String {
+    fs::canonicalize(path)
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+impl ChangeLedger {
+    pub fn load(data_dir: &Path, session_id: &str) -> ChangeLedger {
+        let path = data_dir
+            .join(CHANGE_LEDGERS_DIR)
+            .join(format!("{}.json", session_id));
+        let mut ledger: ChangeLedger = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        ledger.path = path;
+        ledger
+    }
+
+    /// Appends a change whose files have been written
+    pub fn record(
+        &mut self,
+        source: &str,
+        description: &str,
+        files: Vec<FileChange>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.changes.push(AppliedChange {
+            applied_at: Utc::now(),
+            source: source.to_string(),
+            description: description.to_string(),
+            files: files
+                .into_iter()
+                .map(|file| FileChange {
+                    path: absolute(&file.path),
+                    ..file
+                })
+                .collect(),
+        });
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// One line per change: when, what and which files
+    pub fn summary(&self) -> String {
+        self.changes
+            .iter()
+            .enumerate()
+            .map(|(i, change)| {
+                format!(
+                    "{}. {} [{}] {} ({} files)",
+                    i + 1,
+                    change.applied_at.with_timezone(&Local).format("%H:%M"),
+                    change.source,
+                    change.description,
+                    change.files.len()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// All changes as one patch against the files before the first of them, with
+    /// paths relative to `root` so `git apply` takes it from there
+    pub fn combined_patch(&self, root: &Path) -> String {
+        let mut files: BTreeMap<String, (Option<String>, String)> = BTreeMap::new();
+        for change in &self.changes {
+            for file in &change.files {
+                files
+                    .entry(relative(root, &file.path))
+                    .and_modify(|(_, after)| *after = file.after.clone())
+                    .or_insert_with(|| (file.before.clone(), file.after.clone()));
+            }
+        }
+        let mut patch = String::new();
+        for (path, (before, after)) in files {
+            if before.as_deref() == Some(after.as_str()) {
+                continue;
+            }
+            // git apply only creates a file when the header says it is new
+            let (mode, original) = match before {
+                Some(_) => ("", format!("a/{}", path)),
+                None => ("new file mode 100644\n", "/dev/null".to_string()),
+            };
+            let body = diffy::create_patch(before.as_deref().unwrap_or(""), &after).to_string();
+            // Replace diffy's placeholder file names with the repository paths
+            let hunks = body.splitn(3, '\n').nth(2).unwrap_or("");
+            patch.push_str(&format!(
+                "diff --git a/{path} b/{path}\n{}--- {}\n+++ b/{path}\n{}",
+                mode,
+                original,
+                hunks,
+                path = path
+            ));
+        }
+        patch
+    }
+
+    /// Writes the combined patch to the exports folder
+    pub fn export_patch(
+        &self,
+        data_dir: &Path,
+        root: &Path,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let dir = data_dir.join(EXPORTS_DIR);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "changes-{}.patch",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        fs::write(&path, self.combined_patch(root))?;
+        Ok(path)
+    }
+
+    /// Creates `branch` off HEAD with one commit per change, built in a scratch
+    /// worktree so the working tree and the checked-out branch are left alone
+    pub fn export_branch(
+        &self,
+        repo: &Path,
+        branch: &str,
+        data_dir: &Path,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let worktree = data_dir
+            .join(CHANGE_LEDGERS_DIR)
+            .join(format!("worktree-{}", Local::now().format("%Y%m%d-%H%M%S")));
+        let worktree_str = worktree.to_string_lossy().to_string();
+        git_output(
+            repo,
+            &["worktree", "add", "-b", branch, &worktree_str, "HEAD"],
+        )?;
+        let result = self.commit_changes(repo, &worktree);
+        let _ = git_output(repo, &["worktree", "remove", "--force", &worktree_str]);
+        result
+    }
+
+    fn commit_changes(
+        &self,
+        repo: &Path,
+        worktree: &Path,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        for change in &self.changes {
+            for file in &change.files {
+                let target = worktree.join(relative(repo, &file.path));
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(target, &file.after)?;
+            }
+            git_output(worktree, &["add", "-A"])?;
+            let message = format!(
+                "{}\n\nApplied from Sagacity ({})",
+                change.description, change.source
+            );
+            git_output(worktree, &["commit", "--allow-empty", "-q", "-m", &message])?;
+        }
+        Ok(self.changes.len())
+    }
+}
+
+// A ledger path relative to the repository root, as git names it
+fn relative(root: &Path, path: &str) -> String {
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    Path::new(path)
+        .strip_prefix(&root)
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| path.trim_start_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combined_patch_spans_first_before_to_last_after() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        let file = root.join("src/lib.rs").display().to_string();
+        let mut ledger = ChangeLedger::load(&root.join("data"), "session-1");
+        ledger
+            .record(
+                "edits",
+                "Add a",
+                vec![FileChange {
+                    path: file.clone(),
+                    before: Some("fn a() {}\n".to_string()),
+                    after: "fn a() {}\nfn b() {}\n".to_string(),
+                }],
+            )
+            .unwrap();
+        ledger
+            .record(
+                "sweep",
+                "Rename b",
+                vec![FileChange {
+                    path: file,
+                    before: Some("fn a() {}\nfn b() {}\n".to_string()),
+                    after: "fn a() {}\nfn c() {}\n".to_string(),
+                }],
+            )
+            .unwrap();
+
+        let ledger = ChangeLedger::load(&root.join("data"), "session-1");
+        assert_eq!(ledger.changes.len(), 2);
+        let patch = ledger.combined_patch(&root);
+        assert!(patch.starts_with(
+            "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@"
+        ));
+        assert!(patch.contains("+fn c() {}"));
+        assert!(!patch.contains("fn b()"));
+    }
+
+    #[test]
+    fn patches_and_branches_apply_to_the_repository_and_leave_it_checked_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = fs::canonicalize(dir.path()).unwrap().join("repo");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        let git = |args: &[&str]| git_output(&repo, args).unwrap();
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        fs::write(repo.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        fs::write(repo.join("README.md"), "Demo\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "Initial"]);
+
+        let path = |file: &str| repo.join(file).display().to_string();
+        let mut ledger = ChangeLedger::load(&dir.path().join("data"), "s");
+        ledger
+            .record(
+                "edits",
+                "Add b and a test",
+                vec![
+                    FileChange::capture(&path("src/lib.rs"), "fn a() {}\nfn b() {}\n"),
+                    FileChange::capture(&path("tests/b.rs"), "#[test]\nfn b() {}\n"),
+                    FileChange::capture(&path("README.md"), "Edited\n"),
+                ],
+            )
+            .unwrap();
+        ledger
+            .record(
+                "sweep",
+                "Revert the README",
+                vec![FileChange::capture(&path("README.md"), "Demo\n")],
+            )
+            .unwrap();
+
+        let patch = ledger.combined_patch(&repo);
+        assert!(patch.contains("new file mode 100644\n--- /dev/null\n+++ b/tests/b.rs\n"));
+        // The README ends up as it started, so it isn't in the patch
+        assert!(!patch.contains("README.md"));
+        let patch_file = dir.path().join("changes.patch");
+        fs::write(&patch_file, &patch).unwrap();
+        git(&["apply", "--check", &patch_file.display().to_string()]);
+
+        assert_eq!(
+            ledger
+                .export_branch(&repo, "sagacity/s", &dir.path().join("data"))
+                .unwrap(),
+            2
+        );
+        assert_eq!(git(&["rev-parse", "--abbrev-ref", "HEAD"]), "main");
+        assert_eq!(
+            git(&["log", "--format=%s", "sagacity/s"]),
+            "Revert the README\nAdd b and a test\nInitial"
+        );
+        assert_eq!(
+            git(&["show", "sagacity/s:tests/b.rs"]),
+            "#[test]\nfn b() {}"
+        );
+        assert!(git(&["worktree", "list"]).lines().count() == 1);
+    }
+}
@@ -0,0 +1,225 @@
+// src/chaos.rs
+//! Failure injection for resilience testing: wraps the Messages API client and, at a
+//! configurable rate, fails requests, delays them or answers with malformed JSON.
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::claude_client::{ApiFuture, ApiResponse, ClaudeClient};
+
+// Developer settings, read once at startup
+pub const CHAOS_RATE_VAR: &str = "SAGACITY_CHAOS";
+pub const CHAOS_FAULTS_VAR: &str = "SAGACITY_CHAOS_FAULTS";
+pub const CHAOS_SEED_VAR: &str = "SAGACITY_CHAOS_SEED";
+const DEFAULT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    // An overloaded_error response with status 529
+    Failure,
+    // The real response, after a delay
+    Slow,
+    // Status 200 with a body cut off mid-object
+    Malformed,
+}
+
+impl Fault {
+    pub const ALL: [Fault; 3] = [Fault::Failure, Fault::Slow, Fault::Malformed];
+
+    pub fn parse(name: &str) -> Option<Fault> {
+        match name.trim() {
+            "failure" | "fail" => Some(Fault::Failure),
+            "slow" => Some(Fault::Slow),
+            "malformed" | "json" => Some(Fault::Malformed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    // Share of requests that get a fault, from 0 to 1
+    pub rate: f64,
+    pub faults: Vec<Fault>,
+    pub delay: Duration,
+    // Same seed, same faults on the same requests
+    pub seed: u64,
+}
+
+impl ChaosConfig {
+    /// Chaos settings from `SAGACITY_CHAOS=<rate>`, with the faults to draw from in
+    /// `SAGACITY_CHAOS_FAULTS=failure,slow,malformed` and a fixed `SAGACITY_CHAOS_SEED`;
+    /// None when the rate isn't set or is zero
+    pub fn from_env() -> Option<ChaosConfig> {
+        let rate: f64 = std::env::var(CHAOS_RATE_VAR).ok()?.trim().parse().ok()?;
+        if rate <= 0.0 {
+            return None;
+        }
+        let faults: Vec<Fault> = std::env::var(CHAOS_FAULTS_VAR)
+            .map(|names| names.split(',').filter_map(Fault::parse).collect())
+            .unwrap_or_default();
+        let seed = std::env::var(CHAOS_SEED_VAR)
+            .ok()
+            .and_then(|seed| seed.trim().parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(1, |elapsed| elapsed.as_nanos() as u64)
+            });
+        Some(ChaosConfig {
+            rate: rate.min(1.0),
+            faults: if faults.is_empty() {
+                Fault::ALL.to_vec()
+            } else {
+                faults
+            },
+            delay: DEFAULT_DELAY,
+            seed,
+        })
+    }
+}
+
+/// Passes requests on to another client, injecting faults at the configured rate
+pub struct ChaosClient {
+    inner: Arc<dyn ClaudeClient>,
+    config: ChaosConfig,
+    // xorshift state; never zero
+    state: Mutex<u64>,
+}
+
+impl ChaosClient {
+    pub fn new(inner: Arc<dyn ClaudeClient>, config: ChaosConfig) -> ChaosClient {
+        let state = Mutex::new(config.seed.max(1));
+        ChaosClient {
+            inner,
+            config,
+            state,
+        }
+    }
+
+    // A number in [0, 1)
+    fn roll(&self) -> f64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        (*state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn next_fault(&self) -> Option<Fault> {
+        if self.config.faults.is_empty() || self.roll() >= self.config.rate {
+            return None;
+        }
+        let pick = (self.roll() * self.config.faults.len() as f64) as usize;
+        self.config.faults.get(pick).copied()
+    }
+}
+
+impl ClaudeClient for ChaosClient {
+    fn endpoint(&self) -> String {
+        self.inner.endpoint()
+    }
+
+    fn post<'a>(&'a self, api_key: &'a str, request: &'a Value) -> ApiFuture<'a> {
+        let fault = self.next_fault();
+        Box::pin(async move {
+            match fault {
+                Some(Fault::Failure) => Ok(ApiResponse::complete(
+                    529,
+                    r#"{"type":"error","error":{"type":"overloaded_error","message":"Injected failure"}}"#,
+                )),
+                Some(Fault::Slow) => {
+                    tokio::time::sleep(self.config.delay).await;
+                    self.inner.post(api_key, request).await
+                }
+                // A streamed answer breaks off inside an event; a plain one inside the body
+                Some(Fault::Malformed) if request["stream"] == true => Ok(ApiResponse::complete(
+                    200,
+                    "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\n\n",
+                )),
+                Some(Fault::Malformed) => Ok(ApiResponse::complete(200, r#"{"content": [{"type": "te"#)),
+                None => self.inner.post(api_key, request).await,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claude_client::MockClient;
+    use serde_json::json;
+
+    fn chaos(rate: f64, faults: Vec<Fault>, seed: u64) -> (Arc<MockClient>, ChaosClient) {
+        let inner = Arc::new(MockClient::default());
+        let config = ChaosConfig {
+            rate,
+            faults,
+            delay: Duration::ZERO,
+            seed,
+        };
+        (inner.clone(), ChaosClient::new(inner, config))
+    }
+
+    #[tokio::test]
+    async fn injected_faults_never_reach_the_real_client() {
+        let (inner, client) = chaos(1.0, vec![Fault::Failure], 7);
+        let response = client.post("key", &json!({})).await.unwrap();
+        assert_eq!(response.status, 529);
+        assert!(response.text().await.unwrap().contains("overloaded_error"));
+
+        let (inner_malformed, client) = chaos(1.0, vec![Fault::Malformed], 7);
+        let plain = client.post("key", &json!({})).await.unwrap();
+        assert!(plain.json().await.is_err());
+        let streamed = client
+            .post("key", &json!({ "stream": true }))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(streamed.starts_with("event: content_block_delta"));
+        assert!(inner.requests.lock().unwrap().is_empty());
+        assert!(inner_malformed.requests.lock().unwrap().is_empty());
+
+        // Slow requests still get the real answer
+        let (inner, client) = chaos(1.0, vec![Fault::Slow], 7);
+        inner.respond(200, "{}");
+        assert_eq!(client.post("key", &json!({})).await.unwrap().status, 200);
+        assert_eq!(inner.requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn the_same_seed_injects_the_same_faults() {
+        let sequence = |seed| {
+            let (_, client) = chaos(0.5, Fault::ALL.to_vec(), seed);
+            (0..50).map(|_| client.next_fault()).collect::<Vec<_>>()
+        };
+        let first = sequence(42);
+        assert_eq!(first, sequence(42));
+        assert_ne!(first, sequence(43));
+        let faulted = first.iter().filter(|fault| fault.is_some()).count();
+        assert!((10..=40).contains(&faulted), "{} of 50 faulted", faulted);
+
+        // A zero seed would stick xorshift at zero
+        let (_, client) = chaos(0.5, Fault::ALL.to_vec(), 0);
+        assert_ne!(client.roll(), client.roll());
+    }
+
+    #[test]
+    fn fault_names_accept_their_short_forms() {
+        let parsed: Vec<Option<Fault>> = ["fail", " slow ", "json", "timeout"]
+            .into_iter()
+            .map(Fault::parse)
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Some(Fault::Failure),
+                Some(Fault::Slow),
+                Some(Fault::Malformed),
+                None
+            ]
+        );
+    }
+}
@@ -1,30 +1,71 @@
 // chatbot.rs
 
+use crate::answer_cache::{answer_key, AnswerCache};
+use crate::audit::{estimate_usage_cost, usage_from_body, ApiCallLog, AuditLog};
+use crate::carry_over::{is_follow_up, CarryOver};
+use crate::churn::ChurnStats;
+use crate::claude_client::ClaudeClient;
 use crate::constants::*;
+use crate::content_cache::ContentCache;
+use crate::deepdive::{load_deep_dives, save_deep_dives};
+use crate::doc_artifacts::{doc_summary_for, load_doc_summaries};
+use crate::duplicates::find_duplicates;
+use crate::embeddings::{semantic_scores, update_embeddings, EmbeddingConfig, EmbeddingStore};
+use crate::eviction::{detect_evictions, ContextEviction};
+use crate::failed_summaries::FailedSummaries;
+use crate::git_hooks::{clear_reindex_queue, load_reindex_queue};
+use crate::glossary::Glossary;
+use crate::heartbeat::{with_heartbeat, Heartbeat, SharedHeartbeat};
+use crate::history_compaction::{compact_history, HistoryCompaction};
+use crate::imports::direct_dependencies;
+use crate::index_progress::{
+    clear_index_progress, load_index_progress, save_index_progress, IndexProgress,
+};
+use crate::index_snapshot::{remove_snapshot, save_snapshot};
+use crate::index_walker::IndexWalker;
+use crate::model_routing::{is_simple_turn, LatencyTracker};
+use crate::models::{budget_request, estimate_tokens};
+use crate::open_files::boost_open_files;
+use crate::org_policy::OrgPolicy;
+use crate::privacy::{local_outline, PrivacyLevel, PrivacyPolicy};
+use crate::profiler;
+use crate::profiles::{default_profile, TaskProfile};
+use crate::projects::{self, ProjectIndex};
+use crate::prompts::{Prompt, PromptTemplates};
+use crate::refine::Refinement;
+use crate::rerank::{rerank, RERANK_CANDIDATES};
+use crate::response::{delta_text, describe_error, response_text};
+use crate::retrieval_memory::{cited_files, RetrievalMemory};
+use crate::sampling::{Operation, SamplingConfig};
+use crate::schema_drift::{event_drift, message_drift, SchemaDriftLog};
+use crate::search::lexical_candidates;
+use crate::shared_summaries::{content_hash, SharedSummaries, SharedSummary};
+use crate::structured::STRUCTURED_INSTRUCTIONS;
+use crate::style::AnswerStyle;
+use crate::summary_postprocess::{postprocess_summary, PostProcessConfig};
+use crate::todos::TodoTable;
+use crate::token_usage::TokenUsage;
+use crate::turn_latency::TurnLatency;
+use crate::usage::UsageMetrics;
+use crate::workspace::Workspace;
 use chrono::{DateTime, Utc};
-use reqwest;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-// Debug macro for easier logging
+// Debug lines go to the SAGACITY_DEBUG_LOG file, never to the terminal the TUI draws on
 macro_rules! debug_print {
     ($($arg:tt)*) => {
-        eprintln!("[DEBUG] {}", format!($($arg)*));
+        crate::debug_log::write("DEBUG", &format!($($arg)*));
     };
 }
 
-// Struct to log API calls
-#[derive(Debug)]
-pub struct ApiCallLog {
-    pub timestamp: DateTime<Utc>,
-    pub endpoint: String,
-    pub request_summary: String,
-    pub response_status: u16,
-    pub response_time_ms: u128,
-}
+// Returned when retrieval leaves no file to answer from
+pub const NO_RELEVANT_FILES: &str = "No relevant files found in the index for the given query.";
 
 // Struct for indexing cache
 #[derive(Serialize, Deserialize)]
@@ -33,6 +74,13 @@ pub struct IndexCache {
     pub last_modification: u64,
     pub index: HashMap<String, (String, String)>,
     pub file_mod_times: HashMap<String, u64>,
+    #[serde(default)]
+    pub keywords: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub churn: ChurnStats,
+    // Duplicate files, each mapped to the copy indexed in its place
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
 }
 
 // Struct for messages
@@ -44,6 +92,18 @@ pub struct Message {
     pub timestamp: DateTime<Utc>,
 }
 
+/// The API refused a request whose prompt exceeds the model's context window
+#[derive(Debug)]
+pub struct PromptTooLong(pub String);
+
+impl std::fmt::Display for PromptTooLong {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Claude API request failed: prompt too long - {}", self.0)
+    }
+}
+
+impl std::error::Error for PromptTooLong {}
+
 // Struct for conversation sessions
 pub struct ConversationSession {
     pub name: String,
@@ -53,13 +113,124 @@ pub struct ConversationSession {
 
 // Chatbot struct with API call logs and file modification times
 pub struct Chatbot {
-    pub index: HashMap<String, (String, String)>,
+    // Read as cheap snapshots; indexing builds a new map and swaps it in, so a
+    // retrieval in flight never sees a half-updated index
+    pub index: Arc<HashMap<String, (String, String)>>,
     pub api_key: String,
+    // Sends every Messages API request; tests substitute a mock
+    pub client: Arc<dyn ClaudeClient>,
     pub memory: Vec<Message>,
     pub sessions: Vec<ConversationSession>,
     pub current_session: Option<usize>,
-    pub api_call_logs: Vec<ApiCallLog>,
+    pub audit_log: AuditLog,
     pub file_mod_times: HashMap<String, u64>,
+    // Detailed per-file analyses, preferred over plain summaries in context
+    pub deep_dives: HashMap<String, String>,
+    // Learned boosts from files that answered past questions
+    pub retrieval_memory: RetrievalMemory,
+    pub last_context_files: Vec<String>,
+    // The part of the last context that retrieval chose, before pins and imports
+    pub last_retrieved: Vec<String>,
+    // Files imported by the retrieved ones, and the user's pinned additions
+    pub auto_include_imports: bool,
+    pub suggested_imports: Vec<String>,
+    pub pinned_files: Vec<String>,
+    // Summary clean-up applied during indexing, and the keywords it extracts
+    pub postprocess_config: PostProcessConfig,
+    pub keywords: HashMap<String, Vec<String>>,
+    // Files deleted or renamed since the previous index, if any
+    pub last_eviction: Option<ContextEviction>,
+    // Local-only usage counters
+    pub usage: UsageMetrics,
+    // Per-file limits on what may be sent to the API
+    pub privacy: PrivacyPolicy,
+    // Globs left out of indexing on top of the ignore files
+    pub index_exclude: Vec<String>,
+    // Summaries sent for relevance scoring after the lexical pre-filter; 0 sends all
+    pub relevance_candidates: usize,
+    // Optional self-hosted embeddings backend for semantic retrieval
+    pub embedding_config: Option<EmbeddingConfig>,
+    pub embeddings: EmbeddingStore,
+    // Active task profile, selected with /mode
+    pub profile: TaskProfile,
+    // Temperature and top_p per kind of request
+    pub sampling: SamplingConfig,
+    // Set when a prompt came close to the model's context window
+    pub context_warning: Option<String>,
+    // Project terms whose definitions join the system prompt when a question uses them
+    pub glossary: Glossary,
+    // The project's answer preferences from /style, appended to the system prompt
+    pub style: AnswerStyle,
+    // Per-turn time budget; `timed_out` is set when the last answer was cut off by it
+    pub time_budget: Option<Duration>,
+    pub turn_deadline: Option<tokio::time::Instant>,
+    pub timed_out: bool,
+    // Contents of files already sent as context, re-read only when they change on disk
+    pub content_cache: ContentCache,
+    // Ask for answers as JSON with a confidence and cited sources
+    pub structured_answers: bool,
+    // How often each file changes, and how many changed files one index pass re-summarizes
+    pub churn: ChurnStats,
+    pub summary_refresh_limit: Option<usize>,
+    // Summaries by content hash, shared by every project on this machine
+    pub shared_summaries: SharedSummaries,
+    // User-pasted text added to the next question's context only
+    pub scratchpad: Option<String>,
+    // Files of the last retrieval, reused by up to `carry_over_turns` follow-ups;
+    // `carried_over` is set when the last answer reused them
+    pub carry_over: CarryOver,
+    pub carry_over_turns: usize,
+    pub carried_over: bool,
+    // Org-wide model and spend limits every request is checked against
+    pub org_policy: Option<OrgPolicy>,
+    // Answer from summaries first; the full-content request is left in
+    // `pending_refinement` for the app to send in the background
+    pub two_phase_answers: bool,
+    pub pending_refinement: Option<Refinement>,
+    // Send simple turns to the model with the lowest observed latency; `routed_model`
+    // is set when the last answer came from it instead of the profile's model
+    pub fast_mode: bool,
+    pub latency: LatencyTracker,
+    pub routed_model: Option<String>,
+    // Input tokens of the last answer request and what they cost, for attributing to
+    // its context files
+    pub last_answer_input: Option<(u64, f64)>,
+    // Member crates when the project is a Cargo workspace; retrieval keeps to
+    // `crate_filter` when one is chosen with /crate
+    pub workspace: Option<Workspace>,
+    pub crate_filter: Option<String>,
+    // Answers by question, context, model and conversation; `skip_answer_cache` makes the next question
+    // ask the model anyway, and `served_from_cache` is set when the last answer was cached
+    pub answer_cache: AnswerCache,
+    pub skip_answer_cache: bool,
+    pub served_from_cache: bool,
+    // Prompt templates from the data directory, reread when edited
+    pub prompts: PromptTemplates,
+    // The repository map the session opened with, kept as a stable anchor for every turn
+    pub repo_map: Option<String>,
+    // Copies of other files left out of the index, each mapped to the file indexed instead
+    pub aliases: BTreeMap<String, String>,
+    // Registered projects chosen with /projects; retrieval spans their indexes too
+    pub projects: Vec<ProjectIndex>,
+    // TODO, FIXME and HACK comments of the indexed files, for /todos and the model
+    pub todos: TodoTable,
+    // Older turns sent as summaries rather than verbatim, when `compact_history` is on
+    pub compact_history: bool,
+    pub compaction: HistoryCompaction,
+    // Files open in the user's editor, from --open-files; ranked higher in retrieval
+    pub open_files: Vec<String>,
+    // Files whose summary failed; kept out of the index until a retry succeeds
+    pub failed_summaries: FailedSummaries,
+    // Where the current turn's time went, stage by stage
+    pub turn_latency: TurnLatency,
+    // Set while an answer is slow to arrive, for the streaming view's status
+    pub heartbeat: SharedHeartbeat,
+    // Profiles that re-rank their top candidates, overriding the profiles' own setting
+    pub rerank_profiles: Option<Vec<String>>,
+    // Response fields and block types this version doesn't know, logged as they appear
+    pub schema_drift: SchemaDriftLog,
+    // Root of the project the live index belongs to, whose cache it is saved to
+    pub index_root: String,
 }
 
 impl Chatbot {
@@ -67,18 +238,148 @@ impl Chatbot {
         index: HashMap<String, (String, String)>,
         file_mod_times: HashMap<String, u64>,
         api_key: String,
+        client: Arc<dyn ClaudeClient>,
     ) -> Self {
         Chatbot {
-            index,
+            index: Arc::new(index),
             api_key,
+            client,
             memory: Vec::new(),
             sessions: Vec::new(),
             current_session: None,
-            api_call_logs: Vec::new(),
+            audit_log: AuditLog::default(),
             file_mod_times,
+            deep_dives: load_deep_dives()
+                .into_iter()
+                .map(|(file, dive)| (file, dive.analysis))
+                .collect(),
+            retrieval_memory: RetrievalMemory::default(),
+            last_context_files: Vec::new(),
+            last_retrieved: Vec::new(),
+            auto_include_imports: false,
+            suggested_imports: Vec::new(),
+            pinned_files: Vec::new(),
+            postprocess_config: PostProcessConfig::default(),
+            keywords: HashMap::new(),
+            last_eviction: None,
+            usage: UsageMetrics::default(),
+            privacy: PrivacyPolicy::default(),
+            index_exclude: Vec::new(),
+            relevance_candidates: 0,
+            embedding_config: None,
+            embeddings: EmbeddingStore::default(),
+            profile: default_profile(),
+            sampling: SamplingConfig::default(),
+            context_warning: None,
+            glossary: Glossary::default(),
+            style: AnswerStyle::default(),
+            time_budget: None,
+            turn_deadline: None,
+            timed_out: false,
+            content_cache: ContentCache::default(),
+            structured_answers: false,
+            churn: ChurnStats::default(),
+            summary_refresh_limit: None,
+            shared_summaries: SharedSummaries::default(),
+            scratchpad: None,
+            carry_over: CarryOver::default(),
+            carry_over_turns: 0,
+            carried_over: false,
+            org_policy: None,
+            two_phase_answers: false,
+            pending_refinement: None,
+            fast_mode: false,
+            latency: LatencyTracker::default(),
+            routed_model: None,
+            last_answer_input: None,
+            workspace: None,
+            crate_filter: None,
+            answer_cache: AnswerCache::default(),
+            skip_answer_cache: false,
+            served_from_cache: false,
+            prompts: PromptTemplates::default(),
+            repo_map: None,
+            aliases: BTreeMap::new(),
+            projects: Vec::new(),
+            todos: TodoTable::default(),
+            compact_history: false,
+            compaction: HistoryCompaction::default(),
+            open_files: Vec::new(),
+            failed_summaries: FailedSummaries::default(),
+            turn_latency: TurnLatency::default(),
+            heartbeat: SharedHeartbeat::default(),
+            rerank_profiles: None,
+            schema_drift: SchemaDriftLog::default(),
+            index_root: ".".to_string(),
         }
     }
 
+    /// Replaces the live index with the cached one of the project at `root_dir`, or an
+    /// empty one, so indexing that project only summarizes what changed in it
+    pub fn use_index_cache(&mut self, root_dir: &str) {
+        match load_index_cache(root_dir) {
+            Ok(Some(cache)) => {
+                self.index = Arc::new(cache.index);
+                self.file_mod_times = cache.file_mod_times;
+                self.keywords = cache.keywords;
+                self.churn = cache.churn;
+                self.aliases = cache.aliases;
+            }
+            _ => {
+                self.index = Arc::default();
+                self.file_mod_times.clear();
+                self.keywords.clear();
+                self.churn = ChurnStats::default();
+                self.aliases.clear();
+            }
+        }
+        self.failed_summaries = FailedSummaries::load(root_dir);
+        self.content_cache.clear();
+        self.index_root = root_dir.to_string();
+    }
+
+    /// Removes or remaps every reference to files that were deleted or renamed
+    pub fn apply_eviction(&mut self, eviction: &ContextEviction) {
+        eviction.remap_all(&mut self.pinned_files);
+        eviction.remap_all(&mut self.suggested_imports);
+        eviction.remap_all(&mut self.last_context_files);
+        eviction.remap_all(&mut self.last_retrieved);
+
+        if eviction
+            .renamed
+            .iter()
+            .any(|(old, _)| self.deep_dives.contains_key(old))
+            || eviction
+                .removed
+                .iter()
+                .any(|old| self.deep_dives.contains_key(old))
+        {
+            let mut stored = load_deep_dives();
+            for (old, new) in &eviction.renamed {
+                if let Some(analysis) = self.deep_dives.remove(old) {
+                    self.deep_dives.insert(new.clone(), analysis);
+                }
+                if let Some(mut dive) = stored.remove(old) {
+                    dive.file_path = new.clone();
+                    stored.insert(new.clone(), dive);
+                }
+            }
+            for old in &eviction.removed {
+                self.deep_dives.remove(old);
+                stored.remove(old);
+            }
+            if let Err(e) = save_deep_dives(&stored) {
+                debug_print!("Failed to save deep dives after eviction: {}", e);
+            }
+        }
+
+        self.retrieval_memory.evict(eviction);
+        if let Err(e) = self.retrieval_memory.save() {
+            debug_print!("Failed to save retrieval memory after eviction: {}", e);
+        }
+        debug_print!("{}", eviction.notice());
+    }
+
     pub fn create_session(&mut self, name: String, index: HashMap<String, (String, String)>) {
         let session = ConversationSession {
             name,
@@ -89,44 +390,350 @@ impl Chatbot {
         self.current_session = Some(self.sessions.len() - 1);
     }
 
-    pub async fn chat(&mut self, user_query: &str) -> Result<String, Box<dyn std::error::Error>> {
-        debug_print!("Starting chat with system");
+    /// Replaces an earlier question in the conversation with a short placeholder, so the
+    /// parts of a split message don't pile up in every later request
+    pub fn elide_question(&mut self, question: &str, placeholder: &str) {
+        if let Some(message) = self
+            .memory
+            .iter_mut()
+            .rev()
+            .find(|m| m.role == "user" && m.content == question)
+        {
+            message.content = placeholder.to_string();
+        }
+    }
 
-        // Step 1: Find relevant files
-        let index_clone = self.index.clone();
-        let api_key_clone = self.api_key.clone();
-        let relevant_files = search_index(&index_clone, user_query, &api_key_clone, self).await?;
+    /// The conversation as sent with the next question, with older turns summarized
+    pub fn history(&self) -> Vec<Message> {
+        self.compaction.history(&self.memory)
+    }
 
-        // Step 2: Extract file paths and languages from relevant_files with proper handling
-        let relevant_file_info: Vec<(String, String)> = relevant_files
-            .into_iter()
-            .filter_map(|(file, _)| {
-                match self.index.get(&file) {
-                    Some((_, language)) => Some((file.clone(), language.clone())),
-                    None => {
-                        debug_print!("Warning: File '{}' not found in index.", file);
-                        None // Skip files not found in the index
+    /// Ranks indexed files for a query without answering it: semantic search when
+    /// embeddings are set up, model-scored summaries otherwise
+    pub async fn retrieve(
+        &mut self,
+        query: &str,
+    ) -> Result<Vec<(String, f32)>, Box<dyn std::error::Error>> {
+        let _span = profiler::span("retrieval");
+        let index = self.retrieval_index();
+        let api_key_clone = self.api_key.clone();
+        // Embeddings cover the current project alone, so questions spanning projects rank
+        // summaries
+        let ranked = match self.embedding_config.clone() {
+            Some(config) if !self.embeddings.is_empty() && self.projects.is_empty() => {
+                match search_embeddings(&config, query, self).await {
+                    Ok(files) => files,
+                    Err(e) => {
+                        debug_print!("Semantic search failed, falling back: {}", e);
+                        search_index(&index, query, &api_key_clone, self).await?
                     }
                 }
-            })
+            }
+            _ => search_index(&index, query, &api_key_clone, self).await?,
+        };
+        let mut ranked: Vec<(String, f32)> = ranked
+            .into_iter()
+            .filter(|(file, _)| self.in_crate_filter(file))
+            .collect();
+        if self.reranks() && ranked.len() > self.profile.retrieval_k {
+            match rerank(query, &ranked, self).await {
+                Ok(reranked) => ranked = reranked,
+                Err(e) => {
+                    debug_print!("Re-ranking failed, keeping the first ranking: {}", e);
+                }
+            }
+        }
+        ranked.truncate(self.profile.retrieval_k);
+        Ok(ranked)
+    }
+
+    /// Pins a file to the context; one the last answer went without counts against
+    /// retrieval in the usage metrics
+    pub fn pin_file(&mut self, file: String) {
+        if self.pinned_files.contains(&file) {
+            return;
+        }
+        if !self.last_context_files.is_empty() && !self.last_context_files.contains(&file) {
+            self.usage.record_added_file();
+        }
+        self.pinned_files.push(file);
+    }
+
+    /// Whether the active profile re-ranks its top candidates
+    pub fn reranks(&self) -> bool {
+        match &self.rerank_profiles {
+            Some(names) => names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(self.profile.name)),
+            None => self.profile.rerank,
+        }
+    }
+
+    /// How many candidates the first ranking keeps: enough to re-rank from, when the
+    /// profile re-ranks
+    pub fn retrieval_depth(&self) -> usize {
+        if self.reranks() {
+            RERANK_CANDIDATES.max(self.profile.retrieval_k)
+        } else {
+            self.profile.retrieval_k
+        }
+    }
+
+    /// The current index merged with those of the projects questions span; a snapshot
+    /// of the current index alone is shared rather than copied
+    pub fn retrieval_index(&self) -> Arc<HashMap<String, (String, String)>> {
+        if self.projects.is_empty() {
+            return Arc::clone(&self.index);
+        }
+        let mut index = (*self.index).clone();
+        for project in &self.projects {
+            for (file, entry) in &project.index {
+                index.entry(file.clone()).or_insert_with(|| entry.clone());
+            }
+        }
+        Arc::new(index)
+    }
+
+    /// The summary and language of a file in any of the indexes retrieval spans
+    pub fn indexed(&self, file: &str) -> Option<&(String, String)> {
+        self.index
+            .get(file)
+            .or_else(|| projects::project_of(&self.projects, file)?.index.get(file))
+    }
+
+    pub fn keywords_for(&self, file: &str) -> Option<&Vec<String>> {
+        self.keywords.get(file).or_else(|| {
+            projects::project_of(&self.projects, file)?
+                .keywords
+                .get(file)
+        })
+    }
+
+    /// Whether a file belongs to the crate retrieval is restricted to, if any
+    pub fn in_crate_filter(&self, file: &str) -> bool {
+        match (&self.workspace, &self.crate_filter) {
+            (Some(workspace), Some(name)) => workspace.crate_of(file) == Some(name.as_str()),
+            _ => true,
+        }
+    }
+
+    pub async fn chat(
+        &mut self,
+        user_query: &str,
+        on_delta: &mut dyn FnMut(&str),
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        debug_print!("Starting chat with system");
+        self.reload_prompts();
+        self.timed_out = false;
+        self.turn_latency = TurnLatency::default();
+        let retrieval_start = std::time::Instant::now();
+        self.turn_deadline = self
+            .time_budget
+            .map(|budget| tokio::time::Instant::now() + budget);
+
+        // A follow-up reuses the previous turn's files until they've served enough turns
+        self.carried_over = self.carry_over.turns < self.carry_over_turns
+            && !self.carry_over.files.is_empty()
+            && is_follow_up(user_query);
+        let mut relevant_file_info: Vec<(String, String)> = if self.carried_over {
+            self.carry_over.turns += 1;
+            self.carry_over
+                .files
+                .iter()
+                .filter(|(file, _)| self.indexed(file).is_some())
+                .cloned()
+                .collect()
+        } else {
+            // Step 1: Find relevant files
+            let relevant_files = self.retrieve(user_query).await?;
+
+            // Step 2: Extract file paths and languages from relevant_files with proper handling
+            let retrieved: Vec<(String, String)> = relevant_files
+                .into_iter()
+                .filter_map(|(file, _)| {
+                    match self.indexed(&file) {
+                        Some((_, language)) => Some((file.clone(), language.clone())),
+                        None => {
+                            debug_print!("Warning: File '{}' not found in index.", file);
+                            None // Skip files not found in the index
+                        }
+                    }
+                })
+                .collect();
+            self.carry_over = CarryOver {
+                files: retrieved.clone(),
+                turns: 0,
+            };
+            retrieved
+        };
+
+        self.last_retrieved = relevant_file_info
+            .iter()
+            .map(|(file, _)| file.clone())
             .collect();
 
+        // Pinned files are always part of the context
+        for pinned in &self.pinned_files {
+            if !relevant_file_info.iter().any(|(file, _)| file == pinned) {
+                relevant_file_info.push((pinned.clone(), detect_language(pinned)));
+            }
+        }
+
+        // Directly imported local modules are included or offered, per config
+        let imports = self.imported_files(&relevant_file_info);
+        if self.auto_include_imports {
+            relevant_file_info.extend(
+                imports
+                    .into_iter()
+                    .map(|file| (file.clone(), detect_language(&file))),
+            );
+            self.suggested_imports.clear();
+        } else {
+            self.suggested_imports = imports;
+        }
+
+        // Files marked "never" don't reach the model in any form
+        relevant_file_info.retain(|(file, _)| self.privacy.level_for(file) != PrivacyLevel::Never);
+
         // Check if we have any relevant files after filtering
         if relevant_file_info.is_empty() {
-            return Err("No relevant files found in the index for the given query.".into());
+            return Err(NO_RELEVANT_FILES.into());
         }
 
+        self.turn_latency.retrieval_ms = retrieval_start.elapsed().as_millis() as u64;
+
         // Step 3: Prepare context for the LLM
-        let context = prepare_context(&relevant_file_info, user_query)?;
+        let context_start = std::time::Instant::now();
+        let mut context = self.turn_context(&relevant_file_info, user_query)?;
+        self.turn_latency.context_ms = context_start.elapsed().as_millis() as u64;
+
+        // Step 4: Generate response using the LLM, unless the same question was answered
+        // from the same context before; from summaries first in two-phase mode
+        self.pending_refinement = None;
+        self.routed_model = None;
+        self.last_answer_input = None;
+        let cache_key = answer_key(
+            user_query,
+            &context,
+            self.profile.model,
+            &self.history(),
+            &answer_system_prompt(self, user_query),
+        );
+        let cached = if std::mem::take(&mut self.skip_answer_cache) {
+            None
+        } else {
+            self.answer_cache
+                .get(&cache_key)
+                .map(|cached| cached.answer.clone())
+        };
+        self.served_from_cache = cached.is_some();
+        let response = match cached {
+            Some(answer) => {
+                on_delta(&answer);
+                answer
+            }
+            None => {
+                let api_key_clone = self.api_key.clone();
+                // A failed summary leaves those turns verbatim until the next try
+                if self.compact_history {
+                    if let Err(e) = compact_history(self).await {
+                        debug_print!("Failed to compact the conversation history: {}", e);
+                    }
+                }
+                let memory_clone = self.history();
+                let mut quick_context = self.two_phase_answers.then(|| {
+                    prepare_summary_context(
+                        &relevant_file_info,
+                        user_query,
+                        &self.retrieval_index(),
+                    )
+                });
+                let mut retries = 0;
+                let mut dropped: Vec<String> = Vec::new();
+                let (response, _) = loop {
+                    let result = generate_llm_response(
+                        quick_context.as_deref().unwrap_or(&context),
+                        &api_key_clone,
+                        &memory_clone,
+                        user_query,
+                        self,
+                        on_delta,
+                    )
+                    .await;
+                    // Too long a prompt is retried without the least relevant files
+                    match result {
+                        Err(e) if e.is::<PromptTooLong>() && retries < PROMPT_TOO_LONG_RETRIES => {
+                            let removed =
+                                drop_least_relevant(&mut relevant_file_info, &self.pinned_files);
+                            if removed.is_empty() {
+                                return Err(e);
+                            }
+                            debug_print!(
+                                "Prompt too long; retrying without {}",
+                                removed.join(", ")
+                            );
+                            retries += 1;
+                            dropped.extend(removed);
+                            context = self.turn_context(&relevant_file_info, user_query)?;
+                            if quick_context.is_some() {
+                                quick_context = Some(prepare_summary_context(
+                                    &relevant_file_info,
+                                    user_query,
+                                    &self.retrieval_index(),
+                                ));
+                            }
+                        }
+                        result => break result?,
+                    }
+                };
+                if !dropped.is_empty() {
+                    self.context_warning = Some(format!(
+                        "Prompt was too long; answered without {}",
+                        dropped.join(", ")
+                    ));
+                }
+                // The quick answer stands on its own if the full request can't be made
+                if quick_context.is_some() {
+                    match answer_request(&context, &memory_clone, user_query, self) {
+                        Ok((model, request)) => {
+                            self.pending_refinement = Some(Refinement {
+                                question: user_query.to_string(),
+                                quick_answer: response.clone(),
+                                model,
+                                request,
+                                client: self.client.clone(),
+                                api_key: api_key_clone.clone(),
+                            })
+                        }
+                        Err(e) => {
+                            debug_print!("Not refining the answer: {}", e);
+                        }
+                    }
+                }
+                // Quick answers are superseded and cut-off ones incomplete, so neither is kept
+                if quick_context.is_none() && !self.timed_out {
+                    self.answer_cache.insert(cache_key, response.clone());
+                    if let Err(e) = self.answer_cache.save() {
+                        debug_print!("Failed to save answer cache: {}", e);
+                    }
+                }
+                response
+            }
+        };
 
-        // Step 4: Generate response using the LLM
-        let api_key_clone = self.api_key.clone();
-        let memory_clone = self.memory.clone();
-        let (response, _) =
-            generate_llm_response(&context, &api_key_clone, &memory_clone, user_query, self)
-                .await?;
+        // Step 5: Remember which context files the answer actually cited
+        let context_files: Vec<String> = relevant_file_info
+            .iter()
+            .map(|(file, _)| file.clone())
+            .collect();
+        let cited = cited_files(&response, &context_files);
+        self.retrieval_memory.record(user_query, &cited, 1.0);
+        if let Err(e) = self.retrieval_memory.save() {
+            debug_print!("Failed to save retrieval memory: {}", e);
+        }
+        self.last_context_files = context_files;
 
-        // Step 5: Update conversation history
+        // Step 6: Update conversation history
         self.memory.push(Message {
             role: "user".to_string(),
             content: user_query.to_string(),
@@ -140,10 +747,149 @@ impl Chatbot {
 
         Ok(response)
     }
+
+    // Picks up prompt templates edited since they were last read
+    fn reload_prompts(&mut self) {
+        for prompt in self.prompts.reload_changed() {
+            debug_print!("Reloaded prompt template {}", prompt.file_name());
+        }
+    }
+
+    // The context for a turn from its files, cut to the profile's budget, with any
+    // scratchpad notes
+    fn turn_context(
+        &mut self,
+        relevant_file_info: &[(String, String)],
+        user_query: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let _span = profiler::span("context");
+        let index = self.retrieval_index();
+        let mut context = prepare_context(
+            relevant_file_info,
+            user_query,
+            &self.deep_dives,
+            &index,
+            &self.privacy,
+            &mut self.content_cache,
+        )?;
+        if context.len() > self.profile.context_budget {
+            let mut end = self.profile.context_budget;
+            while !context.is_char_boundary(end) {
+                end -= 1;
+            }
+            context.truncate(end);
+            context.push_str("\n[context truncated to the profile's budget]");
+        }
+        // Known-issue comments go after it as well, so "what's unfinished here?" can be
+        // answered from them
+        let files: Vec<String> = relevant_file_info
+            .iter()
+            .map(|(file, _)| file.clone())
+            .filter(|file| self.privacy.allows_contents(file))
+            .collect();
+        if let Some(note) = self.todos.context_note(&files) {
+            context.push_str(&format!("\n\n{}\n", note));
+        }
+        // Pasted notes go after the budget cut so they always arrive whole
+        if let Some(notes) = &self.scratchpad {
+            context.push_str(&format!(
+                "\n\nScratchpad notes pasted by the user for this question:\n{}\n",
+                notes
+            ));
+        }
+        Ok(context)
+    }
+
+    // Function to collect local modules imported by the context files that aren't in it yet
+    fn imported_files(&self, context_files: &[(String, String)]) -> Vec<String> {
+        let mut imports: Vec<String> = Vec::new();
+        for (file, _) in context_files {
+            let Ok(content) = fs::read_to_string(file) else {
+                continue;
+            };
+            for dep in direct_dependencies(file, &content) {
+                let already_included = context_files.iter().any(|(f, _)| f == &dep);
+                if !already_included && !imports.contains(&dep) {
+                    imports.push(dep);
+                }
+            }
+        }
+        imports.truncate(MAX_IMPORT_EXPANSION);
+        imports
+    }
 }
 
 // Helper functions related to Chatbot
 
+// Function to record an API call in the persistent audit log
+fn log_api_call(
+    chatbot: &mut Chatbot,
+    request_summary: &str,
+    model: &str,
+    response_status: u16,
+    response_time_ms: u128,
+    body: Option<&Value>,
+) {
+    let entry = api_call_entry(
+        &chatbot.client.endpoint(),
+        request_summary,
+        model,
+        response_status,
+        response_time_ms,
+        body,
+    );
+    if let Err(e) = chatbot.audit_log.record(entry) {
+        debug_print!("Failed to write audit log: {}", e);
+    }
+    if let Some(body) = body {
+        chatbot.schema_drift.record(model, message_drift(body));
+    }
+}
+
+// The audit log entry of one call, for calls made away from the chatbot as well
+pub fn api_call_entry(
+    endpoint: &str,
+    request_summary: &str,
+    model: &str,
+    response_status: u16,
+    response_time_ms: u128,
+    body: Option<&Value>,
+) -> ApiCallLog {
+    let (usage, truncated) = body.map(usage_from_body).unwrap_or_default();
+    ApiCallLog {
+        timestamp: Utc::now(),
+        endpoint: endpoint.to_string(),
+        request_summary: request_summary.to_string(),
+        model: model.to_string(),
+        response_status,
+        response_time_ms,
+        input_tokens: usage.prompt_tokens,
+        output_tokens: usage.completion_tokens,
+        cost: estimate_usage_cost(model, &usage),
+        truncated,
+        cache_write_tokens: usage.cache_write_tokens,
+        cache_read_tokens: usage.cache_read_tokens,
+        reasoning_tokens: usage.reasoning_tokens,
+    }
+}
+
+// Clamps max_tokens to the model and the prompt's size, keeping any warning for the UI
+fn request_budget(
+    chatbot: &mut Chatbot,
+    model: &str,
+    prompt: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if let Some(policy) = &chatbot.org_policy {
+        policy.check_request(chatbot, model)?;
+    }
+    let budget = budget_request(model, estimate_tokens(prompt), DEFAULT_MAX_TOKENS)?;
+    if let Some(warning) = budget.warning {
+        debug_print!("{}", warning);
+        chatbot.context_warning = Some(warning);
+    }
+    Ok(budget.max_tokens)
+}
+
 // Function to summarize content with Claude API
 pub async fn summarize_with_claude(
     content: &str,
@@ -152,53 +898,50 @@ pub async fn summarize_with_claude(
     chatbot: &mut Chatbot,
 ) -> Result<String, Box<dyn std::error::Error>> {
     debug_print!("Summarizing content with Claude");
-    let client = reqwest::Client::new();
-    let prompt = format!(
-        "Provide a very concise summary (2-3 sentences max) of the following {} code, focusing on its main purpose and key functionalities:\n\n{}",
-        language, content
+    chatbot.reload_prompts();
+    let prompt = chatbot.prompts.render(
+        Prompt::Summarize,
+        &[("language", language), ("content", content)],
     );
 
+    let max_tokens = request_budget(chatbot, DEFAULT_MODEL, &prompt)?;
+    let mut request = json!({
+        "model": DEFAULT_MODEL,
+        "messages": [
+            {
+                "role": "user",
+                "content": prompt
+            }
+        ],
+        "max_tokens": max_tokens
+    });
+    chatbot
+        .sampling
+        .for_operation(Operation::Summarize)
+        .apply(&mut request);
     let start_time = std::time::Instant::now();
 
-    let response = client
-        .post(CLAUDE_API_URL)
-        .header("Content-Type", "application/json")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", ANTHROPIC_VERSION)
-        .json(&json!({
-            "model": DEFAULT_MODEL,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ],
-            "max_tokens": DEFAULT_MAX_TOKENS
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Claude API: {}", e))?;
+    let response = chatbot.client.post(api_key, &request).await?;
 
     let elapsed_time = start_time.elapsed().as_millis();
 
-    // Log the API call
-    chatbot.api_call_logs.push(ApiCallLog {
-        timestamp: Utc::now(),
-        endpoint: CLAUDE_API_URL.to_string(),
-        request_summary: "summarize_with_claude".to_string(),
-        response_status: response.status().as_u16(),
-        response_time_ms: elapsed_time,
-    });
-
-    debug_print!("Response status: {}", response.status());
+    debug_print!("Response status: {}", response.status);
 
-    let status = response.status();
-    if !status.is_success() {
+    let status = response.status;
+    if !response.is_success() {
         let error_body = response
             .text()
             .await
             .map_err(|e| format!("Failed to read error response body: {}", e))?;
         debug_print!("Error response body: {}", error_body);
+        log_api_call(
+            chatbot,
+            "summarize_with_claude",
+            DEFAULT_MODEL,
+            status,
+            elapsed_time,
+            None,
+        );
         return Err(format!("Claude API request failed: {} - {}", status, error_body).into());
     }
 
@@ -206,17 +949,21 @@ pub async fn summarize_with_claude(
         .json()
         .await
         .map_err(|e| format!("Failed to parse JSON response: {}", e))?;
+    log_api_call(
+        chatbot,
+        "summarize_with_claude",
+        DEFAULT_MODEL,
+        status,
+        elapsed_time,
+        Some(&body),
+    );
 
     debug_print!(
         "Response body: {}",
         serde_json::to_string_pretty(&body).unwrap()
     );
 
-    let summary = body["content"][0]["text"]
-        .as_str()
-        .ok_or("Missing 'text' field in API response")?
-        .trim()
-        .to_string();
+    let summary = response_text_logged(&body)?;
     if summary.is_empty() {
         return Err("Empty summary received from Claude API".into());
     }
@@ -224,9 +971,88 @@ pub async fn summarize_with_claude(
     Ok(summary)
 }
 
+// Function to send a single-prompt request to Claude with an explicit model
+pub async fn complete_with_claude(
+    prompt: &str,
+    model: &str,
+    api_key: &str,
+    request_summary: &str,
+    operation: Operation,
+    chatbot: &mut Chatbot,
+) -> Result<String, Box<dyn std::error::Error>> {
+    debug_print!("Sending {} request to {}", request_summary, model);
+    let max_tokens = request_budget(chatbot, model, prompt)?;
+    let mut request = json!({
+        "model": model,
+        "messages": [
+            {
+                "role": "user",
+                "content": prompt
+            }
+        ],
+        "max_tokens": max_tokens
+    });
+    chatbot
+        .sampling
+        .for_operation(operation)
+        .apply(&mut request);
+    let start_time = std::time::Instant::now();
+
+    let response = chatbot.client.post(api_key, &request).await?;
+
+    let elapsed_time = start_time.elapsed().as_millis();
+
+    let status = response.status;
+    if !response.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read error response body: {}", e))?;
+        debug_print!("Error response body: {}", error_body);
+        log_api_call(chatbot, request_summary, model, status, elapsed_time, None);
+        return Err(format!("Claude API request failed: {} - {}", status, error_body).into());
+    }
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse JSON response: {}", e))?;
+    log_api_call(
+        chatbot,
+        request_summary,
+        model,
+        status,
+        elapsed_time,
+        Some(&body),
+    );
+
+    let text = response_text_logged(&body)?;
+    if text.is_empty() {
+        return Err("Empty response received from Claude API".into());
+    }
+    Ok(text)
+}
+
+// Text of a response body, trimmed, logging the content blocks that were skipped
+fn response_text_logged(body: &Value) -> Result<String, Box<dyn std::error::Error>> {
+    let response = response_text(body)?;
+    if !response.skipped.is_empty() {
+        debug_print!("Skipped content blocks: {}", response.skipped.join(", "));
+    }
+    Ok(response.text.trim().to_string())
+}
+
+pub const INDEX_CACHE_FILE: &str = "index_cache.json";
+
+/// Where the index of the project at `root_dir` is cached: at its root, so indexing one
+/// project never overwrites another's
+pub fn index_cache_path(root_dir: &str) -> PathBuf {
+    Path::new(root_dir).join(INDEX_CACHE_FILE)
+}
+
 // Function to load index cache
-pub fn load_index_cache() -> Result<Option<IndexCache>, Box<dyn std::error::Error>> {
-    if let Ok(contents) = fs::read_to_string("index_cache.json") {
+pub fn load_index_cache(root_dir: &str) -> Result<Option<IndexCache>, Box<dyn std::error::Error>> {
+    if let Ok(contents) = fs::read_to_string(index_cache_path(root_dir)) {
         let cache: IndexCache = serde_json::from_str(&contents)?;
         debug_print!("Index cache loaded successfully.");
         Ok(Some(cache))
@@ -238,18 +1064,25 @@ pub fn load_index_cache() -> Result<Option<IndexCache>, Box<dyn std::error::Erro
 
 // Function to save index cache
 pub fn save_index_cache(
+    root_dir: &str,
     index: &HashMap<String, (String, String)>,
     last_modification: u64,
     file_mod_times: &HashMap<String, u64>,
+    keywords: &HashMap<String, Vec<String>>,
+    churn: &ChurnStats,
+    aliases: &BTreeMap<String, String>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let cache = IndexCache {
         timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
         last_modification,
         index: index.clone(),
         file_mod_times: file_mod_times.clone(),
+        keywords: keywords.clone(),
+        churn: churn.clone(),
+        aliases: aliases.clone(),
     };
     let serialized = serde_json::to_string_pretty(&cache)?;
-    fs::write("index_cache.json", serialized)?;
+    fs::write(index_cache_path(root_dir), serialized)?;
     debug_print!("Index cache saved successfully.");
     Ok(())
 }
@@ -260,80 +1093,194 @@ pub async fn index_codebase(
     api_key: &str,
     pb: &indicatif::ProgressBar,
     chatbot: &mut Chatbot,
+    on_progress: &mut dyn FnMut(&IndexProgress),
 ) -> Result<
     (HashMap<String, (String, String)>, u64, HashMap<String, u64>),
     Box<dyn std::error::Error>,
 > {
-    let mut index = chatbot.index.clone();
+    // Built apart from the live index, which callers replace with the result
+    let mut index = (*chatbot.index).clone();
     let mut file_mod_times = chatbot.file_mod_times.clone();
-
-    let walker = ignore::WalkBuilder::new(root_dir)
-        .hidden(false)
-        .ignore(false)
-        .git_ignore(true)
-        .git_global(false)
-        .git_exclude(false)
-        .build();
-
-    let files: Vec<String> = walker
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().map_or(false, |ft| ft.is_file()))
-        .filter(|entry| {
-            let extension = entry.path().extension().and_then(|e| e.to_str());
-            matches!(
-                extension,
-                Some("rs") | Some("toml") | Some("md") | Some("py") | Some("go")
-            )
-        })
-        .map(|entry| entry.path().to_string_lossy().to_string())
+    let mut keywords = chatbot.keywords.clone();
+    let previously_indexed: HashSet<String> = index.keys().cloned().collect();
+
+    let mut files: Vec<String> = IndexWalker::new(root_dir)
+        .exclude(&chatbot.index_exclude)
+        .files()?
+        .into_iter()
+        // Files marked "never" are not even summarized
+        .filter(|file_path| chatbot.privacy.level_for(file_path) != PrivacyLevel::Never)
         .collect();
 
+    // Copies of other files are aliased to them instead of being summarized and retrieved
+    chatbot.aliases = find_duplicates(&files);
+    files.retain(|file_path| !chatbot.aliases.contains_key(file_path));
+    if !chatbot.aliases.is_empty() {
+        debug_print!("Aliased {} duplicate files", chatbot.aliases.len());
+    }
+
+    // Busy files are re-summarized first, so an interrupted or capped pass covers them
+    files.sort_by_key(|file_path| std::cmp::Reverse(chatbot.churn.score(file_path)));
     pb.set_length(files.len() as u64);
 
+    // Keep the timing history of an interrupted run of the same root for a better ETA
+    let mut progress = match load_index_progress(root_dir) {
+        Some(previous) if previous.root == root_dir => IndexProgress {
+            total: files.len(),
+            done: 0,
+            ..previous
+        },
+        _ => IndexProgress::new(root_dir, files.len()),
+    };
+
     let mut last_modification = 0;
     let mut files_set = HashSet::new();
+    let mut summarized = 0;
+    let mut refreshed = 0;
+    let mut reused = 0;
+    let mut from_docs = 0;
+    let mut deferred = 0;
+    // Files documented by rustdoc or TypeDoc output are summarized from those docs
+    let doc_summaries = load_doc_summaries(root_dir);
+    // Files a git hook queued after a commit or checkout, whatever their modification time
+    let queued = if root_dir == "." {
+        load_reindex_queue(root_dir)
+    } else {
+        Default::default()
+    };
 
     for (i, file_path) in files.iter().enumerate() {
+        progress.done = i;
+        progress.current_file = file_path.clone();
         pb.set_message(format!(
-            "Processing file {}/{}: {}",
+            "Processing file {}/{}: {} ({})",
             i + 1,
             files.len(),
-            file_path
+            file_path,
+            progress.summary()
         ));
+        on_progress(&progress);
 
         // Get the last modification time of the file
-        let metadata = fs::metadata(&file_path)?;
+        let metadata = fs::metadata(file_path)?;
         let modified = metadata.modified()?;
         let modified_secs = modified.duration_since(UNIX_EPOCH)?.as_secs();
         last_modification = std::cmp::max(last_modification, modified_secs);
 
         files_set.insert(file_path.clone());
+        if chatbot.privacy.allows_contents(file_path) {
+            chatbot.todos.refresh(file_path, modified_secs);
+        }
 
         // Check if the file has been modified since last indexing
-        let needs_reindex = match file_mod_times.get(file_path) {
-            Some(&cached_mod_time) => modified_secs > cached_mod_time,
-            None => true, // New file
-        };
+        let needs_reindex = queued.contains(file_path)
+            || chatbot.failed_summaries.contains(file_path)
+            || match file_mod_times.get(file_path) {
+                Some(&cached_mod_time) => modified_secs > cached_mod_time,
+                None => true, // New file
+            };
 
-        if needs_reindex {
+        // Past the refresh limit, changed files keep their old summary until the next pass
+        let previously_summarized = file_mod_times.contains_key(file_path);
+        if needs_reindex
+            && previously_summarized
+            && chatbot
+                .summary_refresh_limit
+                .is_some_and(|limit| refreshed >= limit)
+        {
+            debug_print!("Deferring re-summary of less active file: {}", file_path);
+            deferred += 1;
+        } else if needs_reindex {
             debug_print!("Re-indexing file: {}", file_path);
-            let content = fs::read_to_string(&file_path)
+            if previously_summarized {
+                chatbot.churn.record(file_path, modified_secs);
+                refreshed += 1;
+            }
+            chatbot.content_cache.invalidate(file_path);
+            let file_start = std::time::Instant::now();
+            let content = fs::read_to_string(file_path)
                 .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
 
-            let language = detect_language(&file_path);
-            let summary = match summarize_with_claude(&content, api_key, &language, chatbot).await {
-                Ok(summary) => summary,
-                Err(e) => {
-                    debug_print!("Error summarizing {}: {}", file_path, e);
-                    format!(
-                        "Failed to summarize. File content preview: {}",
-                        &content[..std::cmp::min(content.len(), 100)]
-                    )
+            let language = detect_language(file_path);
+            let hash = content_hash(&language, &content);
+            let summary = if !chatbot.privacy.allows_contents(file_path) {
+                Some(local_outline(&content))
+            } else if let Some(docs) =
+                doc_summary_for(&doc_summaries, root_dir, file_path, modified_secs)
+            {
+                debug_print!("Summarizing from generated docs: {}", file_path);
+                from_docs += 1;
+                keywords.insert(file_path.clone(), docs.keywords.clone());
+                Some(docs.summary.clone())
+            } else if let Some(shared) = chatbot.shared_summaries.get(&hash).cloned() {
+                // Identical contents were already summarized, here or in another project
+                debug_print!("Reusing the summary of identical contents: {}", file_path);
+                reused += 1;
+                keywords.insert(file_path.clone(), shared.keywords);
+                Some(shared.summary)
+            } else {
+                // A huge file can take a while; pulse the progress view meanwhile
+                let mut heartbeat = Heartbeat::start(format!("summarizing {}", file_path));
+                let result = with_heartbeat(
+                    &mut heartbeat,
+                    Box::pin(summarize_with_claude(&content, api_key, &language, chatbot)),
+                    &mut |heartbeat| {
+                        progress.heartbeat = Some(heartbeat.clone());
+                        on_progress(&progress);
+                    },
+                )
+                .await;
+                progress.heartbeat = None;
+                match result {
+                    Ok(summary) => {
+                        let processed =
+                            postprocess_summary(&summary, &language, &chatbot.postprocess_config);
+                        keywords.insert(file_path.clone(), processed.keywords.clone());
+                        chatbot.shared_summaries.insert(
+                            hash,
+                            SharedSummary {
+                                summary: processed.text.clone(),
+                                keywords: processed.keywords,
+                            },
+                        );
+                        Some(processed.text)
+                    }
+                    Err(e) => {
+                        debug_print!("Error summarizing {}: {}", file_path, e);
+                        chatbot.failed_summaries.record(file_path, &e.to_string());
+                        None
+                    }
                 }
             };
+            // A failure keeps any earlier summary and is retried by the next pass
+            let Some(summary) = summary else {
+                pb.inc(1);
+                continue;
+            };
+            chatbot.failed_summaries.resolve(file_path);
 
             index.insert(file_path.clone(), (summary, language));
+            if let Err(e) = save_snapshot(file_path, &content) {
+                debug_print!("Failed to keep the indexed version of {}: {}", file_path, e);
+            }
             file_mod_times.insert(file_path.clone(), modified_secs); // Update modification time
+            progress.record_duration(file_start.elapsed().as_millis() as u64);
+            summarized += 1;
+
+            // Checkpoint regularly so an interrupted first run resumes where it stopped
+            if summarized % INDEX_CHECKPOINT_INTERVAL == 0 {
+                save_index_cache(
+                    root_dir,
+                    &index,
+                    last_modification,
+                    &file_mod_times,
+                    &keywords,
+                    &chatbot.churn,
+                    &chatbot.aliases,
+                )?;
+                progress.done = i + 1;
+                save_index_progress(root_dir, &progress)?;
+            }
         } else {
             debug_print!("Skipping file (no changes): {}", file_path);
         }
@@ -341,9 +1288,84 @@ pub async fn index_codebase(
         pb.inc(1);
     }
 
+    // Files of this root that vanished are evicted from context, or remapped if they moved
+    let vanished: Vec<String> = previously_indexed
+        .iter()
+        .filter(|file_path| {
+            file_path.starts_with(root_dir)
+                && !files_set.contains(*file_path)
+                && !chatbot.aliases.contains_key(*file_path)
+        })
+        .cloned()
+        .collect();
+    let added: Vec<String> = files
+        .iter()
+        .filter(|file_path| !previously_indexed.contains(*file_path))
+        .cloned()
+        .collect();
+    let eviction = detect_evictions(&vanished, &added);
+    if eviction.is_empty() {
+        chatbot.last_eviction = None;
+    } else {
+        chatbot.apply_eviction(&eviction);
+        chatbot.last_eviction = Some(eviction);
+    }
+
     // Remove entries for files that no longer exist
+    for file_path in &vanished {
+        chatbot.content_cache.invalidate(file_path);
+        remove_snapshot(file_path);
+    }
     index.retain(|file_path, _| files_set.contains(file_path));
     file_mod_times.retain(|file_path, _| files_set.contains(file_path));
+    keywords.retain(|file_path, _| files_set.contains(file_path));
+    chatbot.keywords = keywords.clone();
+    chatbot
+        .failed_summaries
+        .files
+        .retain(|file_path, _| !file_path.starts_with(root_dir) || files_set.contains(file_path));
+    if let Err(e) = chatbot.failed_summaries.save(root_dir) {
+        debug_print!("Failed to save failed summaries: {}", e);
+    }
+    chatbot
+        .todos
+        .retain(|file_path| files_set.contains(file_path));
+    if let Err(e) = chatbot.todos.save() {
+        debug_print!("Failed to save TODO comments: {}", e);
+    }
+    chatbot
+        .churn
+        .retain(|file_path| files_set.contains(file_path));
+    if deferred > 0 {
+        debug_print!("Deferred {} re-summaries to the next index", deferred);
+    }
+    if reused > 0 {
+        debug_print!("Reused {} summaries of identical contents", reused);
+    }
+    if from_docs > 0 {
+        debug_print!("Summarized {} files from generated docs", from_docs);
+    }
+    if let Err(e) = chatbot.shared_summaries.save() {
+        debug_print!("Failed to save shared summaries: {}", e);
+    }
+
+    if let Some(config) = chatbot.embedding_config.clone() {
+        match update_embeddings(&config, &mut chatbot.embeddings, &index).await {
+            Ok(count) => {
+                debug_print!("Embedded {} changed summaries", count);
+            }
+            Err(e) => {
+                debug_print!("Failed to update embeddings: {}", e);
+            }
+        }
+    }
+
+    chatbot
+        .usage
+        .record_index((files.len() - summarized) as u64, summarized as u64);
+    if let Err(e) = chatbot.usage.save() {
+        debug_print!("Failed to save usage metrics: {}", e);
+    }
 
     pb.finish_with_message(format!(
         "Indexing complete. Total files indexed: {}",
@@ -351,7 +1373,23 @@ pub async fn index_codebase(
     ));
 
     // Save the index cache
-    save_index_cache(&index, last_modification, &file_mod_times)?;
+    chatbot.index_root = root_dir.to_string();
+    save_index_cache(
+        root_dir,
+        &index,
+        last_modification,
+        &file_mod_times,
+        &keywords,
+        &chatbot.churn,
+        &chatbot.aliases,
+    )?;
+    clear_index_progress(root_dir);
+    if !queued.is_empty() {
+        clear_reindex_queue(root_dir);
+    }
+
+    progress.done = files.len();
+    on_progress(&progress);
 
     Ok((index, last_modification, file_mod_times))
 }
@@ -378,87 +1416,127 @@ pub fn detect_language(file_path: &str) -> String {
 }
 
 // Function to search the index based on a query
+// Function to rank files by embedding similarity, without calling the Claude API
+pub async fn search_embeddings(
+    config: &EmbeddingConfig,
+    query: &str,
+    chatbot: &mut Chatbot,
+) -> Result<Vec<(String, f32)>, Box<dyn std::error::Error>> {
+    let mut relevant_files = semantic_scores(config, &chatbot.embeddings, query).await?;
+    chatbot
+        .retrieval_memory
+        .apply_boost(query, &mut relevant_files);
+    boost_open_files(&chatbot.open_files, &chatbot.index, &mut relevant_files);
+    // Vectors come from an external server, so a score may be anything, NaN included
+    relevant_files.sort_by(|a, b| b.1.total_cmp(&a.1));
+    relevant_files.truncate(chatbot.retrieval_depth());
+    Ok(relevant_files)
+}
+
 pub async fn search_index(
     index: &HashMap<String, (String, String)>,
     query: &str,
     api_key: &str,
     chatbot: &mut Chatbot,
 ) -> Result<Vec<(String, f32)>, Box<dyn std::error::Error>> {
-    let mut prompt = format!(
-        "Based on the following query, score the relevance of each summary on a scale of 0 to 1:\n\nQuery: {}\n\n",
-        query
-    );
-
-    for (file, (summary, _)) in index {
-        prompt.push_str(&format!("Summary for {}: {}\n\n", file, summary));
+    // A cheap lexical pass narrows a large index to its likeliest files before the model
+    // scores their summaries
+    let candidates = lexical_candidates(index, query, chatbot.relevance_candidates, |file| {
+        chatbot.keywords_for(file)
+    });
+    let mut summaries = String::new();
+    for file in &candidates {
+        let (summary, _) = &index[file];
+        match chatbot.keywords_for(file) {
+            Some(keywords) if !keywords.is_empty() => summaries.push_str(&format!(
+                "Summary for {}: {} [keywords: {}]\n\n",
+                file,
+                summary,
+                keywords.join(", ")
+            )),
+            _ => summaries.push_str(&format!("Summary for {}: {}\n\n", file, summary)),
+        }
     }
-
-    prompt.push_str(
-        "Provide your response in the following format:\n\n<file_path_1>,<relevance_score_1>\n<file_path_2>,<relevance_score_2>\n...\n",
+    let prompt = chatbot.prompts.render(
+        Prompt::Relevance,
+        &[("query", query), ("summaries", &summaries)],
     );
 
-    let client = reqwest::Client::new();
+    let max_tokens = request_budget(chatbot, DEFAULT_MODEL, &prompt)?;
+    let mut request = json!({
+        "model": DEFAULT_MODEL,
+        "messages": [
+            {
+                "role": "user",
+                "content": prompt
+            }
+        ],
+        "max_tokens": max_tokens
+    });
+    chatbot
+        .sampling
+        .for_operation(Operation::Relevance)
+        .apply(&mut request);
     let start_time = std::time::Instant::now();
 
-    let response = client
-        .post(CLAUDE_API_URL)
-        .header("Content-Type", "application/json")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", ANTHROPIC_VERSION)
-        .json(&json!({
-            "model": DEFAULT_MODEL,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ],
-            "max_tokens": DEFAULT_MAX_TOKENS
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Claude API: {}", e))?;
+    let response = chatbot.client.post(api_key, &request).await?;
 
     let elapsed_time = start_time.elapsed().as_millis();
 
-    // Log the API call
-    chatbot.api_call_logs.push(ApiCallLog {
-        timestamp: Utc::now(),
-        endpoint: CLAUDE_API_URL.to_string(),
-        request_summary: "search_index".to_string(),
-        response_status: response.status().as_u16(),
-        response_time_ms: elapsed_time,
-    });
-
-    let status = response.status();
-    if !status.is_success() {
+    let status = response.status;
+    if !response.is_success() {
         let error_body = response
             .text()
             .await
             .map_err(|e| format!("Failed to read error response body: {}", e))?;
         debug_print!("Error response body: {}", error_body);
+        log_api_call(
+            chatbot,
+            "search_index",
+            DEFAULT_MODEL,
+            status,
+            elapsed_time,
+            None,
+        );
         return Err(format!("Claude API request failed: {} - {}", status, error_body).into());
     }
 
     let body: Value = response.json().await?;
-    let response_text = body["content"][0]["text"]
-        .as_str()
-        .ok_or("Missing 'text' field in API response")?
-        .trim()
-        .to_string();
+    log_api_call(
+        chatbot,
+        "search_index",
+        DEFAULT_MODEL,
+        status,
+        elapsed_time,
+        Some(&body),
+    );
+    let response_text = response_text_logged(&body)?;
 
     let mut relevant_files = Vec::new();
     for line in response_text.lines() {
         let parts: Vec<&str> = line.split(',').collect();
         if parts.len() == 2 {
             let file = parts[0].to_string();
-            let relevance: f32 = parts[1].parse().unwrap_or(0.0);
+            // "NaN" and "inf" parse as floats, but rank like an unreadable score
+            let relevance = parts[1]
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|score| score.is_finite())
+                .unwrap_or(0.0);
             relevant_files.push((file, relevance));
         }
     }
 
-    relevant_files.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-    relevant_files.truncate(5); // Limit to top 5 most relevant files
+    // Boost files that were decisive for similar past questions, and those open in the
+    // editor, before ranking
+    chatbot
+        .retrieval_memory
+        .apply_boost(query, &mut relevant_files);
+    boost_open_files(&chatbot.open_files, index, &mut relevant_files);
+
+    relevant_files.sort_by(|a, b| b.1.total_cmp(&a.1));
+    relevant_files.truncate(chatbot.retrieval_depth());
     Ok(relevant_files)
 }
 
@@ -466,10 +1544,36 @@ pub async fn search_index(
 pub fn prepare_context(
     relevant_files: &[(String, String)],
     user_query: &str,
+    deep_dives: &HashMap<String, String>,
+    index: &HashMap<String, (String, String)>,
+    privacy: &PrivacyPolicy,
+    cache: &mut ContentCache,
 ) -> Result<String, Box<dyn std::error::Error>> {
     let mut context = format!("User query: {}\n\nRelevant file contents:\n", user_query);
     for (file_path, _) in relevant_files {
-        let file_content = fs::read_to_string(file_path)?;
+        match privacy.level_for(file_path) {
+            PrivacyLevel::Never => continue,
+            PrivacyLevel::SummaryOnly => {
+                let summary = index
+                    .get(file_path)
+                    .map(|(summary, _)| summary.as_str())
+                    .unwrap_or("No summary available.");
+                context.push_str(&format!(
+                    "File: {}\nSummary (contents withheld):\n{}\n\n",
+                    file_path, summary
+                ));
+                continue;
+            }
+            PrivacyLevel::Full => {}
+        }
+        // A deep-dive analysis leads the file so the model reads it before the raw source
+        if let Some(analysis) = deep_dives.get(file_path) {
+            context.push_str(&format!(
+                "File: {}\nDeep-dive analysis:\n{}\n\n",
+                file_path, analysis
+            ));
+        }
+        let file_content = cache.read(file_path)?;
         context.push_str(&format!(
             "File: {}\nContent:\n{}\n\n",
             file_path, file_content
@@ -478,17 +1582,64 @@ pub fn prepare_context(
     Ok(context)
 }
 
-// Function to generate LLM response using Claude API
-pub async fn generate_llm_response(
+// Drops the least relevant quarter of the unpinned context files, at least one; files
+// are ranked best first, so they go from the end. Returns the files dropped
+fn drop_least_relevant(files: &mut Vec<(String, String)>, pinned: &[String]) -> Vec<String> {
+    let unpinned = files
+        .iter()
+        .filter(|(file, _)| !pinned.contains(file))
+        .count();
+    let mut to_drop = (unpinned / 4).max(1).min(unpinned);
+    let mut dropped = Vec::new();
+    let mut i = files.len();
+    while to_drop > 0 && i > 0 {
+        i -= 1;
+        if !pinned.contains(&files[i].0) {
+            dropped.push(files.remove(i).0);
+            to_drop -= 1;
+        }
+    }
+    dropped.reverse();
+    dropped
+}
+
+// The system prompt of an answer: the profile's with the style, glossary terms the query
+// uses, the workspace, merged projects and repository map
+fn answer_system_prompt(chatbot: &Chatbot, user_query: &str) -> String {
+    let base = chatbot.prompts.render(
+        Prompt::System,
+        &[("profile", chatbot.profile.system_prompt)],
+    );
+    let mut system = chatbot
+        .style
+        .system_prompt(chatbot.glossary.system_prompt(&base, user_query));
+    if let Some(workspace) = &chatbot.workspace {
+        system.push_str("\n\n");
+        system.push_str(&workspace.system_prompt());
+    }
+    if !chatbot.projects.is_empty() {
+        system.push_str("\n\n");
+        system.push_str(&projects::system_prompt(&chatbot.projects));
+    }
+    if let Some(map) = &chatbot.repo_map {
+        system.push_str("\n\nThe user was shown this map of the repository:\n");
+        system.push_str(map);
+    }
+    if chatbot.structured_answers {
+        system.push_str("\n\n");
+        system.push_str(STRUCTURED_INSTRUCTIONS);
+    }
+    system
+}
+
+// The Messages API request answering the query from the context, after the history,
+// checked against the budget and org policy; streamed unless `stream` is turned off
+pub fn answer_request(
     context: &str,
-    api_key: &str,
-    conversation_history: &Vec<Message>,
+    conversation_history: &[Message],
     user_query: &str,
     chatbot: &mut Chatbot,
-) -> Result<(String, bool), Box<dyn std::error::Error>> {
-    debug_print!("Generating LLM response");
-    let client = reqwest::Client::new();
-
+) -> Result<(String, Value), Box<dyn std::error::Error>> {
     let mut messages: Vec<Value> = conversation_history
         .iter()
         .map(|m| {
@@ -502,62 +1653,332 @@ pub async fn generate_llm_response(
     // Add the current context and user query
     messages.push(json!({
         "role": "user",
-        "content": format!("Based on the following context about a codebase and our previous conversation, please answer the user's query:\n\nContext: {}\n\nUser query: {}", context, user_query)
+        "content": chatbot
+            .prompts
+            .render(Prompt::Answer, &[("context", context), ("query", user_query)])
     }));
 
+    let system = answer_system_prompt(chatbot, user_query);
+    let prompt: String = messages
+        .iter()
+        .filter_map(|m| m["content"].as_str())
+        .chain([system.as_str()])
+        .collect::<Vec<_>>()
+        .join("\n");
+    // In fast mode, short questions with little context go to the quickest model
+    let model = match chatbot.latency.fastest(chatbot.org_policy.as_ref()) {
+        Some(fastest)
+            if chatbot.fast_mode && is_simple_turn(user_query, estimate_tokens(&prompt)) =>
+        {
+            fastest.to_string()
+        }
+        _ => chatbot.profile.model.to_string(),
+    };
+    let max_tokens = request_budget(chatbot, &model, &prompt)?;
+    let mut request = json!({
+        "model": model,
+        "messages": messages,
+        "system": system,
+        "max_tokens": max_tokens,
+        "stream": true
+    });
+    chatbot
+        .sampling
+        .for_operation(Operation::Chat)
+        .apply(&mut request);
+    Ok((model, request))
+}
+
+// Summaries of the relevant files in place of their contents, for a quick first answer
+pub fn prepare_summary_context(
+    relevant_files: &[(String, String)],
+    user_query: &str,
+    index: &HashMap<String, (String, String)>,
+) -> String {
+    let mut context = format!("User query: {}\n\nRelevant file summaries:\n", user_query);
+    for (file_path, _) in relevant_files {
+        let summary = index
+            .get(file_path)
+            .map(|(summary, _)| summary.as_str())
+            .unwrap_or("No summary available.");
+        context.push_str(&format!("File: {}\nSummary:\n{}\n\n", file_path, summary));
+    }
+    context
+}
+
+// Function to generate LLM response using Claude API
+pub async fn generate_llm_response(
+    context: &str,
+    api_key: &str,
+    conversation_history: &[Message],
+    user_query: &str,
+    chatbot: &mut Chatbot,
+    on_delta: &mut dyn FnMut(&str),
+) -> Result<(String, bool), Box<dyn std::error::Error>> {
+    debug_print!("Generating LLM response");
+    let _span = profiler::span("answer request");
+    let (model, request) = answer_request(context, conversation_history, user_query, chatbot)?;
     let start_time = std::time::Instant::now();
 
-    let response = client
-        .post(CLAUDE_API_URL)
-        .header("Content-Type", "application/json")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", ANTHROPIC_VERSION)
-        .json(&json!({
-            "model": DEFAULT_MODEL,
-            "messages": messages,
-            "system": "You are an AI assistant helping with a codebase. Use the provided context and conversation history to answer questions.",
-            "max_tokens": DEFAULT_MAX_TOKENS
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Claude API: {}", e))?;
+    // Slow waits pulse the streaming view, which redraws on a delta
+    let shared_heartbeat = chatbot.heartbeat.clone();
+    *shared_heartbeat.lock().unwrap() = None;
+    let mut heartbeat = Heartbeat::start("waiting for the API");
+    let mut on_pulse = |heartbeat: &Heartbeat| {
+        *shared_heartbeat.lock().unwrap() = Some(heartbeat.clone());
+        on_delta("");
+    };
+    let send = with_heartbeat(
+        &mut heartbeat,
+        chatbot.client.post(api_key, &request),
+        &mut on_pulse,
+    );
+    let response = match chatbot.turn_deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, send)
+            .await
+            .map_err(|_| "No answer started within the time budget")?,
+        None => send.await,
+    }?;
+    let network_ms = start_time.elapsed().as_millis() as u64;
+
+    let status = response.status;
+    if !response.is_success() {
+        let error_body = response.text().await.unwrap_or_default();
+        log_api_call(
+            chatbot,
+            "generate_llm_response",
+            &model,
+            status,
+            start_time.elapsed().as_millis(),
+            None,
+        );
+        if status == 400 && error_body.contains("prompt is too long") {
+            return Err(PromptTooLong(error_body).into());
+        }
+        return Err(format!("Claude API request failed: {} - {}", status, error_body).into());
+    }
 
-    let elapsed_time = start_time.elapsed().as_millis();
+    // Server-sent events: text deltas are reported as they arrive, usage at start and end
+    let mut response = response;
+    let mut pending = String::new();
+    let mut answer = String::new();
+    let mut usage = TokenUsage::default();
+    let mut stop_reason = Value::Null;
+    let mut first_token_ms = None;
+    let mut render_ms = 0;
+    let mut drift = Vec::new();
+    loop {
+        heartbeat.set_stage(if answer.is_empty() {
+            "waiting for the first token"
+        } else {
+            "streaming the answer"
+        });
+        let mut on_pulse = |heartbeat: &Heartbeat| {
+            *shared_heartbeat.lock().unwrap() = Some(heartbeat.clone());
+            on_delta(&answer);
+        };
+        let chunk = with_heartbeat(&mut heartbeat, Box::pin(response.chunk()), &mut on_pulse);
+        // Past the deadline the answer so far is kept and the stream dropped
+        let next = match chatbot.turn_deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, chunk).await {
+                Ok(next) => next,
+                Err(_) => {
+                    chatbot.timed_out = true;
+                    stop_reason = json!("time_budget");
+                    break;
+                }
+            },
+            None => chunk.await,
+        };
+        let Some(chunk) = next.map_err(|e| format!("Failed to read streamed response: {}", e))?
+        else {
+            break;
+        };
+        pending.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(end) = pending.find('\n') {
+            let line: String = pending.drain(..=end).collect();
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let Ok(event) = serde_json::from_str::<Value>(data.trim()) else {
+                continue;
+            };
+            drift.extend(event_drift(&event));
+            match event["type"].as_str() {
+                Some("message_start") => {
+                    usage = TokenUsage::anthropic(&event["message"]["usage"]);
+                }
+                Some("content_block_start") => {
+                    let kind = event["content_block"]["type"].as_str().unwrap_or("untyped");
+                    if kind != "text" {
+                        debug_print!("Skipping streamed {} block", kind);
+                    }
+                }
+                Some("content_block_delta") => {
+                    if let Some(text) = delta_text(&event) {
+                        first_token_ms.get_or_insert(start_time.elapsed().as_millis() as u64);
+                        answer.push_str(text);
+                        let drawn = std::time::Instant::now();
+                        on_delta(&answer);
+                        render_ms += drawn.elapsed().as_millis() as u64;
+                    }
+                }
+                Some("message_delta") => {
+                    usage.completion_tokens = event["usage"]["output_tokens"].as_u64().unwrap_or(0);
+                    stop_reason = event["delta"]["stop_reason"].clone();
+                }
+                Some("error") => {
+                    return Err(format!(
+                        "Claude API stream error: {}",
+                        describe_error(&event["error"])
+                    )
+                    .into());
+                }
+                _ => {}
+            }
+        }
+    }
 
-    // Log the API call
-    chatbot.api_call_logs.push(ApiCallLog {
-        timestamp: Utc::now(),
-        endpoint: CLAUDE_API_URL.to_string(),
-        request_summary: "generate_llm_response".to_string(),
-        response_status: response.status().as_u16(),
-        response_time_ms: elapsed_time,
+    *shared_heartbeat.lock().unwrap() = None;
+    chatbot.schema_drift.record(&model, drift);
+
+    // Logged in the shape of a non-streaming response so the audit log reads it the same way
+    let body = json!({
+        "usage": {
+            "input_tokens": usage.prompt_tokens,
+            "output_tokens": usage.completion_tokens,
+            "cache_creation_input_tokens": usage.cache_write_tokens,
+            "cache_read_input_tokens": usage.cache_read_tokens,
+        },
+        "stop_reason": stop_reason,
     });
+    log_api_call(
+        chatbot,
+        "generate_llm_response",
+        &model,
+        status,
+        start_time.elapsed().as_millis(),
+        Some(&body),
+    );
+    let total_ms = start_time.elapsed().as_millis() as u64;
+    let first_token_at = first_token_ms.unwrap_or(total_ms);
+    chatbot.turn_latency.network_ms = network_ms;
+    chatbot.turn_latency.first_token_ms = first_token_at.saturating_sub(network_ms);
+    chatbot.turn_latency.completion_ms = total_ms
+        .saturating_sub(first_token_at)
+        .saturating_sub(render_ms);
+    chatbot.turn_latency.render_ms = render_ms;
+    if let Some(latency_ms) = first_token_ms {
+        chatbot.latency.record(&model, latency_ms);
+        if let Err(e) = chatbot.latency.save() {
+            debug_print!("Failed to save model latency: {}", e);
+        }
+    }
+    chatbot.routed_model = (model != chatbot.profile.model).then(|| model.clone());
+    chatbot.last_answer_input = Some((
+        usage.input_tokens(),
+        estimate_usage_cost(
+            &model,
+            &TokenUsage {
+                completion_tokens: 0,
+                ..usage
+            },
+        ),
+    ));
 
-    let body: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))?;
+    if answer.is_empty() && chatbot.timed_out {
+        return Err("No answer arrived within the time budget".into());
+    }
+    if answer.is_empty() {
+        return Err(format!(
+            "No text in streamed API response (stop reason: {})",
+            stop_reason
+        )
+        .into());
+    }
+    let is_complete = stop_reason == "stop_sequence";
 
-    debug_print!("API Response: {:?}", body);
+    Ok((answer.trim().to_string(), is_complete))
+}
 
-    let answer = body["content"][0]["text"]
-        .as_str()
-        .ok_or_else(|| {
-            debug_print!("Missing 'text' field in API response: {:?}", body);
-            "Missing 'text' field in API response"
-        })?
-        .trim()
-        .to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let is_complete = !body["stop_reason"].is_null() && body["stop_reason"] == "stop_sequence";
+    fn files(names: &[&str]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), "rust".to_string()))
+            .collect()
+    }
 
-    Ok((answer, is_complete))
-}
+    #[test]
+    fn overflow_retries_drop_the_least_relevant_unpinned_quarter() {
+        let mut context = files(&["a", "b", "c", "d", "e", "f", "g", "h", "pinned"]);
+        let pinned = vec!["pinned".to_string()];
+        // Eight unpinned files lose two, from the end, skipping the pinned one
+        assert_eq!(drop_least_relevant(&mut context, &pinned), vec!["g", "h"]);
+        assert_eq!(context, files(&["a", "b", "c", "d", "e", "f", "pinned"]));
+
+        // At least one goes while any is unpinned
+        let mut context = files(&["a", "pinned"]);
+        assert_eq!(drop_least_relevant(&mut context, &pinned), vec!["a"]);
+        assert!(drop_least_relevant(&mut context, &pinned).is_empty());
+        assert_eq!(context, files(&["pinned"]));
+    }
 
-// Function to chat with the system
-pub async fn chat_with_system(
-    chatbot: &mut Chatbot,
-    user_query: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    chatbot.chat(user_query).await
+    #[tokio::test]
+    async fn unreadable_relevance_scores_rank_last_instead_of_panicking() {
+        let client = Arc::new(crate::claude_client::MockClient::default());
+        let reply = json!({
+            "content": [{ "type": "text", "text": "./a.rs,NaN\n./b.rs,0.4\n./c.rs,inf\n./d.rs,high" }],
+            "usage": { "input_tokens": 10, "output_tokens": 4 },
+            "stop_reason": "end_turn"
+        });
+        client.respond(200, reply.to_string());
+        let index: HashMap<String, (String, String)> = ["./a.rs", "./b.rs", "./c.rs", "./d.rs"]
+            .into_iter()
+            .map(|file| (file.to_string(), ("Code".to_string(), "rust".to_string())))
+            .collect();
+        let mut chatbot = Chatbot::new(index.clone(), HashMap::new(), "key".to_string(), client);
+
+        let ranked = search_index(&index, "code", "key", &mut chatbot)
+            .await
+            .unwrap();
+        assert_eq!(ranked[0], ("./b.rs".to_string(), 0.4));
+        assert!(ranked[1..].iter().all(|(_, score)| *score == 0.0));
+    }
+
+    #[test]
+    fn retrieval_shares_the_index_snapshot_until_projects_are_merged() {
+        let entry = |summary: &str| (summary.to_string(), "rust".to_string());
+        let mut chatbot = Chatbot::new(
+            HashMap::from([("./a.rs".to_string(), entry("Local a"))]),
+            HashMap::new(),
+            "key".to_string(),
+            Arc::new(crate::claude_client::MockClient::default()),
+        );
+        let snapshot = chatbot.retrieval_index();
+        assert!(Arc::ptr_eq(&snapshot, &chatbot.index));
+        // Reindexing swaps the Arc, leaving a query's snapshot as it was
+        chatbot.index = Arc::new(HashMap::new());
+        assert_eq!(snapshot["./a.rs"].0, "Local a");
+
+        chatbot.index = Arc::clone(&snapshot);
+        chatbot.projects.push(crate::projects::ProjectIndex {
+            name: "other".to_string(),
+            root: "/other".to_string(),
+            index: HashMap::from([
+                ("./a.rs".to_string(), entry("Other a")),
+                ("/other/b.rs".to_string(), entry("Other b")),
+            ]),
+            keywords: HashMap::new(),
+        });
+        let merged = chatbot.retrieval_index();
+        assert!(!Arc::ptr_eq(&merged, &chatbot.index));
+        // The current project's entry wins a shared path
+        assert_eq!(merged["./a.rs"].0, "Local a");
+        assert_eq!(merged.len(), 2);
+        assert_eq!(chatbot.index.len(), 1);
+    }
 }
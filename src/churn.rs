@@ -0,0 +1,63 @@
+// src/churn.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Changes older than this no longer count towards a file's churn
+const CHURN_WINDOW_SECS: u64 = 30 * 86_400;
+// Change times kept per file
+const MAX_RECORDED_CHANGES: usize = 32;
+
+/// When each indexed file was seen to change, used to re-summarize busy files first
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ChurnStats {
+    pub changes: HashMap<String, Vec<u64>>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+impl ChurnStats {
+    pub fn record(&mut self, path: &str, modified_secs: u64) {
+        let changes = self.changes.entry(path.to_string()).or_default();
+        changes.push(modified_secs);
+        if changes.len() > MAX_RECORDED_CHANGES {
+            changes.remove(0);
+        }
+    }
+
+    /// Changes seen within the churn window
+    pub fn score(&self, path: &str) -> usize {
+        let since = now_secs().saturating_sub(CHURN_WINDOW_SECS);
+        self.changes
+            .get(path)
+            .map_or(0, |changes| changes.iter().filter(|&&t| t >= since).count())
+    }
+
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.changes.retain(|path, _| keep(path));
+    }
+}
+
+/// Files whose contents changed after their summary was written, so answers drawing on
+/// them may rest on an outdated understanding
+pub fn stale_summaries(files: &[String], file_mod_times: &HashMap<String, u64>) -> Vec<String> {
+    files
+        .iter()
+        .filter(|path| {
+            let Some(&summarized) = file_mod_times.get(*path) else {
+                return false;
+            };
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .is_some_and(|modified| modified.as_secs() > summarized)
+        })
+        .cloned()
+        .collect()
+}
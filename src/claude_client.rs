@@ -0,0 +1,219 @@
+// src/claude_client.rs
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use crate::constants::ANTHROPIC_VERSION;
+
+pub type ApiFuture<'a> = Pin<Box<dyn Future<Output = Result<ApiResponse, String>> + Send + 'a>>;
+
+/// A response whose body may still be arriving, as for a streamed answer
+pub struct ApiResponse {
+    pub status: u16,
+    chunks: UnboundedReceiver<Result<Vec<u8>, String>>,
+}
+
+impl ApiResponse {
+    /// A response with a body that has fully arrived
+    pub fn complete(status: u16, body: impl Into<Vec<u8>>) -> ApiResponse {
+        let (sender, chunks) = mpsc::unbounded_channel();
+        let _ = sender.send(Ok(body.into()));
+        ApiResponse { status, chunks }
+    }
+
+    /// A response whose body arrives on a channel, for clients that wrap another one
+    pub fn streaming(
+        status: u16,
+        chunks: UnboundedReceiver<Result<Vec<u8>, String>>,
+    ) -> ApiResponse {
+        ApiResponse { status, chunks }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The next piece of the body; None once all of it has arrived
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>, String> {
+        self.chunks.recv().await.transpose()
+    }
+
+    pub async fn text(mut self) -> Result<String, String> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            body.extend(chunk);
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    pub async fn json(self) -> Result<Value, String> {
+        serde_json::from_str(&self.text().await?).map_err(|e| e.to_string())
+    }
+}
+
+/// Sends Messages API requests; every call to Claude goes through one, so tests and
+/// other providers can stand in for the HTTP API
+pub trait ClaudeClient: Send + Sync {
+    /// Where requests go, as recorded in the audit log
+    fn endpoint(&self) -> String;
+
+    fn post<'a>(&'a self, api_key: &'a str, request: &'a Value) -> ApiFuture<'a>;
+}
+
+/// The Messages API over HTTP
+pub struct HttpClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpClient {
+    pub fn new(url: impl Into<String>) -> HttpClient {
+        HttpClient {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl ClaudeClient for HttpClient {
+    fn endpoint(&self) -> String {
+        self.url.clone()
+    }
+
+    fn post<'a>(&'a self, api_key: &'a str, request: &'a Value) -> ApiFuture<'a> {
+        Box::pin(async move {
+            let mut response = self
+                .client
+                .post(self.url.as_str())
+                .header("Content-Type", "application/json")
+                .header("x-api-key", api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(request)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to send request to Claude API: {}", e))?;
+            let status = response.status().as_u16();
+            // The body is forwarded as it arrives; dropping the response stops the read
+            let (sender, chunks) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                loop {
+                    let next = match response.chunk().await {
+                        Ok(Some(bytes)) => Ok(bytes.to_vec()),
+                        Ok(None) => break,
+                        Err(e) => Err(e.to_string()),
+                    };
+                    let failed = next.is_err();
+                    if sender.send(next).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+            Ok(ApiResponse { status, chunks })
+        })
+    }
+}
+
+/// Answers requests with queued responses and keeps every request it was sent
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockClient {
+    responses: std::sync::Mutex<std::collections::VecDeque<(u16, String)>>,
+    pub requests: std::sync::Mutex<Vec<Value>>,
+}
+
+#[cfg(test)]
+impl MockClient {
+    /// Queues the response to the next request
+    pub fn respond(&self, status: u16, body: impl Into<String>) {
+        self.responses
+            .lock()
+            .unwrap()
+            .push_back((status, body.into()));
+    }
+}
+
+#[cfg(test)]
+impl ClaudeClient for MockClient {
+    fn endpoint(&self) -> String {
+        "mock".to_string()
+    }
+
+    fn post<'a>(&'a self, _api_key: &'a str, request: &'a Value) -> ApiFuture<'a> {
+        self.requests.lock().unwrap().push(request.clone());
+        let next = self.responses.lock().unwrap().pop_front();
+        Box::pin(async move {
+            let (status, body) = next.ok_or("No mock response queued")?;
+            Ok(ApiResponse::complete(status, body))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chatbot::{complete_with_claude, Chatbot};
+    use crate::sampling::Operation;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn completions_go_through_the_client() {
+        let client = Arc::new(MockClient::default());
+        client.respond(
+            200,
+            json!({
+                "content": [{ "type": "text", "text": "Adds two numbers." }],
+                "usage": { "input_tokens": 10, "output_tokens": 4 },
+                "stop_reason": "end_turn"
+            })
+            .to_string(),
+        );
+        let mut chatbot = Chatbot::new(
+            HashMap::new(),
+            HashMap::new(),
+            "test-key".to_string(),
+            client.clone(),
+        );
+
+        let answer = complete_with_claude(
+            "What does add do?",
+            "claude-test",
+            "test-key",
+            "test",
+            Operation::Chat,
+            &mut chatbot,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(answer, "Adds two numbers.");
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["model"], "claude-test");
+        assert_eq!(chatbot.audit_log.input_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn streamed_bodies_are_joined_until_a_chunk_fails() {
+        let (sender, chunks) = mpsc::unbounded_channel();
+        sender.send(Ok(b"{\"ok\":".to_vec())).unwrap();
+        sender.send(Ok(b" true}".to_vec())).unwrap();
+        drop(sender);
+        let response = ApiResponse::streaming(200, chunks);
+        assert!(response.is_success());
+        assert_eq!(response.json().await.unwrap(), json!({ "ok": true }));
+
+        let (sender, chunks) = mpsc::unbounded_channel();
+        sender.send(Ok(b"partial".to_vec())).unwrap();
+        sender.send(Err("connection reset".to_string())).unwrap();
+        let response = ApiResponse::streaming(200, chunks);
+        assert_eq!(response.text().await.unwrap_err(), "connection reset");
+
+        for (status, success) in [(199, false), (200, true), (299, true), (300, false)] {
+            assert_eq!(ApiResponse::complete(status, "").is_success(), success);
+        }
+        assert!(ApiResponse::complete(200, "not json").json().await.is_err());
+    }
+}
@@ -0,0 +1,59 @@
+// src/clipboard_ring.rs
+use std::collections::VecDeque;
+
+// Copies kept for the Ctrl+Y picker, newest first
+pub const CLIPBOARD_RING_SIZE: usize = 10;
+
+/// The last few things copied, so copying a new snippet doesn't lose the previous one
+#[derive(Debug, Default)]
+pub struct ClipboardRing {
+    entries: VecDeque<String>,
+}
+
+impl ClipboardRing {
+    /// Adds a copy at the front; copying the same text again moves it there
+    pub fn push(&mut self, text: String) {
+        self.entries.retain(|entry| *entry != text);
+        self.entries.push_front(text);
+        self.entries.truncate(CLIPBOARD_RING_SIZE);
+    }
+
+    pub fn get(&self, index: usize) -> Option<&String> {
+        self.entries.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_are_newest_first_without_repeats_and_capped() {
+        let mut ring = ClipboardRing::default();
+        for i in 0..CLIPBOARD_RING_SIZE + 2 {
+            ring.push(format!("copy {}", i));
+        }
+        assert_eq!(ring.len(), CLIPBOARD_RING_SIZE);
+        assert_eq!(ring.get(0).unwrap(), "copy 11");
+        assert_eq!(ring.iter().last().unwrap(), "copy 2");
+
+        // Copying again moves the text to the front instead of adding it twice
+        ring.push("copy 5".to_string());
+        assert_eq!(ring.len(), CLIPBOARD_RING_SIZE);
+        assert_eq!(ring.get(0).unwrap(), "copy 5");
+        assert_eq!(ring.get(1).unwrap(), "copy 11");
+        assert_eq!(ring.iter().filter(|entry| *entry == "copy 5").count(), 1);
+    }
+}
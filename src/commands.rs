@@ -0,0 +1,1029 @@
+// src/commands.rs
+use crate::branches::repo_root;
+use crate::briefing::briefing_prompt;
+use crate::carry_over::CarryOver;
+use crate::change_ledger::{ChangeLedger, FileChange};
+use crate::chatbot::{complete_with_claude, detect_language};
+use crate::config::save_config;
+use crate::constants::DEFAULT_MODEL;
+use crate::deepdive::deep_dive_file;
+use crate::dialog::{Dialog, DialogAction, DialogChoice};
+use crate::digest::{write_digest, Digest};
+use crate::extract::{edits_for, labeled_blocks};
+use crate::file_viewer::EXPLAIN_CONTEXT_LINES;
+use crate::gen_tests::{generate_tests, run_tests, write_tests};
+use crate::profiler;
+use crate::profiles::{builtin_profiles, find_profile};
+use crate::refactor_plan::{execute_step, plan_refactor, write_edits};
+use crate::remote::{fetch_remote, index_remote};
+use crate::sampling::Operation;
+use crate::saved_responses::{filename_prompt, sanitize_stem, SavedResponses};
+use crate::search::{format_hits, search};
+use crate::style::Verbosity;
+use crate::sweep::plan_sweep;
+use crate::transcript_log::load_turns;
+use crate::ui::chat::{Message, Sender};
+use crate::{App, AppState, ChatFocus};
+use std::time::Duration;
+
+/// Runs the action behind a dialog the user accepted
+pub fn run_dialog_action(app: &mut App, action: DialogAction, choice: DialogChoice) {
+    match action {
+        DialogAction::ClearHistory => {
+            // The second option keeps a copy first, and nothing is cleared if that fails
+            if choice.option == 0 {
+                app.clear_history();
+            } else if app.export_transcript(false) {
+                let exported = app.status_message.take().unwrap_or_default();
+                app.clear_history();
+                app.status_message = Some(format!("{}; conversation cleared", exported));
+            }
+        }
+        DialogAction::DeleteSession(id) => app.delete_session(&id),
+        DialogAction::AcceptEdits => accept_edits_command(app),
+        DialogAction::AcceptTests { run } => accept_tests_command(app, run),
+        DialogAction::ApplySweep => app.apply_sweep(),
+        DialogAction::TakeOverLock => app.take_over_lock(),
+        DialogAction::RunShell => app.run_shell_command(&choice.input),
+        DialogAction::FillTemplate => app.fill_template_variable(&choice.input),
+        DialogAction::SendQuestion { missing, .. } if choice.option == 0 => {
+            app.search_symbols(&missing)
+        }
+        DialogAction::UploadGist(snippet) => app.pending_gist = Some(snippet),
+        DialogAction::SendQuestion { question, .. } => {
+            app.input.clear();
+            let question = app.with_reply_quote(&question);
+            app.queued_question = app.check_input_budget(question);
+        }
+    }
+}
+
+/// Dispatches a slash command typed into the chat input
+pub async fn handle_command(app: &mut App, command_line: &str) {
+    let mut parts = command_line.splitn(2, ' ');
+    let command = parts.next().unwrap_or("");
+    let argument = parts.next().unwrap_or("").trim();
+    let spent_before = app.chatbot.audit_log.spent;
+
+    match command {
+        "/deepdive" => deep_dive_command(app, argument).await,
+        "/briefing" => briefing_command(app).await,
+        "/extract" => match app.messages.iter().rposition(|message| {
+            message.sender == Sender::AI && !labeled_blocks(&message.content).is_empty()
+        }) {
+            Some(index) => extract_files(app, index),
+            None => {
+                app.status_message =
+                    Some("No answer has code blocks labeled with a file".to_string())
+            }
+        },
+        "/search" => search_command(app, argument).await,
+        "/explain" => explain_selection_command(app).await,
+        "/save" => save_response_command(app, argument == "code").await,
+        "/refresh" => refresh_remote_command(app).await,
+        "/gen-tests" => gen_tests_command(app, argument).await,
+        "/plan-refactor" => plan_refactor_command(app, argument).await,
+        "/sweep" => sweep_command(app, argument).await,
+        "/accept" if !app.pending_edits.is_empty() => confirm_accept_edits(app),
+        "/accept" => confirm_accept_tests(app, argument == "run"),
+        "/changes" => changes_command(app, argument),
+        "/digest" => digest_command(app, argument).await,
+        "/clear" => app.confirm_clear_history(),
+        "/reject" if !app.pending_edits.is_empty() => {
+            let count = app.pending_edits.len();
+            app.pending_edits.clear();
+            app.pending_step = None;
+            app.status_message = Some(format!("Discarded edits to {} files", count));
+        }
+        "/reject" => {
+            app.status_message = Some(match app.pending_tests.take() {
+                Some(pending) => format!("Discarded tests for {}", pending.source_file),
+                None => "Nothing to reject".to_string(),
+            });
+        }
+        "/export" if argument == "json" => {
+            let session = app.current_session();
+            app.export_dataset(&[session]);
+        }
+        "/export" => {
+            app.export_transcript(false);
+        }
+        "/print" => app.print_transcript(argument),
+        "/share" => {
+            app.export_transcript(true);
+        }
+        "/audit" => app.open_audit(),
+        "/heatmap" => app.open_heatmap(),
+        "/branches" => app.open_branch_switcher(),
+        "/projects" => app.open_projects(),
+        "/todos" => app.open_todos(argument),
+        "/project" => project_command(app, argument),
+        "/include" => include_imports_command(app),
+        "/glossary" => glossary_command(app, argument),
+        "/style" => style_command(app, argument),
+        "/crate" => crate_command(app, argument),
+        "/template" => template_command(app, argument),
+        "/timebox" => timebox_command(app, argument),
+        "/mode" => mode_command(app, argument),
+        "/gutter" => {
+            app.config.transcript_gutter = !app.config.transcript_gutter;
+            app.status_message = Some(match save_config(&app.config) {
+                Ok(()) if app.config.transcript_gutter => "Transcript gutter on".to_string(),
+                Ok(()) => "Transcript gutter off".to_string(),
+                Err(e) => format!("Failed to save config: {}", e),
+            });
+        }
+        "/json" => {
+            app.config.structured_answers = !app.config.structured_answers;
+            app.chatbot.structured_answers = app.config.structured_answers;
+            app.status_message = Some(match save_config(&app.config) {
+                Ok(()) if app.config.structured_answers => {
+                    "Structured answers on: answer, confidence and sources".to_string()
+                }
+                Ok(()) => "Structured answers off".to_string(),
+                Err(e) => format!("Failed to save config: {}", e),
+            });
+        }
+        "/quick" => {
+            app.config.two_phase_answers = !app.config.two_phase_answers;
+            app.chatbot.two_phase_answers = app.config.two_phase_answers && !app.safe_mode;
+            app.status_message = Some(match save_config(&app.config) {
+                Ok(()) if app.config.two_phase_answers => {
+                    "Quick answers on: summaries first, refined from full files if different"
+                        .to_string()
+                }
+                Ok(()) => "Quick answers off".to_string(),
+                Err(e) => format!("Failed to save config: {}", e),
+            });
+        }
+        "/fast" => {
+            app.config.fast_mode = !app.config.fast_mode;
+            app.chatbot.fast_mode = app.config.fast_mode;
+            app.status_message = Some(match save_config(&app.config) {
+                Ok(()) if app.config.fast_mode => {
+                    "Fast mode on: short questions go to the quickest model".to_string()
+                }
+                Ok(()) => "Fast mode off".to_string(),
+                Err(e) => format!("Failed to save config: {}", e),
+            });
+        }
+        "/focus" => app.focus_mode = !app.focus_mode,
+        "/gist" => app.confirm_gist_upload(),
+        "/handoff" => app.write_handoff(),
+        "/import" => app.import_handoff(argument),
+        "/contrast" if app.no_color => {
+            app.status_message = Some("NO_COLOR is set, so nothing is drawn in color".to_string());
+        }
+        "/contrast" => {
+            app.config.high_contrast = !app.config.high_contrast;
+            app.status_message = Some(match save_config(&app.config) {
+                Ok(()) if app.config.high_contrast => "High contrast on".to_string(),
+                Ok(()) => "High contrast off".to_string(),
+                Err(e) => format!("Failed to save config: {}", e),
+            });
+        }
+        "/a11y" => {
+            app.config.accessibility_mode = !app.config.accessibility_mode;
+            app.status_message = Some(match save_config(&app.config) {
+                Ok(()) if app.config.accessibility_mode => "Accessibility mode on".to_string(),
+                Ok(()) => "Accessibility mode off".to_string(),
+                Err(e) => format!("Failed to save config: {}", e),
+            });
+        }
+        "/profile" if !profiler::ENABLED => {
+            app.status_message =
+                Some("Profiling isn't built in; rebuild with --features profiling".to_string());
+        }
+        "/profile" if argument == "reset" => {
+            profiler::reset();
+            app.status_message = Some("Cleared the profile".to_string());
+        }
+        "/profile" => app.show_profile = !app.show_profile,
+        "/fresh" => {
+            app.chatbot.carry_over = CarryOver::default();
+            app.status_message =
+                Some("The next question searches the index instead of reusing context".to_string());
+        }
+        "/unpin" => {
+            app.chatbot.pinned_files.clear();
+            app.status_message = Some("Cleared pinned context files".to_string());
+        }
+        _ => app.status_message = Some(format!("Unknown command: {}", command)),
+    }
+
+    app.session_cost += app.chatbot.audit_log.spent - spent_before;
+    if let Some(warning) = app.chatbot.context_warning.take() {
+        app.status_message = Some(warning);
+    }
+}
+
+async fn deep_dive_command(app: &mut App, file_path: &str) {
+    if file_path.is_empty() {
+        app.status_message = Some("Usage: /deepdive <file>".to_string());
+        return;
+    }
+
+    app.messages
+        .push(Message::user(format!("/deepdive {}", file_path)));
+    let content = match deep_dive_file(file_path, &mut app.chatbot).await {
+        Ok(deep_dive) => format!("Deep dive of {}:\n\n{}", file_path, deep_dive.analysis),
+        Err(e) => format!("Deep dive of {} failed: {}", file_path, e),
+    };
+    app.messages.push(Message::ai(content));
+    app.persist_session();
+}
+
+/// Explains only the lines selected in the file viewer, plus a little surrounding context
+async fn explain_selection_command(app: &mut App) {
+    let Some(viewer) = app.file_viewer.as_ref() else {
+        app.status_message = Some("/explain needs a file open in split view (Ctrl+T)".to_string());
+        return;
+    };
+    if !app.chatbot.privacy.allows_contents(&viewer.path) {
+        app.status_message = Some(format!(
+            "{} is marked private; its contents can't be sent",
+            viewer.path
+        ));
+        return;
+    }
+    let (start, end) = viewer.selection();
+    let path = viewer.path.clone();
+    let excerpt = viewer.excerpt(EXPLAIN_CONTEXT_LINES);
+    let prompt = format!(
+        "Explain this {} code from {}, lines {}-{}. Lines marked with '>' are the selection; \
+         the others are surrounding context only. Be concise and focus on the selected lines.\n\n{}",
+        detect_language(&path),
+        path,
+        start + 1,
+        end + 1,
+        excerpt
+    );
+
+    app.messages.push(Message::user(format!(
+        "/explain {}:{}-{}",
+        path,
+        start + 1,
+        end + 1
+    )));
+    let api_key = app.chatbot.api_key.clone();
+    let content = match complete_with_claude(
+        &prompt,
+        DEFAULT_MODEL,
+        &api_key,
+        "explain",
+        Operation::Explain,
+        &mut app.chatbot,
+    )
+    .await
+    {
+        Ok(explanation) => explanation,
+        Err(e) => format!("Explaining {} failed: {}", path, e),
+    };
+    let mut answer = Message::ai(content);
+    answer.context_files = vec![path];
+    app.messages.push(answer);
+    if let Some(viewer) = app.file_viewer.as_mut() {
+        viewer.anchor = None;
+    }
+    app.persist_session();
+}
+
+/// Saves the focused message, or the latest answer, to the responses directory under a
+/// model-chosen name; with `code_only`, each of its code blocks goes to its own file
+async fn save_response_command(app: &mut App, code_only: bool) {
+    let index = app.selected_message.or_else(|| {
+        app.messages
+            .iter()
+            .rposition(|message| message.sender == Sender::AI)
+    });
+    let Some((index, content)) =
+        index.and_then(|i| Some((i, app.messages.get(i)?.content.clone())))
+    else {
+        app.status_message = Some("No message to save".to_string());
+        return;
+    };
+    let api_key = app.chatbot.api_key.clone();
+    let stem = complete_with_claude(
+        &filename_prompt(&content),
+        DEFAULT_MODEL,
+        &api_key,
+        "generate filename",
+        Operation::Summarize,
+        &mut app.chatbot,
+    )
+    .await
+    .ok()
+    .and_then(|name| sanitize_stem(&name))
+    // Saving shouldn't fail because naming did
+    .unwrap_or_else(|| format!("response-{}", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+    let responses_dir = app.config.responses_path(&app.project_root);
+    let mut saved = SavedResponses::load(&responses_dir, &app.session_id);
+    app.status_message = Some(match saved.save(&stem, &content, index, code_only) {
+        Ok(files) if files.len() == 1 => format!("Saved to {}", files[0].display()),
+        Ok(files) => format!(
+            "Saved {} code blocks to {}",
+            files.len(),
+            responses_dir.join(&app.session_id).display()
+        ),
+        Err(e) => format!("Failed to save: {}", e),
+    });
+}
+
+/// `/digest [date]` posts a day's digest, writing it first for today or a day without
+/// one; `/digest export [date]` writes it as Markdown
+async fn digest_command(app: &mut App, argument: &str) {
+    let (export, date) = match argument.strip_prefix("export") {
+        Some(date) => (true, date.trim()),
+        None => (false, argument),
+    };
+    let today = chrono::Local::now().date_naive();
+    let date = if date.is_empty() {
+        today
+    } else {
+        match chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => {
+                app.status_message = Some(format!("Not a date: {} (use YYYY-MM-DD)", date));
+                return;
+            }
+        }
+    };
+    let data_dir = app.config.data_path();
+    let project = app.project_name();
+    // Today's digest is rewritten each time since the day isn't over
+    let saved = (date != today)
+        .then(|| Digest::load(&data_dir, &project, date))
+        .flatten();
+    let digest = match saved {
+        Some(digest) => digest,
+        None => {
+            let turns = load_turns(&data_dir, &project);
+            match write_digest(&mut app.chatbot, &data_dir, &project, date, &turns).await {
+                Ok(digest) => digest,
+                Err(e) => {
+                    app.status_message = Some(format!("Digest failed: {}", e));
+                    return;
+                }
+            }
+        }
+    };
+    if export {
+        app.status_message = Some(match digest.export(&data_dir) {
+            Ok(path) => format!("Digest exported to {}", path.display()),
+            Err(e) => format!("Failed to export digest: {}", e),
+        });
+        return;
+    }
+    app.messages.push(Message::ai(digest.to_markdown()));
+    app.persist_session();
+}
+
+/// Starts a new session whose first message briefs the user on what changed since the
+/// previous session
+async fn briefing_command(app: &mut App) {
+    let Some(changes) = app.briefing.take() else {
+        app.status_message = Some("Nothing changed since the last session".to_string());
+        return;
+    };
+    app.status_message = None;
+    let prompt = briefing_prompt(&changes, &app.chatbot.index);
+    let api_key = app.chatbot.api_key.clone();
+    let briefing = match complete_with_claude(
+        &prompt,
+        DEFAULT_MODEL,
+        &api_key,
+        "session briefing",
+        Operation::Report,
+        &mut app.chatbot,
+    )
+    .await
+    {
+        Ok(briefing) => briefing,
+        Err(e) => {
+            app.status_message = Some(format!("Briefing failed: {}", e));
+            app.briefing = Some(changes);
+            return;
+        }
+    };
+    app.start_new_session();
+    app.messages.push(Message::ai(format!(
+        "**What changed since {}**: {} added, {} changed and {} removed files\n\n{}",
+        changes.since.format("%Y-%m-%d"),
+        changes.added.len(),
+        changes.changed.len(),
+        changes.removed.len(),
+        briefing
+    )));
+    app.persist_session();
+    app.status_message = Some(format!(
+        "Briefed on {} files; the previous session is in the switcher (Ctrl+O)",
+        changes.count()
+    ));
+    app.state = AppState::Chat;
+}
+
+/// Shows the files retrieval ranks for a query without asking the answer model
+async fn search_command(app: &mut App, query: &str) {
+    if query.is_empty() {
+        app.status_message = Some("Usage: /search <query>".to_string());
+        return;
+    }
+    app.messages
+        .push(Message::user(format!("/search {}", query)));
+    let content = match search(&mut app.chatbot, query).await {
+        Ok(hits) => format!("```\n{}\n```", format_hits(&hits)),
+        Err(e) => format!("Search failed: {}", e),
+    };
+    app.messages.push(Message::ai(content));
+    app.persist_session();
+}
+
+/// Lists the task profiles, or switches to the named one
+fn mode_command(app: &mut App, name: &str) {
+    if name.is_empty() {
+        let profiles: Vec<String> = builtin_profiles()
+            .iter()
+            .map(|p| format!("{} ({})", p.name, p.description))
+            .collect();
+        app.status_message = Some(format!("Usage: /mode <profile> — {}", profiles.join(", ")));
+        return;
+    }
+    let Some(profile) = find_profile(name) else {
+        app.status_message = Some(format!("Unknown profile: {}", name));
+        return;
+    };
+    if let Some(policy) = &app.config.org_policy {
+        if !policy.allows_model(profile.model) {
+            app.status_message = Some(format!(
+                "The {} mode uses {}, which the org policy doesn't allow",
+                profile.name, profile.model
+            ));
+            return;
+        }
+    }
+    app.config.task_profile = profile.name.to_string();
+    app.chatbot.profile = profile;
+    let profile = &app.chatbot.profile;
+    app.status_message = Some(match save_config(&app.config) {
+        Ok(()) => format!(
+            "Mode: {} — top {} files{}, {} model",
+            profile.name,
+            profile.retrieval_k,
+            if app.chatbot.reranks() {
+                " (re-ranked)"
+            } else {
+                ""
+            },
+            profile.model
+        ),
+        Err(e) => format!("Failed to save config: {}", e),
+    });
+}
+
+async fn refresh_remote_command(app: &mut App) {
+    let Some(project) = app.remote_project.clone() else {
+        app.status_message = Some("/refresh only applies to remote repositories".to_string());
+        return;
+    };
+    if !app.ensure_writable() {
+        return;
+    }
+    let project = match fetch_remote(&project, &app.config.data_path()).await {
+        Ok(project) => project,
+        Err(e) => {
+            app.status_message = Some(format!("Fetch failed: {}", e));
+            return;
+        }
+    };
+    app.status_message = Some(
+        match index_remote(&project, &mut app.chatbot, &mut |_| {}).await {
+            Ok(count) => format!("Fetched {} and reindexed {} files", project.url, count),
+            Err(e) => format!("Reindex failed: {}", e),
+        },
+    );
+    app.remote_project = Some(project);
+    app.apply_context_eviction();
+}
+
+async fn gen_tests_command(app: &mut App, file_path: &str) {
+    if file_path.is_empty() {
+        app.status_message = Some("Usage: /gen-tests <file>".to_string());
+        return;
+    }
+
+    app.messages
+        .push(Message::user(format!("/gen-tests {}", file_path)));
+    match generate_tests(file_path, &mut app.chatbot).await {
+        Ok(pending) => {
+            app.messages.push(Message::ai(format!(
+                "Proposed tests for {} → {}\n\n{}\n\n/accept to write them, /accept run to write and run them, /reject to discard.",
+                file_path,
+                pending.test_path.display(),
+                pending.diff
+            )));
+            app.pending_tests = Some(pending);
+        }
+        Err(e) => app.messages.push(Message::ai(format!(
+            "Test generation for {} failed: {}",
+            file_path, e
+        ))),
+    }
+    app.persist_session();
+}
+
+fn confirm_accept_tests(app: &mut App, run_after: bool) {
+    let Some(pending) = &app.pending_tests else {
+        app.status_message = Some("No generated tests are waiting to be accepted".to_string());
+        return;
+    };
+    let verb = if run_after { "Write and run" } else { "Write" };
+    let mut message = format!(
+        "{} tests for {} to {}?",
+        verb,
+        pending.source_file,
+        pending.test_path.display()
+    );
+    if pending.test_path.exists() {
+        message.push_str(" The existing file will be overwritten.");
+    }
+    if run_after {
+        message.push_str(" Running them executes the project's test runner.");
+    }
+    app.open_dialog(Dialog::confirm(
+        "Accept Tests",
+        message,
+        verb,
+        DialogAction::AcceptTests { run: run_after },
+    ));
+}
+
+fn accept_tests_command(app: &mut App, run_after: bool) {
+    let Some(pending) = app.pending_tests.take() else {
+        app.status_message = Some("No generated tests are waiting to be accepted".to_string());
+        return;
+    };
+    let file = FileChange::capture(&pending.test_path.to_string_lossy(), &pending.contents);
+    if let Err(e) = write_tests(&pending) {
+        app.status_message = Some(format!(
+            "Failed to write {}: {}",
+            pending.test_path.display(),
+            e
+        ));
+        return;
+    }
+    app.status_message = Some(format!("Wrote {}", pending.test_path.display()));
+    app.record_change(
+        "tests",
+        &format!("Add tests for {}", pending.source_file),
+        vec![file],
+    );
+    if run_after {
+        let report = run_tests(&pending).unwrap_or_else(|e| format!("Could not run tests: {}", e));
+        app.messages.push(Message::ai(report));
+        app.persist_session();
+    }
+}
+
+/// Asks for a new plan, or reopens the current one when no goal is given
+async fn plan_refactor_command(app: &mut App, goal: &str) {
+    if goal.is_empty() {
+        app.open_refactor_plan();
+        return;
+    }
+
+    app.messages
+        .push(Message::user(format!("/plan-refactor {}", goal)));
+    match plan_refactor(goal, &mut app.chatbot).await {
+        Ok(plan) => {
+            app.messages.push(Message::ai(format!(
+                "Refactor plan for \"{}\": {} steps across {} files. Enter on a step generates its edits.",
+                goal,
+                plan.steps.len(),
+                plan.files.len()
+            )));
+            app.refactor_plan = Some(plan);
+            app.pending_edits.clear();
+            app.pending_step = None;
+            app.open_refactor_plan();
+        }
+        Err(e) => app
+            .messages
+            .push(Message::ai(format!("Planning \"{}\" failed: {}", goal, e))),
+    }
+    app.persist_session();
+}
+
+/// Proposes per-file patches for a mechanical change, or reopens the current sweep
+async fn sweep_command(app: &mut App, description: &str) {
+    if description.is_empty() {
+        app.open_sweep();
+        return;
+    }
+
+    app.messages
+        .push(Message::user(format!("/sweep {}", description)));
+    let root = app.project_root.clone();
+    match plan_sweep(description, &root, &mut app.chatbot).await {
+        Ok(sweep) if sweep.patches.is_empty() => app.messages.push(Message::ai(format!(
+            "No files containing {} need changes.",
+            sweep.patterns.join(", ")
+        ))),
+        Ok(sweep) => {
+            let mut summary = format!(
+                "Sweep \"{}\": patches for {} files matching {}. Review them, Space to leave one out, Enter to apply.",
+                description,
+                sweep.patches.len(),
+                sweep.patterns.join(", ")
+            );
+            if !sweep.skipped.is_empty() {
+                summary.push_str(&format!(
+                    "\n\nSkipped (private or over the limit): {}",
+                    sweep.skipped.join(", ")
+                ));
+            }
+            let mut message = Message::ai(summary);
+            message.context_files = sweep
+                .patches
+                .iter()
+                .map(|patch| patch.edit.path.clone())
+                .collect();
+            app.messages.push(message);
+            app.sweep = Some(sweep);
+            app.open_sweep();
+        }
+        Err(e) => app.messages.push(Message::ai(format!(
+            "Sweep \"{}\" failed: {}",
+            description, e
+        ))),
+    }
+    app.persist_session();
+}
+
+/// Generates the edits for the highlighted plan step and shows them as a diff in chat
+pub async fn execute_plan_step(app: &mut App) {
+    let Some(plan) = app.refactor_plan.clone() else {
+        return;
+    };
+    let index = app.selected_plan_step;
+    let Some(step) = plan.steps.get(index) else {
+        return;
+    };
+
+    app.state = AppState::Chat;
+    app.messages.push(Message::user(format!(
+        "Step {}: {}",
+        index + 1,
+        step.description
+    )));
+    match execute_step(&plan, index, &mut app.chatbot).await {
+        Ok(edits) if edits.is_empty() => {
+            app.messages.push(Message::ai(format!(
+                "Step {} needs no changes. Tick it off with Space in the plan.",
+                index + 1
+            )));
+        }
+        Ok(edits) => {
+            let diffs: Vec<String> = edits
+                .iter()
+                .map(|edit| format!("{}\n{}", edit.path, edit.diff))
+                .collect();
+            let mut message = Message::ai(format!(
+                "Proposed edits for step {}:\n\n{}\n\n/accept to write them, /reject to discard.",
+                index + 1,
+                diffs.join("\n")
+            ));
+            message.context_files = edits.iter().map(|edit| edit.path.clone()).collect();
+            app.messages.push(message);
+            app.pending_edits = edits;
+            app.pending_step = Some(index);
+        }
+        Err(e) => app
+            .messages
+            .push(Message::ai(format!("Step {} failed: {}", index + 1, e))),
+    }
+    app.persist_session();
+}
+
+/// Offers every file-labeled code block of an answer as one set of edits, previewed as
+/// a combined diff and written together with /accept
+pub fn extract_files(app: &mut App, message_index: usize) {
+    let Some(message) = app.messages.get(message_index) else {
+        return;
+    };
+    let blocks = labeled_blocks(&message.content);
+    if blocks.is_empty() {
+        app.status_message = Some(
+            "No code blocks labeled with a file, like \"// src/a.rs\" on their first line"
+                .to_string(),
+        );
+        return;
+    }
+    let edits = edits_for(&blocks, &app.project_root);
+    if edits.is_empty() {
+        app.status_message = Some(format!(
+            "All {} labeled files already match the answer",
+            blocks.len()
+        ));
+        return;
+    }
+    let diffs: Vec<String> = edits
+        .iter()
+        .map(|edit| format!("{}\n{}", edit.path, edit.diff))
+        .collect();
+    let mut preview = Message::ai(format!(
+        "Edits from the answer's {} labeled files:\n\n{}\n\n/accept to write them all, /reject to discard.",
+        edits.len(),
+        diffs.join("\n")
+    ));
+    preview.context_files = edits.iter().map(|edit| edit.path.clone()).collect();
+    app.messages.push(preview);
+    app.pending_edits = edits;
+    app.pending_step = None;
+    app.chat_focus = ChatFocus::Input;
+    app.persist_session();
+}
+
+fn confirm_accept_edits(app: &mut App) {
+    let files: Vec<&str> = app
+        .pending_edits
+        .iter()
+        .map(|edit| edit.path.as_str())
+        .collect();
+    app.open_dialog(Dialog::confirm(
+        "Apply Edits",
+        format!("Overwrite {} files?\n\n{}", files.len(), files.join("\n")),
+        "Apply",
+        DialogAction::AcceptEdits,
+    ));
+}
+
+fn accept_edits_command(app: &mut App) {
+    let edits = std::mem::take(&mut app.pending_edits);
+    let step = app.pending_step.take();
+    let files: Vec<FileChange> = edits
+        .iter()
+        .map(|edit| FileChange::capture(&edit.path, &edit.contents))
+        .collect();
+    if let Err(e) = write_edits(&edits) {
+        app.status_message = Some(format!("Failed to write edits: {}", e));
+        return;
+    }
+    let mut description = format!("Write {} files from an answer", edits.len());
+    if let Some(step) = step.and_then(|i| app.refactor_plan.as_mut()?.steps.get_mut(i)) {
+        step.done = true;
+        description = step.description.clone();
+    }
+    app.status_message = Some(format!("Wrote {} files", edits.len()));
+    app.record_change("edits", &description, files);
+    app.persist_session();
+}
+
+// `/changes` lists what this session wrote to the project; `/changes patch` exports it
+// as one patch and `/changes branch [name]` as a branch with a commit per change
+fn changes_command(app: &mut App, argument: &str) {
+    let ledger = ChangeLedger::load(&app.config.data_path(), &app.session_id);
+    if ledger.changes.is_empty() {
+        app.status_message = Some("No changes applied in this session yet".to_string());
+        return;
+    }
+    let (action, name) = argument
+        .split_once(' ')
+        .map(|(action, name)| (action, name.trim()))
+        .unwrap_or((argument, ""));
+    let root = repo_root(&app.project_root).unwrap_or_else(|| app.project_root.clone());
+    app.status_message = Some(match action {
+        "" => {
+            app.messages.push(Message::ai(format!(
+                "Changes applied in this session ({}):\n\n{}\n\n/changes patch exports them as one patch, /changes branch [name] as a branch with a commit each.",
+                ledger.changes.len(),
+                ledger.summary()
+            )));
+            format!("{} changes applied", ledger.changes.len())
+        }
+        "patch" => match ledger.export_patch(&app.config.data_path(), &root) {
+            Ok(path) => format!(
+                "Exported {} changes to {}",
+                ledger.changes.len(),
+                path.display()
+            ),
+            Err(e) => format!("Patch export failed: {}", e),
+        },
+        "branch" => {
+            let branch = match name {
+                "" => format!("sagacity/{}", app.session_id),
+                name => name.to_string(),
+            };
+            match repo_root(&app.project_root) {
+                Some(repo) => match ledger.export_branch(&repo, &branch, &app.config.data_path()) {
+                    Ok(count) => format!("Created branch {} with {} commits", branch, count),
+                    Err(e) => format!("Branch export failed: {}", e),
+                },
+                None => "Branch export needs a git repository".to_string(),
+            }
+        }
+        _ => "Usage: /changes, /changes patch, /changes branch [name]".to_string(),
+    });
+}
+
+fn include_imports_command(app: &mut App) {
+    let suggested = std::mem::take(&mut app.chatbot.suggested_imports);
+    if suggested.is_empty() {
+        app.status_message = Some("No imported modules to include".to_string());
+        return;
+    }
+    for file in suggested {
+        app.chatbot.pin_file(file);
+    }
+    app.status_message = Some(format!(
+        "Pinned to context: {}",
+        app.chatbot.pinned_files.join(", ")
+    ));
+}
+
+/// Lists the question templates, or starts filling in the named one
+fn template_command(app: &mut App, argument: &str) {
+    if argument.is_empty() {
+        let list: Vec<String> = app
+            .config
+            .question_templates
+            .iter()
+            .map(|(name, template)| format!("- `{}`: {}", name, template))
+            .collect();
+        app.messages.push(Message::ai(format!(
+            "Question templates ({}):\n\n{}",
+            list.len(),
+            list.join("\n")
+        )));
+        app.status_message =
+            Some("/template <name> asks one, prompting for its variables".to_string());
+        return;
+    }
+    app.start_template(argument);
+}
+
+/// Shows the workspace's member crates, or restricts retrieval to one (`all` lifts it)
+fn crate_command(app: &mut App, argument: &str) {
+    let Some(workspace) = &app.chatbot.workspace else {
+        app.status_message = Some("This project isn't a multi-crate Cargo workspace".to_string());
+        return;
+    };
+    app.status_message = Some(match argument {
+        "" => {
+            app.messages.push(Message::ai(format!(
+                "Workspace members ({}):\n\n{}",
+                workspace.members.len(),
+                workspace.member_graph()
+            )));
+            match &app.chatbot.crate_filter {
+                Some(name) => format!("Retrieval is restricted to {}; /crate all lifts it", name),
+                None => "Retrieval searches every crate; /crate <name> restricts it".to_string(),
+            }
+        }
+        "all" => {
+            app.chatbot.crate_filter = None;
+            "Retrieval searches every crate".to_string()
+        }
+        name if workspace.has_crate(name) => {
+            app.chatbot.crate_filter = Some(name.to_string());
+            format!("Retrieval is restricted to {}", name)
+        }
+        name => format!("No workspace member named \"{}\"", name),
+    });
+}
+
+// `/project add [name]` registers the current index for questions spanning projects;
+// `/project remove <name>` unregisters one
+fn project_command(app: &mut App, argument: &str) {
+    let (action, name) = argument
+        .split_once(' ')
+        .map(|(action, name)| (action, name.trim()))
+        .unwrap_or((argument, ""));
+    match (action, name) {
+        ("add", name) => app.register_project(name),
+        ("remove", name) if !name.is_empty() => app.unregister_project(name),
+        _ => {
+            app.status_message =
+                Some("Usage: /project add [name], /project remove <name>".to_string())
+        }
+    }
+}
+
+// `/style concise|detailed|normal`, `/style code on|off`, `/style lang <language>|off`
+// or `/style reset`; saved for the current project
+fn style_command(app: &mut App, argument: &str) {
+    const USAGE: &str =
+        "Usage: /style concise|detailed|normal, /style code on|off, /style lang <language>|off, /style reset";
+    let style = &mut app.chatbot.style;
+    let (setting, value) = argument
+        .split_once(' ')
+        .map(|(setting, value)| (setting, value.trim()))
+        .unwrap_or((argument, ""));
+    match (setting, value) {
+        ("", _) => {
+            app.status_message = Some(format!("Answer style: {}. {}", style.describe(), USAGE));
+            return;
+        }
+        ("concise", "") => style.verbosity = Some(Verbosity::Concise),
+        ("detailed", "") => style.verbosity = Some(Verbosity::Detailed),
+        ("normal", "") => style.verbosity = None,
+        ("code", "on") => style.always_code = true,
+        ("code", "off") => style.always_code = false,
+        ("lang", "off") => style.language = None,
+        ("lang", language) if !language.is_empty() => style.language = Some(language.to_string()),
+        ("reset", "") => style.replace_with(&app.config.answer_style),
+        _ => {
+            app.status_message = Some(USAGE.to_string());
+            return;
+        }
+    }
+    app.status_message = Some(match style.save() {
+        Ok(()) => format!("Answer style for this project: {}", style.describe()),
+        Err(e) => format!("Failed to save the answer style: {}", e),
+    });
+}
+
+/// Lists the project glossary, or adds (`term = definition`) or removes (`rm term`) a term
+fn glossary_command(app: &mut App, argument: &str) {
+    let glossary = &mut app.chatbot.glossary;
+    if argument.is_empty() {
+        if glossary.entries.is_empty() {
+            app.status_message = Some(
+                "The glossary is empty. Usage: /glossary <term> = <definition>, /glossary rm <term>"
+                    .to_string(),
+            );
+            return;
+        }
+        let entries: Vec<String> = glossary
+            .entries
+            .iter()
+            .map(|(term, definition)| format!("- **{}**: {}", term, definition))
+            .collect();
+        app.messages.push(Message::ai(format!(
+            "Project glossary ({} terms):\n\n{}",
+            entries.len(),
+            entries.join("\n")
+        )));
+        return;
+    }
+
+    let status = if let Some(term) = argument.strip_prefix("rm ") {
+        match glossary.entries.remove(term.trim()) {
+            Some(_) => format!("Removed \"{}\" from the glossary", term.trim()),
+            None => format!("\"{}\" is not in the glossary", term.trim()),
+        }
+    } else if let Some((term, definition)) = argument.split_once('=') {
+        let (term, definition) = (term.trim(), definition.trim());
+        if term.is_empty() || definition.is_empty() {
+            app.status_message = Some("Usage: /glossary <term> = <definition>".to_string());
+            return;
+        }
+        glossary
+            .entries
+            .insert(term.to_string(), definition.to_string());
+        format!(
+            "Defined \"{}\"; questions mentioning it will use this meaning",
+            term
+        )
+    } else {
+        app.status_message = Some(match glossary.entries.get(argument) {
+            Some(definition) => format!("{}: {}", argument, definition),
+            None => format!("\"{}\" is not in the glossary", argument),
+        });
+        return;
+    };
+    app.status_message = Some(match glossary.save() {
+        Ok(()) => status,
+        Err(e) => format!("Failed to save the glossary: {}", e),
+    });
+}
+
+/// Sets the per-turn time budget in seconds, or turns it off
+fn timebox_command(app: &mut App, argument: &str) {
+    let budget = match argument {
+        "" => {
+            app.status_message = Some(match app.config.turn_time_budget_secs {
+                Some(secs) => format!(
+                    "Answers are cut off after {}s. Usage: /timebox <seconds|off>",
+                    secs
+                ),
+                None => "No time budget. Usage: /timebox <seconds|off>".to_string(),
+            });
+            return;
+        }
+        "off" => None,
+        secs => match secs.parse::<u64>() {
+            Ok(secs) if secs > 0 => Some(secs),
+            _ => {
+                app.status_message = Some("Usage: /timebox <seconds|off>".to_string());
+                return;
+            }
+        },
+    };
+    app.config.turn_time_budget_secs = budget;
+    app.chatbot.time_budget = budget.map(Duration::from_secs);
+    app.status_message = Some(match (save_config(&app.config), budget) {
+        (Ok(()), Some(secs)) => format!("Answers are cut off after {}s", secs),
+        (Ok(()), None) => "Time budget off".to_string(),
+        (Err(e), _) => format!("Failed to save config: {}", e),
+    });
+}
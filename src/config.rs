@@ -0,0 +1,261 @@
+// src/config.rs
+use home::home_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::constants::*;
+use crate::embeddings::EmbeddingConfig;
+use crate::org_policy::OrgPolicy;
+use crate::privacy::PrivacyRule;
+use crate::profiles::DEFAULT_PROFILE;
+use crate::question_templates::default_question_templates;
+use crate::sampling::SamplingConfig;
+use crate::style::AnswerStyle;
+use crate::summary_postprocess::PostProcessConfig;
+
+pub const CONFIG_DIR: &str = ".sagacity";
+pub const CONFIG_FILE: &str = "config.json";
+
+/// User configuration loaded from ~/.sagacity/config.json
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Config {
+    pub data_dir: String,
+    #[serde(default = "default_model")]
+    pub model: String,
+    // Add imported local modules to context automatically instead of suggesting them
+    #[serde(default)]
+    pub auto_include_imports: bool,
+    #[serde(default)]
+    pub summary_postprocessing: PostProcessConfig,
+    // ASCII borders, textual status prefixes and linear layout for screen readers
+    #[serde(default)]
+    pub accessibility_mode: bool,
+    // White on black, with focus and selection shown by reverse video instead of hue
+    #[serde(default)]
+    pub high_contrast: bool,
+    // Files or globs whose contents are summary-only or never sent
+    #[serde(default)]
+    pub privacy_rules: Vec<PrivacyRule>,
+    // Globs left out of indexing, on top of .gitignore, .ignore and .sagacityignore
+    #[serde(default)]
+    pub index_exclude: Vec<String>,
+    // Summaries the model scores for relevance, picked by a lexical match against the
+    // question from larger indexes; 0 sends every summary
+    #[serde(default = "default_relevance_candidates")]
+    pub relevance_candidates: usize,
+    // Profiles whose top candidates are re-ranked by a second model pass; unset follows
+    // each profile's own choice
+    #[serde(default)]
+    pub rerank_profiles: Option<Vec<String>>,
+    // Log response fields and block types this version doesn't know, names only, and
+    // suggest an upgrade when one appears
+    #[serde(default = "default_schema_drift_checks")]
+    pub schema_drift_checks: bool,
+    // Shared library that counts tokens in place of the built-in tokenizer, for models
+    // behind gateways it doesn't know; needs a build with the tokenizer-plugins feature
+    #[serde(default)]
+    pub tokenizer_plugin: Option<String>,
+    // OpenAI-compatible embeddings endpoint used for retrieval instead of Claude
+    #[serde(default)]
+    pub embeddings: Option<EmbeddingConfig>,
+    // Task profile chosen with /mode
+    #[serde(default = "default_task_profile")]
+    pub task_profile: String,
+    // Speaker bars, relative timestamps and day separators in the transcript
+    #[serde(default)]
+    pub transcript_gutter: bool,
+    // Temperature and top_p per operation: summaries, relevance scoring, chat, ...
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+    // Run `cargo check` on Rust blocks of answers and badge them with the result
+    #[serde(default)]
+    pub check_generated_rust: bool,
+    // Regexes masked, besides emails, tokens and internal hostnames, when a transcript
+    // is exported or shared
+    #[serde(default)]
+    pub scrub_patterns: Vec<String>,
+    // Seconds an answer may stream before it is cut off and kept as a partial answer
+    #[serde(default)]
+    pub turn_time_budget_secs: Option<u64>,
+    // Ask for JSON answers with a confidence and cited sources, rendered in the transcript
+    #[serde(default)]
+    pub structured_answers: bool,
+    // Changed files re-summarized per index pass, busiest first; the rest wait for the
+    // next pass. None re-summarizes every changed file
+    #[serde(default)]
+    pub summary_refresh_limit: Option<usize>,
+    // Follow-up questions answered from the previous turn's files before retrieval runs
+    // again; 0 always retrieves
+    #[serde(default = "default_context_carry_turns")]
+    pub context_carry_turns: usize,
+    // Answer length, code and language preferences for projects without a /style of their own
+    #[serde(default)]
+    pub answer_style: AnswerStyle,
+    // Answer from file summaries at once, then post a refined answer from full contents
+    // when it says something different
+    #[serde(default)]
+    pub two_phase_answers: bool,
+    // Open each new chat session with a map of the repository built from the file summaries
+    #[serde(default)]
+    pub repo_map: bool,
+    // Send short questions with little context to the model with the lowest observed
+    // latency instead of the profile's model
+    #[serde(default)]
+    pub fast_mode: bool,
+    // Questions asked with /template <name>; {file}, {symbol} and other variables are
+    // prompted for, files and symbols with completion against the index
+    #[serde(default = "default_question_templates")]
+    pub question_templates: BTreeMap<String, String>,
+    // Record every request sent to Claude and its response in a hash-chained log in the
+    // data directory; `sagacity verify-audit` checks it hasn't been altered
+    #[serde(default)]
+    pub conversation_audit: bool,
+    // Where /save writes messages and code blocks, one directory per session; a relative
+    // path is taken from the project root
+    #[serde(default = "default_responses_dir")]
+    pub responses_dir: String,
+    // At startup, write a digest of the last day's questions, findings, files and cost if
+    // there isn't one yet; /digest shows or exports it
+    #[serde(default)]
+    pub daily_digest: bool,
+    // Before sending, check that the identifiers and files a question names appear in the
+    // index, and offer to search for those that don't
+    #[serde(default = "default_question_lint")]
+    pub question_lint: bool,
+    // Language of menus, key hints and help, such as "es"; empty follows LANG, and
+    // strings without a translation stay in English
+    #[serde(default)]
+    pub locale: String,
+    // Personal access token with the gist scope, for uploading messages and code blocks
+    // as secret gists
+    #[serde(default)]
+    pub github_token: Option<String>,
+    // Send turns older than the last few as summaries, merged into one running summary as
+    // they age, so long sessions don't resend their whole history with every question
+    #[serde(default = "default_compact_history")]
+    pub compact_history: bool,
+    // Org-wide limits loaded at startup; never written to the local config
+    #[serde(skip)]
+    pub org_policy: Option<OrgPolicy>,
+}
+
+fn default_schema_drift_checks() -> bool {
+    true
+}
+
+fn default_compact_history() -> bool {
+    true
+}
+
+fn default_question_lint() -> bool {
+    true
+}
+
+fn default_context_carry_turns() -> usize {
+    3
+}
+
+fn default_relevance_candidates() -> usize {
+    50
+}
+
+fn default_task_profile() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+fn default_responses_dir() -> String {
+    "ai_responses".to_string()
+}
+
+fn default_model() -> String {
+    DEFAULT_MODEL.to_string()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            data_dir: config_dir().display().to_string(),
+            model: default_model(),
+            auto_include_imports: false,
+            summary_postprocessing: PostProcessConfig::default(),
+            accessibility_mode: false,
+            high_contrast: false,
+            privacy_rules: Vec::new(),
+            index_exclude: Vec::new(),
+            relevance_candidates: default_relevance_candidates(),
+            rerank_profiles: None,
+            tokenizer_plugin: None,
+            schema_drift_checks: default_schema_drift_checks(),
+            embeddings: None,
+            task_profile: default_task_profile(),
+            transcript_gutter: false,
+            sampling: SamplingConfig::default(),
+            check_generated_rust: false,
+            scrub_patterns: Vec::new(),
+            turn_time_budget_secs: None,
+            structured_answers: false,
+            summary_refresh_limit: None,
+            context_carry_turns: default_context_carry_turns(),
+            answer_style: AnswerStyle::default(),
+            two_phase_answers: false,
+            repo_map: false,
+            fast_mode: false,
+            question_templates: default_question_templates(),
+            conversation_audit: false,
+            responses_dir: default_responses_dir(),
+            daily_digest: false,
+            question_lint: default_question_lint(),
+            locale: String::new(),
+            github_token: None,
+            compact_history: default_compact_history(),
+            org_policy: None,
+        }
+    }
+}
+
+impl Config {
+    /// Directory holding sessions and other persisted state, with `~` expanded
+    pub fn data_path(&self) -> PathBuf {
+        PathBuf::from(shellexpand::tilde(&self.data_dir).into_owned())
+    }
+
+    /// Directory /save writes into for a project, with `~` expanded
+    pub fn responses_path(&self, project_root: &Path) -> PathBuf {
+        project_root.join(shellexpand::tilde(&self.responses_dir).into_owned())
+    }
+
+    /// Local privacy rules plus the org policy's protected globs
+    pub fn privacy_rules(&self) -> Vec<PrivacyRule> {
+        let mut rules = self.privacy_rules.clone();
+        if let Some(policy) = &self.org_policy {
+            rules.extend(policy.privacy_rules());
+        }
+        rules
+    }
+
+    /// Local scrub patterns plus the org policy's redaction patterns
+    pub fn scrub_patterns(&self) -> Vec<String> {
+        let mut patterns = self.scrub_patterns.clone();
+        if let Some(policy) = &self.org_policy {
+            patterns.extend(policy.redaction_patterns.iter().cloned());
+        }
+        patterns
+    }
+}
+
+pub fn config_dir() -> PathBuf {
+    home_dir().unwrap_or(PathBuf::from(".")).join(CONFIG_DIR)
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join(CONFIG_FILE)
+}
+
+pub fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(config_dir())?;
+    let serialized = serde_json::to_string_pretty(config)?;
+    fs::write(config_path(), serialized)?;
+    Ok(())
+}
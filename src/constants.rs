@@ -1,15 +1,10 @@
-// UI Constants
-pub const HEAVY_DOWN_AND_RIGHT: char = '┏';
-pub const HEAVY_DOWN_AND_LEFT: char = '┓';
-pub const HEAVY_UP_AND_RIGHT: char = '┗';
-pub const HEAVY_UP_AND_LEFT: char = '┛';
-pub const HEAVY_HORIZONTAL: char = '━';
-pub const HEAVY_VERTICAL: char = '┃';
-
-pub const HEADER_WIDTH: usize = 60;
-
 // API Constants
 pub const CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
 pub const ANTHROPIC_VERSION: &str = "2023-06-01";
 pub const DEFAULT_MODEL: &str = "claude-3-sonnet-20240229";
+pub const STRONG_MODEL: &str = "claude-3-opus-20240229";
 pub const DEFAULT_MAX_TOKENS: usize = 4000;
+pub const MAX_IMPORT_EXPANSION: usize = 5;
+pub const INDEX_CHECKPOINT_INTERVAL: usize = 10;
+// Retries with less context after the API reports the prompt is too long
+pub const PROMPT_TOO_LONG_RETRIES: usize = 3;
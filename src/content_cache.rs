@@ -0,0 +1,67 @@
+// src/content_cache.rs
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+type ContentHash = [u8; 32];
+
+/// File contents kept between questions, by path and the hash of what was read, so prompt
+/// assembly only decodes and copies a file again once its contents changed
+#[derive(Default)]
+pub struct ContentCache {
+    entries: HashMap<String, (ContentHash, String)>,
+}
+
+impl ContentCache {
+    /// The file's contents, from the cache while they hash the same as when cached
+    pub fn read(&mut self, path: &str) -> io::Result<&str> {
+        let bytes = fs::read(path)?;
+        let hash: ContentHash = Sha256::digest(&bytes).into();
+        let stale = self
+            .entries
+            .get(path)
+            .is_none_or(|(cached, _)| *cached != hash);
+        if stale {
+            let text = String::from_utf8(bytes).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                )
+            })?;
+            self.entries.insert(path.to_string(), (hash, text));
+        }
+        Ok(self.entries[path].1.as_str())
+    }
+
+    /// Drops a file the indexer saw change or disappear
+    pub fn invalidate(&mut self, path: &str) {
+        self.entries.remove(path);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_file_is_served_again_only_while_its_contents_hash_the_same() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let path_str = path.to_string_lossy().to_string();
+        let mut cache = ContentCache::default();
+
+        fs::write(&path, "fn one() {}").unwrap();
+        assert_eq!(cache.read(&path_str).unwrap(), "fn one() {}");
+        // Same length, so only the contents tell the versions apart
+        fs::write(&path, "fn two() {}").unwrap();
+        assert_eq!(cache.read(&path_str).unwrap(), "fn two() {}");
+
+        fs::write(&path, [0xff, 0xfe]).unwrap();
+        assert!(cache.read(&path_str).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+// src/conversation_audit.rs
+//! Tamper-evident record of every request sent to Claude and the response it got: each
+//! line of the JSONL file carries the SHA-256 of the previous line's hash and its own
+//! contents, so editing, reordering or deleting an entry breaks the chain after it.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::claude_client::{ApiFuture, ApiResponse, ClaudeClient};
+
+pub const CONVERSATION_AUDIT_FILE: &str = "conversation_audit.jsonl";
+// The previous hash of the first entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One request and its response as they went over the wire
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub endpoint: String,
+    pub request: Value,
+    // 0 when the request never got a response
+    pub status: u16,
+    // The raw body, or the error that stopped the request
+    pub response: String,
+    pub prev_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ChainedEntry {
+    #[serde(flatten)]
+    entry: AuditEntry,
+    hash: String,
+}
+
+fn entry_hash(entry: &AuditEntry) -> Result<String, serde_json::Error> {
+    let mut hasher = Sha256::new();
+    hasher.update(entry.prev_hash.as_bytes());
+    hasher.update(serde_json::to_string(entry)?.as_bytes());
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The append-only log; entries from concurrent requests are chained in the order they
+/// finish
+#[derive(Debug)]
+pub struct ConversationAudit {
+    path: PathBuf,
+    // Sequence number and hash of the last entry
+    head: Mutex<(u64, String)>,
+}
+
+impl ConversationAudit {
+    /// Opens the log in the data directory, continuing the chain of any existing entries
+    pub fn open(data_dir: &Path) -> ConversationAudit {
+        let _ = fs::create_dir_all(data_dir);
+        let path = data_dir.join(CONVERSATION_AUDIT_FILE);
+        let head = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| {
+                let last = contents
+                    .lines()
+                    .rev()
+                    .find(|line| !line.trim().is_empty())?;
+                let chained: ChainedEntry = serde_json::from_str(last).ok()?;
+                Some((chained.entry.seq + 1, chained.hash))
+            })
+            .unwrap_or((0, GENESIS_HASH.to_string()));
+        ConversationAudit {
+            path,
+            head: Mutex::new(head),
+        }
+    }
+
+    pub fn append(
+        &self,
+        endpoint: String,
+        request: Value,
+        status: u16,
+        response: String,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut head = self.head.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = AuditEntry {
+            seq: head.0,
+            timestamp: Utc::now(),
+            endpoint,
+            request,
+            status,
+            response,
+            prev_hash: head.1.clone(),
+        };
+        let hash = entry_hash(&entry)?;
+        let line = serde_json::to_string(&ChainedEntry {
+            entry,
+            hash: hash.clone(),
+        })?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        *head = (head.0 + 1, hash);
+        Ok(())
+    }
+}
+
+/// Checks every entry's hash and link to the one before; the number of entries when the
+/// chain is intact, otherwise where it breaks
+pub fn verify_chain(path: &Path) -> Result<usize, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut count = 0;
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = i + 1;
+        let chained: ChainedEntry = serde_json::from_str(line)
+            .map_err(|e| format!("Line {}: not a valid entry: {}", line_number, e))?;
+        if chained.entry.seq != count as u64 {
+            return Err(format!(
+                "Line {}: expected entry {}, found {}; entries were removed or reordered",
+                line_number, count, chained.entry.seq
+            ));
+        }
+        if chained.entry.prev_hash != prev_hash {
+            return Err(format!(
+                "Line {}: does not follow the entry before it",
+                line_number
+            ));
+        }
+        let hash = entry_hash(&chained.entry).map_err(|e| e.to_string())?;
+        if hash != chained.hash {
+            return Err(format!(
+                "Line {}: contents changed after they were recorded",
+                line_number
+            ));
+        }
+        prev_hash = hash;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Passes requests on to another client and records each with its full response
+pub struct AuditedClient {
+    inner: Arc<dyn ClaudeClient>,
+    audit: Arc<ConversationAudit>,
+}
+
+impl AuditedClient {
+    pub fn new(inner: Arc<dyn ClaudeClient>, audit: ConversationAudit) -> AuditedClient {
+        AuditedClient {
+            inner,
+            audit: Arc::new(audit),
+        }
+    }
+}
+
+impl ClaudeClient for AuditedClient {
+    fn endpoint(&self) -> String {
+        self.inner.endpoint()
+    }
+
+    fn post<'a>(&'a self, api_key: &'a str, request: &'a Value) -> ApiFuture<'a> {
+        Box::pin(async move {
+            let endpoint = self.inner.endpoint();
+            let mut response = match self.inner.post(api_key, request).await {
+                Ok(response) => response,
+                Err(e) => {
+                    let _ = self.audit.append(endpoint, request.clone(), 0, e.clone());
+                    return Err(e);
+                }
+            };
+            // The body is recorded once it has all arrived, or once the reader gives up
+            let status = response.status;
+            let audit = self.audit.clone();
+            let request = request.clone();
+            let (sender, chunks) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                let mut body = Vec::new();
+                loop {
+                    match response.chunk().await {
+                        Ok(Some(chunk)) => {
+                            body.extend_from_slice(&chunk);
+                            if sender.send(Ok(chunk)).is_err() {
+                                break;
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(e) => {
+                            body.extend_from_slice(format!("\n[read failed: {}]", e).as_bytes());
+                            let _ = sender.send(Err(e));
+                            break;
+                        }
+                    }
+                }
+                let body = String::from_utf8_lossy(&body).into_owned();
+                let _ = audit.append(endpoint, request, status, body);
+            });
+            Ok(ApiResponse::streaming(status, chunks))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edited_entry_breaks_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = ConversationAudit::open(dir.path());
+        for answer in ["first", "second", "third"] {
+            audit
+                .append(
+                    "mock".to_string(),
+                    serde_json::json!({ "q": answer }),
+                    200,
+                    answer.to_string(),
+                )
+                .unwrap();
+        }
+        let path = dir.path().join(CONVERSATION_AUDIT_FILE);
+        assert_eq!(verify_chain(&path), Ok(3));
+
+        // Reopening continues the same chain
+        ConversationAudit::open(dir.path())
+            .append("mock".to_string(), Value::Null, 200, "fourth".to_string())
+            .unwrap();
+        assert_eq!(verify_chain(&path), Ok(4));
+
+        let tampered = fs::read_to_string(&path)
+            .unwrap()
+            .replace("\"second\"", "\"edited\"");
+        fs::write(&path, tampered).unwrap();
+        assert!(verify_chain(&path).unwrap_err().starts_with("Line 2:"));
+    }
+
+    #[test]
+    fn removed_or_reordered_entries_are_named() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = ConversationAudit::open(dir.path());
+        for answer in ["first", "second", "third"] {
+            audit
+                .append("mock".to_string(), Value::Null, 200, answer.to_string())
+                .unwrap();
+        }
+        let path = dir.path().join(CONVERSATION_AUDIT_FILE);
+        let lines: Vec<String> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(
+            verify_chain(&path),
+            Err("Line 2: expected entry 1, found 2; entries were removed or reordered".to_string())
+        );
+        fs::write(&path, format!("{}\n", lines[1])).unwrap();
+        assert!(verify_chain(&path)
+            .unwrap_err()
+            .starts_with("Line 1: expected entry 0"));
+        fs::write(&path, format!("{}\nnot json\n", lines[0])).unwrap();
+        assert!(verify_chain(&path)
+            .unwrap_err()
+            .starts_with("Line 2: not a valid entry"));
+    }
+
+    #[tokio::test]
+    async fn requests_are_recorded_with_their_whole_body_or_their_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = Arc::new(crate::claude_client::MockClient::default());
+        inner.respond(200, "{\"content\": []}");
+        let client = AuditedClient::new(inner, ConversationAudit::open(dir.path()));
+        let request = serde_json::json!({ "model": "m" });
+
+        let body = client
+            .post("key", &request)
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "{\"content\": []}");
+        // Nothing is queued, so the second request fails before any response
+        assert!(client.post("key", &request).await.is_err());
+
+        let path = dir.path().join(CONVERSATION_AUDIT_FILE);
+        assert_eq!(verify_chain(&path), Ok(2));
+        let entries: Vec<ChainedEntry> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            (entries[0].entry.status, entries[0].entry.response.as_str()),
+            (200, "{\"content\": []}")
+        );
+        assert_eq!(
+            (entries[1].entry.status, entries[1].entry.response.as_str()),
+            (0, "No mock response queued")
+        );
+        assert_eq!(entries[1].entry.request, request);
+    }
+}
@@ -0,0 +1,105 @@
+// src/dataset.rs
+use chrono::Local;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scrubber::{ScrubReport, Scrubber};
+use crate::session::Session;
+use crate::ui::chat::Sender;
+use crate::usage::EXPORTS_DIR;
+
+// Retrieval recorded as a tool call, so a reproduction shows which files were sent
+const RETRIEVAL_TOOL: &str = "retrieve_context";
+
+fn retrieval_tool() -> Value {
+    json!({
+        "name": RETRIEVAL_TOOL,
+        "description": "Finds the indexed files relevant to the question and adds them to the context",
+        "input_schema": {
+            "type": "object",
+            "properties": { "query": { "type": "string" } },
+            "required": ["query"]
+        }
+    })
+}
+
+/// One session as a Messages API request body plus the final answer: each answer that
+/// had context files is preceded by the retrieval call and its result
+pub fn session_record(
+    session: &Session,
+    model: &str,
+    system: &str,
+    scrubber: &Scrubber,
+    report: &mut ScrubReport,
+) -> Value {
+    let mut messages: Vec<Value> = Vec::new();
+    let mut last_question = String::new();
+    for (i, message) in session.messages.iter().enumerate() {
+        // An opening repository map isn't a turn of the conversation
+        if message.repo_map {
+            continue;
+        }
+        let content = scrubber.scrub(&message.content, report);
+        match message.sender {
+            Sender::User => {
+                last_question = content.clone();
+                messages.push(json!({ "role": "user", "content": content }));
+            }
+            Sender::AI => {
+                if !message.context_files.is_empty() {
+                    let id = format!("toolu_{}_{}", session.id, i);
+                    messages.push(json!({
+                        "role": "assistant",
+                        "content": [{
+                            "type": "tool_use",
+                            "id": id,
+                            "name": RETRIEVAL_TOOL,
+                            "input": { "query": last_question }
+                        }]
+                    }));
+                    messages.push(json!({
+                        "role": "user",
+                        "content": [{
+                            "type": "tool_result",
+                            "tool_use_id": id,
+                            "content": message.context_files.join("\n")
+                        }]
+                    }));
+                }
+                messages.push(json!({
+                    "role": "assistant",
+                    "content": [{ "type": "text", "text": content }]
+                }));
+            }
+        }
+    }
+    json!({
+        "id": session.id,
+        "title": scrubber.scrub(&session.title, report),
+        "model": model,
+        "system": scrubber.scrub(system, report),
+        "tools": [retrieval_tool()],
+        "messages": messages
+    })
+}
+
+/// Writes one record per line to a JSONL file in the exports folder
+pub fn export_dataset(
+    data_dir: &Path,
+    records: &[Value],
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = data_dir.join(EXPORTS_DIR);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "dataset-{}.jsonl",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+    fs::write(&path, lines)?;
+    Ok(path)
+}
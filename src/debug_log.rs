@@ -0,0 +1,30 @@
+// src/debug_log.rs
+//! Developer diagnostics. The TUI owns the terminal, so nothing may go to stderr while it
+//! runs; with `SAGACITY_DEBUG_LOG=<file>` set, lines are appended to that file instead.
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// Developer setting, read once on the first line logged
+pub const DEBUG_LOG_VAR: &str = "SAGACITY_DEBUG_LOG";
+
+fn log_path() -> Option<&'static PathBuf> {
+    static PATH: OnceLock<Option<PathBuf>> = OnceLock::new();
+    PATH.get_or_init(|| {
+        std::env::var_os(DEBUG_LOG_VAR)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })
+    .as_ref()
+}
+
+/// Appends a tagged line to the debug log, when one is set; failures are ignored
+pub fn write(tag: &str, line: &str) {
+    let Some(path) = log_path() else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "[{}] {}", tag, line);
+    }
+}
@@ -0,0 +1,119 @@
+// src/deepdive.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::chatbot::{complete_with_claude, detect_language, Chatbot};
+use crate::constants::*;
+use crate::open_files::index_path;
+use crate::sampling::Operation;
+
+pub const DEEP_DIVE_FILE: &str = "deepdive_cache.json";
+
+/// A detailed structured analysis of a single file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepDive {
+    pub file_path: String,
+    pub analysis: String,
+    pub generated_at: DateTime<Utc>,
+}
+
+pub fn load_deep_dives() -> HashMap<String, DeepDive> {
+    fs::read_to_string(DEEP_DIVE_FILE)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_deep_dives(
+    deep_dives: &HashMap<String, DeepDive>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let serialized = serde_json::to_string_pretty(deep_dives)?;
+    fs::write(DEEP_DIVE_FILE, serialized)?;
+    Ok(())
+}
+
+// The index's name for a typed path, so later answers find the stored analysis
+fn indexed_file(
+    file_path: &str,
+    root: &Path,
+    index: &HashMap<String, (String, String)>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let key = index_path(file_path, root);
+    if index.contains_key(&key) {
+        Ok(key)
+    } else {
+        Err(format!("{} is not in the index", file_path).into())
+    }
+}
+
+// Function to produce a deep-dive analysis of one file with the strong model
+pub async fn deep_dive_file(
+    file_path: &str,
+    chatbot: &mut Chatbot,
+) -> Result<DeepDive, Box<dyn std::error::Error>> {
+    let file_path = &indexed_file(file_path, &std::env::current_dir()?, &chatbot.index)?;
+    if !chatbot.privacy.allows_contents(file_path) {
+        return Err(format!(
+            "{} is marked private; its contents can't be sent",
+            file_path
+        )
+        .into());
+    }
+    let content = fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
+    let language = detect_language(file_path);
+    let prompt = format!(
+        "Produce a detailed structured analysis of the following {} file ({}). Use exactly these markdown sections:\n\n\
+         ## Responsibilities\n## Public API\n## Invariants\n## TODOs\n\n\
+         Be specific and reference item names from the code.\n\n{}",
+        language, file_path, content
+    );
+
+    let api_key = chatbot.api_key.clone();
+    let analysis = complete_with_claude(
+        &prompt,
+        STRONG_MODEL,
+        &api_key,
+        "deep_dive",
+        Operation::DeepDive,
+        chatbot,
+    )
+    .await?;
+
+    let deep_dive = DeepDive {
+        file_path: file_path.to_string(),
+        analysis,
+        generated_at: Utc::now(),
+    };
+
+    // Store it as an auxiliary summary for future context building
+    let mut deep_dives = load_deep_dives();
+    deep_dives.insert(file_path.to_string(), deep_dive.clone());
+    save_deep_dives(&deep_dives)?;
+    chatbot
+        .deep_dives
+        .insert(file_path.to_string(), deep_dive.analysis.clone());
+
+    Ok(deep_dive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_paths_resolve_to_index_keys() {
+        let root = Path::new("/work/project");
+        let index = HashMap::from([(
+            "./src/main.rs".to_string(),
+            ("Entry point".to_string(), "Rust".to_string()),
+        )]);
+        for typed in ["src/main.rs", "./src/main.rs", "/work/project/src/main.rs"] {
+            assert_eq!(indexed_file(typed, root, &index).unwrap(), "./src/main.rs");
+        }
+        assert!(indexed_file("src/missing.rs", root, &index).is_err());
+    }
+}
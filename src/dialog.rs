@@ -0,0 +1,165 @@
+// src/dialog.rs
+use crate::gist::GistSnippet;
+use crate::AppState;
+
+/// What a dialog asks of the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogKind {
+    // Yes or no, with the given label on the accepting button
+    Confirm(String),
+    // One of several options
+    Choose(Vec<String>),
+    // Free text, prefilled
+    Input,
+}
+
+/// The action a dialog stands in front of, run only once the user accepts
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogAction {
+    ClearHistory,
+    DeleteSession(String),
+    AcceptEdits,
+    AcceptTests {
+        run: bool,
+    },
+    ApplySweep,
+    TakeOverLock,
+    RunShell,
+    FillTemplate,
+    // A question naming code the index doesn't know
+    SendQuestion {
+        question: String,
+        missing: Vec<String>,
+    },
+    UploadGist(GistSnippet),
+}
+
+/// What the user accepted: the chosen option and, for input dialogs, the text
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogChoice {
+    pub option: usize,
+    pub input: String,
+}
+
+/// A modal over the current screen that must be answered before the action runs
+#[derive(Debug, Clone)]
+pub struct Dialog {
+    pub title: String,
+    pub message: String,
+    pub kind: DialogKind,
+    pub action: DialogAction,
+    // Highlighted button; Cancel is always the last one
+    pub selected: usize,
+    pub input: String,
+    // Values Tab completes the input to, for input dialogs
+    pub completions: Vec<String>,
+    pub return_state: AppState,
+}
+
+impl Dialog {
+    fn new(title: &str, message: String, kind: DialogKind, action: DialogAction) -> Self {
+        Dialog {
+            title: title.to_string(),
+            message,
+            kind,
+            action,
+            selected: 0,
+            input: String::new(),
+            completions: Vec::new(),
+            return_state: AppState::MainMenu,
+        }
+    }
+
+    pub fn confirm(title: &str, message: String, label: &str, action: DialogAction) -> Self {
+        let mut dialog = Dialog::new(
+            title,
+            message,
+            DialogKind::Confirm(label.to_string()),
+            action,
+        );
+        // Destructive actions start on Cancel so a stray Enter does nothing
+        dialog.selected = 1;
+        dialog
+    }
+
+    pub fn choose(title: &str, message: String, options: &[&str], action: DialogAction) -> Self {
+        let options = options.iter().map(|option| option.to_string()).collect();
+        Dialog::new(title, message, DialogKind::Choose(options), action)
+    }
+
+    pub fn input(title: &str, message: String, initial: &str, action: DialogAction) -> Self {
+        let mut dialog = Dialog::new(title, message, DialogKind::Input, action);
+        dialog.input = initial.to_string();
+        dialog
+    }
+
+    pub fn with_completions(mut self, completions: Vec<String>) -> Self {
+        self.completions = completions;
+        self
+    }
+
+    /// Completions containing the input, those starting with it first
+    pub fn matching_completions(&self) -> Vec<&str> {
+        let input = self.input.to_lowercase();
+        let (mut matches, containing): (Vec<&str>, Vec<&str>) = self
+            .completions
+            .iter()
+            .map(String::as_str)
+            .filter(|completion| completion.to_lowercase().contains(&input))
+            .partition(|completion| completion.to_lowercase().starts_with(&input));
+        matches.extend(containing);
+        matches
+    }
+
+    // The input completed to the only match, or as far as every match agrees, when that
+    // adds to it
+    fn common_completion(&self) -> Option<String> {
+        let matches = self.matching_completions();
+        let (first, rest) = matches.split_first()?;
+        let mut completed = first.to_string();
+        for other in rest {
+            while !other.starts_with(&completed) {
+                completed.pop();
+            }
+        }
+        (completed.chars().count() > self.input.chars().count()).then_some(completed)
+    }
+
+    /// Completes the input, or moves on to the next control when that adds nothing
+    pub fn complete(&mut self) {
+        match self.common_completion() {
+            Some(completed) => self.input = completed,
+            None => self.move_selection(1),
+        }
+    }
+
+    /// Button labels in order, ending with Cancel
+    pub fn buttons(&self) -> Vec<String> {
+        let mut buttons = match &self.kind {
+            DialogKind::Confirm(label) => vec![label.clone()],
+            DialogKind::Choose(options) => options.clone(),
+            DialogKind::Input => vec!["OK".to_string()],
+        };
+        buttons.push("Cancel".to_string());
+        buttons
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let count = self.buttons().len() as isize;
+        self.selected = (self.selected as isize + delta).rem_euclid(count) as usize;
+    }
+
+    /// The accepted choice, or None when Cancel is highlighted or the input is empty
+    pub fn choice(&self) -> Option<DialogChoice> {
+        if self.selected + 1 >= self.buttons().len() {
+            return None;
+        }
+        if self.kind == DialogKind::Input && self.input.trim().is_empty() {
+            return None;
+        }
+        Some(DialogChoice {
+            option: self.selected,
+            input: self.input.trim().to_string(),
+        })
+    }
+}
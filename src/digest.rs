@@ -0,0 +1,300 @@
+// src/digest.rs
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::chatbot::{complete_with_claude, Chatbot};
+use crate::constants::DEFAULT_MODEL;
+use crate::events::{AppEvent, EventSender};
+use crate::sampling::Operation;
+use crate::transcript_log::{project_file_name, TranscriptTurn};
+use crate::usage::EXPORTS_DIR;
+
+pub const DIGESTS_DIR: &str = "digests";
+// Characters of each answer the findings are drawn from
+const MAX_DIGEST_ANSWER_CHARS: usize = 600;
+// Files listed in a digest, most discussed first
+const MAX_DIGEST_FILES: usize = 15;
+
+/// One day's questions about a project, what they turned up and what they cost
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub date: NaiveDate,
+    pub project: String,
+    pub questions: Vec<String>,
+    pub findings: String,
+    // Context files and how many answers drew on each
+    pub files: Vec<(String, usize)>,
+    pub cost: f64,
+}
+
+/// The turns asked on `date`, in local time
+pub fn turns_on(turns: &[TranscriptTurn], date: NaiveDate) -> Vec<&TranscriptTurn> {
+    turns
+        .iter()
+        .filter(|turn| turn.timestamp.with_timezone(&Local).date_naive() == date)
+        .collect()
+}
+
+fn digest_path(data_dir: &Path, project: &str, date: NaiveDate) -> PathBuf {
+    data_dir
+        .join(DIGESTS_DIR)
+        .join(project_file_name(project))
+        .join(format!("{}.json", date))
+}
+
+/// The latest day before `today` with questions but no digest yet
+pub fn undigested_day(
+    data_dir: &Path,
+    project: &str,
+    turns: &[TranscriptTurn],
+    today: NaiveDate,
+) -> Option<NaiveDate> {
+    let day = turns
+        .iter()
+        .map(|turn| turn.timestamp.with_timezone(&Local).date_naive())
+        .filter(|&day| day < today)
+        .max()?;
+    (!digest_path(data_dir, project, day).exists()).then_some(day)
+}
+
+fn findings_prompt(turns: &[&TranscriptTurn]) -> String {
+    let exchanges: Vec<String> = turns
+        .iter()
+        .filter(|turn| !turn.error)
+        .map(|turn| {
+            let answer: String = turn.answer.chars().take(MAX_DIGEST_ANSWER_CHARS).collect();
+            format!("Q: {}\nA: {}", turn.question.trim(), answer.trim())
+        })
+        .collect();
+    format!(
+        "These are the questions a developer asked about their codebase in one day, with the \
+         start of each answer:\n\n{}\n\n\
+         Write the key findings as a short Markdown bullet list for a standup: what was \
+         learned about the code, decisions or problems uncovered, and open questions. \
+         Leave out pleasantries and don't repeat the questions.",
+        exchanges.join("\n\n")
+    )
+}
+
+impl Digest {
+    pub fn new(
+        date: NaiveDate,
+        project: &str,
+        turns: &[&TranscriptTurn],
+        findings: String,
+    ) -> Digest {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for file in turns.iter().flat_map(|turn| &turn.context_files) {
+            *counts.entry(file.as_str()).or_default() += 1;
+        }
+        let mut files: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(file, count)| (file.to_string(), count))
+            .collect();
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        files.truncate(MAX_DIGEST_FILES);
+        Digest {
+            date,
+            project: project.to_string(),
+            questions: turns
+                .iter()
+                .map(|turn| turn.question.trim().to_string())
+                .collect(),
+            findings: findings.trim().to_string(),
+            files,
+            cost: turns.iter().map(|turn| turn.cost).sum(),
+        }
+    }
+
+    pub fn load(data_dir: &Path, project: &str, date: NaiveDate) -> Option<Digest> {
+        fs::read_to_string(digest_path(data_dir, project, date))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let path = digest_path(data_dir, &self.project, self.date);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "# Sagacity digest for {}\n\n_{}: {} questions, ${:.2}_\n\n## Key findings\n\n{}\n\n## Questions\n\n",
+            self.date,
+            self.project,
+            self.questions.len(),
+            self.cost,
+            self.findings
+        );
+        for question in &self.questions {
+            let first_line = question.lines().next().unwrap_or_default();
+            markdown.push_str(&format!("- {}\n", first_line));
+        }
+        if !self.files.is_empty() {
+            markdown.push_str("\n## Files discussed\n\n");
+            for (file, count) in &self.files {
+                markdown.push_str(&format!("- `{}` ({})\n", file, count));
+            }
+        }
+        markdown
+    }
+
+    /// Writes the Markdown to the exports folder
+    pub fn export(&self, data_dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let dir = data_dir.join(EXPORTS_DIR);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("digest-{}.md", self.date));
+        fs::write(&path, self.to_markdown())?;
+        Ok(path)
+    }
+}
+
+/// Asks the model for the day's findings and saves the digest
+pub async fn write_digest(
+    chatbot: &mut Chatbot,
+    data_dir: &Path,
+    project: &str,
+    date: NaiveDate,
+    turns: &[TranscriptTurn],
+) -> Result<Digest, Box<dyn std::error::Error>> {
+    let turns = turns_on(turns, date);
+    if turns.is_empty() {
+        return Err(format!("No questions were asked on {}", date).into());
+    }
+    let api_key = chatbot.api_key.clone();
+    let findings = complete_with_claude(
+        &findings_prompt(&turns),
+        DEFAULT_MODEL,
+        &api_key,
+        "daily digest",
+        Operation::Report,
+        chatbot,
+    )
+    .await?;
+    let digest = Digest::new(date, project, &turns, findings);
+    digest.save(data_dir)?;
+    Ok(digest)
+}
+
+/// Writes the digest on a chatbot of its own and reports the questions it covered, and
+/// what it spent, on the event bus
+pub async fn write_digest_in_background(
+    mut chatbot: Chatbot,
+    data_dir: PathBuf,
+    project: String,
+    date: NaiveDate,
+    turns: Vec<TranscriptTurn>,
+    events: EventSender,
+) {
+    let result = write_digest(&mut chatbot, &data_dir, &project, date, &turns)
+        .await
+        .map(|digest| digest.questions.len())
+        .map_err(|e| e.to_string());
+    let _ = events.send(AppEvent::DigestWritten {
+        date,
+        result,
+        spent: chatbot.audit_log.spent,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn turn(day: u32, question: &str, files: &[&str], cost: f64) -> TranscriptTurn {
+        TranscriptTurn {
+            timestamp: Local
+                .with_ymd_and_hms(2024, 5, day, 12, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc),
+            session_id: "s".to_string(),
+            project: "/work/app".to_string(),
+            question: question.to_string(),
+            answer: "An answer.".to_string(),
+            context_files: files.iter().map(|file| file.to_string()).collect(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cost,
+            latency_ms: 0,
+            error: false,
+            structured: None,
+        }
+    }
+
+    #[test]
+    fn digest_covers_one_day_and_is_written_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let turns = vec![
+            turn(2, "How is auth done?", &["src/auth.rs", "src/db.rs"], 0.02),
+            turn(2, "Where are tokens refreshed?", &["src/auth.rs"], 0.03),
+            turn(3, "What does main do?", &["src/main.rs"], 0.01),
+        ];
+        let may = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+        assert_eq!(
+            undigested_day(dir.path(), "/work/app", &turns, may(3)),
+            Some(may(2))
+        );
+
+        let digest = Digest::new(
+            may(2),
+            "/work/app",
+            &turns_on(&turns, may(2)),
+            "- Auth uses JWTs".into(),
+        );
+        assert_eq!(digest.questions.len(), 2);
+        assert_eq!(digest.files[0], ("src/auth.rs".to_string(), 2));
+        assert!((digest.cost - 0.05).abs() < 1e-9);
+        assert!(digest
+            .to_markdown()
+            .contains("## Key findings\n\n- Auth uses JWTs"));
+
+        digest.save(dir.path()).unwrap();
+        assert_eq!(
+            undigested_day(dir.path(), "/work/app", &turns, may(3)),
+            None
+        );
+        assert_eq!(Digest::load(dir.path(), "/work/app", may(2)), Some(digest));
+    }
+
+    #[test]
+    fn failed_turns_and_long_answers_are_left_out_of_the_findings() {
+        let mut failed = turn(2, "Why did it crash?", &[], 0.0);
+        failed.error = true;
+        failed.answer = "Rate limited".to_string();
+        let mut long = turn(2, "Explain the parser\nwith details", &[], 0.0);
+        long.answer = "x".repeat(MAX_DIGEST_ANSWER_CHARS + 100);
+        let turns = [failed, long];
+        let prompt = findings_prompt(&turns.iter().collect::<Vec<_>>());
+        assert!(!prompt.contains("Rate limited"));
+        assert!(prompt.contains(&format!("A: {}\n", "x".repeat(MAX_DIGEST_ANSWER_CHARS))));
+        assert!(!prompt.contains(&"x".repeat(MAX_DIGEST_ANSWER_CHARS + 1)));
+
+        // Questions are listed by their first line, and no files section without files
+        let may2 = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+        let markdown = Digest::new(
+            may2,
+            "/work/app",
+            &turns.iter().collect::<Vec<_>>(),
+            String::new(),
+        )
+        .to_markdown();
+        assert!(markdown.ends_with("## Questions\n\n- Why did it crash?\n- Explain the parser\n"));
+    }
+
+    #[test]
+    fn only_finished_days_are_digested() {
+        let dir = tempfile::tempdir().unwrap();
+        let turns = vec![turn(3, "Asked today", &[], 0.0)];
+        let may3 = NaiveDate::from_ymd_opt(2024, 5, 3).unwrap();
+        assert_eq!(undigested_day(dir.path(), "/work/app", &turns, may3), None);
+        assert_eq!(undigested_day(dir.path(), "/work/app", &[], may3), None);
+    }
+}
@@ -0,0 +1,286 @@
+// src/doc_artifacts.rs
+//! Summaries built from documentation the project already generated: rustdoc JSON in
+//! `target/doc` and TypeDoc JSON output. A file whose public items are documented there
+//! is summarized from those docs instead of by the LLM.
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+// Where `typedoc --json` output is usually written
+const TYPEDOC_OUTPUTS: &[&str] = &["docs.json", "docs/docs.json", "docs/api.json"];
+// Documented items listed per file; the rest are left out of the summary
+const MAX_DOC_ITEMS: usize = 40;
+// rustdoc item kinds worth listing; impls, fields and imports only add noise
+const RUSTDOC_KINDS: &[&str] = &[
+    "module",
+    "function",
+    "struct",
+    "enum",
+    "trait",
+    "type_alias",
+    "typedef",
+    "constant",
+    "static",
+    "macro",
+];
+
+/// A file's summary taken from documentation artifacts
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocSummary {
+    pub summary: String,
+    pub keywords: Vec<String>,
+    // When the artifact was written; a file changed since then isn't summarized from it
+    pub generated_secs: u64,
+}
+
+#[derive(Debug, Default)]
+struct DocItems {
+    module_docs: Option<String>,
+    items: Vec<(String, String, String)>,
+    generated_secs: u64,
+}
+
+// The first paragraph of a doc comment, on one line
+fn first_paragraph(docs: &str) -> String {
+    docs.split("\n\n")
+        .map(str::trim)
+        .find(|paragraph| !paragraph.is_empty())
+        .unwrap_or("")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize(path: &str) -> String {
+    path.trim_start_matches("./").replace('\\', "/")
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs())
+}
+
+fn add_item(
+    files: &mut HashMap<String, DocItems>,
+    generated_secs: u64,
+    file: &str,
+    kind: &str,
+    name: &str,
+    docs: &str,
+) {
+    let docs = first_paragraph(docs);
+    if docs.is_empty() {
+        return;
+    }
+    let entry = files.entry(normalize(file)).or_default();
+    entry.generated_secs = entry.generated_secs.max(generated_secs);
+    if kind == "module" && entry.module_docs.is_none() {
+        entry.module_docs = Some(docs);
+    } else if kind != "module" {
+        entry.items.push((kind.to_string(), name.to_string(), docs));
+    }
+}
+
+// rustdoc's `--output-format json`: items of the local crate (crate_id 0) by source file
+fn read_rustdoc(json: &Value, generated_secs: u64, files: &mut HashMap<String, DocItems>) {
+    let Some(index) = json["index"].as_object() else {
+        return;
+    };
+    for item in index.values() {
+        if item["crate_id"].as_u64() != Some(0) {
+            continue;
+        }
+        // Older formats name the kind directly, newer ones by the key of `inner`
+        let kind = item["kind"].as_str().map(str::to_string).or_else(|| {
+            item["inner"]
+                .as_object()
+                .and_then(|inner| inner.keys().next().cloned())
+        });
+        let (Some(kind), Some(file), Some(docs)) = (
+            kind,
+            item["span"]["filename"].as_str(),
+            item["docs"].as_str(),
+        ) else {
+            continue;
+        };
+        if !RUSTDOC_KINDS.contains(&kind.as_str()) {
+            continue;
+        }
+        let name = item["name"].as_str().unwrap_or("");
+        add_item(files, generated_secs, file, &kind, name, docs);
+    }
+}
+
+// TypeDoc's JSON output: reflections nest under `children`, each with its sources
+fn read_typedoc(reflection: &Value, generated_secs: u64, files: &mut HashMap<String, DocItems>) {
+    for child in reflection["children"].as_array().into_iter().flatten() {
+        let comment = &child["comment"];
+        // TypeDoc 0.23+ splits comments into parts; older versions had shortText
+        let docs = match comment["summary"].as_array() {
+            Some(parts) => parts
+                .iter()
+                .filter_map(|part| part["text"].as_str())
+                .collect::<String>(),
+            None => comment["shortText"].as_str().unwrap_or("").to_string(),
+        };
+        let file = child["sources"][0]["fileName"].as_str();
+        if let (Some(file), Some(name)) = (file, child["name"].as_str()) {
+            let kind = child["kindString"]
+                .as_str()
+                .unwrap_or("item")
+                .to_lowercase();
+            add_item(files, generated_secs, file, &kind, name, &docs);
+        }
+        read_typedoc(child, generated_secs, files);
+    }
+}
+
+/// Summaries for every source file documented by the artifacts under `root`, keyed by
+/// the path relative to it
+pub fn load_doc_summaries(root: &str) -> HashMap<String, DocSummary> {
+    let root = Path::new(root);
+    let mut files: HashMap<String, DocItems> = HashMap::new();
+
+    if let Ok(entries) = fs::read_dir(root.join("target").join("doc")) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(json) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+            {
+                read_rustdoc(&json, modified_secs(&path), &mut files);
+            }
+        }
+    }
+    for output in TYPEDOC_OUTPUTS {
+        let path = root.join(output);
+        if let Some(json) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+        {
+            read_typedoc(&json, modified_secs(&path), &mut files);
+        }
+    }
+
+    files
+        .into_iter()
+        .filter(|(_, docs)| !docs.items.is_empty() || docs.module_docs.is_some())
+        .map(|(file, docs)| {
+            let mut summary = docs.module_docs.unwrap_or_default();
+            // Sorted so a re-run over the same docs gives the same summary
+            let items: BTreeMap<(String, String), String> = docs
+                .items
+                .into_iter()
+                .map(|(kind, name, docs)| ((name, kind), docs))
+                .collect();
+            let mut keywords: Vec<String> = items.keys().map(|(name, _)| name.clone()).collect();
+            keywords.dedup();
+            if !items.is_empty() {
+                if !summary.is_empty() {
+                    summary.push_str("\n\n");
+                }
+                summary.push_str("Documented items:");
+                for ((name, kind), docs) in items.iter().take(MAX_DOC_ITEMS) {
+                    summary.push_str(&format!("\n- {} `{}`: {}", kind, name, docs));
+                }
+            }
+            let doc_summary = DocSummary {
+                summary,
+                keywords,
+                generated_secs: docs.generated_secs,
+            };
+            (file, doc_summary)
+        })
+        .collect()
+}
+
+/// The doc summary for an indexed file, unless the file changed after the docs were built
+pub fn doc_summary_for<'a>(
+    summaries: &'a HashMap<String, DocSummary>,
+    root: &str,
+    file_path: &str,
+    modified_secs: u64,
+) -> Option<&'a DocSummary> {
+    let relative = file_path.strip_prefix(root).unwrap_or(file_path);
+    let relative = normalize(relative.trim_start_matches(['/', '\\']));
+    summaries
+        .get(&relative)
+        .filter(|docs| docs.generated_secs >= modified_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rustdoc_items_are_grouped_by_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let doc_dir = dir.path().join("target").join("doc");
+        fs::create_dir_all(&doc_dir).unwrap();
+        let rustdoc = json!({
+            "index": {
+                "0": { "crate_id": 0, "name": "lib", "docs": "Parses things.\n\nMore.",
+                       "span": { "filename": "src/lib.rs" }, "inner": { "module": {} } },
+                "1": { "crate_id": 0, "name": "parse", "docs": "Parses one\nthing.",
+                       "span": { "filename": "src/lib.rs" }, "inner": { "function": {} } },
+                "2": { "crate_id": 0, "name": "Undocumented", "docs": null,
+                       "span": { "filename": "src/lib.rs" }, "inner": { "struct": {} } },
+                "3": { "crate_id": 1, "name": "external", "docs": "Not ours.",
+                       "span": { "filename": "src/lib.rs" }, "inner": { "function": {} } }
+            }
+        });
+        fs::write(doc_dir.join("demo.json"), rustdoc.to_string()).unwrap();
+
+        let root = dir.path().to_string_lossy().to_string();
+        let summaries = load_doc_summaries(&root);
+        let file_path = format!("{}/src/lib.rs", root);
+        let docs = doc_summary_for(&summaries, &root, &file_path, 0).unwrap();
+        assert_eq!(
+            docs.summary,
+            "Parses things.\n\nDocumented items:\n- function `parse`: Parses one thing."
+        );
+        assert_eq!(docs.keywords, vec!["parse".to_string()]);
+        assert!(doc_summary_for(&summaries, &root, &file_path, u64::MAX).is_none());
+    }
+
+    #[test]
+    fn typedoc_reflections_are_read_from_old_and_new_comment_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("docs")).unwrap();
+        let typedoc = json!({
+            "children": [{
+                "name": "api", "kindString": "Module",
+                "sources": [{ "fileName": "./src/api.ts" }],
+                "children": [
+                    { "name": "fetchUser", "kindString": "Function",
+                      "comment": { "summary": [{ "text": "Loads a " }, { "text": "user." }] },
+                      "sources": [{ "fileName": "./src/api.ts" }] },
+                    { "name": "Client", "kindString": "Class",
+                      "comment": { "shortText": "Talks to the server." },
+                      "sources": [{ "fileName": "src\\api.ts" }] },
+                    { "name": "internal", "kindString": "Function",
+                      "sources": [{ "fileName": "src/api.ts" }] }
+                ]
+            }]
+        });
+        fs::write(dir.path().join("docs/api.json"), typedoc.to_string()).unwrap();
+
+        let summaries = load_doc_summaries(&dir.path().to_string_lossy());
+        assert_eq!(summaries.len(), 1);
+        let docs = &summaries["src/api.ts"];
+        assert_eq!(
+            docs.summary,
+            "Documented items:\n- class `Client`: Talks to the server.\n- function `fetchUser`: Loads a user."
+        );
+        assert_eq!(docs.keywords, vec!["Client", "fetchUser"]);
+    }
+}
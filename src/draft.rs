@@ -0,0 +1,36 @@
+// src/draft.rs
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DRAFT_FILE: &str = "draft.json";
+pub const DRAFT_SAVE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The unsent chat input and view positions, saved periodically for crash recovery
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Draft {
+    pub session_id: String,
+    pub input: String,
+    pub selected_message: Option<usize>,
+    pub viewer_path: Option<String>,
+    pub viewer_scroll: usize,
+}
+
+fn draft_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(DRAFT_FILE)
+}
+
+pub fn load_draft(data_dir: &Path) -> Option<Draft> {
+    let contents = fs::read_to_string(draft_path(data_dir)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn save_draft(data_dir: &Path, draft: &Draft) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(draft_path(data_dir), serde_json::to_string_pretty(draft)?)?;
+    Ok(())
+}
+
+pub fn clear_draft(data_dir: &Path) {
+    let _ = fs::remove_file(draft_path(data_dir));
+}
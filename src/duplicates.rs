@@ -0,0 +1,147 @@
+// src/duplicates.rs
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::shared_summaries::content_hash;
+
+// Share of distinct lines two same-named files must have in common to count as copies
+const NEAR_DUPLICATE_SIMILARITY: f32 = 0.9;
+
+// Whitespace changes from reformatting don't make a different file
+fn normalized(content: &str) -> String {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn line_similarity(a: &str, b: &str) -> f32 {
+    let a: HashSet<&str> = a.lines().collect();
+    let b: HashSet<&str> = b.lines().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+// The copy to keep: the shallowest path, so `src/x.rs` wins over `vendor/lib/src/x.rs`
+fn canonical_first(a: &String, b: &String) -> std::cmp::Ordering {
+    Path::new(a)
+        .components()
+        .count()
+        .cmp(&Path::new(b).components().count())
+        .then(a.cmp(b))
+}
+
+/// Files whose contents repeat another file's, each mapped to the copy that is indexed
+/// in its place. Identical contents (ignoring whitespace) always match; same-named files
+/// match when nearly all their lines are shared, as with lightly patched vendored copies
+pub fn find_duplicates(files: &[String]) -> BTreeMap<String, String> {
+    let mut contents: Vec<(String, String)> = files
+        .iter()
+        .filter_map(|file| {
+            let content = fs::read_to_string(file).ok()?;
+            Some((file.clone(), normalized(&content)))
+        })
+        .collect();
+    contents.sort_by(|a, b| canonical_first(&a.0, &b.0));
+
+    let mut aliases = BTreeMap::new();
+    let mut by_hash: HashMap<String, String> = HashMap::new();
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, (file, content)) in contents.iter().enumerate() {
+        if content.is_empty() {
+            continue;
+        }
+        if let Some(canonical) = by_hash.get(&content_hash("", content)) {
+            aliases.insert(file.clone(), canonical.clone());
+            continue;
+        }
+        let name = Path::new(file)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let similar = by_name.get(&name).and_then(|kept| {
+            kept.iter()
+                .map(|&j| &contents[j])
+                .find(|(_, kept)| line_similarity(kept, content) >= NEAR_DUPLICATE_SIMILARITY)
+        });
+        match similar {
+            Some((canonical, _)) => {
+                aliases.insert(file.clone(), canonical.clone());
+            }
+            None => {
+                by_hash.insert(content_hash("", content), file.clone());
+                by_name.entry(name).or_default().push(i);
+            }
+        }
+    }
+    aliases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, relative: &str, content: &str) -> String {
+        let path = dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path.display().to_string()
+    }
+
+    #[test]
+    fn vendored_copies_alias_the_shallowest_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |relative: &str, content: &str| write(dir.path(), relative, content);
+        let original = write("src/util.rs", "fn a() {}\nfn b() {}\n");
+        let reformatted = write("vendor/x/util2.rs", "  fn a() {}\n\nfn b() {}");
+        let other = write("src/main.rs", "fn main() {}\n");
+
+        let aliases = find_duplicates(&[reformatted.clone(), other, original.clone()]);
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[&reformatted], original);
+    }
+
+    #[test]
+    fn only_same_named_files_match_on_shared_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |relative: &str, content: &str| write(dir.path(), relative, content);
+        let lines: String = (0..20).map(|i| format!("let x{} = {};\n", i, i)).collect();
+        let patched = lines.replacen("let x0 = 0;", "let x0 = 1;", 1);
+        let rewritten = lines.replacen("let x0 = 0;\nlet x1 = 1;\nlet x2 = 2;", "", 1);
+
+        let kept = write("lib/parse.rs", &lines);
+        // One line of twenty changed: 19 shared of 21 distinct
+        let vendored = write("vendor/dep/lib/parse.rs", &patched);
+        // Nearly the same lines under another name are a different file
+        let renamed = write(
+            "lib/parse_old.rs",
+            &lines.replacen("let x19 = 19;", "let x19 = 0;", 1),
+        );
+        // A third of the lines gone falls under the threshold
+        let forked = write(
+            "fork/lib/parse.rs",
+            &format!("{}fn fork() {{}}\n", rewritten),
+        );
+        let empty = write("a/empty.rs", "\n\n");
+        let also_empty = write("b/empty.rs", "");
+
+        let aliases = find_duplicates(&[
+            vendored.clone(),
+            renamed,
+            forked,
+            empty,
+            also_empty,
+            kept.clone(),
+        ]);
+        assert_eq!(
+            aliases.into_iter().collect::<Vec<_>>(),
+            vec![(vendored, kept)]
+        );
+    }
+}
@@ -0,0 +1,306 @@
+// src/embeddings.rs
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+pub const EMBEDDINGS_FILE: &str = "embeddings.json";
+const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// An OpenAI-compatible embeddings endpoint, e.g. text-embeddings-inference or llama.cpp server
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbeddingConfig {
+    // Base URL such as http://localhost:8080/v1; "/embeddings" is appended when missing
+    pub endpoint: String,
+    #[serde(default)]
+    pub model: String,
+    // Environment variable holding a bearer token, for servers that want one
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_batch_size() -> usize {
+    DEFAULT_BATCH_SIZE
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct StoredEmbedding {
+    summary_hash: u64,
+    vector: Vec<f32>,
+}
+
+/// Summary embeddings per file; the dimension is detected from the server's first reply
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EmbeddingStore {
+    model: String,
+    dimension: Option<usize>,
+    vectors: HashMap<String, StoredEmbedding>,
+    // Vectors by file name and summary, kept across projects so identical files are
+    // embedded once
+    #[serde(default)]
+    shared: HashMap<String, Vec<f32>>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl EmbeddingStore {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(EMBEDDINGS_FILE);
+        let mut store: EmbeddingStore = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        store.path = Some(path);
+        store
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string(self)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+}
+
+fn summary_hash(summary: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    summary.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn shared_key(file: &str, summary: &str) -> String {
+    let name = Path::new(file)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("{}:{:016x}", name, summary_hash(summary))
+}
+
+fn embeddings_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/embeddings") {
+        endpoint.to_string()
+    } else {
+        format!("{}/embeddings", endpoint)
+    }
+}
+
+// Function to embed a batch of texts, returned in input order
+pub async fn embed_batch(
+    config: &EmbeddingConfig,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    let mut request = client
+        .post(embeddings_url(&config.endpoint))
+        .header("Content-Type", "application/json");
+    if let Some(token) = config
+        .api_key_env
+        .as_ref()
+        .and_then(|var| std::env::var(var).ok())
+    {
+        request = request.bearer_auth(token);
+    }
+    let mut payload = json!({ "input": texts });
+    if !config.model.is_empty() {
+        payload["model"] = json!(config.model);
+    }
+
+    let response = request
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach embeddings endpoint: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = response.text().await.unwrap_or_default();
+        return Err(format!("Embeddings request failed: {} - {}", status, error_body).into());
+    }
+
+    let body: Value = response.json().await?;
+    let mut data: Vec<(u64, Vec<f32>)> = body["data"]
+        .as_array()
+        .ok_or("Missing 'data' field in embeddings response")?
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let vector = item["embedding"]
+                .as_array()
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v.as_f64())
+                        .map(|v| v as f32)
+                        .collect()
+                })
+                .unwrap_or_default();
+            (item["index"].as_u64().unwrap_or(i as u64), vector)
+        })
+        .collect();
+    data.sort_by_key(|(index, _)| *index);
+
+    if data.len() != texts.len() {
+        return Err(format!(
+            "Embeddings endpoint returned {} vectors for {} inputs",
+            data.len(),
+            texts.len()
+        )
+        .into());
+    }
+    Ok(data.into_iter().map(|(_, vector)| vector).collect())
+}
+
+/// Embeds the summaries that are new or changed since the last run, in batches
+pub async fn update_embeddings(
+    config: &EmbeddingConfig,
+    store: &mut EmbeddingStore,
+    index: &HashMap<String, (String, String)>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if store.model != config.model {
+        store.vectors.clear();
+        store.shared.clear();
+        store.dimension = None;
+        store.model = config.model.clone();
+    }
+    store.vectors.retain(|file, _| index.contains_key(file));
+
+    let mut stale: Vec<(&String, &String)> = index
+        .iter()
+        .filter(|(file, (summary, _))| {
+            store
+                .vectors
+                .get(*file)
+                .is_none_or(|stored| stored.summary_hash != summary_hash(summary))
+        })
+        .map(|(file, (summary, _))| (file, summary))
+        .collect();
+    stale.sort();
+
+    // Files whose name and summary match one embedded before, in any project
+    let mut embedded = 0;
+    stale.retain(|(file, summary)| {
+        let Some(vector) = store.shared.get(&shared_key(file, summary)).cloned() else {
+            return true;
+        };
+        store.vectors.insert(
+            (*file).clone(),
+            StoredEmbedding {
+                summary_hash: summary_hash(summary),
+                vector,
+            },
+        );
+        embedded += 1;
+        false
+    });
+
+    let batch_size = config.batch_size.max(1);
+    let mut start = 0;
+    while start < stale.len() {
+        let batch = &stale[start..(start + batch_size).min(stale.len())];
+        let texts: Vec<String> = batch
+            .iter()
+            .map(|(file, summary)| format!("{}\n{}", file, summary))
+            .collect();
+        let vectors = embed_batch(config, &texts).await?;
+
+        // A different dimension means a different model behind the endpoint: start over
+        let dimension = vectors.first().map_or(0, Vec::len);
+        if store.dimension != Some(dimension) {
+            let restart = store.dimension.is_some();
+            store.dimension = Some(dimension);
+            if restart {
+                store.vectors.clear();
+                store.shared.clear();
+                embedded = 0;
+                stale = index
+                    .iter()
+                    .map(|(file, (summary, _))| (file, summary))
+                    .collect();
+                stale.sort();
+                start = 0;
+                continue;
+            }
+        }
+
+        for ((file, summary), vector) in batch.iter().zip(vectors) {
+            store
+                .shared
+                .insert(shared_key(file, summary), vector.clone());
+            store.vectors.insert(
+                (*file).clone(),
+                StoredEmbedding {
+                    summary_hash: summary_hash(summary),
+                    vector,
+                },
+            );
+            embedded += 1;
+        }
+        start += batch_size;
+    }
+
+    store.save()?;
+    Ok(embedded)
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let similarity = dot / (norm_a * norm_b);
+    // Zero vectors, or components so large their norm overflows, match nothing
+    if similarity.is_nan() {
+        0.0
+    } else {
+        similarity
+    }
+}
+
+/// Ranks every embedded file by cosine similarity to the query
+pub async fn semantic_scores(
+    config: &EmbeddingConfig,
+    store: &EmbeddingStore,
+    query: &str,
+) -> Result<Vec<(String, f32)>, Box<dyn std::error::Error>> {
+    let query_vector = embed_batch(config, &[query.to_string()])
+        .await?
+        .pop()
+        .unwrap_or_default();
+    Ok(store
+        .vectors
+        .iter()
+        .map(|(file, stored)| {
+            (
+                file.clone(),
+                cosine_similarity(&query_vector, &stored.vector),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degenerate_vectors_score_zero() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]), 1.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(
+            cosine_similarity(&[f32::MAX, f32::MAX], &[f32::MAX, 1.0]),
+            0.0
+        );
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+}
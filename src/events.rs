@@ -0,0 +1,70 @@
+// src/events.rs
+use chrono::NaiveDate;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::audit::ApiCallLog;
+use crate::rust_check::RustCheck;
+
+/// What background tasks report back to the UI
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppEvent {
+    // A line of output from a running task
+    LogLine(String),
+    // A task finished with its report, or why it couldn't run
+    TaskDone {
+        task: String,
+        result: Result<String, String>,
+    },
+    // The full-content answer to a question first answered from summaries, with the
+    // call to add to the audit log
+    Refined {
+        question: String,
+        quick_answer: String,
+        answer: Result<String, String>,
+        call: Option<ApiCallLog>,
+    },
+    // `cargo check` results for the Rust blocks of an answer, in order
+    RustChecked {
+        answer: String,
+        checks: Vec<RustCheck>,
+    },
+    // The digest of a past day was written, with the number of questions it covers, and
+    // what the call cost
+    DigestWritten {
+        date: NaiveDate,
+        result: Result<usize, String>,
+        spent: f64,
+    },
+}
+
+pub type EventSender = UnboundedSender<AppEvent>;
+
+/// Channel that background tasks send on instead of touching the app; the render loop
+/// drains it every tick, so nothing waits on a task while drawing
+#[derive(Debug)]
+pub struct EventBus {
+    sender: EventSender,
+    receiver: UnboundedReceiver<AppEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        EventBus { sender, receiver }
+    }
+}
+
+impl EventBus {
+    pub fn sender(&self) -> EventSender {
+        self.sender.clone()
+    }
+
+    /// Events sent since the last drain, without waiting for more
+    pub fn drain(&mut self) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+}
@@ -0,0 +1,81 @@
+// src/eviction.rs
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Files that disappeared from the codebase since the last index
+#[derive(Debug, Clone, Default)]
+pub struct ContextEviction {
+    pub removed: Vec<String>,
+    // (old path, new path) pairs for files that moved
+    pub renamed: Vec<(String, String)>,
+}
+
+impl ContextEviction {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.renamed.is_empty()
+    }
+
+    /// Where a reference to `path` should point now, or None if it should be dropped
+    pub fn remap(&self, path: &str) -> Option<String> {
+        if let Some((_, new)) = self.renamed.iter().find(|(old, _)| old == path) {
+            return Some(new.clone());
+        }
+        if self.removed.iter().any(|removed| removed == path) {
+            return None;
+        }
+        Some(path.to_string())
+    }
+
+    /// Applies `remap` to a list of paths, dropping deleted ones and duplicates
+    pub fn remap_all(&self, paths: &mut Vec<String>) {
+        let mut remapped: Vec<String> = Vec::new();
+        for path in paths.drain(..) {
+            if let Some(new_path) = self.remap(&path) {
+                if !remapped.contains(&new_path) {
+                    remapped.push(new_path);
+                }
+            }
+        }
+        *paths = remapped;
+    }
+
+    pub fn notice(&self) -> String {
+        format!(
+            "Context updated: {} deleted, {} renamed file(s) removed or remapped",
+            self.removed.len(),
+            self.renamed.len()
+        )
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// Function to pair vanished files with new ones that share a unique file name
+pub fn detect_evictions(vanished: &[String], added: &[String]) -> ContextEviction {
+    let mut added_by_name: HashMap<String, Vec<&String>> = HashMap::new();
+    for path in added {
+        added_by_name.entry(file_name(path)).or_default().push(path);
+    }
+    let mut vanished_name_counts: HashMap<String, usize> = HashMap::new();
+    for path in vanished {
+        *vanished_name_counts.entry(file_name(path)).or_default() += 1;
+    }
+
+    let mut eviction = ContextEviction::default();
+    for path in vanished {
+        let name = file_name(path);
+        // Only an unambiguous one-to-one match on file name counts as a rename
+        match added_by_name.get(&name) {
+            Some(candidates) if candidates.len() == 1 && vanished_name_counts[&name] == 1 => {
+                eviction.renamed.push((path.clone(), candidates[0].clone()));
+            }
+            _ => eviction.removed.push(path.clone()),
+        }
+    }
+    eviction
+}
@@ -0,0 +1,88 @@
+// src/extract.rs
+use std::fs;
+use std::path::{Component, Path};
+
+use crate::refactor_plan::PendingEdit;
+
+// Comment openers a block's first line may name its file with, like "// src/a.rs"
+const LABEL_COMMENTS: &[&str] = &["//", "#", "--", "/*", "<!--", ";"];
+
+/// A code block from an answer, labeled with the file it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledBlock {
+    pub path: String,
+    pub contents: String,
+}
+
+// A relative path with a file extension that stays inside the project
+fn as_file_path(candidate: &str) -> Option<String> {
+    let candidate = candidate.trim().trim_start_matches("./");
+    let path = Path::new(candidate);
+    let inside = !candidate.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    let has_extension = path
+        .extension()
+        .is_some_and(|extension| !extension.is_empty())
+        && !candidate.contains(char::is_whitespace);
+    (inside && has_extension).then(|| candidate.to_string())
+}
+
+// The file named by a fence's info string ("rust src/a.rs", "src/a.rs") or by a comment
+// on the block's first line
+fn block_label(info: &str, first_line: &str) -> Option<String> {
+    if let Some(path) = info.split_whitespace().find_map(as_file_path) {
+        return Some(path);
+    }
+    let line = first_line.trim();
+    let comment = LABEL_COMMENTS
+        .iter()
+        .find_map(|opener| line.strip_prefix(opener))?;
+    let label = comment
+        .trim()
+        .trim_end_matches("*/")
+        .trim_end_matches("-->")
+        .trim();
+    as_file_path(label)
+}
+
+/// Code blocks of an answer that name their file; a later block for the same file
+/// replaces an earlier one
+pub fn labeled_blocks(markdown: &str) -> Vec<LabeledBlock> {
+    let mut blocks: Vec<LabeledBlock> = Vec::new();
+    let mut lines = markdown.lines();
+    while let Some(line) = lines.next() {
+        let Some(info) = line.trim_start().strip_prefix("```") else {
+            continue;
+        };
+        let body: Vec<&str> = lines
+            .by_ref()
+            .take_while(|line| !line.trim_start().starts_with("```"))
+            .collect();
+        let Some(path) = block_label(info, body.first().copied().unwrap_or("")) else {
+            continue;
+        };
+        let mut contents = body.join("\n");
+        contents.push('\n');
+        blocks.retain(|block| block.path != path);
+        blocks.push(LabeledBlock { path, contents });
+    }
+    blocks
+}
+
+/// The blocks as edits against the files under `root`, leaving out unchanged files
+pub fn edits_for(blocks: &[LabeledBlock], root: &Path) -> Vec<PendingEdit> {
+    blocks
+        .iter()
+        .filter_map(|block| {
+            let path = root.join(&block.path);
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+            (existing != block.contents).then(|| PendingEdit {
+                path: path.display().to_string(),
+                diff: diffy::create_patch(&existing, &block.contents).to_string(),
+                contents: block.contents.clone(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,270 @@
+// src/failed_summaries.rs
+//! Files whose summary couldn't be written. They stay out of the index, so retrieval never
+//! ranks a placeholder, and are retried by the next index pass or in bulk from the menu.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::chatbot::{detect_language, save_index_cache, summarize_with_claude, Chatbot};
+use crate::summary_postprocess::postprocess_summary;
+
+// Next to each project's index_cache.json
+pub const FAILED_SUMMARIES_FILE: &str = "failed_summaries.json";
+// What older versions stored in the index in place of a failed summary
+const PLACEHOLDER_PREFIX: &str = "Failed to summarize.";
+// A bulk retry tries each file this many times, waiting twice as long after each failure
+const RETRY_ATTEMPTS: u32 = 3;
+const FIRST_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Failed files with the error of their last attempt
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct FailedSummaries {
+    pub files: BTreeMap<String, String>,
+}
+
+impl FailedSummaries {
+    /// The failures recorded while indexing the project at `root_dir`
+    pub fn load(root_dir: &str) -> FailedSummaries {
+        fs::read_to_string(failed_summaries_path(root_dir))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the failures of files under `root_dir` beside that project's index
+    pub fn save(&self, root_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = failed_summaries_path(root_dir);
+        let own = FailedSummaries {
+            files: self
+                .files
+                .iter()
+                .filter(|(file, _)| Path::new(file).starts_with(root_dir))
+                .map(|(file, error)| (file.clone(), error.clone()))
+                .collect(),
+        };
+        if own.files.is_empty() {
+            let _ = fs::remove_file(path);
+            return Ok(());
+        }
+        fs::write(path, serde_json::to_string_pretty(&own)?)?;
+        Ok(())
+    }
+
+    /// Failed files of the project at `root_dir`
+    pub fn under(&self, root_dir: &str) -> Vec<String> {
+        self.files
+            .keys()
+            .filter(|file| Path::new(file).starts_with(root_dir))
+            .cloned()
+            .collect()
+    }
+
+    pub fn record(&mut self, file: &str, error: &str) {
+        self.files.insert(file.to_string(), error.to_string());
+    }
+
+    pub fn resolve(&mut self, file: &str) {
+        self.files.remove(file);
+    }
+
+    pub fn contains(&self, file: &str) -> bool {
+        self.files.contains_key(file)
+    }
+
+    /// Moves placeholder summaries left by older versions out of the index
+    pub fn take_placeholders(&mut self, index: &mut HashMap<String, (String, String)>) {
+        index.retain(|file, (summary, _)| {
+            let placeholder = summary.starts_with(PLACEHOLDER_PREFIX);
+            if placeholder {
+                self.record(file, "summarized before failures were tracked");
+            }
+            !placeholder
+        });
+    }
+}
+
+pub fn failed_summaries_path(root_dir: &str) -> PathBuf {
+    Path::new(root_dir).join(FAILED_SUMMARIES_FILE)
+}
+
+/// Wait before retry `attempt` (counted from 1)
+fn backoff(attempt: u32) -> Duration {
+    FIRST_BACKOFF * 2u32.pow(attempt.saturating_sub(1))
+}
+
+/// Summarizes every failed file of the indexed project again, backing off between attempts
+/// at the same file; returns how many were fixed and how many still fail
+pub async fn retry_failed_summaries(
+    chatbot: &mut Chatbot,
+    api_key: &str,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut index = (*chatbot.index).clone();
+    let mut fixed = 0;
+    let root_dir = chatbot.index_root.clone();
+    let files = chatbot.failed_summaries.under(&root_dir);
+    for file in files {
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            // Deleted since; the next index pass forgets it anyway
+            Err(_) => {
+                chatbot.failed_summaries.resolve(&file);
+                continue;
+            }
+        };
+        let language = detect_language(&file);
+        for attempt in 1..=RETRY_ATTEMPTS {
+            match summarize_with_claude(&content, api_key, &language, chatbot).await {
+                Ok(summary) => {
+                    let processed =
+                        postprocess_summary(&summary, &language, &chatbot.postprocess_config);
+                    chatbot.keywords.insert(file.clone(), processed.keywords);
+                    index.insert(file.clone(), (processed.text, language.clone()));
+                    chatbot.failed_summaries.resolve(&file);
+                    fixed += 1;
+                    break;
+                }
+                Err(e) => {
+                    chatbot.failed_summaries.record(&file, &e.to_string());
+                    if attempt < RETRY_ATTEMPTS {
+                        tokio::time::sleep(backoff(attempt)).await;
+                    }
+                }
+            }
+        }
+    }
+
+    let last_modification = chatbot.file_mod_times.values().copied().max().unwrap_or(0);
+    save_index_cache(
+        &root_dir,
+        &index,
+        last_modification,
+        &chatbot.file_mod_times,
+        &chatbot.keywords,
+        &chatbot.churn,
+        &chatbot.aliases,
+    )?;
+    chatbot.index = std::sync::Arc::new(index);
+    chatbot.failed_summaries.save(&root_dir)?;
+    Ok((fixed, chatbot.failed_summaries.under(&root_dir).len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claude_client::MockClient;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn placeholders_leave_the_index_and_backoff_doubles() {
+        let mut index: HashMap<String, (String, String)> = HashMap::from([
+            (
+                "./src/a.rs".to_string(),
+                ("Parses input.".to_string(), "rust".to_string()),
+            ),
+            (
+                "./src/b.rs".to_string(),
+                (
+                    "Failed to summarize. File content preview: fn b()".to_string(),
+                    "rust".to_string(),
+                ),
+            ),
+        ]);
+        let mut failed = FailedSummaries::default();
+        failed.take_placeholders(&mut index);
+        assert!(index.contains_key("./src/a.rs"));
+        assert!(!index.contains_key("./src/b.rs"));
+        assert!(failed.contains("./src/b.rs"));
+
+        assert_eq!(backoff(1), Duration::from_secs(2));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn the_last_error_is_kept_and_only_leading_placeholders_count() {
+        let mut failed = FailedSummaries::default();
+        failed.record("./a.rs", "timeout");
+        failed.record("./a.rs", "429 rate limited");
+        assert_eq!(failed.files["./a.rs"], "429 rate limited");
+        let saved: FailedSummaries =
+            serde_json::from_str(&serde_json::to_string(&failed).unwrap()).unwrap();
+        assert_eq!(saved.files, failed.files);
+        failed.resolve("./a.rs");
+        failed.resolve("./never-failed.rs");
+        assert!(failed.files.is_empty());
+
+        // A real summary that merely mentions the phrase stays indexed
+        let mut index = HashMap::from([(
+            "./retry.rs".to_string(),
+            (
+                "Logs \"Failed to summarize.\" and retries.".to_string(),
+                "rust".to_string(),
+            ),
+        )]);
+        failed.take_placeholders(&mut index);
+        assert_eq!(index.len(), 1);
+        assert!(failed.files.is_empty());
+
+        // An attempt counted from 0 waits like the first
+        assert_eq!(backoff(0), FIRST_BACKOFF);
+        assert_eq!(backoff(2), FIRST_BACKOFF * 2);
+    }
+
+    #[tokio::test]
+    async fn a_retry_only_touches_the_indexed_projects_failures() {
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let (root_a, root_b) = (
+            a.path().display().to_string(),
+            b.path().display().to_string(),
+        );
+        let file_a = a.path().join("a.rs").display().to_string();
+        let file_b = b.path().join("b.rs").display().to_string();
+        fs::write(&file_a, "fn a() {}").unwrap();
+        fs::write(&file_b, "fn b() {}").unwrap();
+
+        let mut failed = FailedSummaries::default();
+        failed.record(&file_a, "timeout");
+        failed.record(&file_b, "timeout");
+        failed.save(&root_a).unwrap();
+        failed.save(&root_b).unwrap();
+        assert_eq!(
+            FailedSummaries::load(&root_a).under(&root_a),
+            vec![file_a.clone()]
+        );
+        assert!(!FailedSummaries::load(&root_a).contains(&file_b));
+
+        let client = Arc::new(MockClient::default());
+        client.respond(
+            200,
+            json!({
+                "content": [{ "type": "text", "text": "Defines a." }],
+                "usage": { "input_tokens": 10, "output_tokens": 3 },
+                "stop_reason": "end_turn"
+            })
+            .to_string(),
+        );
+        let mut chatbot = Chatbot::new(
+            HashMap::new(),
+            HashMap::new(),
+            "test-key".to_string(),
+            client.clone(),
+        );
+        chatbot.index_root = root_a.clone();
+        chatbot.failed_summaries = failed;
+
+        assert_eq!(
+            retry_failed_summaries(&mut chatbot, "test-key")
+                .await
+                .unwrap(),
+            (1, 0)
+        );
+        assert_eq!(client.requests.lock().unwrap().len(), 1);
+        assert!(chatbot.index.contains_key(&file_a));
+        assert!(!chatbot.index.contains_key(&file_b));
+        // The other project's failure is left for its own index
+        assert!(!failed_summaries_path(&root_a).exists());
+        assert!(FailedSummaries::load(&root_b).contains(&file_b));
+    }
+}
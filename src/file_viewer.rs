@@ -0,0 +1,175 @@
+// src/file_viewer.rs
+use std::fs;
+use std::path::Path;
+
+use crate::index_snapshot::IndexDiff;
+use crate::retrieval_memory::cited_files;
+
+// Lines kept visible above a cited range when scrolling to it
+const SCROLL_CONTEXT: usize = 3;
+// Shorter code lines are too generic to locate a quoted snippet by
+const MIN_SNIPPET_LINE: usize = 8;
+// Unselected lines sent on either side of an excerpt for /explain
+pub const EXPLAIN_CONTEXT_LINES: usize = 3;
+
+/// The file shown next to the conversation in split-screen mode
+#[derive(Debug, Clone)]
+pub struct FileViewer {
+    pub path: String,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+    // Zero-based, inclusive range of the lines the latest answer pointed at
+    pub highlighted: Option<(usize, usize)>,
+    // Line cursor and visual-selection anchor while the viewer has focus
+    pub cursor: usize,
+    pub anchor: Option<usize>,
+    // Shown instead of the file: its changes since the version that was summarized
+    pub index_diff: Option<IndexDiff>,
+}
+
+impl FileViewer {
+    pub fn open(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read file {}: {}", path, e))?;
+        Ok(FileViewer {
+            path: path.to_string(),
+            lines: content.lines().map(str::to_string).collect(),
+            scroll: 0,
+            highlighted: None,
+            cursor: 0,
+            anchor: None,
+            index_diff: None,
+        })
+    }
+
+    pub fn focus(&mut self, range: Option<(usize, usize)>) {
+        self.highlighted = range;
+        self.scroll = range.map_or(0, |(start, _)| start.saturating_sub(SCROLL_CONTEXT));
+        self.cursor = range.map_or(0, |(start, _)| start);
+        self.anchor = None;
+    }
+
+    /// Moves the line cursor, keeping it inside a view of `height` lines
+    pub fn move_cursor(&mut self, delta: isize, height: usize) {
+        let max = self.lines.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(max);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+    }
+
+    /// Starts a visual selection at the cursor, or clears the current one
+    pub fn toggle_selection(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// Zero-based, inclusive selected range; just the cursor line without a selection
+    pub fn selection(&self) -> (usize, usize) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        (anchor.min(self.cursor), anchor.max(self.cursor))
+    }
+
+    /// The selected lines with a few numbered lines of surrounding context
+    pub fn excerpt(&self, context_lines: usize) -> String {
+        let (start, end) = self.selection();
+        let from = start.saturating_sub(context_lines);
+        let to = (end + context_lines).min(self.lines.len().saturating_sub(1));
+        (from..=to)
+            .filter_map(|i| {
+                self.lines.get(i).map(|line| {
+                    let marker = if i >= start && i <= end { ">" } else { " " };
+                    format!("{}{:>5} | {}", marker, i + 1, line)
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.lines.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+}
+
+/// The context file the answer cites first
+pub fn first_cited_file(answer: &str, context_files: &[String]) -> Option<String> {
+    cited_files(answer, context_files)
+        .into_iter()
+        .min_by_key(|path| {
+            let file_name = Path::new(path.as_str())
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            answer
+                .find(path.as_str())
+                .or_else(|| answer.find(&file_name))
+                .unwrap_or(usize::MAX)
+        })
+}
+
+fn parse_range(text: &str) -> Option<(usize, usize)> {
+    let digits = |s: &str| -> String { s.chars().take_while(|c| c.is_ascii_digit()).collect() };
+    let start: usize = digits(text).parse().ok()?;
+    let rest = &text[digits(text).len()..];
+    let end = rest
+        .strip_prefix('-')
+        .or_else(|| rest.strip_prefix('–'))
+        .and_then(|r| digits(r).parse().ok())
+        .unwrap_or(start);
+    // Cited line numbers are one-based
+    (start >= 1 && end >= start).then(|| (start - 1, end - 1))
+}
+
+/// Finds the lines an answer refers to: `file:12`, `lines 12-20`, or a quoted snippet
+pub fn cited_lines(answer: &str, path: &str, lines: &[String]) -> Option<(usize, usize)> {
+    let file_name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let in_file = |range: &(usize, usize)| range.0 < lines.len();
+    let clamp = |(start, end): (usize, usize)| (start, end.min(lines.len().saturating_sub(1)));
+
+    if !file_name.is_empty() {
+        let marker = format!("{}:", file_name);
+        if let Some(range) = answer
+            .match_indices(&marker)
+            .filter_map(|(i, _)| parse_range(&answer[i + marker.len()..]))
+            .find(in_file)
+        {
+            return Some(clamp(range));
+        }
+    }
+
+    let lower = answer.to_lowercase();
+    for marker in ["lines ", "line "] {
+        if let Some(range) = lower
+            .match_indices(marker)
+            .filter_map(|(i, _)| parse_range(&lower[i + marker.len()..]))
+            .find(in_file)
+        {
+            return Some(clamp(range));
+        }
+    }
+
+    // Fall back to the first line of a quoted code block that appears in the file
+    let mut in_fence = false;
+    for answer_line in answer.lines() {
+        let trimmed = answer_line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if !in_fence || trimmed.len() < MIN_SNIPPET_LINE {
+            continue;
+        }
+        if let Some(found) = lines.iter().position(|line| line.trim() == trimmed) {
+            return Some((found, found));
+        }
+    }
+    None
+}
@@ -0,0 +1,236 @@
+// src/gen_tests.rs
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::chatbot::{complete_with_claude, detect_language, Chatbot};
+use crate::constants::*;
+use crate::events::{AppEvent, EventSender};
+use crate::imports::direct_dependencies;
+use crate::sampling::Operation;
+
+pub const CONVENTIONS_FILE: &str = "notes/CONVENTIONS.md";
+
+/// Generated tests waiting for the user to accept the diff preview
+#[derive(Debug, Clone)]
+pub struct PendingTests {
+    pub source_file: String,
+    pub test_path: PathBuf,
+    pub contents: String,
+    pub diff: String,
+}
+
+/// Where tests for `file_path` conventionally live for its language
+pub fn conventional_test_path(file_path: &str) -> PathBuf {
+    let path = Path::new(file_path);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "generated".to_string());
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let crate_root = path
+        .ancestors()
+        .find(|d| d.join("Cargo.toml").is_file())
+        .unwrap_or(Path::new("."));
+
+    match detect_language(file_path).as_str() {
+        "rust" => crate_root.join("tests").join(format!("{}_test.rs", stem)),
+        "python" => Path::new("tests").join(format!("test_{}.py", stem)),
+        "go" => dir.join(format!("{}_test.go", stem)),
+        "typescript" => dir.join(format!("{}.test.ts", stem)),
+        "javascript" => dir.join(format!("{}.test.js", stem)),
+        _ => Path::new("tests").join(format!("{}_test.txt", stem)),
+    }
+}
+
+/// Pulls the code out of the first fenced block, or returns the text unchanged
+pub fn strip_code_fence(response: &str) -> String {
+    let mut in_fence = false;
+    let mut code = Vec::new();
+    for line in response.lines() {
+        if line.trim_start().starts_with("```") {
+            if in_fence {
+                return code.join("\n") + "\n";
+            }
+            in_fence = true;
+            continue;
+        }
+        if in_fence {
+            code.push(line);
+        }
+    }
+    response.to_string()
+}
+
+// Function to ask the model for unit tests of a file and its direct dependencies
+pub async fn generate_tests(
+    file_path: &str,
+    chatbot: &mut Chatbot,
+) -> Result<PendingTests, Box<dyn std::error::Error>> {
+    if !chatbot.privacy.allows_contents(file_path) {
+        return Err(format!(
+            "{} is marked private; its contents can't be sent",
+            file_path
+        )
+        .into());
+    }
+    let content = fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read file {}: {}", file_path, e))?;
+    let language = detect_language(file_path);
+
+    let mut context = format!(
+        "Target file: {}\n```{}\n{}\n```\n\n",
+        file_path, language, content
+    );
+    for dep in direct_dependencies(file_path, &content) {
+        if !chatbot.privacy.allows_contents(&dep) {
+            continue;
+        }
+        if let Ok(dep_content) = fs::read_to_string(&dep) {
+            context.push_str(&format!(
+                "Dependency: {}\n```{}\n{}\n```\n\n",
+                dep, language, dep_content
+            ));
+        }
+    }
+
+    let conventions = fs::read_to_string(CONVENTIONS_FILE).unwrap_or_default();
+    if !conventions.trim().is_empty() {
+        context.push_str(&format!("Project conventions:\n{}\n\n", conventions));
+    }
+
+    let test_path = conventional_test_path(file_path);
+    let prompt = format!(
+        "{}Write unit tests for the target file. They will be saved as {}. \
+         Follow the project conventions if given. Reply with a single fenced code block containing the complete test file.",
+        context,
+        test_path.display()
+    );
+
+    let api_key = chatbot.api_key.clone();
+    let response = complete_with_claude(
+        &prompt,
+        DEFAULT_MODEL,
+        &api_key,
+        "gen_tests",
+        Operation::GenTests,
+        chatbot,
+    )
+    .await?;
+    let contents = strip_code_fence(&response);
+
+    let existing = fs::read_to_string(&test_path).unwrap_or_default();
+    let diff = diffy::create_patch(&existing, &contents).to_string();
+
+    Ok(PendingTests {
+        source_file: file_path.to_string(),
+        test_path,
+        contents,
+        diff,
+    })
+}
+
+pub fn write_tests(pending: &PendingTests) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = pending.test_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&pending.test_path, &pending.contents)?;
+    Ok(())
+}
+
+// Function to run the generated tests with the language's test runner
+pub fn run_tests(pending: &PendingTests) -> Result<String, Box<dyn std::error::Error>> {
+    let test_path = pending.test_path.to_string_lossy().to_string();
+    let stem = pending
+        .test_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let output = match detect_language(&pending.source_file).as_str() {
+        "rust" => Command::new("cargo")
+            .args(["test", "--test", &stem])
+            .output()?,
+        "python" => Command::new("pytest").arg(&test_path).output()?,
+        "go" => {
+            let dir = pending
+                .test_path
+                .parent()
+                .map(|d| format!("./{}", d.display()))
+                .unwrap_or_else(|| "./...".to_string());
+            Command::new("go").args(["test", &dir]).output()?
+        }
+        "typescript" | "javascript" => Command::new("npx").args(["jest", &test_path]).output()?,
+        other => return Err(format!("No test runner known for {}", other).into()),
+    };
+
+    let mut report = String::from_utf8_lossy(&output.stdout).to_string();
+    report.push_str(&String::from_utf8_lossy(&output.stderr));
+    let status = if output.status.success() {
+        "passed"
+    } else {
+        "failed"
+    };
+    Ok(format!("Tests {}:\n\n{}", status, report.trim()))
+}
+
+/// The command that runs the project's whole test suite, picked from its manifest
+pub fn test_command(root: &Path) -> Option<&'static str> {
+    if root.join("Cargo.toml").is_file() {
+        Some("cargo test")
+    } else if root.join("go.mod").is_file() {
+        Some("go test ./...")
+    } else if root.join("package.json").is_file() {
+        Some("npm test")
+    } else if root.join("pyproject.toml").is_file()
+        || root.join("setup.py").is_file()
+        || root.join("pytest.ini").is_file()
+    {
+        Some("pytest")
+    } else {
+        None
+    }
+}
+
+/// Runs a shell command in the project root on a background task, sending each line
+/// of output and then the report over the event bus
+pub fn spawn_shell(root: &Path, command: &str, events: EventSender) {
+    let root = root.to_path_buf();
+    let command = command.to_string();
+    tokio::spawn(async move {
+        let result = stream_shell(&root, &command, &events)
+            .await
+            .map_err(|e| format!("Could not run `{}`: {}", command, e));
+        let _ = events.send(AppEvent::TaskDone {
+            task: command,
+            result,
+        });
+    });
+}
+
+async fn stream_shell(root: &Path, command: &str, events: &EventSender) -> std::io::Result<String> {
+    // Stderr is folded into stdout so lines arrive in the order they were written
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("exec 2>&1; {}", command))
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let mut report = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines.next_line().await? {
+            let _ = events.send(AppEvent::LogLine(line.clone()));
+            report.push_str(&line);
+            report.push('\n');
+        }
+    }
+    let status = if child.wait().await?.success() {
+        "succeeded"
+    } else {
+        "failed"
+    };
+    Ok(format!("`{}` {}:\n\n{}", command, status, report.trim()))
+}
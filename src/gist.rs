@@ -0,0 +1,172 @@
+// src/gist.rs
+use serde_json::{json, Value};
+
+use crate::saved_responses::{code_blocks, extension_for};
+use crate::scrubber::{ScrubReport, Scrubber};
+
+const GISTS_API: &str = "https://api.github.com/gists";
+
+/// A message or one of its code blocks, scrubbed and ready to upload
+#[derive(Debug, Clone, PartialEq)]
+pub struct GistSnippet {
+    pub file_name: String,
+    pub content: String,
+    pub description: String,
+}
+
+/// The snippet for a message, or for its `block`th code block when one is focused;
+/// masked like a shared transcript
+pub fn snippet_for(
+    message: &str,
+    block: Option<usize>,
+    scrubber: &Scrubber,
+    report: &mut ScrubReport,
+) -> Option<GistSnippet> {
+    let (file_name, content, description) = match block {
+        Some(block) => {
+            let (language, contents) = code_blocks(message).into_iter().nth(block)?;
+            (
+                format!("snippet.{}", extension_for(&language)),
+                contents,
+                "Code block shared from Sagacity".to_string(),
+            )
+        }
+        None => (
+            "answer.md".to_string(),
+            message.to_string(),
+            "Answer shared from Sagacity".to_string(),
+        ),
+    };
+    Some(GistSnippet {
+        file_name,
+        content: scrubber.scrub(&content, report),
+        description,
+    })
+}
+
+/// Uploads the snippet as a secret gist and returns its URL
+pub async fn create_gist(
+    token: &str,
+    snippet: &GistSnippet,
+) -> Result<String, Box<dyn std::error::Error>> {
+    post_gist(GISTS_API, token, snippet).await
+}
+
+async fn post_gist(
+    api: &str,
+    token: &str,
+    snippet: &GistSnippet,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut files = serde_json::Map::new();
+    files.insert(
+        snippet.file_name.clone(),
+        json!({ "content": snippet.content }),
+    );
+    let body = json!({
+        "description": snippet.description,
+        "public": false,
+        "files": files
+    });
+    let response = reqwest::Client::new()
+        .post(api)
+        .bearer_auth(token)
+        .header(reqwest::header::USER_AGENT, "sagacity")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .json(&body)
+        .send()
+        .await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        let message = body["message"].as_str().unwrap_or("no details");
+        return Err(format!("GitHub refused the gist ({}): {}", status, message).into());
+    }
+    body["html_url"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| "GitHub's response had no gist URL".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focused_blocks_are_shared_alone_and_scrubbed() {
+        let (scrubber, _) = Scrubber::new(&[]);
+        let mut report = ScrubReport::default();
+        let answer = "Try this:\n\n```python\nprint('ops@example.com')\n```\n";
+        let snippet = snippet_for(answer, Some(0), &scrubber, &mut report).unwrap();
+        assert_eq!(snippet.file_name, "snippet.py");
+        assert!(!snippet.content.contains("ops@example.com"));
+        assert!(snippet_for(answer, Some(1), &scrubber, &mut report).is_none());
+        assert_eq!(
+            snippet_for(answer, None, &scrubber, &mut report)
+                .unwrap()
+                .file_name,
+            "answer.md"
+        );
+    }
+
+    #[test]
+    fn untagged_blocks_are_text_and_whole_answers_are_scrubbed() {
+        let (scrubber, _) = Scrubber::new(&[]);
+        let mut report = ScrubReport::default();
+        let answer = "Mail ops@example.com\n\n```\nplain\n```\n";
+        let block = snippet_for(answer, Some(0), &scrubber, &mut report).unwrap();
+        assert_eq!(
+            (block.file_name.as_str(), block.content.trim_end()),
+            ("snippet.txt", "plain")
+        );
+        let whole = snippet_for(answer, None, &scrubber, &mut report).unwrap();
+        assert!(!whole.content.contains("ops@example.com"));
+        assert_eq!(whole.description, "Answer shared from Sagacity");
+    }
+
+    #[tokio::test]
+    async fn gists_are_secret_and_refusals_carry_github_message() {
+        use wiremock::matchers::{body_partial_json, header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let snippet = GistSnippet {
+            file_name: "answer.md".to_string(),
+            content: "Hi".to_string(),
+            description: "Answer shared from Sagacity".to_string(),
+        };
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "Bearer good"))
+            .and(body_partial_json(json!({
+                "public": false,
+                "files": { "answer.md": { "content": "Hi" } }
+            })))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(json!({ "html_url": "https://gist.github.com/abc" })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "Bearer stale"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "message": "Bad credentials"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "Bearer odd"))
+            .respond_with(ResponseTemplate::new(201).set_body_string("{}"))
+            .mount(&server)
+            .await;
+
+        let api = server.uri();
+        assert_eq!(
+            post_gist(&api, "good", &snippet).await.unwrap(),
+            "https://gist.github.com/abc"
+        );
+        let refused = post_gist(&api, "stale", &snippet).await.unwrap_err();
+        assert!(refused.to_string().ends_with("): Bad credentials"));
+        let unexpected = post_gist(&api, "odd", &snippet).await.unwrap_err();
+        assert_eq!(unexpected.to_string(), "GitHub's response had no gist URL");
+    }
+}
@@ -0,0 +1,201 @@
+// src/git_hooks.rs
+//! Git hooks that keep the index fresh: after a commit or checkout they queue the files
+//! it changed and reindex them in the background, so the TUI opens on a current index.
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::branches::git_output;
+
+// Next to index_cache.json; read and cleared by the next index pass of the project
+pub const REINDEX_QUEUE_FILE: &str = "reindex_queue.json";
+pub const HOOKS: [&str; 2] = ["post-commit", "post-checkout"];
+// Marks the lines this module added to a hook, so installing twice changes nothing
+const HOOK_MARKER: &str = "# sagacity: queue changed files for reindexing";
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+// Runs `sagacity hook run <hook>` from the project in the background, so git isn't held up
+fn hook_lines(hook: &str, project: &Path, exe: &Path) -> String {
+    format!(
+        "{}\n(cd {} && {} hook run {} \"$@\") >/dev/null 2>&1 &\n",
+        HOOK_MARKER,
+        shell_quote(&project.display().to_string()),
+        shell_quote(&exe.display().to_string()),
+        hook
+    )
+}
+
+/// Writes the hooks into `hooks_dir`, appending to hooks that already exist; returns
+/// the hooks that were changed
+pub fn install_into(
+    hooks_dir: &Path,
+    project: &Path,
+    exe: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    fs::create_dir_all(hooks_dir)?;
+    let mut installed = Vec::new();
+    for hook in HOOKS {
+        let path = hooks_dir.join(hook);
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        if existing.contains(HOOK_MARKER) {
+            continue;
+        }
+        let mut script = if existing.trim().is_empty() {
+            "#!/bin/sh\n".to_string()
+        } else {
+            format!("{}\n", existing.trim_end())
+        };
+        script.push_str(&hook_lines(hook, project, exe));
+        fs::write(&path, script)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        installed.push(path);
+    }
+    Ok(installed)
+}
+
+/// Installs the hooks in the repository containing `project`, honoring core.hooksPath
+pub fn install_hooks(project: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let project = project.canonicalize()?;
+    let hooks_dir = PathBuf::from(git_output(&project, &["rev-parse", "--git-path", "hooks"])?);
+    let hooks_dir = if hooks_dir.is_relative() {
+        project.join(hooks_dir)
+    } else {
+        hooks_dir
+    };
+    install_into(&hooks_dir, &project, &std::env::current_exe()?)
+}
+
+/// Files changed by the commit or checkout that ran `hook`, as the index names them;
+/// `args` are the arguments git passed to the hook
+pub fn changed_files(
+    project: &Path,
+    hook: &str,
+    args: &[String],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = match (hook, args) {
+        ("post-commit", _) => git_output(
+            project,
+            &[
+                "diff-tree",
+                "--root",
+                "--no-commit-id",
+                "--name-only",
+                "--relative",
+                "-r",
+                "HEAD",
+            ],
+        )?,
+        ("post-checkout", [previous, new, ..]) => git_output(
+            project,
+            &[
+                "diff",
+                "--name-only",
+                "--relative",
+                previous.as_str(),
+                new.as_str(),
+            ],
+        )?,
+        _ => return Err(format!("Not a hook sagacity installs: {}", hook).into()),
+    };
+    Ok(output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("./{}", line))
+        .collect())
+}
+
+fn reindex_queue_path(root_dir: &str) -> PathBuf {
+    Path::new(root_dir).join(REINDEX_QUEUE_FILE)
+}
+
+pub fn load_reindex_queue(root_dir: &str) -> BTreeSet<String> {
+    fs::read_to_string(reindex_queue_path(root_dir))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Adds files to the queue; returns how many are queued in all
+pub fn queue_for_reindex(
+    root_dir: &str,
+    files: &[String],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut queue = load_reindex_queue(root_dir);
+    queue.extend(files.iter().cloned());
+    fs::write(
+        reindex_queue_path(root_dir),
+        serde_json::to_string_pretty(&queue)?,
+    )?;
+    Ok(queue.len())
+}
+
+pub fn clear_reindex_queue(root_dir: &str) {
+    let _ = fs::remove_file(reindex_queue_path(root_dir));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_are_added_once_and_keep_existing_scripts() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = dir.path().join("hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("post-commit"), "#!/bin/sh\nmake lint\n").unwrap();
+        let project = Path::new("/work/it's here");
+        let exe = Path::new("/usr/bin/sagacity");
+
+        let installed = install_into(&hooks, project, exe).unwrap();
+        assert_eq!(installed.len(), 2);
+        let script = fs::read_to_string(hooks.join("post-commit")).unwrap();
+        assert!(script.starts_with("#!/bin/sh\nmake lint\n# sagacity"));
+        assert!(
+            script.contains("cd '/work/it'\\''s here' && '/usr/bin/sagacity' hook run post-commit")
+        );
+
+        assert!(install_into(&hooks, project, exe).unwrap().is_empty());
+    }
+
+    #[test]
+    fn changed_files_cover_the_root_commit_and_checkouts_relative_to_the_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = fs::canonicalize(dir.path()).unwrap();
+        let project = repo.join("app");
+        fs::create_dir_all(&project).unwrap();
+        let git = |args: &[&str]| git_output(&repo, args).unwrap();
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        fs::write(project.join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(repo.join("README.md"), "Demo\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "Initial"]);
+
+        // The first commit has no parent; files outside the project aren't listed
+        assert_eq!(
+            changed_files(&project, "post-commit", &[]).unwrap(),
+            vec!["./main.rs"]
+        );
+
+        let first = git(&["rev-parse", "HEAD"]);
+        fs::write(project.join("lib.rs"), "fn lib() {}\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "Add lib"]);
+        let second = git(&["rev-parse", "HEAD"]);
+        assert_eq!(
+            changed_files(&project, "post-checkout", &[second, first, "1".to_string()]).unwrap(),
+            vec!["./lib.rs"]
+        );
+
+        assert!(changed_files(&project, "post-checkout", &[]).is_err());
+        assert!(changed_files(&project, "pre-push", &[]).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+// src/glossary.rs
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::transcript_log::project_file_name;
+
+pub const GLOSSARIES_DIR: &str = "glossaries";
+// Definitions added to one request at most, so a large glossary can't crowd out context
+const MAX_INJECTED_ENTRIES: usize = 12;
+
+/// Project terms and what they mean, sent along with the questions that mention them
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Glossary {
+    pub entries: BTreeMap<String, String>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+// Whole-word, case-insensitive match so "id" doesn't match "provider"
+fn mentions(query: &str, term: &str) -> bool {
+    let query = query.to_lowercase();
+    let term = term.to_lowercase();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    query.match_indices(&term).any(|(start, _)| {
+        let before = query[..start].chars().next_back();
+        let after = query[start + term.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+impl Glossary {
+    pub fn load(data_dir: &Path, project: &str) -> Self {
+        let path = data_dir
+            .join(GLOSSARIES_DIR)
+            .join(format!("{}.json", project_file_name(project)));
+        let mut glossary: Glossary = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        glossary.path = Some(path);
+        glossary
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+        }
+        Ok(())
+    }
+
+    /// Entries whose term appears in the query, longest terms first
+    pub fn relevant(&self, query: &str) -> Vec<(&String, &String)> {
+        let mut matches: Vec<(&String, &String)> = self
+            .entries
+            .iter()
+            .filter(|(term, _)| mentions(query, term))
+            .collect();
+        matches.sort_by_key(|(term, _)| std::cmp::Reverse(term.len()));
+        matches.truncate(MAX_INJECTED_ENTRIES);
+        matches
+    }
+
+    /// The system prompt with the definitions of the terms the query mentions
+    pub fn system_prompt(&self, base: &str, query: &str) -> String {
+        let relevant = self.relevant(query);
+        if relevant.is_empty() {
+            return base.to_string();
+        }
+        let definitions: Vec<String> = relevant
+            .iter()
+            .map(|(term, definition)| format!("- {}: {}", term, definition))
+            .collect();
+        format!(
+            "{}\n\nThis project uses these terms with specific meanings; use them accordingly:\n{}",
+            base,
+            definitions.join("\n")
+        )
+    }
+}
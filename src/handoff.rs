@@ -0,0 +1,277 @@
+// src/handoff.rs
+//! Handoff bundles for pairing: `/handoff` packs what an investigation has found so far
+//! into one file, and `/import` opens it as a new session on another machine.
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::query_suggestions::LOW_CONFIDENCE;
+use crate::scrubber::{ScrubReport, Scrubber};
+use crate::session::Bookmark;
+use crate::ui::chat::{Message, Sender};
+use crate::usage::EXPORTS_DIR;
+
+// Messages carried over verbatim; older ones are left to the notes
+pub const HANDOFF_MESSAGES: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Handoff {
+    // Name of the repository's folder, to warn when it's imported into another one
+    pub project: String,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    // Questions left unanswered, cut off or answered with low confidence
+    pub open_questions: Vec<String>,
+    // Bookmarked findings: their annotation, or the message's first line
+    pub pinned_facts: Vec<String>,
+    pub pinned_files: Vec<String>,
+    // Files the recent answers drew on
+    pub context_files: Vec<String>,
+    pub messages: Vec<Message>,
+}
+
+fn first_line(text: &str) -> String {
+    text.trim().lines().next().unwrap_or("").to_string()
+}
+
+// Whether the answer to a question left it open
+fn left_open(answer: Option<&Message>) -> bool {
+    match answer {
+        Some(answer) if answer.sender == Sender::AI => {
+            answer.timed_out
+                || answer
+                    .structured
+                    .as_ref()
+                    .and_then(|structured| structured.confidence)
+                    .is_some_and(|confidence| confidence < LOW_CONFIDENCE)
+        }
+        _ => true,
+    }
+}
+
+impl Handoff {
+    /// Builds the bundle from a session, masked like a shared transcript
+    pub fn build(
+        project: &str,
+        title: &str,
+        messages: &[Message],
+        bookmarks: &[Bookmark],
+        pinned_files: &[String],
+        scrubber: &Scrubber,
+        report: &mut ScrubReport,
+    ) -> Handoff {
+        let open_questions = messages
+            .iter()
+            .enumerate()
+            .filter(|(i, message)| message.sender == Sender::User && left_open(messages.get(i + 1)))
+            .map(|(_, message)| scrubber.scrub(&first_line(&message.content), report))
+            .collect();
+        let pinned_facts = bookmarks
+            .iter()
+            .filter_map(|bookmark| {
+                let message = messages.get(bookmark.message_index)?;
+                let fact = if bookmark.annotation.is_empty() {
+                    first_line(&message.content)
+                } else {
+                    bookmark.annotation.clone()
+                };
+                Some(scrubber.scrub(&fact, report))
+            })
+            .collect();
+        let recent = &messages[messages.len().saturating_sub(HANDOFF_MESSAGES)..];
+        let mut context_files: Vec<String> = Vec::new();
+        for file in recent.iter().flat_map(|message| &message.context_files) {
+            if !context_files.contains(file) {
+                context_files.push(file.clone());
+            }
+        }
+        Handoff {
+            project: project.to_string(),
+            title: scrubber.scrub(title, report),
+            created_at: Utc::now(),
+            open_questions,
+            pinned_facts,
+            pinned_files: pinned_files.to_vec(),
+            context_files,
+            messages: recent
+                .iter()
+                .map(|message| {
+                    let mut message = message.clone();
+                    message.content = scrubber.scrub(&message.content, report);
+                    message
+                })
+                .collect(),
+        }
+    }
+
+    /// The findings and open questions as scratchpad notes for the next question
+    pub fn notes(&self) -> String {
+        let mut notes = format!("Handed off: {}", self.title);
+        for (heading, items) in [
+            ("Findings so far:", &self.pinned_facts),
+            ("Open questions:", &self.open_questions),
+        ] {
+            if !items.is_empty() {
+                notes.push_str(&format!("\n{}", heading));
+                for item in items {
+                    notes.push_str(&format!("\n- {}", item));
+                }
+            }
+        }
+        notes
+    }
+}
+
+/// Writes the bundle into the data directory's exports folder and returns its path
+pub fn write_handoff(
+    data_dir: &Path,
+    handoff: &Handoff,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = data_dir.join(EXPORTS_DIR);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "handoff-{}.json",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, serde_json::to_string_pretty(handoff)?)?;
+    Ok(path)
+}
+
+pub fn read_handoff(path: &Path) -> Result<Handoff, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not a handoff file: {}", path.display(), e))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handoffs_keep_open_questions_and_findings() {
+        let mut answer = Message::ai("Tokens are refreshed in `refresh`.");
+        answer.context_files = vec!["./src/auth.rs".to_string()];
+        let messages = vec![
+            Message::user("How are tokens refreshed?"),
+            answer,
+            Message::user("Why does refresh race with logout?"),
+        ];
+        let bookmarks = vec![Bookmark {
+            message_index: 1,
+            annotation: String::new(),
+        }];
+        let (scrubber, _) = Scrubber::new(&[]);
+        let handoff = Handoff::build(
+            "app",
+            "Auth",
+            &messages,
+            &bookmarks,
+            &[],
+            &scrubber,
+            &mut ScrubReport::default(),
+        );
+        assert_eq!(
+            handoff.open_questions,
+            vec!["Why does refresh race with logout?"]
+        );
+        assert_eq!(
+            handoff.pinned_facts,
+            vec!["Tokens are refreshed in `refresh`."]
+        );
+        assert_eq!(handoff.context_files, vec!["./src/auth.rs"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_handoff(dir.path(), &handoff).unwrap();
+        assert_eq!(read_handoff(&path).unwrap(), handoff);
+        assert!(handoff
+            .notes()
+            .contains("Open questions:\n- Why does refresh"));
+    }
+
+    #[test]
+    fn unsure_or_cut_off_answers_leave_questions_open_and_old_turns_are_dropped() {
+        let answered = |confidence: Option<f64>| {
+            let mut answer = Message::ai("It depends.");
+            answer.structured = Some(crate::structured::StructuredAnswer {
+                answer: "It depends.".to_string(),
+                confidence,
+                sources: Vec::new(),
+            });
+            answer
+        };
+        let mut timed_out = Message::ai("Partial");
+        timed_out.timed_out = true;
+        let mut messages = vec![
+            Message::user("Sure?\nmore detail"),
+            answered(Some(0.9)),
+            Message::user("Unsure?"),
+            answered(Some(0.2)),
+            Message::user("Cut off?"),
+            timed_out,
+            Message::user("No confidence given?"),
+            answered(None),
+        ];
+        for i in 0..HANDOFF_MESSAGES {
+            let mut answer = Message::ai(format!("Answer {} for ops@example.com", i));
+            answer.context_files = vec![format!("./src/{}.rs", i % 2)];
+            messages.push(answer);
+        }
+        let bookmarks = vec![
+            Bookmark {
+                message_index: 0,
+                annotation: "Refresh holds the lock".to_string(),
+            },
+            Bookmark {
+                message_index: 999,
+                annotation: String::new(),
+            },
+        ];
+        let (scrubber, _) = Scrubber::new(&[]);
+        let handoff = Handoff::build(
+            "app",
+            "Auth",
+            &messages,
+            &bookmarks,
+            &[],
+            &scrubber,
+            &mut ScrubReport::default(),
+        );
+        assert_eq!(handoff.open_questions, vec!["Unsure?", "Cut off?"]);
+        assert_eq!(handoff.pinned_facts, vec!["Refresh holds the lock"]);
+        assert_eq!(handoff.messages.len(), HANDOFF_MESSAGES);
+        assert!(handoff
+            .messages
+            .iter()
+            .all(|message| !message.content.contains("ops@example.com")));
+        assert_eq!(handoff.context_files, vec!["./src/0.rs", "./src/1.rs"]);
+    }
+
+    #[test]
+    fn empty_sections_are_left_out_of_the_notes_and_bad_files_are_named() {
+        let (scrubber, _) = Scrubber::new(&[]);
+        let handoff = Handoff::build(
+            "app",
+            "Nothing yet",
+            &[],
+            &[],
+            &[],
+            &scrubber,
+            &mut ScrubReport::default(),
+        );
+        assert_eq!(handoff.notes(), "Handed off: Nothing yet");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.json");
+        fs::write(&path, "{\"title\": \"x\"}").unwrap();
+        assert!(read_handoff(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("is not a handoff file"));
+        assert!(read_handoff(&dir.path().join("missing.json"))
+            .unwrap_err()
+            .to_string()
+            .starts_with("Failed to read"));
+    }
+}
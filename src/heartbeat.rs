@@ -0,0 +1,143 @@
+// src/heartbeat.rs
+//! Heartbeats for slow operations: past 10 seconds, a wait logs its elapsed time and
+//! stage now and then and pulses the status, so slow can be told apart from hung.
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::debug_log;
+
+// How long an operation runs before it counts as slow
+const SLOW_AFTER: Duration = Duration::from_secs(10);
+// How often a slow wait logs a heartbeat
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+// How often a slow wait redraws its status
+const PULSE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The latest heartbeat of the operation in flight, for a UI drawn from elsewhere
+pub type SharedHeartbeat = Arc<Mutex<Option<Heartbeat>>>;
+
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    pub stage: String,
+    started: Instant,
+    last_log: Option<Instant>,
+    pub pulses: usize,
+}
+
+impl Heartbeat {
+    pub fn start(stage: impl Into<String>) -> Heartbeat {
+        Heartbeat {
+            stage: stage.into(),
+            started: Instant::now(),
+            last_log: None,
+            pulses: 0,
+        }
+    }
+
+    /// Moves on to the next stage; the elapsed time keeps counting from the start
+    pub fn set_stage(&mut self, stage: impl Into<String>) {
+        self.stage = stage.into();
+    }
+
+    pub fn is_slow(&self) -> bool {
+        self.started.elapsed() >= SLOW_AFTER
+    }
+
+    /// "Still waiting for the API (14s)"
+    pub fn status(&self) -> String {
+        format!(
+            "Still {} ({}s)",
+            self.stage,
+            self.started.elapsed().as_secs()
+        )
+    }
+
+    /// The line to log, when the operation is slow and the last one is old enough
+    fn log_line(&mut self, now: Instant) -> Option<String> {
+        if now.duration_since(self.started) < SLOW_AFTER
+            || self
+                .last_log
+                .is_some_and(|last| now.duration_since(last) < LOG_INTERVAL)
+        {
+            return None;
+        }
+        self.last_log = Some(now);
+        Some(format!(
+            "still {} after {}s",
+            self.stage,
+            now.duration_since(self.started).as_secs()
+        ))
+    }
+}
+
+/// Awaits `future`, logging heartbeats while it is slow and calling `on_pulse` every
+/// second so the status can be redrawn
+pub async fn with_heartbeat<F: Future + Unpin>(
+    heartbeat: &mut Heartbeat,
+    mut future: F,
+    on_pulse: &mut dyn FnMut(&Heartbeat),
+) -> F::Output {
+    loop {
+        match tokio::time::timeout(PULSE_INTERVAL, &mut future).await {
+            Ok(output) => return output,
+            Err(_) => {
+                // The TUI shows the heartbeat through `on_pulse`; the line only goes to the
+                // debug log, never over the screen
+                if let Some(line) = heartbeat.log_line(Instant::now()) {
+                    debug_log::write("HEARTBEAT", &line);
+                }
+                if heartbeat.is_slow() {
+                    heartbeat.pulses += 1;
+                    on_pulse(heartbeat);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heartbeats_are_logged_only_once_slow_and_then_every_interval() {
+        let mut heartbeat = Heartbeat::start("waiting for the API");
+        let start = heartbeat.started;
+        assert_eq!(heartbeat.log_line(start + Duration::from_secs(5)), None);
+        assert_eq!(
+            heartbeat.log_line(start + Duration::from_secs(11)),
+            Some("still waiting for the API after 11s".to_string())
+        );
+        assert_eq!(heartbeat.log_line(start + Duration::from_secs(15)), None);
+        heartbeat.set_stage("streaming the answer");
+        assert_eq!(
+            heartbeat.log_line(start + Duration::from_secs(21)),
+            Some("still streaming the answer after 21s".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn only_slow_waits_pulse_and_the_output_is_passed_through() {
+        let mut pulsed = Vec::new();
+        let mut heartbeat = Heartbeat::start("indexing");
+        let ready = with_heartbeat(&mut heartbeat, Box::pin(async { 7 }), &mut |beat| {
+            pulsed.push(beat.pulses)
+        })
+        .await;
+        assert_eq!((ready, heartbeat.pulses), (7, 0));
+
+        // An operation already past SLOW_AFTER pulses once per second it still takes
+        heartbeat.started = Instant::now() - SLOW_AFTER;
+        let slow = Box::pin(async {
+            tokio::time::sleep(PULSE_INTERVAL + PULSE_INTERVAL / 2).await;
+            "done"
+        });
+        let output =
+            with_heartbeat(&mut heartbeat, slow, &mut |beat| pulsed.push(beat.pulses)).await;
+        assert_eq!(output, "done");
+        assert_eq!(pulsed, vec![1]);
+        assert!(heartbeat.status().starts_with("Still indexing ("));
+        assert!(heartbeat.last_log.is_some());
+    }
+}
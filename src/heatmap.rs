@@ -0,0 +1,100 @@
+// src/heatmap.rs
+use ratatui::layout::Rect;
+use std::collections::HashMap;
+use std::fs;
+
+use crate::models::estimate_tokens;
+use crate::transcript_log::TranscriptTurn;
+
+// Largest files shown; the rest would be too small to see anyway
+pub const MAX_HEATMAP_CELLS: usize = 200;
+
+/// One indexed file: its size in tokens and how often it was put into context
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapCell {
+    pub path: String,
+    pub tokens: usize,
+    pub retrievals: usize,
+}
+
+/// Cells for the indexed files, largest first, with retrieval counts from past turns
+pub fn build_heatmap<'a>(
+    files: impl Iterator<Item = &'a String>,
+    turns: &[TranscriptTurn],
+) -> Vec<HeatmapCell> {
+    let mut retrievals: HashMap<&str, usize> = HashMap::new();
+    for turn in turns {
+        for file in &turn.context_files {
+            *retrievals.entry(file.as_str()).or_default() += 1;
+        }
+    }
+    let mut cells: Vec<HeatmapCell> = files
+        .filter_map(|path| {
+            let contents = fs::read_to_string(path).ok()?;
+            Some(HeatmapCell {
+                path: path.clone(),
+                tokens: estimate_tokens(&contents),
+                retrievals: retrievals.get(path.as_str()).copied().unwrap_or(0),
+            })
+        })
+        .collect();
+    cells.sort_by(|a, b| b.tokens.cmp(&a.tokens).then(a.path.cmp(&b.path)));
+    cells.truncate(MAX_HEATMAP_CELLS);
+    cells
+}
+
+/// Treemap layout: each weight gets a rectangle of `area` proportional to it. Items are
+/// split into two halves of about equal weight along the longer side, recursively.
+pub fn treemap(weights: &[usize], area: Rect) -> Vec<Rect> {
+    let mut rects = vec![Rect::default(); weights.len()];
+    let items: Vec<usize> = (0..weights.len()).collect();
+    split(weights, &items, area, &mut rects);
+    rects
+}
+
+fn split(weights: &[usize], items: &[usize], area: Rect, rects: &mut [Rect]) {
+    let weight = |i: &usize| weights[*i].max(1) as u64;
+    match items {
+        [] => return,
+        [only] => {
+            rects[*only] = area;
+            return;
+        }
+        _ => {}
+    }
+    let total: u64 = items.iter().map(weight).sum();
+    let mut first_weight = 0;
+    let mut cut = 0;
+    for item in &items[..items.len() - 1] {
+        first_weight += weight(item);
+        cut += 1;
+        if first_weight * 2 >= total {
+            break;
+        }
+    }
+
+    // Terminal cells are about twice as tall as wide, so width counts half
+    let (first, rest) = if area.width as u64 >= area.height as u64 * 2 {
+        let width = (area.width as u64 * first_weight / total) as u16;
+        (
+            Rect { width, ..area },
+            Rect {
+                x: area.x.saturating_add(width),
+                width: area.width - width,
+                ..area
+            },
+        )
+    } else {
+        let height = (area.height as u64 * first_weight / total) as u16;
+        (
+            Rect { height, ..area },
+            Rect {
+                y: area.y.saturating_add(height),
+                height: area.height - height,
+                ..area
+            },
+        )
+    };
+    split(weights, &items[..cut], first, rects);
+    split(weights, &items[cut..], rest, rects);
+}
@@ -0,0 +1,243 @@
+// src/history_compaction.rs
+//! Keeps long conversations cheap to send: the latest turns go verbatim, older ones as
+//! summaries of a few turns each, and the oldest as a single epoch summary. Each step
+//! summarizes only what's new, so a request carries a bounded history however long
+//! the session runs.
+use chrono::Utc;
+
+use crate::chatbot::{complete_with_claude, Chatbot, Message};
+use crate::constants::DEFAULT_MODEL;
+use crate::sampling::Operation;
+
+// Turns (a question and its answer) always sent verbatim
+const RECENT_TURNS: usize = 6;
+// Turns folded into each summary
+const TURNS_PER_SUMMARY: usize = 6;
+// Summaries kept before the older half is merged into the epoch summary
+const MAX_SUMMARIES: usize = 4;
+
+/// What of the conversation has been compacted, and into what
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HistoryCompaction {
+    // Messages at the start of the memory covered by `epoch` and `summaries`
+    pub compacted: usize,
+    pub summaries: Vec<String>,
+    pub epoch: Option<String>,
+}
+
+impl HistoryCompaction {
+    /// The next messages due for a summary: a full chunk once enough newer turns follow it
+    pub fn next_chunk(&self, message_count: usize) -> Option<std::ops::Range<usize>> {
+        let chunk = TURNS_PER_SUMMARY * 2;
+        (message_count.saturating_sub(self.compacted) >= chunk + RECENT_TURNS * 2)
+            .then(|| self.compacted..self.compacted + chunk)
+    }
+
+    /// The history as sent: the summaries as one opening exchange, then the recent turns
+    pub fn history(&self, memory: &[Message]) -> Vec<Message> {
+        // A memory cleared since it was compacted is sent as it is
+        let Some(recent) = memory.get(self.compacted..) else {
+            return memory.to_vec();
+        };
+        let mut parts: Vec<&str> = self.epoch.iter().map(String::as_str).collect();
+        parts.extend(self.summaries.iter().map(String::as_str));
+        if parts.is_empty() {
+            return recent.to_vec();
+        }
+        let timestamp = memory.first().map_or_else(Utc::now, |m| m.timestamp);
+        let mut history = vec![
+            Message {
+                role: "user".to_string(),
+                content: format!(
+                    "[Summary of the earlier part of this conversation, oldest first]\n{}",
+                    parts.join("\n\n")
+                ),
+                timestamp,
+            },
+            Message {
+                role: "assistant".to_string(),
+                content: "Understood; I'll keep that earlier discussion in mind.".to_string(),
+                timestamp,
+            },
+        ];
+        history.extend_from_slice(recent);
+        history
+    }
+}
+
+fn chunk_prompt(messages: &[Message]) -> String {
+    let exchanges: Vec<String> = messages
+        .iter()
+        .map(|m| format!("{}: {}", m.role, m.content.trim()))
+        .collect();
+    format!(
+        "Summarize this part of a conversation about a codebase in a few sentences. Keep \
+         the questions asked, the conclusions reached, and file names, identifiers and \
+         decisions exactly as written.\n\n{}",
+        exchanges.join("\n\n")
+    )
+}
+
+fn epoch_prompt(epoch: Option<&str>, summaries: &[String]) -> String {
+    format!(
+        "Merge these summaries of a conversation about a codebase, oldest first, into one \
+         short summary. Keep conclusions, decisions, file names and identifiers; drop \
+         detail that later parts superseded.\n\n{}",
+        epoch
+            .into_iter()
+            .chain(summaries.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    )
+}
+
+async fn summarize(
+    prompt: &str,
+    request_summary: &str,
+    chatbot: &mut Chatbot,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let api_key = chatbot.api_key.clone();
+    let summary = complete_with_claude(
+        prompt,
+        DEFAULT_MODEL,
+        &api_key,
+        request_summary,
+        Operation::Summarize,
+        chatbot,
+    )
+    .await?;
+    Ok(summary.trim().to_string())
+}
+
+/// Summarizes the turns that have aged out of the recent window, and folds old summaries
+/// into the epoch summary once there are too many; usually nothing is due
+pub async fn compact_history(chatbot: &mut Chatbot) -> Result<(), Box<dyn std::error::Error>> {
+    if chatbot.compaction.compacted > chatbot.memory.len() {
+        chatbot.compaction = HistoryCompaction::default();
+    }
+    while let Some(chunk) = chatbot.compaction.next_chunk(chatbot.memory.len()) {
+        let prompt = chunk_prompt(&chatbot.memory[chunk.clone()]);
+        let summary = summarize(&prompt, "History compaction", chatbot).await?;
+        chatbot.compaction.summaries.push(summary);
+        chatbot.compaction.compacted = chunk.end;
+    }
+    if chatbot.compaction.summaries.len() > MAX_SUMMARIES {
+        let merged = chatbot.compaction.summaries.len() - MAX_SUMMARIES / 2;
+        let prompt = epoch_prompt(
+            chatbot.compaction.epoch.as_deref(),
+            &chatbot.compaction.summaries[..merged],
+        );
+        let epoch = summarize(&prompt, "History epoch summary", chatbot).await?;
+        chatbot.compaction.epoch = Some(epoch);
+        chatbot.compaction.summaries.drain(..merged);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn only_turns_past_the_recent_window_are_compacted() {
+        let memory: Vec<Message> = (0..(RECENT_TURNS + TURNS_PER_SUMMARY) * 2)
+            .map(|i| message(if i % 2 == 0 { "user" } else { "assistant" }, "turn"))
+            .collect();
+        let mut compaction = HistoryCompaction::default();
+        assert_eq!(compaction.next_chunk(memory.len() - 2), None);
+        let chunk = compaction.next_chunk(memory.len()).unwrap();
+        assert_eq!(chunk, 0..TURNS_PER_SUMMARY * 2);
+
+        compaction
+            .summaries
+            .push("Asked how auth works.".to_string());
+        compaction.compacted = chunk.end;
+        let history = compaction.history(&memory);
+        assert_eq!(history.len(), 2 + RECENT_TURNS * 2);
+        assert!(history[0].content.ends_with("Asked how auth works."));
+        assert_eq!(history[2].role, "user");
+        assert_eq!(compaction.history(&[]).len(), 0);
+    }
+
+    fn reply(text: &str) -> String {
+        serde_json::json!({
+            "content": [{ "type": "text", "text": text }],
+            "usage": { "input_tokens": 10, "output_tokens": 4 },
+            "stop_reason": "end_turn"
+        })
+        .to_string()
+    }
+
+    fn prompt(request: &serde_json::Value) -> String {
+        request["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn old_summaries_fold_into_the_epoch_and_failures_change_nothing() {
+        let client = std::sync::Arc::new(crate::claude_client::MockClient::default());
+        let mut chatbot = Chatbot::new(
+            Default::default(),
+            Default::default(),
+            "test-key".to_string(),
+            client.clone(),
+        );
+        // One chunk more than MAX_SUMMARIES, plus the recent window
+        let chunks = MAX_SUMMARIES + 1;
+        chatbot.memory = (0..(chunks * TURNS_PER_SUMMARY + RECENT_TURNS) * 2)
+            .map(|i| {
+                message(
+                    if i % 2 == 0 { "user" } else { "assistant" },
+                    &format!("m{}", i),
+                )
+            })
+            .collect();
+        for i in 1..=chunks {
+            client.respond(200, reply(&format!("summary {}", i)));
+        }
+        client.respond(200, reply("epoch"));
+
+        compact_history(&mut chatbot).await.unwrap();
+        let compaction = chatbot.compaction.clone();
+        assert_eq!(compaction.compacted, chunks * TURNS_PER_SUMMARY * 2);
+        assert_eq!(compaction.summaries, vec!["summary 4", "summary 5"]);
+        assert_eq!(compaction.epoch.as_deref(), Some("epoch"));
+        {
+            let requests = client.requests.lock().unwrap();
+            assert_eq!(requests.len(), chunks + 1);
+            // Each chunk is summarized once, and the epoch merges only the older summaries
+            assert!(prompt(&requests[1]).contains("user: m12"));
+            assert!(!prompt(&requests[1]).contains("user: m10\n"));
+            let epoch = prompt(&requests[chunks]);
+            assert!(epoch.contains("summary 1") && epoch.contains("summary 3"));
+            assert!(!epoch.contains("summary 4"));
+        }
+
+        // Nothing new is due, so no request is made
+        compact_history(&mut chatbot).await.unwrap();
+        assert_eq!(client.requests.lock().unwrap().len(), chunks + 1);
+
+        // A failed summary leaves the compaction as it was
+        chatbot
+            .memory
+            .extend(chatbot.memory[..TURNS_PER_SUMMARY * 2].to_vec());
+        client.respond(500, "overloaded");
+        assert!(compact_history(&mut chatbot).await.is_err());
+        assert_eq!(chatbot.compaction, compaction);
+
+        // A cleared memory starts over
+        chatbot.memory.clear();
+        compact_history(&mut chatbot).await.unwrap();
+        assert_eq!(chatbot.compaction, HistoryCompaction::default());
+    }
+}
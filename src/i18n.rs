@@ -0,0 +1,73 @@
+// src/i18n.rs
+//! Translations of user-facing strings. Catalogs are keyed by the English text, so
+//! anything without a translation is shown in English.
+use std::collections::HashMap;
+
+// Locale code and its catalog, built into the binary
+const CATALOGS: [(&str, &str); 1] = [("es", include_str!("../locales/es.json"))];
+
+/// The messages of one locale, looked up when strings are drawn
+#[derive(Debug, Default)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+/// The language part of a locale like "es_ES.UTF-8"; empty takes it from LANG
+fn language(locale: &str) -> String {
+    let locale = if locale.trim().is_empty() {
+        std::env::var("LANG").unwrap_or_default()
+    } else {
+        locale.to_string()
+    };
+    locale
+        .split(['_', '-', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+impl Catalog {
+    /// The catalog for `locale`; English, or a locale without a catalog, translates
+    /// nothing
+    pub fn load(locale: &str) -> Catalog {
+        let language = language(locale);
+        let messages = CATALOGS
+            .iter()
+            .find(|(code, _)| *code == language)
+            .and_then(|(_, catalog)| serde_json::from_str(catalog).ok())
+            .unwrap_or_default();
+        Catalog { messages }
+    }
+
+    pub fn tr<'a>(&'a self, text: &'a str) -> &'a str {
+        self.messages.get(text).map_or(text, String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_fall_back_to_english() {
+        let spanish = Catalog::load("es_ES.UTF-8");
+        assert_eq!(spanish.tr("❓ Help"), "❓ Ayuda");
+        assert_eq!(spanish.tr("Not in any catalog"), "Not in any catalog");
+        assert_eq!(Catalog::load("en").tr("❓ Help"), "❓ Help");
+        assert_eq!(Catalog::load("fr").tr("❓ Help"), "❓ Help");
+    }
+
+    #[test]
+    fn shipped_catalogs_parse_and_locales_match_by_language() {
+        // A catalog that fails to parse would silently show English
+        for (code, catalog) in CATALOGS {
+            let messages: HashMap<String, String> = serde_json::from_str(catalog)
+                .unwrap_or_else(|e| panic!("locales/{}.json: {}", code, e));
+            assert!(messages.values().all(|text| !text.trim().is_empty()));
+        }
+        assert_eq!(language("ES"), "es");
+        assert_eq!(language("pt-BR"), "pt");
+        assert_eq!(language("es.UTF-8"), "es");
+        assert_eq!(Catalog::load("ES-mx").tr("❓ Help"), "❓ Ayuda");
+    }
+}
@@ -0,0 +1,113 @@
+// src/identifier_completion.rs
+
+/// Completion of the identifier being typed at the end of the input; pressing Ctrl+N
+/// again cycles through the other matches
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentifierCompletion {
+    // Byte offset in the input where the completed word starts
+    start: usize,
+    candidates: Vec<String>,
+    current: usize,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == ':'
+}
+
+impl IdentifierCompletion {
+    /// Starts completing the last word of the input against the known symbols; exact
+    /// prefix matches come first, then case-insensitive ones
+    pub fn begin(input: &str, symbols: &[String]) -> Option<IdentifierCompletion> {
+        let start = input
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_identifier_char(*c))
+            .last()
+            .map(|(i, _)| i)?;
+        let word = &input[start..];
+        let lowered = word.to_lowercase();
+        let mut candidates: Vec<String> = symbols
+            .iter()
+            .filter(|symbol| symbol.as_str() != word && symbol.starts_with(word))
+            .cloned()
+            .collect();
+        candidates.extend(
+            symbols
+                .iter()
+                .filter(|symbol| {
+                    !symbol.starts_with(word) && symbol.to_lowercase().starts_with(&lowered)
+                })
+                .cloned(),
+        );
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(IdentifierCompletion {
+            start,
+            candidates,
+            current: 0,
+        })
+    }
+
+    /// Replaces the word being completed with the current candidate
+    pub fn apply(&self, input: &mut String) {
+        input.truncate(self.start);
+        input.push_str(&self.candidates[self.current]);
+    }
+
+    pub fn advance(&mut self) {
+        self.current = (self.current + 1) % self.candidates.len();
+    }
+
+    /// "2/5" for the status line
+    pub fn position(&self) -> String {
+        format!("{}/{}", self.current + 1, self.candidates.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_through_matches_for_the_last_word() {
+        let symbols: Vec<String> = ["chat", "Chatbot", "ChatFocus", "index_codebase"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut input = "where is Chat".to_string();
+        let mut completion = IdentifierCompletion::begin(&input, &symbols).unwrap();
+        completion.apply(&mut input);
+        assert_eq!(input, "where is Chatbot");
+        completion.advance();
+        completion.apply(&mut input);
+        assert_eq!(input, "where is ChatFocus");
+        completion.advance();
+        completion.apply(&mut input);
+        assert_eq!(input, "where is chat");
+        assert!(IdentifierCompletion::begin("ends with space ", &symbols).is_none());
+    }
+
+    #[test]
+    fn paths_complete_as_one_word_and_exact_matches_are_skipped() {
+        let symbols: Vec<String> = ["Chatbot", "Chatbot::chat", "Chatbot::chat_stream", "résumé"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut input = "does Chatbot::chat".to_string();
+        let mut completion = IdentifierCompletion::begin(&input, &symbols).unwrap();
+        completion.apply(&mut input);
+        assert_eq!(input, "does Chatbot::chat_stream");
+        // The only other candidate was the word itself, so there is nothing to cycle to
+        assert_eq!(completion.position(), "1/1");
+        completion.advance();
+        assert_eq!(completion.position(), "1/1");
+
+        let mut input = "über RÉS".to_string();
+        IdentifierCompletion::begin(&input, &symbols)
+            .unwrap()
+            .apply(&mut input);
+        assert_eq!(input, "über résumé");
+        assert!(IdentifierCompletion::begin("", &symbols).is_none());
+    }
+}
@@ -0,0 +1,320 @@
+// src/imports.rs
+use std::path::{Component, Path, PathBuf};
+
+/// Finds the crate's `src` directory that contains `file_path`
+fn rust_src_root(file_path: &Path) -> Option<PathBuf> {
+    file_path
+        .ancestors()
+        .find(|dir| dir.file_name().is_some_and(|name| name == "src"))
+        .map(Path::to_path_buf)
+}
+
+/// Resolves a module path like `foo::bar` to `foo/bar.rs` or `foo/bar/mod.rs`
+fn resolve_rust_module(base: &Path, segments: &[&str]) -> Option<PathBuf> {
+    if segments.is_empty() {
+        return None;
+    }
+    let joined: PathBuf = segments.iter().collect();
+    let as_file = base.join(&joined).with_extension("rs");
+    if as_file.is_file() {
+        return Some(as_file);
+    }
+    let as_mod = base.join(&joined).join("mod.rs");
+    if as_mod.is_file() {
+        return Some(as_mod);
+    }
+    // `use crate::foo::Item` refers to foo.rs, so retry without the last segment
+    resolve_rust_module(base, &segments[..segments.len() - 1])
+}
+
+// Function to list local Rust modules referenced by `mod` and `use crate::` lines
+fn rust_dependencies(file_path: &Path, content: &str) -> Vec<PathBuf> {
+    let Some(src_root) = rust_src_root(file_path) else {
+        return Vec::new();
+    };
+    let parent = file_path.parent().unwrap_or(&src_root).to_path_buf();
+    // `mod x;` in mod.rs, lib.rs or main.rs is a sibling file; in foo.rs it is foo/x.rs
+    let owns_directory = matches!(
+        file_path.file_name().and_then(|name| name.to_str()),
+        Some("mod.rs" | "lib.rs" | "main.rs")
+    );
+    let mod_dir = match file_path.file_stem() {
+        Some(stem) if !owns_directory => parent.join(stem),
+        _ => parent,
+    };
+    let mut deps = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        if let Some(rest) = line
+            .strip_prefix("pub mod ")
+            .or_else(|| line.strip_prefix("mod "))
+        {
+            let name = rest.trim_end_matches(';').trim();
+            if rest.ends_with(';') {
+                if let Some(path) = resolve_rust_module(&mod_dir, &[name]) {
+                    deps.push(path);
+                }
+            }
+        } else if let Some(rest) = line
+            .strip_prefix("pub use crate::")
+            .or_else(|| line.strip_prefix("use crate::"))
+        {
+            let path_part = rest.split(['{', ';']).next().unwrap_or("");
+            let segments: Vec<&str> = path_part
+                .split("::")
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .collect();
+            if let Some(path) = resolve_rust_module(&src_root, &segments) {
+                deps.push(path);
+            }
+        }
+    }
+
+    deps
+}
+
+// Function to resolve `import a.b` and `from .c import d` lines to local Python files
+fn python_dependencies(file_path: &Path, content: &str) -> Vec<PathBuf> {
+    let parent = file_path.parent().unwrap_or(Path::new("."));
+    let mut deps = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        let module = if let Some(rest) = line.strip_prefix("from ") {
+            rest.split_whitespace().next()
+        } else if let Some(rest) = line.strip_prefix("import ") {
+            rest.split([',', ' ']).next()
+        } else {
+            None
+        };
+        let Some(module) = module else {
+            continue;
+        };
+
+        // Leading dots are relative to this file's package; otherwise try the working directory
+        let dots = module.chars().take_while(|c| *c == '.').count();
+        let base = if dots > 0 {
+            parent
+                .ancestors()
+                .nth(dots - 1)
+                .unwrap_or(parent)
+                .to_path_buf()
+        } else {
+            PathBuf::from(".")
+        };
+        let relative: PathBuf = module[dots..]
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .collect();
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let as_file = base.join(&relative).with_extension("py");
+        let as_package = base.join(&relative).join("__init__.py");
+        if as_file.is_file() {
+            deps.push(as_file);
+        } else if as_package.is_file() {
+            deps.push(as_package);
+        }
+    }
+
+    deps
+}
+
+/// Joins a relative specifier like `./x` or `../lib/y` onto `dir` without leaving `.` or
+/// `..` in the middle, so the result matches index keys
+fn join_relative(dir: &Path, specifier: &str) -> PathBuf {
+    let mut path = dir.to_path_buf();
+    for component in Path::new(specifier).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(path.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                path.pop();
+            }
+            other => path.push(other),
+        }
+    }
+    path
+}
+
+// Function to resolve relative `import ... from './x'` and `require('./x')` specifiers
+fn javascript_dependencies(file_path: &Path, content: &str) -> Vec<PathBuf> {
+    const EXTENSIONS: [&str; 4] = ["ts", "tsx", "js", "jsx"];
+    let parent = file_path.parent().unwrap_or(Path::new("."));
+    let mut deps = Vec::new();
+
+    for line in content.lines() {
+        if !(line.contains("import") || line.contains("require(")) {
+            continue;
+        }
+        // The specifier is the first quoted string starting with '.'
+        let specifier = line
+            .split(['\'', '"', '`'])
+            .skip(1)
+            .step_by(2)
+            .find(|s| s.starts_with('.'));
+        let Some(specifier) = specifier else {
+            continue;
+        };
+        let target = join_relative(parent, specifier);
+        let resolved = if target.is_file() {
+            Some(target.clone())
+        } else {
+            EXTENSIONS
+                .iter()
+                .map(|ext| target.with_extension(ext))
+                .chain(
+                    EXTENSIONS
+                        .iter()
+                        .map(|ext| target.join(format!("index.{}", ext))),
+                )
+                .find(|candidate| candidate.is_file())
+        };
+        if let Some(path) = resolved {
+            deps.push(path);
+        }
+    }
+
+    deps
+}
+
+/// Lists the local files directly imported by `file_path`, without duplicates
+pub fn direct_dependencies(file_path: &str, content: &str) -> Vec<String> {
+    let path = Path::new(file_path);
+    let deps = match path.extension().and_then(|e| e.to_str()) {
+        Some("rs") => rust_dependencies(path, content),
+        Some("py") => python_dependencies(path, content),
+        Some("ts") | Some("tsx") | Some("js") | Some("jsx") => {
+            javascript_dependencies(path, content)
+        }
+        _ => Vec::new(),
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    deps.into_iter()
+        .filter(|dep| dep != path)
+        .map(|dep| dep.to_string_lossy().to_string())
+        .filter(|dep| seen.insert(dep.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(root: &Path, file: &str, content: &str) -> String {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn names(root: &Path, deps: Vec<String>) -> Vec<String> {
+        deps.iter()
+            .map(|dep| {
+                Path::new(dep)
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rust_mod_lines_resolve_beside_mod_rs_and_under_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let main = write(
+            root,
+            "src/main.rs",
+            "mod ui;\npub mod db;\nmod inline {\n}\n",
+        );
+        write(root, "src/ui/mod.rs", "");
+        write(root, "src/db.rs", "");
+        write(root, "src/inline.rs", "");
+        // A same-named sibling must not be taken for db's child module
+        let db = write(root, "src/db.rs", "mod pool;\n");
+        write(root, "src/pool.rs", "");
+        write(root, "src/db/pool.rs", "");
+        let ui = write(root, "src/ui/mod.rs", "mod chat;\n");
+        write(root, "src/ui/chat.rs", "");
+
+        assert_eq!(
+            names(
+                root,
+                direct_dependencies(&main, &fs::read_to_string(&main).unwrap())
+            ),
+            vec!["src/ui/mod.rs", "src/db.rs"]
+        );
+        assert_eq!(
+            names(root, direct_dependencies(&db, "mod pool;")),
+            vec!["src/db/pool.rs"]
+        );
+        assert_eq!(
+            names(root, direct_dependencies(&ui, "mod chat;")),
+            vec!["src/ui/chat.rs"]
+        );
+    }
+
+    #[test]
+    fn rust_use_crate_resolves_the_module_of_an_item_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/config.rs", "");
+        write(root, "src/ui/chat.rs", "");
+        let app = write(root, "src/app.rs", "");
+        let content = "use crate::config::Config;\n\
+                       use crate::config::{self, load};\n\
+                       pub use crate::ui::chat::render;\n\
+                       use crate::app::App;\n\
+                       use crate::{a, b};\n\
+                       use std::fs;\n";
+        assert_eq!(
+            names(root, direct_dependencies(&app, content)),
+            vec!["src/config.rs", "src/ui/chat.rs"]
+        );
+    }
+
+    #[test]
+    fn relative_python_imports_climb_one_package_per_extra_dot() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "pkg/util.py", "");
+        write(root, "pkg/sub/models/__init__.py", "");
+        let view = write(root, "pkg/sub/view.py", "");
+        let content = "from .models import User\nfrom ..util import helper\nfrom . import x\n";
+        assert_eq!(
+            names(root, direct_dependencies(&view, content)),
+            vec!["pkg/sub/models/__init__.py", "pkg/util.py"]
+        );
+    }
+
+    #[test]
+    fn javascript_specifiers_try_extensions_then_index_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "web/api.ts", "");
+        write(root, "web/components/index.tsx", "");
+        write(root, "web/style.css", "");
+        write(root, "shared/format.js", "");
+        let app = write(root, "web/app.js", "");
+        let content = "import { get } from './api';\n\
+                       const components = require(\"./components\");\n\
+                       import './style.css';\n\
+                       import { format } from '../shared/format';\n\
+                       import React from 'react';\n";
+        assert_eq!(
+            names(root, direct_dependencies(&app, content)),
+            vec![
+                "web/api.ts",
+                "web/components/index.tsx",
+                "web/style.css",
+                "shared/format.js"
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// src/index_progress.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::heartbeat::Heartbeat;
+
+pub const INDEX_PROGRESS_FILE: &str = "index_progress.json";
+
+// Number of recent per-file durations averaged for the ETA
+const ETA_WINDOW: usize = 20;
+
+/// Progress of an indexing run, persisted so an interrupted run can report on restart
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IndexProgress {
+    pub root: String,
+    pub total: usize,
+    pub done: usize,
+    pub current_file: String,
+    pub started_at: DateTime<Utc>,
+    // Milliseconds spent on each file that needed summarizing
+    pub durations_ms: Vec<u64>,
+    // Set while the current file is slow to summarize
+    #[serde(skip)]
+    pub heartbeat: Option<Heartbeat>,
+}
+
+impl IndexProgress {
+    pub fn new(root: &str, total: usize) -> Self {
+        IndexProgress {
+            root: root.to_string(),
+            total,
+            done: 0,
+            current_file: String::new(),
+            started_at: Utc::now(),
+            durations_ms: Vec::new(),
+            heartbeat: None,
+        }
+    }
+
+    pub fn record_duration(&mut self, duration_ms: u64) {
+        self.durations_ms.push(duration_ms);
+        if self.durations_ms.len() > ETA_WINDOW {
+            self.durations_ms.remove(0);
+        }
+    }
+
+    /// Moving-average estimate of the time left, assuming remaining files need summarizing
+    pub fn eta_secs(&self) -> Option<u64> {
+        if self.durations_ms.is_empty() {
+            return None;
+        }
+        let average = self.durations_ms.iter().sum::<u64>() / self.durations_ms.len() as u64;
+        let remaining = self.total.saturating_sub(self.done) as u64;
+        Some(average * remaining / 1000)
+    }
+
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        (self.done as f64 / self.total as f64).min(1.0)
+    }
+
+    pub fn summary(&self) -> String {
+        match self.eta_secs() {
+            Some(eta) => format!(
+                "{}/{} files done, ~{} remaining",
+                self.done,
+                self.total,
+                format_duration(eta)
+            ),
+            None => format!("{}/{} files done", self.done, self.total),
+        }
+    }
+}
+
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+/// Where an indexing run of the project at `root_dir` records its progress, beside its index
+pub fn index_progress_path(root_dir: &str) -> PathBuf {
+    Path::new(root_dir).join(INDEX_PROGRESS_FILE)
+}
+
+pub fn load_index_progress(root_dir: &str) -> Option<IndexProgress> {
+    let contents = fs::read_to_string(index_progress_path(root_dir)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn save_index_progress(
+    root_dir: &str,
+    progress: &IndexProgress,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(
+        index_progress_path(root_dir),
+        serde_json::to_string_pretty(progress)?,
+    )?;
+    Ok(())
+}
+
+/// Forgets a finished run so the next start doesn't offer to resume it
+pub fn clear_index_progress(root_dir: &str) {
+    let _ = fs::remove_file(index_progress_path(root_dir));
+}
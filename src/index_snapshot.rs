@@ -0,0 +1,65 @@
+// src/index_snapshot.rs
+use std::fs;
+use std::path::PathBuf;
+
+// Copies of each file as it was when summarized, next to index_cache.json
+pub const INDEX_SNAPSHOT_DIR: &str = "index_snapshots";
+
+/// How the file on disk differs from the version its summary was written from
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexDiff {
+    pub diff: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl IndexDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0
+    }
+}
+
+fn snapshot_path(file_path: &str) -> PathBuf {
+    // FNV-1a, so names stay the same across toolchains
+    let hash = file_path
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    PathBuf::from(INDEX_SNAPSHOT_DIR).join(format!("{:016x}.txt", hash))
+}
+
+pub fn save_snapshot(file_path: &str, content: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(INDEX_SNAPSHOT_DIR)?;
+    fs::write(snapshot_path(file_path), content)?;
+    Ok(())
+}
+
+pub fn remove_snapshot(file_path: &str) {
+    let _ = fs::remove_file(snapshot_path(file_path));
+}
+
+/// Diff from the summarized version to the current contents; None when no snapshot
+/// was kept, as for files indexed before snapshots existed
+pub fn diff_against_index(file_path: &str, current: &str) -> Option<IndexDiff> {
+    let indexed = fs::read_to_string(snapshot_path(file_path)).ok()?;
+    let patch = diffy::create_patch(&indexed, current);
+    let diff = patch.to_string();
+    let (added, removed) = diff
+        .lines()
+        .filter(|line| !line.starts_with("+++") && !line.starts_with("---"))
+        .fold((0, 0), |(added, removed), line| {
+            if line.starts_with('+') {
+                (added + 1, removed)
+            } else if line.starts_with('-') {
+                (added, removed + 1)
+            } else {
+                (added, removed)
+            }
+        });
+    Some(IndexDiff {
+        diff,
+        added,
+        removed,
+    })
+}
@@ -0,0 +1,150 @@
+// src/index_walker.rs
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+// Project-specific exclusions that shouldn't go in .gitignore, in the same syntax
+pub const SAGACITY_IGNORE_FILE: &str = ".sagacityignore";
+// Extensions of the files that are summarized and retrieved
+pub const INDEXED_EXTENSIONS: [&str; 5] = ["rs", "toml", "md", "py", "go"];
+
+/// The files of a project that get indexed. Honors .gitignore (in a git repository or
+/// not), .ignore, .sagacityignore and the `index_exclude` globs of the config; hidden
+/// files are included, except for the .git directory
+#[derive(Debug, Clone)]
+pub struct IndexWalker {
+    root: PathBuf,
+    excludes: Vec<String>,
+}
+
+impl IndexWalker {
+    pub fn new(root: impl AsRef<Path>) -> IndexWalker {
+        IndexWalker {
+            root: root.as_ref().to_path_buf(),
+            excludes: Vec::new(),
+        }
+    }
+
+    /// Leaves out files matching these globs as well
+    pub fn exclude(mut self, globs: &[String]) -> IndexWalker {
+        self.excludes.extend(globs.iter().cloned());
+        self
+    }
+
+    fn builder(&self) -> Result<WalkBuilder, Box<dyn std::error::Error>> {
+        let mut overrides = OverrideBuilder::new(&self.root);
+        for glob in &self.excludes {
+            overrides.add(&format!("!{}", glob.trim()))?;
+        }
+        let mut builder = WalkBuilder::new(&self.root);
+        builder
+            .hidden(false)
+            .ignore(true)
+            .git_ignore(true)
+            .git_global(false)
+            .git_exclude(true)
+            .require_git(false)
+            .add_custom_ignore_filename(SAGACITY_IGNORE_FILE)
+            .overrides(overrides.build()?)
+            .filter_entry(|entry| entry.file_name() != ".git");
+        Ok(builder)
+    }
+
+    /// Paths of the indexed files, as the root joined with their relative path
+    pub fn files(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut files: Vec<String> = self
+            .builder()?
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|extension| INDEXED_EXTENSIONS.contains(&extension))
+            })
+            .map(|entry| entry.path().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn every_ignore_source_applies_without_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, contents) in [
+            (".gitignore", "target/\n"),
+            (".ignore", "scratch.md\n"),
+            (SAGACITY_IGNORE_FILE, "fixtures/\n"),
+            ("src/main.rs", "fn main() {}"),
+            ("src/generated.rs", "// generated"),
+            ("src/notes.txt", "not indexed"),
+            (".github/README.md", "hidden but kept"),
+            ("target/debug/build.rs", "ignored"),
+            ("scratch.md", "ignored"),
+            ("fixtures/case.rs", "ignored"),
+            (".git/hooks/pre-commit.py", "never walked"),
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        let files = IndexWalker::new(root)
+            .exclude(&["**/generated.rs".to_string()])
+            .files()
+            .unwrap();
+        let relative: Vec<String> = files
+            .iter()
+            .map(|file| {
+                Path::new(file)
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(relative, vec![".github/README.md", "src/main.rs"]);
+    }
+
+    #[test]
+    fn nested_ignores_and_negations_apply_and_bad_globs_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, contents) in [
+            ("crates/a/.gitignore", "*.md\n!KEEP.md\n"),
+            ("crates/a/notes.md", "ignored below a"),
+            ("crates/a/KEEP.md", "re-included"),
+            ("crates/b/notes.md", "not under a's .gitignore"),
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        let files = IndexWalker::new(root).files().unwrap();
+        let names: Vec<&str> = files
+            .iter()
+            .map(|file| file.strip_prefix(&*root.to_string_lossy()).unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                format!("{0}crates{0}a{0}KEEP.md", std::path::MAIN_SEPARATOR),
+                format!("{0}crates{0}b{0}notes.md", std::path::MAIN_SEPARATOR),
+            ]
+        );
+        assert!(IndexWalker::new(root)
+            .exclude(&["src/[".to_string()])
+            .files()
+            .is_err());
+    }
+}
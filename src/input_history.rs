@@ -0,0 +1,102 @@
+// src/input_history.rs
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const INPUT_HISTORY_FILE: &str = "input_history.json";
+// Entries kept across runs, oldest dropped first
+const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// Questions and commands sent from the chat input, oldest first, recalled with
+/// Ctrl+Up and Ctrl+Down
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    // Entry being shown while recalling, and the text typed before recall began;
+    // only entries starting with that text are recalled
+    #[serde(skip)]
+    cursor: Option<usize>,
+    #[serde(skip)]
+    prefix: String,
+}
+
+impl InputHistory {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(INPUT_HISTORY_FILE);
+        let mut history: InputHistory = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        history.path = Some(path);
+        history
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string(self)?)?;
+        }
+        Ok(())
+    }
+
+    /// Records a sent input as the newest entry, moving an identical older one
+    pub fn push(&mut self, entry: &str) {
+        self.cursor = None;
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return;
+        }
+        self.entries.retain(|existing| existing != entry);
+        self.entries.push(entry.to_string());
+        if self.entries.len() > MAX_HISTORY_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    /// The next older entry matching the prefix; `input` becomes the prefix when recall
+    /// starts. None when there is nothing older
+    pub fn older(&mut self, input: &str) -> Option<String> {
+        if self.cursor.is_none() {
+            self.prefix = input.to_string();
+        }
+        let end = self.cursor.unwrap_or(self.entries.len());
+        let found = self.entries[..end]
+            .iter()
+            .rposition(|entry| entry.starts_with(&self.prefix))?;
+        self.cursor = Some(found);
+        Some(self.entries[found].clone())
+    }
+
+    /// The next newer matching entry, or the typed prefix once past the newest
+    pub fn newer(&mut self) -> Option<String> {
+        let cursor = self.cursor?;
+        let found = self.entries[cursor + 1..]
+            .iter()
+            .position(|entry| entry.starts_with(&self.prefix))
+            .map(|offset| cursor + 1 + offset);
+        self.cursor = found;
+        Some(match found {
+            Some(index) => self.entries[index].clone(),
+            None => self.prefix.clone(),
+        })
+    }
+
+    /// Ends recall, keeping whatever entry is in the input for editing
+    pub fn reset(&mut self) {
+        self.cursor = None;
+    }
+
+    /// Position among the matching entries while recalling, counted from the newest
+    pub fn position(&self) -> Option<(usize, usize)> {
+        let cursor = self.cursor?;
+        let matching = |entry: &&String| entry.starts_with(&self.prefix);
+        let total = self.entries.iter().filter(matching).count();
+        let newer = self.entries[cursor + 1..].iter().filter(matching).count();
+        Some((newer + 1, total))
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+}
@@ -0,0 +1,195 @@
+// src/instance_lock.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+// Sits next to index_cache.json, which every instance in the directory shares
+pub const LOCK_FILE: &str = "sagacity.lock";
+// The instance that creates the lock writes it right after, so a lock that can't be
+// parsed yet is read again for a moment before it counts as unreadable
+const LOCK_READ_ATTEMPTS: u32 = 10;
+const LOCK_READ_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Which instance owns a project's index, so a second one doesn't write it concurrently
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockInfo {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockOutcome {
+    Acquired,
+    HeldBy(LockInfo),
+}
+
+fn lock_path(project_root: &Path) -> PathBuf {
+    project_root.join(LOCK_FILE)
+}
+
+fn read_lock(project_root: &Path) -> Option<LockInfo> {
+    fs::read_to_string(lock_path(project_root))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+}
+
+// The lock as its holder wrote it: None when there is no lock file, an error when there
+// is one that stays unreadable
+fn read_existing_lock(project_root: &Path) -> io::Result<Option<LockInfo>> {
+    let path = lock_path(project_root);
+    for attempt in 1..=LOCK_READ_ATTEMPTS {
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                if let Ok(holder) = serde_json::from_str(&contents) {
+                    return Ok(Some(holder));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        if attempt < LOCK_READ_ATTEMPTS {
+            thread::sleep(LOCK_READ_INTERVAL);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} exists but can't be read; delete it if no other instance is running",
+            path.display()
+        ),
+    ))
+}
+
+fn our_lock() -> LockInfo {
+    LockInfo {
+        pid: std::process::id(),
+        started_at: Utc::now(),
+    }
+}
+
+fn is_running(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some()
+}
+
+/// Takes the project lock unless a live instance holds it; locks left behind by
+/// crashed instances are replaced
+pub fn try_acquire(project_root: &Path) -> io::Result<LockOutcome> {
+    if let Some(holder) = read_existing_lock(project_root)? {
+        if holder.pid != std::process::id() && is_running(holder.pid) {
+            return Ok(LockOutcome::HeldBy(holder));
+        }
+        // Unless another instance replaced the stale lock in the meantime
+        if read_existing_lock(project_root)? == Some(holder) {
+            match fs::remove_file(lock_path(project_root)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+    }
+    // create_new so two instances starting together can't both win
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(lock_path(project_root))
+    {
+        Ok(mut file) => {
+            file.write_all(serde_json::to_string(&our_lock())?.as_bytes())?;
+            Ok(LockOutcome::Acquired)
+        }
+        // Another instance created it first; the lock is never taken without a file of our own
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            match read_existing_lock(project_root)? {
+                Some(holder) => Ok(LockOutcome::HeldBy(holder)),
+                None => Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "another instance is taking the project lock",
+                )),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Claims the lock from a live instance, which finds out before its next write
+pub fn take_over(project_root: &Path) -> io::Result<()> {
+    fs::write(lock_path(project_root), serde_json::to_string(&our_lock())?)
+}
+
+pub fn holds_lock(project_root: &Path) -> bool {
+    read_lock(project_root).is_some_and(|holder| holder.pid == std::process::id())
+}
+
+pub fn lock_holder(project_root: &Path) -> Option<LockInfo> {
+    read_lock(project_root)
+}
+
+/// Removes the lock on exit, unless another instance took it over
+pub fn release(project_root: &Path) {
+    if holds_lock(project_root) {
+        let _ = fs::remove_file(lock_path(project_root));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn write_lock(root: &Path, pid: u32) {
+        let holder = LockInfo {
+            pid,
+            started_at: Utc::now(),
+        };
+        fs::write(lock_path(root), serde_json::to_string(&holder).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn a_live_holder_keeps_the_lock_and_a_dead_one_loses_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut live = Command::new("sleep").arg("5").spawn().unwrap();
+        write_lock(dir.path(), live.id());
+        assert!(matches!(
+            try_acquire(dir.path()).unwrap(),
+            LockOutcome::HeldBy(holder) if holder.pid == live.id()
+        ));
+        live.kill().unwrap();
+        live.wait().unwrap();
+
+        assert_eq!(try_acquire(dir.path()).unwrap(), LockOutcome::Acquired);
+        assert!(holds_lock(dir.path()));
+    }
+
+    #[test]
+    fn a_lock_being_written_is_waited_for_and_never_taken() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut live = Command::new("sleep").arg("5").spawn().unwrap();
+        // Created by another instance that hasn't written it yet
+        fs::write(lock_path(dir.path()), "").unwrap();
+        let root = dir.path().to_path_buf();
+        let pid = live.id();
+        let writer = thread::spawn(move || {
+            thread::sleep(LOCK_READ_INTERVAL * 2);
+            write_lock(&root, pid);
+        });
+        assert!(matches!(
+            try_acquire(dir.path()).unwrap(),
+            LockOutcome::HeldBy(holder) if holder.pid == pid
+        ));
+        writer.join().unwrap();
+        live.kill().unwrap();
+        live.wait().unwrap();
+
+        // One that stays unreadable is reported rather than taken
+        fs::write(lock_path(dir.path()), "{").unwrap();
+        assert!(try_acquire(dir.path()).is_err());
+        assert!(!holds_lock(dir.path()));
+    }
+}
@@ -1,31 +1,220 @@
+mod answer_cache;
+mod answer_error;
 mod app;
+mod ask_about;
+mod audit;
+mod bench;
+mod branches;
+mod briefing;
+mod carry_over;
+mod change_ledger;
+mod chaos;
+mod chatbot;
+mod churn;
+mod claude_client;
+mod clipboard_ring;
+mod commands;
+mod config;
+mod constants;
+mod content_cache;
+mod conversation_audit;
+mod dataset;
+mod debug_log;
+mod deepdive;
+mod dialog;
+mod digest;
+mod doc_artifacts;
+mod draft;
+mod duplicates;
+mod embeddings;
+mod events;
+mod eviction;
+mod extract;
+mod failed_summaries;
+mod file_viewer;
+mod gen_tests;
+mod gist;
+mod git_hooks;
+mod glossary;
+mod handoff;
+mod heartbeat;
+mod heatmap;
+mod history_compaction;
+mod i18n;
+mod identifier_completion;
+mod imports;
+mod index_progress;
+mod index_snapshot;
+mod index_walker;
+mod input_history;
+mod instance_lock;
+mod model_routing;
+mod models;
+mod open_files;
+mod org_policy;
+mod outline;
+mod privacy;
+mod profiler;
+mod profiles;
+mod project_status;
+mod projects;
+mod prompts;
+mod query_suggestions;
+mod question_lint;
+mod question_templates;
+mod refactor_plan;
+mod refine;
+mod remote;
+mod repo_map;
+mod report;
+mod rerank;
+mod response;
+mod retrieval_memory;
+mod rust_check;
+mod sampling;
+mod saved_responses;
+mod schema_drift;
+mod scrubber;
+mod search;
+mod session;
+mod shared_summaries;
+mod startup;
+mod structured;
+mod style;
+mod summary_postprocess;
+mod sweep;
+#[cfg(test)]
+mod test_harness;
+mod todos;
+mod token_counter;
+mod token_usage;
+mod transcript_log;
+mod transcript_print;
+mod truncation;
+mod turn_latency;
 pub mod ui;
+mod usage;
+mod viewer;
+mod whats_new;
+mod workspace;
 
 use app::*;
+use dialog::DialogKind;
+use structured::StructuredAnswer;
+use truncation::STRATEGIES;
+use ui::audit::draw_audit;
+use ui::bookmarks::draw_bookmarks;
+use ui::branch_switcher::draw_branch_switcher;
 use ui::chat::draw_chat;
-use ui::chat::{Message, Sender};
+use ui::clipboard_ring::draw_clipboard_ring;
+use ui::dialog::draw_dialog;
+use ui::file_viewer::draw_file_viewer;
 use ui::footer::draw_footer;
 use ui::header::draw_header;
+use ui::heatmap::draw_heatmap;
+use ui::indexing::draw_indexing;
+use ui::lock_conflict::draw_lock_conflict;
 use ui::main_menu::draw_main_menu;
+use ui::outline::draw_outline;
 use ui::placeholder::draw_placeholder;
+use ui::profile::draw_profile;
+use ui::projects::draw_projects;
 use ui::quit_confirm::draw_quit_confirm;
+use ui::refactor_plan::draw_refactor_plan;
+use ui::remote_repo::draw_remote_repo;
+use ui::restore_draft::draw_restore_draft;
+use ui::ribbon::draw_ribbon;
+use ui::session_switcher::draw_session_switcher;
+use ui::startup_error::draw_startup_error;
+use ui::streaming::draw_streaming;
+use ui::sweep::draw_sweep;
+use ui::theme::{apply_color_mode, color_mode};
+use ui::todos::draw_todos;
+use ui::truncation::draw_truncation;
+use ui::usage::draw_usage;
+use ui::viewer::draw_viewer;
+use ui::whats_new::draw_whats_new;
+use viewer::Viewer;
 
 use crossterm::{
+    cursor,
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyModifiers,
+        self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyEvent,
+        KeyModifiers,
     },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::{Backend, CrosstermBackend},
     layout::{Constraint, Direction, Layout},
     Frame, Terminal,
 };
-use std::{error::Error as StdError, io, time::Duration};
+use std::{
+    error::Error as StdError,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+// How long the UI loop waits for input while a request or task is running, and while idle
+const ACTIVE_TICK: Duration = Duration::from_millis(100);
+const IDLE_TICK: Duration = Duration::from_secs(1);
+// An idle screen is still redrawn this often so its clock and relative times stay current
+const IDLE_REDRAW_INTERVAL: Duration = Duration::from_secs(30);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn StdError>> {
+    // An editor plugin passes the files it has open with `--open-files a.rs,b.rs`
+    let (args, open_files) = open_files::split_open_files(
+        std::env::args().skip(1).collect(),
+        &std::env::current_dir()?,
+    );
+    // `sagacity --safe-mode` starts from persisted state alone, making no network calls
+    // and starting no indexing or background work
+    let safe_mode = args.iter().any(|arg| arg == "--safe-mode");
+    // A policy served from a URL is fetched before startup checks read it; when the
+    // fetch fails, the last downloaded copy applies. Git hooks, the export viewer and
+    // audit verification don't wait on the network
+    let offline_command = matches!(
+        args.first().map(String::as_str),
+        Some("hook" | "view" | "verify-audit")
+    );
+    if !safe_mode && !offline_command {
+        let _ = org_policy::refresh_remote_policy().await;
+    }
+    // `sagacity report` writes an onboarding document without starting the UI
+    if args.first().map(String::as_str) == Some("report") {
+        return run_report(&args[1..]).await;
+    }
+    // `sagacity ask [--json] <question>` answers once on stdout for scripts
+    if args.first().map(String::as_str) == Some("ask") {
+        return run_ask(&args[1..], open_files).await;
+    }
+    // `sagacity search <query>` ranks files without asking for an answer
+    if args.first().map(String::as_str) == Some("search") {
+        return run_search(&args[1..], open_files).await;
+    }
+    // `sagacity verify-audit` checks the conversation audit log for tampering
+    if args.first().map(String::as_str) == Some("verify-audit") {
+        return run_verify_audit();
+    }
+    // `sagacity hook install` adds the git hooks; git runs `sagacity hook run <hook>`
+    if args.first().map(String::as_str) == Some("hook") {
+        return run_hook(&args[1..]).await;
+    }
+    // `sagacity view <export>` browses an exported conversation without an API key
+    if args.first().map(String::as_str) == Some("view") {
+        return run_view(&args[1..]);
+    }
+    // `sagacity bench [--full]` times index load, retrieval, prompt assembly and rendering
+    if args.first().map(String::as_str) == Some("bench") {
+        return run_bench(&args[1..]).await;
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -33,11 +222,27 @@ async fn main() -> Result<(), Box<dyn StdError>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create application instance
-    let mut app = App::new();
+    // Create application instance, falling back to the error screen if startup checks fail
+    let mut app = match startup::run_startup_checks() {
+        Ok(config) => App::new(config),
+        Err(error) => App::with_startup_error(error),
+    };
+    app.chatbot.open_files = open_files;
+    if safe_mode {
+        app.enter_safe_mode();
+    }
+    let size = terminal.size()?;
+    app.on_resize(size.width, size.height);
+    app.check_instance_lock();
+    app.check_for_draft();
+    app.check_whats_new();
+    app.write_missing_digest();
+    app.check_reindex_queue();
 
-    // Run the UI
+    // Run the UI, keeping an unsent draft around if the user quit while typing
     let res = run_ui(&mut terminal, &mut app).await;
+    app.autosave_draft(true);
+    instance_lock::release(Path::new("."));
 
     // Restore terminal
     disable_raw_mode()?;
@@ -55,142 +260,1050 @@ async fn main() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+/// Indexes a project and writes its onboarding report, reporting progress on stderr
+async fn run_report(args: &[String]) -> Result<(), Box<dyn StdError>> {
+    let config = startup::run_startup_checks()
+        .map_err(|error| format!("{}: {}", error.title(), error.detail))?;
+    let mut app = App::new(config);
+    // The report reindexes, so it must not run beside an instance that owns the index
+    if let instance_lock::LockOutcome::HeldBy(holder) = instance_lock::try_acquire(Path::new("."))?
+    {
+        return Err(format!(
+            "Another sagacity instance (pid {}) is using this project; close it first",
+            holder.pid
+        )
+        .into());
+    }
+    let result = report::run_report_command(args, &mut app.chatbot).await;
+    instance_lock::release(Path::new("."));
+    let path = result?;
+    println!("Onboarding report written to {}", path.display());
+    Ok(())
+}
+
+/// Answers one question from the existing index; with --json, prints the structured answer
+async fn run_ask(args: &[String], open_files: Vec<String>) -> Result<(), Box<dyn StdError>> {
+    let json = args.iter().any(|arg| arg == "--json");
+    let question = args
+        .iter()
+        .filter(|arg| *arg != "--json")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if question.trim().is_empty() {
+        return Err("Usage: sagacity ask [--json] <question>".into());
+    }
+    let config = startup::run_startup_checks()
+        .map_err(|error| format!("{}: {}", error.title(), error.detail))?;
+    let mut app = App::new(config);
+    app.chatbot.open_files = open_files;
+    app.chatbot.structured_answers |= json;
+    app.ask(&question, &mut |_| {}).await;
+    let answer = app.messages.pop().ok_or("No answer")?;
+    if !json {
+        println!("{}", answer.content);
+    } else {
+        // A reply that wasn't valid JSON is still returned in the same shape
+        let structured = answer.structured.unwrap_or(StructuredAnswer {
+            answer: answer.content,
+            confidence: None,
+            sources: Vec::new(),
+        });
+        println!("{}", serde_json::to_string_pretty(&structured)?);
+    }
+    Ok(())
+}
+
+/// Prints the files retrieval ranks for a query, with scores and matching lines
+async fn run_search(args: &[String], open_files: Vec<String>) -> Result<(), Box<dyn StdError>> {
+    let query = args.join(" ");
+    if query.trim().is_empty() {
+        return Err("Usage: sagacity search <query>".into());
+    }
+    let config = startup::run_startup_checks()
+        .map_err(|error| format!("{}: {}", error.title(), error.detail))?;
+    let mut app = App::new(config);
+    app.chatbot.open_files = open_files;
+    let hits = search::search(&mut app.chatbot, &query).await?;
+    println!("{}", search::format_hits(&hits));
+    Ok(())
+}
+
+/// Walks the conversation audit chain, failing at the first entry that doesn't check out
+fn run_verify_audit() -> Result<(), Box<dyn StdError>> {
+    let config = startup::run_startup_checks()
+        .map_err(|error| format!("{}: {}", error.title(), error.detail))?;
+    let path = config
+        .data_path()
+        .join(conversation_audit::CONVERSATION_AUDIT_FILE);
+    let count = conversation_audit::verify_chain(&path)
+        .map_err(|error| format!("Audit log {} is not intact: {}", path.display(), error))?;
+    println!("Audit log {} is intact: {} entries", path.display(), count);
+    Ok(())
+}
+
+/// Prints how long the paths behind the app's responsiveness take on this project
+async fn run_bench(args: &[String]) -> Result<(), Box<dyn StdError>> {
+    let full = args.iter().any(|arg| arg == "--full");
+    let config = startup::run_startup_checks()
+        .map_err(|error| format!("{}: {}", error.title(), error.detail))?;
+    // Timed before App::new, which loads the index cache itself
+    let index_load = bench::cold_index_load()?;
+    let mut app = App::new(config);
+    let mut measurements = vec![index_load];
+    measurements.extend(bench::run_bench(&mut app, full, ui).await?);
+    print!("{}", bench::format_report(&measurements));
+    Ok(())
+}
+
+/// Installs the git hooks, or, run by one, queues the files a commit or checkout changed
+/// and reindexes them unless another instance has the project open
+async fn run_hook(args: &[String]) -> Result<(), Box<dyn StdError>> {
+    match args.first().map(String::as_str) {
+        Some("install") => {
+            let installed = git_hooks::install_hooks(Path::new("."))?;
+            if installed.is_empty() {
+                println!("Sagacity's git hooks are already installed");
+            }
+            for hook in installed {
+                println!("Installed {}", hook.display());
+            }
+            Ok(())
+        }
+        Some("run") => {
+            let hook = args
+                .get(1)
+                .ok_or("Usage: sagacity hook run <hook> [args]")?;
+            let files = git_hooks::changed_files(Path::new("."), hook, &args[2..])?;
+            if files.is_empty() || git_hooks::queue_for_reindex(".", &files)? == 0 {
+                return Ok(());
+            }
+            let config = startup::run_startup_checks()
+                .map_err(|error| format!("{}: {}", error.title(), error.detail))?;
+            // An open instance owns the index; the queue waits for its next index pass
+            if let instance_lock::LockOutcome::HeldBy(_) =
+                instance_lock::try_acquire(Path::new("."))?
+            {
+                return Ok(());
+            }
+            let mut app = App::new(config);
+            app.index_current_dir(&mut |_| {}).await;
+            instance_lock::release(Path::new("."));
+            Ok(())
+        }
+        _ => Err("Usage: sagacity hook install".into()),
+    }
+}
+
+/// Opens an exported transcript, dataset or session in a read-only viewer; needs no
+/// config or API key
+fn run_view(args: &[String]) -> Result<(), Box<dyn StdError>> {
+    let path = args
+        .first()
+        .ok_or("Usage: sagacity view <transcript.md|dataset.jsonl|session.json>")?;
+    let mut viewer = Viewer::new(viewer::load_export(Path::new(path))?);
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    let res = view_loop(&mut terminal, &mut viewer);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    res
+}
+
+fn view_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    viewer: &mut Viewer,
+) -> Result<(), Box<dyn StdError>> {
+    loop {
+        terminal.draw(|f| draw_viewer(f, viewer))?;
+        let CEvent::Key(key) = event::read()? else {
+            continue;
+        };
+        let page = viewer.page as isize;
+        if viewer.searching {
+            match key.code {
+                KeyCode::Enter => {
+                    viewer.searching = false;
+                    // Match rows are found while drawing, so draw before jumping
+                    terminal.draw(|f| draw_viewer(f, viewer))?;
+                    viewer.scroll = viewer.scroll.saturating_sub(1);
+                    viewer.jump_to_match(true);
+                }
+                KeyCode::Esc => {
+                    viewer.searching = false;
+                    viewer.query.clear();
+                }
+                KeyCode::Backspace => {
+                    viewer.query.pop();
+                }
+                KeyCode::Char(c) => viewer.query.push(c),
+                _ => {}
+            }
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => viewer.scroll_by(1),
+            KeyCode::Up | KeyCode::Char('k') => viewer.scroll_by(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => viewer.scroll_by(page),
+            KeyCode::PageUp => viewer.scroll_by(-page),
+            KeyCode::Home | KeyCode::Char('g') => viewer.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => {
+                viewer.scroll = viewer.rows.saturating_sub(viewer.page)
+            }
+            KeyCode::Char('/') => {
+                viewer.searching = true;
+                viewer.query.clear();
+            }
+            KeyCode::Char('n') => viewer.jump_to_match(true),
+            KeyCode::Char('N') => viewer.jump_to_match(false),
+            KeyCode::Char(']') | KeyCode::Tab => viewer.switch_conversation(1),
+            KeyCode::Char('[') | KeyCode::BackTab => viewer.switch_conversation(-1),
+            _ => {}
+        }
+    }
+}
+
+/// Asks the chatbot, redrawing the streaming view as the answer arrives
+async fn ask_streaming<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, message: &str) {
+    let accessible = app.config.accessibility_mode;
+    let colors = color_mode(app);
+    let heartbeat = app.chatbot.heartbeat.clone();
+    let mut tick = 0;
+    app.ask(message, &mut |partial| {
+        tick += 1;
+        let heartbeat = heartbeat.lock().unwrap().clone();
+        let _ = terminal.draw(|f| {
+            draw_streaming(f, message, partial, accessible, tick, heartbeat.as_ref());
+            apply_color_mode(f.buffer_mut(), colors);
+        });
+    })
+    .await;
+}
+
 /// Runs the UI loop of the application
 async fn run_ui(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
 ) -> Result<(), Box<dyn StdError>> {
+    let mut dirty = true;
+    let mut last_draw = Instant::now();
     loop {
-        terminal.draw(|f| ui(f, app))?;
+        if app.drain_events() {
+            dirty = true;
+        }
+        if app.state == AppState::MainMenu && app.refresh_project_status() {
+            dirty = true;
+        }
+        // Relative times like "5m ago" still move on while nothing happens
+        if dirty || last_draw.elapsed() >= IDLE_REDRAW_INTERVAL {
+            terminal.draw(|f| ui(f, app))?;
+            dirty = false;
+            last_draw = Instant::now();
+        }
+        app.autosave_draft(false);
 
-        // Poll for events with a timeout
-        if event::poll(Duration::from_millis(100))? {
+        // Poll quickly only while something is in flight, so an idle session barely wakes
+        let tick = if app.is_active() {
+            ACTIVE_TICK
+        } else {
+            IDLE_TICK
+        };
+        if event::poll(tick)? {
+            let event = event::read()?;
+            if let CEvent::Resize(width, height) = event {
+                // Redraw right away so wrapped layouts never show stale dimensions
+                app.on_resize(width, height);
+                terminal.autoresize()?;
+                terminal.draw(|f| ui(f, app))?;
+                last_draw = Instant::now();
+            }
+            if let CEvent::Key(key) = event {
+                handle_key(terminal, app, key).await?;
+                dirty = true;
+            }
+        }
+        if let Some(lines) = app.pending_print.take() {
+            print_to_scrollback(terminal, &lines)?;
+            dirty = true;
+        }
+
+        // Exit the loop if the state is Quit
+        if app.state == AppState::Quit {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Leaves the alternate screen to print `lines` to the terminal's own scrollback a page
+/// at a time, where they outlive the app and reach tmux logging
+fn print_to_scrollback(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    lines: &[String],
+) -> Result<(), Box<dyn StdError>> {
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    let page = (terminal.size()?.height as usize).saturating_sub(1).max(1);
+    let mut stdout = io::stdout();
+    let mut printed = 0;
+    let mut step = page;
+    loop {
+        let end = (printed + step).min(lines.len());
+        // Raw mode is still on, so lines end with a carriage return as well
+        for line in &lines[printed..end] {
+            write!(stdout, "{}\r\n", line)?;
+        }
+        printed = end;
+        let prompt = if printed == lines.len() {
+            "-- End -- press any key to return".to_string()
+        } else {
+            format!(
+                "-- More ({}/{}) -- Space: next page, Enter: next line, q: stop",
+                printed,
+                lines.len()
+            )
+        };
+        write!(stdout, "{}", prompt)?;
+        stdout.flush()?;
+        let key = loop {
             if let CEvent::Key(key) = event::read()? {
-                match app.state {
-                    AppState::MainMenu => match key.code {
-                        KeyCode::Up => {
-                            if app.selected_menu_item > 0 {
-                                app.selected_menu_item -= 1;
+                break key;
+            }
+        };
+        execute!(
+            stdout,
+            cursor::MoveToColumn(0),
+            Clear(ClearType::CurrentLine)
+        )?;
+        step = match key.code {
+            _ if printed == lines.len() => break,
+            KeyCode::Char(' ') => page,
+            KeyCode::Enter => 1,
+            _ => break,
+        };
+    }
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    Ok(())
+}
+
+/// Applies one key press to the app; drawing goes through `terminal` so tests can
+/// drive the UI with a `TestBackend`
+async fn handle_key<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<(), Box<dyn StdError>> {
+    // Ctrl+O opens the session switcher from anywhere, and cycles it once open
+    if key.code == KeyCode::Char('o')
+        && key.modifiers.contains(KeyModifiers::CONTROL)
+        && app.state != AppState::QuitConfirm
+        && app.state != AppState::StartupError
+        && app.state != AppState::RestoreDraft
+        && app.state != AppState::LockConflict
+        && app.state != AppState::Truncation
+        && app.state != AppState::Dialog
+    {
+        if app.state == AppState::SessionSwitcher && !app.session_list.is_empty() {
+            app.selected_session = (app.selected_session + 1) % app.session_list.len();
+        } else {
+            app.open_session_switcher();
+        }
+        return Ok(());
+    }
+    // Any key but another Ctrl+N accepts the identifier completion shown
+    if !(key.code == KeyCode::Char('n') && key.modifiers.contains(KeyModifiers::CONTROL)) {
+        app.identifier_completion = None;
+    }
+    match app.state {
+        AppState::MainMenu => match key.code {
+            KeyCode::Up if app.selected_menu_item > 0 => app.selected_menu_item -= 1,
+            KeyCode::Down if app.selected_menu_item < app.menu_items.len() - 1 => {
+                app.selected_menu_item += 1
+            }
+            KeyCode::Enter => {
+                // Change state based on selected menu item
+                app.state = match app.selected_menu_item {
+                    0 => AppState::Chat,
+                    1 => {
+                        let accessible = app.config.accessibility_mode;
+                        let colors = color_mode(app);
+                        app.index_current_dir(&mut |progress| {
+                            let _ = terminal.draw(|f| {
+                                draw_indexing(f, progress, accessible);
+                                apply_color_mode(f.buffer_mut(), colors);
+                            });
+                        })
+                        .await;
+                        AppState::Chat
+                    }
+                    2 => AppState::RemoteRepo,
+                    3 => AppState::BrowseIndex,
+                    4 => AppState::GitHubRecommendations,
+                    5 => {
+                        app.open_audit();
+                        AppState::Audit
+                    }
+                    6 => AppState::Usage,
+                    7 => {
+                        app.confirm_run_tests();
+                        AppState::Dialog
+                    }
+                    8 => {
+                        app.open_full_changelog();
+                        AppState::WhatsNew
+                    }
+                    9 => AppState::Help,
+                    10 => AppState::Settings,
+                    11 => AppState::QuitConfirm,
+                    _ => AppState::MainMenu,
+                };
+                if app.state == AppState::Chat {
+                    app.post_repo_map();
+                }
+            }
+            KeyCode::Char('b') if app.briefing.is_some() => {
+                app.busy = true;
+                terminal.draw(|f| ui(f, app))?;
+                commands::handle_command(app, "/briefing").await;
+                app.busy = false;
+            }
+            KeyCode::Char('r') if !app.chatbot.failed_summaries.files.is_empty() => {
+                app.busy = true;
+                terminal.draw(|f| ui(f, app))?;
+                app.retry_failed_summaries().await;
+                app.busy = false;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => app.state = AppState::QuitConfirm,
+            _ => {}
+        },
+        AppState::Chat if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            match key.code {
+                // Handle Ctrl+C for quitting
+                KeyCode::Char('c') => app.state = AppState::QuitConfirm,
+                KeyCode::Char('b') => {
+                    app.selected_bookmark = 0;
+                    app.state = AppState::Bookmarks;
+                }
+                KeyCode::Char('g') => app.open_branch_switcher(),
+                KeyCode::Char('t') => app.toggle_split_view(),
+                KeyCode::Char('l') => app.toggle_outline(),
+                KeyCode::Char('f') => app.focus_mode = !app.focus_mode,
+                KeyCode::Char('s') => app.toggle_scratchpad(),
+                KeyCode::Char('p') => app.toggle_scratchpad_pin(),
+                KeyCode::Char('y') => app.open_clipboard_ring(),
+                KeyCode::Char('n') if app.chat_focus == ChatFocus::Input => {
+                    app.complete_identifier()
+                }
+                KeyCode::Up if app.chat_focus == ChatFocus::Input => app.recall_history(-1),
+                KeyCode::Down if app.chat_focus == ChatFocus::Input => app.recall_history(1),
+                _ => {}
+            }
+        }
+        AppState::Chat => match app.chat_focus {
+            ChatFocus::Input => match key.code {
+                KeyCode::Esc => {
+                    app.state = AppState::MainMenu;
+                }
+                KeyCode::Tab => app.next_chat_focus(),
+                // With nothing typed, Enter resends the question as the selected chip has it
+                KeyCode::Enter if app.input.is_empty() && !app.query_suggestions.is_empty() => {
+                    if let Some(question) = app.take_suggestion() {
+                        app.input_received = Some(Instant::now());
+                        app.busy = true;
+                        terminal.draw(|f| ui(f, app))?;
+                        ask_streaming(terminal, app, &question).await;
+                        app.busy = false;
+                    }
+                }
+                KeyCode::Left if !app.query_suggestions.is_empty() => app.move_suggestion(-1),
+                KeyCode::Right if !app.query_suggestions.is_empty() => app.move_suggestion(1),
+                KeyCode::Enter => {
+                    let user_message = app.input.drain(..).collect::<String>();
+                    if !user_message.trim().is_empty() {
+                        app.input_received = Some(Instant::now());
+                        app.remember_input(&user_message);
+                        // Show the thinking state before blocking on the request
+                        app.busy = true;
+                        terminal.draw(|f| ui(f, app))?;
+                        if user_message.trim() == "/continue" {
+                            if let Some(prompt) = app.continuation_prompt() {
+                                ask_streaming(terminal, app, prompt).await;
                             }
-                        }
-                        KeyCode::Down => {
-                            if app.selected_menu_item < app.menu_items.len() - 1 {
-                                app.selected_menu_item += 1;
+                        } else if user_message.trim() == "/regen" {
+                            if let Some(question) = app.regeneration_prompt() {
+                                ask_streaming(terminal, app, &question).await;
                             }
-                        }
-                        KeyCode::Enter => {
-                            // Change state based on selected menu item
-                            app.state = match app.selected_menu_item {
-                                0 => AppState::Chat,
-                                1 => AppState::BrowseIndex,
-                                2 => AppState::GitHubRecommendations,
-                                3 => AppState::Help,
-                                4 => AppState::Settings,
-                                5 => AppState::QuitConfirm,
-                                _ => AppState::MainMenu,
-                            };
-                        }
-                        KeyCode::Char('q') | KeyCode::Esc => app.state = AppState::QuitConfirm,
-                        _ => {}
-                    },
-                    AppState::Chat => match key.code {
-                        KeyCode::Esc => {
-                            app.state = AppState::MainMenu;
-                        }
-                        KeyCode::Enter => {
-                            let user_message = app.input.drain(..).collect::<String>();
-                            if !user_message.trim().is_empty() {
-                                app.messages.push(Message {
-                                    sender: Sender::User,
-                                    content: user_message.clone(),
-                                });
-                                // Here you can implement sending the message to your backend or AI
-                                // For demonstration, we'll add a mock AI responsestruct Sen
-                                app.messages.push(Message {
-                                    sender: Sender::AI,
-                                    content: format!("Echo: {}", user_message),
-                                });
+                        } else if user_message.trim_start().starts_with('/') {
+                            commands::handle_command(app, user_message.trim()).await;
+                            // A question template without variables is sent at once
+                            if let Some(question) = app.queued_question.take() {
+                                ask_streaming(terminal, app, &question).await;
                             }
-                        }
-                        KeyCode::Backspace => {
-                            app.input.pop();
-                        }
-                        KeyCode::Char(c) => {
-                            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                // Handle Ctrl+C for quitting
-                                if c == 'c' {
-                                    app.state = AppState::QuitConfirm;
-                                }
-                            } else {
-                                app.input.push(c);
+                        } else if let Some(user_message) = app.lint_question(user_message) {
+                            let user_message = app.with_reply_quote(&user_message);
+                            if let Some(user_message) = app.check_input_budget(user_message) {
+                                ask_streaming(terminal, app, &user_message).await;
                             }
                         }
-                        _ => {}
-                    },
-                    AppState::QuitConfirm => match key.code {
-                        KeyCode::Char('y') | KeyCode::Enter => {
-                            app.state = AppState::Quit;
-                        }
-                        KeyCode::Char('n') | KeyCode::Esc => {
-                            app.state = AppState::MainMenu;
-                        }
-                        _ => {}
-                    },
-                    // Handle other states if necessary
-                    _ => {
-                        // From any other state, pressing 'q' or Esc brings up the quit confirmation prompt
-                        match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => app.state = AppState::QuitConfirm,
-                            _ => {}
-                        }
+                        app.busy = false;
                     }
                 }
+                KeyCode::Backspace => {
+                    app.input_history.reset();
+                    if app.input.pop().is_none() {
+                        app.reply_quote = None;
+                    }
+                }
+                KeyCode::PageUp => app.scroll_file_viewer(-10),
+                KeyCode::PageDown => app.scroll_file_viewer(10),
+                KeyCode::Char(c) => {
+                    app.input_history.reset();
+                    app.input.push(c);
+                }
+                _ => {}
+            },
+            ChatFocus::Transcript => match key.code {
+                KeyCode::Esc => app.chat_focus = ChatFocus::Input,
+                KeyCode::Tab => app.next_chat_focus(),
+                KeyCode::Up => app.move_message_selection(-1),
+                KeyCode::Down => app.move_message_selection(1),
+                KeyCode::Char('m') => app.toggle_bookmark(),
+                KeyCode::Char('a') => app.begin_annotation(),
+                KeyCode::Char('+') => app.mark_answer_helpful(),
+                // A failed turn is asked again; any other message is quoted
+                KeyCode::Char('r') => match app.take_retry() {
+                    Some(question) => {
+                        app.chat_focus = ChatFocus::Input;
+                        app.busy = true;
+                        terminal.draw(|f| ui(f, app))?;
+                        ask_streaming(terminal, app, &question).await;
+                        app.busy = false;
+                    }
+                    None => app.quote_selected_message(),
+                },
+                KeyCode::Char('?') => app.ask_about_selected_failure(),
+                KeyCode::Char('y') => app.copy_selected_message(),
+                KeyCode::Char('g') => app.confirm_gist_upload(),
+                KeyCode::Char('w') => {
+                    if let Some(index) = app.selected_message {
+                        commands::extract_files(app, index);
+                    }
+                }
+                KeyCode::Char('s') => app.toggle_raw_markdown(),
+                KeyCode::Char('c') => app.toggle_check_errors(),
+                KeyCode::Char('[') => app.move_code_block_selection(-1),
+                KeyCode::Char(']') => app.move_code_block_selection(1),
+                KeyCode::PageUp => app.scroll_file_viewer(-10),
+                KeyCode::PageDown => app.scroll_file_viewer(10),
+                _ => {}
+            },
+            ChatFocus::Viewer => match key.code {
+                KeyCode::Esc => app.chat_focus = ChatFocus::Input,
+                KeyCode::Tab => app.next_chat_focus(),
+                KeyCode::Up => app.move_viewer_cursor(-1),
+                KeyCode::Down => app.move_viewer_cursor(1),
+                KeyCode::PageUp => app.move_viewer_cursor(-10),
+                KeyCode::PageDown => app.move_viewer_cursor(10),
+                KeyCode::Char('v') => {
+                    if let Some(viewer) = app.file_viewer.as_mut() {
+                        viewer.toggle_selection();
+                    }
+                }
+                KeyCode::Char('i') => app.toggle_index_diff(),
+                KeyCode::Char('e') => {
+                    app.busy = true;
+                    terminal.draw(|f| ui(f, app))?;
+                    commands::handle_command(app, "/explain").await;
+                    app.busy = false;
+                }
+                _ => {}
+            },
+            ChatFocus::Outline => match key.code {
+                KeyCode::Esc => app.chat_focus = ChatFocus::Input,
+                KeyCode::Tab => app.next_chat_focus(),
+                KeyCode::Up | KeyCode::Char('k') => app.move_outline_selection(-1),
+                KeyCode::Down | KeyCode::Char('j') => app.move_outline_selection(1),
+                KeyCode::Enter => app.jump_to_outline_turn(),
+                _ => {}
+            },
+            // Enter adds a line so pasted text keeps its shape
+            ChatFocus::Scratchpad => match key.code {
+                KeyCode::Esc | KeyCode::Tab => app.chat_focus = ChatFocus::Input,
+                KeyCode::Enter => app.scratchpad.push('\n'),
+                KeyCode::Backspace => {
+                    app.scratchpad.pop();
+                }
+                KeyCode::Char(c) => app.scratchpad.push(c),
+                _ => {}
+            },
+            ChatFocus::Annotation => match key.code {
+                KeyCode::Esc => {
+                    app.annotation_input.clear();
+                    app.chat_focus = ChatFocus::Transcript;
+                }
+                KeyCode::Enter => app.commit_annotation(),
+                KeyCode::Backspace => {
+                    app.annotation_input.pop();
+                }
+                KeyCode::Char(c) => app.annotation_input.push(c),
+                _ => {}
+            },
+        },
+        AppState::Bookmarks => match key.code {
+            KeyCode::Up if app.selected_bookmark > 0 => app.selected_bookmark -= 1,
+            KeyCode::Down if app.selected_bookmark + 1 < app.bookmarks.len() => {
+                app.selected_bookmark += 1
+            }
+            KeyCode::Enter => app.jump_to_selected_bookmark(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::ClipboardRing => match key.code {
+            KeyCode::Up => app.selected_clip = app.selected_clip.saturating_sub(1),
+            KeyCode::Down if app.selected_clip + 1 < app.clipboard_ring.len() => {
+                app.selected_clip += 1
+            }
+            KeyCode::Enter => app.recopy_selected_clip(),
+            KeyCode::Char('i') => app.insert_selected_clip(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::RefactorPlan => match key.code {
+            KeyCode::Up => app.selected_plan_step = app.selected_plan_step.saturating_sub(1),
+            KeyCode::Down => {
+                let steps = app
+                    .refactor_plan
+                    .as_ref()
+                    .map_or(0, |plan| plan.steps.len());
+                if app.selected_plan_step + 1 < steps {
+                    app.selected_plan_step += 1;
+                }
+            }
+            KeyCode::Char(' ') => app.toggle_plan_step(),
+            KeyCode::Enter => {
+                app.busy = true;
+                terminal.draw(|f| ui(f, app))?;
+                commands::execute_plan_step(app).await;
+                app.busy = false;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::Sweep => match key.code {
+            KeyCode::Up => app.move_sweep_selection(-1),
+            KeyCode::Down => app.move_sweep_selection(1),
+            KeyCode::PageUp => app.sweep_scroll = app.sweep_scroll.saturating_sub(10),
+            KeyCode::PageDown => app.sweep_scroll += 10,
+            KeyCode::Char(' ') => app.toggle_sweep_patch(false),
+            KeyCode::Char('a') => app.toggle_sweep_patch(true),
+            KeyCode::Enter => app.confirm_sweep(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::Heatmap => match key.code {
+            KeyCode::Up | KeyCode::Left => {
+                app.selected_heatmap_cell = app.selected_heatmap_cell.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Right if app.selected_heatmap_cell + 1 < app.heatmap.len() => {
+                app.selected_heatmap_cell += 1
+            }
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::SessionSwitcher => match key.code {
+            KeyCode::Up if app.selected_session > 0 => app.selected_session -= 1,
+            KeyCode::Down if app.selected_session + 1 < app.session_list.len() => {
+                app.selected_session += 1
+            }
+            KeyCode::Enter => app.switch_to_selected_session(),
+            KeyCode::Char('n') => app.start_new_session(),
+            KeyCode::Char('m') => app.merge_selected_session(),
+            KeyCode::Char('x') => app.toggle_export_selection(),
+            KeyCode::Char('e') => app.export_selected_sessions(),
+            KeyCode::Char('d') | KeyCode::Delete => app.confirm_delete_session(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = app.state_before_switcher,
+            _ => {}
+        },
+        AppState::BranchSwitcher => match key.code {
+            KeyCode::Up => app.selected_branch = app.selected_branch.saturating_sub(1),
+            KeyCode::Down if app.selected_branch + 1 < app.branch_list.len() => {
+                app.selected_branch += 1
+            }
+            KeyCode::Enter => {
+                let accessible = app.config.accessibility_mode;
+                let colors = color_mode(app);
+                app.switch_to_selected_branch(&mut |progress| {
+                    let _ = terminal.draw(|f| {
+                        draw_indexing(f, progress, accessible);
+                        apply_color_mode(f.buffer_mut(), colors);
+                    });
+                })
+                .await
+            }
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::Todos => match key.code {
+            KeyCode::Up => app.selected_todo = app.selected_todo.saturating_sub(1),
+            KeyCode::Down if app.selected_todo + 1 < app.todo_list.len() => app.selected_todo += 1,
+            KeyCode::Enter => app.jump_to_selected_todo(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::Projects => match key.code {
+            KeyCode::Up => app.selected_project = app.selected_project.saturating_sub(1),
+            KeyCode::Down if app.selected_project + 1 < app.project_list.len() => {
+                app.selected_project += 1
+            }
+            KeyCode::Char(' ') => app.toggle_project(),
+            KeyCode::Enter => app.apply_projects(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::Chat,
+            _ => {}
+        },
+        AppState::Audit if app.audit_filter_editing => match key.code {
+            KeyCode::Enter | KeyCode::Esc => app.audit_filter_editing = false,
+            KeyCode::Backspace => {
+                app.audit_filter.pop();
+                app.audit_scroll = 0;
+            }
+            KeyCode::Char(c) => {
+                app.audit_filter.push(c);
+                app.audit_scroll = 0;
+            }
+            _ => {}
+        },
+        AppState::Audit => match key.code {
+            KeyCode::Up => app.audit_scroll = app.audit_scroll.saturating_sub(1),
+            KeyCode::Down if app.audit_scroll + 1 < app.filtered_audit_entries().len() => {
+                app.audit_scroll += 1
+            }
+            KeyCode::PageUp => app.audit_scroll = app.audit_scroll.saturating_sub(10),
+            KeyCode::PageDown => {
+                let last = app.filtered_audit_entries().len().saturating_sub(1);
+                app.audit_scroll = (app.audit_scroll + 10).min(last);
+            }
+            KeyCode::Char('/') => app.audit_filter_editing = true,
+            KeyCode::Char('?') => app.ask_about_audit_entry(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::MainMenu,
+            _ => {}
+        },
+        AppState::RemoteRepo => match key.code {
+            KeyCode::Esc => app.state = AppState::MainMenu,
+            KeyCode::Enter => {
+                let accessible = app.config.accessibility_mode;
+                let colors = color_mode(app);
+                app.open_remote_repo(&mut |progress| {
+                    let _ = terminal.draw(|f| {
+                        draw_indexing(f, progress, accessible);
+                        apply_color_mode(f.buffer_mut(), colors);
+                    });
+                })
+                .await
+            }
+            KeyCode::Backspace => {
+                app.url_input.pop();
+            }
+            KeyCode::Char(c) => app.url_input.push(c),
+            _ => {}
+        },
+        AppState::StartupError if app.data_dir_input.is_some() => match key.code {
+            KeyCode::Enter => app.commit_data_dir(),
+            KeyCode::Esc => app.data_dir_input = None,
+            KeyCode::Backspace => {
+                if let Some(input) = app.data_dir_input.as_mut() {
+                    input.pop();
+                }
+            }
+            KeyCode::Char(c) => {
+                if let Some(input) = app.data_dir_input.as_mut() {
+                    input.push(c);
+                }
+            }
+            _ => {}
+        },
+        AppState::StartupError => match key.code {
+            KeyCode::Up if app.selected_remediation > 0 => app.selected_remediation -= 1,
+            KeyCode::Down if app.selected_remediation + 1 < app.remediations().len() => {
+                app.selected_remediation += 1
+            }
+            KeyCode::Enter => app.apply_selected_remediation(),
+            _ => {}
+        },
+        AppState::Usage => match key.code {
+            KeyCode::Char('e') => app.export_usage(),
+            KeyCode::Esc | KeyCode::Char('q') => app.state = AppState::MainMenu,
+            _ => {}
+        },
+        AppState::Truncation => match key.code {
+            KeyCode::Up => app.selected_truncation = app.selected_truncation.saturating_sub(1),
+            KeyCode::Down if app.selected_truncation + 1 < STRATEGIES.len() => {
+                app.selected_truncation += 1
+            }
+            KeyCode::Enter => {
+                app.busy = true;
+                terminal.draw(|f| ui(f, app))?;
+                let parts = app.truncate_pending_input().await;
+                let count = parts.len();
+                for (i, part) in parts.iter().enumerate() {
+                    ask_streaming(terminal, app, part).await;
+                    // Earlier parts live on as the model's notes on them
+                    if i + 1 < count {
+                        let placeholder =
+                            format!("[Part {} of {} of a long message]", i + 1, count);
+                        app.chatbot.elide_question(part, &placeholder);
+                    }
+                }
+                app.busy = false;
+            }
+            KeyCode::Esc => app.cancel_truncation(),
+            _ => {}
+        },
+        AppState::LockConflict => match key.code {
+            KeyCode::Char('r') | KeyCode::Enter => app.attach_read_only(),
+            KeyCode::Char('t') => app.confirm_take_over_lock(),
+            KeyCode::Char('q') | KeyCode::Esc => app.state = AppState::Quit,
+            _ => {}
+        },
+        AppState::RestoreDraft => match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => app.restore_draft(),
+            KeyCode::Char('n') | KeyCode::Esc => app.discard_draft(),
+            _ => {}
+        },
+        AppState::WhatsNew => match key.code {
+            KeyCode::Up => app.whats_new_scroll = app.whats_new_scroll.saturating_sub(1),
+            KeyCode::Down => app.whats_new_scroll = app.whats_new_scroll.saturating_add(1),
+            KeyCode::PageUp => app.whats_new_scroll = app.whats_new_scroll.saturating_sub(10),
+            KeyCode::PageDown => app.whats_new_scroll = app.whats_new_scroll.saturating_add(10),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => app.dismiss_whats_new(),
+            _ => {}
+        },
+        AppState::Dialog => {
+            let Some(dialog) = app.dialog.as_mut() else {
+                app.state = AppState::MainMenu;
+                return Ok(());
+            };
+            let input = dialog.kind == DialogKind::Input;
+            match key.code {
+                KeyCode::Esc => {
+                    app.close_dialog();
+                    app.status_message = Some("Cancelled".to_string());
+                }
+                // Tab completes the input while there's something to complete
+                KeyCode::Tab if input && !dialog.completions.is_empty() => dialog.complete(),
+                KeyCode::Tab | KeyCode::Down => dialog.move_selection(1),
+                KeyCode::BackTab | KeyCode::Up => dialog.move_selection(-1),
+                KeyCode::Right if !input => dialog.move_selection(1),
+                KeyCode::Left if !input => dialog.move_selection(-1),
+                // Confirm dialogs answer to y/n, choices to their number
+                KeyCode::Char('y') if matches!(dialog.kind, DialogKind::Confirm(_)) => {
+                    dialog.selected = 0;
+                    accept_dialog(terminal, app).await?;
+                }
+                KeyCode::Char('n') if matches!(dialog.kind, DialogKind::Confirm(_)) => {
+                    app.close_dialog();
+                    app.status_message = Some("Cancelled".to_string());
+                }
+                KeyCode::Char(c) if !input && c.is_ascii_digit() => {
+                    let option = c.to_digit(10).unwrap_or(0) as usize;
+                    if option >= 1 && option < dialog.buttons().len() {
+                        dialog.selected = option - 1;
+                        accept_dialog(terminal, app).await?;
+                    }
+                }
+                KeyCode::Backspace if input => {
+                    dialog.input.pop();
+                }
+                KeyCode::Char(c) if input => dialog.input.push(c),
+                KeyCode::Enter => accept_dialog(terminal, app).await?,
+                _ => {}
             }
         }
-
-        // Exit the loop if the state is Quit
-        if app.state == AppState::Quit {
-            break;
+        AppState::QuitConfirm => match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                app.state = AppState::Quit;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                app.state = AppState::MainMenu;
+            }
+            _ => {}
+        },
+        // Handle other states if necessary
+        _ => {
+            // From any other state, pressing 'q' or Esc brings up the quit confirmation prompt
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => app.state = AppState::QuitConfirm,
+                _ => {}
+            }
         }
     }
+    Ok(())
+}
 
+/// Closes the dialog and runs its action unless Cancel was chosen
+async fn accept_dialog<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+) -> Result<(), Box<dyn StdError>> {
+    let Some(dialog) = app.close_dialog() else {
+        return Ok(());
+    };
+    match dialog.choice() {
+        Some(choice) => {
+            app.busy = true;
+            terminal.draw(|f| ui(f, app))?;
+            commands::run_dialog_action(app, dialog.action, choice);
+            // The last variable of a question template, or sending a question despite
+            // the lint, sends the question
+            if let Some(question) = app.queued_question.take() {
+                ask_streaming(terminal, app, &question).await;
+            }
+            if let Some(snippet) = app.pending_gist.take() {
+                app.upload_gist(snippet).await;
+            }
+            app.busy = false;
+        }
+        None => app.status_message = Some("Cancelled".to_string()),
+    }
     Ok(())
 }
 
 /// Draws the user interface based on the current application state
 fn ui(f: &mut Frame<'_>, app: &App) {
-    // Define the overall layout with header, body, and footer
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Length(7), // Header
-                Constraint::Min(1),    // Body
-                Constraint::Length(3), // Footer
-            ]
-            .as_ref(),
-        )
-        .split(f.area());
+    let _span = profiler::span("draw");
+    let accessible = app.config.accessibility_mode;
+
+    // A dialog is drawn over the screen it was opened from
+    let screen = match (&app.dialog, app.state) {
+        (Some(dialog), AppState::Dialog) => dialog.return_state,
+        (_, state) => state,
+    };
+    // Focus mode gives the chat the whole screen, with no chrome around it
+    if app.focus_mode && screen == AppState::Chat {
+        draw_chat(f, f.area(), app);
+        if let (Some(dialog), AppState::Dialog) = (&app.dialog, app.state) {
+            draw_dialog(f, f.area(), app, dialog);
+        }
+        apply_color_mode(f.buffer_mut(), color_mode(app));
+        return;
+    }
+
+    // Define the overall layout with header, body, key hints and footer. Accessibility
+    // mode reads linearly: a one-line title, then the status and hints, then the content.
+    let (header_area, body_area, ribbon_area, footer_area) = if accessible {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(1), // Title
+                    Constraint::Length(3), // Status and instructions
+                    Constraint::Length(1), // Key hints
+                    Constraint::Min(1),    // Body
+                ]
+                .as_ref(),
+            )
+            .split(f.area());
+        (chunks[0], chunks[3], chunks[2], chunks[1])
+    } else {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(7), // Header
+                    Constraint::Min(1),    // Body
+                    Constraint::Length(1), // Key hints
+                    Constraint::Length(3), // Footer
+                ]
+                .as_ref(),
+            )
+            .split(f.area());
+        (chunks[0], chunks[1], chunks[2], chunks[3])
+    };
 
     // Draw header
-    draw_header(f, chunks[0]);
+    draw_header(f, header_area, accessible);
 
     // Draw body based on state
-    match app.state {
-        AppState::MainMenu => draw_main_menu(f, chunks[1], app),
-        AppState::Chat => draw_chat(f, chunks[1], app),
-        AppState::BrowseIndex => draw_placeholder(f, chunks[1], "Browse Index"),
-        AppState::GitHubRecommendations => draw_placeholder(f, chunks[1], "GitHub Recommendations"),
-        AppState::Help => draw_placeholder(f, chunks[1], "Help"),
-        AppState::Settings => draw_placeholder(f, chunks[1], "Settings"),
-        AppState::QuitConfirm => draw_quit_confirm(f, chunks[1]),
+    match screen {
+        AppState::MainMenu => draw_main_menu(f, body_area, app),
+        AppState::Chat => {
+            // The outline sidebar takes a quarter of the width on the left
+            let chat_area = if app.outline_open {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(25), Constraint::Percentage(75)].as_ref())
+                    .split(body_area);
+                draw_outline(f, columns[0], app);
+                columns[1]
+            } else {
+                body_area
+            };
+            if app.split_view {
+                let panes = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                    .split(chat_area);
+                draw_chat(f, panes[0], app);
+                draw_file_viewer(f, panes[1], app);
+            } else {
+                draw_chat(f, chat_area, app);
+            }
+        }
+        AppState::BrowseIndex => draw_placeholder(f, body_area, app, "Browse Index"),
+        AppState::GitHubRecommendations => {
+            draw_placeholder(f, body_area, app, "GitHub Recommendations")
+        }
+        AppState::Help => draw_placeholder(f, body_area, app, "Help"),
+        AppState::Settings => draw_placeholder(f, body_area, app, "Settings"),
+        AppState::Bookmarks => draw_bookmarks(f, body_area, app),
+        AppState::ClipboardRing => draw_clipboard_ring(f, body_area, app),
+        AppState::SessionSwitcher => draw_session_switcher(f, body_area, app),
+        AppState::StartupError => draw_startup_error(f, body_area, app),
+        AppState::RemoteRepo => draw_remote_repo(f, body_area, app),
+        AppState::Audit => draw_audit(f, body_area, app),
+        AppState::BranchSwitcher => draw_branch_switcher(f, body_area, app),
+        AppState::Projects => draw_projects(f, body_area, app),
+        AppState::Todos => draw_todos(f, body_area, app),
+        AppState::Usage => draw_usage(f, body_area, app),
+        AppState::QuitConfirm => draw_quit_confirm(f, body_area, app),
+        AppState::RestoreDraft => draw_restore_draft(f, body_area, app),
+        AppState::LockConflict => draw_lock_conflict(f, body_area, app),
+        AppState::Truncation => draw_truncation(f, body_area, app),
+        AppState::RefactorPlan => draw_refactor_plan(f, body_area, app),
+        AppState::Heatmap => draw_heatmap(f, body_area, app),
+        AppState::Sweep => draw_sweep(f, body_area, app),
+        AppState::WhatsNew => draw_whats_new(f, body_area, app),
         AppState::SelectCodebase => {
             // Render the directory tree
-            app.dir_tree.render(f, chunks[1]);
+            app.dir_tree.render(f, body_area);
         }
-        AppState::Quit => {}
+        AppState::Dialog | AppState::Quit => {}
     }
+    if let (Some(dialog), AppState::Dialog) = (&app.dialog, app.state) {
+        draw_dialog(f, body_area, app, dialog);
+    }
+    if app.show_profile {
+        draw_profile(f, body_area, app);
+    }
+
+    // Draw key hints and footer
+    draw_ribbon(f, ribbon_area, app);
+    draw_footer(f, footer_area, app);
 
-    // Draw footer
-    draw_footer(f, chunks[2], app);
+    // High contrast and NO_COLOR recolor whatever the screens drew
+    apply_color_mode(f.buffer_mut(), color_mode(app));
 }
@@ -0,0 +1,153 @@
+// src/model_routing.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::MODEL_CATALOGUE;
+use crate::org_policy::OrgPolicy;
+
+pub const MODEL_LATENCY_FILE: &str = "model_latency.json";
+// Tried first in fast mode until its latency has been observed
+pub const FAST_MODEL: &str = "claude-3-5-haiku-20241022";
+// Weight of the newest sample in a model's running average
+const LATENCY_SMOOTHING: f64 = 0.3;
+// Turns at or under both limits are simple enough for the fastest model
+const FAST_MODE_MAX_QUESTION_WORDS: usize = 40;
+const FAST_MODE_MAX_PROMPT_TOKENS: usize = 12_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelLatency {
+    pub samples: u64,
+    // Running average of the time to the first streamed token
+    pub average_ms: f64,
+}
+
+/// Observed time to first token per model, kept across runs
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LatencyTracker {
+    models: HashMap<String, ModelLatency>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl LatencyTracker {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(MODEL_LATENCY_FILE);
+        let mut tracker: LatencyTracker = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        tracker.path = Some(path);
+        tracker
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn record(&mut self, model: &str, latency_ms: u64) {
+        let sample = latency_ms as f64;
+        self.models
+            .entry(model.to_string())
+            .and_modify(|latency| {
+                latency.samples += 1;
+                latency.average_ms += LATENCY_SMOOTHING * (sample - latency.average_ms);
+            })
+            .or_insert(ModelLatency {
+                samples: 1,
+                average_ms: sample,
+            });
+    }
+
+    pub fn get(&self, model: &str) -> Option<&ModelLatency> {
+        self.models.get(model)
+    }
+
+    /// The known model with the lowest observed latency that the org policy allows;
+    /// FAST_MODEL until it has been measured, so it gets a chance against the others
+    pub fn fastest(&self, policy: Option<&OrgPolicy>) -> Option<&'static str> {
+        let allowed = |model: &str| policy.is_none_or(|policy| policy.allows_model(model));
+        if allowed(FAST_MODEL) && self.get(FAST_MODEL).is_none() {
+            return Some(FAST_MODEL);
+        }
+        MODEL_CATALOGUE
+            .iter()
+            .filter(|model| allowed(model.id))
+            .filter_map(|model| Some((model.id, self.get(model.id)?.average_ms)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+}
+
+/// Whether a turn is short enough to send to the fastest model in fast mode
+pub fn is_simple_turn(question: &str, prompt_tokens: usize) -> bool {
+    question.split_whitespace().count() <= FAST_MODE_MAX_QUESTION_WORDS
+        && prompt_tokens <= FAST_MODE_MAX_PROMPT_TOKENS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fastest_prefers_the_unmeasured_fast_model_then_the_lowest_average() {
+        let mut tracker = LatencyTracker::default();
+        tracker.record("claude-3-sonnet-20240229", 900);
+        assert_eq!(tracker.fastest(None), Some(FAST_MODEL));
+
+        tracker.record(FAST_MODEL, 2_000);
+        assert_eq!(tracker.fastest(None), Some("claude-3-sonnet-20240229"));
+
+        // Later samples pull the average down gradually
+        tracker.record(FAST_MODEL, 100);
+        assert_eq!(tracker.get(FAST_MODEL).unwrap().average_ms, 1_430.0);
+    }
+
+    #[test]
+    fn only_models_the_policy_allows_are_picked() {
+        let mut tracker = LatencyTracker::default();
+        tracker.record("claude-3-opus-20240229", 3_000);
+        tracker.record("claude-3-sonnet-20240229", 900);
+        // Unknown to the catalogue, however fast
+        tracker.record("claude-next", 10);
+        let policy = OrgPolicy {
+            allowed_models: vec!["claude-3-opus-20240229".to_string()],
+            ..Default::default()
+        };
+        // The unmeasured fast model isn't tried when the policy forbids it
+        assert_eq!(
+            tracker.fastest(Some(&policy)),
+            Some("claude-3-opus-20240229")
+        );
+        let nothing_measured = OrgPolicy {
+            allowed_models: vec!["claude-3-haiku-20240307".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(tracker.fastest(Some(&nothing_measured)), None);
+    }
+
+    #[test]
+    fn latencies_persist_and_simple_turns_stop_at_both_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut tracker = LatencyTracker::load(dir.path());
+        tracker.record(FAST_MODEL, 400);
+        tracker.save().unwrap();
+        let reloaded = LatencyTracker::load(dir.path());
+        assert_eq!(
+            reloaded.get(FAST_MODEL),
+            Some(&ModelLatency {
+                samples: 1,
+                average_ms: 400.0
+            })
+        );
+
+        let forty_words = vec!["word"; FAST_MODE_MAX_QUESTION_WORDS].join(" ");
+        assert!(is_simple_turn(&forty_words, FAST_MODE_MAX_PROMPT_TOKENS));
+        assert!(!is_simple_turn(&format!("{} more", forty_words), 0));
+        assert!(!is_simple_turn("short", FAST_MODE_MAX_PROMPT_TOKENS + 1));
+    }
+}
@@ -0,0 +1,121 @@
+// src/models.rs
+use crate::token_counter;
+
+// Share of the context window past which a prompt gets a warning
+const CONTEXT_WARNING_RATIO: f64 = 0.9;
+// Output room kept when the prompt leaves less than the requested max_tokens
+const MIN_OUTPUT_TOKENS: usize = 256;
+
+/// Limits of a model the app knows how to talk to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelInfo {
+    pub id: &'static str,
+    pub context_window: usize,
+    pub max_output: usize,
+    // USD per million input and output tokens, as billed
+    pub input_price: f64,
+    pub output_price: f64,
+}
+
+pub const MODEL_CATALOGUE: [ModelInfo; 6] = [
+    ModelInfo {
+        id: "claude-3-opus-20240229",
+        context_window: 200_000,
+        max_output: 4096,
+        input_price: 15.0,
+        output_price: 75.0,
+    },
+    ModelInfo {
+        id: "claude-3-sonnet-20240229",
+        context_window: 200_000,
+        max_output: 4096,
+        input_price: 3.0,
+        output_price: 15.0,
+    },
+    ModelInfo {
+        id: "claude-3-haiku-20240307",
+        context_window: 200_000,
+        max_output: 4096,
+        input_price: 0.25,
+        output_price: 1.25,
+    },
+    ModelInfo {
+        id: "claude-3-5-sonnet-20240620",
+        context_window: 200_000,
+        max_output: 8192,
+        input_price: 3.0,
+        output_price: 15.0,
+    },
+    ModelInfo {
+        id: "claude-3-5-sonnet-20241022",
+        context_window: 200_000,
+        max_output: 8192,
+        input_price: 3.0,
+        output_price: 15.0,
+    },
+    ModelInfo {
+        id: "claude-3-5-haiku-20241022",
+        context_window: 200_000,
+        max_output: 8192,
+        input_price: 0.80,
+        output_price: 4.00,
+    },
+];
+
+pub fn find_model(id: &str) -> Option<&'static ModelInfo> {
+    MODEL_CATALOGUE.iter().find(|model| model.id == id.trim())
+}
+
+pub fn model_ids() -> Vec<&'static str> {
+    MODEL_CATALOGUE.iter().map(|model| model.id).collect()
+}
+
+/// Tokens of a prompt, counted by the tokenizer plugin when one is loaded
+pub fn estimate_tokens(text: &str) -> usize {
+    token_counter::count(text)
+}
+
+/// max_tokens for one request and a warning when the prompt nearly fills the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestBudget {
+    pub max_tokens: usize,
+    pub warning: Option<String>,
+}
+
+/// Clamps `requested` output tokens to the model's limits and what the prompt leaves of the
+/// context window. Fails before sending when the prompt alone doesn't fit.
+pub fn budget_request(
+    model: &str,
+    prompt_tokens: usize,
+    requested: usize,
+) -> Result<RequestBudget, String> {
+    let Some(info) = find_model(model) else {
+        // Unknown models are sent as configured; the API has the final word
+        return Ok(RequestBudget {
+            max_tokens: requested,
+            warning: None,
+        });
+    };
+    let room = info.context_window.saturating_sub(prompt_tokens);
+    if room < MIN_OUTPUT_TOKENS {
+        return Err(format!(
+            "Prompt of ~{} tokens doesn't fit the {}-token context window of {}; narrow the question or lower the profile's context budget",
+            prompt_tokens, info.context_window, info.id
+        ));
+    }
+    let max_tokens = requested.min(info.max_output).min(room);
+    let warning = if prompt_tokens as f64 >= info.context_window as f64 * CONTEXT_WARNING_RATIO
+        || max_tokens < requested.min(info.max_output)
+    {
+        Some(format!(
+            "Prompt uses ~{} of {}'s {} context tokens; the answer is limited to {} tokens",
+            prompt_tokens, info.id, info.context_window, max_tokens
+        ))
+    } else {
+        None
+    };
+    Ok(RequestBudget {
+        max_tokens,
+        warning,
+    })
+}
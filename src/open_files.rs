@@ -0,0 +1,119 @@
+// src/open_files.rs
+//! Files open in the user's editor, passed by an editor plugin with `--open-files`. They
+//! almost certainly relate to what is being asked, so retrieval ranks them higher.
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const OPEN_FILES_FLAG: &str = "--open-files";
+// Added to the 0 to 1 relevance of an open file
+const OPEN_FILE_BOOST: f32 = 0.25;
+
+/// A path as the index names it: relative to the project root, starting with "./"
+pub fn index_path(path: &str, root: &Path) -> String {
+    let path = Path::new(path.trim());
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let relative = relative.to_string_lossy();
+    if relative.starts_with("./") || Path::new(relative.as_ref()).is_absolute() {
+        relative.to_string()
+    } else {
+        format!("./{}", relative)
+    }
+}
+
+/// Takes `--open-files a.rs,b.rs` (or `--open-files=...`, repeatable) out of the
+/// arguments, returning the rest and the files as the index names them
+pub fn split_open_files(args: Vec<String>, root: &Path) -> (Vec<String>, Vec<String>) {
+    let mut rest = Vec::new();
+    let mut open_files = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let list = if arg == OPEN_FILES_FLAG {
+            args.next().unwrap_or_default()
+        } else if let Some(list) = arg
+            .strip_prefix(OPEN_FILES_FLAG)
+            .and_then(|value| value.strip_prefix('='))
+        {
+            list.to_string()
+        } else {
+            rest.push(arg);
+            continue;
+        };
+        open_files.extend(
+            list.split(',')
+                .filter(|path| !path.trim().is_empty())
+                .map(|path| index_path(path, root)),
+        );
+    }
+    (rest, open_files)
+}
+
+/// Raises the scores of open files, adding indexed ones the ranking left out
+pub fn boost_open_files(
+    open_files: &[String],
+    index: &HashMap<String, (String, String)>,
+    scores: &mut Vec<(String, f32)>,
+) {
+    for file in open_files {
+        match scores.iter_mut().find(|(scored, _)| scored == file) {
+            Some((_, score)) => *score += OPEN_FILE_BOOST,
+            None if index.contains_key(file) => scores.push((file.clone(), OPEN_FILE_BOOST)),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_files_are_read_from_arguments_and_ranked_higher() {
+        let args = [
+            "ask",
+            "--open-files",
+            "/work/app/src/db.rs,src/auth.rs",
+            "why?",
+        ]
+        .map(String::from)
+        .to_vec();
+        let (rest, open_files) = split_open_files(args, Path::new("/work/app"));
+        assert_eq!(rest, vec!["ask", "why?"]);
+        assert_eq!(open_files, vec!["./src/db.rs", "./src/auth.rs"]);
+
+        let index: HashMap<String, (String, String)> = ["./src/db.rs", "./src/auth.rs"]
+            .into_iter()
+            .map(|file| (file.to_string(), (String::new(), "Rust".to_string())))
+            .collect();
+        let mut scores = vec![("./src/db.rs".to_string(), 0.5)];
+        boost_open_files(&open_files, &index, &mut scores);
+        assert_eq!(
+            scores,
+            vec![
+                ("./src/db.rs".to_string(), 0.75),
+                ("./src/auth.rs".to_string(), 0.25)
+            ]
+        );
+    }
+
+    #[test]
+    fn flag_forms_repeat_and_odd_paths_are_kept_as_given() {
+        let args = [
+            "--open-files=./a.rs, ,",
+            "--open-filesx",
+            "--open-files",
+            "/elsewhere/b.rs",
+            "--open-files",
+        ]
+        .map(String::from)
+        .to_vec();
+        let (rest, open_files) = split_open_files(args, Path::new("/work/app"));
+        // A trailing flag without a value takes nothing with it
+        assert_eq!(rest, vec!["--open-filesx"]);
+        assert_eq!(open_files, vec!["./a.rs", "/elsewhere/b.rs"]);
+
+        // Files that aren't indexed aren't added to the ranking
+        let mut scores = Vec::new();
+        boost_open_files(&open_files, &HashMap::new(), &mut scores);
+        assert!(scores.is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+// src/org_policy.rs
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::chatbot::Chatbot;
+use crate::config::config_dir;
+use crate::privacy::{PrivacyLevel, PrivacyRule};
+
+// Path or URL of the policy; set by whoever provisions the machine, not in the config
+pub const ORG_POLICY_ENV: &str = "SAGACITY_ORG_POLICY";
+// Read when the environment variable isn't set
+pub const ORG_POLICY_PATH: &str = "/etc/sagacity/org_policy.json";
+// Last copy fetched from a URL, used when the URL can't be reached
+const ORG_POLICY_CACHE_FILE: &str = "org_policy_cache.json";
+// A policy server that doesn't answer in time leaves the cached copy in force
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Team-wide limits applied on top of the local config, which can only tighten them
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OrgPolicy {
+    // Models requests may use; empty allows every catalogued model
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    // Dollars one run of sagacity, and one day of questions, may spend
+    #[serde(default)]
+    pub session_budget_usd: Option<f64>,
+    #[serde(default)]
+    pub daily_budget_usd: Option<f64>,
+    // Regexes masked in exported and shared transcripts besides the local ones
+    #[serde(default)]
+    pub redaction_patterns: Vec<String>,
+    // Files or globs that are never indexed or sent
+    #[serde(default)]
+    pub protected_globs: Vec<String>,
+    // Where the policy was read from, shown with the limits it imposes
+    #[serde(skip)]
+    pub source: String,
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+fn cache_path() -> PathBuf {
+    config_dir().join(ORG_POLICY_CACHE_FILE)
+}
+
+/// The configured policy location, if any
+pub fn policy_source() -> Option<String> {
+    match std::env::var(ORG_POLICY_ENV) {
+        Ok(source) if !source.trim().is_empty() => Some(source.trim().to_string()),
+        _ => Path::new(ORG_POLICY_PATH)
+            .is_file()
+            .then(|| ORG_POLICY_PATH.to_string()),
+    }
+}
+
+/// Downloads a policy served from a URL into the local cache, keeping the previous copy
+/// when the download fails; startup reads the cache
+pub async fn refresh_remote_policy() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(url) = policy_source().filter(|source| is_url(source)) else {
+        return Ok(());
+    };
+    let client = reqwest::Client::builder().timeout(FETCH_TIMEOUT).build()?;
+    let response = client.get(&url).send().await?.error_for_status()?;
+    let contents = response.text().await?;
+    // Only a policy that parses replaces the cached one
+    serde_json::from_str::<OrgPolicy>(&contents)?;
+    fs::create_dir_all(config_dir())?;
+    fs::write(cache_path(), contents)?;
+    Ok(())
+}
+
+/// Reads the policy, if one is configured; an unreadable policy is an error rather than
+/// no policy, so a broken file never lifts the limits
+pub fn load_org_policy() -> Result<Option<OrgPolicy>, String> {
+    let Some(source) = policy_source() else {
+        return Ok(None);
+    };
+    let path = if is_url(&source) {
+        cache_path()
+    } else {
+        PathBuf::from(shellexpand::tilde(&source).into_owned())
+    };
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("The org policy from {} could not be read: {}", source, e))?;
+    let mut policy: OrgPolicy = serde_json::from_str(&contents)
+        .map_err(|e| format!("The org policy from {} is not valid: {}", source, e))?;
+    policy.source = source;
+    Ok(Some(policy))
+}
+
+impl OrgPolicy {
+    pub fn allows_model(&self, model: &str) -> bool {
+        self.allowed_models.is_empty() || self.allowed_models.iter().any(|m| m == model)
+    }
+
+    /// Protected globs as privacy rules, which outrank any looser local rule
+    pub fn privacy_rules(&self) -> Vec<PrivacyRule> {
+        self.protected_globs
+            .iter()
+            .map(|pattern| PrivacyRule {
+                pattern: pattern.clone(),
+                level: PrivacyLevel::Never,
+            })
+            .collect()
+    }
+
+    /// Refuses a request the policy doesn't allow: a model outside the allowed list, or
+    /// spend past a budget
+    pub fn check_request(&self, chatbot: &Chatbot, model: &str) -> Result<(), String> {
+        if !self.allows_model(model) {
+            return Err(format!(
+                "The org policy doesn't allow {}; allowed models: {}",
+                model,
+                self.allowed_models.join(", ")
+            ));
+        }
+        if let Some(cap) = self.session_budget_usd {
+            if chatbot.audit_log.spent >= cap {
+                return Err(format!(
+                    "This session reached the org policy's ${:.2} budget",
+                    cap
+                ));
+            }
+        }
+        if let Some(cap) = self.daily_budget_usd {
+            let today = chatbot
+                .usage
+                .days
+                .get(&Local::now().date_naive())
+                .map_or(0.0, |day| day.cost);
+            if today >= cap {
+                return Err(format!(
+                    "Today's spend reached the org policy's ${:.2} budget",
+                    cap
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claude_client::MockClient;
+    use crate::privacy::PrivacyPolicy;
+    use crate::usage::DailyUsage;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn chatbot() -> Chatbot {
+        Chatbot::new(
+            HashMap::new(),
+            HashMap::new(),
+            "test-key".to_string(),
+            Arc::new(MockClient::default()),
+        )
+    }
+
+    #[test]
+    fn requests_past_the_allowed_models_or_a_budget_are_refused() {
+        let policy = OrgPolicy {
+            allowed_models: vec!["claude-allowed".to_string()],
+            session_budget_usd: Some(1.0),
+            daily_budget_usd: Some(5.0),
+            ..OrgPolicy::default()
+        };
+        let mut chatbot = chatbot();
+        assert!(policy.check_request(&chatbot, "claude-allowed").is_ok());
+        let refused = policy.check_request(&chatbot, "claude-other").unwrap_err();
+        assert!(
+            refused.contains("doesn't allow claude-other"),
+            "{}",
+            refused
+        );
+
+        chatbot.audit_log.spent = 1.0;
+        let refused = policy
+            .check_request(&chatbot, "claude-allowed")
+            .unwrap_err();
+        assert!(refused.contains("$1.00 budget"), "{}", refused);
+
+        chatbot.audit_log.spent = 0.5;
+        chatbot.usage.days.insert(
+            Local::now().date_naive(),
+            DailyUsage {
+                cost: 5.0,
+                ..DailyUsage::default()
+            },
+        );
+        let refused = policy
+            .check_request(&chatbot, "claude-allowed")
+            .unwrap_err();
+        assert!(refused.starts_with("Today's spend"), "{}", refused);
+
+        // Without limits anything goes
+        assert!(OrgPolicy::default()
+            .check_request(&chatbot, "claude-other")
+            .is_ok());
+    }
+
+    #[test]
+    fn protected_globs_are_never_sent() {
+        let policy = OrgPolicy {
+            protected_globs: vec!["secrets/**".to_string(), "*.pem".to_string()],
+            ..OrgPolicy::default()
+        };
+        let rules = policy.privacy_rules();
+        assert!(rules.iter().all(|rule| rule.level == PrivacyLevel::Never));
+        let privacy = PrivacyPolicy::new(&rules);
+        assert_eq!(privacy.level_for("./secrets/prod.env"), PrivacyLevel::Never);
+        assert_eq!(privacy.level_for("./certs/server.pem"), PrivacyLevel::Never);
+        assert_eq!(privacy.level_for("./src/main.rs"), PrivacyLevel::Full);
+    }
+}
@@ -0,0 +1,127 @@
+// src/outline.rs
+//! One line per turn of the transcript, for skimming a long session from the sidebar.
+use crate::ui::chat::{Message, Sender};
+
+// Characters kept of a question's gist and of an answer's first sentence
+const GIST_CHARS: usize = 40;
+const ANSWER_CHARS: usize = 60;
+
+/// A question and the start of its answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    // Index of the question in the transcript
+    pub message_index: usize,
+    pub question: String,
+    pub answer: String,
+}
+
+fn shorten(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        let cut: String = text.chars().take(max - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        text.to_string()
+    }
+}
+
+// The first line outside quotes and code blocks, so replies and pasted code show the
+// question itself
+fn gist(question: &str) -> String {
+    let mut in_fence = false;
+    let line = question
+        .lines()
+        .map(str::trim)
+        .find(|line| {
+            if line.starts_with("```") {
+                in_fence = !in_fence;
+                return false;
+            }
+            !in_fence && !line.is_empty() && !line.starts_with('>')
+        })
+        .unwrap_or("");
+    shorten(line, GIST_CHARS)
+}
+
+// The answer up to the end of its first sentence, headings and markup left out
+fn first_sentence(answer: &str) -> String {
+    let text = answer
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("```"))
+        .unwrap_or("")
+        .replace(['*', '`'], "");
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| matches!(c, '.' | '!' | '?') && text[i + c.len_utf8()..].starts_with(' '))
+        .map_or(text.len(), |(i, c)| i + c.len_utf8());
+    shorten(&text[..end], ANSWER_CHARS)
+}
+
+/// The transcript's turns in order, each question with the answer that followed it
+pub fn outline(messages: &[Message]) -> Vec<OutlineEntry> {
+    messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| message.sender == Sender::User)
+        .map(|(i, message)| OutlineEntry {
+            message_index: i,
+            question: gist(&message.content),
+            answer: messages
+                .get(i + 1)
+                .filter(|answer| answer.sender == Sender::AI)
+                .map(|answer| first_sentence(&answer.content))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_question_is_outlined_with_its_answers_first_sentence() {
+        let messages = vec![
+            Message::user("> earlier answer\n\nWhere are sessions saved?"),
+            Message::ai("## Sessions\n\nSessions are saved by `persist_session`. It writes JSON."),
+            Message::user("And loaded?"),
+        ];
+        assert_eq!(
+            outline(&messages),
+            vec![
+                OutlineEntry {
+                    message_index: 0,
+                    question: "Where are sessions saved?".to_string(),
+                    answer: "Sessions are saved by persist_session.".to_string(),
+                },
+                OutlineEntry {
+                    message_index: 2,
+                    question: "And loaded?".to_string(),
+                    answer: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn long_turns_are_cut_on_characters_and_dots_inside_words_dont_end_sentences() {
+        let question = format!("{} tail", "ü".repeat(GIST_CHARS));
+        let messages = vec![
+            Message::user(question),
+            Message::ai("Version 1.2 fixed it! Then more."),
+            Message::user("```\ncode only\n```\nWhy?"),
+            Message::ai("```rust\nfn main() {}\n```"),
+        ];
+        let entries = outline(&messages);
+        assert_eq!(entries[0].question.chars().count(), GIST_CHARS);
+        assert!(entries[0].question.ends_with("ü…"));
+        assert_eq!(entries[0].answer, "Version 1.2 fixed it!");
+        // Pasted code is skipped for the question after it
+        assert_eq!(entries[1].question, "Why?");
+        assert_eq!(entries[1].answer, "fn main() {}");
+        assert_eq!(
+            shorten(&"a".repeat(ANSWER_CHARS), ANSWER_CHARS).len(),
+            ANSWER_CHARS
+        );
+    }
+}
@@ -0,0 +1,210 @@
+// src/privacy.rs
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// Signature lines kept in the local outline of a summary-only file
+const MAX_OUTLINE_LINES: usize = 40;
+
+/// How much of a file may leave the machine, ordered from least to most strict
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrivacyLevel {
+    #[default]
+    Full,
+    // Only the stored summary is sent, never the contents
+    SummaryOnly,
+    // Excluded from indexing and context entirely
+    Never,
+}
+
+impl PrivacyLevel {
+    pub fn badge(&self) -> Option<&'static str> {
+        match self {
+            PrivacyLevel::Full => None,
+            PrivacyLevel::SummaryOnly => Some("[summary-only]"),
+            PrivacyLevel::Never => Some("[never]"),
+        }
+    }
+}
+
+/// A file or glob pattern from the config and the level it imposes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PrivacyRule {
+    pub pattern: String,
+    pub level: PrivacyLevel,
+}
+
+/// Compiled privacy rules; the strictest matching rule wins
+#[derive(Debug, Clone, Default)]
+pub struct PrivacyPolicy {
+    rules: Vec<(Regex, PrivacyLevel)>,
+}
+
+// Translates a glob into an anchored regex: `**` crosses directories, `*` and `?` don't.
+// Patterns without a slash match the file name in any directory, like .gitignore
+fn glob_to_regex(pattern: &str) -> Option<Regex> {
+    let pattern = pattern.trim().trim_start_matches("./");
+    let mut regex = if pattern.contains('/') {
+        String::from("^")
+    } else {
+        String::from("^(?:.*/)?")
+    };
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches no directory at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+impl PrivacyPolicy {
+    pub fn new(rules: &[PrivacyRule]) -> Self {
+        PrivacyPolicy {
+            rules: rules
+                .iter()
+                .filter_map(|rule| glob_to_regex(&rule.pattern).map(|re| (re, rule.level)))
+                .collect(),
+        }
+    }
+
+    pub fn level_for(&self, path: &str) -> PrivacyLevel {
+        let path = path.trim_start_matches("./");
+        self.rules
+            .iter()
+            .filter(|(re, _)| re.is_match(path))
+            .map(|(_, level)| *level)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn allows_contents(&self, path: &str) -> bool {
+        self.level_for(path) == PrivacyLevel::Full
+    }
+}
+
+/// A summary built on this machine from declaration lines, for files whose contents can't be sent
+pub fn local_outline(content: &str) -> String {
+    const DECLARATIONS: [&str; 14] = [
+        "pub ",
+        "fn ",
+        "struct ",
+        "enum ",
+        "trait ",
+        "impl ",
+        "mod ",
+        "type ",
+        "class ",
+        "def ",
+        "func ",
+        "interface ",
+        "export ",
+        "const ",
+    ];
+    let outline: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            line.starts_with("//!")
+                || line.starts_with("///")
+                || DECLARATIONS.iter().any(|d| line.starts_with(d))
+        })
+        .take(MAX_OUTLINE_LINES)
+        .collect();
+    format!(
+        "Summary-only file (contents withheld); local outline:\n{}",
+        outline.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(rules: &[(&str, PrivacyLevel)]) -> PrivacyPolicy {
+        let rules: Vec<PrivacyRule> = rules
+            .iter()
+            .map(|(pattern, level)| PrivacyRule {
+                pattern: pattern.to_string(),
+                level: *level,
+            })
+            .collect();
+        PrivacyPolicy::new(&rules)
+    }
+
+    #[test]
+    fn globs_without_a_slash_match_the_name_in_any_directory() {
+        let policy = compile(&[("*.pem", PrivacyLevel::Never)]);
+        assert_eq!(policy.level_for("key.pem"), PrivacyLevel::Never);
+        assert_eq!(
+            policy.level_for("./certs/prod/key.pem"),
+            PrivacyLevel::Never
+        );
+        assert_eq!(policy.level_for("key.pem.txt"), PrivacyLevel::Full);
+    }
+
+    #[test]
+    fn single_stars_stay_in_one_directory_and_double_stars_cross_them() {
+        let policy = compile(&[
+            ("src/*.rs", PrivacyLevel::SummaryOnly),
+            ("secrets/**", PrivacyLevel::Never),
+            ("**/fixtures/*.json", PrivacyLevel::SummaryOnly),
+        ]);
+        assert_eq!(policy.level_for("src/main.rs"), PrivacyLevel::SummaryOnly);
+        assert_eq!(policy.level_for("src/ui/chat.rs"), PrivacyLevel::Full);
+        assert_eq!(policy.level_for("lib/src/main.rs"), PrivacyLevel::Full);
+        assert_eq!(policy.level_for("secrets/a/b/c.txt"), PrivacyLevel::Never);
+        // `**/` also matches no directory at all
+        assert_eq!(
+            policy.level_for("fixtures/a.json"),
+            PrivacyLevel::SummaryOnly
+        );
+        assert_eq!(
+            policy.level_for("tests/fixtures/a.json"),
+            PrivacyLevel::SummaryOnly
+        );
+    }
+
+    #[test]
+    fn the_strictest_matching_rule_wins_regardless_of_order() {
+        let policy = compile(&[
+            ("config/*", PrivacyLevel::Never),
+            ("config/*.toml", PrivacyLevel::SummaryOnly),
+            ("./config/public.toml", PrivacyLevel::Full),
+        ]);
+        assert_eq!(policy.level_for("config/public.toml"), PrivacyLevel::Never);
+        assert!(!policy.allows_contents("config/app.toml"));
+        assert!(policy.allows_contents("src/config.rs"));
+        // Dots in patterns are literal
+        assert_eq!(
+            compile(&[("a.b", PrivacyLevel::Never)]).level_for("axb"),
+            PrivacyLevel::Full
+        );
+    }
+
+    #[test]
+    fn outlines_keep_declarations_and_docs_but_no_bodies() {
+        let content = "//! Billing\nuse std::fs;\n\n/// Charges a card\npub fn charge(card: &str) {\n    let secret = \"sk-live\";\n}\nstruct Card;\n";
+        assert_eq!(
+            local_outline(content),
+            "Summary-only file (contents withheld); local outline:\n\
+             //! Billing\n/// Charges a card\npub fn charge(card: &str) {\nstruct Card;"
+        );
+
+        let long: String = (0..100).map(|i| format!("fn f{}() {{}}\n", i)).collect();
+        assert_eq!(local_outline(&long).lines().count(), 1 + MAX_OUTLINE_LINES);
+    }
+}
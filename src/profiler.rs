@@ -0,0 +1,70 @@
+// src/profiler.rs
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Whether this build records timings; build with `--features profiling` to turn it on
+pub const ENABLED: bool = cfg!(feature = "profiling");
+
+/// Time spent in one instrumented section over every frame or turn so far
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timing {
+    pub calls: u64,
+    pub total: Duration,
+    pub max: Duration,
+    pub last: Duration,
+}
+
+impl Timing {
+    pub fn average(&self) -> Duration {
+        self.total / self.calls.max(1) as u32
+    }
+}
+
+static TIMINGS: Mutex<BTreeMap<&'static str, Timing>> = Mutex::new(BTreeMap::new());
+
+/// Measures a section from creation until dropped; free when profiling isn't built in
+pub struct Span {
+    label: &'static str,
+    start: Option<Instant>,
+}
+
+pub fn span(label: &'static str) -> Span {
+    Span {
+        label,
+        start: ENABLED.then(Instant::now),
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+        let elapsed = start.elapsed();
+        if let Ok(mut timings) = TIMINGS.lock() {
+            let timing = timings.entry(self.label).or_default();
+            timing.calls += 1;
+            timing.total += elapsed;
+            timing.max = timing.max.max(elapsed);
+            timing.last = elapsed;
+        }
+    }
+}
+
+/// The sections that took the most time in total, largest first
+pub fn top(count: usize) -> Vec<(&'static str, Timing)> {
+    let mut timings: Vec<(&'static str, Timing)> = TIMINGS
+        .lock()
+        .map(|timings| timings.iter().map(|(label, t)| (*label, *t)).collect())
+        .unwrap_or_default();
+    timings.sort_by_key(|(_, timing)| std::cmp::Reverse(timing.total));
+    timings.truncate(count);
+    timings
+}
+
+pub fn reset() {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        timings.clear();
+    }
+}
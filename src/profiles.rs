@@ -0,0 +1,81 @@
+// src/profiles.rs
+use crate::constants::*;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Retrieval and prompting settings tuned for one kind of task
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskProfile {
+    pub name: &'static str,
+    pub description: &'static str,
+    // Number of retrieved files put into context
+    pub retrieval_k: usize,
+    // Maximum characters of file context sent with a question
+    pub context_budget: usize,
+    pub system_prompt: &'static str,
+    pub model: &'static str,
+    // Whether the top candidates get a second, comparative ranking before the cut to
+    // `retrieval_k`; worth the extra call where the wrong file derails the answer
+    pub rerank: bool,
+}
+
+pub fn builtin_profiles() -> Vec<TaskProfile> {
+    vec![
+        TaskProfile {
+            name: DEFAULT_PROFILE,
+            description: "General questions about the codebase",
+            retrieval_k: 5,
+            context_budget: 120_000,
+            system_prompt: "You are an AI assistant helping with a codebase. Use the provided context and conversation history to answer questions.",
+            model: DEFAULT_MODEL,
+            rerank: false,
+        },
+        TaskProfile {
+            name: "debugging",
+            description: "Tracking down a bug from symptoms to cause",
+            retrieval_k: 8,
+            context_budget: 160_000,
+            system_prompt: "You are debugging a codebase with the user. Form hypotheses from the provided context, point to the exact lines that could cause the reported behavior, and suggest how to confirm each hypothesis before proposing a fix.",
+            model: DEFAULT_MODEL,
+            rerank: true,
+        },
+        TaskProfile {
+            name: "architecture",
+            description: "High-level overview of modules and how they fit together",
+            retrieval_k: 12,
+            context_budget: 200_000,
+            system_prompt: "You are explaining the architecture of a codebase. Describe modules, their responsibilities and the data flow between them. Prefer the big picture over line-level detail.",
+            model: STRONG_MODEL,
+            rerank: false,
+        },
+        TaskProfile {
+            name: "tests",
+            description: "Writing and reviewing tests",
+            retrieval_k: 4,
+            context_budget: 100_000,
+            system_prompt: "You are helping write tests for a codebase. Follow the conventions of existing tests in the context, cover edge cases and failure paths, and reply with complete, runnable test code.",
+            model: DEFAULT_MODEL,
+            rerank: false,
+        },
+        TaskProfile {
+            name: "security",
+            description: "Security review of the retrieved code",
+            retrieval_k: 8,
+            context_budget: 160_000,
+            system_prompt: "You are performing a security review of a codebase. Look for injection, unsafe deserialization, path traversal, secrets in code, missing validation and unsafe blocks. Rate each finding by severity and cite the file and lines.",
+            model: STRONG_MODEL,
+            rerank: true,
+        },
+    ]
+}
+
+/// Looks up a profile by name, ignoring case
+pub fn find_profile(name: &str) -> Option<TaskProfile> {
+    builtin_profiles()
+        .into_iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(name.trim()))
+}
+
+pub fn default_profile() -> TaskProfile {
+    find_profile(DEFAULT_PROFILE).expect("the default profile is built in")
+}
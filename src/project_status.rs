@@ -0,0 +1,87 @@
+// src/project_status.rs
+use chrono::{DateTime, Local, Utc};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::chatbot::{index_cache_path, Chatbot};
+use crate::session::list_sessions;
+
+// The main menu redraws constantly; the file system is checked at most this often
+pub const STATUS_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+const RECENT_SESSIONS: usize = 5;
+
+/// A past session as listed on the main menu
+#[derive(Debug, Clone)]
+pub struct RecentSession {
+    pub title: String,
+    pub last_activity: DateTime<Utc>,
+    pub cost: f64,
+}
+
+/// What the main menu shows about the project: recent sessions, index freshness and spend
+#[derive(Debug, Clone)]
+pub struct ProjectStatus {
+    pub recent_sessions: Vec<RecentSession>,
+    pub indexed_files: usize,
+    // Copies left out of the index in favor of the file they duplicate
+    pub duplicate_files: usize,
+    // Indexed files changed or deleted since they were indexed
+    pub stale_files: usize,
+    // Files whose summary failed, with the last error, awaiting a retry
+    pub failed_summaries: Vec<(String, String)>,
+    pub indexed_at: Option<DateTime<Utc>>,
+    pub last_session_cost: Option<f64>,
+    pub today_cost: f64,
+    pub today_questions: u64,
+    pub refreshed: Instant,
+}
+
+pub(crate) fn modified_secs(path: &str) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+pub fn project_status(chatbot: &Chatbot, data_dir: &Path) -> ProjectStatus {
+    let sessions = list_sessions(data_dir);
+    let stale_files = chatbot
+        .file_mod_times
+        .iter()
+        .filter(|(path, &indexed)| modified_secs(path).is_none_or(|now| now > indexed))
+        .count();
+    let indexed_at = fs::metadata(index_cache_path(&chatbot.index_root))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+    let today = chatbot
+        .usage
+        .days
+        .get(&Local::now().date_naive())
+        .cloned()
+        .unwrap_or_default();
+    ProjectStatus {
+        last_session_cost: sessions.first().map(|session| session.cost),
+        recent_sessions: sessions
+            .into_iter()
+            .take(RECENT_SESSIONS)
+            .map(|session| RecentSession {
+                title: session.title,
+                last_activity: session.last_activity,
+                cost: session.cost,
+            })
+            .collect(),
+        indexed_files: chatbot.index.len(),
+        duplicate_files: chatbot.aliases.len(),
+        stale_files,
+        failed_summaries: chatbot
+            .failed_summaries
+            .files
+            .iter()
+            .map(|(file, error)| (file.clone(), error.clone()))
+            .collect(),
+        indexed_at,
+        today_cost: today.cost,
+        today_questions: today.questions,
+        refreshed: Instant::now(),
+    }
+}
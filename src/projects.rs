@@ -0,0 +1,198 @@
+// src/projects.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PROJECTS_DIR: &str = "projects";
+pub const PROJECTS_FILE: &str = "projects.json";
+
+/// The index of a registered project, searched alongside the current one
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProjectIndex {
+    pub name: String,
+    pub root: String,
+    pub index: HashMap<String, (String, String)>,
+    #[serde(default)]
+    pub keywords: HashMap<String, Vec<String>>,
+}
+
+/// A project in the registry; its index is kept in a file of its own
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegisteredProject {
+    pub name: String,
+    pub root: String,
+    pub files: usize,
+    pub registered_at: DateTime<Utc>,
+}
+
+/// Indexed projects that questions can span, kept in the data directory
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ProjectRegistry {
+    pub projects: Vec<RegisteredProject>,
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+// Other bytes are escaped as `_xx`, so distinct names never share a file
+fn index_file(name: &str) -> String {
+    let stem: String = name
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b == b'-' {
+                (b as char).to_string()
+            } else {
+                format!("_{:02x}", b)
+            }
+        })
+        .collect();
+    format!("{}.json", stem)
+}
+
+impl ProjectRegistry {
+    pub fn load(data_dir: &Path) -> ProjectRegistry {
+        let dir = data_dir.join(PROJECTS_DIR);
+        let mut registry: ProjectRegistry = fs::read_to_string(dir.join(PROJECTS_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        registry.dir = dir;
+        registry
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.dir.join(PROJECTS_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Adds a project, or replaces the registered index of one with the same name
+    pub fn register(&mut self, project: &ProjectIndex) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.dir.join(index_file(&project.name)),
+            serde_json::to_string(project)?,
+        )?;
+        self.projects
+            .retain(|registered| registered.name != project.name);
+        self.projects.push(RegisteredProject {
+            name: project.name.clone(),
+            root: project.root.clone(),
+            files: project.index.len(),
+            registered_at: Utc::now(),
+        });
+        self.projects.sort_by(|a, b| a.name.cmp(&b.name));
+        self.save()
+    }
+
+    /// Unregisters a project; false when none has that name
+    pub fn remove(&mut self, name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let before = self.projects.len();
+        self.projects.retain(|registered| registered.name != name);
+        if self.projects.len() == before {
+            return Ok(false);
+        }
+        let _ = fs::remove_file(self.dir.join(index_file(name)));
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn load_index(&self, name: &str) -> Option<ProjectIndex> {
+        let contents = fs::read_to_string(self.dir.join(index_file(name))).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+}
+
+/// The registered project whose index holds a file
+pub fn project_of<'a>(projects: &'a [ProjectIndex], file: &str) -> Option<&'a ProjectIndex> {
+    projects
+        .iter()
+        .find(|project| project.index.contains_key(file))
+}
+
+/// Tells the model which files come from which project and how to cite them
+pub fn system_prompt(projects: &[ProjectIndex]) -> String {
+    let roots: Vec<String> = projects
+        .iter()
+        .map(|project| format!("- {}: {}", project.name, project.root))
+        .collect();
+    format!(
+        "This question spans several projects. Files under these roots belong to the named project:\n{}\nEvery other file belongs to the current project. When you cite a file, prefix it with its project, as `project: path`, and say which project each piece of code lives in.",
+        roots.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_indexes_round_trip_and_replace_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = |root: &str, files: &[&str]| ProjectIndex {
+            name: "service-a".to_string(),
+            root: root.to_string(),
+            index: files
+                .iter()
+                .map(|file| {
+                    (
+                        file.to_string(),
+                        ("summary".to_string(), "Rust".to_string()),
+                    )
+                })
+                .collect(),
+            keywords: HashMap::new(),
+        };
+        let mut registry = ProjectRegistry::load(dir.path());
+        registry
+            .register(&project("/a", &["/a/src/main.rs"]))
+            .unwrap();
+        registry
+            .register(&project("/a", &["/a/src/main.rs", "/a/src/client.rs"]))
+            .unwrap();
+
+        let registry = ProjectRegistry::load(dir.path());
+        assert_eq!(registry.projects.len(), 1);
+        assert_eq!(registry.projects[0].files, 2);
+        let loaded = registry.load_index("service-a").unwrap();
+        assert_eq!(
+            project_of(&[loaded], "/a/src/client.rs").map(|p| p.name.as_str()),
+            Some("service-a")
+        );
+    }
+
+    #[test]
+    fn similar_names_keep_separate_indexes_inside_the_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = |name: &str| ProjectIndex {
+            name: name.to_string(),
+            root: format!("/{}", name),
+            index: HashMap::new(),
+            keywords: HashMap::new(),
+        };
+        let mut registry = ProjectRegistry::load(dir.path());
+        for name in ["lib b", "lib_b", "../escape"] {
+            registry.register(&project(name)).unwrap();
+        }
+        for name in ["lib b", "lib_b", "../escape"] {
+            assert_eq!(
+                registry.load_index(name).unwrap().root,
+                format!("/{}", name)
+            );
+        }
+        let names: Vec<&str> = registry.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["../escape", "lib b", "lib_b"]);
+        // Every index file stays in the projects directory
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        assert!(!registry.remove("unknown").unwrap());
+        assert!(registry.remove("lib b").unwrap());
+        assert!(registry.load_index("lib b").is_none());
+        assert!(registry.load_index("lib_b").is_some());
+        assert_eq!(ProjectRegistry::load(dir.path()).projects.len(), 2);
+    }
+}
@@ -0,0 +1,212 @@
+// src/prompts.rs
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const PROMPTS_DIR: &str = "prompts";
+
+/// The prompts sent to Claude, each with the `{variables}` it is rendered with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prompt {
+    // {language}, {content}
+    Summarize,
+    // {query}, {summaries}
+    Relevance,
+    // {query}, {candidates}: summaries with the start of each file
+    Rerank,
+    // {profile}: the active profile's own system prompt
+    System,
+    // {context}, {query}
+    Answer,
+}
+
+impl Prompt {
+    pub const ALL: [Prompt; 5] = [
+        Prompt::Summarize,
+        Prompt::Relevance,
+        Prompt::Rerank,
+        Prompt::System,
+        Prompt::Answer,
+    ];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Prompt::Summarize => "summarize.txt",
+            Prompt::Relevance => "relevance.txt",
+            Prompt::Rerank => "rerank.txt",
+            Prompt::System => "system.txt",
+            Prompt::Answer => "answer.txt",
+        }
+    }
+
+    pub fn default_template(self) -> &'static str {
+        match self {
+            Prompt::Summarize => "Provide a very concise summary (2-3 sentences max) of the following {language} code, focusing on its main purpose and key functionalities:\n\n{content}",
+            Prompt::Relevance => "Based on the following query, score the relevance of each summary on a scale of 0 to 1:\n\nQuery: {query}\n\n{summaries}Provide your response in the following format:\n\n<file_path_1>,<relevance_score_1>\n<file_path_2>,<relevance_score_2>\n...\n",
+            Prompt::Rerank => "The following files were retrieved for a query about a codebase. Compare them against each other and order them from most to least useful for answering the query.\n\nQuery: {query}\n\n{candidates}Reply with the file paths alone, one per line, most useful first.\n",
+            Prompt::System => "{profile}",
+            Prompt::Answer => "Based on the following context about a codebase and our previous conversation, please answer the user's query:\n\nContext: {context}\n\nUser query: {query}",
+        }
+    }
+}
+
+/// Replaces each `{name}` in the template with its value in one pass, so values that
+/// contain braces, like source code, are left as they are. Unknown names stay as written
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Prompt templates read from `prompts/` in the data directory, written out with the
+/// built-in text the first time and reread whenever a file changes
+#[derive(Debug, Default)]
+pub struct PromptTemplates {
+    templates: HashMap<Prompt, String>,
+    modified: HashMap<Prompt, SystemTime>,
+    dir: Option<PathBuf>,
+}
+
+impl PromptTemplates {
+    pub fn load(data_dir: &Path) -> Self {
+        let dir = data_dir.join(PROMPTS_DIR);
+        if fs::create_dir_all(&dir).is_ok() {
+            for prompt in Prompt::ALL {
+                let path = dir.join(prompt.file_name());
+                if !path.exists() {
+                    let _ = fs::write(&path, prompt.default_template());
+                }
+            }
+        }
+        let mut templates = PromptTemplates {
+            dir: Some(dir),
+            ..Default::default()
+        };
+        templates.reload_changed();
+        templates
+    }
+
+    /// Rereads the template files edited since they were last read; returns the prompts
+    /// that changed
+    pub fn reload_changed(&mut self) -> Vec<Prompt> {
+        let Some(dir) = &self.dir else {
+            return Vec::new();
+        };
+        let mut changed = Vec::new();
+        for prompt in Prompt::ALL {
+            let path = dir.join(prompt.file_name());
+            let Ok(modified) = fs::metadata(&path).and_then(|metadata| metadata.modified()) else {
+                // A deleted file falls back to the built-in text
+                if self.templates.remove(&prompt).is_some() {
+                    self.modified.remove(&prompt);
+                    changed.push(prompt);
+                }
+                continue;
+            };
+            if self.modified.get(&prompt) == Some(&modified) {
+                continue;
+            }
+            if let Ok(template) = fs::read_to_string(&path) {
+                self.templates.insert(prompt, template);
+                self.modified.insert(prompt, modified);
+                changed.push(prompt);
+            }
+        }
+        changed
+    }
+
+    pub fn template(&self, prompt: Prompt) -> &str {
+        self.templates
+            .get(&prompt)
+            .map(String::as_str)
+            .unwrap_or(prompt.default_template())
+    }
+
+    pub fn render(&self, prompt: Prompt, vars: &[(&str, &str)]) -> String {
+        render(self.template(prompt), vars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_not_rendered_again() {
+        let rendered = render(
+            "Summarize {language}: {content} {unknown}",
+            &[
+                ("language", "rust"),
+                ("content", "fn main() { {language} }"),
+            ],
+        );
+        assert_eq!(
+            rendered,
+            "Summarize rust: fn main() { {language} } {unknown}"
+        );
+    }
+
+    #[test]
+    fn stray_braces_are_kept_as_written() {
+        assert_eq!(
+            render("{ {query} }{query", &[("query", "q")]),
+            "{ q }{query"
+        );
+    }
+
+    #[test]
+    fn edited_and_deleted_files_are_picked_up_without_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut templates = PromptTemplates::load(dir.path());
+        let path = dir.path().join(PROMPTS_DIR).join("answer.txt");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            Prompt::Answer.default_template()
+        );
+        assert!(templates.reload_changed().is_empty());
+
+        fs::write(&path, "Q: {query}").unwrap();
+        // Set the time explicitly; a fast rewrite can keep the old one
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(templates.reload_changed(), vec![Prompt::Answer]);
+        assert_eq!(
+            templates.render(Prompt::Answer, &[("query", "why?")]),
+            "Q: why?"
+        );
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(templates.reload_changed(), vec![Prompt::Answer]);
+        assert_eq!(
+            templates.template(Prompt::Answer),
+            Prompt::Answer.default_template()
+        );
+    }
+}
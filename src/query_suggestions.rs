@@ -0,0 +1,145 @@
+// src/query_suggestions.rs
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::search::lexical_scores;
+
+// Structured answers below this confidence get suggestions
+pub const LOW_CONFIDENCE: f64 = 0.5;
+const MAX_SUGGESTIONS: usize = 4;
+// Files offered for adding to the context
+const MAX_FILE_SUGGESTIONS: usize = 2;
+
+/// A rewrite of a question that did poorly, shown as a chip under the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuerySuggestion {
+    pub label: String,
+    pub query: String,
+    // Pinned to the context before the query is resent
+    pub pin: Option<String>,
+}
+
+// The module a file stands for: its stem, or its directory's name for mod.rs and the like
+fn module_name(file: &str) -> Option<String> {
+    let path = Path::new(file);
+    let stem = path.file_stem()?.to_string_lossy().to_string();
+    if matches!(stem.as_str(), "mod" | "lib" | "main" | "index" | "__init__") {
+        return Some(path.parent()?.file_name()?.to_string_lossy().to_string());
+    }
+    Some(stem)
+}
+
+/// Ways to sharpen `query`, from the files the index matches it with best: name their
+/// module, ask about one of their identifiers, or add them to the context
+pub fn suggest<'a>(
+    query: &str,
+    index: &HashMap<String, (String, String)>,
+    keywords: impl Fn(&str) -> Option<&'a Vec<String>>,
+    context_files: &[String],
+) -> Vec<QuerySuggestion> {
+    let query = query.trim();
+    let lower = query.to_lowercase();
+    let matched: Vec<String> = lexical_scores(index, query, &keywords)
+        .into_iter()
+        .filter(|(score, _)| *score > 0)
+        .map(|(_, file)| file)
+        .collect();
+    let mut suggestions = Vec::new();
+
+    if let Some(module) = matched
+        .iter()
+        .filter_map(|file| module_name(file))
+        .find(|module| !lower.contains(&module.to_lowercase()))
+    {
+        suggestions.push(QuerySuggestion {
+            label: format!("Mention `{}`", module),
+            query: format!("{} (in the `{}` module)", query, module),
+            pin: None,
+        });
+    }
+    if let Some(identifier) = matched
+        .iter()
+        .filter_map(|file| keywords(file))
+        .flatten()
+        .find(|identifier| !lower.contains(&identifier.to_lowercase()))
+    {
+        suggestions.push(QuerySuggestion {
+            label: format!("Ask about `{}`", identifier),
+            query: format!("{}, specifically `{}`", query, identifier),
+            pin: None,
+        });
+    }
+    for file in matched
+        .iter()
+        .filter(|file| !context_files.contains(file))
+        .take(MAX_FILE_SUGGESTIONS)
+    {
+        let name = Path::new(file)
+            .file_name()
+            .map_or(file.clone(), |name| name.to_string_lossy().to_string());
+        suggestions.push(QuerySuggestion {
+            label: format!("Add {}", name),
+            query: query.to_string(),
+            pin: Some(file.clone()),
+        });
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions_come_from_the_best_matching_files() {
+        let index: HashMap<String, (String, String)> = [
+            ("src/auth/token.rs", "Refreshes expired access tokens."),
+            ("src/db.rs", "Connection pool for the database."),
+        ]
+        .into_iter()
+        .map(|(file, summary)| (file.to_string(), (summary.to_string(), "Rust".to_string())))
+        .collect();
+        let keywords = vec!["refresh_token".to_string()];
+        let suggestions = suggest(
+            "why do sessions expire?",
+            &index,
+            |file| (file == "src/auth/token.rs").then_some(&keywords),
+            &[],
+        );
+        let labels: Vec<&str> = suggestions.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Mention `token`",
+                "Ask about `refresh_token`",
+                "Add token.rs"
+            ]
+        );
+        assert_eq!(suggestions[2].pin.as_deref(), Some("src/auth/token.rs"));
+    }
+
+    #[test]
+    fn nothing_already_in_the_question_or_context_is_suggested() {
+        let index: HashMap<String, (String, String)> = [
+            ("src/billing/mod.rs", "Invoices and billing retries."),
+            ("src/cli.rs", "Parses arguments."),
+        ]
+        .into_iter()
+        .map(|(file, summary)| (file.to_string(), (summary.to_string(), "Rust".to_string())))
+        .collect();
+        let keywords = vec!["Invoice".to_string()];
+        let suggestions = suggest(
+            "  How are BILLING invoice retries scheduled?  ",
+            &index,
+            |file| (file == "src/billing/mod.rs").then_some(&keywords),
+            &["src/billing/mod.rs".to_string()],
+        );
+        // mod.rs stands for its directory, which the question names, as it does Invoice
+        assert!(suggestions.is_empty(), "{:?}", suggestions);
+
+        assert!(suggest("unrelated words", &index, |_| None, &[]).is_empty());
+        assert_eq!(module_name("web/index.ts").as_deref(), Some("web"));
+        assert_eq!(module_name("pkg/__init__.py").as_deref(), Some("pkg"));
+    }
+}
@@ -0,0 +1,194 @@
+// src/question_lint.rs
+use std::collections::HashMap;
+use std::fs;
+
+use crate::search::matched_snippets;
+
+// Extensions that make a word a file name rather than the end of a sentence
+const FILE_EXTENSIONS: [&str; 22] = [
+    "rs", "py", "ts", "tsx", "js", "jsx", "go", "java", "rb", "c", "h", "cpp", "hpp", "cs", "kt",
+    "swift", "toml", "json", "yaml", "yml", "sql", "sh",
+];
+// Lines listed per name by the symbol search
+const MAX_SYMBOL_HITS: usize = 10;
+
+// A word that reads as code: a path or file name, CamelCase or snake_case
+fn looks_like_code(word: &str) -> bool {
+    if word.len() < 3 {
+        return false;
+    }
+    // "and/or" is prose, so paths need a file name with a known extension too
+    let is_file = word
+        .rsplit_once('.')
+        .is_some_and(|(stem, extension)| !stem.is_empty() && FILE_EXTENSIONS.contains(&extension));
+    let chars: Vec<char> = word.chars().collect();
+    let camel_case = chars
+        .windows(2)
+        .any(|pair| pair[0].is_lowercase() && pair[1].is_uppercase());
+    let snake_case = word.trim_matches('_').contains('_');
+    is_file || camel_case || snake_case
+}
+
+// A referenced name as it would be searched for: `Type::method()` becomes `method`
+fn clean_name(word: &str) -> String {
+    let word = word.trim_end_matches("()");
+    let word = word.rsplit("::").next().unwrap_or(word);
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_')
+        .to_string()
+}
+
+/// Names of code in a question: `backticked` spans, CamelCase and snake_case
+/// identifiers, and file names or paths
+pub fn referenced_names(question: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut prose = String::new();
+    // Odd segments between backticks are code
+    for (i, segment) in question.split('`').enumerate() {
+        if i % 2 == 1 {
+            names.push(clean_name(segment.trim()));
+        } else {
+            prose.push_str(segment);
+            prose.push(' ');
+        }
+    }
+    for word in prose.split_whitespace() {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '/');
+        if looks_like_code(word) {
+            names.push(clean_name(word));
+        }
+    }
+    let mut unique: Vec<String> = Vec::new();
+    for name in names {
+        if !name.is_empty() && !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+    unique
+}
+
+/// The names a question refers to that appear nowhere the model could find them: not in
+/// an indexed path, keyword or summary, nor in the readable contents of an indexed file
+pub fn missing_names<'a>(
+    question: &str,
+    index: &HashMap<String, (String, String)>,
+    keywords: impl Fn(&str) -> Option<&'a Vec<String>>,
+    readable: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let mut missing: Vec<String> = referenced_names(question)
+        .into_iter()
+        .filter(|name| {
+            let lower = name.to_lowercase();
+            !index.iter().any(|(file, (summary, _))| {
+                file.to_lowercase().contains(&lower)
+                    || summary.to_lowercase().contains(&lower)
+                    || keywords(file).is_some_and(|keywords| {
+                        keywords
+                            .iter()
+                            .any(|keyword| keyword.eq_ignore_ascii_case(name))
+                    })
+            })
+        })
+        .collect();
+    // Summaries name few symbols, so look in the files before calling a name missing
+    for file in index.keys().filter(|file| readable(file)) {
+        if missing.is_empty() {
+            break;
+        }
+        if let Ok(content) = fs::read_to_string(file) {
+            missing.retain(|name| !content.contains(name.as_str()));
+        }
+    }
+    missing
+}
+
+/// Lines of the given files that mention `name`, as `path:line: text`
+pub fn symbol_search<'a>(files: impl Iterator<Item = &'a String>, name: &str) -> Vec<String> {
+    let mut hits = Vec::new();
+    for file in files {
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+        for (line, text) in matched_snippets(&content, name) {
+            hits.push(format!("{}:{}: {}", file, line, text));
+        }
+        if hits.len() >= MAX_SYMBOL_HITS {
+            break;
+        }
+    }
+    hits.truncate(MAX_SYMBOL_HITS);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_unknown_to_the_index_are_reported() {
+        let question =
+            "Where is FooBarManager, and how does `Index::load_index()` read src/chatbot.rs?";
+        assert_eq!(
+            referenced_names(question),
+            vec!["load_index", "FooBarManager", "src/chatbot.rs"]
+        );
+
+        let index: HashMap<String, (String, String)> = [(
+            "src/chatbot.rs".to_string(),
+            (
+                "Loads the index with `load_index`.".to_string(),
+                "Rust".to_string(),
+            ),
+        )]
+        .into_iter()
+        .collect();
+        let missing = missing_names(question, &index, |_| None, |_| true);
+        assert_eq!(missing, vec!["FooBarManager"]);
+    }
+
+    #[test]
+    fn prose_punctuation_is_not_taken_for_code() {
+        assert!(referenced_names(
+            "Should I read and/or write the config, e.g. at startup? It is a JSON file. Done."
+        )
+        .is_empty());
+        assert_eq!(
+            referenced_names("Why does __init__ call setUp in tests/test_app.py?"),
+            vec!["setUp", "tests/test_app.py"]
+        );
+    }
+
+    #[test]
+    fn names_are_found_in_readable_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = dir.path().join("open.rs").to_string_lossy().to_string();
+        let secret = dir.path().join("secret.rs").to_string_lossy().to_string();
+        fs::write(&open, "fn retry_with_backoff() {}\n").unwrap();
+        fs::write(&secret, "fn rotate_keys() {}\n").unwrap();
+        let index: HashMap<String, (String, String)> = [&open, &secret]
+            .into_iter()
+            .map(|file| (file.clone(), ("A file.".to_string(), "Rust".to_string())))
+            .collect();
+        let keywords = vec!["ParseConfig".to_string()];
+
+        let missing = missing_names(
+            "Do retry_with_backoff, rotate_keys and parseconfig interact?",
+            &index,
+            |file| (file == open).then_some(&keywords),
+            |file| file != secret,
+        );
+        assert_eq!(missing, vec!["rotate_keys"]);
+
+        // A few lines per file, up to the limit across files
+        let files: Vec<String> = (0..MAX_SYMBOL_HITS)
+            .map(|i| {
+                let file = dir.path().join(format!("caller{}.rs", i));
+                fs::write(&file, "retry_with_backoff();\nretry_with_backoff();\n").unwrap();
+                file.to_string_lossy().to_string()
+            })
+            .collect();
+        assert_eq!(
+            symbol_search(files.iter(), "retry_with_backoff").len(),
+            MAX_SYMBOL_HITS
+        );
+    }
+}
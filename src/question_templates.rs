@@ -0,0 +1,179 @@
+// src/question_templates.rs
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use crate::prompts::render;
+
+/// The templates offered until the config defines its own
+pub fn default_question_templates() -> BTreeMap<String, String> {
+    [
+        (
+            "explain",
+            "Explain what {file} does and how the rest of the codebase uses it.",
+        ),
+        (
+            "callers",
+            "Find the callers of {symbol} and describe how each one uses it.",
+        ),
+        (
+            "tests",
+            "Which tests cover {file}, and what do they leave untested?",
+        ),
+        (
+            "impact",
+            "What would break if {symbol} changed its signature or behavior?",
+        ),
+    ]
+    .into_iter()
+    .map(|(name, template)| (name.to_string(), template.to_string()))
+    .collect()
+}
+
+/// The `{variables}` of a template, each once, in order of appearance
+pub fn template_variables(template: &str) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = &rest[..end];
+        let is_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        if is_name && !variables.iter().any(|variable| variable == name) {
+            variables.push(name.to_string());
+        }
+    }
+    variables
+}
+
+/// What a variable is completed against, going by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableKind {
+    File,
+    Symbol,
+    Text,
+}
+
+pub fn variable_kind(name: &str) -> VariableKind {
+    match name {
+        "file" | "path" | "module" => VariableKind::File,
+        "symbol" | "function" | "fn" | "type" | "struct" | "trait" => VariableKind::Symbol,
+        _ => VariableKind::Text,
+    }
+}
+
+/// Indexed paths, or the identifiers their summaries mention, to complete a variable with
+pub fn completions(
+    kind: VariableKind,
+    index: &HashMap<String, (String, String)>,
+    keywords: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    match kind {
+        VariableKind::File => {
+            let paths: BTreeSet<String> = index
+                .keys()
+                .map(|path| path.trim_start_matches("./").to_string())
+                .collect();
+            paths.into_iter().collect()
+        }
+        VariableKind::Symbol => {
+            let symbols: BTreeSet<String> = keywords.values().flatten().cloned().collect();
+            symbols.into_iter().collect()
+        }
+        VariableKind::Text => Vec::new(),
+    }
+}
+
+/// A question template being filled in one variable at a time
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateFill {
+    pub name: String,
+    pub template: String,
+    values: Vec<(String, String)>,
+    remaining: VecDeque<String>,
+}
+
+impl TemplateFill {
+    pub fn new(name: &str, template: &str) -> TemplateFill {
+        TemplateFill {
+            name: name.to_string(),
+            template: template.to_string(),
+            values: Vec::new(),
+            remaining: template_variables(template).into(),
+        }
+    }
+
+    /// The variable to ask for next; None once every one has a value
+    pub fn next_variable(&self) -> Option<&str> {
+        self.remaining.front().map(String::as_str)
+    }
+
+    pub fn fill(&mut self, value: &str) {
+        if let Some(variable) = self.remaining.pop_front() {
+            self.values.push((variable, value.to_string()));
+        }
+    }
+
+    /// The template with the values filled in so far
+    pub fn question(&self) -> String {
+        let vars: Vec<(&str, &str)> = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        render(&self.template, &vars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_each_variable_once_in_order() {
+        let mut fill = TemplateFill::new("t", "How does {symbol} reach {file}? Is {symbol} pub?");
+        assert_eq!(fill.next_variable(), Some("symbol"));
+        fill.fill("Chatbot::chat");
+        assert_eq!(fill.next_variable(), Some("file"));
+        fill.fill("src/app.rs");
+        assert_eq!(fill.next_variable(), None);
+        assert_eq!(
+            fill.question(),
+            "How does Chatbot::chat reach src/app.rs? Is Chatbot::chat pub?"
+        );
+    }
+
+    #[test]
+    fn braces_that_do_not_hold_a_name_are_not_variables() {
+        assert_eq!(
+            template_variables("Is {} or { a: 1 } or {a {file} in {module-path}? {unclosed"),
+            vec!["file", "module-path"]
+        );
+        // A value that looks like a variable is not filled in again
+        let mut fill = TemplateFill::new("t", "Compare {file} with {symbol}");
+        fill.fill("{symbol}.rs");
+        assert_eq!(fill.question(), "Compare {symbol}.rs with {symbol}");
+    }
+
+    #[test]
+    fn variables_complete_against_paths_or_identifiers_by_name() {
+        let index: HashMap<String, (String, String)> = ["./src/b.rs", "src/b.rs", "./src/a.rs"]
+            .into_iter()
+            .map(|path| (path.to_string(), (String::new(), String::new())))
+            .collect();
+        let keywords: HashMap<String, Vec<String>> = HashMap::from([
+            (
+                "./src/a.rs".to_string(),
+                vec!["parse".to_string(), "Config".to_string()],
+            ),
+            ("./src/b.rs".to_string(), vec!["parse".to_string()]),
+        ]);
+        let complete = |name| completions(variable_kind(name), &index, &keywords);
+        assert_eq!(complete("module"), vec!["src/a.rs", "src/b.rs"]);
+        assert_eq!(complete("fn"), vec!["Config", "parse"]);
+        assert!(complete("reason").is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+// src/refactor_plan.rs
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+
+use crate::chatbot::{complete_with_claude, detect_language, Chatbot};
+use crate::constants::*;
+use crate::gen_tests::strip_code_fence;
+use crate::sampling::Operation;
+
+/// One ordered step of a refactor plan
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlanStep {
+    pub description: String,
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// A multi-file change plan produced by /plan-refactor, persisted with the session
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RefactorPlan {
+    pub goal: String,
+    pub files: Vec<String>,
+    pub steps: Vec<PlanStep>,
+    pub risks: Vec<String>,
+}
+
+impl RefactorPlan {
+    pub fn completed(&self) -> usize {
+        self.steps.iter().filter(|step| step.done).count()
+    }
+}
+
+/// A proposed file change waiting for /accept, shown to the user as a diff first
+#[derive(Debug, Clone)]
+pub struct PendingEdit {
+    pub path: String,
+    pub contents: String,
+    pub diff: String,
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Function to parse the plan from the model's reply, tolerating prose around the JSON
+fn parse_plan(goal: &str, response: &str) -> Result<RefactorPlan, Box<dyn std::error::Error>> {
+    let start = response
+        .find('{')
+        .ok_or("No JSON object in the plan response")?;
+    let end = response
+        .rfind('}')
+        .ok_or("No JSON object in the plan response")?;
+    let json: Value = serde_json::from_str(&response[start..=end])?;
+
+    let steps: Vec<PlanStep> = json["steps"]
+        .as_array()
+        .map(|steps| {
+            steps
+                .iter()
+                .map(|step| PlanStep {
+                    description: step["description"].as_str().unwrap_or("").to_string(),
+                    files: string_list(&step["files"]),
+                    done: false,
+                })
+                .filter(|step| !step.description.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    if steps.is_empty() {
+        return Err("The plan has no steps".into());
+    }
+
+    Ok(RefactorPlan {
+        goal: goal.to_string(),
+        files: string_list(&json["files"]),
+        steps,
+        risks: string_list(&json["risks"]),
+    })
+}
+
+// Function to ask the model for an ordered multi-file refactor plan
+pub async fn plan_refactor(
+    goal: &str,
+    chatbot: &mut Chatbot,
+) -> Result<RefactorPlan, Box<dyn std::error::Error>> {
+    let mut summaries: Vec<String> = chatbot
+        .index
+        .iter()
+        .map(|(file, (summary, _))| format!("{}: {}", file, summary))
+        .collect();
+    summaries.sort();
+
+    let prompt = format!(
+        "You are planning a refactor of a codebase. Goal: {}\n\n\
+         Files in the codebase with their summaries:\n{}\n\n\
+         Reply with only a JSON object of this shape:\n\
+         {{\"files\": [\"paths that will change\"], \
+         \"steps\": [{{\"description\": \"what to change\", \"files\": [\"paths touched by this step\"]}}], \
+         \"risks\": [\"what could break\"]}}\n\
+         Order the steps so the code builds after each one, and use paths exactly as listed.",
+        goal,
+        summaries.join("\n")
+    );
+
+    let api_key = chatbot.api_key.clone();
+    let response = complete_with_claude(
+        &prompt,
+        STRONG_MODEL,
+        &api_key,
+        "plan_refactor",
+        Operation::Refactor,
+        chatbot,
+    )
+    .await?;
+    parse_plan(goal, &response)
+}
+
+// Function to generate the edits for one plan step, one full file at a time
+pub async fn execute_step(
+    plan: &RefactorPlan,
+    step_index: usize,
+    chatbot: &mut Chatbot,
+) -> Result<Vec<PendingEdit>, Box<dyn std::error::Error>> {
+    let step = plan.steps.get(step_index).ok_or("No such step")?;
+    let mut edits = Vec::new();
+    for path in &step.files {
+        if !chatbot.privacy.allows_contents(path) {
+            return Err(format!("{} is marked private; its contents can't be sent", path).into());
+        }
+        let existing = fs::read_to_string(path).unwrap_or_default();
+        let prompt = format!(
+            "We are refactoring a codebase. Overall goal: {}\n\
+             Current step ({} of {}): {}\n\n\
+             File {} ({}):\n```\n{}\n```\n\n\
+             Apply only this step to this file. Reply with a single fenced code block containing the complete new file.",
+            plan.goal,
+            step_index + 1,
+            plan.steps.len(),
+            step.description,
+            path,
+            detect_language(path),
+            existing
+        );
+        let api_key = chatbot.api_key.clone();
+        let response = complete_with_claude(
+            &prompt,
+            DEFAULT_MODEL,
+            &api_key,
+            "refactor_step",
+            Operation::Refactor,
+            chatbot,
+        )
+        .await?;
+        let contents = strip_code_fence(&response);
+        if contents == existing {
+            continue;
+        }
+        edits.push(PendingEdit {
+            path: path.clone(),
+            diff: diffy::create_patch(&existing, &contents).to_string(),
+            contents,
+        });
+    }
+    Ok(edits)
+}
+
+pub fn write_edits(edits: &[PendingEdit]) -> Result<(), Box<dyn std::error::Error>> {
+    for edit in edits {
+        if let Some(parent) = std::path::Path::new(&edit.path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&edit.path, &edit.contents)?;
+    }
+    Ok(())
+}
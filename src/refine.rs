@@ -0,0 +1,84 @@
+// src/refine.rs
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::audit::ApiCallLog;
+use crate::chatbot::api_call_entry;
+use crate::claude_client::ClaudeClient;
+use crate::events::{AppEvent, EventSender};
+use crate::response::response_text;
+
+// Answers sharing less of their wording than this say something different
+const SAME_ANSWER_SIMILARITY: f32 = 0.6;
+
+/// The full-content request for a question first answered from summaries alone
+#[derive(Clone)]
+pub struct Refinement {
+    pub question: String,
+    pub quick_answer: String,
+    pub model: String,
+    pub request: Value,
+    pub client: Arc<dyn ClaudeClient>,
+    pub api_key: String,
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether the refined answer is worth posting: little wording in common with the
+/// quick one, or code the quick one didn't have
+pub fn materially_differs(quick: &str, refined: &str) -> bool {
+    let (quick_words, refined_words) = (words(quick), words(refined));
+    let union = quick_words.union(&refined_words).count();
+    if union == 0 {
+        return false;
+    }
+    let similarity = quick_words.intersection(&refined_words).count() as f32 / union as f32;
+    similarity < SAME_ANSWER_SIMILARITY || (refined.contains("```") && !quick.contains("```"))
+}
+
+// Sends the request without streaming; the audit entry is returned once the API answered
+async fn send(refinement: &Refinement) -> (Option<ApiCallLog>, Result<String, String>) {
+    let start_time = Instant::now();
+    let mut request = refinement.request.clone();
+    request["stream"] = Value::Bool(false);
+    let response = match refinement.client.post(&refinement.api_key, &request).await {
+        Ok(response) => response,
+        Err(e) => return (None, Err(e)),
+    };
+    let status = response.status;
+    let success = response.is_success();
+    let body: Option<Value> = response.json().await.ok();
+    let call = api_call_entry(
+        &refinement.client.endpoint(),
+        "refine_answer",
+        &refinement.model,
+        status,
+        start_time.elapsed().as_millis(),
+        body.as_ref().filter(|_| success),
+    );
+    let answer = match body {
+        _ if !success => Err(format!("Claude API request failed: {}", status)),
+        Some(body) => response_text(&body).map(|response| response.text.trim().to_string()),
+        None => Err("Failed to parse JSON response".to_string()),
+    };
+    (Some(call), answer)
+}
+
+/// Answers the question again from full file contents and reports back on the event bus
+pub async fn refine(refinement: Refinement, events: EventSender) {
+    let (call, answer) = send(&refinement).await;
+    let _ = events.send(AppEvent::Refined {
+        question: refinement.question,
+        quick_answer: refinement.quick_answer,
+        answer,
+        call,
+    });
+}
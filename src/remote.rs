@@ -0,0 +1,247 @@
+// src/remote.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::process::Command;
+
+use crate::chatbot::{index_codebase, Chatbot};
+use crate::index_progress::IndexProgress;
+
+pub const REMOTES_DIR: &str = "remotes";
+pub const REMOTES_FILE: &str = "remotes.json";
+
+/// A repository cloned from a URL; its files are only ever read, and its index is cached
+/// at the clone's root
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteProject {
+    pub url: String,
+    pub local_path: PathBuf,
+    pub last_fetched: DateTime<Utc>,
+}
+
+pub fn is_git_url(input: &str) -> bool {
+    let input = input.trim();
+    input.starts_with("https://")
+        || input.starts_with("http://")
+        || input.starts_with("git@")
+        || input.starts_with("ssh://")
+        || input.ends_with(".git")
+}
+
+/// Turns a URL into a stable directory name, e.g. github.com_owner_repo
+fn cache_dir_name(url: &str) -> String {
+    let trimmed = url
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("ssh://")
+        .trim_start_matches("git@")
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    trimmed
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+pub fn load_remote_projects(data_dir: &Path) -> Vec<RemoteProject> {
+    fs::read_to_string(data_dir.join(REMOTES_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_remote_project(
+    data_dir: &Path,
+    project: &RemoteProject,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut projects = load_remote_projects(data_dir);
+    projects.retain(|p| p.url != project.url);
+    projects.push(project.clone());
+    fs::write(
+        data_dir.join(REMOTES_FILE),
+        serde_json::to_string_pretty(&projects)?,
+    )?;
+    Ok(())
+}
+
+// Runs git without blocking the UI while it clones or fetches, returning what it printed
+async fn run_git(args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new("git").args(args).output().await?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Whether a directory is the root of a clone with a checked-out commit; a directory left
+// by an interrupted clone, or one inside some other repository, isn't
+async fn is_complete_clone(path: &Path) -> bool {
+    let path_str = path.to_string_lossy().to_string();
+    let git_dir = run_git(&["-C", &path_str, "rev-parse", "--git-dir"]).await;
+    matches!(git_dir.as_deref(), Ok(".git"))
+        && run_git(&["-C", &path_str, "rev-parse", "--verify", "--quiet", "HEAD"])
+            .await
+            .is_ok()
+}
+
+// Function to shallow-clone a repository into the cache, or reuse an existing clone
+pub async fn clone_remote(
+    url: &str,
+    data_dir: &Path,
+) -> Result<RemoteProject, Box<dyn std::error::Error>> {
+    let remotes_dir = data_dir.join(REMOTES_DIR);
+    let local_path = remotes_dir.join(cache_dir_name(url));
+    if local_path.exists() && !is_complete_clone(&local_path).await {
+        fs::remove_dir_all(&local_path)?;
+    }
+    if !local_path.exists() {
+        fs::create_dir_all(&remotes_dir)?;
+        // Cloned beside the cache entry and moved into place once complete, so a failed
+        // clone leaves nothing behind to be mistaken for one
+        let partial = tempfile::Builder::new()
+            .prefix(".partial-")
+            .tempdir_in(&remotes_dir)?;
+        let partial_path = partial.path().join("clone");
+        let path_str = partial_path.to_string_lossy().to_string();
+        // "--" keeps a URL starting with "-" from being read as an option
+        run_git(&["clone", "--depth", "1", "--", url.trim(), &path_str]).await?;
+        fs::rename(&partial_path, &local_path)?;
+    }
+    let project = RemoteProject {
+        url: url.trim().to_string(),
+        local_path,
+        last_fetched: Utc::now(),
+    };
+    save_remote_project(data_dir, &project)?;
+    Ok(project)
+}
+
+// Function to fetch the latest upstream commit, discarding nothing since clones are read-only
+pub async fn fetch_remote(
+    project: &RemoteProject,
+    data_dir: &Path,
+) -> Result<RemoteProject, Box<dyn std::error::Error>> {
+    let path_str = project.local_path.to_string_lossy().to_string();
+    run_git(&["-C", &path_str, "fetch", "--depth", "1", "origin"]).await?;
+    run_git(&["-C", &path_str, "reset", "--hard", "FETCH_HEAD"]).await?;
+    let refreshed = RemoteProject {
+        last_fetched: Utc::now(),
+        ..project.clone()
+    };
+    save_remote_project(data_dir, &refreshed)?;
+    Ok(refreshed)
+}
+
+// Function to (re)index a cloned repository into the chatbot
+pub async fn index_remote(
+    project: &RemoteProject,
+    chatbot: &mut Chatbot,
+    on_progress: &mut dyn FnMut(&IndexProgress),
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let root_dir = project.local_path.to_string_lossy().to_string();
+    let api_key = chatbot.api_key.clone();
+    let pb = indicatif::ProgressBar::hidden();
+    // Start from the clone's own cache, not the index of the project open before it
+    chatbot.use_index_cache(&root_dir);
+    let (index, _, file_mod_times) =
+        index_codebase(&root_dir, &api_key, &pb, chatbot, on_progress).await?;
+    let indexed = index.len();
+    chatbot.index = Arc::new(index);
+    chatbot.file_mod_times = file_mod_times;
+    Ok(indexed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chatbot::{load_index_cache, save_index_cache};
+    use crate::churn::ChurnStats;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn indexing_a_clone_leaves_the_local_index_cache_alone() {
+        let local = tempfile::tempdir().unwrap();
+        let clone = tempfile::tempdir().unwrap();
+        let local_root = local.path().to_string_lossy().to_string();
+        let clone_root = clone.path().to_string_lossy().to_string();
+        let index = HashMap::from([(
+            "./src/lib.rs".to_string(),
+            ("Library root".to_string(), "Rust".to_string()),
+        )]);
+        save_index_cache(
+            &clone_root,
+            &index,
+            0,
+            &HashMap::new(),
+            &HashMap::new(),
+            &ChurnStats::default(),
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        assert!(load_index_cache(&local_root).unwrap().is_none());
+        assert_eq!(load_index_cache(&clone_root).unwrap().unwrap().index, index);
+    }
+
+    // A one-commit repository to clone from, as a file:// URL
+    fn upstream(dir: &Path) -> String {
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "--quiet"]);
+        fs::write(dir.join("README.md"), "upstream\n").unwrap();
+        git(&["add", "README.md"]);
+        git(&["commit", "--quiet", "-m", "initial"]);
+        format!("file://{}", dir.display())
+    }
+
+    #[tokio::test]
+    async fn an_interrupted_clone_is_replaced_and_a_failed_one_leaves_nothing() {
+        let source = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let url = upstream(source.path());
+        // What a clone killed half-way leaves: the directory, without a commit
+        let leftover = data_dir.path().join(REMOTES_DIR).join(cache_dir_name(&url));
+        fs::create_dir_all(leftover.join("partial")).unwrap();
+
+        let project = clone_remote(&url, data_dir.path()).await.unwrap();
+        assert_eq!(project.local_path, leftover);
+        assert_eq!(
+            fs::read_to_string(leftover.join("README.md")).unwrap(),
+            "upstream\n"
+        );
+        assert!(!leftover.join("partial").exists());
+        // A complete clone is reused as it is
+        fs::write(leftover.join("local.txt"), "kept").unwrap();
+        clone_remote(&url, data_dir.path()).await.unwrap();
+        assert!(leftover.join("local.txt").exists());
+
+        let missing = format!("file://{}", source.path().join("missing").display());
+        assert!(clone_remote(&missing, data_dir.path()).await.is_err());
+        let entries: Vec<_> = fs::read_dir(data_dir.path().join(REMOTES_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec![leftover.file_name().unwrap().to_os_string()]);
+    }
+}
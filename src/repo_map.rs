@@ -0,0 +1,175 @@
+// src/repo_map.rs
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+// File names that usually start a program or define a package's surface
+const ENTRY_POINTS: &[&str] = &[
+    "main.rs",
+    "lib.rs",
+    "main.py",
+    "__main__.py",
+    "app.py",
+    "index.js",
+    "index.ts",
+    "main.go",
+    "main.c",
+    "main.cpp",
+    "Main.java",
+];
+// Files described under each top-level directory
+const KEY_FILES_PER_DIR: usize = 3;
+const DESCRIPTION_WIDTH: usize = 120;
+
+// The first sentence of a summary, cut to fit on one line
+fn first_sentence(summary: &str) -> String {
+    let line = summary
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("");
+    let sentence = match line.find(". ") {
+        Some(end) => &line[..=end],
+        None => line,
+    };
+    let sentence = sentence.trim();
+    if sentence.chars().count() > DESCRIPTION_WIDTH {
+        let cut: String = sentence.chars().take(DESCRIPTION_WIDTH).collect();
+        format!("{}…", cut)
+    } else {
+        sentence.to_string()
+    }
+}
+
+fn relative(path: &str, root: &Path) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .map(|relative| relative.display().to_string())
+        .unwrap_or_else(|_| path.to_string())
+        .trim_start_matches("./")
+        .to_string()
+}
+
+fn is_entry_point(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    ENTRY_POINTS.contains(&name)
+}
+
+/// An orientation message for a new session: the top-level directories with their most
+/// substantial files, the top-level files and the entry points, each described by the
+/// first sentence of its summary. None when nothing is indexed
+pub fn repo_map(index: &HashMap<String, (String, String)>, root: &Path) -> Option<String> {
+    if index.is_empty() {
+        return None;
+    }
+    let mut dirs: BTreeMap<String, Vec<(String, &str)>> = BTreeMap::new();
+    let mut top_files: Vec<(String, &str)> = Vec::new();
+    let mut entry_points: Vec<(String, &str)> = Vec::new();
+    for (path, (summary, _)) in index {
+        let path = relative(path, root);
+        let summary = summary.as_str();
+        if is_entry_point(&path) {
+            entry_points.push((path.clone(), summary));
+        }
+        match path.split_once('/') {
+            Some((dir, _)) => dirs
+                .entry(dir.to_string())
+                .or_default()
+                .push((path, summary)),
+            None => top_files.push((path, summary)),
+        }
+    }
+
+    let mut map = format!("**Repository map** ({} files indexed)\n", index.len());
+    if !dirs.is_empty() {
+        map.push_str("\nTop-level directories:\n");
+        for (dir, mut files) in dirs {
+            map.push_str(&format!("- `{}/` ({} files)\n", dir, files.len()));
+            // Entry points first, then the files with the most to say about them
+            files.sort_by(|a, b| {
+                is_entry_point(&b.0)
+                    .cmp(&is_entry_point(&a.0))
+                    .then(b.1.len().cmp(&a.1.len()))
+                    .then(a.0.cmp(&b.0))
+            });
+            for (path, summary) in files.iter().take(KEY_FILES_PER_DIR) {
+                map.push_str(&format!("  - `{}`: {}\n", path, first_sentence(summary)));
+            }
+        }
+    }
+    if !top_files.is_empty() {
+        top_files.sort();
+        map.push_str("\nTop-level files:\n");
+        for (path, summary) in &top_files {
+            map.push_str(&format!("- `{}`: {}\n", path, first_sentence(summary)));
+        }
+    }
+    if !entry_points.is_empty() {
+        entry_points.sort();
+        map.push_str("\nEntry points:\n");
+        for (path, summary) in &entry_points {
+            map.push_str(&format!("- `{}`: {}\n", path, first_sentence(summary)));
+        }
+    }
+    Some(map.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_files_under_their_top_level_directory() {
+        let index: HashMap<String, (String, String)> = [
+            ("./src/main.rs", "Starts the app. Parses flags."),
+            ("./src/ui/chat.rs", "Draws the chat."),
+            ("./Cargo.toml", "Package manifest."),
+        ]
+        .into_iter()
+        .map(|(path, summary)| (path.to_string(), (summary.to_string(), String::new())))
+        .collect();
+        let map = repo_map(&index, Path::new(".")).unwrap();
+        assert!(map.contains("- `src/` (2 files)\n  - `src/main.rs`: Starts the app.\n"));
+        assert!(map.contains("Top-level files:\n- `Cargo.toml`: Package manifest."));
+        assert!(map.contains("Entry points:\n- `src/main.rs`"));
+    }
+
+    #[test]
+    fn each_directory_lists_entry_points_then_the_best_described_files() {
+        let root = Path::new("/work/app");
+        let mut index: HashMap<String, (String, String)> = HashMap::new();
+        for (file, summary) in [
+            ("src/a.rs", "Short."),
+            ("src/b.rs", "A much longer summary of b. More."),
+            ("src/c.rs", "Medium summary."),
+            ("src/lib.rs", "Crate root."),
+        ] {
+            index.insert(
+                root.join(file).display().to_string(),
+                (summary.to_string(), String::new()),
+            );
+        }
+        let map = repo_map(&index, root).unwrap();
+        assert!(map.contains(
+            "- `src/` (4 files)\n  - `src/lib.rs`: Crate root.\n  - `src/b.rs`: A much longer summary of b.\n  - `src/c.rs`: Medium summary.\n"
+        ));
+        assert!(!map.contains("src/a.rs"));
+        assert!(!map.contains("Top-level files"));
+        assert!(repo_map(&HashMap::new(), root).is_none());
+    }
+
+    #[test]
+    fn descriptions_are_one_line_cut_at_character_boundaries() {
+        assert_eq!(
+            first_sentence("\n\nFirst line. Second.\nThird."),
+            "First line."
+        );
+        assert_eq!(
+            first_sentence("Version 1.2 is parsed"),
+            "Version 1.2 is parsed"
+        );
+        let long = "é".repeat(DESCRIPTION_WIDTH + 10);
+        assert_eq!(
+            first_sentence(&long),
+            format!("{}…", "é".repeat(DESCRIPTION_WIDTH))
+        );
+    }
+}
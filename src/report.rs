@@ -0,0 +1,270 @@
+// src/report.rs
+use chrono::Local;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::chatbot::{complete_with_claude, index_codebase, Chatbot};
+use crate::constants::*;
+use crate::privacy::PrivacyLevel;
+use crate::sampling::Operation;
+use crate::todos::TODO_MARKERS;
+
+pub const REPORT_FILE: &str = "ONBOARDING.md";
+// Characters of the directory outline sent with each section prompt
+const OUTLINE_BUDGET: usize = 100_000;
+const MAX_TODOS: usize = 200;
+const BUILD_FILES: [&str; 10] = [
+    "Cargo.toml",
+    "package.json",
+    "Makefile",
+    "justfile",
+    "pyproject.toml",
+    "setup.py",
+    "go.mod",
+    "build.gradle",
+    "pom.xml",
+    "CMakeLists.txt",
+];
+
+/// Extra material a section prompt gets besides the directory outline
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SectionInput {
+    OutlineOnly,
+    BuildFiles,
+    TodoComments,
+}
+
+/// One step of the fixed report pipeline
+struct ReportSection {
+    title: &'static str,
+    instructions: &'static str,
+    input: SectionInput,
+    model: &'static str,
+}
+
+const SECTIONS: [ReportSection; 5] = [
+    ReportSection {
+        title: "Architecture overview",
+        instructions: "Write an architecture overview for a developer joining this project: what it does, its main layers or components, and how data flows between them. Two to four short paragraphs.",
+        input: SectionInput::OutlineOnly,
+        model: STRONG_MODEL,
+    },
+    ReportSection {
+        title: "Key modules",
+        instructions: "List the 5 to 12 modules or directories a newcomer should read first, as a markdown bullet list of `path` followed by one sentence on its responsibility. Order them from most to least central.",
+        input: SectionInput::OutlineOnly,
+        model: DEFAULT_MODEL,
+    },
+    ReportSection {
+        title: "Entry points",
+        instructions: "List the entry points of the project (binaries, main functions, servers, CLI commands, public library roots) as a markdown bullet list of `path` and what starts there.",
+        input: SectionInput::OutlineOnly,
+        model: DEFAULT_MODEL,
+    },
+    ReportSection {
+        title: "Build and test commands",
+        instructions: "Give the commands to build, run, test and lint the project as a markdown list of fenced shell snippets with one line of explanation each. Only list commands supported by the build files below; say so if none are present.",
+        input: SectionInput::BuildFiles,
+        model: DEFAULT_MODEL,
+    },
+    ReportSection {
+        title: "Noteworthy TODOs",
+        instructions: "From the TODO-style comments below, pick the ones a newcomer should know about (known bugs, unfinished features, risky workarounds) and list them as markdown bullets of `path:line` and why it matters. Skip trivial ones; say so if there are none.",
+        input: SectionInput::TodoComments,
+        model: DEFAULT_MODEL,
+    },
+];
+
+/// Per-file summaries grouped by directory, one indented heading per directory level
+pub fn directory_outline(chatbot: &Chatbot, root: &str) -> String {
+    let mut directories: BTreeMap<String, Vec<(String, &str)>> = BTreeMap::new();
+    for (path, (summary, _)) in chatbot.index.iter() {
+        let Ok(relative) = Path::new(path).strip_prefix(root) else {
+            continue;
+        };
+        if chatbot.privacy.level_for(path) == PrivacyLevel::Never {
+            continue;
+        }
+        let directory = relative
+            .parent()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let file = relative
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        directories
+            .entry(directory)
+            .or_default()
+            .push((file, summary.as_str()));
+    }
+
+    let mut outline = String::new();
+    for (directory, mut files) in directories {
+        let depth = Path::new(&directory).components().count();
+        let indent = "  ".repeat(depth);
+        let name = if directory.is_empty() {
+            "./".to_string()
+        } else {
+            format!("{}/", directory)
+        };
+        outline.push_str(&format!(
+            "{}{}\n",
+            "  ".repeat(depth.saturating_sub(1)),
+            name
+        ));
+        files.sort();
+        for (file, summary) in files {
+            outline.push_str(&format!("{}- {}: {}\n", indent, file, summary));
+        }
+    }
+    truncate_at_char_boundary(&mut outline, OUTLINE_BUDGET);
+    outline
+}
+
+fn truncate_at_char_boundary(text: &mut String, max: usize) {
+    if text.len() <= max {
+        return;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str("\n[outline truncated]\n");
+}
+
+// Build manifests at the project root whose contents may be sent
+fn build_files(chatbot: &Chatbot, root: &Path) -> String {
+    let mut material = String::new();
+    for name in BUILD_FILES {
+        let path = root.join(name);
+        if !chatbot.privacy.allows_contents(&path.display().to_string()) {
+            continue;
+        }
+        if let Ok(contents) = fs::read_to_string(&path) {
+            material.push_str(&format!("File: {}\n{}\n\n", name, contents));
+        }
+    }
+    material
+}
+
+// TODO-style comments of the indexed files, as `path:line: text`
+fn todo_comments(chatbot: &Chatbot, root: &str) -> String {
+    let mut files: Vec<&String> = chatbot
+        .index
+        .keys()
+        .filter(|path| Path::new(path).starts_with(root))
+        .filter(|path| chatbot.privacy.allows_contents(path))
+        .collect();
+    files.sort();
+
+    let mut todos = Vec::new();
+    for path in files {
+        let Ok(contents) = fs::read_to_string(path) else {
+            continue;
+        };
+        for (number, line) in contents.lines().enumerate() {
+            if TODO_MARKERS.iter().any(|marker| line.contains(marker)) {
+                todos.push(format!("{}:{}: {}", path, number + 1, line.trim()));
+            }
+        }
+    }
+    todos.truncate(MAX_TODOS);
+    todos.join("\n")
+}
+
+// Function to run the report pipeline over an indexed project and assemble the document
+pub async fn generate_report(
+    chatbot: &mut Chatbot,
+    root: &str,
+    on_section: &mut dyn FnMut(&str),
+) -> Result<String, Box<dyn std::error::Error>> {
+    let outline = directory_outline(chatbot, root);
+    if outline.is_empty() {
+        return Err(format!("No indexed files under {}", root).into());
+    }
+    let project = Path::new(root)
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| root.to_string());
+
+    let mut report = format!(
+        "# Onboarding: {}\n\n_Generated by sagacity on {} from {} indexed file summaries._\n",
+        project,
+        Local::now().format("%Y-%m-%d"),
+        chatbot
+            .index
+            .keys()
+            .filter(|path| Path::new(path).starts_with(root))
+            .count()
+    );
+    let api_key = chatbot.api_key.clone();
+    for section in &SECTIONS {
+        on_section(section.title);
+        let material = match section.input {
+            SectionInput::OutlineOnly => String::new(),
+            SectionInput::BuildFiles => {
+                format!("Build files:\n\n{}", build_files(chatbot, Path::new(root)))
+            }
+            SectionInput::TodoComments => {
+                format!("TODO-style comments:\n\n{}", todo_comments(chatbot, root))
+            }
+        };
+        let prompt = format!(
+            "You are writing the \"{}\" section of an onboarding document for the {} project. {}\n\n\
+             Reply with the section body in markdown only, without the section heading.\n\n\
+             Directory outline with a summary per file:\n\n{}\n{}",
+            section.title, project, section.instructions, outline, material
+        );
+        let body = complete_with_claude(
+            &prompt,
+            section.model,
+            &api_key,
+            "report",
+            Operation::Report,
+            chatbot,
+        )
+        .await?;
+        report.push_str(&format!("\n## {}\n\n{}\n", section.title, body.trim()));
+    }
+    Ok(report)
+}
+
+// Function behind `sagacity report [path] [--out file]`: indexes, then writes the report
+pub async fn run_report_command(
+    args: &[String],
+    chatbot: &mut Chatbot,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut root = ".".to_string();
+    let mut out = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" | "-o" => {
+                out = Some(PathBuf::from(args.next().ok_or("--out needs a file path")?));
+            }
+            _ => root = arg.clone(),
+        }
+    }
+    let out = out.unwrap_or_else(|| Path::new(&root).join(REPORT_FILE));
+
+    eprintln!("Indexing {}…", root);
+    let api_key = chatbot.api_key.clone();
+    let pb = indicatif::ProgressBar::hidden();
+    chatbot.use_index_cache(&root);
+    let (index, _, file_mod_times) =
+        index_codebase(&root, &api_key, &pb, chatbot, &mut |_| {}).await?;
+    chatbot.index = Arc::new(index);
+    chatbot.file_mod_times = file_mod_times;
+
+    let report = generate_report(chatbot, &root, &mut |title| {
+        eprintln!("Writing section: {}", title);
+    })
+    .await?;
+    fs::write(&out, report)?;
+    Ok(out)
+}
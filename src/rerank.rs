@@ -0,0 +1,180 @@
+// src/rerank.rs
+//! A second, stronger ranking of the top retrieved files: the model sees each candidate's
+//! summary with the start of its code and orders them against each other for the
+//! question, which settles ambiguous questions better than scoring summaries one by one.
+use std::collections::HashMap;
+use std::fs;
+
+use crate::chatbot::{complete_with_claude, Chatbot};
+use crate::constants::DEFAULT_MODEL;
+use crate::prompts::Prompt;
+use crate::sampling::Operation;
+
+// Candidates the first ranking hands to the re-ranking
+pub const RERANK_CANDIDATES: usize = 20;
+// Lines of each file shown beside its summary
+const EXCERPT_LINES: usize = 30;
+
+// Each candidate with its summary and, where privacy rules allow, its first lines
+fn describe_candidates(
+    candidates: &[(String, f32)],
+    index: &HashMap<String, (String, String)>,
+    chatbot: &Chatbot,
+) -> String {
+    let mut described = String::new();
+    for (file, _) in candidates {
+        let summary = index.get(file).map_or("", |(summary, _)| summary.as_str());
+        described.push_str(&format!("### {}\nSummary: {}\n", file, summary));
+        if chatbot.privacy.allows_contents(file) {
+            if let Ok(content) = fs::read_to_string(file) {
+                let excerpt: Vec<&str> = content.lines().take(EXCERPT_LINES).collect();
+                described.push_str(&format!("```\n{}\n```\n", excerpt.join("\n")));
+            }
+        }
+        described.push('\n');
+    }
+    described
+}
+
+/// Orders the candidates as the reply lists them, one path per line; those it leaves out
+/// follow in their first order. Scores fall evenly from 1 by position
+pub fn apply_ranking(candidates: &[(String, f32)], reply: &str) -> Vec<(String, f32)> {
+    let mut ordered: Vec<&String> = Vec::new();
+    for line in reply.lines().map(str::trim) {
+        // A list marker like "1." or "-" is its own word; "./src" is part of the path
+        let path = match line.split_once(char::is_whitespace) {
+            Some((marker, rest))
+                if marker
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*')) =>
+            {
+                rest.trim()
+            }
+            _ => line,
+        }
+        .trim_matches('`');
+        if let Some((file, _)) = candidates.iter().find(|(file, _)| file == path) {
+            if !ordered.contains(&file) {
+                ordered.push(file);
+            }
+        }
+    }
+    for (file, _) in candidates {
+        if !ordered.contains(&file) {
+            ordered.push(file);
+        }
+    }
+    let count = ordered.len() as f32;
+    ordered
+        .into_iter()
+        .enumerate()
+        .map(|(rank, file)| (file.clone(), 1.0 - rank as f32 / count))
+        .collect()
+}
+
+/// Re-ranks the candidates for the question with one more model call
+pub async fn rerank(
+    query: &str,
+    candidates: &[(String, f32)],
+    chatbot: &mut Chatbot,
+) -> Result<Vec<(String, f32)>, Box<dyn std::error::Error>> {
+    let index = chatbot.retrieval_index();
+    let described = describe_candidates(candidates, &index, chatbot);
+    let prompt = chatbot.prompts.render(
+        Prompt::Rerank,
+        &[("query", query), ("candidates", &described)],
+    );
+    let api_key = chatbot.api_key.clone();
+    let reply = complete_with_claude(
+        &prompt,
+        DEFAULT_MODEL,
+        &api_key,
+        "rerank",
+        Operation::Relevance,
+        chatbot,
+    )
+    .await?;
+    Ok(apply_ranking(candidates, &reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_reply_decides_the_order_and_omitted_files_follow() {
+        let candidates = vec![
+            ("./src/a.rs".to_string(), 0.9),
+            ("./src/b.rs".to_string(), 0.8),
+            ("./src/c.rs".to_string(), 0.7),
+            ("./src/d.rs".to_string(), 0.6),
+        ];
+        let reply = "1. `./src/c.rs`\n2. ./src/a.rs\n./src/unknown.rs\n./src/c.rs\n";
+        let ranked: Vec<String> = apply_ranking(&candidates, reply)
+            .into_iter()
+            .map(|(file, _)| file)
+            .collect();
+        assert_eq!(
+            ranked,
+            vec!["./src/c.rs", "./src/a.rs", "./src/b.rs", "./src/d.rs"]
+        );
+        assert_eq!(
+            apply_ranking(&candidates, "")[0],
+            ("./src/a.rs".to_string(), 1.0)
+        );
+    }
+
+    #[test]
+    fn bare_paths_match_and_markers_are_only_stripped_as_words() {
+        let candidates = vec![
+            ("./src/a.rs".to_string(), 0.9),
+            ("./src/b.rs".to_string(), 0.8),
+            ("10.rs".to_string(), 0.7),
+        ];
+        let ranked = apply_ranking(&candidates, "./src/b.rs\n10.rs\n* `./src/a.rs`\n");
+        assert_eq!(
+            ranked,
+            vec![
+                ("./src/b.rs".to_string(), 1.0),
+                ("10.rs".to_string(), 1.0 - 1.0 / 3.0),
+                ("./src/a.rs".to_string(), 1.0 - 2.0 / 3.0),
+            ]
+        );
+        assert!(apply_ranking(&[], "./src/a.rs").is_empty());
+    }
+
+    #[test]
+    fn excerpts_are_shown_only_where_privacy_allows() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = dir.path().join("open.rs").display().to_string();
+        let secret = dir.path().join("secret.rs").display().to_string();
+        let long: Vec<String> = (0..EXCERPT_LINES + 5)
+            .map(|i| format!("line {}", i))
+            .collect();
+        fs::write(&open, long.join("\n")).unwrap();
+        fs::write(&secret, "const KEY: &str = \"k\";\n").unwrap();
+        let mut chatbot = Chatbot::new(
+            HashMap::new(),
+            HashMap::new(),
+            "key".to_string(),
+            std::sync::Arc::new(crate::claude_client::MockClient::default()),
+        );
+        chatbot.privacy = crate::privacy::PrivacyPolicy::new(&[crate::privacy::PrivacyRule {
+            pattern: "secret.rs".to_string(),
+            level: crate::privacy::PrivacyLevel::SummaryOnly,
+        }]);
+        let index = HashMap::from([(
+            secret.clone(),
+            ("Holds the key.".to_string(), "rust".to_string()),
+        )]);
+        let described = describe_candidates(
+            &[(open.clone(), 0.9), (secret.clone(), 0.8)],
+            &index,
+            &chatbot,
+        );
+        assert!(described.contains(&format!("line {}\n```", EXCERPT_LINES - 1)));
+        assert!(!described.contains(&format!("line {}", EXCERPT_LINES)));
+        assert!(described.contains("Summary: Holds the key.\n\n"));
+        assert!(!described.contains("KEY"));
+    }
+}
@@ -0,0 +1,61 @@
+// src/response.rs
+use serde_json::Value;
+
+/// Text of a Messages API response, with the content blocks that carried none
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseText {
+    pub text: String,
+    // Block types that were skipped, such as tool_use or types newer than this client
+    pub skipped: Vec<String>,
+}
+
+/// "type: message" of an API error object
+pub fn describe_error(error: &Value) -> String {
+    match (error["type"].as_str(), error["message"].as_str()) {
+        (Some(kind), Some(message)) => format!("{}: {}", kind, message),
+        (None, Some(message)) => message.to_string(),
+        _ => error.to_string(),
+    }
+}
+
+/// Joins every text block of a response body in order. An error envelope is an error;
+/// other block types are skipped rather than failing the request
+pub fn response_text(body: &Value) -> Result<ResponseText, String> {
+    if body["type"] == "error" || body["error"].is_object() {
+        return Err(format!(
+            "Claude API returned an error: {}",
+            describe_error(&body["error"])
+        ));
+    }
+    let Some(blocks) = body["content"].as_array() else {
+        return Err(format!(
+            "API response has no content (stop reason: {})",
+            body["stop_reason"]
+        ));
+    };
+    let mut text = String::new();
+    let mut skipped = Vec::new();
+    for block in blocks {
+        match (block["type"].as_str(), block["text"].as_str()) {
+            (Some("text"), Some(block_text)) => text.push_str(block_text),
+            (kind, _) => skipped.push(kind.unwrap_or("untyped").to_string()),
+        }
+    }
+    if text.trim().is_empty() {
+        return Err(format!(
+            "API response has no text (stop reason: {}, blocks: {})",
+            body["stop_reason"],
+            skipped.join(", ")
+        ));
+    }
+    Ok(ResponseText { text, skipped })
+}
+
+/// Text of a streamed content_block_delta; None for deltas that carry tool input or
+/// other non-text content
+pub fn delta_text(event: &Value) -> Option<&str> {
+    match event["delta"]["type"].as_str() {
+        Some("text_delta") | None => event["delta"]["text"].as_str(),
+        Some(_) => None,
+    }
+}
@@ -0,0 +1,126 @@
+// src/retrieval_memory.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::eviction::ContextEviction;
+
+pub const RETRIEVAL_MEMORY_FILE: &str = "retrieval_memory.json";
+
+// How much a perfectly similar past question can add to a relevance score
+const MAX_BOOST: f32 = 0.3;
+// Past questions less similar than this are ignored
+const MIN_SIMILARITY: f32 = 0.2;
+const MAX_RECORDS: usize = 500;
+
+/// Files that were decisive for one answered question
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QaRecord {
+    pub question: String,
+    pub files: Vec<String>,
+    pub weight: f32,
+}
+
+/// A lightweight learned ranking layer over past answers
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RetrievalMemory {
+    pub records: Vec<QaRecord>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+fn keywords(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.len() >= 3)
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Jaccard similarity of the keyword sets of two questions
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count() as f32;
+    shared / a.union(b).count() as f32
+}
+
+impl RetrievalMemory {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(RETRIEVAL_MEMORY_FILE);
+        let mut memory: RetrievalMemory = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        memory.path = Some(path);
+        memory
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+        }
+        Ok(())
+    }
+
+    /// Remembers that `files` answered `question`; feedback passes a higher weight
+    pub fn record(&mut self, question: &str, files: &[String], weight: f32) {
+        if files.is_empty() {
+            return;
+        }
+        self.records.push(QaRecord {
+            question: question.to_string(),
+            files: files.to_vec(),
+            weight,
+        });
+        if self.records.len() > MAX_RECORDS {
+            let excess = self.records.len() - MAX_RECORDS;
+            self.records.drain(..excess);
+        }
+    }
+
+    /// Points records at renamed files and forgets deleted ones
+    pub fn evict(&mut self, eviction: &ContextEviction) {
+        for record in self.records.iter_mut() {
+            eviction.remap_all(&mut record.files);
+        }
+        self.records.retain(|record| !record.files.is_empty());
+    }
+
+    /// Raises the scores of files that were decisive for similar past questions
+    pub fn apply_boost(&self, query: &str, scores: &mut [(String, f32)]) {
+        let query_keywords = keywords(query);
+        for (file, score) in scores.iter_mut() {
+            let boost: f32 = self
+                .records
+                .iter()
+                .filter(|record| record.files.iter().any(|f| f == file))
+                .map(|record| {
+                    let sim = similarity(&query_keywords, &keywords(&record.question));
+                    if sim < MIN_SIMILARITY {
+                        0.0
+                    } else {
+                        sim * record.weight
+                    }
+                })
+                .sum();
+            *score += (boost * MAX_BOOST).min(MAX_BOOST);
+        }
+    }
+}
+
+/// Files from the context whose paths or names are cited in the answer
+pub fn cited_files(answer: &str, context_files: &[String]) -> Vec<String> {
+    context_files
+        .iter()
+        .filter(|path| {
+            let file_name = Path::new(path.as_str())
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            answer.contains(path.as_str()) || (!file_name.is_empty() && answer.contains(&file_name))
+        })
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,251 @@
+// src/rust_check.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tokio::process::Command;
+
+// Shared between checks so the project's dependencies are only compiled once
+const CHECK_TARGET_DIR: &str = "rust-check-target";
+const SCRATCH_PACKAGE: &str = "sagacity_scratch";
+const MAX_ERROR_LINES: usize = 40;
+
+/// Result of `cargo check` on one Rust code block of an answer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", content = "output", rename_all = "snake_case")]
+pub enum RustCheck {
+    Compiles,
+    Errors(String),
+    // cargo could not be run at all, so nothing is known about the code
+    Unavailable(String),
+}
+
+impl RustCheck {
+    pub fn badge(&self, accessible: bool) -> &'static str {
+        match (self, accessible) {
+            (RustCheck::Compiles, false) => "✓ compiles",
+            (RustCheck::Errors(_), false) => "✗ errors (view)",
+            (RustCheck::Unavailable(_), false) => "? not checked",
+            (RustCheck::Compiles, true) => "[compiles]",
+            (RustCheck::Errors(_), true) => "[errors (view)]",
+            (RustCheck::Unavailable(_), true) => "[not checked]",
+        }
+    }
+}
+
+/// Contents of the ```rust (or ```rs) fences of a message, in order
+pub fn rust_code_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    // Outside a fence, inside a Rust fence with its lines, or inside another fence
+    let mut fence: Option<Option<Vec<&str>>> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            fence = match fence {
+                Some(Some(code)) => {
+                    blocks.push(code.join("\n"));
+                    None
+                }
+                Some(None) => None,
+                None if is_rust_fence(trimmed.trim_start_matches('`').trim()) => {
+                    Some(Some(Vec::new()))
+                }
+                None => Some(None),
+            };
+            continue;
+        }
+        if let Some(Some(code)) = fence.as_mut() {
+            code.push(line);
+        }
+    }
+    blocks
+}
+
+pub fn is_rust_fence(language: &str) -> bool {
+    matches!(language, "rust" | "rs")
+}
+
+// Lines of one `[section]` of a manifest, without blanks and comments
+fn section_lines<'a>(manifest: &'a str, section: &str) -> Vec<&'a str> {
+    let mut inside = false;
+    let mut lines = Vec::new();
+    for line in manifest.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            inside = trimmed == section;
+            continue;
+        }
+        if inside && !trimmed.is_empty() && !trimmed.starts_with('#') {
+            lines.push(trimmed);
+        }
+    }
+    lines
+}
+
+// The crate a dependency line names, as in `serde = ...` or `serde.workspace = true`
+fn dependency_name(line: &str) -> &str {
+    line.split(['=', '.']).next().unwrap_or("").trim()
+}
+
+fn is_path_dependency(line: &str) -> bool {
+    line.split([',', '{']).any(|part| {
+        part.trim_start()
+            .strip_prefix("path")
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+}
+
+fn is_workspace_dependency(line: &str) -> bool {
+    line.split(['{', ',']).any(|part| {
+        let part = part.trim();
+        let part = part.strip_prefix(dependency_name(line)).unwrap_or(part);
+        let part = part.trim_start_matches('.').trim_start();
+        part.strip_prefix("workspace")
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+}
+
+// `[workspace.dependencies]` of the nearest manifest at or above `project_root`, by crate
+fn workspace_dependencies(project_root: &Path) -> HashMap<String, String> {
+    project_root
+        .ancestors()
+        .filter_map(|dir| fs::read_to_string(dir.join("Cargo.toml")).ok())
+        .find(|manifest| manifest.contains("[workspace.dependencies]"))
+        .map(|manifest| {
+            section_lines(&manifest, "[workspace.dependencies]")
+                .into_iter()
+                .map(|line| (dependency_name(line).to_string(), line.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// [dependencies] of the project's Cargo.toml, with `workspace = true` entries taken from
+// the workspace; path dependencies can't resolve from the scratch crate and are left out
+fn project_dependencies(project_root: &Path) -> String {
+    let Ok(manifest) = fs::read_to_string(project_root.join("Cargo.toml")) else {
+        return String::new();
+    };
+    let workspace = workspace_dependencies(project_root);
+    section_lines(&manifest, "[dependencies]")
+        .into_iter()
+        .filter_map(|line| {
+            if is_workspace_dependency(line) {
+                workspace.get(dependency_name(line)).map(String::as_str)
+            } else {
+                Some(line)
+            }
+        })
+        .filter(|line| !is_path_dependency(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Runs `cargo check` on a code block in a scratch crate that has the project's dependencies
+pub async fn check_rust_block(code: &str, project_root: &Path, data_dir: &Path) -> RustCheck {
+    let scratch = match tempfile::tempdir() {
+        Ok(dir) => dir,
+        Err(e) => return RustCheck::Unavailable(format!("No scratch directory: {}", e)),
+    };
+    let manifest = format!(
+        "[package]\nname = \"{}\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[dependencies]\n{}\n",
+        SCRATCH_PACKAGE,
+        project_dependencies(project_root)
+    );
+    // Snippets with a main are binaries; anything else is checked as a library
+    let (source, contents) = if code.contains("fn main(") {
+        ("main.rs", code.to_string())
+    } else {
+        // Same line as the code so compiler line numbers match the snippet
+        ("lib.rs", format!("#![allow(dead_code, unused)] {}", code))
+    };
+    let written = fs::write(scratch.path().join("Cargo.toml"), manifest)
+        .and_then(|_| fs::create_dir_all(scratch.path().join("src")))
+        .and_then(|_| fs::write(scratch.path().join("src").join(source), contents));
+    if let Err(e) = written {
+        return RustCheck::Unavailable(format!("Failed to write scratch crate: {}", e));
+    }
+
+    let output = Command::new("cargo")
+        .args(["check", "--quiet", "--message-format", "short"])
+        .env("CARGO_TARGET_DIR", data_dir.join(CHECK_TARGET_DIR))
+        .current_dir(scratch.path())
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => RustCheck::Compiles,
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let errors: Vec<&str> = stderr
+                .lines()
+                .filter(|line| line.contains("error"))
+                .take(MAX_ERROR_LINES)
+                .collect();
+            RustCheck::Errors(
+                errors
+                    .join("\n")
+                    .replace(&format!("src/{}", source), "snippet"),
+            )
+        }
+        Err(e) => RustCheck::Unavailable(format!("Failed to run cargo: {}", e)),
+    }
+}
+
+/// Checks every Rust block of an answer, in order
+pub async fn check_rust_blocks(text: &str, project_root: &Path, data_dir: &Path) -> Vec<RustCheck> {
+    let mut checks = Vec::new();
+    for code in rust_code_blocks(text) {
+        checks.push(check_rust_block(&code, project_root, data_dir).await);
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_rust_fences_are_extracted() {
+        let answer = "Use it like so:\n```rust\nlet x = 1;\nlet y = x + 1;\n```\n\
+                      Then run:\n```sh\ncargo run\n```\n  ```rs\nfn f() {}\n  ```\n\
+                      ```\nplain\n```";
+        assert_eq!(
+            rust_code_blocks(answer),
+            vec!["let x = 1;\nlet y = x + 1;", "fn f() {}"]
+        );
+        // An unclosed fence yields nothing rather than half a block
+        assert!(rust_code_blocks("```rust\nfn g() {}").is_empty());
+    }
+
+    #[test]
+    fn dependencies_resolve_workspace_entries_and_drop_paths() {
+        let workspace = tempfile::tempdir().unwrap();
+        fs::write(
+            workspace.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\"]\n\n[workspace.dependencies]\n\
+             serde = { version = \"1\", features = [\"derive\"] }\n\
+             shared = { path = \"shared\" }\n",
+        )
+        .unwrap();
+        let member = workspace.path().join("app");
+        fs::create_dir(&member).unwrap();
+        fs::write(
+            member.join("Cargo.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\n\
+             # pinned for MSRV\n\
+             regex = \"1.11\"\n\
+             serde = { workspace = true }\n\
+             shared.workspace = true\n\
+             local={path=\"../local\"}\n\
+             unknown = { workspace = true }\n\n\
+             [dev-dependencies]\nproptest = \"1\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            project_dependencies(&member),
+            "regex = \"1.11\"\nserde = { version = \"1\", features = [\"derive\"] }"
+        );
+        assert_eq!(project_dependencies(&member.join("missing")), "");
+    }
+}
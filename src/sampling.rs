@@ -0,0 +1,111 @@
+// src/sampling.rs
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Kind of request sent to the model, each with its own sampling parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Summarize,
+    Relevance,
+    Chat,
+    DeepDive,
+    GenTests,
+    Refactor,
+    Explain,
+    Report,
+}
+
+/// Temperature and top_p for one operation; unset values are left to the API default
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct SamplingParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+}
+
+impl SamplingParams {
+    pub fn deterministic() -> Self {
+        SamplingParams {
+            temperature: Some(0.0),
+            top_p: None,
+        }
+    }
+
+    pub fn with_temperature(temperature: f32) -> Self {
+        SamplingParams {
+            temperature: Some(temperature),
+            top_p: None,
+        }
+    }
+
+    /// Adds the set parameters to a Messages API request body
+    pub fn apply(&self, body: &mut Value) {
+        if let Some(temperature) = self.temperature {
+            body["temperature"] = Value::from(temperature);
+        }
+        if let Some(top_p) = self.top_p {
+            body["top_p"] = Value::from(top_p);
+        }
+    }
+}
+
+/// Sampling parameters per operation. Summaries and relevance scoring are
+/// deterministic by default so re-indexing the same code gives the same result.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SamplingConfig {
+    #[serde(default = "SamplingParams::deterministic")]
+    pub summarize: SamplingParams,
+    #[serde(default = "SamplingParams::deterministic")]
+    pub relevance: SamplingParams,
+    #[serde(default = "default_chat")]
+    pub chat: SamplingParams,
+    #[serde(default = "default_analysis")]
+    pub deep_dive: SamplingParams,
+    #[serde(default = "default_analysis")]
+    pub gen_tests: SamplingParams,
+    #[serde(default = "default_analysis")]
+    pub refactor: SamplingParams,
+    #[serde(default = "default_analysis")]
+    pub explain: SamplingParams,
+    #[serde(default = "default_analysis")]
+    pub report: SamplingParams,
+}
+
+fn default_chat() -> SamplingParams {
+    SamplingParams::with_temperature(0.7)
+}
+
+fn default_analysis() -> SamplingParams {
+    SamplingParams::with_temperature(0.2)
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        SamplingConfig {
+            summarize: SamplingParams::deterministic(),
+            relevance: SamplingParams::deterministic(),
+            chat: default_chat(),
+            deep_dive: default_analysis(),
+            gen_tests: default_analysis(),
+            refactor: default_analysis(),
+            explain: default_analysis(),
+            report: default_analysis(),
+        }
+    }
+}
+
+impl SamplingConfig {
+    pub fn for_operation(&self, operation: Operation) -> SamplingParams {
+        match operation {
+            Operation::Summarize => self.summarize,
+            Operation::Relevance => self.relevance,
+            Operation::Chat => self.chat,
+            Operation::DeepDive => self.deep_dive,
+            Operation::GenTests => self.gen_tests,
+            Operation::Refactor => self.refactor,
+            Operation::Explain => self.explain,
+            Operation::Report => self.report,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// src/saved_responses.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SAVED_RESPONSES_INDEX: &str = "index.json";
+// Longest file stem kept from a generated name
+const MAX_STEM_CHARS: usize = 50;
+
+/// Asks for a file stem naming what a response is about
+pub fn filename_prompt(content: &str) -> String {
+    format!(
+        "Based on the following content, generate a concise and descriptive filename of 1 to 4 words that summarizes the main topic or purpose. Separate words with hyphens and leave out the extension. Only return the filename, nothing else:\n\n{}",
+        content
+    )
+}
+
+/// A generated name cut down to a safe file stem: lowercase words joined by hyphens
+pub fn sanitize_stem(name: &str) -> Option<String> {
+    let name = name.lines().next().unwrap_or("").trim().trim_matches('`');
+    let name = match name.rsplit_once('.') {
+        Some((stem, extension)) if extension.len() <= 4 => stem,
+        _ => name,
+    };
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let stem: String = words.join("-").chars().take(MAX_STEM_CHARS).collect();
+    let stem = stem.trim_end_matches('-').to_string();
+    (!stem.is_empty()).then_some(stem)
+}
+
+/// Fenced code blocks of a message as (language, contents), in order
+pub fn code_blocks(markdown: &str) -> Vec<(String, String)> {
+    let mut blocks = Vec::new();
+    let mut lines = markdown.lines();
+    while let Some(line) = lines.next() {
+        let Some(info) = line.trim_start().strip_prefix("```") else {
+            continue;
+        };
+        let language = info.split_whitespace().next().unwrap_or("").to_lowercase();
+        let mut contents = lines
+            .by_ref()
+            .take_while(|line| !line.trim_start().starts_with("```"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        contents.push('\n');
+        blocks.push((language, contents));
+    }
+    blocks
+}
+
+pub fn extension_for(language: &str) -> &str {
+    match language {
+        "rust" | "rs" => "rs",
+        "python" | "py" => "py",
+        "go" => "go",
+        "typescript" | "ts" => "ts",
+        "javascript" | "js" => "js",
+        "java" => "java",
+        "c" => "c",
+        "cpp" | "c++" => "cpp",
+        "toml" => "toml",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "sh" | "bash" | "shell" => "sh",
+        "sql" => "sql",
+        "markdown" | "md" => "md",
+        _ => "txt",
+    }
+}
+
+/// A file written by /save
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedArtifact {
+    pub file: String,
+    pub saved_at: DateTime<Utc>,
+    // Position of the saved message in the session transcript
+    pub message: usize,
+    // "response" for a whole message, otherwise the code block's language
+    pub kind: String,
+}
+
+/// The files saved from one session, kept beside them in its directory
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SavedResponses {
+    pub artifacts: Vec<SavedArtifact>,
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+impl SavedResponses {
+    /// The saves of a session under the responses directory
+    pub fn load(responses_dir: &Path, session_id: &str) -> SavedResponses {
+        let dir = responses_dir.join(session_id);
+        let mut saved: SavedResponses = fs::read_to_string(dir.join(SAVED_RESPONSES_INDEX))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        saved.dir = dir;
+        saved
+    }
+
+    // A path in the session directory that doesn't overwrite an earlier save
+    fn free_path(&self, stem: &str, extension: &str) -> PathBuf {
+        let mut path = self.dir.join(format!("{}.{}", stem, extension));
+        let mut n = 2;
+        while path.exists() {
+            path = self.dir.join(format!("{}-{}.{}", stem, n, extension));
+            n += 1;
+        }
+        path
+    }
+
+    /// Writes a whole message as Markdown, or only its code blocks, and records them in
+    /// the session's index; returns the files written
+    pub fn save(
+        &mut self,
+        stem: &str,
+        content: &str,
+        message: usize,
+        code_only: bool,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let files: Vec<(String, String)> = if code_only {
+            code_blocks(content)
+        } else {
+            vec![("response".to_string(), content.to_string())]
+        };
+        if files.is_empty() {
+            return Err("The message has no code blocks".into());
+        }
+        fs::create_dir_all(&self.dir)?;
+        let mut written = Vec::new();
+        for (kind, contents) in files {
+            let extension = if code_only {
+                extension_for(&kind)
+            } else {
+                "md"
+            };
+            let path = self.free_path(stem, extension);
+            fs::write(&path, contents)?;
+            self.artifacts.push(SavedArtifact {
+                file: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                saved_at: Utc::now(),
+                message,
+                kind,
+            });
+            written.push(path);
+        }
+        fs::write(
+            self.dir.join(SAVED_RESPONSES_INDEX),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_blocks_are_saved_beside_an_index() {
+        assert_eq!(
+            sanitize_stem("`Retry Backoff Logic.md`\n"),
+            Some("retry-backoff-logic".to_string())
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut saved = SavedResponses::load(dir.path(), "session-1");
+        let answer = "Try:\n```rust\nfn a() {}\n```\nand\n```sh\ncargo test\n```\n";
+        let written = saved.save("retry", answer, 3, true).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["retry.rs", "retry.sh"]);
+
+        saved.save("retry", answer, 3, false).unwrap();
+        let reloaded = SavedResponses::load(dir.path(), "session-1");
+        assert_eq!(reloaded.artifacts.len(), 3);
+        assert_eq!(reloaded.artifacts[2].file, "retry.md");
+    }
+
+    #[test]
+    fn generated_names_cannot_leave_the_session_directory() {
+        assert_eq!(
+            sanitize_stem("../../etc/passwd"),
+            Some("etc-passwd".to_string())
+        );
+        assert_eq!(
+            sanitize_stem("C:\\Users\\x.txt"),
+            Some("c-users-x".to_string())
+        );
+        assert_eq!(sanitize_stem("\"...\""), None);
+        assert_eq!(sanitize_stem(""), None);
+        // Cut to the limit without a dangling hyphen
+        let long = format!("{}-b", "a".repeat(MAX_STEM_CHARS - 1));
+        assert_eq!(sanitize_stem(&long), Some("a".repeat(MAX_STEM_CHARS - 1)));
+    }
+
+    #[test]
+    fn same_language_blocks_get_numbered_and_unclosed_fences_run_to_the_end() {
+        assert_eq!(
+            code_blocks("```Python title\nx = 1\n```\n```\nplain\n```\n```rust\nfn a() {}"),
+            vec![
+                ("python".to_string(), "x = 1\n".to_string()),
+                (String::new(), "plain\n".to_string()),
+                ("rust".to_string(), "fn a() {}\n".to_string()),
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut saved = SavedResponses::load(dir.path(), "s");
+        let answer = "```rs\nfn a() {}\n```\n```rust\nfn b() {}\n```\n";
+        let written = saved.save("fix", answer, 0, true).unwrap();
+        assert_eq!(written[1].file_name().unwrap(), "fix-2.rs");
+        assert_eq!(fs::read_to_string(&written[1]).unwrap(), "fn b() {}\n");
+        assert!(saved.save("fix", "No code here.", 0, true).is_err());
+    }
+}
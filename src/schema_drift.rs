@@ -0,0 +1,318 @@
+// src/schema_drift.rs
+//! Schema drift: fields and block types in Anthropic responses that this version doesn't
+//! know. They are parsed around silently, so each new one is logged locally, by name only
+//! and never with its content, and the user is told once that an upgrade may be due.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::token_usage::Provider;
+
+pub const SCHEMA_DRIFT_FILE: &str = "schema_drift.jsonl";
+
+const MESSAGE_KEYS: [&str; 9] = [
+    "id",
+    "type",
+    "role",
+    "model",
+    "content",
+    "stop_reason",
+    "stop_sequence",
+    "usage",
+    "container",
+];
+const USAGE_KEYS: [&str; 7] = [
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_input_tokens",
+    "cache_read_input_tokens",
+    "cache_creation",
+    "server_tool_use",
+    "service_tier",
+];
+const BLOCK_TYPES: [&str; 4] = ["text", "tool_use", "thinking", "redacted_thinking"];
+// "time_budget" is set locally when the time budget cuts a stream short
+const STOP_REASONS: [&str; 7] = [
+    "end_turn",
+    "max_tokens",
+    "stop_sequence",
+    "tool_use",
+    "pause_turn",
+    "refusal",
+    "time_budget",
+];
+const EVENT_TYPES: [&str; 8] = [
+    "message_start",
+    "content_block_start",
+    "content_block_delta",
+    "content_block_stop",
+    "message_delta",
+    "message_stop",
+    "ping",
+    "error",
+];
+const DELTA_TYPES: [&str; 4] = [
+    "text_delta",
+    "input_json_delta",
+    "thinking_delta",
+    "signature_delta",
+];
+
+// Names of an object's keys missing from `known`, under `prefix`
+fn unknown_keys(object: &Value, known: &[&str], prefix: &str) -> Vec<String> {
+    object
+        .as_object()
+        .map(|object| {
+            object
+                .keys()
+                .filter(|key| !known.contains(&key.as_str()))
+                .map(|key| format!("{}.{}", prefix, key))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// "kind:value" when a type field holds a value missing from `known`
+fn unknown_type(value: &Value, known: &[&str], kind: &str) -> Option<String> {
+    value
+        .as_str()
+        .filter(|value| !known.contains(value))
+        .map(|value| format!("{}:{}", kind, value))
+}
+
+/// What a Messages API response body has that this version doesn't know
+pub fn message_drift(body: &Value) -> Vec<String> {
+    if !body.is_object()
+        || Provider::detect(body) != Provider::Anthropic
+        || body["error"].is_object()
+    {
+        return Vec::new();
+    }
+    let mut unknown = unknown_keys(body, &MESSAGE_KEYS, "message");
+    unknown.extend(unknown_keys(&body["usage"], &USAGE_KEYS, "usage"));
+    if let Some(blocks) = body["content"].as_array() {
+        unknown.extend(
+            blocks
+                .iter()
+                .filter_map(|block| unknown_type(&block["type"], &BLOCK_TYPES, "block")),
+        );
+    }
+    unknown.extend(unknown_type(
+        &body["stop_reason"],
+        &STOP_REASONS,
+        "stop_reason",
+    ));
+    unknown
+}
+
+/// What one streamed event has that this version doesn't know
+pub fn event_drift(event: &Value) -> Vec<String> {
+    match event["type"].as_str() {
+        Some("message_start") => message_drift(&event["message"]),
+        Some("content_block_start") => {
+            unknown_type(&event["content_block"]["type"], &BLOCK_TYPES, "block")
+                .into_iter()
+                .collect()
+        }
+        Some("content_block_delta") => unknown_type(&event["delta"]["type"], &DELTA_TYPES, "delta")
+            .into_iter()
+            .collect(),
+        Some("message_delta") => {
+            let mut unknown = unknown_keys(&event["usage"], &USAGE_KEYS, "usage");
+            unknown.extend(unknown_type(
+                &event["delta"]["stop_reason"],
+                &STOP_REASONS,
+                "stop_reason",
+            ));
+            unknown
+        }
+        _ => unknown_type(&event["type"], &EVENT_TYPES, "event")
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+struct DriftEvent {
+    timestamp: DateTime<Utc>,
+    model: String,
+    unknown: Vec<String>,
+}
+
+/// Drift seen since startup; each unknown name is logged once per run
+#[derive(Debug, Default)]
+pub struct SchemaDriftLog {
+    path: Option<PathBuf>,
+    seen: BTreeSet<String>,
+    notice: Option<String>,
+    // Off with `schema_drift_checks` set to false in the config
+    pub enabled: bool,
+}
+
+impl SchemaDriftLog {
+    pub fn open(data_dir: &Path) -> Self {
+        SchemaDriftLog {
+            path: Some(data_dir.join(SCHEMA_DRIFT_FILE)),
+            enabled: true,
+            ..SchemaDriftLog::default()
+        }
+    }
+
+    /// Logs the names not seen before in this run and queues a notice about them
+    pub fn record(&mut self, model: &str, unknown: Vec<String>) {
+        if !self.enabled {
+            return;
+        }
+        let new: Vec<String> = unknown
+            .into_iter()
+            .filter(|name| self.seen.insert(name.clone()))
+            .collect();
+        if new.is_empty() {
+            return;
+        }
+        let names = new.join(", ");
+        let event = DriftEvent {
+            timestamp: Utc::now(),
+            model: model.to_string(),
+            unknown: new,
+        };
+        let written = match &self.path {
+            Some(path) => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| {
+                    writeln!(
+                        file,
+                        "{}",
+                        serde_json::to_string(&event).unwrap_or_default()
+                    )
+                }),
+            None => Ok(()),
+        };
+        // Shown in the status line; stderr would draw over the TUI
+        let logged = match written {
+            Ok(()) => format!("logged in {}", SCHEMA_DRIFT_FILE),
+            Err(e) => format!("failed to log it in {}: {}", SCHEMA_DRIFT_FILE, e),
+        };
+        self.notice = Some(format!(
+            "The API sent {} this version doesn't know; consider upgrading Sagacity ({})",
+            names, logged
+        ));
+    }
+
+    /// The notice about drift seen since the last call, if any
+    pub fn take_notice(&mut self) -> Option<String> {
+        self.notice.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_names_are_logged_once_without_content() {
+        let body = json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                {"type": "text", "text": "secret answer"},
+                {"type": "citations_block", "text": "secret quote"}
+            ],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 10, "output_tokens": 5, "priority_tokens": 3},
+            "trace": {"secret": true}
+        });
+        let unknown = message_drift(&body);
+        assert_eq!(
+            unknown,
+            vec![
+                "message.trace",
+                "usage.priority_tokens",
+                "block:citations_block"
+            ]
+        );
+        assert_eq!(
+            event_drift(&json!({"type": "content_block_delta", "delta": {"type": "audio_delta"}})),
+            vec!["delta:audio_delta"]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = SchemaDriftLog::open(dir.path());
+        log.record("claude", unknown.clone());
+        assert!(log.take_notice().unwrap().contains("usage.priority_tokens"));
+        log.record("claude", unknown);
+        assert_eq!(log.take_notice(), None);
+        let logged = std::fs::read_to_string(dir.path().join(SCHEMA_DRIFT_FILE)).unwrap();
+        assert_eq!(logged.lines().count(), 1);
+        assert!(!logged.contains("secret"));
+
+        // A log that can't be written is reported in the notice
+        let mut log = SchemaDriftLog::open(&dir.path().join("missing"));
+        log.record("claude", vec!["message.beta".to_string()]);
+        let notice = log.take_notice().unwrap();
+        assert!(
+            notice.contains("message.beta") && notice.contains("failed to log it in"),
+            "{}",
+            notice
+        );
+    }
+
+    #[test]
+    fn errors_other_providers_and_disabled_logs_record_nothing() {
+        // Error bodies and other providers' shapes aren't Messages API responses
+        assert!(
+            message_drift(&json!({"type": "error", "error": {"type": "overloaded_error"}}))
+                .is_empty()
+        );
+        assert!(message_drift(&json!({"choices": [], "usage": {"prompt_tokens": 1}})).is_empty());
+        assert!(message_drift(&json!("not an object")).is_empty());
+
+        assert_eq!(
+            event_drift(&json!({
+                "type": "message_delta",
+                "delta": {"stop_reason": "model_context_window_exceeded"},
+                "usage": {"output_tokens": 3}
+            })),
+            vec!["stop_reason:model_context_window_exceeded"]
+        );
+        assert_eq!(
+            event_drift(&json!({"type": "message_start", "message": {"id": "m", "beta": 1}})),
+            vec!["message.beta"]
+        );
+        assert_eq!(
+            event_drift(&json!({"type": "web_search"})),
+            vec!["event:web_search"]
+        );
+        assert!(event_drift(&json!({"type": "ping"})).is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = SchemaDriftLog::open(dir.path());
+        log.enabled = false;
+        log.record("claude", vec!["message.beta".to_string()]);
+        assert_eq!(log.take_notice(), None);
+        assert!(!dir.path().join(SCHEMA_DRIFT_FILE).exists());
+
+        // A new name alongside a known one logs only the new one
+        log.enabled = true;
+        log.record("claude", vec!["message.beta".to_string()]);
+        log.record(
+            "claude",
+            vec!["message.beta".to_string(), "usage.x".to_string()],
+        );
+        let logged = std::fs::read_to_string(dir.path().join(SCHEMA_DRIFT_FILE)).unwrap();
+        assert_eq!(logged.lines().count(), 2);
+        assert!(logged
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains("\"unknown\":[\"usage.x\"]"));
+    }
+}
@@ -0,0 +1,210 @@
+// src/search.rs
+use std::collections::HashMap;
+use std::fs;
+
+use crate::chatbot::Chatbot;
+use crate::privacy::PrivacyLevel;
+
+// Matching lines shown under each file
+const SNIPPET_LINES: usize = 2;
+const SNIPPET_WIDTH: usize = 120;
+// Query words shorter than this match too many lines to be useful
+const MIN_TERM_LEN: usize = 3;
+
+/// One file ranked by the retrieval stage, with the lines that best match the query
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub path: String,
+    pub score: f32,
+    // 1-based line numbers and their text
+    pub snippets: Vec<(usize, String)>,
+}
+
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|term| term.len() >= MIN_TERM_LEN)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Lines containing the most query terms, best first, ties in file order
+pub fn matched_snippets(content: &str, query: &str) -> Vec<(usize, String)> {
+    let terms = query_terms(query);
+    let mut matches: Vec<(usize, usize, &str)> = content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let lower = line.to_lowercase();
+            let count = terms.iter().filter(|term| lower.contains(*term)).count();
+            (count > 0).then_some((count, i + 1, line))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    matches
+        .into_iter()
+        .take(SNIPPET_LINES)
+        .map(|(_, number, line)| (number, line.trim().chars().take(SNIPPET_WIDTH).collect()))
+        .collect()
+}
+
+/// Every file scored by the query terms its path, keywords and summary share, best
+/// first; a match in the path or keywords counts double
+pub fn lexical_scores<'a>(
+    index: &HashMap<String, (String, String)>,
+    query: &str,
+    keywords: impl Fn(&str) -> Option<&'a Vec<String>>,
+) -> Vec<(usize, String)> {
+    let terms = query_terms(query);
+    let mut scored: Vec<(usize, String)> = index
+        .iter()
+        .map(|(file, (summary, _))| {
+            let path = file.to_lowercase();
+            let keywords = keywords(file)
+                .map(|keywords| keywords.join(" ").to_lowercase())
+                .unwrap_or_default();
+            let summary = summary.to_lowercase();
+            let score = terms
+                .iter()
+                .map(|term| {
+                    2 * usize::from(path.contains(term.as_str()))
+                        + 2 * usize::from(keywords.contains(term.as_str()))
+                        + usize::from(summary.contains(term.as_str()))
+                })
+                .sum();
+            (score, file.clone())
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored
+}
+
+/// The files with the best lexical scores, at most `limit` of them, best first.
+/// A limit of 0, or one the index fits in, keeps every file
+pub fn lexical_candidates<'a>(
+    index: &HashMap<String, (String, String)>,
+    query: &str,
+    limit: usize,
+    keywords: impl Fn(&str) -> Option<&'a Vec<String>>,
+) -> Vec<String> {
+    if limit == 0 || index.len() <= limit {
+        return index.keys().cloned().collect();
+    }
+    let mut scored = lexical_scores(index, query, keywords);
+    scored.truncate(limit);
+    scored.into_iter().map(|(_, file)| file).collect()
+}
+
+/// Runs only the retrieval stage of a chat turn; no answer is generated
+pub async fn search(
+    chatbot: &mut Chatbot,
+    query: &str,
+) -> Result<Vec<SearchHit>, Box<dyn std::error::Error>> {
+    let ranked = chatbot.retrieve(query).await?;
+    Ok(ranked
+        .into_iter()
+        .filter(|(path, _)| {
+            chatbot.index.contains_key(path)
+                && chatbot.privacy.level_for(path) != PrivacyLevel::Never
+        })
+        .map(|(path, score)| {
+            let snippets = fs::read_to_string(&path)
+                .map(|content| matched_snippets(&content, query))
+                .unwrap_or_default();
+            SearchHit {
+                path,
+                score,
+                snippets,
+            }
+        })
+        .collect())
+}
+
+/// One line per file with its score, matched lines indented beneath
+pub fn format_hits(hits: &[SearchHit]) -> String {
+    if hits.is_empty() {
+        return "No matching files".to_string();
+    }
+    let mut text = String::new();
+    for hit in hits {
+        text.push_str(&format!("{:.2}  {}\n", hit.score, hit.path));
+        for (number, line) in &hit.snippets {
+            text.push_str(&format!("      {:>5}: {}\n", number, line));
+        }
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexical_candidates_keep_the_best_matching_files() {
+        let index: HashMap<String, (String, String)> = [
+            ("src/auth/token.rs", "Refreshes expired access tokens"),
+            ("src/db.rs", "Connection pool for the database"),
+            ("src/ui.rs", "Draws the settings screen"),
+        ]
+        .into_iter()
+        .map(|(file, summary)| (file.to_string(), (summary.to_string(), "Rust".to_string())))
+        .collect();
+        let keywords = vec!["pool".to_string()];
+        let candidates = lexical_candidates(&index, "where is the token refresh?", 2, |file| {
+            (file == "src/db.rs").then_some(&keywords)
+        });
+        assert_eq!(candidates, vec!["src/auth/token.rs", "src/db.rs"]);
+        assert_eq!(lexical_candidates(&index, "anything", 0, |_| None).len(), 3);
+    }
+
+    #[test]
+    fn path_and_keyword_matches_outweigh_summaries_and_ties_go_by_path() {
+        let index: HashMap<String, (String, String)> = [
+            ("b/cache.rs", "Stores entries"),
+            ("a/cache.rs", "Stores entries"),
+            ("store.rs", "A cache of parsed files in memory"),
+        ]
+        .into_iter()
+        .map(|(file, summary)| (file.to_string(), (summary.to_string(), "Rust".to_string())))
+        .collect();
+        // "is" and "of" are too short to count; case doesn't matter
+        let scores = lexical_scores(&index, "Is the CACHE of it here?", |_| None);
+        assert_eq!(
+            scores,
+            vec![
+                (2, "a/cache.rs".to_string()),
+                (2, "b/cache.rs".to_string()),
+                (1, "store.rs".to_string()),
+            ]
+        );
+        // An index that fits the limit is kept whole
+        assert_eq!(lexical_candidates(&index, "cache", 3, |_| None).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn only_the_lexical_candidates_are_sent_for_relevance_scoring() {
+        let client = std::sync::Arc::new(crate::claude_client::MockClient::default());
+        let index: HashMap<String, (String, String)> = [
+            ("./src/token.rs", "Refreshes access tokens"),
+            ("./src/db.rs", "Connection pool"),
+            ("./src/ui.rs", "Draws screens"),
+        ]
+        .into_iter()
+        .map(|(file, summary)| (file.to_string(), (summary.to_string(), "Rust".to_string())))
+        .collect();
+        let mut chatbot = Chatbot::new(
+            index.clone(),
+            HashMap::new(),
+            "key".to_string(),
+            client.clone(),
+        );
+        chatbot.relevance_candidates = 1;
+        let _ = crate::chatbot::search_index(&index, "token refresh", "key", &mut chatbot).await;
+
+        let requests = client.requests.lock().unwrap();
+        let prompt = requests[0]["messages"][0]["content"].as_str().unwrap();
+        assert!(prompt.contains("Summary for ./src/token.rs"));
+        assert!(!prompt.contains("./src/db.rs") && !prompt.contains("./src/ui.rs"));
+    }
+}
@@ -0,0 +1,370 @@
+// src/session.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::chatbot;
+use crate::refactor_plan::RefactorPlan;
+use crate::ui::chat::{Message, Sender};
+
+pub const SESSIONS_DIR: &str = "sessions";
+const TITLE_MAX_CHARS: usize = 48;
+
+/// A bookmarked transcript message with an optional short annotation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub message_index: usize,
+    pub annotation: String,
+}
+
+/// Everything that survives between runs of the TUI
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    pub last_activity: DateTime<Utc>,
+    #[serde(default)]
+    pub cost: f64,
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    // The /plan-refactor checklist, with completed steps ticked off
+    #[serde(default)]
+    pub refactor_plan: Option<RefactorPlan>,
+    // Modification times of the indexed files when the session was last saved, for the
+    // "what changed since" briefing
+    #[serde(default)]
+    pub index_snapshot: HashMap<String, u64>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session {
+            id: Utc::now().format("%Y%m%d-%H%M%S%3f").to_string(),
+            title: String::new(),
+            last_activity: Utc::now(),
+            cost: 0.0,
+            messages: Vec::new(),
+            bookmarks: Vec::new(),
+            refactor_plan: None,
+            index_snapshot: HashMap::new(),
+        }
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+/// Derives a session title from the first question asked in it
+pub fn title_from_messages(messages: &[Message]) -> String {
+    match messages.iter().find(|m| m.sender == Sender::User) {
+        Some(message) => {
+            let first_line = message.content.lines().next().unwrap_or("").trim();
+            if first_line.chars().count() > TITLE_MAX_CHARS {
+                let truncated: String = first_line.chars().take(TITLE_MAX_CHARS).collect();
+                format!("{}…", truncated)
+            } else {
+                first_line.to_string()
+            }
+        }
+        None => "Untitled session".to_string(),
+    }
+}
+
+/// Rebuilds the model's memory from a transcript: each question with the answer that
+/// followed it, leaving out failed turns and the repository map
+pub fn model_memory(messages: &[Message]) -> Vec<chatbot::Message> {
+    let answered = |message: &Message| {
+        message.sender == Sender::AI && message.error.is_none() && !message.repo_map
+    };
+    let memory_message = |role: &str, message: &Message| chatbot::Message {
+        role: role.to_string(),
+        content: message.content.clone(),
+        timestamp: message.timestamp.unwrap_or_else(Utc::now),
+    };
+    messages
+        .windows(2)
+        .filter(|pair| pair[0].sender == Sender::User && answered(&pair[1]))
+        .flat_map(|pair| {
+            [
+                memory_message("user", &pair[0]),
+                memory_message("assistant", &pair[1]),
+            ]
+        })
+        .collect()
+}
+
+fn sessions_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(SESSIONS_DIR)
+}
+
+fn session_path(data_dir: &Path, id: &str) -> PathBuf {
+    sessions_dir(data_dir).join(format!("{}.json", id))
+}
+
+pub fn load_session(data_dir: &Path, id: &str) -> Option<Session> {
+    let contents = fs::read_to_string(session_path(data_dir, id)).ok()?;
+    serde_json::from_str::<Session>(&contents).ok()
+}
+
+pub fn save_session(data_dir: &Path, session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(sessions_dir(data_dir))?;
+    let serialized = serde_json::to_string_pretty(session)?;
+    fs::write(session_path(data_dir, &session.id), serialized)?;
+    Ok(())
+}
+
+pub fn delete_session(data_dir: &Path, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fs::remove_file(session_path(data_dir, id))?;
+    Ok(())
+}
+
+/// Lists saved sessions, most recently active first
+pub fn list_sessions(data_dir: &Path) -> Vec<Session> {
+    let mut sessions: Vec<Session> = fs::read_dir(sessions_dir(data_dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+                .filter_map(|contents| serde_json::from_str::<Session>(&contents).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last_activity));
+    sessions
+}
+
+/// Merges two sessions into a new one: exchanges are interleaved by timestamp, identical
+/// exchanges kept once, and every message is tagged with the session it came from
+pub fn merge_sessions(first: &Session, second: &Session) -> Session {
+    // A question and the answers that follow it move together
+    struct Exchange {
+        at: DateTime<Utc>,
+        messages: Vec<Message>,
+        annotations: Vec<(usize, String)>,
+    }
+
+    let exchanges = |session: &Session| -> Vec<Exchange> {
+        let origin = title_or_untitled(session);
+        let mut exchanges: Vec<Exchange> = Vec::new();
+        // Messages saved before timestamps existed inherit the previous one's
+        let mut at = DateTime::<Utc>::MIN_UTC;
+        for (i, message) in session.messages.iter().enumerate() {
+            at = message.timestamp.unwrap_or(at);
+            let mut message = message.clone();
+            message.origin.get_or_insert_with(|| origin.clone());
+            if message.sender == Sender::User || exchanges.is_empty() {
+                exchanges.push(Exchange {
+                    at,
+                    messages: Vec::new(),
+                    annotations: Vec::new(),
+                });
+            }
+            let exchange = exchanges.last_mut().expect("an exchange was just pushed");
+            if let Some(bookmark) = session.bookmarks.iter().find(|b| b.message_index == i) {
+                exchange
+                    .annotations
+                    .push((exchange.messages.len(), bookmark.annotation.clone()));
+            }
+            exchange.messages.push(message);
+        }
+        exchanges
+    };
+
+    let mut all = exchanges(first);
+    all.extend(exchanges(second));
+    // Stable, so ties keep the first session's exchanges ahead
+    all.sort_by_key(|exchange| exchange.at);
+
+    let mut merged = Session::new();
+    let mut seen: Vec<Vec<(Sender, String)>> = Vec::new();
+    for exchange in all {
+        let key: Vec<(Sender, String)> = exchange
+            .messages
+            .iter()
+            .map(|m| (m.sender.clone(), m.content.clone()))
+            .collect();
+        let offset = match seen.iter().position(|k| *k == key) {
+            // A duplicate only contributes bookmarks the kept copy doesn't have
+            Some(found) => merged_offset(&seen, found),
+            None => {
+                seen.push(key);
+                let offset = merged.messages.len();
+                merged.messages.extend(exchange.messages);
+                offset
+            }
+        };
+        for (index, annotation) in exchange.annotations {
+            let message_index = offset + index;
+            match merged
+                .bookmarks
+                .iter_mut()
+                .find(|b| b.message_index == message_index)
+            {
+                Some(existing) if existing.annotation == annotation => {}
+                Some(existing) if existing.annotation.is_empty() => {
+                    existing.annotation = annotation
+                }
+                Some(existing) if !annotation.is_empty() => {
+                    existing.annotation = format!("{} / {}", existing.annotation, annotation)
+                }
+                Some(_) => {}
+                None => merged.bookmarks.push(Bookmark {
+                    message_index,
+                    annotation,
+                }),
+            }
+        }
+    }
+
+    // Duplicates add bookmarks behind later exchanges', so order them by position
+    merged.bookmarks.sort_by_key(|b| b.message_index);
+    merged.title = title_from_messages(&merged.messages);
+    merged.cost = first.cost + second.cost;
+    merged
+}
+
+// Index of the first message of the `n`th kept exchange
+fn merged_offset(seen: &[Vec<(Sender, String)>], n: usize) -> usize {
+    seen[..n].iter().map(Vec::len).sum()
+}
+
+fn title_or_untitled(session: &Session) -> String {
+    if session.title.is_empty() {
+        title_from_messages(&session.messages)
+    } else {
+        session.title.clone()
+    }
+}
+
+pub fn load_most_recent_session(data_dir: &Path) -> Option<Session> {
+    list_sessions(data_dir).into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // A session of question-and-answer exchanges, each asked at the given second
+    fn session(
+        title: &str,
+        exchanges: &[(i64, &str, &str)],
+        bookmarks: &[(usize, &str)],
+    ) -> Session {
+        let mut session = Session::new();
+        session.title = title.to_string();
+        session.cost = 0.5;
+        for &(second, question, answer) in exchanges {
+            let at = Utc.timestamp_opt(second, 0).unwrap();
+            for mut message in [Message::user(question), Message::ai(answer)] {
+                message.timestamp = Some(at);
+                session.messages.push(message);
+            }
+        }
+        session.bookmarks = bookmarks
+            .iter()
+            .map(|&(message_index, annotation)| Bookmark {
+                message_index,
+                annotation: annotation.to_string(),
+            })
+            .collect();
+        session
+    }
+
+    fn contents(session: &Session) -> Vec<&str> {
+        session
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn merged_exchanges_interleave_by_time_with_answers_kept_after_their_questions() {
+        let first = session("Alpha", &[(10, "q1", "a1"), (30, "q3", "a3")], &[]);
+        let second = session("Beta", &[(20, "q2", "a2"), (40, "q4", "a4")], &[]);
+        let merged = merge_sessions(&first, &second);
+        assert_eq!(
+            contents(&merged),
+            vec!["q1", "a1", "q2", "a2", "q3", "a3", "q4", "a4"]
+        );
+        assert_eq!(merged.title, "q1");
+        assert_eq!(merged.cost, 1.0);
+    }
+
+    #[test]
+    fn identical_exchanges_are_kept_once() {
+        let first = session("Alpha", &[(10, "shared", "same"), (30, "q1", "a1")], &[]);
+        let second = session(
+            "Beta",
+            &[(40, "shared", "same"), (50, "shared", "different")],
+            &[],
+        );
+        let merged = merge_sessions(&first, &second);
+        assert_eq!(
+            contents(&merged),
+            vec!["shared", "same", "q1", "a1", "shared", "different"]
+        );
+    }
+
+    #[test]
+    fn every_merged_message_names_the_session_it_came_from() {
+        let first = session("Alpha", &[(10, "q1", "a1")], &[]);
+        let mut second = session("", &[(20, "q2", "a2")], &[]);
+        // Already merged once, so its provenance is kept rather than overwritten
+        second.messages[1].origin = Some("Gamma".to_string());
+        let merged = merge_sessions(&first, &second);
+        let origins: Vec<Option<&str>> = merged
+            .messages
+            .iter()
+            .map(|m| m.origin.as_deref())
+            .collect();
+        assert_eq!(
+            origins,
+            vec![Some("Alpha"), Some("Alpha"), Some("q2"), Some("Gamma")]
+        );
+    }
+
+    #[test]
+    fn merged_bookmarks_follow_their_messages_in_transcript_order() {
+        let first = session(
+            "Alpha",
+            &[(5, "shared", "same"), (30, "q1", "a1")],
+            &[(2, "alpha question")],
+        );
+        let second = session(
+            "Beta",
+            &[(20, "q2", "a2"), (40, "shared", "same")],
+            &[(3, "keep"), (1, "")],
+        );
+        let merged = merge_sessions(&first, &second);
+        assert_eq!(
+            contents(&merged),
+            vec!["shared", "same", "q2", "a2", "q1", "a1"]
+        );
+        assert_eq!(
+            merged.bookmarks,
+            vec![
+                Bookmark {
+                    message_index: 1,
+                    annotation: "keep".to_string()
+                },
+                Bookmark {
+                    message_index: 3,
+                    annotation: String::new()
+                },
+                Bookmark {
+                    message_index: 4,
+                    annotation: "alpha question".to_string()
+                },
+            ]
+        );
+    }
+}
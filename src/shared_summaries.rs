@@ -0,0 +1,63 @@
+// src/shared_summaries.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// In the data directory, so every project indexed on this machine shares it
+pub const SHARED_SUMMARIES_FILE: &str = "shared_summaries.json";
+
+/// A summary written for some file's exact contents
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SharedSummary {
+    pub summary: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+/// Summaries keyed by content hash, so vendored or copied files are only paid for once
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SharedSummaries {
+    summaries: HashMap<String, SharedSummary>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// FNV-1a over the language and contents; the language is part of the summary prompt
+pub fn content_hash(language: &str, content: &str) -> String {
+    let hash = language
+        .bytes()
+        .chain([0])
+        .chain(content.bytes())
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}-{}", hash, content.len())
+}
+
+impl SharedSummaries {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SHARED_SUMMARIES_FILE);
+        let mut store: SharedSummaries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        store.path = Some(path);
+        store
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string(self)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&SharedSummary> {
+        self.summaries.get(hash)
+    }
+
+    pub fn insert(&mut self, hash: String, summary: SharedSummary) {
+        self.summaries.insert(hash, summary);
+    }
+}
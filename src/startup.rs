@@ -0,0 +1,202 @@
+// src/startup.rs
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::{config_path, save_config, Config};
+use crate::constants::DEFAULT_MODEL;
+use crate::models::{find_model, model_ids};
+use crate::org_policy::load_org_policy;
+
+/// What went wrong before the app could start normally
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupErrorKind {
+    MissingConfig,
+    InvalidConfig,
+    DataDirUnavailable,
+    UnknownModel,
+    OrgPolicyUnavailable,
+    ModelNotAllowed,
+}
+
+/// Actions offered on the startup error screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Remediation {
+    CreateConfig,
+    ChangeDataDir,
+    UseDefaultModel,
+    Retry,
+    Quit,
+}
+
+impl Remediation {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Remediation::CreateConfig => "Create a default config",
+            Remediation::ChangeDataDir => "Change the data directory",
+            Remediation::UseDefaultModel => "Use the default model",
+            Remediation::Retry => "Retry",
+            Remediation::Quit => "Quit",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StartupError {
+    pub kind: StartupErrorKind,
+    pub detail: String,
+}
+
+impl StartupError {
+    pub fn title(&self) -> &'static str {
+        match self.kind {
+            StartupErrorKind::MissingConfig => "No configuration found",
+            StartupErrorKind::InvalidConfig => "Configuration could not be read",
+            StartupErrorKind::DataDirUnavailable => "Data directory is not usable",
+            StartupErrorKind::UnknownModel => "Configured model is not supported",
+            StartupErrorKind::OrgPolicyUnavailable => "Org policy could not be loaded",
+            StartupErrorKind::ModelNotAllowed => {
+                "Configured model is not allowed by the org policy"
+            }
+        }
+    }
+
+    pub fn remediations(&self) -> Vec<Remediation> {
+        match self.kind {
+            StartupErrorKind::MissingConfig | StartupErrorKind::InvalidConfig => vec![
+                Remediation::CreateConfig,
+                Remediation::Retry,
+                Remediation::Quit,
+            ],
+            StartupErrorKind::DataDirUnavailable => vec![
+                Remediation::ChangeDataDir,
+                Remediation::Retry,
+                Remediation::Quit,
+            ],
+            StartupErrorKind::OrgPolicyUnavailable => vec![Remediation::Retry, Remediation::Quit],
+            StartupErrorKind::UnknownModel | StartupErrorKind::ModelNotAllowed => vec![
+                Remediation::UseDefaultModel,
+                Remediation::Retry,
+                Remediation::Quit,
+            ],
+        }
+    }
+}
+
+// The config at `path`, or None when there is no file there yet
+fn read_config(path: &Path) -> Result<Option<Config>, StartupError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(StartupError {
+                kind: StartupErrorKind::InvalidConfig,
+                detail: format!("Could not read {}: {}", path.display(), e),
+            })
+        }
+    };
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| StartupError {
+            kind: StartupErrorKind::InvalidConfig,
+            detail: format!("{} is not valid: {}", path.display(), e),
+        })
+}
+
+// A first launch writes the default config instead of stopping on the error screen
+fn load_config() -> Result<Config, StartupError> {
+    let path = config_path();
+    if let Some(config) = read_config(&path)? {
+        return Ok(config);
+    }
+    let config = Config::default();
+    save_config(&config).map_err(|e| StartupError {
+        kind: StartupErrorKind::MissingConfig,
+        detail: format!("Could not create {}: {}", path.display(), e),
+    })?;
+    Ok(config)
+}
+
+fn check_data_dir(config: &Config) -> Result<(), StartupError> {
+    let data_path = config.data_path();
+    let probe = data_path.join(".write_test");
+    fs::create_dir_all(&data_path)
+        .and_then(|_| fs::write(&probe, b"ok"))
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|e| StartupError {
+            kind: StartupErrorKind::DataDirUnavailable,
+            detail: format!("Cannot write to {}: {}", data_path.display(), e),
+        })
+}
+
+/// Runs every check needed before the main menu can be shown
+pub fn run_startup_checks() -> Result<Config, StartupError> {
+    let mut config = load_config()?;
+    config.org_policy = load_org_policy().map_err(|detail| StartupError {
+        kind: StartupErrorKind::OrgPolicyUnavailable,
+        detail,
+    })?;
+    check_data_dir(&config)?;
+    check_model(&config)?;
+    Ok(config)
+}
+
+fn check_model(config: &Config) -> Result<(), StartupError> {
+    match find_model(&config.model) {
+        Some(_) => match &config.org_policy {
+            Some(policy) if !policy.allows_model(&config.model) => Err(StartupError {
+                kind: StartupErrorKind::ModelNotAllowed,
+                detail: format!(
+                    "{} doesn't allow \"{}\". Allowed models: {}",
+                    policy.source,
+                    config.model,
+                    policy.allowed_models.join(", ")
+                ),
+            }),
+            _ => Ok(()),
+        },
+        None => Err(StartupError {
+            kind: StartupErrorKind::UnknownModel,
+            detail: format!(
+                "\"{}\" is not in the model catalogue. Supported models: {}",
+                config.model,
+                model_ids().join(", ")
+            ),
+        }),
+    }
+}
+
+/// Replaces an unsupported model in the config file with the default one, or with the
+/// first model the org policy allows
+pub fn reset_model() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = load_config().map_err(|e| e.detail)?;
+    let policy = load_org_policy()?.unwrap_or_default();
+    config.model = match policy.allowed_models.first() {
+        Some(model) if !policy.allows_model(DEFAULT_MODEL) => model.clone(),
+        _ => DEFAULT_MODEL.to_string(),
+    };
+    save_config(&config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_missing_config_falls_back_to_the_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        assert!(read_config(&path).unwrap().is_none());
+
+        fs::write(&path, "{ not json").unwrap();
+        let error = read_config(&path).unwrap_err();
+        assert_eq!(error.kind, StartupErrorKind::InvalidConfig);
+
+        fs::write(&path, serde_json::to_string(&Config::default()).unwrap()).unwrap();
+        assert!(read_config(&path).unwrap().is_some());
+
+        // A directory where the file should be can't be read as one
+        let error = read_config(dir.path()).unwrap_err();
+        assert_eq!(error.kind, StartupErrorKind::InvalidConfig);
+    }
+}
@@ -0,0 +1,72 @@
+// src/structured.rs
+use serde::{Deserialize, Serialize};
+
+// Appended to the system prompt while structured answers are on
+pub const STRUCTURED_INSTRUCTIONS: &str = "Reply with only a JSON object, no prose or code fence around it, of the form {\"answer\": \"<markdown answer>\", \"confidence\": <0.0 to 1.0>, \"sources\": [{\"path\": \"<file path>\", \"start_line\": <n>, \"end_line\": <n>}]}. List every file and line range the answer relies on; leave sources empty if it relies on none.";
+
+/// A file and one-based, inclusive line range an answer relies on
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SourceRef {
+    pub path: String,
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+}
+
+impl SourceRef {
+    // `path:12-20` so the file viewer can follow the citation
+    pub fn label(&self) -> String {
+        match (self.start_line, self.end_line) {
+            (Some(start), Some(end)) if end > start => format!("{}:{}-{}", self.path, start, end),
+            (Some(start), _) => format!("{}:{}", self.path, start),
+            _ => self.path.clone(),
+        }
+    }
+}
+
+/// An answer returned as JSON while structured answers are on
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StructuredAnswer {
+    pub answer: String,
+    #[serde(default)]
+    pub confidence: Option<f64>,
+    #[serde(default)]
+    pub sources: Vec<SourceRef>,
+}
+
+impl StructuredAnswer {
+    /// Parses a reply, tolerating a code fence or stray text around the object
+    pub fn parse(reply: &str) -> Option<StructuredAnswer> {
+        let start = reply.find('{')?;
+        let end = reply.rfind('}')?;
+        if end < start {
+            return None;
+        }
+        serde_json::from_str(&reply[start..=end]).ok()
+    }
+
+    /// The answer as it is shown in the transcript
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = self.answer.trim().to_string();
+        if let Some(confidence) = self.confidence {
+            let level = match confidence {
+                c if c >= 0.75 => "high",
+                c if c >= 0.4 => "medium",
+                _ => "low",
+            };
+            markdown.push_str(&format!(
+                "\n\n**Confidence:** {} ({:.0}%)",
+                level,
+                confidence.clamp(0.0, 1.0) * 100.0
+            ));
+        }
+        if !self.sources.is_empty() {
+            markdown.push_str("\n\n**Sources**\n");
+            for source in &self.sources {
+                markdown.push_str(&format!("- `{}`\n", source.label()));
+            }
+        }
+        markdown
+    }
+}
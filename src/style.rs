@@ -0,0 +1,118 @@
+// src/style.rs
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::transcript_log::project_file_name;
+
+pub const STYLES_DIR: &str = "styles";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Concise,
+    Detailed,
+}
+
+/// How answers should read, added to every request's system prompt
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AnswerStyle {
+    // None leaves the length to the model
+    #[serde(default)]
+    pub verbosity: Option<Verbosity>,
+    #[serde(default)]
+    pub always_code: bool,
+    // Natural language to answer in, such as "German"
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl AnswerStyle {
+    /// The project's saved style, or `default` (from the config) until one is saved
+    pub fn load(data_dir: &Path, project: &str, default: &AnswerStyle) -> Self {
+        let path = data_dir
+            .join(STYLES_DIR)
+            .join(format!("{}.json", project_file_name(project)));
+        let mut style: AnswerStyle = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_else(|| default.clone());
+        style.path = Some(path);
+        style
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps where the style is saved while taking the preferences of `other`
+    pub fn replace_with(&mut self, other: &AnswerStyle) {
+        let path = self.path.take();
+        *self = other.clone();
+        self.path = path;
+    }
+
+    /// Short description for the status bar, like "concise, code, in German"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        match self.verbosity {
+            Some(Verbosity::Concise) => parts.push("concise".to_string()),
+            Some(Verbosity::Detailed) => parts.push("detailed".to_string()),
+            None => {}
+        }
+        if self.always_code {
+            parts.push("code".to_string());
+        }
+        if let Some(language) = &self.language {
+            parts.push(format!("in {}", language));
+        }
+        if parts.is_empty() {
+            "default".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// The system prompt with the answer preferences appended
+    pub fn system_prompt(&self, base: String) -> String {
+        let mut instructions: Vec<String> = Vec::new();
+        match self.verbosity {
+            Some(Verbosity::Concise) => instructions.push(
+                "Keep answers brief: lead with the answer and leave out background the user didn't ask for."
+                    .to_string(),
+            ),
+            Some(Verbosity::Detailed) => instructions.push(
+                "Give thorough answers: explain the reasoning, the relevant code paths and any caveats."
+                    .to_string(),
+            ),
+            None => {}
+        }
+        if self.always_code {
+            instructions.push(
+                "Always include a code example or the relevant code in the answer.".to_string(),
+            );
+        }
+        if let Some(language) = &self.language {
+            instructions.push(format!(
+                "Respond in {}, keeping code and identifiers as they are.",
+                language
+            ));
+        }
+        if instructions.is_empty() {
+            return base;
+        }
+        format!(
+            "{}\n\nThe user's answer preferences:\n- {}",
+            base,
+            instructions.join("\n- ")
+        )
+    }
+}
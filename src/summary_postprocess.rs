@@ -0,0 +1,167 @@
+// src/summary_postprocess.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Phrases models like to open summaries with that carry no information
+const BOILERPLATE_PREFIXES: [&str; 8] = [
+    "the provided code ",
+    "the provided file ",
+    "this code file ",
+    "this code ",
+    "this file ",
+    "the code ",
+    "this module ",
+    "this script ",
+];
+const BOILERPLATE_SENTENCES: [&str; 3] = ["in summary", "overall,", "in conclusion"];
+const MAX_KEYWORDS: usize = 8;
+
+/// A summary after post-processing, with keywords kept apart from the prose
+#[derive(Debug, Clone, Default)]
+pub struct ProcessedSummary {
+    pub text: String,
+    pub keywords: Vec<String>,
+}
+
+/// One step of the summary post-processing pipeline
+pub trait SummaryPostProcessor {
+    fn process(&self, summary: &mut ProcessedSummary);
+}
+
+pub struct StripBoilerplate;
+
+impl SummaryPostProcessor for StripBoilerplate {
+    fn process(&self, summary: &mut ProcessedSummary) {
+        let kept: Vec<String> = summary
+            .text
+            .split_inclusive(". ")
+            .filter(|sentence| {
+                let lower = sentence.trim_start().to_lowercase();
+                !BOILERPLATE_SENTENCES.iter().any(|p| lower.starts_with(p))
+            })
+            .map(|sentence| {
+                let lower = sentence.to_lowercase();
+                match BOILERPLATE_PREFIXES.iter().find(|p| lower.starts_with(*p)) {
+                    Some(prefix) => sentence
+                        .get(prefix.len()..)
+                        .map(capitalize)
+                        .unwrap_or_else(|| sentence.to_string()),
+                    None => sentence.to_string(),
+                }
+            })
+            .collect();
+        if !kept.is_empty() {
+            summary.text = kept.concat().trim().to_string();
+        }
+    }
+}
+
+pub struct MaxLength(pub usize);
+
+impl SummaryPostProcessor for MaxLength {
+    fn process(&self, summary: &mut ProcessedSummary) {
+        if summary.text.chars().count() <= self.0 {
+            return;
+        }
+        let truncated: String = summary.text.chars().take(self.0).collect();
+        // Prefer ending on a full sentence when one fits
+        summary.text = match truncated.rfind(". ") {
+            Some(end) if end > self.0 / 2 => truncated[..=end].to_string(),
+            _ => format!("{}…", truncated.trim_end()),
+        };
+    }
+}
+
+pub struct ExtractKeywords;
+
+impl SummaryPostProcessor for ExtractKeywords {
+    fn process(&self, summary: &mut ProcessedSummary) {
+        for word in summary
+            .text
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+        {
+            let looks_like_identifier = word.contains('_')
+                || word.chars().skip(1).any(|c| c.is_uppercase())
+                || summary.text.contains(&format!("`{}`", word));
+            if word.len() > 2
+                && looks_like_identifier
+                && !summary.keywords.iter().any(|k| k == word)
+                && summary.keywords.len() < MAX_KEYWORDS
+            {
+                summary.keywords.push(word.to_string());
+            }
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Which post-processors run for each language; "*" applies to languages without an entry
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostProcessConfig {
+    pub pipelines: HashMap<String, Vec<String>>,
+    pub max_chars: usize,
+}
+
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        let mut pipelines = HashMap::new();
+        pipelines.insert(
+            "*".to_string(),
+            vec![
+                "strip_boilerplate".to_string(),
+                "keywords".to_string(),
+                "max_length".to_string(),
+            ],
+        );
+        PostProcessConfig {
+            pipelines,
+            max_chars: 400,
+        }
+    }
+}
+
+// Function to build the configured pipeline for a language, skipping unknown names
+pub fn pipeline_for(
+    language: &str,
+    config: &PostProcessConfig,
+) -> Vec<Box<dyn SummaryPostProcessor>> {
+    let names = config
+        .pipelines
+        .get(language)
+        .or_else(|| config.pipelines.get("*"))
+        .cloned()
+        .unwrap_or_default();
+    names
+        .iter()
+        .filter_map(|name| -> Option<Box<dyn SummaryPostProcessor>> {
+            match name.as_str() {
+                "strip_boilerplate" => Some(Box::new(StripBoilerplate)),
+                "max_length" => Some(Box::new(MaxLength(config.max_chars))),
+                "keywords" => Some(Box::new(ExtractKeywords)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+pub fn postprocess_summary(
+    summary: &str,
+    language: &str,
+    config: &PostProcessConfig,
+) -> ProcessedSummary {
+    let mut processed = ProcessedSummary {
+        text: summary.trim().to_string(),
+        keywords: Vec::new(),
+    };
+    for processor in pipeline_for(language, config) {
+        processor.process(&mut processed);
+    }
+    processed
+}
@@ -0,0 +1,301 @@
+// src/sweep.rs
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::chatbot::{complete_with_claude, detect_language, Chatbot};
+use crate::constants::*;
+use crate::gen_tests::strip_code_fence;
+use crate::refactor_plan::PendingEdit;
+use crate::sampling::Operation;
+
+// Each matching file costs a request, so very broad patterns are cut off
+pub const MAX_SWEEP_FILES: usize = 40;
+// Written next to each target, then renamed over it once every file is written
+const TEMP_SUFFIX: &str = ".sagacity-sweep";
+// Reply the model gives for files the change doesn't apply to
+const NO_CHANGE: &str = "NO CHANGE";
+
+/// One file's proposed patch in a /sweep, with the contents it was generated from
+#[derive(Debug, Clone)]
+pub struct SweepPatch {
+    pub edit: PendingEdit,
+    pub original: String,
+    pub selected: bool,
+}
+
+/// A mechanical change across the repo: the grep patterns and a patch per matching file
+#[derive(Debug, Clone, Default)]
+pub struct Sweep {
+    pub description: String,
+    pub patterns: Vec<String>,
+    pub patches: Vec<SweepPatch>,
+    // Matching files left out: private ones and those past MAX_SWEEP_FILES
+    pub skipped: Vec<String>,
+}
+
+impl Sweep {
+    pub fn selected_count(&self) -> usize {
+        self.patches.iter().filter(|patch| patch.selected).count()
+    }
+}
+
+// Function to ask the model which literal strings identify the code the change touches
+async fn grep_patterns(
+    description: &str,
+    chatbot: &mut Chatbot,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let prompt = format!(
+        "A developer wants to make this mechanical change across a codebase: {}\n\n\
+         Reply with only a JSON object {{\"patterns\": [\"...\"]}} listing the literal strings \
+         (identifiers, paths, messages) that every file needing the change must contain. \
+         Prefer one or two specific identifiers; never regular expressions.",
+        description
+    );
+    let api_key = chatbot.api_key.clone();
+    let response = complete_with_claude(
+        &prompt,
+        DEFAULT_MODEL,
+        &api_key,
+        "sweep_patterns",
+        Operation::Refactor,
+        chatbot,
+    )
+    .await?;
+    let start = response
+        .find('{')
+        .ok_or("No JSON object in the patterns response")?;
+    let end = response
+        .rfind('}')
+        .ok_or("No JSON object in the patterns response")?;
+    let json: Value = serde_json::from_str(&response[start..=end])?;
+    let patterns: Vec<String> = json["patterns"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(str::trim)
+                .filter(|pattern| pattern.len() > 1)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    if patterns.is_empty() {
+        return Err("Couldn't tell what to search for; name the identifier to change".into());
+    }
+    Ok(patterns)
+}
+
+/// Files under `root` containing any of the patterns: `git grep` where the project is a
+/// repository, otherwise the indexed files
+pub fn grep_files(root: &Path, patterns: &[String], chatbot: &Chatbot) -> Vec<String> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(root).args(["grep", "-l", "-I", "-F"]);
+    for pattern in patterns {
+        command.arg("-e").arg(pattern);
+    }
+    let mut files: Vec<String> = match command.output() {
+        // git grep exits with 1 when nothing matches
+        Ok(output) if output.status.success() || output.status.code() == Some(1) => {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| root.join(line).display().to_string())
+                .collect()
+        }
+        _ => chatbot
+            .index
+            .keys()
+            .filter(|path| {
+                fs::read_to_string(path)
+                    .is_ok_and(|contents| patterns.iter().any(|pattern| contents.contains(pattern)))
+            })
+            .cloned()
+            .collect(),
+    };
+    files.sort();
+    files
+}
+
+// Function to ask for one file's patch; None when the change doesn't apply to it
+async fn patch_file(
+    description: &str,
+    path: &str,
+    existing: &str,
+    chatbot: &mut Chatbot,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let prompt = format!(
+        "Apply this mechanical change to the file below: {}\n\n\
+         File {} ({}):\n```\n{}\n```\n\n\
+         Change only what the description asks for. Reply with a single fenced code block \
+         containing the complete new file, or with exactly {} if nothing in this file needs it.",
+        description,
+        path,
+        detect_language(path),
+        existing,
+        NO_CHANGE
+    );
+    let api_key = chatbot.api_key.clone();
+    let response = complete_with_claude(
+        &prompt,
+        DEFAULT_MODEL,
+        &api_key,
+        "sweep_patch",
+        Operation::Refactor,
+        chatbot,
+    )
+    .await?;
+    if response.trim() == NO_CHANGE {
+        return Ok(None);
+    }
+    let contents = strip_code_fence(&response);
+    Ok((contents != existing).then_some(contents))
+}
+
+/// Finds the files the described change touches and proposes a patch for each
+pub async fn plan_sweep(
+    description: &str,
+    root: &Path,
+    chatbot: &mut Chatbot,
+) -> Result<Sweep, Box<dyn std::error::Error>> {
+    let patterns = grep_patterns(description, chatbot).await?;
+    let mut sweep = Sweep {
+        description: description.to_string(),
+        patterns,
+        ..Sweep::default()
+    };
+    for path in grep_files(root, &sweep.patterns, chatbot) {
+        if !chatbot.privacy.allows_contents(&path) || sweep.patches.len() >= MAX_SWEEP_FILES {
+            sweep.skipped.push(path);
+            continue;
+        }
+        let Ok(existing) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(contents) = patch_file(description, &path, &existing, chatbot).await? {
+            sweep.patches.push(SweepPatch {
+                edit: PendingEdit {
+                    path: path.clone(),
+                    diff: diffy::create_patch(&existing, &contents).to_string(),
+                    contents,
+                },
+                original: existing,
+                selected: true,
+            });
+        }
+    }
+    Ok(sweep)
+}
+
+fn temp_path(path: &str) -> String {
+    format!("{}{}", path, TEMP_SUFFIX)
+}
+
+/// Writes the selected patches all or nothing: every file is written to a temporary
+/// first, and already replaced files are restored if a rename fails
+pub fn apply_selected(sweep: &Sweep) -> Result<usize, Box<dyn std::error::Error>> {
+    let selected: Vec<&SweepPatch> = sweep.patches.iter().filter(|p| p.selected).collect();
+    for patch in &selected {
+        let current = fs::read_to_string(&patch.edit.path).unwrap_or_default();
+        if current != patch.original {
+            return Err(format!(
+                "{} changed since the sweep was proposed; run /sweep again",
+                patch.edit.path
+            )
+            .into());
+        }
+    }
+
+    for (i, patch) in selected.iter().enumerate() {
+        if let Err(e) = fs::write(temp_path(&patch.edit.path), &patch.edit.contents) {
+            for written in &selected[..i] {
+                let _ = fs::remove_file(temp_path(&written.edit.path));
+            }
+            return Err(format!("Writing {} failed: {}", patch.edit.path, e).into());
+        }
+    }
+
+    for (i, patch) in selected.iter().enumerate() {
+        if let Err(e) = fs::rename(temp_path(&patch.edit.path), &patch.edit.path) {
+            for replaced in &selected[..i] {
+                let _ = fs::write(&replaced.edit.path, &replaced.original);
+            }
+            for pending in &selected[i..] {
+                let _ = fs::remove_file(temp_path(&pending.edit.path));
+            }
+            return Err(format!(
+                "Replacing {} failed, no files were changed: {}",
+                patch.edit.path, e
+            )
+            .into());
+        }
+    }
+    Ok(selected.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(path: &Path, original: &str, contents: &str) -> SweepPatch {
+        SweepPatch {
+            edit: PendingEdit {
+                path: path.display().to_string(),
+                diff: diffy::create_patch(original, contents).to_string(),
+                contents: contents.to_string(),
+            },
+            original: original.to_string(),
+            selected: true,
+        }
+    }
+
+    fn leftover_temps(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(TEMP_SUFFIX))
+            .collect()
+    }
+
+    #[test]
+    fn a_failed_write_restores_the_files_already_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.rs"), dir.path().join("b.rs"));
+        fs::write(&a, "fn old_a() {}\n").unwrap();
+        fs::write(&b, "fn old_b() {}\n").unwrap();
+        // A directory where the last file should be: its temporary is written, but it
+        // can't be renamed over, after a.rs and b.rs already were
+        let blocked = dir.path().join("c.rs");
+        fs::create_dir(&blocked).unwrap();
+        let sweep = Sweep {
+            patches: vec![
+                patch(&a, "fn old_a() {}\n", "fn new_a() {}\n"),
+                patch(&b, "fn old_b() {}\n", "fn new_b() {}\n"),
+                patch(&blocked, "", "fn new_c() {}\n"),
+            ],
+            ..Sweep::default()
+        };
+
+        let error = apply_selected(&sweep).unwrap_err().to_string();
+        assert!(error.starts_with("Replacing"), "{}", error);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "fn old_a() {}\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "fn old_b() {}\n");
+        assert!(blocked.is_dir());
+        assert!(leftover_temps(dir.path()).is_empty());
+
+        // A temporary that can't be written stops the sweep before anything is replaced
+        let unwritable = dir.path().join("missing").join("d.rs");
+        let sweep = Sweep {
+            patches: vec![
+                patch(&a, "fn old_a() {}\n", "fn new_a() {}\n"),
+                patch(&unwritable, "", "fn new_d() {}\n"),
+            ],
+            ..Sweep::default()
+        };
+        let error = apply_selected(&sweep).unwrap_err().to_string();
+        assert!(error.starts_with("Writing"), "{}", error);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "fn old_a() {}\n");
+        assert!(leftover_temps(dir.path()).is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+// src/test_harness.rs
+//! Drives the full app through scripted key presses against a fake terminal and a
+//! mocked Messages API, for regression tests that assert on the rendered buffer.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::TestBackend, Terminal};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use tempfile::TempDir;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::app::App;
+use crate::chaos::{ChaosClient, ChaosConfig};
+use crate::claude_client::{ClaudeClient, HttpClient};
+use crate::config::Config;
+use crate::{handle_key, ui};
+
+pub const WIDTH: u16 = 100;
+pub const HEIGHT: u16 = 40;
+
+pub struct Harness {
+    pub terminal: Terminal<TestBackend>,
+    pub app: App,
+    pub server: MockServer,
+    // The project root and data directory, kept alive for the test's duration
+    pub workspace: TempDir,
+}
+
+/// A server-sent event stream in the shape of a streaming Messages API response
+fn streamed_answer(answer: &str) -> String {
+    let mut events = vec![json!({
+        "type": "message_start",
+        "message": { "usage": { "input_tokens": 10, "output_tokens": 0 } }
+    })];
+    // Split into several deltas so partial rendering is exercised
+    let words: Vec<&str> = answer.split_inclusive(' ').collect();
+    for chunk in words.chunks(3) {
+        events.push(json!({
+            "type": "content_block_delta",
+            "delta": { "type": "text_delta", "text": chunk.concat() }
+        }));
+    }
+    events.push(json!({
+        "type": "message_delta",
+        "delta": { "stop_reason": "end_turn" },
+        "usage": { "output_tokens": 20 }
+    }));
+    events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {}\n\n",
+                event["type"].as_str().unwrap(),
+                event
+            )
+        })
+        .collect()
+}
+
+fn text_response(text: &str) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "usage": { "input_tokens": 10, "output_tokens": 5 },
+        "stop_reason": "end_turn"
+    })
+}
+
+impl Harness {
+    /// An app on a fresh project and data directory with one indexed file, talking to a
+    /// mock server
+    pub async fn new() -> Harness {
+        Harness::with_chaos(None).await
+    }
+
+    /// Like `new`, with faults injected between the app and the mock server
+    pub async fn with_chaos(chaos: Option<ChaosConfig>) -> Harness {
+        let workspace = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+
+        let client: Arc<dyn ClaudeClient> =
+            Arc::new(HttpClient::new(format!("{}/v1/messages", server.uri())));
+        let client: Arc<dyn ClaudeClient> = match chaos {
+            Some(chaos) => Arc::new(ChaosClient::new(client, chaos)),
+            None => client,
+        };
+        let mut app = App::with_client(
+            Config {
+                data_dir: workspace.path().display().to_string(),
+                ..Config::default()
+            },
+            client,
+            workspace.path().to_path_buf(),
+        );
+        app.chatbot.api_key = "test-key".to_string();
+
+        let file = workspace.path().join("lib.rs");
+        fs::write(&file, "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n").unwrap();
+        let file = file.display().to_string();
+        app.chatbot.index = Arc::new(HashMap::from([(
+            file,
+            ("Adds two integers.".to_string(), "rust".to_string()),
+        )]));
+        app.on_resize(WIDTH, HEIGHT);
+
+        Harness {
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+            app,
+            server,
+            workspace,
+        }
+    }
+
+    pub fn indexed_file(&self) -> String {
+        self.workspace.path().join("lib.rs").display().to_string()
+    }
+
+    /// Answers the next question: retrieval ranks the indexed file, then `answer` streams
+    pub async fn mock_answer(&self, answer: &str) {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "stream": true })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(streamed_answer(answer)),
+            )
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(text_response(&format!("{},0.9", self.indexed_file()))),
+            )
+            .with_priority(2)
+            .mount(&self.server)
+            .await;
+    }
+
+    pub async fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        handle_key(
+            &mut self.terminal,
+            &mut self.app,
+            KeyEvent::new(code, modifiers),
+        )
+        .await
+        .unwrap();
+        self.draw();
+    }
+
+    pub async fn press(&mut self, code: KeyCode) {
+        self.press_with(code, KeyModifiers::NONE).await;
+    }
+
+    pub async fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c)).await;
+        }
+    }
+
+    pub fn draw(&mut self) {
+        let app = &self.app;
+        self.terminal.draw(|f| ui(f, app)).unwrap();
+    }
+
+    /// The rendered screen as plain text, one line per row
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{AppState, ChatFocus};
+    use crate::chaos::Fault;
+    use crate::events::AppEvent;
+    use crate::session::Session;
+    use crate::transcript_log::{append_turn, TranscriptTurn};
+    use crate::ui::chat::Message;
+    use std::time::Duration;
+
+    fn chaos(fault: Fault) -> Option<ChaosConfig> {
+        Some(ChaosConfig {
+            rate: 1.0,
+            faults: vec![fault],
+            delay: Duration::from_millis(50),
+            seed: 7,
+        })
+    }
+
+    #[tokio::test]
+    async fn main_menu_opens_chat() {
+        let mut harness = Harness::new().await;
+        harness.draw();
+        assert!(harness.screen().contains("Chat with CWD"));
+
+        harness.press(KeyCode::Enter).await;
+        assert_eq!(harness.app.state, AppState::Chat);
+        assert!(harness.screen().contains("Input"));
+    }
+
+    #[tokio::test]
+    async fn asked_question_renders_streamed_answer() {
+        let mut harness = Harness::new().await;
+        harness
+            .mock_answer("The add function in lib.rs returns the sum of its arguments.")
+            .await;
+
+        harness.press(KeyCode::Enter).await;
+        harness.type_text("what does add do?").await;
+        harness.press(KeyCode::Enter).await;
+
+        let screen = harness.screen();
+        assert!(screen.contains("what does add do?"), "{}", screen);
+        assert!(screen.contains("returns the sum"), "{}", screen);
+        assert_eq!(
+            harness.app.messages.last().unwrap().context_files,
+            vec![harness.indexed_file()]
+        );
+    }
+
+    #[tokio::test]
+    async fn focused_code_block_is_copied_from_the_transcript() {
+        let mut harness = Harness::new().await;
+        harness
+            .mock_answer("Call it like this:\n```rust\nlet sum = add(1, 2);\n```\nIt returns 3.")
+            .await;
+
+        harness.press(KeyCode::Enter).await;
+        harness.type_text("how do I call add?").await;
+        harness.press(KeyCode::Enter).await;
+
+        harness.press(KeyCode::Tab).await;
+        assert_eq!(harness.app.chat_focus, ChatFocus::Transcript);
+        harness.press(KeyCode::Char('[')).await;
+        let answer = harness.app.messages.len() - 1;
+        assert_eq!(harness.app.selected_code_block, Some((answer, 0)));
+        assert!(harness.screen().contains("Code block 1 of 1"));
+
+        harness.press(KeyCode::Char('y')).await;
+        assert_eq!(
+            harness.app.clipboard_ring.get(0).map(String::as_str),
+            Some("let sum = add(1, 2);\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_request_is_shown_as_an_error_answer() {
+        let mut harness = Harness::new().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("overloaded"))
+            .mount(&harness.server)
+            .await;
+
+        harness.press(KeyCode::Enter).await;
+        harness.type_text("anything").await;
+        harness.press(KeyCode::Enter).await;
+
+        let failed = harness.app.messages.last().unwrap();
+        let error = failed.error.as_ref().expect("the turn failed");
+        assert_eq!(error.question, "anything");
+        let screen = harness.screen();
+        assert!(screen.contains(error.class.label()), "{}", screen);
+    }
+
+    #[tokio::test]
+    async fn malformed_response_is_an_error_answer_and_clears_busy() {
+        let mut harness = Harness::with_chaos(chaos(Fault::Malformed)).await;
+        harness.mock_answer("never seen").await;
+
+        harness.press(KeyCode::Enter).await;
+        harness.type_text("anything").await;
+        harness.press(KeyCode::Enter).await;
+
+        assert!(harness.screen().contains("Error:"));
+        assert!(!harness.app.busy);
+    }
+
+    #[tokio::test]
+    async fn slow_response_still_arrives() {
+        let mut harness = Harness::with_chaos(chaos(Fault::Slow)).await;
+        harness
+            .mock_answer("The add function sums its arguments.")
+            .await;
+
+        harness.press(KeyCode::Enter).await;
+        harness.type_text("what does add do?").await;
+        harness.press(KeyCode::Enter).await;
+
+        assert!(harness.screen().contains("sums its arguments"));
+    }
+
+    #[tokio::test]
+    async fn switching_sessions_replaces_the_models_memory() {
+        let mut harness = Harness::new().await;
+        harness
+            .mock_answer("The add function sums its arguments.")
+            .await;
+        harness.press(KeyCode::Enter).await;
+        harness.type_text("what does add do?").await;
+        harness.press(KeyCode::Enter).await;
+        harness.app.chatbot.pinned_files = vec![harness.indexed_file()];
+        assert_eq!(harness.app.chatbot.history().len(), 2);
+
+        let mut other = Session::new();
+        other.id = "other".to_string();
+        other.messages = vec![
+            Message::user("how is config loaded?"),
+            Message::ai("From config.toml."),
+        ];
+        harness.app.session_list = vec![other];
+        harness.app.selected_session = 0;
+        harness.app.switch_to_selected_session();
+
+        let history = harness.app.chatbot.history();
+        let turns: Vec<(&str, &str)> = history
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                ("user", "how is config loaded?"),
+                ("assistant", "From config.toml.")
+            ]
+        );
+        assert!(harness.app.chatbot.pinned_files.is_empty());
+        assert!(harness.app.chatbot.carry_over.files.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_repeated_question_is_cached_only_within_the_same_conversation() {
+        let mut harness = Harness::new().await;
+        harness
+            .mock_answer("The add function sums its arguments.")
+            .await;
+        harness.press(KeyCode::Enter).await;
+        async fn ask(harness: &mut Harness) -> bool {
+            harness.type_text("what does add do?").await;
+            harness.press(KeyCode::Enter).await;
+            harness.app.messages.last().unwrap().cached
+        }
+
+        assert!(!ask(&mut harness).await);
+        // The first answer is now part of the history, so the repeat is a new question
+        assert!(!ask(&mut harness).await);
+        harness.app.clear_history();
+        assert!(ask(&mut harness).await);
+    }
+
+    #[tokio::test]
+    async fn background_events_mark_the_frame_changed_once() {
+        let mut harness = Harness::new().await;
+        assert!(!harness.app.drain_events());
+        assert!(!harness.app.is_active());
+
+        harness.app.running_task = Some("cargo test".to_string());
+        assert!(harness.app.is_active());
+        let sender = harness.app.events.sender();
+        sender
+            .send(AppEvent::LogLine("test a ... ok".to_string()))
+            .unwrap();
+        assert!(harness.app.drain_events());
+        assert_eq!(
+            harness.app.status_message.as_deref(),
+            Some("cargo test: test a ... ok")
+        );
+        sender
+            .send(AppEvent::TaskDone {
+                task: "cargo test".to_string(),
+                result: Ok("2 passed".to_string()),
+            })
+            .unwrap();
+        assert!(harness.app.drain_events());
+        // Nothing new since, so an idle tick can skip drawing
+        assert!(!harness.app.drain_events());
+        assert!(!harness.app.is_active());
+        assert_eq!(harness.app.messages.last().unwrap().content, "2 passed");
+    }
+
+    #[tokio::test]
+    async fn a_missing_digest_is_written_without_holding_up_the_screen() {
+        let mut harness = Harness::new().await;
+        harness.app.config.daily_digest = true;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(text_response("Traced add's callers.")),
+            )
+            .mount(&harness.server)
+            .await;
+        let data_dir = harness.app.config.data_path();
+        let yesterday = chrono::Local::now() - chrono::Duration::days(1);
+        append_turn(
+            &data_dir,
+            &TranscriptTurn {
+                timestamp: yesterday.with_timezone(&chrono::Utc),
+                session_id: "s".to_string(),
+                project: harness.app.project_name(),
+                question: "who calls add?".to_string(),
+                answer: "Only main.".to_string(),
+                context_files: vec![harness.indexed_file()],
+                input_tokens: 10,
+                output_tokens: 5,
+                cost: 0.01,
+                latency_ms: 100,
+                error: false,
+                structured: None,
+            },
+        )
+        .unwrap();
+
+        harness.app.write_missing_digest();
+        // Returns before the model answers; the result arrives on the event bus
+        assert!(harness.app.is_active());
+        assert_eq!(harness.app.status_message, None);
+        while !harness.app.drain_events() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!harness.app.is_active());
+        let day = yesterday.date_naive();
+        assert_eq!(
+            harness.app.status_message,
+            Some(format!(
+                "Wrote the digest of {} (1 questions); /digest {} shows it",
+                day, day
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn focus_mode_leaves_only_the_transcript_and_input() {
+        let mut harness = Harness::new().await;
+        harness.press(KeyCode::Enter).await;
+        assert!(harness.screen().contains("/ ____|"));
+
+        harness
+            .press_with(KeyCode::Char('f'), KeyModifiers::CONTROL)
+            .await;
+        let screen = harness.screen();
+        assert!(
+            screen.contains("Focus mode · Ctrl+F to leave"),
+            "{}",
+            screen
+        );
+        assert!(!screen.contains("/ ____|"));
+        assert!(screen.contains("Input"));
+
+        // /focus toggles it back, and the header returns
+        harness.type_text("/focus").await;
+        harness.press(KeyCode::Enter).await;
+        assert!(!harness.app.focus_mode);
+        assert!(harness.screen().contains("/ ____|"));
+    }
+
+    #[tokio::test]
+    async fn safe_mode_refuses_indexing_and_skips_background_work() {
+        let mut harness = Harness::new().await;
+        harness.app.chatbot.two_phase_answers = true;
+        harness.app.enter_safe_mode();
+        assert!(!harness.app.chatbot.two_phase_answers);
+        assert!(harness
+            .app
+            .status_message
+            .as_deref()
+            .unwrap()
+            .starts_with("Safe mode:"));
+
+        assert!(!harness.app.ensure_writable());
+        assert_eq!(
+            harness.app.status_message.as_deref(),
+            Some("Safe mode: indexing is disabled in this instance")
+        );
+        // Safe mode wins over read-only, so the lock isn't even checked
+        assert!(!harness.app.read_only);
+        assert!(!harness.app.refresh_project_status());
+        assert!(harness.app.project_status.is_none());
+    }
+}
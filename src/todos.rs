@@ -0,0 +1,248 @@
+// src/todos.rs
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const TODOS_FILE: &str = "todos.json";
+// Markers of known issues, as written in comments; the onboarding report scans for the same
+pub const TODO_MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+const COMMENT_OPENERS: [&str; 5] = ["//", "#", "/*", "<!--", "--"];
+// Known issues listed in a question's context, across all its files
+const MAX_CONTEXT_TODOS: usize = 40;
+
+/// A TODO, FIXME or HACK comment
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TodoItem {
+    pub kind: String,
+    // 1-based
+    pub line: usize,
+    pub text: String,
+}
+
+// The marker of a comment line and the text after it
+fn todo_in(line: &str) -> Option<(&'static str, String)> {
+    let trimmed = line.trim_start();
+    let comment = if trimmed.starts_with('*') {
+        trimmed
+    } else {
+        let start = COMMENT_OPENERS
+            .iter()
+            .filter_map(|opener| line.find(opener))
+            .min()?;
+        &line[start..]
+    };
+    TODO_MARKERS.iter().find_map(|&marker| {
+        let at = comment.find(marker)?;
+        let before = comment[..at].chars().next_back();
+        let rest = &comment[at + marker.len()..];
+        let after = rest.chars().next();
+        let word_start = before.is_none_or(|c| !c.is_alphanumeric() && c != '_');
+        let word_end = after.is_none_or(|c| matches!(c, ':' | '(' | ' ' | '!'));
+        if !(word_start && word_end) {
+            return None;
+        }
+        // An owner like TODO(alice): stays out of the text
+        let rest = match rest.strip_prefix('(') {
+            Some(owned) => owned.split_once(')').map_or(owned, |(_, rest)| rest),
+            None => rest,
+        };
+        let text = rest
+            .trim_start_matches([':', '!', ' '])
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim();
+        Some((marker, text.to_string()))
+    })
+}
+
+/// The TODO, FIXME and HACK comments of a file, in line order
+pub fn extract_todos(content: &str) -> Vec<TodoItem> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let (kind, text) = todo_in(line)?;
+            Some(TodoItem {
+                kind: kind.to_string(),
+                line: i + 1,
+                text,
+            })
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct FileTodos {
+    modified_secs: u64,
+    items: Vec<TodoItem>,
+}
+
+/// Known-issue comments per indexed file, kept up to date by indexing
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct TodoTable {
+    files: BTreeMap<String, FileTodos>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl TodoTable {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(TODOS_FILE);
+        let mut table: TodoTable = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        table.path = Some(path);
+        table
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+        }
+        Ok(())
+    }
+
+    /// Rereads a file's comments unless they were taken from this version of it
+    pub fn refresh(&mut self, file: &str, modified_secs: u64) {
+        if self
+            .files
+            .get(file)
+            .is_some_and(|todos| todos.modified_secs == modified_secs)
+        {
+            return;
+        }
+        let items = fs::read_to_string(file)
+            .map(|content| extract_todos(&content))
+            .unwrap_or_default();
+        self.files.insert(
+            file.to_string(),
+            FileTodos {
+                modified_secs,
+                items,
+            },
+        );
+    }
+
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.files.retain(|file, _| keep(file));
+    }
+
+    pub fn for_file(&self, file: &str) -> &[TodoItem] {
+        self.files
+            .get(file)
+            .map_or(&[][..], |todos| todos.items.as_slice())
+    }
+
+    /// Every comment as (file, item), grouped by file, of files whose path contains
+    /// `filter`
+    pub fn list(&self, filter: &str) -> Vec<(String, TodoItem)> {
+        self.files
+            .iter()
+            .filter(|(file, _)| file.contains(filter))
+            .flat_map(|(file, todos)| {
+                todos
+                    .items
+                    .iter()
+                    .map(move |item| (file.clone(), item.clone()))
+            })
+            .collect()
+    }
+
+    /// The comments of a question's context files, for the model to refer to
+    pub fn context_note(&self, files: &[String]) -> Option<String> {
+        let lines: Vec<String> = files
+            .iter()
+            .flat_map(|file| {
+                self.for_file(file).iter().map(move |item| {
+                    format!("- {}:{} {}: {}", file, item.line, item.kind, item.text)
+                })
+            })
+            .take(MAX_CONTEXT_TODOS)
+            .collect();
+        (!lines.is_empty()).then(|| {
+            format!(
+                "Known issues marked in the context files (TODO, FIXME and HACK comments):\n{}",
+                lines.join("\n")
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers_are_taken_from_comments_only() {
+        let content = "fn a() {} // TODO: handle retries\n\
+                       let todo_list = 1; // not a TODOS marker\n\
+                       # FIXME(bob) breaks on empty input\n\
+                       /* HACK: works around the parser */\n\
+                       let s = \"TODO in a string\";\n";
+        let todos = extract_todos(content);
+        let found: Vec<(usize, &str, &str)> = todos
+            .iter()
+            .map(|item| (item.line, item.kind.as_str(), item.text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "TODO", "handle retries"),
+                (3, "FIXME", "breaks on empty input"),
+                (4, "HACK", "works around the parser"),
+            ]
+        );
+    }
+
+    #[test]
+    fn block_html_and_sql_comments_are_trimmed_of_their_closers() {
+        let content = "/**\n * TODO: document the return value\n */\n\
+                       <!-- FIXME: broken link -->\n\
+                       SELECT 1; -- HACK! bypasses the cache\n\
+                       // TODO\n\
+                       // todo: lowercase is a word, not a marker\n";
+        let found: Vec<(usize, String, String)> = extract_todos(content)
+            .into_iter()
+            .map(|item| (item.line, item.kind, item.text))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    2,
+                    "TODO".to_string(),
+                    "document the return value".to_string()
+                ),
+                (4, "FIXME".to_string(), "broken link".to_string()),
+                (5, "HACK".to_string(), "bypasses the cache".to_string()),
+                (6, "TODO".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn files_are_reread_only_when_they_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs").to_string_lossy().to_string();
+        fs::write(&file, "// TODO: one\n").unwrap();
+        let mut table = TodoTable::load(dir.path());
+        table.refresh(&file, 1);
+
+        fs::write(&file, "// TODO: one\n// FIXME: two\n").unwrap();
+        table.refresh(&file, 1);
+        assert_eq!(table.for_file(&file).len(), 1);
+        table.refresh(&file, 2);
+        assert_eq!(table.for_file(&file).len(), 2);
+
+        assert_eq!(table.list("lib").len(), 2);
+        assert!(table.list("main").is_empty());
+        let note = table.context_note(std::slice::from_ref(&file)).unwrap();
+        assert!(note.ends_with(&format!("- {}:2 FIXME: two", file)));
+        assert!(table.context_note(&["other.rs".to_string()]).is_none());
+
+        table.retain(|kept| kept != file);
+        assert!(table.for_file(&file).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+// src/token_counter.rs
+//! Token counting that organizations can replace: a shared library named by
+//! `tokenizer_plugin` in the config counts tokens for every budget and estimate in place
+//! of the built-in Claude tokenizer. Plugins need a build with
+//! `--features tokenizer-plugins`.
+//!
+//! A plugin exports one C function:
+//!
+//! ```c
+//! size_t sagacity_count_tokens(const uint8_t *text, size_t len); // UTF-8, not terminated
+//! ```
+use std::path::Path;
+use std::sync::RwLock;
+
+use claude_tokenizer::count_tokens;
+
+/// Counts the tokens a model would see for a piece of text
+pub trait TokenCounter: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// The Claude tokenizer, estimated at four characters per token if it fails
+pub struct BuiltinCounter;
+
+impl TokenCounter for BuiltinCounter {
+    fn count_tokens(&self, text: &str) -> usize {
+        count_tokens(text).unwrap_or(text.len() / 4)
+    }
+}
+
+static INSTALLED: RwLock<Option<Box<dyn TokenCounter>>> = RwLock::new(None);
+
+/// Counts with the installed counter, or the built-in one when none is
+pub fn count(text: &str) -> usize {
+    match INSTALLED.read().ok().as_deref() {
+        Some(Some(counter)) => counter.count_tokens(text),
+        _ => BuiltinCounter.count_tokens(text),
+    }
+}
+
+/// Loads a plugin and counts with it for the rest of the run, returning its name
+#[cfg(feature = "tokenizer-plugins")]
+pub fn load_plugin(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let counter = plugin::PluginCounter::load(path)?;
+    let name = counter.name().to_string();
+    *INSTALLED
+        .write()
+        .map_err(|_| "The token counter is poisoned")? = Some(Box::new(counter));
+    Ok(name)
+}
+
+#[cfg(not(feature = "tokenizer-plugins"))]
+pub fn load_plugin(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    Err(format!(
+        "{} not loaded: this build has no tokenizer plugin support (--features tokenizer-plugins)",
+        path.display()
+    )
+    .into())
+}
+
+#[cfg(feature = "tokenizer-plugins")]
+mod plugin {
+    use libloading::{Library, Symbol};
+    use std::path::Path;
+
+    use super::TokenCounter;
+
+    type CountFn = unsafe extern "C" fn(*const u8, usize) -> usize;
+
+    pub struct PluginCounter {
+        // Kept loaded for as long as `count` may be called
+        _library: Library,
+        count: CountFn,
+        name: String,
+    }
+
+    impl PluginCounter {
+        pub fn load(path: &Path) -> Result<PluginCounter, Box<dyn std::error::Error>> {
+            // Safety: loading runs the library's initializers; the path is the user's own
+            // configuration, trusted like the binary itself
+            let library = unsafe { Library::new(path) }
+                .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+            let count = unsafe {
+                let symbol: Symbol<CountFn> =
+                    library.get(b"sagacity_count_tokens\0").map_err(|e| {
+                        format!("{} has no sagacity_count_tokens: {}", path.display(), e)
+                    })?;
+                *symbol
+            };
+            let name = path.file_stem().map_or_else(
+                || path.display().to_string(),
+                |stem| stem.to_string_lossy().to_string(),
+            );
+            Ok(PluginCounter {
+                _library: library,
+                count,
+                name,
+            })
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    impl TokenCounter for PluginCounter {
+        fn count_tokens(&self, text: &str) -> usize {
+            // Safety: the pointer and length describe `text`, which outlives the call
+            unsafe { (self.count)(text.as_ptr(), text.len()) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_plugin_that_fails_to_load_leaves_the_built_in_counter() {
+        let text = "fn main() { println!(\"hello\"); }";
+        assert!(load_plugin(Path::new("/nonexistent/libtokens.so")).is_err());
+        assert_eq!(count(text), BuiltinCounter.count_tokens(text));
+    }
+
+    #[test]
+    fn load_errors_name_the_plugin_and_empty_text_is_free() {
+        let dir = tempfile::tempdir().unwrap();
+        // Not a shared library, so no build can load it
+        let bogus = dir.path().join("libtokens.so");
+        std::fs::write(&bogus, "not a library").unwrap();
+        let error = load_plugin(&bogus).unwrap_err().to_string();
+        assert!(error.contains(&bogus.display().to_string()), "{}", error);
+        #[cfg(not(feature = "tokenizer-plugins"))]
+        assert!(error.ends_with("(--features tokenizer-plugins)"));
+        #[cfg(feature = "tokenizer-plugins")]
+        assert!(error.starts_with("Failed to load"));
+
+        assert_eq!(count(""), 0);
+    }
+}
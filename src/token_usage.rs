@@ -0,0 +1,165 @@
+// src/token_usage.rs
+//! Token usage in one shape whichever backend answered, so costs, budgets and the audit
+//! log read the same for Anthropic, OpenAI-compatible and local servers.
+use serde_json::Value;
+
+/// Which API shape a response body has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    Anthropic,
+    // OpenAI and the servers compatible with it, such as llama.cpp and vLLM
+    OpenAi,
+    // Ollama's native API
+    Local,
+}
+
+impl Provider {
+    /// Tells the provider from the fields its usage report has
+    pub fn detect(body: &Value) -> Provider {
+        if body.get("prompt_eval_count").is_some() || body.get("eval_count").is_some() {
+            Provider::Local
+        } else if body["usage"].get("prompt_tokens").is_some() {
+            Provider::OpenAi
+        } else {
+            Provider::Anthropic
+        }
+    }
+}
+
+/// Tokens of one call. `prompt_tokens` excludes cached input, which is billed on its
+/// own; `reasoning_tokens` are part of `completion_tokens`, broken out for display
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub reasoning_tokens: u64,
+}
+
+fn count(value: &Value) -> u64 {
+    value.as_u64().unwrap_or(0)
+}
+
+impl TokenUsage {
+    /// Normalizes the usage of a response body, whichever provider sent it
+    pub fn from_body(body: &Value) -> TokenUsage {
+        match Provider::detect(body) {
+            Provider::Anthropic => TokenUsage::anthropic(&body["usage"]),
+            Provider::OpenAi => TokenUsage::openai(&body["usage"]),
+            Provider::Local => TokenUsage::local(body),
+        }
+    }
+
+    /// A Messages API `usage` object, also sent at the start of a stream
+    pub fn anthropic(usage: &Value) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: count(&usage["input_tokens"]),
+            completion_tokens: count(&usage["output_tokens"]),
+            cache_read_tokens: count(&usage["cache_read_input_tokens"]),
+            cache_write_tokens: count(&usage["cache_creation_input_tokens"]),
+            reasoning_tokens: 0,
+        }
+    }
+
+    // OpenAI counts cached input within prompt_tokens and caches without a write charge
+    fn openai(usage: &Value) -> TokenUsage {
+        let cached = count(&usage["prompt_tokens_details"]["cached_tokens"]);
+        TokenUsage {
+            prompt_tokens: count(&usage["prompt_tokens"]).saturating_sub(cached),
+            completion_tokens: count(&usage["completion_tokens"]),
+            cache_read_tokens: cached,
+            cache_write_tokens: 0,
+            reasoning_tokens: count(&usage["completion_tokens_details"]["reasoning_tokens"]),
+        }
+    }
+
+    fn local(body: &Value) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: count(&body["prompt_eval_count"]),
+            completion_tokens: count(&body["eval_count"]),
+            ..TokenUsage::default()
+        }
+    }
+
+    /// Every input token, cached or not
+    pub fn input_tokens(&self) -> u64 {
+        self.prompt_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+}
+
+/// Whether the answer stopped at its token limit, in any provider's words
+pub fn truncated(body: &Value) -> bool {
+    body["stop_reason"] == "max_tokens"
+        || body["choices"][0]["finish_reason"] == "length"
+        || body["done_reason"] == "length"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn providers_are_normalized_to_one_shape() {
+        let anthropic = json!({
+            "usage": { "input_tokens": 10, "output_tokens": 4, "cache_read_input_tokens": 90 },
+            "stop_reason": "max_tokens"
+        });
+        let openai = json!({
+            "choices": [{ "finish_reason": "stop" }],
+            "usage": {
+                "prompt_tokens": 100,
+                "completion_tokens": 40,
+                "prompt_tokens_details": { "cached_tokens": 90 },
+                "completion_tokens_details": { "reasoning_tokens": 30 }
+            }
+        });
+        let local = json!({ "prompt_eval_count": 100, "eval_count": 4, "done_reason": "length" });
+
+        assert_eq!(
+            TokenUsage::from_body(&anthropic),
+            TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 4,
+                cache_read_tokens: 90,
+                ..TokenUsage::default()
+            }
+        );
+        assert_eq!(
+            TokenUsage::from_body(&openai),
+            TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 40,
+                cache_read_tokens: 90,
+                reasoning_tokens: 30,
+                ..TokenUsage::default()
+            }
+        );
+        assert_eq!(TokenUsage::from_body(&local).input_tokens(), 100);
+        assert!(truncated(&anthropic) && truncated(&local) && !truncated(&openai));
+    }
+
+    #[test]
+    fn missing_or_inconsistent_counts_read_as_zero() {
+        // A body without usage, such as an error, counts nothing
+        assert_eq!(Provider::detect(&json!({})), Provider::Anthropic);
+        assert_eq!(TokenUsage::from_body(&json!({})), TokenUsage::default());
+        // Ollama may send only one of its counts
+        let local = json!({ "eval_count": 7 });
+        assert_eq!(Provider::detect(&local), Provider::Local);
+        assert_eq!(TokenUsage::from_body(&local).completion_tokens, 7);
+        // More cached than prompt tokens doesn't underflow
+        let openai = json!({
+            "usage": { "prompt_tokens": 5, "prompt_tokens_details": { "cached_tokens": 9 } }
+        });
+        assert_eq!(TokenUsage::from_body(&openai).prompt_tokens, 0);
+        // Cache writes are input too
+        let written = json!({ "usage": { "input_tokens": 3, "cache_creation_input_tokens": 40 } });
+        assert_eq!(TokenUsage::from_body(&written).input_tokens(), 43);
+        assert!(truncated(
+            &json!({ "choices": [{ "finish_reason": "length" }] })
+        ));
+        assert!(!truncated(&json!({})));
+    }
+}
@@ -0,0 +1,84 @@
+// src/transcript_log.rs
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::structured::StructuredAnswer;
+
+pub const TRANSCRIPTS_DIR: &str = "transcripts";
+
+/// One question and its answer, appended as a JSON line the moment the turn completes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TranscriptTurn {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    pub project: String,
+    pub question: String,
+    pub answer: String,
+    pub context_files: Vec<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+    pub latency_ms: u64,
+    pub error: bool,
+    // Answer, confidence and sources as returned in structured mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured: Option<StructuredAnswer>,
+}
+
+/// A file name for per-project data, derived from the project's root or URL so
+/// different checkouts don't mix
+pub fn project_file_name(project: &str) -> String {
+    let name: String = project
+        .trim_start_matches("./")
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_matches(|c: char| c == '_' || c == '.');
+    if name.is_empty() {
+        "project".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn transcript_path(data_dir: &Path, project: &str) -> PathBuf {
+    data_dir
+        .join(TRANSCRIPTS_DIR)
+        .join(format!("{}.jsonl", project_file_name(project)))
+}
+
+/// Appends a turn to the project's transcript; kept apart from sessions so it
+/// survives their deletion
+pub fn append_turn(
+    data_dir: &Path,
+    turn: &TranscriptTurn,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = transcript_path(data_dir, &turn.project);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(turn)?)?;
+    Ok(())
+}
+
+/// Reads back every turn of a project's transcript, skipping lines that don't parse
+pub fn load_turns(data_dir: &Path, project: &str) -> Vec<TranscriptTurn> {
+    fs::read_to_string(transcript_path(data_dir, project))
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,160 @@
+// src/transcript_print.rs
+use chrono::Local;
+use std::ops::Range;
+
+use crate::scrubber::{ScrubReport, Scrubber};
+use crate::ui::chat::{Message, Sender};
+
+// Narrowest width printed lines are wrapped to
+const MIN_PRINT_WIDTH: usize = 20;
+
+/// Messages picked by a `/print` argument: every message, `N` or `N-M`, numbered from 1
+pub fn print_range(argument: &str, len: usize) -> Result<Range<usize>, String> {
+    let number = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| (1..=len).contains(&n))
+            .ok_or_else(|| format!("No message {} (there are {})", n.trim(), len))
+    };
+    if argument.is_empty() {
+        return Ok(0..len);
+    }
+    match argument.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (number(start)?, number(end)?);
+            if start > end {
+                return Err(format!("Range {}-{} runs backwards", start, end));
+            }
+            Ok(start - 1..end)
+        }
+        None => {
+            let n = number(argument)?;
+            Ok(n - 1..n)
+        }
+    }
+}
+
+/// The messages in `range` as plain lines for the terminal's scrollback, wrapped to
+/// `width` and scrubbed like a shared transcript
+pub fn transcript_lines(
+    messages: &[Message],
+    range: Range<usize>,
+    width: usize,
+    scrubber: &Scrubber,
+    report: &mut ScrubReport,
+) -> Vec<String> {
+    let width = width.max(MIN_PRINT_WIDTH);
+    let mut lines = Vec::new();
+    for (i, message) in messages
+        .iter()
+        .enumerate()
+        .take(range.end)
+        .skip(range.start)
+    {
+        let speaker = match message.sender {
+            Sender::User => "You",
+            Sender::AI => "Sagacity",
+        };
+        let time = message
+            .timestamp
+            .map(|time| {
+                time.with_timezone(&Local)
+                    .format(", %Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let heading = format!("── #{} {}{} ", i + 1, speaker, time);
+        let fill = width.saturating_sub(heading.chars().count());
+        lines.push(format!("{}{}", heading, "─".repeat(fill)));
+        let mut body = scrubber.scrub(&message.content, report);
+        if !message.context_files.is_empty() {
+            body.push_str(&format!(
+                "\n\nContext: {}",
+                message.context_files.join(", ")
+            ));
+        }
+        for line in body.lines() {
+            if line.is_empty() {
+                lines.push(String::new());
+            } else {
+                lines.extend(textwrap::wrap(line, width).into_iter().map(String::from));
+            }
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_numbered_from_one_and_checked() {
+        assert_eq!(print_range("", 5), Ok(0..5));
+        assert_eq!(print_range("3", 5), Ok(2..3));
+        assert_eq!(print_range("2-4", 5), Ok(1..4));
+        assert!(print_range("4-2", 5).is_err());
+        assert!(print_range("6", 5).is_err());
+
+        let (scrubber, _) = Scrubber::new(&[]);
+        let messages = vec![Message::user("first"), Message::ai("second")];
+        let lines = transcript_lines(&messages, 1..2, 40, &scrubber, &mut ScrubReport::default());
+        assert!(lines[0].starts_with("── #2 Sagacity"));
+        assert_eq!(lines[0].chars().count(), 40);
+        assert_eq!(lines[1], "second");
+    }
+
+    #[test]
+    fn malformed_ranges_name_the_problem() {
+        assert_eq!(print_range("", 0), Ok(0..0));
+        assert_eq!(
+            print_range("0", 5),
+            Err("No message 0 (there are 5)".to_string())
+        );
+        assert_eq!(
+            print_range("-3", 5),
+            Err("No message  (there are 5)".to_string())
+        );
+        assert_eq!(
+            print_range("two", 5),
+            Err("No message two (there are 5)".to_string())
+        );
+        assert_eq!(
+            print_range("4-2", 5),
+            Err("Range 4-2 runs backwards".to_string())
+        );
+        assert_eq!(print_range("5-5", 5), Ok(4..5));
+    }
+
+    #[test]
+    fn printed_messages_are_scrubbed_wrapped_and_list_their_context() {
+        let (scrubber, _) = Scrubber::new(&[]);
+        let mut answer = Message::ai(
+            "Ask ops@example.com about it.\n\nThe retry loop backs off exponentially between attempts.",
+        );
+        answer.timestamp = None;
+        answer.context_files = vec!["src/retry.rs".to_string()];
+        let mut report = ScrubReport::default();
+        // Narrower than the minimum, so wrapped at 20
+        let lines = transcript_lines(&[answer], 0..1, 5, &scrubber, &mut report);
+        assert_eq!(
+            lines,
+            vec![
+                "── #1 Sagacity ─────",
+                "Ask [redacted email]",
+                "about it.",
+                "",
+                "The retry loop backs",
+                "off exponentially",
+                "between attempts.",
+                "",
+                "Context: src/",
+                "retry.rs",
+                "",
+            ]
+        );
+        assert_eq!(report.counts["email"], 1);
+    }
+}
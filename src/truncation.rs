@@ -0,0 +1,236 @@
+// src/truncation.rs
+use crate::chatbot::{complete_with_claude, Chatbot};
+use crate::constants::{DEFAULT_MAX_TOKENS, DEFAULT_MODEL};
+use crate::models::{estimate_tokens, find_model};
+use crate::sampling::Operation;
+
+// Conservative characters per token when cutting text to a budget; code tokenizes
+// denser than prose
+const CHARS_PER_TOKEN: usize = 3;
+// Tokens kept free for the system prompt and the request wrapper
+const PROMPT_OVERHEAD_TOKENS: usize = 2_000;
+// Below this there is no sensible way to send a long message
+pub const MIN_INPUT_BUDGET: usize = 500;
+
+/// Ways to send a message that doesn't fit the model's context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncationStrategy {
+    HeadTail,
+    SummarizeFirst,
+    Split,
+}
+
+pub const STRATEGIES: [TruncationStrategy; 3] = [
+    TruncationStrategy::HeadTail,
+    TruncationStrategy::SummarizeFirst,
+    TruncationStrategy::Split,
+];
+
+impl TruncationStrategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TruncationStrategy::HeadTail => "Keep the head and tail",
+            TruncationStrategy::SummarizeFirst => "Summarize first",
+            TruncationStrategy::Split => "Split across turns",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            TruncationStrategy::HeadTail => {
+                "Sends the beginning and the end, dropping the middle. Free and instant."
+            }
+            TruncationStrategy::SummarizeFirst => {
+                "Summarizes the content piece by piece, then sends the summary. Costs extra requests."
+            }
+            TruncationStrategy::Split => {
+                "Sends the content in parts; the model keeps notes on each until the last."
+            }
+        }
+    }
+}
+
+/// Tokens left for the message once retrieved context, the conversation so far and the
+/// answer are accounted for; None for models outside the catalogue
+pub fn input_budget(chatbot: &Chatbot) -> Option<usize> {
+    let info = find_model(chatbot.profile.model)?;
+    let history: usize = chatbot
+        .history()
+        .iter()
+        .map(|message| estimate_tokens(&message.content))
+        .sum();
+    Some(info.context_window.saturating_sub(
+        chatbot.profile.context_budget / CHARS_PER_TOKEN
+            + history
+            + info.max_output.min(DEFAULT_MAX_TOKENS)
+            + PROMPT_OVERHEAD_TOKENS,
+    ))
+}
+
+fn floor_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Keeps the first and last halves of the budget with a marker where the middle was
+pub fn head_tail(text: &str, budget: usize) -> String {
+    let keep = budget * CHARS_PER_TOKEN / 2;
+    if text.len() <= keep * 2 {
+        return text.to_string();
+    }
+    let head_end = floor_boundary(text, keep);
+    let tail_start = floor_boundary(text, text.len() - keep);
+    let omitted = text[head_end..tail_start].lines().count();
+    format!(
+        "{}\n[… {} lines omitted to fit the context window …]\n{}",
+        &text[..head_end],
+        omitted,
+        &text[tail_start..]
+    )
+}
+
+/// Cuts text into pieces of at most `budget` tokens, at line breaks where possible
+pub fn split_parts(text: &str, budget: usize) -> Vec<String> {
+    let max_chars = (budget * CHARS_PER_TOKEN).max(1);
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for line in text.split_inclusive('\n') {
+        let mut line = line;
+        // A single line longer than a whole part is cut mid-line
+        while line.len() > max_chars {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            let cut =
+                floor_boundary(line, max_chars).max(line.chars().next().map_or(1, char::len_utf8));
+            parts.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+        if current.len() + line.len() > max_chars {
+            parts.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Labels each piece so the model takes notes and waits for the last one before answering
+pub fn split_turns(text: &str, budget: usize) -> Vec<String> {
+    // Leave room for the label itself
+    let parts = split_parts(text, budget.saturating_sub(100).max(1));
+    let count = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let note = if i + 1 == count {
+                "This is the last part; now respond to the whole message."
+            } else {
+                "Reply only with brief notes on this part that you will need to answer at the end."
+            };
+            format!(
+                "[Part {} of {} of a long message. {}]\n{}",
+                i + 1,
+                count,
+                note,
+                part
+            )
+        })
+        .collect()
+}
+
+/// Summarizes the text piece by piece and joins the summaries, keeping any question
+/// or instruction verbatim
+pub async fn summarize_first(
+    text: &str,
+    budget: usize,
+    chatbot: &mut Chatbot,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let parts = split_parts(text, budget);
+    let api_key = chatbot.api_key.clone();
+    let mut summaries = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        let prompt = format!(
+            "The following is part {} of {} of a long message pasted into a chat about a codebase. Summarize it concisely, keeping identifiers, error messages and numbers exact. Copy any question or instruction addressed to the reader verbatim.\n\n{}",
+            i + 1,
+            parts.len(),
+            part
+        );
+        let summary = complete_with_claude(
+            &prompt,
+            DEFAULT_MODEL,
+            &api_key,
+            "Pasted content summary",
+            Operation::Summarize,
+            chatbot,
+        )
+        .await?;
+        summaries.push(summary);
+    }
+    let summary = format!(
+        "[Summary of a {}-line message too long to send in full]\n{}",
+        text.lines().count(),
+        summaries.join("\n\n")
+    );
+    // Summaries of very long pastes may still be too long
+    Ok(head_tail(&summary, budget))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn head_tail_keeps_both_ends_and_counts_the_omitted_lines() {
+        let text: String = (0..40).map(|i| format!("line {:02}\n", i)).collect();
+        assert_eq!(head_tail(&text, 1_000), text);
+
+        // 24 characters each side, three whole lines
+        let cut = head_tail(&text, 16);
+        assert!(cut.starts_with("line 00\nline 01\nline 02\n\n[… 34 lines omitted"));
+        assert!(cut.ends_with("context window …]\nline 37\nline 38\nline 39\n"));
+    }
+
+    #[test]
+    fn cuts_never_fall_inside_a_character() {
+        let text = "é".repeat(100);
+        let cut = head_tail(&text, 3);
+        assert!(cut.starts_with("éé\n"));
+        assert!(cut.ends_with("\néé"));
+
+        // A part smaller than one character still holds that character
+        let parts = split_parts("日本語", 0);
+        assert_eq!(parts, vec!["日", "本", "語"]);
+    }
+
+    #[test]
+    fn split_parts_breaks_at_lines_and_cuts_only_overlong_ones() {
+        let long_line = format!("{}\n", "x".repeat(20));
+        let text = format!("ab\ncd\n{}ef\n", long_line);
+        let parts = split_parts(&text, 3);
+        assert_eq!(parts.concat(), text);
+        assert!(parts.iter().all(|part| part.len() <= 9));
+        // Short lines share a part; the long one is cut mid-line and its rest goes on
+        assert_eq!(
+            parts,
+            vec!["ab\ncd\n", "xxxxxxxxx", "xxxxxxxxx", "xx\nef\n"]
+        );
+    }
+
+    #[test]
+    fn only_the_last_turn_asks_for_the_answer() {
+        let text = "word ".repeat(400);
+        let turns = split_turns(&text, 200);
+        assert!(turns.len() > 1);
+        let count = turns.len();
+        for (i, turn) in turns.iter().enumerate() {
+            assert!(turn.starts_with(&format!("[Part {} of {} ", i + 1, count)));
+            assert_eq!(turn.contains("now respond"), i + 1 == count);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// src/turn_latency.rs
+use serde::{Deserialize, Serialize};
+
+/// Where one turn's time went, from the key press to the last drawn delta, in ms. Kept
+/// with the answer so a slow turn can be traced to its stage afterwards.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TurnLatency {
+    // Linting and budget checks between Enter and the question going out
+    pub input_ms: u64,
+    pub retrieval_ms: u64,
+    // Reading and summarizing the context files into the prompt
+    pub context_ms: u64,
+    // Until the response headers arrived
+    pub network_ms: u64,
+    // From the headers to the first text delta
+    pub first_token_ms: u64,
+    // From the first delta to the end of the stream, drawing excluded
+    pub completion_ms: u64,
+    // Drawing the answer as it streamed in
+    pub render_ms: u64,
+}
+
+impl TurnLatency {
+    pub fn total_ms(&self) -> u64 {
+        self.stages().iter().map(|(_, ms)| ms).sum()
+    }
+
+    fn stages(&self) -> [(&'static str, u64); 7] {
+        [
+            ("input", self.input_ms),
+            ("retrieval", self.retrieval_ms),
+            ("context", self.context_ms),
+            ("network", self.network_ms),
+            ("first token", self.first_token_ms),
+            ("completion", self.completion_ms),
+            ("render", self.render_ms),
+        ]
+    }
+
+    /// "2.4s: input 3ms → retrieval 410ms → … (slowest: completion)"
+    pub fn summary(&self) -> String {
+        let stages = self.stages();
+        // On a tie the earlier stage is named, as it held up the later ones
+        let slowest = stages
+            .iter()
+            .rev()
+            .max_by_key(|(_, ms)| *ms)
+            .map_or("", |(stage, _)| stage);
+        format!(
+            "{}: {} (slowest: {})",
+            format_ms(self.total_ms()),
+            stages
+                .iter()
+                .map(|(stage, ms)| format!("{} {}", stage, format_ms(*ms)))
+                .collect::<Vec<_>>()
+                .join(" → "),
+            slowest
+        )
+    }
+}
+
+fn format_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_names_every_stage_and_the_slowest() {
+        let latency = TurnLatency {
+            input_ms: 3,
+            retrieval_ms: 410,
+            context_ms: 12,
+            network_ms: 300,
+            first_token_ms: 900,
+            completion_ms: 1500,
+            render_ms: 40,
+        };
+        assert_eq!(latency.total_ms(), 3165);
+        assert_eq!(
+            latency.summary(),
+            "3.2s: input 3ms → retrieval 410ms → context 12ms → network 300ms → \
+             first token 900ms → completion 1.5s → render 40ms (slowest: completion)"
+        );
+    }
+
+    #[test]
+    fn ties_name_the_earlier_stage_and_seconds_start_at_1000ms() {
+        let latency = TurnLatency {
+            retrieval_ms: 999,
+            network_ms: 999,
+            ..TurnLatency::default()
+        };
+        assert!(latency
+            .summary()
+            .starts_with("2.0s: input 0ms → retrieval 999ms"));
+        assert!(latency.summary().ends_with("(slowest: retrieval)"));
+        assert_eq!(format_ms(1000), "1.0s");
+        assert!(TurnLatency::default()
+            .summary()
+            .ends_with("(slowest: input)"));
+    }
+}
@@ -0,0 +1,77 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::ui::theme::bordered;
+use crate::App;
+
+/// Draws the persistent API audit log with its filter box
+pub fn draw_audit(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area);
+
+    let filter_title = if app.audit_filter_editing {
+        "Filter (editing)"
+    } else {
+        "Filter ('/' to edit)"
+    };
+    let filter = Paragraph::new(app.audit_filter.as_str())
+        .style(Style::default().fg(Color::LightYellow))
+        .block(bordered(app).title(filter_title));
+    f.render_widget(filter, chunks[0]);
+
+    let entries = app.filtered_audit_entries();
+    let total_cost: f64 = entries.iter().map(|e| e.cost).sum();
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            let style = if entry.response_status >= 400 {
+                Style::default().fg(Color::LightRed)
+            } else if entry.truncated {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(format!(
+                "{}  {:<22} {:<26} {:>3}  {:>6}ms  {:>6}→{:<6} ${:.4}{}",
+                entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                entry.request_summary,
+                entry.model,
+                entry.response_status,
+                entry.response_time_ms,
+                entry.input_tokens,
+                entry.output_tokens,
+                entry.cost,
+                if entry.truncated { "  [truncated]" } else { "" }
+            ))
+            .style(style)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            bordered(app)
+                .title(format!(
+                    "API Audit Log — {} calls, ${:.4}",
+                    entries.len(),
+                    total_cost
+                ))
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let mut state = ListState::default();
+    if !entries.is_empty() {
+        state.select(Some(app.audit_scroll.min(entries.len() - 1)));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
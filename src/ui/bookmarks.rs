@@ -0,0 +1,65 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::theme::{bordered, highlight_symbol};
+use crate::App;
+
+/// Draws the jump list of bookmarked transcript messages
+pub fn draw_bookmarks(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let block = bordered(app)
+        .title("Bookmarks")
+        .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
+
+    if app.bookmarks.is_empty() {
+        let paragraph = Paragraph::new(
+            "🔖 No bookmarks yet.\n\nIn chat, press Tab to focus the transcript and 'm' to bookmark a message.",
+        )
+        .block(block)
+        .style(Style::default().fg(Color::White))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .bookmarks
+        .iter()
+        .map(|bookmark| {
+            let preview = app
+                .messages
+                .get(bookmark.message_index)
+                .map(|m| m.content.lines().next().unwrap_or("").to_string())
+                .unwrap_or_default();
+            let label = if bookmark.annotation.is_empty() {
+                format!("#{:<4} {}", bookmark.message_index + 1, preview)
+            } else {
+                format!(
+                    "#{:<4} {} — {}",
+                    bookmark.message_index + 1,
+                    bookmark.annotation,
+                    preview
+                )
+            };
+            ListItem::new(label).style(Style::default().fg(Color::White))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_bookmark));
+    f.render_stateful_widget(list, area, &mut state);
+}
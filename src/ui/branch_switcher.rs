@@ -0,0 +1,56 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+
+use crate::ui::theme::{bordered, highlight_symbol};
+use crate::App;
+
+/// Draws the git branch and worktree switcher
+pub fn draw_branch_switcher(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let items: Vec<ListItem> = app
+        .branch_list
+        .iter()
+        .map(|branch| {
+            let marker = if app.active_branch.as_ref() == Some(&branch.name) {
+                "●"
+            } else {
+                " "
+            };
+            let location = match &branch.worktree {
+                Some(path) => format!("worktree {}", path.display()),
+                None => "no worktree".to_string(),
+            };
+            ListItem::new(format!(
+                "{} {:<32} {:<10} {}",
+                marker,
+                branch.name,
+                &branch.commit[..branch.commit.len().min(8)],
+                location
+            ))
+            .style(Style::default().fg(Color::White))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            bordered(app)
+                .title("Branches")
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    if !app.branch_list.is_empty() {
+        state.select(Some(app.selected_branch));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
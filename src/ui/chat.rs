@@ -1,86 +1,573 @@
+use chrono::{DateTime, Local, Utc};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
 
-use crate::App;
+use crate::answer_error::AnswerError;
+use crate::profiler;
+use crate::projects::project_of;
+use crate::rust_check::RustCheck;
+use crate::structured::StructuredAnswer;
+use crate::turn_latency::TurnLatency;
+use crate::ui::geometry::{inner_width, input_cursor};
+use crate::ui::highlight::rendered_markdown;
+use crate::ui::theme::{accessible, bordered, focus_title, highlight_symbol};
+use crate::ui::time::relative_time;
+use crate::{App, ChatFocus};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+// Speaker bar plus a relative timestamp column
+const GUTTER_WIDTH: usize = 11;
+// Quoted lines kept in a reply, and how many of them are previewed above the input
+pub const MAX_QUOTE_LINES: usize = 12;
+const QUOTE_PREVIEW_LINES: usize = 2;
+// Widest column focus mode reads in, however wide the terminal
+const FOCUS_COLUMN_WIDTH: u16 = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Sender {
     User,
     AI,
 }
 
 /// Represents a chat message
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
     pub sender: Sender,
     pub content: String,
+    // Files that were in context when an AI answer was generated
+    #[serde(default)]
+    pub context_files: Vec<String>,
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+    // Title of the session a merged message originally came from
+    #[serde(default)]
+    pub origin: Option<String>,
+    // Shown as raw markdown source instead of rendered, toggled per message
+    #[serde(default)]
+    pub raw: bool,
+    // `cargo check` results of the answer's Rust blocks, in order
+    #[serde(default)]
+    pub rust_checks: Vec<RustCheck>,
+    // Streaming was cut off by the per-turn time budget
+    #[serde(default)]
+    pub timed_out: bool,
+    // The parsed reply when structured answers were on; `content` holds its rendering
+    #[serde(default)]
+    pub structured: Option<StructuredAnswer>,
+    // Scratchpad notes that were sent along with the question
+    #[serde(default)]
+    pub scratchpad: Option<String>,
+    // Context files whose summary predated their contents when the answer was given
+    #[serde(default)]
+    pub stale_summaries: Vec<String>,
+    // Context was the previous turn's files, reused for a follow-up question
+    #[serde(default)]
+    pub carried_over: bool,
+    // Answered from file summaries while the full files are read in the background, or
+    // the answer that reading them produced
+    #[serde(default)]
+    pub from_summaries: bool,
+    #[serde(default)]
+    pub refined: bool,
+    // Served from the answer cache instead of asking the model
+    #[serde(default)]
+    pub cached: bool,
+    // The repository map a session opened with; the model sees it in the system prompt
+    #[serde(default)]
+    pub repo_map: bool,
+    // The model fast mode sent the question to, when not the profile's
+    #[serde(default)]
+    pub routed_model: Option<String>,
+    // Time spent in each stage of the turn that produced an answer
+    #[serde(default)]
+    pub latency: Option<TurnLatency>,
+    // Set on a failed turn in place of an answer; `content` holds its headline
+    #[serde(default)]
+    pub error: Option<AnswerError>,
+}
+
+impl Message {
+    pub fn user(content: impl Into<String>) -> Self {
+        Message {
+            sender: Sender::User,
+            content: content.into(),
+            context_files: Vec::new(),
+            timestamp: Some(Utc::now()),
+            origin: None,
+            raw: false,
+            rust_checks: Vec::new(),
+            timed_out: false,
+            structured: None,
+            scratchpad: None,
+            stale_summaries: Vec::new(),
+            carried_over: false,
+            from_summaries: false,
+            refined: false,
+            cached: false,
+            repo_map: false,
+            routed_model: None,
+            latency: None,
+            error: None,
+        }
+    }
+
+    pub fn ai(content: impl Into<String>) -> Self {
+        Message {
+            sender: Sender::AI,
+            content: content.into(),
+            context_files: Vec::new(),
+            timestamp: Some(Utc::now()),
+            origin: None,
+            raw: false,
+            rust_checks: Vec::new(),
+            timed_out: false,
+            structured: None,
+            scratchpad: None,
+            stale_summaries: Vec::new(),
+            carried_over: false,
+            from_summaries: false,
+            refined: false,
+            cached: false,
+            repo_map: false,
+            routed_model: None,
+            latency: None,
+            error: None,
+        }
+    }
+
+    /// A failed turn, shown as an error that can be retried
+    pub fn error(error: AnswerError) -> Self {
+        Message {
+            content: error.headline(),
+            error: Some(error),
+            ..Message::ai("")
+        }
+    }
+}
+
+/// Markdown blockquote of a message, cut to `max_lines` lines
+pub fn blockquote(content: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = content.trim().lines().collect();
+    let mut quoted: Vec<String> = lines
+        .iter()
+        .take(max_lines)
+        .map(|line| format!("> {}", line).trim_end().to_string())
+        .collect();
+    if lines.len() > max_lines {
+        quoted.push("> …".to_string());
+    }
+    quoted.join("\n")
 }
 
 pub fn draw_chat(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let _span = profiler::span("draw chat");
+    // Focus mode reads from a centered column, leaving the margins empty
+    let area = if app.focus_mode {
+        let width = area.width.min(FOCUS_COLUMN_WIDTH);
+        Rect {
+            x: area.x + (area.width - width) / 2,
+            width,
+            ..area
+        }
+    } else {
+        area
+    };
     // Create a block for the chat background
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title("Chat")
+    let mut title = match &app.remote_project {
+        Some(project) => format!("Chat [remote · read-only] {}", project.url),
+        None => "Chat".to_string(),
+    };
+    if let Some(branch) = &app.active_branch {
+        title.push_str(&format!(" [branch: {}]", branch));
+    }
+    if app.focus_mode {
+        title = "Focus mode · Ctrl+F to leave".to_string();
+    }
+    let block = bordered(app)
+        .title(title)
         .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
 
     f.render_widget(block, area);
 
+    let quote_rows =
+        app.reply_quote
+            .as_ref()
+            .map_or(0, |quote| quote.lines().count().min(QUOTE_PREVIEW_LINES)) as u16;
+    let chip_rows = u16::from(!app.query_suggestions.is_empty());
+
     // Split chat area into message view and input
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints(
             [
-                Constraint::Min(1),    // Messages
-                Constraint::Length(3), // Input
+                Constraint::Min(1),                 // Messages
+                Constraint::Length(chip_rows),      // Suggested rewrites of a weak question
+                Constraint::Length(3 + quote_rows), // Input, below the quote being replied to
             ]
             .as_ref(),
         )
         .split(area);
 
-    // Render messages
+    // Render messages, wrapped to the current width so resizes reflow the transcript
+    let gutter = app.config.transcript_gutter && !app.focus_mode;
+    let gutter_width = if gutter { GUTTER_WIDTH } else { 0 };
+    let wrap_width = inner_width(chunks[0])
+        .saturating_sub(1 + gutter_width)
+        .max(1);
+    let local_day = |msg: &Message| msg.timestamp.map(|t| t.with_timezone(&Local).date_naive());
+    let days: Vec<_> = app.messages.iter().filter_map(local_day).collect();
+    let spans_days = days.first() != days.last();
     let messages: Vec<ListItem> = app
         .messages
         .iter()
-        .map(|msg| {
-            let prefix = match msg.sender {
-                Sender::User => "💬 You: ",
-                Sender::AI => "🤖 AI: ",
+        .enumerate()
+        .map(|(i, msg)| {
+            let prefix = match (&msg.sender, accessible(app)) {
+                _ if msg.error.is_some() && !accessible(app) => "⚠ ",
+                _ if msg.error.is_some() => "Failed: ",
+                (Sender::User, false) => "💬 You: ",
+                (Sender::AI, false) => "🤖 AI: ",
+                (Sender::User, true) => "You: ",
+                (Sender::AI, true) => "AI: ",
+            };
+            let (bookmark_marker, note_marker, stale_marker) = if accessible(app) {
+                ("[bookmark] ", "Note:", "[stale]")
+            } else {
+                ("🔖 ", "✎", "⟳")
             };
-            ListItem::new(format!("{}{}", prefix, msg.content)).style(
-                Style::default()
-                    .fg(match msg.sender {
-                        Sender::User => Color::LightGreen,
-                        Sender::AI => Color::LightBlue,
+            // Provenance of messages merged in from another session
+            let prefix = match &msg.origin {
+                Some(origin) => format!("[from {}] {}", origin, prefix),
+                None => prefix.to_string(),
+            };
+            let prefix = if msg.refined {
+                format!("[refined] {}", prefix)
+            } else if msg.cached {
+                format!("[cached] {}", prefix)
+            } else {
+                prefix
+            };
+            let header = match app.bookmark_for(i) {
+                Some(_) => format!("{}{}", bookmark_marker, prefix),
+                None => prefix,
+            };
+            let mut trailer = String::new();
+            if let Some(bookmark) = app.bookmark_for(i) {
+                if !bookmark.annotation.is_empty() {
+                    trailer.push_str(&format!("\n   {} {}", note_marker, bookmark.annotation));
+                }
+            }
+            if msg.cached {
+                trailer.push_str("\n   Cached answer to the same question and context; /regen asks again");
+            }
+            if let Some(model) = &msg.routed_model {
+                trailer.push_str(&format!("\n   Fast mode: answered by {}", model));
+            }
+            // The latency breakdown of the selected answer
+            if let Some(latency) = msg.latency.as_ref().filter(|_| app.selected_message == Some(i)) {
+                trailer.push_str(&format!("\n   Latency {}", latency.summary()));
+            }
+            // Errors expand to the full report when selected, with the retry key
+            if let Some(error) = &msg.error {
+                if app.selected_message == Some(i) {
+                    trailer.push_str(&format!(
+                        "\n   Details: {}\n   Press r to retry \"{}\"",
+                        error.detail, error.question
+                    ));
+                } else {
+                    trailer.push_str("\n   Select for details and retry");
+                }
+            }
+            if msg.timed_out {
+                let clock = if accessible(app) { "Timed out:" } else { "⏱" };
+                trailer.push_str(&format!(
+                    "\n   {} cut off by the time budget; /continue picks it up",
+                    clock
+                ));
+            }
+            // Pasted notes are listed apart from the retrieved files
+            if let Some(notes) = &msg.scratchpad {
+                let marker = if accessible(app) { "Scratchpad:" } else { "📋" };
+                let first = notes.lines().next().unwrap_or("");
+                trailer.push_str(&format!(
+                    "\n   {} {} lines of pasted notes: {}",
+                    marker,
+                    notes.lines().count(),
+                    first.chars().take(60).collect::<String>()
+                ));
+            }
+            // Context files of an answer, badged when privacy rules limited what was sent
+            if !msg.context_files.is_empty() {
+                let files: Vec<String> = msg
+                    .context_files
+                    .iter()
+                    .map(|file| {
+                        let mut label = match project_of(&app.chatbot.projects, file) {
+                            Some(project) => format!("{}: {}", project.name, file),
+                            None => file.clone(),
+                        };
+                        if let Some(badge) = app.chatbot.privacy.level_for(file).badge() {
+                            label.push_str(&format!(" {}", badge));
+                        }
+                        if msg.stale_summaries.contains(file) {
+                            label.push_str(&format!(" {}", stale_marker));
+                        }
+                        label
                     })
-                    .add_modifier(Modifier::ITALIC),
-            )
+                    .collect();
+                let carried = if msg.carried_over {
+                    " (carried over; /fresh to search again)"
+                } else if msg.from_summaries {
+                    " (summaries only)"
+                } else if msg.refined {
+                    " (refined from full contents)"
+                } else {
+                    ""
+                };
+                trailer.push_str(&format!("\n   Context{}: {}", carried, files.join(", ")));
+                // Summary freshness: the answer may rest on an outdated understanding
+                if !msg.stale_summaries.is_empty() {
+                    trailer.push_str(&format!(
+                        "\n   Summaries: {} of {} changed since summarized ({}); reindex to refresh",
+                        msg.stale_summaries.len(),
+                        msg.context_files.len(),
+                        stale_marker
+                    ));
+                }
+            }
+            // Focus mode keeps to what was said
+            if app.focus_mode {
+                trailer.clear();
+            }
+            // Raw markdown source when toggled for this message, rendered otherwise
+            let mut body: Vec<Line> = if msg.raw {
+                textwrap::wrap(&format!("{}{}{}", header, msg.content, trailer), wrap_width)
+                    .iter()
+                    .map(|row| Line::from(row.to_string()))
+                    .collect()
+            } else {
+                let focused_block = app
+                    .selected_code_block
+                    .filter(|(message, _)| *message == i && app.selected_message == Some(i))
+                    .filter(|_| app.chat_focus == ChatFocus::Transcript)
+                    .map(|(_, block)| block);
+                let rendered = rendered_markdown(
+                    &msg.content,
+                    wrap_width.saturating_sub(header.width()),
+                    accessible(app),
+                    &msg.rust_checks,
+                    app.expanded_check == Some(i),
+                    focused_block,
+                );
+                let mut body = rendered.lines;
+                match body.first_mut() {
+                    Some(first) => first.spans.insert(0, Span::raw(header)),
+                    None => body.push(Line::from(header)),
+                }
+                // The list scrolls whole messages, so rows above a focused block deep in
+                // a long message are folded away to bring the block into view
+                if let Some(row) = rendered.focused_row.filter(|&row| row > 1) {
+                    let fold = if accessible(app) { "..." } else { "⋮" };
+                    body.drain(1..row);
+                    body.insert(
+                        1,
+                        Line::from(Span::styled(
+                            format!("   {} {} lines above", fold, row - 1),
+                            Style::default().fg(Color::DarkGray),
+                        )),
+                    );
+                }
+                if !trailer.is_empty() {
+                    body.extend(
+                        textwrap::wrap(trailer.trim_start_matches('\n'), wrap_width)
+                            .iter()
+                            .map(|row| Line::from(row.to_string())),
+                    );
+                }
+                body
+            };
+            let speaker_color = match msg.sender {
+                _ if msg.error.is_some() => Color::LightRed,
+                Sender::User => Color::LightGreen,
+                Sender::AI => Color::LightBlue,
+            };
+            let style = Style::default()
+                .fg(speaker_color)
+                .add_modifier(Modifier::ITALIC);
+            for line in body.iter_mut() {
+                for span in line.spans.iter_mut() {
+                    span.style = style.patch(span.style);
+                }
+            }
+            if !gutter {
+                return ListItem::new(Text::from(body));
+            }
+
+            // Speaker bar and relative time beside the message, with a separator
+            // above the first message of each day
+            let mut lines: Vec<Line> = Vec::new();
+            let day = local_day(msg);
+            let previous_day = i
+                .checked_sub(1)
+                .and_then(|prev| local_day(&app.messages[prev]));
+            if let Some(day) = day {
+                if spans_days && previous_day != Some(day) {
+                    lines.push(Line::from(Span::styled(
+                        format!("── {} ──", day.format("%a, %b %-d %Y")),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+            }
+            let bar = if accessible(app) { "| " } else { "▌ " };
+            let time = msg.timestamp.map(relative_time).unwrap_or_default();
+            for (n, row) in body.into_iter().enumerate() {
+                let time_column = if n == 0 { time.as_str() } else { "" };
+                let mut spans = vec![
+                    Span::styled(bar, Style::default().fg(speaker_color)),
+                    Span::styled(
+                        format!("{:<width$}", time_column, width = GUTTER_WIDTH - 2),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ];
+                spans.extend(row.spans);
+                lines.push(Line::from(spans));
+            }
+            ListItem::new(Text::from(lines))
         })
         .collect();
 
     let messages_list = List::new(messages)
         .block(Block::default())
         .style(Style::default())
-        .highlight_style(Style::default())
-        .highlight_symbol("");
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    // Only show the selection while the transcript has focus
+    let mut list_state = ListState::default();
+    if matches!(
+        app.chat_focus,
+        ChatFocus::Transcript | ChatFocus::Annotation
+    ) {
+        list_state.select(app.selected_message);
+    }
 
-    f.render_widget(messages_list, chunks[0]);
+    f.render_stateful_widget(messages_list, chunks[0], &mut list_state);
 
-    // Render input box
-    let input = Paragraph::new(app.input.as_str())
+    // Render input box, which doubles as the annotation and scratchpad editor; the
+    // scratchpad shows the line being typed and its size in the title
+    let scratchpad_lines = app.scratchpad.lines().count();
+    let pinned = if app.scratchpad_pinned {
+        ", pinned"
+    } else {
+        ""
+    };
+    // While recalling history, which entry is shown and the prefix it matched
+    let history = match app.input_history.position() {
+        Some((index, total)) if app.input_history.prefix().is_empty() => {
+            format!(" [history {}/{}]", index, total)
+        }
+        Some((index, total)) => format!(
+            " [history {}/{} starting \"{}\"]",
+            index,
+            total,
+            app.input_history.prefix()
+        ),
+        None => String::new(),
+    };
+    let (input_text, input_title) = match app.chat_focus {
+        ChatFocus::Annotation => (app.annotation_input.as_str(), "Annotation".to_string()),
+        ChatFocus::Scratchpad => (
+            app.scratchpad.rsplit('\n').next().unwrap_or(""),
+            format!(
+                "Scratchpad ({} lines{}) - sent with the next question",
+                scratchpad_lines, pinned
+            ),
+        ),
+        _ if !app.scratchpad.trim().is_empty() => (
+            app.input.as_str(),
+            format!(
+                "Input [+ scratchpad, {} lines{}]{}",
+                scratchpad_lines, pinned, history
+            ),
+        ),
+        _ => (app.input.as_str(), format!("Input{}", history)),
+    };
+    // A quoted reply is previewed above the typed text, one row per quoted line
+    let mut input_lines: Vec<Line> = Vec::new();
+    if let (Some(quote), false) = (&app.reply_quote, app.chat_focus == ChatFocus::Annotation) {
+        let width = inner_width(chunks[2]);
+        for line in quote.lines().take(QUOTE_PREVIEW_LINES) {
+            input_lines.push(Line::from(Span::styled(
+                line.chars().take(width).collect::<String>(),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+    let input_rows = input_lines.len() as u16;
+    input_lines.push(Line::from(input_text));
+    let input = Paragraph::new(Text::from(input_lines))
         .style(Style::default().fg(Color::LightYellow))
-        .block(Block::default().borders(Borders::ALL).title("Input"))
+        .block(bordered(app).title(focus_title(
+            app,
+            input_title,
+            app.chat_focus != ChatFocus::Transcript && app.chat_focus != ChatFocus::Viewer,
+        )))
         .alignment(Alignment::Left)
         .wrap(Wrap { trim: true });
 
-    f.render_widget(input, chunks[1]);
+    f.render_widget(input, chunks[2]);
+    draw_suggestion_chips(f, chunks[1], app);
+
+    // Set cursor position, on the row below the quote preview
+    if matches!(
+        app.chat_focus,
+        ChatFocus::Input | ChatFocus::Annotation | ChatFocus::Scratchpad
+    ) {
+        let input_area = Rect {
+            y: chunks[2].y.saturating_add(input_rows),
+            height: chunks[2].height.saturating_sub(input_rows),
+            ..chunks[2]
+        };
+        let (x, y) = input_cursor(input_area, input_text);
+        f.set_cursor_position((x, y));
+    }
+}
 
-    // Set cursor position
-    let x = chunks[1].x + app.input.len() as u16 + 1;
-    let y = chunks[1].y + 1;
-    f.set_cursor(x, y);
+// Suggested rewrites of a question that found nothing or was answered with low
+// confidence, one chip each, the selected one highlighted
+fn draw_suggestion_chips(f: &mut Frame<'_>, area: Rect, app: &App) {
+    if app.query_suggestions.is_empty() {
+        return;
+    }
+    let mut spans = vec![Span::styled("Try: ", Style::default().fg(Color::DarkGray))];
+    for (i, suggestion) in app.query_suggestions.iter().enumerate() {
+        let selected = i == app.selected_suggestion;
+        let chip = match (accessible(app), selected) {
+            (true, true) => format!("> [{}]", suggestion.label),
+            (true, false) => format!("[{}]", suggestion.label),
+            (false, _) => format!(" {} ", suggestion.label),
+        };
+        let style = if selected {
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().bg(Color::DarkGray).fg(Color::White)
+        };
+        spans.push(Span::styled(chip, style));
+        spans.push(Span::raw(" "));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
 }
@@ -0,0 +1,62 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::theme::{bordered, highlight_symbol};
+use crate::App;
+
+/// Draws the picker over the recently copied snippets, newest first
+pub fn draw_clipboard_ring(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let block = bordered(app)
+        .title("Clipboard")
+        .style(Style::default().fg(Color::LightCyan).bg(Color::Black));
+
+    if app.clipboard_ring.is_empty() {
+        let paragraph = Paragraph::new(
+            "📋 Nothing copied yet.\n\nIn chat, press Tab to focus the transcript and 'y' to copy a message.",
+        )
+        .block(block)
+        .style(Style::default().fg(Color::White))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .clipboard_ring
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let preview = entry
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or("")
+                .trim();
+            let lines = entry.lines().count();
+            let label = if lines > 1 {
+                format!("{:>2}. {} (+{} lines)", i + 1, preview, lines - 1)
+            } else {
+                format!("{:>2}. {}", i + 1, preview)
+            };
+            ListItem::new(label).style(Style::default().fg(Color::White))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_clip));
+    f.render_stateful_widget(list, area, &mut state);
+}
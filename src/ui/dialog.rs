@@ -0,0 +1,113 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::dialog::{Dialog, DialogKind};
+use crate::ui::theme::{accessible, bordered};
+use crate::App;
+
+// Width of the dialog box, narrowed to fit small terminals
+const DIALOG_WIDTH: u16 = 64;
+// Completions listed under the input as it's typed
+const SHOWN_COMPLETIONS: usize = 5;
+
+/// Draws a dialog centered over the body area
+pub fn draw_dialog(f: &mut Frame<'_>, area: Rect, app: &App, dialog: &Dialog) {
+    let mut text: Vec<Line> = dialog
+        .message
+        .lines()
+        .map(|line| Line::from(line.to_string()))
+        .collect();
+    text.push(Line::from(""));
+
+    if dialog.kind == DialogKind::Input {
+        text.push(Line::from(Span::styled(
+            format!("> {}_", dialog.input),
+            Style::default().fg(Color::LightGreen),
+        )));
+        if !dialog.input.is_empty() && !dialog.completions.is_empty() {
+            let matches = dialog.matching_completions();
+            for completion in matches.iter().take(SHOWN_COMPLETIONS) {
+                text.push(Line::from(Span::styled(
+                    format!("  {}", completion),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            if matches.len() > SHOWN_COMPLETIONS {
+                text.push(Line::from(Span::styled(
+                    format!(
+                        "  … {} more; Tab completes",
+                        matches.len() - SHOWN_COMPLETIONS
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+        }
+        text.push(Line::from(""));
+    }
+
+    // Accessibility mode marks the highlighted button in text as well as color
+    let buttons: Vec<Span> = dialog
+        .buttons()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, label)| {
+            let label = app.catalog.tr(label);
+            let numbered = match dialog.kind {
+                DialogKind::Choose(_) if i + 1 < dialog.buttons().len() => {
+                    format!("{}. {}", i + 1, label)
+                }
+                _ => label.to_string(),
+            };
+            let button = if i == dialog.selected {
+                Span::styled(
+                    if accessible(app) {
+                        format!("> [{}] <", numbered)
+                    } else {
+                        format!("[ {} ]", numbered)
+                    },
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::LightMagenta)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                Span::styled(
+                    format!("[ {} ]", numbered),
+                    Style::default().fg(Color::White),
+                )
+            };
+            [button, Span::raw("  ")]
+        })
+        .collect();
+    text.push(Line::from(buttons));
+
+    let width = DIALOG_WIDTH.min(area.width);
+    let inner_width = width.saturating_sub(2).max(1) as usize;
+    let rows: usize = text
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(inner_width))
+        .sum();
+    let height = (rows as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Color::White))
+        .block(
+            bordered(app)
+                .title(dialog.title.clone())
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: false });
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
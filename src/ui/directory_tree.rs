@@ -2,8 +2,6 @@
 
 use ratatui::{
     layout::Rect,
-    prelude::Backend,
-    style::{Color, Modifier, Style},
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
@@ -0,0 +1,125 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::index_snapshot::IndexDiff;
+use crate::profiler;
+use crate::ui::geometry::inner_height;
+use crate::ui::theme::{accessible, bordered, focus_title};
+use crate::{App, ChatFocus};
+
+/// Draws the split-screen file viewer, scrolled to the lines the latest answer cites
+pub fn draw_file_viewer(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let _span = profiler::span("draw file viewer");
+    let Some(viewer) = &app.file_viewer else {
+        let empty = Paragraph::new("No cited file yet. Ask a question about the code.")
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center)
+            .block(bordered(app).title("File Viewer"));
+        f.render_widget(empty, area);
+        return;
+    };
+
+    if let Some(index_diff) = &viewer.index_diff {
+        draw_index_diff(f, area, app, &viewer.path, index_diff, viewer.scroll);
+        return;
+    }
+
+    let visible = inner_height(area);
+    let number_width = viewer.lines.len().to_string().len();
+    let focused = app.chat_focus == ChatFocus::Viewer;
+    let (selection_start, selection_end) = viewer.selection();
+    let lines: Vec<Line> = viewer
+        .lines
+        .iter()
+        .enumerate()
+        .skip(viewer.scroll)
+        .take(visible)
+        .map(|(i, text)| {
+            let cited = viewer
+                .highlighted
+                .is_some_and(|(start, end)| i >= start && i <= end);
+            let selected = focused && i >= selection_start && i <= selection_end;
+            // A textual marker as well, so neither range relies on color alone
+            let marker = match (selected, cited, accessible(app)) {
+                (true, _, true) => "*",
+                (true, _, false) => "┃",
+                (false, true, true) => ">",
+                (false, true, false) => "▶",
+                (false, false, _) => " ",
+            };
+            let style = if selected {
+                Style::default().fg(Color::White).bg(Color::Blue)
+            } else if cited {
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{}{:>width$} ", marker, i + 1, width = number_width),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(text.clone(), style),
+            ])
+        })
+        .collect();
+
+    let title = match viewer.highlighted {
+        Some((start, end)) if start == end => format!("{} (line {})", viewer.path, start + 1),
+        Some((start, end)) => format!("{} (lines {}-{})", viewer.path, start + 1, end + 1),
+        None => viewer.path.clone(),
+    };
+    let paragraph = Paragraph::new(lines).block(
+        bordered(app)
+            .title(focus_title(app, title, focused))
+            .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+    );
+    f.render_widget(paragraph, area);
+}
+
+// Changes since the summarized version, with + and - kept so they don't rely on color
+fn draw_index_diff(
+    f: &mut Frame<'_>,
+    area: Rect,
+    app: &App,
+    path: &str,
+    index_diff: &IndexDiff,
+    scroll: usize,
+) {
+    let rows: Vec<&str> = index_diff.diff.lines().collect();
+    let lines: Vec<Line> = rows
+        .iter()
+        .skip(scroll.min(rows.len().saturating_sub(1)))
+        .take(inner_height(area))
+        .map(|row| {
+            let color = if row.starts_with("@@") {
+                Color::Cyan
+            } else if row.starts_with('+') {
+                Color::Green
+            } else if row.starts_with('-') {
+                Color::Red
+            } else {
+                Color::Gray
+            };
+            Line::from(Span::styled(row.to_string(), Style::default().fg(color)))
+        })
+        .collect();
+    let title = format!(
+        "{} since indexed (+{} -{})",
+        path, index_diff.added, index_diff.removed
+    );
+    let paragraph = Paragraph::new(lines).block(
+        bordered(app)
+            .title(title)
+            .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+    );
+    f.render_widget(paragraph, area);
+}
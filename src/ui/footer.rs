@@ -1,4 +1,6 @@
-use crate::{App, AppState};
+use crate::dialog::DialogKind;
+use crate::ui::theme::{accessible, monochrome, status_prefix};
+use crate::{App, AppState, ChatFocus};
 use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Style},
@@ -8,15 +10,116 @@ use ratatui::{
 /// Draws the footer with dynamic instructions
 pub fn draw_footer(f: &mut Frame<'_>, area: Rect, app: &App) {
     let instructions = match app.state {
+        AppState::MainMenu if app.briefing.is_some() => {
+            "Use Up/Down arrows to navigate, Enter to select, 'b' for what changed since your last session, 'q' or Esc to quit."
+        }
         AppState::MainMenu => {
             "Use Up/Down arrows to navigate, Enter to select, 'q' or Esc to quit."
         }
-        AppState::Chat => "Type your message and press Enter to send. Esc to return to main menu.",
+        AppState::Chat => match app.chat_focus {
+            ChatFocus::Input => {
+                "Type your message and press Enter to send, Ctrl+Up/Down to recall earlier ones, Ctrl+N to complete an identifier, Left/Right and Enter on an empty input to resend with a suggestion. Tab to browse the transcript, Ctrl+B for bookmarks, Ctrl+G for branches, Ctrl+T for split view, Ctrl+L for the outline, Ctrl+F for focus mode, Ctrl+S for the scratchpad, Esc to return to main menu."
+            }
+            ChatFocus::Transcript => {
+                "Up/Down to select, '[' and ']' to jump between code blocks, 'm' to bookmark, 'a' to annotate, '+' if an answer helped, 'r' to quote in a reply, '?' to ask why a command failed, 'y' to copy it or the focused code block, 'g' to upload either as a gist, 'w' to write its labeled files, 's' for raw markdown, 'c' for compile errors, PgUp/PgDn to scroll the file, Ctrl+B for bookmarks, Tab to type."
+            }
+            ChatFocus::Annotation => "Type an annotation and press Enter to save, Esc to cancel.",
+            ChatFocus::Scratchpad => {
+                "Paste or type notes for the next question; Enter adds a line. Ctrl+P to pin them to every question, Ctrl+S or Esc to go back."
+            }
+            ChatFocus::Viewer => {
+                "Up/Down to move, 'v' to start or clear a selection, 'e' to explain the selected lines, 'i' for changes since indexing, Tab/Esc to type."
+            }
+            ChatFocus::Outline => {
+                "j/k or Up/Down to move between turns, Enter to jump the transcript to one, Ctrl+L to close the outline, Tab/Esc to type."
+            }
+        },
+        AppState::SessionSwitcher => {
+            "Up/Down or Ctrl+O to cycle, Enter to switch, 'n' for a new session, 'm' to merge it with the current one, 'd' to delete it, 'x' to mark it and 'e' to export the marked sessions as JSON, Esc to go back."
+        }
+        AppState::StartupError => match app.data_dir_input {
+            Some(_) => "Type the new data directory and press Enter, Esc to cancel.",
+            None => "Up/Down to choose an action, Enter to run it.",
+        },
+        AppState::RemoteRepo => {
+            "Paste a git URL and press Enter to clone and index it. Esc to return to main menu."
+        }
+        AppState::Audit => {
+            "Up/Down/PgUp/PgDn to scroll, '/' to filter by purpose, model or status, '?' to ask about the entry, Esc to return."
+        }
+        AppState::BranchSwitcher => {
+            "Up/Down to choose a branch, Enter to switch and load its index, Esc to go back."
+        }
+        AppState::Todos => {
+            "Up/Down to move, Enter to open the file at the comment beside the chat, Esc to go back."
+        }
+        AppState::Projects => {
+            "Up/Down to move, Space to select the projects questions should span, Enter to apply, Esc to go back."
+        }
+        AppState::Usage => "Usage is tracked on this machine only. 'e' to export API usage as CSV, Esc to return to main menu.",
+        AppState::Bookmarks => "Up/Down to navigate, Enter to jump to the message, Esc to go back.",
+        AppState::ClipboardRing => {
+            "Up/Down to choose a copied snippet, Enter to copy it again, 'i' to insert it into the input, Esc to go back."
+        }
+        AppState::RefactorPlan => {
+            "Up/Down to choose a step, Space to tick it off, Enter to generate its edits, Esc to return to chat."
+        }
+        AppState::Sweep => {
+            "Up/Down to pick a file, PgUp/PgDn to scroll its diff, Space to include it, 'a' for all, Enter to apply, Esc to return to chat."
+        }
+        AppState::Heatmap => {
+            "Arrows to step through files from largest to smallest, Esc to return to chat."
+        }
+        AppState::Truncation => {
+            "Up/Down to choose how to shorten the message, Enter to send it, Esc to keep editing."
+        }
+        AppState::LockConflict => {
+            "Press 'r' to attach read-only, 't' to take over the lock or 'q' to quit."
+        }
+        AppState::Dialog => match app.dialog.as_ref().map(|dialog| &dialog.kind) {
+            Some(DialogKind::Input)
+                if app
+                    .dialog
+                    .as_ref()
+                    .is_some_and(|dialog| !dialog.completions.is_empty()) =>
+            {
+                "Type to narrow the matches, Tab to complete, Enter to confirm, Esc to cancel."
+            }
+            Some(DialogKind::Input) => {
+                "Edit the text, Tab to pick OK or Cancel, Enter to confirm, Esc to cancel."
+            }
+            Some(DialogKind::Choose(_)) => {
+                "Left/Right or Tab to choose, a number to pick directly, Enter to confirm, Esc to cancel."
+            }
+            _ => "Left/Right or Tab to choose, Enter to confirm, 'y' to accept, 'n' or Esc to cancel.",
+        },
+        AppState::WhatsNew => {
+            "Up/Down or PgUp/PgDn to scroll, Enter or Esc to dismiss. Reopen it from the main menu."
+        }
+        AppState::RestoreDraft => "Press 'y' to restore the draft or 'n' to discard it.",
         AppState::QuitConfirm => "Press 'y' to confirm quit or 'n' to cancel.",
         _ => "Press 'q' or Esc to quit.",
     };
+    let instructions = app.catalog.tr(instructions);
+
+    // Fixed status messages are translated too; those with details stay as written
+    let status = if app.busy {
+        Some("Thinking…")
+    } else {
+        app.status_message.as_deref()
+    }
+    .map(|status| app.catalog.tr(status));
+    let mut text = match (status, accessible(app) || monochrome(app)) {
+        (Some(status), true) => format!("{} {}  |  {}", status_prefix(app), status, instructions),
+        (Some(status), false) => format!("{}  |  {}", status, instructions),
+        (None, _) => instructions.to_string(),
+    };
+    // The active task profile, shown wherever questions can be asked
+    if app.state == AppState::Chat {
+        text = format!("[mode: {}]  {}", app.chatbot.profile.name, text);
+    }
 
-    let footer = Paragraph::new(instructions)
+    let footer = Paragraph::new(text)
         .style(Style::default().fg(Color::LightCyan))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
@@ -0,0 +1,138 @@
+use ratatui::layout::Rect;
+use unicode_width::UnicodeWidthStr;
+
+// Saturating layout arithmetic. Terminals can be a single cell (or zero) wide, so every
+// computation on u16 sizes goes through here instead of plain `+`/`-`.
+
+/// Rows inside a bordered block
+pub fn inner_height(area: Rect) -> usize {
+    area.height.saturating_sub(2) as usize
+}
+
+/// Columns inside a bordered block
+pub fn inner_width(area: Rect) -> usize {
+    area.width.saturating_sub(2) as usize
+}
+
+/// Adds a length to a coordinate without wrapping or truncating
+pub fn offset(base: u16, length: usize) -> u16 {
+    base.saturating_add(u16::try_from(length).unwrap_or(u16::MAX))
+}
+
+/// Cursor position after `text` typed into a bordered single-line input,
+/// clamped so it never leaves the input box
+pub fn input_cursor(area: Rect, text: &str) -> (u16, u16) {
+    let right = offset(area.x, area.width.saturating_sub(2) as usize).max(area.x);
+    let bottom = offset(area.y, area.height.saturating_sub(1) as usize);
+    let x = offset(offset(area.x, 1), text.width()).min(right);
+    let y = offset(area.y, 1).min(bottom);
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{App, AppState, ChatFocus};
+    use crate::config::Config;
+    use crate::index_progress::IndexProgress;
+    use crate::ui::indexing::draw_indexing;
+    use crate::ui::streaming::draw_streaming;
+    use proptest::prelude::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    const STATES: [AppState; 27] = [
+        AppState::MainMenu,
+        AppState::Chat,
+        AppState::BrowseIndex,
+        AppState::GitHubRecommendations,
+        AppState::Help,
+        AppState::Settings,
+        AppState::QuitConfirm,
+        AppState::SelectCodebase,
+        AppState::Bookmarks,
+        AppState::SessionSwitcher,
+        AppState::StartupError,
+        AppState::RemoteRepo,
+        AppState::Audit,
+        AppState::BranchSwitcher,
+        AppState::Usage,
+        AppState::RestoreDraft,
+        AppState::RefactorPlan,
+        AppState::Heatmap,
+        AppState::LockConflict,
+        AppState::Truncation,
+        AppState::Sweep,
+        AppState::Dialog,
+        AppState::ClipboardRing,
+        AppState::WhatsNew,
+        AppState::Projects,
+        AppState::Todos,
+        AppState::Quit,
+    ];
+
+    fn test_app(data_dir: &std::path::Path) -> App {
+        App::new(Config {
+            data_dir: data_dir.display().to_string(),
+            ..Config::default()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn input_cursor_stays_in_bounds(
+            x in 0u16..=u16::MAX,
+            y in 0u16..=u16::MAX,
+            width in 0u16..=u16::MAX,
+            height in 0u16..=u16::MAX,
+            text in ".{0,300}",
+        ) {
+            let area = Rect { x, y, width, height };
+            let (cx, cy) = input_cursor(area, &text);
+            prop_assert!(cx >= area.x && cy >= area.y);
+            prop_assert!(cx <= offset(area.x, area.width as usize));
+            prop_assert!(cy <= offset(area.y, area.height as usize));
+        }
+
+        #[test]
+        fn every_view_renders_at_pathological_sizes(
+            width in 0u16..48,
+            height in 0u16..32,
+            state in 0usize..STATES.len(),
+            accessible in any::<bool>(),
+            split_view in any::<bool>(),
+            focus_mode in any::<bool>(),
+            input in ".{0,80}",
+        ) {
+            let data_dir = tempfile::tempdir().unwrap();
+            let mut app = test_app(data_dir.path());
+            app.config.accessibility_mode = accessible;
+            app.split_view = split_view;
+            app.focus_mode = focus_mode;
+            app.input = input.clone();
+            app.url_input = input.clone();
+            app.messages.push(crate::ui::chat::Message::user(input.clone()));
+            app.messages.push(crate::ui::chat::Message::ai("```rust\nfn main() {}\n```"));
+            app.state = STATES[state];
+            app.chat_focus = ChatFocus::Transcript;
+            app.on_resize(width, height);
+
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal.draw(|f| crate::ui(f, &app)).unwrap();
+        }
+
+        #[test]
+        fn progress_views_render_at_pathological_sizes(
+            width in 0u16..48,
+            height in 0u16..32,
+            partial in "(.{0,20}\n){0,10}",
+            tick in 0usize..100,
+        ) {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            let progress = IndexProgress::new(".", 3);
+            terminal.draw(|f| draw_indexing(f, &progress, false)).unwrap();
+            terminal
+                .draw(|f| draw_streaming(f, "question", &format!("```rust\n{}", partial), false, tick, None))
+                .unwrap();
+        }
+    }
+}
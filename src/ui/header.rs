@@ -5,7 +5,15 @@ use ratatui::{
     Frame,
 };
 
-pub fn draw_header(f: &mut Frame<'_>, area: Rect) {
+pub fn draw_header(f: &mut Frame<'_>, area: Rect, accessible: bool) {
+    // Screen readers get a single plain title line instead of ASCII art
+    if accessible {
+        let title = Paragraph::new("Sagacity - Elite Terminal Assistant")
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        f.render_widget(title, area);
+        return;
+    }
+
     // ASCII Art Logo
     let logo = r#"
      _____                 _
@@ -0,0 +1,119 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::heatmap::treemap;
+use crate::ui::theme::{accessible, bordered};
+use crate::App;
+
+// Retrieval frequency buckets, from never retrieved to the most retrieved file
+const COLORS: [Color; 4] = [Color::DarkGray, Color::Blue, Color::Yellow, Color::Red];
+const SHADES: [&str; 4] = ["░", "▒", "▓", "█"];
+const ASCII_SHADES: [&str; 4] = [".", ":", "#", "@"];
+
+fn bucket(retrievals: usize, max_retrievals: usize) -> usize {
+    if retrievals == 0 || max_retrievals == 0 {
+        return 0;
+    }
+    1 + ((retrievals * 3 - 1) / max_retrievals).min(2)
+}
+
+/// Draws the context size heatmap: area is token count, color and shade are how often
+/// the file was retrieved into context
+pub fn draw_heatmap(f: &mut Frame<'_>, area: Rect, app: &App) {
+    if app.heatmap.is_empty() {
+        let paragraph =
+            Paragraph::new("Nothing indexed yet.\n\nIndex a codebase, then type /heatmap in chat.")
+                .block(bordered(app).title("Context Heatmap"))
+                .style(Style::default().fg(Color::White))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(5)].as_ref())
+        .split(area);
+
+    let total_tokens: usize = app.heatmap.iter().map(|cell| cell.tokens).sum();
+    let block = bordered(app).title(format!(
+        "Context Heatmap — {} files, ~{} tokens",
+        app.heatmap.len(),
+        total_tokens
+    ));
+    let inner = block.inner(chunks[0]);
+    f.render_widget(block, chunks[0]);
+
+    let shades = if accessible(app) {
+        ASCII_SHADES
+    } else {
+        SHADES
+    };
+    let max_retrievals = app.heatmap.iter().map(|c| c.retrievals).max().unwrap_or(0);
+    let weights: Vec<usize> = app.heatmap.iter().map(|cell| cell.tokens).collect();
+    let rects = treemap(&weights, inner);
+    let buffer = f.buffer_mut();
+    for (i, (cell, rect)) in app.heatmap.iter().zip(rects).enumerate() {
+        let rect = rect.intersection(buffer.area);
+        if rect.is_empty() {
+            continue;
+        }
+        let level = bucket(cell.retrievals, max_retrievals);
+        let mut style = Style::default().fg(COLORS[level]);
+        if i == app.selected_heatmap_cell {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        for y in rect.top()..rect.bottom() {
+            for x in rect.left()..rect.right() {
+                buffer[(x, y)].set_symbol(shades[level]).set_style(style);
+            }
+        }
+        // File name in the top-left corner of cells wide enough to read it
+        let name = cell.path.rsplit('/').next().unwrap_or(&cell.path);
+        if rect.width as usize > name.len() {
+            buffer.set_stringn(
+                rect.x,
+                rect.y,
+                name,
+                rect.width as usize,
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+    }
+
+    let selected = &app.heatmap[app.selected_heatmap_cell.min(app.heatmap.len() - 1)];
+    let share = selected.tokens as f64 * 100.0 / total_tokens.max(1) as f64;
+    let mut legend = vec![Span::raw("Retrievals: ")];
+    for (label, level) in [("never ", 0), ("few ", 1), ("some ", 2), ("most", 3)] {
+        legend.push(Span::styled(
+            format!("{}{} ", shades[level], shades[level]),
+            Style::default().fg(COLORS[level]),
+        ));
+        legend.push(Span::raw(label));
+    }
+    let details = Paragraph::new(vec![
+        Line::from(Span::styled(
+            selected.path.clone(),
+            Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "~{} tokens ({:.1}% of indexed), retrieved into context {} times",
+            selected.tokens, share, selected.retrievals
+        )),
+        Line::from(legend),
+    ])
+    .block(bordered(app).title("Selected file"))
+    .wrap(Wrap { trim: true });
+    f.render_widget(details, chunks[1]);
+}
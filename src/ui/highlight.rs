@@ -0,0 +1,336 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use unicode_width::UnicodeWidthStr;
+
+use crate::rust_check::{is_rust_fence, RustCheck};
+
+const THEME: &str = "base16-ocean.dark";
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+// Loading syntect's defaults is slow, so it happens once per run
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+fn fence_highlighter(language: &str) -> HighlightLines<'static> {
+    let syntax_set = syntaxes();
+    let syntax = syntax_set
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+    HighlightLines::new(syntax, &themes().themes[THEME])
+}
+
+fn highlight_code_line(highlighter: &mut HighlightLines, raw: &str) -> Line<'static> {
+    let line = format!("{}\n", raw);
+    let spans = match highlighter.highlight_line(&line, syntaxes()) {
+        Ok(ranges) => ranges
+            .into_iter()
+            .map(|(style, piece)| {
+                Span::styled(
+                    piece.trim_end_matches('\n').to_string(),
+                    Style::default().fg(Color::Rgb(
+                        style.foreground.r,
+                        style.foreground.g,
+                        style.foreground.b,
+                    )),
+                )
+            })
+            .collect(),
+        Err(_) => vec![Span::raw(raw.to_string())],
+    };
+    Line::from(spans)
+}
+
+/// Renders markdown text with code fences highlighted line by line, so a fence that is
+/// still being streamed is highlighted up to its last complete line
+pub fn highlighted_lines(text: &str, accessible: bool, spinner_tick: usize) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut fence: Option<(String, HighlightLines)> = None;
+
+    for raw in text.lines() {
+        let trimmed = raw.trim_start();
+        if trimmed.starts_with("```") {
+            lines.push(Line::from(Span::styled(
+                raw.to_string(),
+                Style::default().fg(Color::DarkGray),
+            )));
+            fence = match fence {
+                Some(_) => None,
+                None => {
+                    let language = trimmed.trim_start_matches('`').trim().to_string();
+                    let highlighter = fence_highlighter(&language);
+                    Some((language, highlighter))
+                }
+            };
+            continue;
+        }
+
+        match fence.as_mut() {
+            Some((_, highlighter)) => lines.push(highlight_code_line(highlighter, raw)),
+            None => lines.push(Line::from(Span::styled(
+                raw.to_string(),
+                Style::default().fg(Color::LightBlue),
+            ))),
+        }
+    }
+
+    // An unterminated fence is still being written
+    if let Some((language, _)) = fence {
+        let label = if language.is_empty() {
+            "code".to_string()
+        } else {
+            language
+        };
+        let indicator = if accessible {
+            format!("[writing {}...]", label)
+        } else {
+            format!(
+                "{} writing {}…",
+                SPINNER_FRAMES[spinner_tick % SPINNER_FRAMES.len()],
+                label
+            )
+        };
+        lines.push(Line::from(Span::styled(
+            indicator,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::ITALIC),
+        )));
+    }
+    lines
+}
+
+/// A rendered message and the row of its focused code block, if any
+pub struct RenderedMarkdown {
+    pub lines: Vec<Line<'static>>,
+    pub focused_row: Option<usize>,
+}
+
+/// Renders a finished markdown message for the transcript: prose is wrapped to `width`
+/// with headings, lists, quotes, bold and inline code styled and their markers hidden;
+/// code blocks are highlighted and left unwrapped. Rust blocks are badged with their
+/// `cargo check` result, followed by the errors when `show_errors` is set. Accessible mode
+/// sticks to ASCII markers. The label of `focused_block` is highlighted and its row
+/// returned so the transcript can scroll to it.
+pub fn rendered_markdown(
+    text: &str,
+    width: usize,
+    accessible: bool,
+    rust_checks: &[RustCheck],
+    show_errors: bool,
+    focused_block: Option<usize>,
+) -> RenderedMarkdown {
+    let width = width.max(1);
+    let mut rust_blocks = 0;
+    let mut blocks = 0;
+    let mut focused_row = None;
+    let mut pending_errors: Option<&str> = None;
+    let (bullet, quote_bar, rule) = if accessible {
+        ("- ", "| ", "--")
+    } else {
+        ("• ", "│ ", "──")
+    };
+    let mut lines = Vec::new();
+    let mut fence: Option<HighlightLines> = None;
+
+    for raw in text.lines() {
+        let trimmed = raw.trim_start();
+        if trimmed.starts_with("```") {
+            fence = match fence {
+                Some(_) => {
+                    if let Some(errors) = pending_errors.take() {
+                        for error in errors.lines() {
+                            lines.push(Line::from(Span::styled(
+                                format!("  {}", error),
+                                Style::default().fg(Color::LightRed),
+                            )));
+                        }
+                    }
+                    None
+                }
+                None => {
+                    let language = trimmed.trim_start_matches('`').trim();
+                    let focused = focused_block == Some(blocks);
+                    blocks += 1;
+                    // Unlabelled blocks get a label only while focused, to show where it is
+                    if !language.is_empty() || focused {
+                        let label_style = if focused {
+                            Style::default()
+                                .fg(Color::LightYellow)
+                                .add_modifier(Modifier::REVERSED)
+                        } else {
+                            Style::default().fg(Color::DarkGray)
+                        };
+                        let name = if language.is_empty() {
+                            "code"
+                        } else {
+                            language
+                        };
+                        let mut label = vec![Span::styled(
+                            format!("{} {} {}", rule, name, rule),
+                            label_style,
+                        )];
+                        if is_rust_fence(language) {
+                            if let Some(check) = rust_checks.get(rust_blocks) {
+                                let color = match check {
+                                    RustCheck::Compiles => Color::LightGreen,
+                                    RustCheck::Errors(_) => Color::LightRed,
+                                    RustCheck::Unavailable(_) => Color::DarkGray,
+                                };
+                                label.push(Span::styled(
+                                    format!(" {}", check.badge(accessible)),
+                                    Style::default().fg(color),
+                                ));
+                                if let (RustCheck::Errors(errors), true) = (check, show_errors) {
+                                    pending_errors = Some(errors.as_str());
+                                }
+                            }
+                            rust_blocks += 1;
+                        }
+                        if focused {
+                            focused_row = Some(lines.len());
+                        }
+                        lines.push(Line::from(label));
+                    }
+                    Some(fence_highlighter(language))
+                }
+            };
+            continue;
+        }
+        if let Some(highlighter) = fence.as_mut() {
+            lines.push(highlight_code_line(highlighter, raw));
+            continue;
+        }
+
+        let indent = &raw[..raw.len() - trimmed.len()];
+        let (marker, body, base) = if trimmed.starts_with('#') {
+            let heading = trimmed.trim_start_matches('#');
+            match heading.strip_prefix(' ') {
+                Some(heading) => (
+                    String::new(),
+                    heading,
+                    Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                ),
+                None => (String::new(), trimmed, Style::default()),
+            }
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            (format!("{}{}", indent, bullet), item, Style::default())
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            (
+                quote_bar.to_string(),
+                quote.trim_start(),
+                Style::default().fg(Color::DarkGray),
+            )
+        } else {
+            (indent.to_string(), trimmed, Style::default())
+        };
+
+        let segments = inline_segments(body, base);
+        let hanging = if marker == quote_bar {
+            marker.clone()
+        } else {
+            " ".repeat(marker.width())
+        };
+        lines.extend(wrap_segments(&marker, &hanging, &segments, width));
+    }
+    RenderedMarkdown { lines, focused_row }
+}
+
+/// Number of fenced code blocks in a message, counted the way they are rendered
+pub fn code_block_count(text: &str) -> usize {
+    let fences = text
+        .lines()
+        .filter(|line| line.trim_start().starts_with("```"))
+        .count();
+    fences.div_ceil(2)
+}
+
+// Splits a line on **bold** and `code` markers, dropping the markers
+fn inline_segments(text: &str, base: Style) -> Vec<(String, Style)> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let next = [("**", Modifier::BOLD), ("`", Modifier::empty())]
+            .into_iter()
+            .filter_map(|(marker, modifier)| {
+                let start = rest.find(marker)?;
+                let end = rest[start + marker.len()..].find(marker)?;
+                Some((start, end, marker, modifier))
+            })
+            .min_by_key(|(start, ..)| *start);
+        let Some((start, end, marker, modifier)) = next else {
+            segments.push((rest.to_string(), base));
+            break;
+        };
+        if start > 0 {
+            segments.push((rest[..start].to_string(), base));
+        }
+        let inner = &rest[start + marker.len()..start + marker.len() + end];
+        let style = if marker == "`" {
+            base.fg(Color::Yellow)
+        } else {
+            base.add_modifier(modifier)
+        };
+        segments.push((inner.to_string(), style));
+        rest = &rest[start + 2 * marker.len() + end..];
+    }
+    segments
+}
+
+// Greedy word wrap that keeps each word's style; continuation rows start with `indent`
+fn wrap_segments(
+    marker: &str,
+    indent: &str,
+    segments: &[(String, Style)],
+    width: usize,
+) -> Vec<Line<'static>> {
+    let mut rows: Vec<Line<'static>> = Vec::new();
+    let mut row: Vec<Span<'static>> = vec![Span::raw(marker.to_string())];
+    let mut row_width = marker.width();
+    let mut row_has_words = false;
+    // Segments can meet inside a word, so spaces are carried across them
+    let mut pending_space = false;
+
+    for (text, style) in segments {
+        for (i, word) in text.split(' ').enumerate() {
+            pending_space |= i > 0;
+            if word.is_empty() {
+                continue;
+            }
+            let spaced = row_has_words && pending_space;
+            pending_space = false;
+            let needed = word.width() + usize::from(spaced);
+            if row_has_words && row_width + needed > width {
+                rows.push(Line::from(std::mem::take(&mut row)));
+                row.push(Span::raw(indent.to_string()));
+                row_width = indent.width();
+                row_has_words = false;
+            }
+            let piece = if spaced && row_has_words {
+                format!(" {}", word)
+            } else {
+                word.to_string()
+            };
+            row_width += piece.width();
+            row.push(Span::styled(piece, *style));
+            row_has_words = true;
+        }
+    }
+    rows.push(Line::from(row));
+    rows
+}
@@ -0,0 +1,56 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Gauge, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::index_progress::IndexProgress;
+use crate::ui::header::draw_header;
+
+/// Draws a full-screen indexing progress view with a moving-average ETA
+pub fn draw_indexing(f: &mut Frame<'_>, progress: &IndexProgress, accessible: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(7), // Header
+                Constraint::Length(3), // Gauge
+                Constraint::Min(1),    // Current file
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+
+    draw_header(f, chunks[0], accessible);
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Indexing {}", progress.root)),
+        )
+        .gauge_style(
+            Style::default()
+                .fg(Color::LightMagenta)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .ratio(progress.ratio())
+        .label(progress.summary());
+
+    f.render_widget(gauge, chunks[1]);
+
+    let mut text = format!("Processing: {}", progress.current_file);
+    // Alternates brightness with each pulse so a slow file visibly isn't stuck
+    let mut style = Style::default().fg(Color::White);
+    if let Some(heartbeat) = &progress.heartbeat {
+        text.push_str(&format!("\n{}", heartbeat.status()));
+        if heartbeat.pulses % 2 == 1 {
+            style = style.add_modifier(Modifier::DIM);
+        }
+    }
+    let current = Paragraph::new(text).style(style).wrap(Wrap { trim: true });
+
+    f.render_widget(current, chunks[2]);
+}
@@ -0,0 +1,46 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::theme::bordered;
+use crate::ui::time::relative_time;
+use crate::App;
+
+/// Draws the choice offered when another live instance holds the project lock
+pub fn draw_lock_conflict(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let Some(holder) = &app.lock_holder else {
+        return;
+    };
+    let text = vec![
+        Line::from(Span::styled(
+            "Another sagacity instance is running on this project.",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "pid {}, started {}",
+                holder.pid,
+                relative_time(holder.started_at)
+            ),
+            Style::default().fg(Color::LightGreen),
+        )),
+        Line::from(""),
+        Line::from("Both writing the index would corrupt it."),
+        Line::from("(r) attach read-only  (t) take over the lock  (q) quit"),
+    ];
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Color::White))
+        .block(
+            bordered(app)
+                .title("Project In Use")
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
@@ -1,27 +1,52 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem},
+    text::Line,
+    widgets::{Block, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
+use crate::project_status::ProjectStatus;
+use crate::ui::theme::{accessible, bordered, highlight_symbol};
+use crate::ui::time::relative_time;
 use crate::App;
 
+// Failed files named on the menu; the rest are counted
+const MAX_FAILED_LISTED: usize = 3;
+
 pub fn draw_main_menu(f: &mut Frame<'_>, area: Rect, app: &App) {
     // Create a block for the menu background
-    let block = Block::default()
-        .borders(Borders::ALL)
+    let block = bordered(app)
         .title("Main Menu")
         .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
 
     f.render_widget(block, area);
 
+    // Status widgets sit to the right of the menu, or below it in accessible mode
+    let (menu_area, status_area) = match &app.project_status {
+        Some(_) => {
+            let direction = if accessible(app) {
+                Direction::Vertical
+            } else {
+                Direction::Horizontal
+            };
+            let split = Layout::default()
+                .direction(direction)
+                .margin(1)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .split(area);
+            (split[0], Some(split[1]))
+        }
+        None => (area, None),
+    };
+
     // Define menu items with icons
     let items: Vec<ListItem> = app
         .menu_items
         .iter()
         .enumerate()
         .map(|(i, &item)| {
+            let item = app.catalog.tr(item);
             if i == app.selected_menu_item {
                 ListItem::new(item).style(
                     Style::default()
@@ -43,14 +68,111 @@ pub fn draw_main_menu(f: &mut Frame<'_>, area: Rect, app: &App) {
                 .fg(Color::Black)
                 .add_modifier(Modifier::BOLD),
         )
-        .highlight_symbol("➤ ");
+        .highlight_symbol(highlight_symbol(app));
 
     // Calculate the layout for the list
     let list_area = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
         .constraints([Constraint::Min(1)].as_ref())
-        .split(area)[0];
+        .split(menu_area)[0];
 
     f.render_widget(list, list_area);
+
+    if let (Some(status), Some(status_area)) = (&app.project_status, status_area) {
+        draw_project_status(f, status_area, app, status);
+    }
+}
+
+fn draw_project_status(f: &mut Frame<'_>, area: Rect, app: &App, status: &ProjectStatus) {
+    let sessions: Vec<Line> = if status.recent_sessions.is_empty() {
+        vec![Line::from("No sessions yet")]
+    } else {
+        status
+            .recent_sessions
+            .iter()
+            .map(|session| {
+                Line::from(format!(
+                    "{} · {} · ${:.4}",
+                    session.title,
+                    relative_time(session.last_activity),
+                    session.cost
+                ))
+            })
+            .collect()
+    };
+    let mut index = vec![
+        Line::from(format!(
+            "{} files indexed {}{}",
+            status.indexed_files,
+            status
+                .indexed_at
+                .map_or("(no cache)".to_string(), relative_time),
+            match status.duplicate_files {
+                0 => String::new(),
+                count => format!(" · {} duplicates aliased", count),
+            }
+        )),
+        if status.stale_files == 0 {
+            Line::from("Up to date").style(Style::default().fg(Color::Green))
+        } else {
+            Line::from(format!(
+                "{} changed since indexing; reindex to refresh",
+                status.stale_files
+            ))
+            .style(Style::default().fg(Color::Yellow))
+        },
+    ];
+    if !status.failed_summaries.is_empty() {
+        index.push(
+            Line::from(format!(
+                "{} summaries failed and are left out of retrieval; 'r' to retry",
+                status.failed_summaries.len()
+            ))
+            .style(Style::default().fg(Color::LightRed)),
+        );
+        for (file, error) in status.failed_summaries.iter().take(MAX_FAILED_LISTED) {
+            index.push(Line::from(format!("  {}: {}", file, error)));
+        }
+        if status.failed_summaries.len() > MAX_FAILED_LISTED {
+            index.push(Line::from(format!(
+                "  …and {} more",
+                status.failed_summaries.len() - MAX_FAILED_LISTED
+            )));
+        }
+    }
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(4),
+            Constraint::Length(index.len() as u16 + 2),
+            Constraint::Length(4),
+        ])
+        .split(area);
+
+    let cost = vec![
+        Line::from(match status.last_session_cost {
+            Some(cost) => format!("Last session: ${:.4}", cost),
+            None => "Last session: -".to_string(),
+        }),
+        Line::from(format!(
+            "Today: ${:.4} over {} questions",
+            status.today_cost, status.today_questions
+        )),
+    ];
+
+    for ((title, lines), section) in [
+        ("Recent Sessions", sessions),
+        ("Index", index),
+        ("Cost", cost),
+    ]
+    .into_iter()
+    .zip(sections.iter())
+    {
+        let widget = Paragraph::new(lines)
+            .block(bordered(app).title(title))
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: true });
+        f.render_widget(widget, *section);
+    }
 }
@@ -1,7 +1,36 @@
+pub mod audit;
+pub mod bookmarks;
+pub mod branch_switcher;
 pub mod chat;
+pub mod clipboard_ring;
+pub mod dialog;
 pub mod directory_tree;
+pub mod file_viewer;
 pub mod footer;
+pub mod geometry;
 pub mod header;
+pub mod heatmap;
+pub mod highlight;
+pub mod indexing;
+pub mod lock_conflict;
 pub mod main_menu;
+pub mod outline;
 pub mod placeholder;
+pub mod profile;
+pub mod projects;
 pub mod quit_confirm;
+pub mod refactor_plan;
+pub mod remote_repo;
+pub mod restore_draft;
+pub mod ribbon;
+pub mod session_switcher;
+pub mod startup_error;
+pub mod streaming;
+pub mod sweep;
+pub mod theme;
+pub mod time;
+pub mod todos;
+pub mod truncation;
+pub mod usage;
+pub mod viewer;
+pub mod whats_new;
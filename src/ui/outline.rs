@@ -0,0 +1,68 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::outline::outline;
+use crate::ui::theme::{bordered, focus_title, highlight_symbol};
+use crate::{App, ChatFocus};
+
+/// Draws the outline sidebar: one line per turn, the question's gist and the answer's
+/// first sentence, with the turn being read highlighted
+pub fn draw_outline(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let focused = app.chat_focus == ChatFocus::Outline;
+    let entries = outline(&app.messages);
+    let block = bordered(app)
+        .title(focus_title(app, "Outline".to_string(), focused))
+        .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
+
+    if entries.is_empty() {
+        let paragraph = Paragraph::new("No turns yet.")
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = entries
+        .iter()
+        .enumerate()
+        .map(|(turn, entry)| {
+            let mut spans = vec![
+                Span::styled(
+                    format!("{:>3} ", turn + 1),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    entry.question.clone(),
+                    Style::default().fg(Color::LightGreen),
+                ),
+            ];
+            if !entry.answer.is_empty() {
+                spans.push(Span::styled(
+                    format!(" — {}", entry.answer),
+                    Style::default().fg(Color::White),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_outline.min(entries.len() - 1)));
+    f.render_stateful_widget(list, area, &mut state);
+}
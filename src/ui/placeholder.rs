@@ -1,14 +1,16 @@
 use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Paragraph, Wrap},
     Frame,
 };
 
-pub fn draw_placeholder(f: &mut Frame<'_>, area: Rect, title: &str) {
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title(title)
+use crate::ui::theme::bordered;
+use crate::App;
+
+pub fn draw_placeholder(f: &mut Frame<'_>, area: Rect, app: &App, title: &str) {
+    let block = bordered(app)
+        .title(app.catalog.tr(title))
         .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
 
     f.render_widget(block, area);
@@ -22,7 +24,7 @@ pub fn draw_placeholder(f: &mut Frame<'_>, area: Rect, title: &str) {
         _ => "Under construction.\n\nPress 'q' or Esc to return to the main menu.",
     };
 
-    let paragraph = Paragraph::new(placeholder_text)
+    let paragraph = Paragraph::new(app.catalog.tr(placeholder_text))
         .style(Style::default().fg(Color::White))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
@@ -0,0 +1,60 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+use crate::profiler;
+use crate::ui::theme::bordered;
+use crate::App;
+
+// Sections listed, slowest in total first
+const PROFILE_ROWS: usize = 12;
+const PROFILE_WIDTH: u16 = 72;
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1000.0)
+}
+
+/// Draws the instrumented sections that took the most time over the top right of the body
+pub fn draw_profile(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:<20} {:>6} {:>9} {:>9} {:>9} {:>10}",
+            "section", "calls", "last ms", "avg ms", "max ms", "total ms"
+        ),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    let timings = profiler::top(PROFILE_ROWS);
+    if timings.is_empty() {
+        lines.push(Line::from("Nothing measured yet"));
+    }
+    for (label, timing) in timings {
+        lines.push(Line::from(format!(
+            "{:<20} {:>6} {:>9} {:>9} {:>9} {:>10}",
+            label,
+            timing.calls,
+            millis(timing.last),
+            millis(timing.average()),
+            millis(timing.max),
+            millis(timing.total)
+        )));
+    }
+
+    let width = PROFILE_WIDTH.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width - width,
+        y: area.y,
+        width,
+        height,
+    };
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .block(bordered(app).title("Profile - /profile to close, /profile reset to clear"));
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
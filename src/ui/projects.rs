@@ -0,0 +1,54 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+
+use crate::ui::theme::{accessible, bordered, highlight_symbol};
+use crate::App;
+
+/// Draws the registered projects, ticking those questions span
+pub fn draw_projects(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let (chosen_mark, skipped_mark) = if accessible(app) {
+        ("[x]", "[ ]")
+    } else {
+        ("☑", "☐")
+    };
+    let items: Vec<ListItem> = app
+        .project_list
+        .iter()
+        .map(|project| {
+            let mark = if app.chosen_projects.contains(&project.name) {
+                chosen_mark
+            } else {
+                skipped_mark
+            };
+            ListItem::new(format!(
+                "{} {:<24} {:>6} files  {}",
+                mark, project.name, project.files, project.root
+            ))
+            .style(Style::default().fg(Color::White))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            bordered(app)
+                .title("Projects")
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    if !app.project_list.is_empty() {
+        state.select(Some(app.selected_project));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
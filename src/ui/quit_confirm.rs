@@ -1,12 +1,15 @@
 use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Paragraph, Wrap},
     Frame,
 };
-pub fn draw_quit_confirm(f: &mut Frame<'_>, area: Rect) {
-    let block = Block::default()
-        .borders(Borders::ALL)
+
+use crate::ui::theme::bordered;
+use crate::App;
+
+pub fn draw_quit_confirm(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let block = bordered(app)
         .title("Confirm Quit")
         .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
 
@@ -0,0 +1,100 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::theme::{accessible, bordered, highlight_symbol};
+use crate::App;
+
+/// Draws the /plan-refactor checklist: steps on top, touched files and risks below
+pub fn draw_refactor_plan(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let Some(plan) = &app.refactor_plan else {
+        let paragraph =
+            Paragraph::new("No refactor plan yet.\n\nIn chat, type /plan-refactor <goal>.")
+                .block(bordered(app).title("Refactor Plan"))
+                .style(Style::default().fg(Color::White))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(8)].as_ref())
+        .split(area);
+
+    let (done_mark, todo_mark) = if accessible(app) {
+        ("[x]", "[ ]")
+    } else {
+        ("☑", "☐")
+    };
+    let items: Vec<ListItem> = plan
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let mark = if step.done { done_mark } else { todo_mark };
+            let pending = if app.pending_step == Some(i) {
+                " (awaiting /accept)"
+            } else {
+                ""
+            };
+            let style = if step.done {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(vec![
+                Line::from(Span::styled(
+                    format!("{} {}. {}{}", mark, i + 1, step.description, pending),
+                    style,
+                )),
+                Line::from(Span::styled(
+                    format!("     {}", step.files.join(", ")),
+                    Style::default().fg(Color::Cyan),
+                )),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        "Refactor Plan: {} ({}/{} done)",
+        plan.goal,
+        plan.completed(),
+        plan.steps.len()
+    );
+    let list = List::new(items)
+        .block(
+            bordered(app)
+                .title(title)
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_plan_step));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let mut details = vec![Line::from(format!("Files: {}", plan.files.join(", ")))];
+    details.extend(plan.risks.iter().map(|risk| {
+        Line::from(Span::styled(
+            format!("Risk: {}", risk),
+            Style::default().fg(Color::LightRed),
+        ))
+    }));
+    let paragraph = Paragraph::new(details)
+        .block(bordered(app).title("Files & Risks"))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, chunks[1]);
+}
@@ -0,0 +1,41 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::geometry::input_cursor;
+use crate::ui::theme::bordered;
+use crate::App;
+
+/// Draws the git URL prompt for indexing a remote repository
+pub fn draw_remote_repo(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let block = bordered(app)
+        .title("Chat with GitHub Repo")
+        .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
+
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([Constraint::Length(3), Constraint::Length(3)].as_ref())
+        .split(area);
+
+    let explanation = Paragraph::new(
+        "🌐 The repository is shallow-cloned into the cache and indexed read-only. Use /refresh in chat to fetch and reindex.",
+    )
+    .style(Style::default().fg(Color::White))
+    .wrap(Wrap { trim: true });
+
+    f.render_widget(explanation, chunks[0]);
+
+    let input = Paragraph::new(app.url_input.as_str())
+        .style(Style::default().fg(Color::LightYellow))
+        .block(bordered(app).title("Git URL"));
+
+    f.render_widget(input, chunks[1]);
+    let (x, y) = input_cursor(chunks[1], &app.url_input);
+    f.set_cursor_position((x, y));
+}
@@ -0,0 +1,40 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::theme::bordered;
+use crate::App;
+
+/// Draws the prompt offering to restore an unsent draft after a crash
+pub fn draw_restore_draft(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let Some(draft) = &app.recovered_draft else {
+        return;
+    };
+    let text = vec![
+        Line::from(Span::styled(
+            "An unsent draft was left behind last time.",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            draft.input.clone(),
+            Style::default().fg(Color::LightGreen),
+        )),
+        Line::from(""),
+        Line::from("Restore it and reopen its session? (y/n)"),
+    ];
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Color::White))
+        .block(
+            bordered(app)
+                .title("Restore Draft")
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
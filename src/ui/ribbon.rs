@@ -0,0 +1,153 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::ui::theme::accessible;
+use crate::{App, AppState, ChatFocus};
+
+/// The few keys that matter most right now, as (key, action) pairs
+pub fn ribbon_hints(app: &App) -> Vec<(&'static str, &'static str)> {
+    match app.state {
+        AppState::MainMenu => {
+            let mut hints = vec![("Up/Down", "move"), ("Enter", "open")];
+            if app.briefing.is_some() {
+                hints.push(("b", "briefing"));
+            }
+            if !app.chatbot.failed_summaries.files.is_empty() {
+                hints.push(("r", "retry failed summaries"));
+            }
+            hints.push(("q", "quit"));
+            hints
+        }
+        AppState::Chat => match app.chat_focus {
+            ChatFocus::Input if app.input.starts_with('/') => vec![
+                ("Enter", "run command"),
+                ("/help", "all commands"),
+                ("Ctrl+Up", "earlier input"),
+                ("Esc", "menu"),
+            ],
+            ChatFocus::Input if app.input.is_empty() && !app.query_suggestions.is_empty() => vec![
+                ("Left/Right", "pick a suggestion"),
+                ("Enter", "resend with it"),
+                ("Tab", "transcript"),
+                ("Esc", "menu"),
+            ],
+            ChatFocus::Input => vec![
+                ("Enter", "send"),
+                ("Tab", "transcript"),
+                ("Ctrl+Up", "earlier input"),
+                ("Ctrl+S", "scratchpad"),
+                ("Ctrl+T", "split view"),
+                ("Ctrl+L", "outline"),
+                ("Ctrl+F", "focus mode"),
+                ("Ctrl+O", "sessions"),
+            ],
+            ChatFocus::Transcript if app.selected_message.is_some() => vec![
+                ("y", "copy"),
+                ("g", "gist"),
+                (
+                    "r",
+                    if app
+                        .selected_message
+                        .and_then(|i| app.messages.get(i))
+                        .is_some_and(|message| message.error.is_some())
+                    {
+                        "retry"
+                    } else {
+                        "quote"
+                    },
+                ),
+                ("/regen", "regenerate"),
+                ("m", "bookmark"),
+                ("w", "write files"),
+                ("Tab", "type"),
+            ],
+            ChatFocus::Transcript => vec![
+                ("Up/Down", "select"),
+                ("[ ]", "code blocks"),
+                ("Ctrl+B", "bookmarks"),
+                ("Tab", "type"),
+            ],
+            ChatFocus::Annotation => vec![("Enter", "save note"), ("Esc", "cancel")],
+            ChatFocus::Scratchpad => {
+                vec![("Enter", "new line"), ("Ctrl+P", "pin"), ("Esc", "back")]
+            }
+            ChatFocus::Viewer => vec![
+                ("v", "select lines"),
+                ("e", "explain"),
+                ("i", "changes"),
+                ("Tab", "type"),
+            ],
+            ChatFocus::Outline => vec![
+                ("j/k", "move"),
+                ("Enter", "jump to"),
+                ("Ctrl+L", "close"),
+                ("Esc", "back"),
+            ],
+        },
+        AppState::SessionSwitcher => vec![
+            ("Enter", "switch"),
+            ("n", "new"),
+            ("m", "merge"),
+            ("x", "mark"),
+            ("e", "export"),
+            ("Esc", "back"),
+        ],
+        AppState::Bookmarks | AppState::BranchSwitcher => {
+            vec![("Up/Down", "move"), ("Enter", "open"), ("Esc", "back")]
+        }
+        AppState::ClipboardRing => vec![("Enter", "copy"), ("i", "insert"), ("Esc", "back")],
+        AppState::Projects => vec![("Space", "select"), ("Enter", "apply"), ("Esc", "back")],
+        AppState::Todos => vec![("Up/Down", "move"), ("Enter", "jump to"), ("Esc", "back")],
+        AppState::RefactorPlan => vec![
+            ("Space", "tick off"),
+            ("Enter", "generate edits"),
+            ("Esc", "chat"),
+        ],
+        AppState::Sweep => vec![
+            ("Space", "include"),
+            ("a", "all"),
+            ("Enter", "apply"),
+            ("Esc", "chat"),
+        ],
+        AppState::Audit => vec![
+            ("/", "filter"),
+            ("?", "ask about"),
+            ("PgUp/PgDn", "scroll"),
+            ("Esc", "back"),
+        ],
+        AppState::Usage => vec![("e", "export CSV"), ("Esc", "back")],
+        AppState::WhatsNew => vec![("Up/Down", "scroll"), ("Enter", "dismiss")],
+        AppState::Truncation => vec![("Enter", "send"), ("Esc", "keep editing")],
+        AppState::Dialog => vec![("Tab", "choose"), ("Enter", "confirm"), ("Esc", "cancel")],
+        AppState::QuitConfirm | AppState::RestoreDraft => vec![("y", "yes"), ("n", "no")],
+        _ => vec![("Esc", "back")],
+    }
+}
+
+/// Draws the contextual key hints in a single row above the footer
+pub fn draw_ribbon(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let separator = if accessible(app) { " | " } else { "  " };
+    let mut spans = Vec::new();
+    for (i, (key, action)) in ribbon_hints(app).into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(separator));
+        }
+        spans.push(Span::styled(
+            format!(" {} ", key),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(
+            format!(" {}", app.catalog.tr(action)),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
@@ -0,0 +1,64 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+
+use crate::ui::theme::{bordered, highlight_symbol};
+use crate::ui::time::relative_time;
+use crate::App;
+
+/// Draws the most-recently-used session switcher
+pub fn draw_session_switcher(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let items: Vec<ListItem> = app
+        .session_list
+        .iter()
+        .map(|session| {
+            let marker = if session.id == app.session_id {
+                "●"
+            } else {
+                " "
+            };
+            let export = if app.export_selection.contains(&session.id) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            let title = if session.title.is_empty() {
+                "Untitled session"
+            } else {
+                session.title.as_str()
+            };
+            ListItem::new(format!(
+                "{} {} {:<50} {:>10}  ${:.4}",
+                marker,
+                export,
+                title,
+                relative_time(session.last_activity),
+                session.cost
+            ))
+            .style(Style::default().fg(Color::White))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            bordered(app)
+                .title("Recent Sessions")
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    if !app.session_list.is_empty() {
+        state.select(Some(app.selected_session));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
@@ -0,0 +1,73 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::geometry::input_cursor;
+use crate::ui::theme::{bordered, highlight_symbol};
+use crate::App;
+
+/// Draws the startup failure explanation and the available remediation actions
+pub fn draw_startup_error(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let Some(error) = &app.startup_error else {
+        return;
+    };
+
+    let block = bordered(app)
+        .title(format!("⚠️ {}", error.title()))
+        .style(Style::default().fg(Color::LightRed).bg(Color::Black));
+
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints(
+            [
+                Constraint::Length(4), // Explanation
+                Constraint::Min(1),    // Actions
+                Constraint::Length(3), // Data directory input
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let explanation = Paragraph::new(format!(
+        "{}\n\nSagacity can fix this for you, or you can quit and fix it by hand.",
+        error.detail
+    ))
+    .style(Style::default().fg(Color::White))
+    .wrap(Wrap { trim: true });
+
+    f.render_widget(explanation, chunks[0]);
+
+    let items: Vec<ListItem> = error
+        .remediations()
+        .iter()
+        .map(|action| ListItem::new(action.label()).style(Style::default().fg(Color::White)))
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_remediation));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    if let Some(input) = &app.data_dir_input {
+        let input_box = Paragraph::new(input.as_str())
+            .style(Style::default().fg(Color::LightYellow))
+            .block(bordered(app).title("New data directory"));
+        f.render_widget(input_box, chunks[2]);
+        let (x, y) = input_cursor(chunks[2], input);
+        f.set_cursor_position((x, y));
+    }
+}
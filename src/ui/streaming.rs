@@ -0,0 +1,67 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::heartbeat::Heartbeat;
+use crate::ui::geometry::{inner_height, offset};
+use crate::ui::header::draw_header;
+use crate::ui::highlight::highlighted_lines;
+
+/// Draws a full-screen view of an answer as it streams in, with the heartbeat of a slow
+/// one in the title
+pub fn draw_streaming(
+    f: &mut Frame<'_>,
+    question: &str,
+    partial: &str,
+    accessible: bool,
+    spinner_tick: usize,
+    heartbeat: Option<&Heartbeat>,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(7), // Header
+                Constraint::Length(3), // Question
+                Constraint::Min(1),    // Answer so far
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+
+    draw_header(f, chunks[0], accessible);
+
+    let question = Paragraph::new(question.to_string())
+        .style(Style::default().fg(Color::LightGreen))
+        .block(Block::default().borders(Borders::ALL).title("You"))
+        .wrap(Wrap { trim: true });
+    f.render_widget(question, chunks[1]);
+
+    // Keep the newest lines in view while the answer grows
+    let lines = highlighted_lines(partial, accessible, spinner_tick);
+    let visible = inner_height(chunks[2]);
+    let scroll = offset(0, lines.len().saturating_sub(visible));
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title("AI (streaming)");
+    if let Some(heartbeat) = heartbeat {
+        // Alternates brightness with each pulse so a slow answer visibly isn't stuck
+        let style = if heartbeat.pulses % 2 == 1 {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::DIM)
+        } else {
+            Style::default().fg(Color::Yellow)
+        };
+        block = block.title(Line::from(Span::styled(
+            format!(" {} ", heartbeat.status()),
+            style,
+        )));
+    }
+    let answer = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    f.render_widget(answer, chunks[2]);
+}
@@ -0,0 +1,120 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::theme::{accessible, bordered, highlight_symbol};
+use crate::App;
+
+fn diff_style(line: &str) -> Style {
+    if line.starts_with("+++") || line.starts_with("---") {
+        Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD)
+    } else if line.starts_with('+') {
+        Style::default().fg(Color::LightGreen)
+    } else if line.starts_with('-') {
+        Style::default().fg(Color::LightRed)
+    } else if line.starts_with("@@") {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default().fg(Color::Gray)
+    }
+}
+
+/// Draws the /sweep review: matching files with their change counts on the left, the
+/// highlighted file's diff on the right
+pub fn draw_sweep(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let Some(sweep) = &app.sweep else {
+        let paragraph = Paragraph::new("No sweep yet.\n\nIn chat, type /sweep <change>.")
+            .block(bordered(app).title("Sweep"))
+            .style(Style::default().fg(Color::White))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+        .split(area);
+
+    let (selected_mark, skipped_mark) = if accessible(app) {
+        ("[x]", "[ ]")
+    } else {
+        ("☑", "☐")
+    };
+    let items: Vec<ListItem> = sweep
+        .patches
+        .iter()
+        .map(|patch| {
+            let lines = patch.edit.diff.lines();
+            let added = lines
+                .clone()
+                .filter(|l| l.starts_with('+') && !l.starts_with("+++"))
+                .count();
+            let removed = lines
+                .filter(|l| l.starts_with('-') && !l.starts_with("---"))
+                .count();
+            let mark = if patch.selected {
+                selected_mark
+            } else {
+                skipped_mark
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{} {} ", mark, patch.edit.path)),
+                Span::styled(
+                    format!("+{}", added),
+                    Style::default().fg(Color::LightGreen),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("-{}", removed),
+                    Style::default().fg(Color::LightRed),
+                ),
+            ]))
+            .style(Style::default().fg(Color::White))
+        })
+        .collect();
+
+    let title = format!(
+        "Sweep: {} ({}/{} selected)",
+        sweep.description,
+        sweep.selected_count(),
+        sweep.patches.len()
+    );
+    let list = List::new(items)
+        .block(
+            bordered(app)
+                .title(title)
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_sweep_patch));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let Some(patch) = sweep.patches.get(app.selected_sweep_patch) else {
+        return;
+    };
+    let diff: Vec<Line> = patch
+        .edit
+        .diff
+        .lines()
+        .skip(app.sweep_scroll)
+        .map(|line| Line::from(Span::styled(line.to_string(), diff_style(line))))
+        .collect();
+    let paragraph = Paragraph::new(diff).block(bordered(app).title(patch.edit.path.clone()));
+    f.render_widget(paragraph, chunks[1]);
+}
@@ -0,0 +1,117 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+    symbols::border,
+    widgets::{Block, Borders},
+};
+
+use crate::App;
+
+/// Plain ASCII borders for accessibility mode, instead of box-drawing characters
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+pub fn accessible(app: &App) -> bool {
+    app.config.accessibility_mode
+}
+
+/// How the finished frame is colored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    Full,
+    HighContrast,
+    // NO_COLOR: terminal default colors only
+    NoColor,
+}
+
+pub fn color_mode(app: &App) -> ColorMode {
+    if app.no_color {
+        ColorMode::NoColor
+    } else if app.config.high_contrast {
+        ColorMode::HighContrast
+    } else {
+        ColorMode::Full
+    }
+}
+
+/// Whether state has to be shown without relying on hue
+pub fn monochrome(app: &App) -> bool {
+    color_mode(app) != ColorMode::Full
+}
+
+/// Recolors a drawn frame: highlighted backgrounds (selections, focus, gauges) turn
+/// into reverse video, and every other cell into the mode's plain colors
+pub fn apply_color_mode(buffer: &mut Buffer, mode: ColorMode) {
+    let (fg, bg) = match mode {
+        ColorMode::Full => return,
+        ColorMode::HighContrast => (Color::White, Color::Black),
+        ColorMode::NoColor => (Color::Reset, Color::Reset),
+    };
+    for cell in buffer.content.iter_mut() {
+        if !matches!(cell.bg, Color::Reset | Color::Black) {
+            cell.modifier.insert(Modifier::REVERSED);
+            if mode == ColorMode::HighContrast {
+                cell.modifier.insert(Modifier::BOLD);
+            }
+        }
+        // Dim text is hard to read against the high-contrast background
+        if mode == ColorMode::HighContrast {
+            cell.modifier.remove(Modifier::DIM);
+        }
+        cell.fg = fg;
+        cell.bg = bg;
+    }
+}
+
+/// A fully bordered block that respects accessibility mode
+pub fn bordered(app: &App) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL);
+    if accessible(app) {
+        block.border_set(ASCII_BORDER)
+    } else {
+        block
+    }
+}
+
+pub fn highlight_symbol(app: &App) -> &'static str {
+    if accessible(app) {
+        "> "
+    } else {
+        "➤ "
+    }
+}
+
+/// A pane title marked when the pane has focus and color can't show it
+pub fn focus_title(app: &App, title: String, focused: bool) -> String {
+    match (focused && monochrome(app), accessible(app)) {
+        (true, true) => format!("[*] {}", title),
+        (true, false) => format!("● {}", title),
+        (false, _) => title,
+    }
+}
+
+/// Textual status prefix so state is never conveyed by color alone
+pub fn status_prefix(app: &App) -> &'static str {
+    if app.busy {
+        return "[THINKING]";
+    }
+    match &app.status_message {
+        Some(status) => {
+            let lower = status.to_lowercase();
+            if lower.contains("fail") || lower.contains("error") {
+                "[FAIL]"
+            } else {
+                "[OK]"
+            }
+        }
+        None => "",
+    }
+}
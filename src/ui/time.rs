@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+
+/// Formats how long ago something happened, e.g. "5m ago"
+pub fn relative_time(then: DateTime<Utc>) -> String {
+    let seconds = (Utc::now() - then).num_seconds().max(0);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86_399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86_400),
+    }
+}
@@ -0,0 +1,66 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+
+use crate::ui::theme::{bordered, highlight_symbol};
+use crate::App;
+
+fn kind_color(kind: &str) -> Color {
+    match kind {
+        "FIXME" => Color::LightRed,
+        "HACK" => Color::LightMagenta,
+        _ => Color::LightYellow,
+    }
+}
+
+/// Draws the /todos list: a heading per file with its comments beneath
+pub fn draw_todos(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let mut items: Vec<ListItem> = Vec::new();
+    let mut selected = None;
+    let mut current_file: Option<&str> = None;
+    for (i, (file, item)) in app.todo_list.iter().enumerate() {
+        if current_file != Some(file.as_str()) {
+            current_file = Some(file.as_str());
+            items.push(ListItem::new(Line::from(Span::styled(
+                file.clone(),
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            ))));
+        }
+        if i == app.selected_todo {
+            selected = Some(items.len());
+        }
+        items.push(ListItem::new(Line::from(vec![
+            Span::raw(format!("  {:>5}  ", item.line)),
+            Span::styled(
+                format!("{:<5}", item.kind),
+                Style::default().fg(kind_color(&item.kind)),
+            ),
+            Span::raw(format!("  {}", item.text)),
+        ])));
+    }
+
+    let list = List::new(items)
+        .block(
+            bordered(app)
+                .title(format!("TODOs ({})", app.todo_list.len()))
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .style(Style::default().fg(Color::White))
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(selected);
+    f.render_stateful_widget(list, area, &mut state);
+}
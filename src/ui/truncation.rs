@@ -0,0 +1,67 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::models::estimate_tokens;
+use crate::truncation::{input_budget, STRATEGIES};
+use crate::ui::theme::{bordered, highlight_symbol};
+use crate::App;
+
+/// Draws the truncation strategies offered for a message too long to send
+pub fn draw_truncation(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let Some(message) = &app.pending_long_input else {
+        return;
+    };
+
+    let block = bordered(app)
+        .title("Message Too Long")
+        .style(Style::default().fg(Color::LightYellow).bg(Color::Black));
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area);
+
+    let explanation = Paragraph::new(format!(
+        "This message is ~{} tokens ({} lines), but only ~{} fit beside the retrieved context and the conversation. Sent as is, the request would be rejected.",
+        estimate_tokens(message),
+        message.lines().count(),
+        input_budget(&app.chatbot).unwrap_or(0)
+    ))
+    .style(Style::default().fg(Color::White))
+    .wrap(Wrap { trim: true });
+    f.render_widget(explanation, chunks[0]);
+
+    let items: Vec<ListItem> = STRATEGIES
+        .iter()
+        .map(|strategy| {
+            ListItem::new(vec![
+                Line::from(Span::styled(
+                    strategy.label(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )),
+                Line::from(format!("  {}", strategy.description())),
+            ])
+            .style(Style::default().fg(Color::White))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(Color::LightMagenta)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(highlight_symbol(app));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_truncation));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
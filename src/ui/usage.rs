@@ -0,0 +1,157 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{List, ListItem, Sparkline},
+    Frame,
+};
+
+use crate::ui::theme::bordered;
+use crate::usage::DailyUsage;
+use crate::App;
+
+// Days of history shown on the usage screen
+const TREND_DAYS: usize = 14;
+// Files listed by the context cost they ran up
+const EXPENSIVE_FILES: usize = 5;
+// Characters of a path shown, keeping its end
+const PATH_WIDTH: usize = 48;
+
+/// Draws local usage trends: questions per day, latency, cache hit rate, cost and retrieval
+/// quality, and the files that cost the most as context
+pub fn draw_usage(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(6),
+                Constraint::Min(1),
+                Constraint::Length(EXPENSIVE_FILES as u16 + 3),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let charts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(34),
+                Constraint::Percentage(33),
+                Constraint::Percentage(33),
+            ]
+            .as_ref(),
+        )
+        .split(chunks[0]);
+    let trend = app.chatbot.usage.trend(TREND_DAYS);
+    let questions: Vec<u64> = trend.iter().map(|(_, day)| day.questions).collect();
+    let sparkline = Sparkline::default()
+        .block(bordered(app).title(format!("Questions per day (last {} days)", TREND_DAYS)))
+        .data(&questions)
+        .style(Style::default().fg(Color::LightGreen));
+    f.render_widget(sparkline, charts[0]);
+
+    // Daily rates as percentages; days without retrieval data draw as empty bars
+    let percents = |rate: fn(&DailyUsage) -> Option<f64>| -> Vec<u64> {
+        trend
+            .iter()
+            .map(|(_, day)| rate(day).map_or(0, |rate| (rate * 100.0).round() as u64))
+            .collect()
+    };
+    let precision = percents(DailyUsage::retrieval_precision);
+    let recall = percents(DailyUsage::retrieval_recall);
+    for (chart, title, data, color) in [
+        (
+            charts[1],
+            "Retrieval precision: retrieved files cited",
+            &precision,
+            Color::LightCyan,
+        ),
+        (
+            charts[2],
+            "Retrieval recall: needed files retrieved",
+            &recall,
+            Color::LightMagenta,
+        ),
+    ] {
+        let sparkline = Sparkline::default()
+            .block(bordered(app).title(title))
+            .data(data)
+            .max(100)
+            .style(Style::default().fg(color));
+        f.render_widget(sparkline, chart);
+    }
+
+    let percent = |rate: Option<f64>| match rate {
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => "-".to_string(),
+    };
+    let dollars = |cost: Option<f64>| match cost {
+        Some(cost) => format!("${:.4}", cost),
+        None => "-".to_string(),
+    };
+    let mut items = vec![ListItem::new(format!(
+        "{:<12} {:>9} {:>12} {:>10} {:>14} {:>10} {:>8}",
+        "Date", "Questions", "Avg latency", "Cache hit", "Cost/answer", "Precision", "Recall"
+    ))
+    .style(Style::default().fg(Color::LightYellow))];
+    items.extend(trend.iter().rev().map(|(date, day)| {
+        ListItem::new(format!(
+            "{:<12} {:>9} {:>10}ms {:>10} {:>14} {:>10} {:>8}",
+            date.format("%Y-%m-%d"),
+            day.questions,
+            day.average_latency_ms(),
+            percent(day.cache_hit_rate()),
+            dollars(day.cost_per_answer()),
+            percent(day.retrieval_precision()),
+            percent(day.retrieval_recall())
+        ))
+        .style(Style::default().fg(Color::White))
+    }));
+
+    let list = List::new(items).block(
+        bordered(app)
+            .title("Usage — stored locally, never sent anywhere")
+            .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+    );
+    f.render_widget(list, chunks[1]);
+
+    let mut files = vec![ListItem::new(format!(
+        "{:<width$} {:>8} {:>12} {:>10}",
+        "File",
+        "Answers",
+        "Input tokens",
+        "Cost",
+        width = PATH_WIDTH
+    ))
+    .style(Style::default().fg(Color::LightYellow))];
+    files.extend(
+        app.chatbot
+            .usage
+            .most_expensive_files(EXPENSIVE_FILES)
+            .into_iter()
+            .map(|(path, file)| {
+                let chars: Vec<char> = path.chars().collect();
+                let shown: String = if chars.len() > PATH_WIDTH {
+                    let tail: String = chars[chars.len() - (PATH_WIDTH - 1)..].iter().collect();
+                    format!("…{}", tail)
+                } else {
+                    path.clone()
+                };
+                ListItem::new(format!(
+                    "{:<width$} {:>8} {:>12} {:>10}",
+                    shown,
+                    file.answers,
+                    file.input_tokens,
+                    format!("${:.4}", file.cost),
+                    width = PATH_WIDTH
+                ))
+                .style(Style::default().fg(Color::White))
+            }),
+    );
+    let expensive = List::new(files).block(
+        bordered(app)
+            .title("Most expensive context files — consider privacy rules or index_exclude")
+            .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+    );
+    f.render_widget(expensive, chunks[2]);
+}
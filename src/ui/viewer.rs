@@ -0,0 +1,108 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::ui::chat::Sender;
+use crate::ui::geometry::{inner_height, inner_width, offset};
+use crate::ui::highlight::rendered_markdown;
+use crate::viewer::Viewer;
+
+/// Draws the current conversation of `sagacity view`, highlighting the rows that match
+/// the search and recording them for n/N
+pub fn draw_viewer(f: &mut Frame<'_>, viewer: &mut Viewer) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(f.area());
+
+    let width = inner_width(chunks[0]);
+    let query = viewer.query.to_lowercase();
+    let mut lines: Vec<Line> = Vec::new();
+    for message in &viewer.conversation().messages {
+        let (speaker, color) = match message.sender {
+            Sender::User => ("You", Color::LightGreen),
+            Sender::AI => ("Sagacity", Color::LightCyan),
+        };
+        lines.push(Line::from(Span::styled(
+            format!("── {} ", speaker),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(
+            rendered_markdown(
+                &message.content,
+                width,
+                false,
+                &message.rust_checks,
+                false,
+                None,
+            )
+            .lines,
+        );
+        if !message.context_files.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!("Context: {}", message.context_files.join(", ")),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.push(Line::from(""));
+    }
+
+    viewer.match_rows.clear();
+    if !query.is_empty() {
+        for (row, line) in lines.iter_mut().enumerate() {
+            let text: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            if text.to_lowercase().contains(&query) {
+                viewer.match_rows.push(row);
+                line.style = line.style.bg(Color::Rgb(70, 60, 0));
+            }
+        }
+    }
+    viewer.rows = lines.len();
+    viewer.page = inner_height(chunks[0]).max(1);
+    viewer.scroll = viewer.scroll.min(viewer.rows.saturating_sub(1));
+
+    let conversation = viewer.conversation();
+    let title = if viewer.conversations.len() > 1 {
+        format!(
+            " {} ({} of {}, read-only) ",
+            conversation.title,
+            viewer.current + 1,
+            viewer.conversations.len()
+        )
+    } else {
+        format!(" {} (read-only) ", conversation.title)
+    };
+    let transcript = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .scroll((offset(0, viewer.scroll), 0));
+    f.render_widget(transcript, chunks[0]);
+
+    let footer = if viewer.searching {
+        format!("/{}▏  Enter: search  Esc: cancel", viewer.query)
+    } else {
+        let mut hints = "↑/↓ PgUp/PgDn g/G: scroll  /: search".to_string();
+        if !viewer.query.is_empty() {
+            hints.push_str(&format!(
+                "  n/N: next/previous of {} matches",
+                viewer.match_rows.len()
+            ));
+        }
+        if viewer.conversations.len() > 1 {
+            hints.push_str("  [/]: conversation");
+        }
+        hints.push_str("  q: quit");
+        hints
+    };
+    f.render_widget(
+        Paragraph::new(footer).style(Style::default().fg(Color::Gray)),
+        chunks[1],
+    );
+}
@@ -0,0 +1,67 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::theme::bordered;
+use crate::App;
+
+// A changelog line with `code` spans, which name keys and config options, highlighted
+fn changelog_line(line: &str) -> Line<'static> {
+    let spans: Vec<Span<'static>> = line
+        .split('`')
+        .enumerate()
+        .filter(|(_, part)| !part.is_empty())
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                Span::styled(
+                    part.to_string(),
+                    Style::default()
+                        .fg(Color::LightCyan)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                Span::raw(part.to_string())
+            }
+        })
+        .collect();
+    Line::from(spans)
+}
+
+/// Draws the changelog sections since the last version used
+pub fn draw_whats_new(f: &mut Frame<'_>, area: Rect, app: &App) {
+    let mut text = Vec::new();
+    for release in &app.whats_new {
+        text.push(Line::from(Span::styled(
+            format!("Version {}", release.version),
+            Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for line in release.notes.lines() {
+            match line.strip_prefix("### ") {
+                Some(heading) => text.push(Line::from(Span::styled(
+                    heading.to_string(),
+                    Style::default()
+                        .fg(Color::LightYellow)
+                        .add_modifier(Modifier::BOLD),
+                ))),
+                None => text.push(changelog_line(line)),
+            }
+        }
+        text.push(Line::from(""));
+    }
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Color::White))
+        .block(
+            bordered(app)
+                .title("What's New")
+                .style(Style::default().fg(Color::LightYellow).bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((app.whats_new_scroll, 0));
+    f.render_widget(paragraph, area);
+}
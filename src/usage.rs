@@ -0,0 +1,304 @@
+// src/usage.rs
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::audit::ApiCallLog;
+
+pub const USAGE_FILE: &str = "usage_metrics.json";
+
+/// One day of usage counters
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DailyUsage {
+    pub questions: u64,
+    pub answered: u64,
+    pub total_latency_ms: u64,
+    pub cost: f64,
+    // Index cache: files reused unchanged versus files that needed a new summary
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    // Retrieval quality: files retrieval put in context and how many of them answers
+    // cited, every cited context file, and files pinned by hand that an answer went without
+    #[serde(default)]
+    pub retrieved_files: u64,
+    #[serde(default)]
+    pub retrieved_cited: u64,
+    #[serde(default)]
+    pub cited_files: u64,
+    #[serde(default)]
+    pub added_files: u64,
+}
+
+impl DailyUsage {
+    pub fn average_latency_ms(&self) -> u64 {
+        self.total_latency_ms
+            .checked_div(self.questions)
+            .unwrap_or(0)
+    }
+
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+
+    pub fn cost_per_answer(&self) -> Option<f64> {
+        (self.answered > 0).then(|| self.cost / self.answered as f64)
+    }
+
+    /// Share of retrieved files the answers cited
+    pub fn retrieval_precision(&self) -> Option<f64> {
+        (self.retrieved_files > 0)
+            .then(|| self.retrieved_cited as f64 / self.retrieved_files as f64)
+    }
+
+    /// Share of the files answers needed, cited or added by hand, that retrieval found
+    pub fn retrieval_recall(&self) -> Option<f64> {
+        let needed = self.cited_files + self.added_files;
+        (needed > 0).then(|| self.retrieved_cited as f64 / needed as f64)
+    }
+}
+
+/// Input spend attributed to one file over every answer it was context for
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FileCost {
+    pub answers: u64,
+    pub input_tokens: u64,
+    pub cost: f64,
+}
+
+/// Usage counters kept in the data directory only; nothing here is ever sent off-machine
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UsageMetrics {
+    pub days: BTreeMap<NaiveDate, DailyUsage>,
+    // Keyed by the file's full path, so projects don't mix
+    #[serde(default)]
+    pub files: BTreeMap<String, FileCost>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl UsageMetrics {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(USAGE_FILE);
+        let mut metrics: UsageMetrics = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        metrics.path = Some(path);
+        metrics
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+        }
+        Ok(())
+    }
+
+    fn today(&mut self) -> &mut DailyUsage {
+        self.days.entry(Local::now().date_naive()).or_default()
+    }
+
+    pub fn record_question(&mut self, latency_ms: u64, answered: bool, cost: f64) {
+        let day = self.today();
+        day.questions += 1;
+        day.total_latency_ms += latency_ms;
+        day.cost += cost;
+        if answered {
+            day.answered += 1;
+        }
+    }
+
+    pub fn record_retrieval(&mut self, retrieved: usize, retrieved_cited: usize, cited: usize) {
+        let day = self.today();
+        day.retrieved_files += retrieved as u64;
+        day.retrieved_cited += retrieved_cited as u64;
+        day.cited_files += cited as u64;
+    }
+
+    pub fn record_added_file(&mut self) {
+        self.today().added_files += 1;
+    }
+
+    pub fn record_index(&mut self, cache_hits: u64, cache_misses: u64) {
+        let day = self.today();
+        day.cache_hits += cache_hits;
+        day.cache_misses += cache_misses;
+    }
+
+    /// Splits an answer's input tokens and their cost across its context files, in
+    /// proportion to each file's weight (its size)
+    pub fn record_context_cost(&mut self, weights: &[(String, u64)], input_tokens: u64, cost: f64) {
+        let total: u64 = weights.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return;
+        }
+        for (file, weight) in weights {
+            let share = *weight as f64 / total as f64;
+            let entry = self.files.entry(file.clone()).or_default();
+            entry.answers += 1;
+            entry.input_tokens += (input_tokens as f64 * share).round() as u64;
+            entry.cost += cost * share;
+        }
+    }
+
+    /// Files that cost the most as context, most expensive first
+    pub fn most_expensive_files(&self, count: usize) -> Vec<(&String, &FileCost)> {
+        let mut files: Vec<(&String, &FileCost)> = self.files.iter().collect();
+        files.sort_by(|a, b| b.1.cost.total_cmp(&a.1.cost));
+        files.truncate(count);
+        files
+    }
+
+    /// The last `days` days up to today, oldest first, with empty days filled in
+    pub fn trend(&self, days: usize) -> Vec<(NaiveDate, DailyUsage)> {
+        let today = Local::now().date_naive();
+        (0..days as i64)
+            .rev()
+            .map(|offset| {
+                let date = today - Duration::days(offset);
+                (date, self.days.get(&date).cloned().unwrap_or_default())
+            })
+            .collect()
+    }
+}
+
+pub const EXPORTS_DIR: &str = "exports";
+
+// One CSV row: a model's usage on one day
+#[derive(Default)]
+struct BillingRow {
+    requests: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_write_tokens: u64,
+    cache_read_tokens: u64,
+    cost: f64,
+}
+
+/// Usage per day and model, in the categories of the Anthropic invoice, as CSV
+pub fn usage_csv(entries: &[ApiCallLog]) -> String {
+    let mut rows: BTreeMap<(NaiveDate, String), BillingRow> = BTreeMap::new();
+    for entry in entries {
+        let key = (
+            entry.timestamp.with_timezone(&Local).date_naive(),
+            entry.model.clone(),
+        );
+        let row = rows.entry(key).or_default();
+        row.requests += 1;
+        row.input_tokens += entry.input_tokens;
+        row.output_tokens += entry.output_tokens;
+        row.cache_write_tokens += entry.cache_write_tokens;
+        row.cache_read_tokens += entry.cache_read_tokens;
+        row.cost += entry.cost;
+    }
+
+    let mut csv = String::from(
+        "date,model,requests,input_tokens,output_tokens,cache_write_tokens,cache_read_tokens,cost_usd\n",
+    );
+    for ((date, model), row) in rows {
+        let _ = writeln!(
+            csv,
+            "{},\"{}\",{},{},{},{},{},{:.6}",
+            date,
+            model.replace('"', "\"\""),
+            row.requests,
+            row.input_tokens,
+            row.output_tokens,
+            row.cache_write_tokens,
+            row.cache_read_tokens,
+            row.cost
+        );
+    }
+    csv
+}
+
+/// Writes the usage CSV into the data directory and returns its path
+pub fn export_usage_csv(
+    data_dir: &Path,
+    entries: &[ApiCallLog],
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = data_dir.join(EXPORTS_DIR);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "usage-{}.csv",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, usage_csv(entries))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_input_cost_is_split_by_file_size() {
+        let mut usage = UsageMetrics::default();
+        usage.record_context_cost(
+            &[("big.rs".to_string(), 300), ("small.rs".to_string(), 100)],
+            1_000,
+            0.04,
+        );
+        usage.record_context_cost(&[("small.rs".to_string(), 10)], 500, 0.02);
+        // Nothing to split by, so nothing is attributed
+        usage.record_context_cost(&[("empty.rs".to_string(), 0)], 500, 0.02);
+
+        let big = &usage.files["big.rs"];
+        assert_eq!((big.answers, big.input_tokens), (1, 750));
+        assert!((big.cost - 0.03).abs() < 1e-9);
+        let small = &usage.files["small.rs"];
+        assert_eq!((small.answers, small.input_tokens), (2, 750));
+        assert!((small.cost - 0.03).abs() < 1e-9);
+        assert!(!usage.files.contains_key("empty.rs"));
+
+        usage.record_context_cost(&[("small.rs".to_string(), 1)], 0, 0.001);
+        let ranked: Vec<&str> = usage
+            .most_expensive_files(5)
+            .iter()
+            .map(|(file, _)| file.as_str())
+            .collect();
+        assert_eq!(ranked, vec!["small.rs", "big.rs"]);
+        assert_eq!(usage.most_expensive_files(1).len(), 1);
+    }
+
+    #[test]
+    fn precision_and_recall_count_hand_added_files_and_old_days_load() {
+        let dir = tempfile::tempdir().unwrap();
+        // A day saved before retrieval quality was tracked
+        let today = Local::now().date_naive();
+        fs::write(
+            dir.path().join(USAGE_FILE),
+            format!(
+                "{{\"days\": {{\"{}\": {{\"questions\": 1, \"answered\": 1, \
+                 \"total_latency_ms\": 900, \"cost\": 0.01, \"cache_hits\": 0, \
+                 \"cache_misses\": 0}}}}}}",
+                today
+            ),
+        )
+        .unwrap();
+        let mut usage = UsageMetrics::load(dir.path());
+        let day = &usage.days[&today];
+        assert_eq!(
+            (day.retrieval_precision(), day.retrieval_recall()),
+            (None, None)
+        );
+
+        // Four retrieved, one cited, plus one cited file retrieval missed and one added
+        usage.record_retrieval(4, 1, 2);
+        usage.record_added_file();
+        let day = &usage.days[&today];
+        assert_eq!(day.retrieval_precision(), Some(0.25));
+        assert_eq!(day.retrieval_recall(), Some(1.0 / 3.0));
+
+        let trend = usage.trend(3);
+        assert_eq!(trend.len(), 3);
+        assert_eq!(trend[2].0, today);
+        assert_eq!(trend[0].1.retrieval_precision(), None);
+        usage.save().unwrap();
+        assert_eq!(UsageMetrics::load(dir.path()).days[&today].added_files, 1);
+    }
+}
@@ -0,0 +1,280 @@
+// src/viewer.rs
+//! Read-only browsing of exported conversations: Markdown transcripts, dataset JSONL
+//! files and saved session JSON. Nothing here talks to the API, so no key is needed.
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::session::Session;
+use crate::ui::chat::Message;
+
+/// One conversation of an export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewedConversation {
+    pub title: String,
+    pub messages: Vec<Message>,
+}
+
+// Splits a transcript written by /export at its "## You" and "## Sagacity" headings
+fn parse_transcript(markdown: &str) -> ViewedConversation {
+    let mut title = String::new();
+    let mut messages: Vec<Message> = Vec::new();
+    for line in markdown.lines() {
+        match line.trim_end() {
+            "## You" => messages.push(Message::user(String::new())),
+            "## Sagacity" => messages.push(Message::ai(String::new())),
+            _ => match messages.last_mut() {
+                Some(message) => {
+                    message.content.push_str(line);
+                    message.content.push('\n');
+                }
+                None if title.is_empty() => {
+                    title = line.trim_start_matches('#').trim().to_string();
+                }
+                None => {}
+            },
+        }
+    }
+    for message in &mut messages {
+        message.content = message.content.trim().to_string();
+        message.timestamp = None;
+    }
+    ViewedConversation { title, messages }
+}
+
+fn text_of(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| block["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+// A dataset record: retrieval tool results become the context files of the next answer
+fn parse_record(record: &Value) -> ViewedConversation {
+    let mut messages = Vec::new();
+    let mut context_files: Vec<String> = Vec::new();
+    for message in record["messages"].as_array().into_iter().flatten() {
+        let blocks = message["content"].as_array();
+        let block_type = |kind: &str| {
+            blocks.is_some_and(|blocks| blocks.iter().any(|block| block["type"] == kind))
+        };
+        if block_type("tool_use") {
+            continue;
+        }
+        if block_type("tool_result") {
+            context_files = blocks
+                .into_iter()
+                .flatten()
+                .filter_map(|block| block["content"].as_str())
+                .flat_map(str::lines)
+                .map(String::from)
+                .collect();
+            continue;
+        }
+        let mut viewed = if message["role"] == "user" {
+            Message::user(text_of(&message["content"]))
+        } else {
+            let mut answer = Message::ai(text_of(&message["content"]));
+            answer.context_files = std::mem::take(&mut context_files);
+            answer
+        };
+        viewed.timestamp = None;
+        messages.push(viewed);
+    }
+    ViewedConversation {
+        title: record["title"].as_str().unwrap_or_default().to_string(),
+        messages,
+    }
+}
+
+/// The conversations in an exported file, told apart by extension: `.md` transcripts,
+/// `.jsonl` datasets (one conversation per line) and `.json` sessions
+pub fn load_export(path: &Path) -> Result<Vec<ViewedConversation>, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)?;
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let conversations = match extension.as_deref() {
+        Some("md") | Some("markdown") => vec![parse_transcript(&contents)],
+        Some("jsonl") => contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map(|record| parse_record(&record)))
+            .collect::<Result<_, _>>()?,
+        Some("json") => {
+            let session: Session = serde_json::from_str(&contents)?;
+            vec![ViewedConversation {
+                title: session.title,
+                messages: session.messages,
+            }]
+        }
+        _ => return Err("Expected a .md transcript, .jsonl dataset or .json session".into()),
+    };
+    if conversations
+        .iter()
+        .all(|conversation| conversation.messages.is_empty())
+    {
+        return Err(format!("{} has no messages to show", path.display()).into());
+    }
+    Ok(conversations)
+}
+
+/// Where the viewer is: which conversation, how far scrolled and what is searched for
+#[derive(Debug, Default)]
+pub struct Viewer {
+    pub conversations: Vec<ViewedConversation>,
+    pub current: usize,
+    pub scroll: usize,
+    // Typed after `/`; `searching` while it is being typed
+    pub query: String,
+    pub searching: bool,
+    // Filled in when drawing, since rows depend on the wrap width
+    pub rows: usize,
+    pub match_rows: Vec<usize>,
+    pub page: usize,
+}
+
+impl Viewer {
+    pub fn new(conversations: Vec<ViewedConversation>) -> Viewer {
+        Viewer {
+            conversations,
+            ..Viewer::default()
+        }
+    }
+
+    pub fn conversation(&self) -> &ViewedConversation {
+        &self.conversations[self.current]
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(self.rows.saturating_sub(1));
+    }
+
+    pub fn switch_conversation(&mut self, delta: isize) {
+        let count = self.conversations.len() as isize;
+        self.current = (self.current as isize + delta).rem_euclid(count) as usize;
+        self.scroll = 0;
+    }
+
+    /// Scrolls to the next match below the top row, or the previous one above it,
+    /// wrapping around
+    pub fn jump_to_match(&mut self, forward: bool) {
+        let next = if forward {
+            self.match_rows
+                .iter()
+                .find(|&&row| row > self.scroll)
+                .or(self.match_rows.first())
+        } else {
+            self.match_rows
+                .iter()
+                .rev()
+                .find(|&&row| row < self.scroll)
+                .or(self.match_rows.last())
+        };
+        if let Some(&row) = next {
+            self.scroll = row;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::chat::Sender;
+
+    #[test]
+    fn transcripts_and_datasets_are_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = dir.path().join("transcript.md");
+        fs::write(
+            &transcript,
+            "# Auth flow\n\n## You\n\nHow are tokens refreshed?\n\n## Sagacity\n\nIn `refresh`.\n",
+        )
+        .unwrap();
+        let conversations = load_export(&transcript).unwrap();
+        assert_eq!(conversations[0].title, "Auth flow");
+        assert_eq!(conversations[0].messages.len(), 2);
+        assert_eq!(conversations[0].messages[1].sender, Sender::AI);
+        assert_eq!(conversations[0].messages[1].content, "In `refresh`.");
+
+        let dataset = dir.path().join("dataset.jsonl");
+        fs::write(
+            &dataset,
+            r#"{"title":"Auth","messages":[{"role":"user","content":"Where?"},{"role":"assistant","content":[{"type":"tool_use","id":"t","name":"retrieve_context","input":{}}]},{"role":"user","content":[{"type":"tool_result","tool_use_id":"t","content":"src/auth.rs"}]},{"role":"assistant","content":[{"type":"text","text":"Here."}]}]}"#,
+        )
+        .unwrap();
+        let conversations = load_export(&dataset).unwrap();
+        assert_eq!(conversations[0].messages.len(), 2);
+        assert_eq!(
+            conversations[0].messages[1].context_files,
+            vec!["src/auth.rs"]
+        );
+    }
+
+    #[test]
+    fn exports_without_messages_or_of_unknown_kinds_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            path
+        };
+        let empty = write("empty.md", "# Nothing asked\n");
+        assert!(load_export(&empty)
+            .unwrap_err()
+            .to_string()
+            .ends_with("has no messages to show"));
+        assert!(load_export(&write("notes.txt", "## You\nhi\n")).is_err());
+        // A malformed line fails the whole dataset; blank lines are skipped
+        assert!(load_export(&write("bad.jsonl", "{\"messages\":[]}\nnot json\n")).is_err());
+        let dataset = write(
+            "two.jsonl",
+            "{\"messages\":[{\"role\":\"user\",\"content\":\"a\"}]}\n\n\
+             {\"messages\":[{\"role\":\"user\",\"content\":\"b\"}]}\n",
+        );
+        assert_eq!(load_export(&dataset).unwrap().len(), 2);
+        // Extensions match regardless of case
+        let upper = write("UPPER.MD", "## You\n\nhi\n");
+        assert_eq!(load_export(&upper).unwrap()[0].messages[0].content, "hi");
+    }
+
+    #[test]
+    fn scrolling_is_clamped_and_switching_and_matches_wrap() {
+        let conversation = |title: &str| ViewedConversation {
+            title: title.to_string(),
+            messages: Vec::new(),
+        };
+        let mut viewer = Viewer::new(vec![conversation("a"), conversation("b")]);
+        viewer.rows = 10;
+        viewer.scroll_by(-3);
+        assert_eq!(viewer.scroll, 0);
+        viewer.scroll_by(50);
+        assert_eq!(viewer.scroll, 9);
+
+        viewer.switch_conversation(-1);
+        assert_eq!((viewer.current, viewer.scroll), (1, 0));
+        viewer.switch_conversation(1);
+        assert_eq!(viewer.conversation().title, "a");
+
+        viewer.match_rows = vec![2, 6];
+        viewer.jump_to_match(true);
+        assert_eq!(viewer.scroll, 2);
+        viewer.jump_to_match(true);
+        assert_eq!(viewer.scroll, 6);
+        viewer.jump_to_match(true);
+        assert_eq!(viewer.scroll, 2);
+        viewer.jump_to_match(false);
+        assert_eq!(viewer.scroll, 6);
+        viewer.match_rows.clear();
+        viewer.jump_to_match(true);
+        assert_eq!(viewer.scroll, 6);
+    }
+}
@@ -0,0 +1,109 @@
+// src/whats_new.rs
+use std::fs;
+use std::path::Path;
+
+// The changelog shipped with this build
+pub const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+pub const LAST_SEEN_VERSION_FILE: &str = "last_seen_version";
+
+/// One version's section of the changelog
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub version: String,
+    pub notes: String,
+}
+
+/// The changelog's `## <version>` sections, newest first as written
+pub fn releases(changelog: &str) -> Vec<Release> {
+    let mut releases: Vec<Release> = Vec::new();
+    for line in changelog.lines() {
+        if let Some(version) = line.strip_prefix("## ") {
+            releases.push(Release {
+                version: version.trim().to_string(),
+                notes: String::new(),
+            });
+        } else if let Some(release) = releases.last_mut() {
+            release.notes.push_str(line);
+            release.notes.push('\n');
+        }
+    }
+    for release in &mut releases {
+        release.notes = release.notes.trim().to_string();
+    }
+    releases
+}
+
+/// Releases newer than the last one seen; only the newest when nothing was seen yet
+pub fn unseen_releases(changelog: &str, last_seen: Option<&str>) -> Vec<Release> {
+    let releases = releases(changelog);
+    match last_seen {
+        Some(seen) => releases
+            .into_iter()
+            .take_while(|release| release.version != seen)
+            .collect(),
+        None => releases.into_iter().take(1).collect(),
+    }
+}
+
+pub fn last_seen_version(data_dir: &Path) -> Option<String> {
+    fs::read_to_string(data_dir.join(LAST_SEEN_VERSION_FILE))
+        .ok()
+        .map(|version| version.trim().to_string())
+}
+
+/// Records that this build's what's-new screen was dismissed
+pub fn mark_seen(data_dir: &Path) -> std::io::Result<()> {
+    fs::write(
+        data_dir.join(LAST_SEEN_VERSION_FILE),
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unseen_releases_stop_at_the_last_seen_version() {
+        let changelog = "# Changelog\n\n## 0.3.0\n\n- c\n\n## 0.2.0\n\n- b\n\n## 0.1.0\n\n- a\n";
+        let versions = |releases: Vec<Release>| -> Vec<String> {
+            releases
+                .into_iter()
+                .map(|release| release.version)
+                .collect()
+        };
+        assert_eq!(
+            versions(unseen_releases(changelog, Some("0.1.0"))),
+            vec!["0.3.0", "0.2.0"]
+        );
+        assert_eq!(versions(unseen_releases(changelog, None)), vec!["0.3.0"]);
+        assert!(unseen_releases(changelog, Some("0.3.0")).is_empty());
+        assert_eq!(releases(changelog)[1].notes, "- b");
+    }
+
+    #[test]
+    fn the_changelog_opens_with_this_builds_version() {
+        // Otherwise an upgrade never shows what's new
+        assert_eq!(releases(CHANGELOG)[0].version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn subsections_stay_in_their_release_and_seen_versions_persist() {
+        let changelog = "## 0.2.0\n\n### New keys\n\n- `x`\n\n## 0.1.0\n- a\n";
+        let all = releases(changelog);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].notes, "### New keys\n\n- `x`");
+        // A version missing from the changelog shows every release rather than none
+        assert_eq!(unseen_releases(changelog, Some("0.0.9")).len(), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(last_seen_version(dir.path()), None);
+        fs::write(dir.path().join(LAST_SEEN_VERSION_FILE), "0.1.0\n").unwrap();
+        assert_eq!(last_seen_version(dir.path()).as_deref(), Some("0.1.0"));
+        mark_seen(dir.path()).unwrap();
+        assert_eq!(
+            last_seen_version(dir.path()).as_deref(),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+    }
+}
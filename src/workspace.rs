@@ -0,0 +1,191 @@
+// src/workspace.rs
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One member crate of a Cargo workspace
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceCrate {
+    pub name: String,
+    // Directory relative to the workspace root, "." for a root package
+    pub relative_dir: String,
+    dir: PathBuf,
+    // Other members this crate depends on
+    pub dependencies: Vec<String>,
+}
+
+/// The member crates of a multi-crate project, which own the files under their directories
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub members: Vec<WorkspaceCrate>,
+}
+
+// Lines of a manifest grouped under their `[section]` header
+fn sections(manifest: &str) -> Vec<(String, Vec<&str>)> {
+    let mut sections: Vec<(String, Vec<&str>)> = vec![(String::new(), Vec::new())];
+    for line in manifest.lines() {
+        let trimmed = line.split('#').next().unwrap_or("").trim();
+        if let Some(header) = trimmed.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            sections.push((
+                header.trim_matches(['[', ']']).trim().to_string(),
+                Vec::new(),
+            ));
+        } else if !trimmed.is_empty() {
+            if let Some((_, lines)) = sections.last_mut() {
+                lines.push(trimmed);
+            }
+        }
+    }
+    sections
+}
+
+fn quoted_strings(text: &str) -> Vec<String> {
+    text.split('"')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string)
+        .collect()
+}
+
+// `members = [...]` of the [workspace] section, which may span several lines
+fn workspace_members(manifest: &str) -> Option<Vec<String>> {
+    let (_, lines) = sections(manifest)
+        .into_iter()
+        .find(|(header, _)| header == "workspace")?;
+    let joined = lines.join(" ");
+    let start = joined.find("members")?;
+    let list = &joined[start..];
+    let end = list.find(']').unwrap_or(list.len());
+    Some(quoted_strings(&list[..end]))
+}
+
+fn package_name(manifest: &str) -> Option<String> {
+    let (_, lines) = sections(manifest)
+        .into_iter()
+        .find(|(header, _)| header == "package")?;
+    lines.iter().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() != "name" {
+            return None;
+        }
+        quoted_strings(value).into_iter().next()
+    })
+}
+
+// Names in every dependency table, including `[dependencies.name]` headers
+fn dependency_names(manifest: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for (header, lines) in sections(manifest) {
+        let table = header.rsplit('.').next().unwrap_or("");
+        if table.ends_with("dependencies") {
+            for line in lines {
+                let key = line.split(['=', '.']).next().unwrap_or("").trim();
+                names.push(key.trim_matches('"').to_string());
+            }
+        } else if let Some((prefix, name)) = header.rsplit_once('.') {
+            if prefix.ends_with("dependencies") {
+                names.push(name.trim_matches('"').to_string());
+            }
+        }
+    }
+    names
+}
+
+// Member paths with a trailing `/*` expanded to the crates in that directory
+fn member_dirs(root: &Path, patterns: &[String]) -> Vec<String> {
+    let mut dirs = Vec::new();
+    for pattern in patterns {
+        let Some(parent) = pattern.strip_suffix("/*") else {
+            dirs.push(pattern.trim_end_matches('/').to_string());
+            continue;
+        };
+        let Ok(entries) = fs::read_dir(root.join(parent)) else {
+            continue;
+        };
+        let mut found: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join("Cargo.toml").is_file())
+            .map(|entry| format!("{}/{}", parent, entry.file_name().to_string_lossy()))
+            .collect();
+        found.sort();
+        dirs.extend(found);
+    }
+    dirs
+}
+
+impl Workspace {
+    /// Reads the workspace manifest at `root`; None unless it lists two or more crates
+    pub fn detect(root: &Path) -> Option<Workspace> {
+        let manifest = fs::read_to_string(root.join("Cargo.toml")).ok()?;
+        let mut relative_dirs = member_dirs(root, &workspace_members(&manifest)?);
+        if package_name(&manifest).is_some() {
+            relative_dirs.insert(0, ".".to_string());
+        }
+        let mut members: Vec<(WorkspaceCrate, Vec<String>)> = relative_dirs
+            .into_iter()
+            .filter_map(|relative_dir| {
+                let dir = match relative_dir.as_str() {
+                    "." => root.to_path_buf(),
+                    relative => root.join(relative),
+                };
+                let manifest = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+                let member = WorkspaceCrate {
+                    name: package_name(&manifest)?,
+                    relative_dir,
+                    dir,
+                    dependencies: Vec::new(),
+                };
+                Some((member, dependency_names(&manifest)))
+            })
+            .collect();
+        if members.len() < 2 {
+            return None;
+        }
+        let names: Vec<String> = members.iter().map(|(m, _)| m.name.clone()).collect();
+        for (member, dependencies) in &mut members {
+            member.dependencies = names
+                .iter()
+                .filter(|name| *name != &member.name && dependencies.contains(name))
+                .cloned()
+                .collect();
+        }
+        Some(Workspace {
+            members: members.into_iter().map(|(member, _)| member).collect(),
+        })
+    }
+
+    /// The crate owning an indexed file: the member with the deepest directory holding it
+    pub fn crate_of(&self, file: &str) -> Option<&str> {
+        self.members
+            .iter()
+            .filter(|member| Path::new(file).starts_with(&member.dir))
+            .max_by_key(|member| member.dir.components().count())
+            .map(|member| member.name.as_str())
+    }
+
+    pub fn has_crate(&self, name: &str) -> bool {
+        self.members.iter().any(|member| member.name == name)
+    }
+
+    /// One line per member: its directory and the members it depends on
+    pub fn member_graph(&self) -> String {
+        self.members
+            .iter()
+            .map(|member| {
+                let mut line = format!("- {} ({})", member.name, member.relative_dir);
+                if !member.dependencies.is_empty() {
+                    line.push_str(&format!(" -> {}", member.dependencies.join(", ")));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Explains the layout to the model so same-named modules of different crates stay apart
+    pub fn system_prompt(&self) -> String {
+        format!(
+            "This project is a Cargo workspace. Each file belongs to the member crate whose directory contains it, and modules with the same name in different crates are unrelated. Members, with the members they depend on:\n{}",
+            self.member_graph()
+        )
+    }
+}